@@ -0,0 +1,82 @@
+/*
+ * C bindings for the run control and memory access of probe-rs.
+ *
+ * All functions return a probe_rs_status. If a function fails, the message of the error can be
+ * retrieved with probe_rs_last_error() on the same thread.
+ *
+ * A session must not be used by multiple threads at the same time.
+ */
+
+#ifndef PROBE_RS_H
+#define PROBE_RS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum probe_rs_status {
+    /* The function succeeded. */
+    PROBE_RS_OK = 0,
+    /* A pointer was null, or an argument was out of range. */
+    PROBE_RS_INVALID_ARGUMENT = 1,
+    /* No matching probe, chip or core was found. */
+    PROBE_RS_NOT_FOUND = 2,
+    /* The operation timed out. */
+    PROBE_RS_TIMEOUT = 3,
+    /* The operation failed, see probe_rs_last_error(). */
+    PROBE_RS_ERROR = 4,
+} probe_rs_status;
+
+/* An open debug session. */
+typedef struct ProbeRsSession probe_rs_session;
+
+/* The message of the last error of the calling thread, or NULL. Valid until the next failing call. */
+const char *probe_rs_last_error(void);
+
+/* The number of connected probes. */
+probe_rs_status probe_rs_probe_count(size_t *count_out);
+
+/*
+ * Opens a probe and attaches to the chip.
+ *
+ * probe selects the probe as "VID:PID[:SERIAL]", the first probe is used if it is NULL.
+ * chip is the name of the chip, which is detected automatically if it is NULL.
+ * The session must be closed with probe_rs_session_close().
+ */
+probe_rs_status probe_rs_attach(const char *probe, const char *chip, bool under_reset,
+                                probe_rs_session **session_out);
+
+/* Closes the session and the probe. Passing NULL does nothing. */
+void probe_rs_session_close(probe_rs_session *session);
+
+/* The number of cores of the chip. */
+probe_rs_status probe_rs_core_count(probe_rs_session *session, size_t *count_out);
+
+probe_rs_status probe_rs_read_memory(probe_rs_session *session, size_t core, uint64_t address,
+                                     uint8_t *data, size_t len);
+probe_rs_status probe_rs_write_memory(probe_rs_session *session, size_t core, uint64_t address,
+                                      const uint8_t *data, size_t len);
+
+/* The program counter is written to pc_out unless it is NULL. */
+probe_rs_status probe_rs_halt(probe_rs_session *session, size_t core, uint32_t timeout_ms,
+                              uint64_t *pc_out);
+probe_rs_status probe_rs_run(probe_rs_session *session, size_t core);
+probe_rs_status probe_rs_step(probe_rs_session *session, size_t core, uint64_t *pc_out);
+probe_rs_status probe_rs_reset_and_halt(probe_rs_session *session, size_t core,
+                                        uint32_t timeout_ms, uint64_t *pc_out);
+probe_rs_status probe_rs_core_halted(probe_rs_session *session, size_t core, bool *halted_out);
+
+probe_rs_status probe_rs_set_hw_breakpoint(probe_rs_session *session, size_t core,
+                                           uint64_t address);
+probe_rs_status probe_rs_clear_hw_breakpoint(probe_rs_session *session, size_t core,
+                                             uint64_t address);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PROBE_RS_H */
@@ -0,0 +1,508 @@
+//! C bindings for the run control and memory access of probe-rs.
+//!
+//! The functions are declared in `include/probe_rs.h`. All functions return a [`ProbeRsStatus`],
+//! and the message of the last error of the calling thread is returned by
+//! [`probe_rs_last_error`].
+//!
+//! A session is opened with [`probe_rs_attach`] and must be closed with
+//! [`probe_rs_session_close`]. A session must not be used by multiple threads at the same time.
+
+use std::{
+    cell::RefCell,
+    ffi::{c_char, CStr, CString},
+    panic::{catch_unwind, AssertUnwindSafe},
+    ptr,
+    time::Duration,
+};
+
+use probe_rs::{
+    config::TargetSelector, DebugProbeError, DebugProbeSelector, Error, Lister, MemoryInterface,
+    Permissions, ProbeCreationError, Session,
+};
+
+/// The result of a function of the C API.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProbeRsStatus {
+    /// The function succeeded.
+    Ok = 0,
+    /// A pointer was null, or an argument was out of range.
+    InvalidArgument = 1,
+    /// No matching probe, chip or core was found.
+    NotFound = 2,
+    /// The operation timed out.
+    Timeout = 3,
+    /// The operation failed, see [`probe_rs_last_error`].
+    Error = 4,
+}
+
+/// An open debug session, created by [`probe_rs_attach`].
+pub struct ProbeRsSession {
+    session: Session,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// The error of a function of the C API, which is stored as the last error of the thread.
+struct CError {
+    status: ProbeRsStatus,
+    message: String,
+}
+
+impl CError {
+    fn invalid_argument(message: impl Into<String>) -> Self {
+        Self {
+            status: ProbeRsStatus::InvalidArgument,
+            message: message.into(),
+        }
+    }
+
+    fn not_found(message: impl Into<String>) -> Self {
+        Self {
+            status: ProbeRsStatus::NotFound,
+            message: message.into(),
+        }
+    }
+}
+
+impl From<Error> for CError {
+    fn from(error: Error) -> Self {
+        let status = match error {
+            Error::Timeout => ProbeRsStatus::Timeout,
+            Error::CoreNotFound(_)
+            | Error::ChipNotFound(_)
+            | Error::Probe(DebugProbeError::ProbeCouldNotBeCreated(ProbeCreationError::NotFound)) => {
+                ProbeRsStatus::NotFound
+            }
+            _ => ProbeRsStatus::Error,
+        };
+
+        Self {
+            status,
+            message: error_chain(&error),
+        }
+    }
+}
+
+impl From<DebugProbeError> for CError {
+    fn from(error: DebugProbeError) -> Self {
+        Error::Probe(error).into()
+    }
+}
+
+/// Renders the error with all of its sources.
+fn error_chain(error: &(dyn std::error::Error + 'static)) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        message.push_str(": ");
+        message.push_str(&error.to_string());
+        source = error.source();
+    }
+    message
+}
+
+fn set_last_error(message: String) {
+    // Interior nul bytes would truncate the message, so they are dropped.
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Runs `f`, storing its error or panic as the last error of the thread.
+fn ffi_call(f: impl FnOnce() -> Result<(), CError>) -> ProbeRsStatus {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => ProbeRsStatus::Ok,
+        Ok(Err(error)) => {
+            set_last_error(error.message);
+            error.status
+        }
+        Err(_) => {
+            set_last_error("probe-rs panicked".to_string());
+            ProbeRsStatus::Error
+        }
+    }
+}
+
+/// Returns the session behind `session`, or an error if it is null.
+///
+/// # Safety
+///
+/// `session` must be null or a pointer returned by [`probe_rs_attach`] which was not closed.
+unsafe fn session_mut<'a>(session: *mut ProbeRsSession) -> Result<&'a mut Session, CError> {
+    session
+        .as_mut()
+        .map(|session| &mut session.session)
+        .ok_or_else(|| CError::invalid_argument("The session is null"))
+}
+
+/// Returns the string behind `string`, or `None` if it is null.
+///
+/// # Safety
+///
+/// `string` must be null or point to a nul terminated string.
+unsafe fn optional_str<'a>(string: *const c_char, name: &str) -> Result<Option<&'a str>, CError> {
+    if string.is_null() {
+        return Ok(None);
+    }
+
+    CStr::from_ptr(string)
+        .to_str()
+        .map(Some)
+        .map_err(|_| CError::invalid_argument(format!("The {name} is not valid UTF-8")))
+}
+
+/// Writes the program counter to `pc_out`, unless it is null.
+///
+/// # Safety
+///
+/// `pc_out` must be null or valid for writes.
+unsafe fn write_pc(pc_out: *mut u64, pc: u64) {
+    if let Some(pc_out) = pc_out.as_mut() {
+        *pc_out = pc;
+    }
+}
+
+/// Returns the message of the last error of the calling thread, or null if no function failed.
+///
+/// The message is valid until the next failing call on the same thread.
+#[no_mangle]
+pub extern "C" fn probe_rs_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(ptr::null(), |message| message.as_ptr())
+    })
+}
+
+/// Writes the number of connected probes to `count_out`.
+///
+/// # Safety
+///
+/// `count_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_probe_count(count_out: *mut usize) -> ProbeRsStatus {
+    ffi_call(|| {
+        let count_out = count_out
+            .as_mut()
+            .ok_or_else(|| CError::invalid_argument("The count pointer is null"))?;
+        *count_out = Lister::new().list_all().len();
+        Ok(())
+    })
+}
+
+/// Opens a probe and attaches to the chip, storing the new session in `session_out`.
+///
+/// `probe` selects the probe as `VID:PID[:SERIAL]`, the first probe is used if it is null.
+/// `chip` is the name of the chip, which is detected automatically if it is null.
+///
+/// # Safety
+///
+/// `probe` and `chip` must be null or point to nul terminated strings, and `session_out` must
+/// be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_attach(
+    probe: *const c_char,
+    chip: *const c_char,
+    under_reset: bool,
+    session_out: *mut *mut ProbeRsSession,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        if session_out.is_null() {
+            return Err(CError::invalid_argument("The session pointer is null"));
+        }
+        let probe = optional_str(probe, "probe selector")?;
+        let chip = optional_str(chip, "chip name")?;
+
+        let lister = Lister::new();
+        let probe = match probe {
+            Some(selector) => {
+                let selector = DebugProbeSelector::try_from(selector)
+                    .map_err(|error| CError::invalid_argument(error.to_string()))?;
+                lister.open(selector)?
+            }
+            None => {
+                let Some(info) = lister.list_all().into_iter().next() else {
+                    return Err(CError::not_found("No probe was found"));
+                };
+                info.open(&lister)?
+            }
+        };
+
+        let target = match chip {
+            Some(chip) => TargetSelector::from(chip),
+            None => TargetSelector::Auto,
+        };
+        let session = if under_reset {
+            probe.attach_under_reset(target, Permissions::default())?
+        } else {
+            probe.attach(target, Permissions::default())?
+        };
+
+        *session_out = Box::into_raw(Box::new(ProbeRsSession { session }));
+        Ok(())
+    })
+}
+
+/// Closes the session and the probe. Passing null does nothing.
+///
+/// # Safety
+///
+/// `session` must be null or a pointer returned by [`probe_rs_attach`] which was not closed.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_session_close(session: *mut ProbeRsSession) {
+    if !session.is_null() {
+        // Detaching may fail, which is logged by the session itself.
+        let _ = catch_unwind(AssertUnwindSafe(|| drop(Box::from_raw(session))));
+    }
+}
+
+/// Writes the number of cores of the chip to `count_out`.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed, and
+/// `count_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_count(
+    session: *mut ProbeRsSession,
+    count_out: *mut usize,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        let count_out = count_out
+            .as_mut()
+            .ok_or_else(|| CError::invalid_argument("The count pointer is null"))?;
+        *count_out = session.list_cores().len();
+        Ok(())
+    })
+}
+
+/// Reads `len` bytes at `address` into `data`.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed, and
+/// `data` must be valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_read_memory(
+    session: *mut ProbeRsSession,
+    core: usize,
+    address: u64,
+    data: *mut u8,
+    len: usize,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        if data.is_null() && len > 0 {
+            return Err(CError::invalid_argument("The data pointer is null"));
+        }
+        if len == 0 {
+            return Ok(());
+        }
+
+        let data = std::slice::from_raw_parts_mut(data, len);
+        session.core(core)?.read(address, data)?;
+        Ok(())
+    })
+}
+
+/// Writes `len` bytes from `data` to `address`.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed, and
+/// `data` must be valid for reads of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_write_memory(
+    session: *mut ProbeRsSession,
+    core: usize,
+    address: u64,
+    data: *const u8,
+    len: usize,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        if data.is_null() && len > 0 {
+            return Err(CError::invalid_argument("The data pointer is null"));
+        }
+        if len == 0 {
+            return Ok(());
+        }
+
+        let data = std::slice::from_raw_parts(data, len);
+        let mut core = session.core(core)?;
+        core.write(address, data)?;
+        core.flush()?;
+        Ok(())
+    })
+}
+
+/// Halts the core, waiting up to `timeout_ms` milliseconds, and writes the program counter to
+/// `pc_out` unless it is null.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed, and
+/// `pc_out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_halt(
+    session: *mut ProbeRsSession,
+    core: usize,
+    timeout_ms: u32,
+    pc_out: *mut u64,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        let info = session
+            .core(core)?
+            .halt(Duration::from_millis(timeout_ms.into()))?;
+        write_pc(pc_out, info.pc);
+        Ok(())
+    })
+}
+
+/// Resumes the core.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_run(session: *mut ProbeRsSession, core: usize) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        session.core(core)?.run()?;
+        Ok(())
+    })
+}
+
+/// Steps a single instruction of the halted core, and writes the new program counter to
+/// `pc_out` unless it is null.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed, and
+/// `pc_out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_step(
+    session: *mut ProbeRsSession,
+    core: usize,
+    pc_out: *mut u64,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        let info = session.core(core)?.step()?;
+        write_pc(pc_out, info.pc);
+        Ok(())
+    })
+}
+
+/// Resets the core and halts it before the first instruction, waiting up to `timeout_ms`
+/// milliseconds, and writes the program counter to `pc_out` unless it is null.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed, and
+/// `pc_out` must be null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_reset_and_halt(
+    session: *mut ProbeRsSession,
+    core: usize,
+    timeout_ms: u32,
+    pc_out: *mut u64,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        let info = session
+            .core(core)?
+            .reset_and_halt(Duration::from_millis(timeout_ms.into()))?;
+        write_pc(pc_out, info.pc);
+        Ok(())
+    })
+}
+
+/// Writes whether the core is halted to `halted_out`.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed, and
+/// `halted_out` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_core_halted(
+    session: *mut ProbeRsSession,
+    core: usize,
+    halted_out: *mut bool,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        let halted_out = halted_out
+            .as_mut()
+            .ok_or_else(|| CError::invalid_argument("The halted pointer is null"))?;
+        *halted_out = session.core(core)?.core_halted()?;
+        Ok(())
+    })
+}
+
+/// Sets a hardware breakpoint at `address`.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_set_hw_breakpoint(
+    session: *mut ProbeRsSession,
+    core: usize,
+    address: u64,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        session.core(core)?.set_hw_breakpoint(address)?;
+        Ok(())
+    })
+}
+
+/// Clears the hardware breakpoint at `address`.
+///
+/// # Safety
+///
+/// `session` must be a pointer returned by [`probe_rs_attach`] which was not closed.
+#[no_mangle]
+pub unsafe extern "C" fn probe_rs_clear_hw_breakpoint(
+    session: *mut ProbeRsSession,
+    core: usize,
+    address: u64,
+) -> ProbeRsStatus {
+    ffi_call(|| {
+        let session = session_mut(session)?;
+        session.core(core)?.clear_hw_breakpoint(address)?;
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn null_arguments_are_rejected() {
+        let mut count = 0;
+        let status = unsafe { probe_rs_core_count(ptr::null_mut(), &mut count) };
+        assert_eq!(status, ProbeRsStatus::InvalidArgument);
+
+        let message = unsafe { CStr::from_ptr(probe_rs_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "The session is null");
+
+        let status = unsafe { probe_rs_probe_count(ptr::null_mut()) };
+        assert_eq!(status, ProbeRsStatus::InvalidArgument);
+    }
+
+    #[test]
+    fn panics_are_caught() {
+        let status = ffi_call(|| panic!("oops"));
+        assert_eq!(status, ProbeRsStatus::Error);
+
+        let message = unsafe { CStr::from_ptr(probe_rs_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "probe-rs panicked");
+    }
+}
@@ -0,0 +1,357 @@
+//! Python bindings for scripting probe-rs debug sessions.
+//!
+//! ```python
+//! import probe_rs
+//!
+//! session = probe_rs.Session.attach(chip="nRF52840_xxAA")
+//! core = session.core(0)
+//! core.reset_and_halt()
+//! core.set_hw_breakpoint(0x0000_1234)
+//! core.run()
+//! print(hex(core.read_register("pc")))
+//! ```
+//!
+//! All errors are raised as `probe_rs.ProbeRsError`.
+
+use std::time::Duration;
+
+use probe_rs::{
+    config::TargetSelector, rtt, CoreInterface, CoreRegister, DebugProbeSelector, Lister,
+    MemoryInterface, Permissions, RegisterValue,
+};
+use pyo3::{create_exception, exceptions::PyException, prelude::*, types::PyBytes};
+
+create_exception!(probe_rs, ProbeRsError, PyException);
+
+fn py_err(error: impl std::fmt::Display) -> PyErr {
+    ProbeRsError::new_err(error.to_string())
+}
+
+/// Returns the selectors of all connected probes, as `VID:PID[:SERIAL]`.
+#[pyfunction]
+fn list_probes() -> Vec<String> {
+    Lister::new()
+        .list_all()
+        .iter()
+        .map(|info| DebugProbeSelector::from(info).to_string())
+        .collect()
+}
+
+/// A debug session of a chip.
+#[pyclass(unsendable)]
+struct Session {
+    session: probe_rs::Session,
+}
+
+#[pymethods]
+impl Session {
+    /// Opens a probe and attaches to the chip.
+    ///
+    /// `probe` selects the probe as `VID:PID[:SERIAL]`, the first probe is used if it is not
+    /// given. The chip is detected automatically if `chip` is not given.
+    #[staticmethod]
+    #[pyo3(signature = (chip=None, probe=None, under_reset=false, speed=None))]
+    fn attach(
+        chip: Option<&str>,
+        probe: Option<&str>,
+        under_reset: bool,
+        speed: Option<u32>,
+    ) -> PyResult<Self> {
+        let lister = Lister::new();
+        let mut probe = match probe {
+            Some(selector) => {
+                let selector = DebugProbeSelector::try_from(selector).map_err(py_err)?;
+                lister.open(selector).map_err(py_err)?
+            }
+            None => {
+                let Some(info) = lister.list_all().into_iter().next() else {
+                    return Err(ProbeRsError::new_err("No probe was found"));
+                };
+                info.open(&lister).map_err(py_err)?
+            }
+        };
+
+        if let Some(speed) = speed {
+            probe.set_speed(speed).map_err(py_err)?;
+        }
+
+        let target = match chip {
+            Some(chip) => TargetSelector::from(chip),
+            None => TargetSelector::Auto,
+        };
+        let session = if under_reset {
+            probe.attach_under_reset(target, Permissions::default())
+        } else {
+            probe.attach(target, Permissions::default())
+        }
+        .map_err(py_err)?;
+
+        Ok(Self { session })
+    }
+
+    /// The name of the chip.
+    #[getter]
+    fn target_name(&self) -> String {
+        self.session.target().name.clone()
+    }
+
+    /// The indices and types of all cores, e.g. `[(0, "Armv7em")]`.
+    fn cores(&self) -> Vec<(usize, String)> {
+        self.session
+            .list_cores()
+            .into_iter()
+            .map(|(index, core_type)| (index, format!("{core_type:?}")))
+            .collect()
+    }
+
+    /// Returns the core with the given index.
+    fn core(slf: Py<Self>, py: Python<'_>, index: usize) -> PyResult<Core> {
+        // Check the index now, instead of failing on the first access.
+        slf.borrow_mut(py).session.core(index).map_err(py_err)?;
+        Ok(Core {
+            session: slf,
+            index,
+        })
+    }
+
+    /// Attaches to the RTT control block in the RAM of the chip, using the given core.
+    #[pyo3(signature = (core=0, address=None))]
+    fn rtt(slf: Py<Self>, py: Python<'_>, core: usize, address: Option<u32>) -> PyResult<Rtt> {
+        let rtt = {
+            let mut this = slf.borrow_mut(py);
+            let memory_map = this.session.target().memory_map.clone();
+            let mut core = this.session.core(core).map_err(py_err)?;
+            let region = match address {
+                Some(address) => rtt::ScanRegion::Exact(address),
+                None => rtt::ScanRegion::Ram,
+            };
+            rtt::Rtt::attach_region(&mut core, &memory_map, &region).map_err(py_err)?
+        };
+
+        Ok(Rtt {
+            session: slf,
+            core,
+            rtt,
+        })
+    }
+}
+
+/// A core of a [`Session`].
+#[pyclass(unsendable)]
+struct Core {
+    session: Py<Session>,
+    index: usize,
+}
+
+impl Core {
+    /// Runs `f` on the core.
+    fn with_core<T>(
+        &self,
+        py: Python<'_>,
+        f: impl FnOnce(&mut probe_rs::Core<'_>) -> Result<T, probe_rs::Error>,
+    ) -> PyResult<T> {
+        let mut session = self.session.borrow_mut(py);
+        let mut core = session.session.core(self.index).map_err(py_err)?;
+        f(&mut core).map_err(py_err)
+    }
+
+    fn register(core: &probe_rs::Core<'_>, name: &str) -> Result<&'static CoreRegister, PyErr> {
+        core.registers()
+            .all_registers()
+            .find(|register| register.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| ProbeRsError::new_err(format!("The core has no register {name}")))
+    }
+}
+
+#[pymethods]
+impl Core {
+    /// The index of the core.
+    #[getter]
+    fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Halts the core and returns the program counter.
+    #[pyo3(signature = (timeout_ms=500))]
+    fn halt(&self, py: Python<'_>, timeout_ms: u64) -> PyResult<u64> {
+        self.with_core(py, |core| {
+            Ok(core.halt(Duration::from_millis(timeout_ms))?.pc)
+        })
+    }
+
+    /// Resumes the core.
+    fn run(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_core(py, |core| core.run())
+    }
+
+    /// Steps a single instruction and returns the new program counter.
+    fn step(&self, py: Python<'_>) -> PyResult<u64> {
+        self.with_core(py, |core| Ok(core.step()?.pc))
+    }
+
+    /// Resets the core, halts it before the first instruction and returns the program counter.
+    #[pyo3(signature = (timeout_ms=500))]
+    fn reset_and_halt(&self, py: Python<'_>, timeout_ms: u64) -> PyResult<u64> {
+        self.with_core(py, |core| {
+            Ok(core.reset_and_halt(Duration::from_millis(timeout_ms))?.pc)
+        })
+    }
+
+    /// Resets the core without halting it.
+    fn reset(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_core(py, |core| core.reset())
+    }
+
+    /// Returns `True` if the core is halted.
+    fn is_halted(&self, py: Python<'_>) -> PyResult<bool> {
+        self.with_core(py, |core| core.core_halted())
+    }
+
+    /// Reads `length` bytes at `address`.
+    fn read_memory<'py>(
+        &self,
+        py: Python<'py>,
+        address: u64,
+        length: usize,
+    ) -> PyResult<&'py PyBytes> {
+        let mut data = vec![0; length];
+        self.with_core(py, |core| core.read(address, &mut data))?;
+        Ok(PyBytes::new(py, &data))
+    }
+
+    /// Writes `data` to `address`.
+    fn write_memory(&self, py: Python<'_>, address: u64, data: &[u8]) -> PyResult<()> {
+        self.with_core(py, |core| {
+            core.write(address, data)?;
+            core.flush()
+        })
+    }
+
+    /// Reads the 32 bit word at `address`.
+    fn read_word_32(&self, py: Python<'_>, address: u64) -> PyResult<u32> {
+        self.with_core(py, |core| core.read_word_32(address))
+    }
+
+    /// Writes the 32 bit word at `address`.
+    fn write_word_32(&self, py: Python<'_>, address: u64, value: u32) -> PyResult<()> {
+        self.with_core(py, |core| {
+            core.write_word_32(address, value)?;
+            core.flush()
+        })
+    }
+
+    /// The names of all registers of the core.
+    fn register_names(&self, py: Python<'_>) -> PyResult<Vec<&'static str>> {
+        self.with_core(py, |core| {
+            Ok(core
+                .registers()
+                .all_registers()
+                .map(|register| register.name())
+                .collect())
+        })
+    }
+
+    /// Reads the register with the given name, e.g. `"pc"` or `"a0"`.
+    fn read_register(&self, py: Python<'_>, name: &str) -> PyResult<u64> {
+        let mut session = self.session.borrow_mut(py);
+        let mut core = session.session.core(self.index).map_err(py_err)?;
+        let register = Self::register(&core, name)?;
+        core.read_core_reg(register.id()).map_err(py_err)
+    }
+
+    /// Writes the register with the given name.
+    fn write_register(&self, py: Python<'_>, name: &str, value: u64) -> PyResult<()> {
+        let mut session = self.session.borrow_mut(py);
+        let mut core = session.session.core(self.index).map_err(py_err)?;
+        let register = Self::register(&core, name)?;
+        core.write_core_reg(register.id(), RegisterValue::from(value))
+            .map_err(py_err)
+    }
+
+    /// Sets a hardware breakpoint at `address`.
+    fn set_hw_breakpoint(&self, py: Python<'_>, address: u64) -> PyResult<()> {
+        self.with_core(py, |core| core.set_hw_breakpoint(address))
+    }
+
+    /// Clears the hardware breakpoint at `address`.
+    fn clear_hw_breakpoint(&self, py: Python<'_>, address: u64) -> PyResult<()> {
+        self.with_core(py, |core| core.clear_hw_breakpoint(address))
+    }
+
+    /// Clears all hardware breakpoints.
+    fn clear_all_hw_breakpoints(&self, py: Python<'_>) -> PyResult<()> {
+        self.with_core(py, |core| core.clear_all_hw_breakpoints())
+    }
+
+    /// The addresses of the set hardware breakpoints.
+    fn hw_breakpoints(&self, py: Python<'_>) -> PyResult<Vec<u64>> {
+        self.with_core(py, |core| {
+            Ok(core.hw_breakpoints()?.into_iter().flatten().collect())
+        })
+    }
+}
+
+/// The RTT channels of a [`Session`].
+#[pyclass(unsendable)]
+struct Rtt {
+    session: Py<Session>,
+    core: usize,
+    rtt: rtt::Rtt,
+}
+
+#[pymethods]
+impl Rtt {
+    /// The numbers and names of the up channels, which send data from the target.
+    fn up_channels(&mut self) -> Vec<(usize, Option<String>)> {
+        self.rtt
+            .up_channels()
+            .iter()
+            .map(|channel| (channel.number(), channel.name().map(String::from)))
+            .collect()
+    }
+
+    /// The numbers and names of the down channels, which send data to the target.
+    fn down_channels(&mut self) -> Vec<(usize, Option<String>)> {
+        self.rtt
+            .down_channels()
+            .iter()
+            .map(|channel| (channel.number(), channel.name().map(String::from)))
+            .collect()
+    }
+
+    /// Reads the pending data of an up channel, without blocking.
+    fn read<'py>(&mut self, py: Python<'py>, channel: usize) -> PyResult<&'py PyBytes> {
+        let mut session = self.session.borrow_mut(py);
+        let mut core = session.session.core(self.core).map_err(py_err)?;
+        let Some(channel) = self.rtt.up_channels().get(channel) else {
+            return Err(ProbeRsError::new_err(format!("No up channel {channel}")));
+        };
+
+        let mut data = vec![0; channel.buffer_size()];
+        let read = channel.read(&mut core, &mut data).map_err(py_err)?;
+        Ok(PyBytes::new(py, &data[..read]))
+    }
+
+    /// Writes `data` to a down channel, returning the number of bytes which fit in its buffer.
+    fn write(&mut self, py: Python<'_>, channel: usize, data: &[u8]) -> PyResult<usize> {
+        let mut session = self.session.borrow_mut(py);
+        let mut core = session.session.core(self.core).map_err(py_err)?;
+        let Some(channel) = self.rtt.down_channels().get(channel) else {
+            return Err(ProbeRsError::new_err(format!("No down channel {channel}")));
+        };
+
+        channel.write(&mut core, data).map_err(py_err)
+    }
+}
+
+/// Python bindings for scripting probe-rs debug sessions.
+#[pymodule]
+#[pyo3(name = "probe_rs")]
+fn probe_rs_py(py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add("ProbeRsError", py.get_type::<ProbeRsError>())?;
+    m.add_function(wrap_pyfunction!(list_probes, m)?)?;
+    m.add_class::<Session>()?;
+    m.add_class::<Core>()?;
+    m.add_class::<Rtt>()?;
+    Ok(())
+}
@@ -1,5 +1,7 @@
 use super::memory::MemoryRegion;
-use crate::{serialize::hex_option, CoreType};
+use crate::serialize::{hex_option, hex_range, hex_u_int};
+use crate::CoreType;
+use core::ops::Range;
 use serde::{Deserialize, Serialize};
 
 /// Represents a DAP scan chain element.
@@ -9,6 +11,19 @@ pub struct ScanChainElement {
     pub name: Option<String>,
     /// Specifies the IR length of the DAP (default value: 4).
     pub ir_len: Option<u8>,
+    /// The IDCODE of the TAP, used to detect the chip on the JTAG chain.
+    #[serde(default, serialize_with = "hex_option")]
+    pub idcode: Option<u32>,
+}
+
+/// A register which tells apart chips that share the IDCODEs of their scan chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChipDetectRegister {
+    /// The address of the 32-bit register.
+    #[serde(serialize_with = "hex_u_int")]
+    pub address: u64,
+    /// The values the register has on this chip, e.g. one per chip revision.
+    pub values: Vec<u32>,
 }
 
 /// A finite list of all possible binary formats a target might support.
@@ -69,6 +84,24 @@ pub struct Chip {
     /// ref: `<https://open-cmsis-pack.github.io/Open-CMSIS-Pack-Spec/main/html/sdf_pg.html#sdf_element_scanchain>`
     #[serde(default)]
     pub scan_chain: Option<Vec<ScanChainElement>>,
+    /// A RAM range which the debugger may use for its own code and data, like flash stubs or
+    /// buffers of fast memory operations.
+    ///
+    /// It must be enclosed in exactly one RAM region from `memory_map`. The original contents
+    /// are restored after each use, so the range may overlap with application memory.
+    #[serde(default)]
+    pub scratch_region: Option<std::ops::Range<u64>>,
+    /// Address ranges through which the program reads the flash, e.g. the instruction and data
+    /// buses of a cache MMU, with the flash addresses they map to.
+    ///
+    /// ELF segments linked to these ranges are written to the flash at the translated address.
+    /// Each translated range must be enclosed in exactly one NVM region from `memory_map`.
+    #[serde(default)]
+    pub flash_mappings: Vec<AddressTranslation>,
+    /// A register which is read during auto-detection to tell this chip apart from other chips
+    /// with the same IDCODEs in their `scan_chain`.
+    #[serde(default)]
+    pub detect_register: Option<ChipDetectRegister>,
     /// The default binary format for this chip
     pub default_binary_format: Option<BinaryFormat>,
 }
@@ -90,6 +123,9 @@ impl Chip {
             flash_algorithms: vec![],
             rtt_scan_ranges: None,
             scan_chain: Some(vec![]),
+            scratch_region: None,
+            flash_mappings: vec![],
+            detect_register: None,
             default_binary_format: Some(BinaryFormat::Raw),
         }
     }
@@ -143,8 +179,108 @@ pub struct ArmCoreAccessOptions {
 pub struct RiscvCoreAccessOptions {}
 
 /// The data required to access an Xtensa core
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct XtensaCoreAccessOptions {}
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct XtensaCoreAccessOptions {
+    /// Whether the core implements the Xtensa NX instruction set variant.
+    ///
+    /// NX cores can not be told apart from LX cores through the debug module, so this
+    /// has to be declared in the target description.
+    #[serde(default)]
+    pub nx: bool,
+    /// The position of the core's TAP in the JTAG scan chain.
+    ///
+    /// Defaults to the index of the core, which fits chips whose scan chain only contains
+    /// the TAPs of their cores.
+    #[serde(default)]
+    pub jtag_tap: Option<usize>,
+    /// Whether the core lacks the Code Density Option, i.e. the 16-bit instructions.
+    ///
+    /// Instruction lengths are decoded differently without it, so this has to be declared in
+    /// the target description.
+    #[serde(default)]
+    pub no_code_density: bool,
+    /// Whether the core lacks the Windowed Register Option, i.e. only implements the 16
+    /// address registers of the call0 ABI. NX cores never have register windows.
+    #[serde(default)]
+    pub no_windowed_registers: bool,
+    /// Whether the core lacks the Loop Option, i.e. the zero-overhead loop instructions.
+    #[serde(default)]
+    pub no_loops: bool,
+    /// The interrupt level of debug exceptions (`DEBUGLEVEL`), between 2 and 7.
+    ///
+    /// Defaults to 6, which is used by the Espressif chips.
+    #[serde(default)]
+    pub debug_level: Option<u8>,
+    /// The number of instruction breakpoint units (`NIBREAK`), at most 2. Defaults to 2.
+    #[serde(default)]
+    pub ibreak_count: Option<u8>,
+    /// The number of data breakpoint units (`NDBREAK`), at most 2. Defaults to 2.
+    #[serde(default)]
+    pub dbreak_count: Option<u8>,
+    /// Whether the core implements the Floating-Point Coprocessor Option.
+    ///
+    /// Detected by probing the core if not given.
+    #[serde(default)]
+    pub fpu: Option<bool>,
+    /// Address ranges that the debugger accesses at a different address than the program.
+    ///
+    /// Some memory is only mapped for the program, e.g. by a cache MMU that is not configured
+    /// while the debugger accesses the memory, or is only reachable with 32-bit accesses on the
+    /// bus the program uses, while another bus allows narrower accesses.
+    #[serde(default)]
+    pub address_translations: Vec<AddressTranslation>,
+    /// Address ranges that the core accesses through its instruction or data cache.
+    ///
+    /// The debugger accesses memory behind the caches, so the cache lines covering these ranges
+    /// have to be maintained after the debugger writes to them.
+    #[serde(default)]
+    pub cached_regions: Vec<CachedRange>,
+}
+
+/// An address range that is accessed through the cache of an Xtensa core.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CachedRange {
+    /// The cached addresses.
+    #[serde(serialize_with = "hex_range")]
+    pub range: Range<u64>,
+    /// The cache line size in bytes. Must be a power of two.
+    pub line_size: u32,
+    /// Whether the range is read through the data cache, whose lines have to be written back
+    /// and invalidated.
+    #[serde(default)]
+    pub data_cache: bool,
+    /// Whether the range is fetched through the instruction cache, whose lines have to be
+    /// invalidated.
+    #[serde(default)]
+    pub instruction_cache: bool,
+}
+
+/// An address range that is accessed at a different address by the debugger.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AddressTranslation {
+    /// The addresses used by the program.
+    #[serde(serialize_with = "hex_range")]
+    pub range: Range<u64>,
+    /// The address at which the debugger accesses the start of the range.
+    #[serde(serialize_with = "hex_u_int")]
+    pub target: u64,
+}
+
+impl AddressTranslation {
+    /// Translates `address`, if it is in the range.
+    pub fn translate(&self, address: u64) -> Option<u64> {
+        self.range
+            .contains(&address)
+            .then(|| address - self.range.start + self.target)
+    }
+
+    /// Translates `address` back to the address used by the program, if it is in the translated
+    /// range.
+    pub fn untranslate(&self, address: u64) -> Option<u64> {
+        let offset = address.checked_sub(self.target)?;
+        (offset < self.range.end - self.range.start).then(|| self.range.start + offset)
+    }
+}
 
 /// Helper function that interates the scan chain and returns a vector of all of
 /// the ir_lengths of the scan chain elements.
@@ -93,6 +93,8 @@ pub enum InstructionSet {
     RV32C,
     /// Xtensa instruction set
     Xtensa,
+    /// Xtensa NX instruction set, which drops the register windows and uses the Call0 ABI.
+    XtensaNx,
 }
 
 impl InstructionSet {
@@ -107,7 +109,7 @@ impl InstructionSet {
             InstructionSet::A64 => 4,
             InstructionSet::RV32 => 4,
             InstructionSet::RV32C => 2,
-            InstructionSet::Xtensa => 2,
+            InstructionSet::Xtensa | InstructionSet::XtensaNx => 2,
         }
     }
     /// Get the maximum instruction size in bytes. All supported architectures have a maximum instruction size of 4 bytes.
@@ -232,13 +234,24 @@ impl ChipFamily {
                             ));
                         }
                     }
-                    CoreAccessOptions::Xtensa(_) => {
+                    CoreAccessOptions::Xtensa(options) => {
                         if core.core_type != CoreType::Xtensa {
                             return Err(format!(
                                 "Xtensa options don't match core type {:?} on core {}",
                                 core.core_type, core.name
                             ));
                         }
+
+                        if let Some(region) = options
+                            .cached_regions
+                            .iter()
+                            .find(|region| !region.line_size.is_power_of_two())
+                        {
+                            return Err(format!(
+                                "Core {} has a cached region {:#x?} with a line size that is not a power of two",
+                                core.name, region.range
+                            ));
+                        }
                     }
                 }
             }
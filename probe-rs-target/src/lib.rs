@@ -19,8 +19,9 @@ mod memory;
 pub(crate) mod serialize;
 
 pub use chip::{
-    get_ir_lengths, ArmCoreAccessOptions, BinaryFormat, Chip, Core, CoreAccessOptions,
-    RiscvCoreAccessOptions, ScanChainElement, XtensaCoreAccessOptions,
+    get_ir_lengths, AddressTranslation, ArmCoreAccessOptions, BinaryFormat, CachedRange, Chip,
+    ChipDetectRegister, Core, CoreAccessOptions, RiscvCoreAccessOptions, ScanChainElement,
+    XtensaCoreAccessOptions,
 };
 pub use chip_family::{
     Architecture, ChipFamily, CoreType, InstructionSet, TargetDescriptionSource,
@@ -28,6 +29,6 @@ pub use chip_family::{
 pub use flash_algorithm::{RawFlashAlgorithm, TransferEncoding};
 pub use flash_properties::FlashProperties;
 pub use memory::{
-    GenericRegion, MemoryRange, MemoryRegion, NvmRegion, PageInfo, RamRegion, SectorDescription,
-    SectorInfo,
+    AccessWidth, GenericRegion, MemoryRange, MemoryRegion, NvmRegion, PageInfo, RamRegion,
+    SectorDescription, SectorInfo,
 };
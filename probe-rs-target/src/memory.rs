@@ -39,6 +39,9 @@ pub struct RamRegion {
     pub is_boot_memory: bool,
     /// List of cores that can access this region
     pub cores: Vec<String>,
+    /// The widths of the accesses this region tolerates.
+    #[serde(default)]
+    pub access_width: AccessWidth,
 }
 
 /// Represents a generic region.
@@ -51,6 +54,28 @@ pub struct GenericRegion {
     pub range: Range<u64>,
     /// List of cores that can access this region
     pub cores: Vec<String>,
+    /// The widths of the accesses this region tolerates.
+    #[serde(default)]
+    pub access_width: AccessWidth,
+}
+
+/// The widths of the memory accesses that a region tolerates.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessWidth {
+    /// 8, 16 and 32-bit accesses are supported.
+    #[default]
+    Any,
+
+    /// Only 32-bit accesses are supported.
+    ///
+    /// Narrower accesses are emulated by accessing the containing words. Writes are performed as a
+    /// read-modify-write, so this must not be used for regions with read side effects or
+    /// write-only registers.
+    Word32,
+
+    /// Only 32-bit accesses are supported, and narrower or unaligned accesses are rejected.
+    Word32Strict,
 }
 
 /// Holds information about a specific, individual flash
@@ -167,6 +192,26 @@ impl MemoryRegion {
             MemoryRegion::Nvm(region) => &region.cores,
         }
     }
+
+    /// Get the address range of this memory region.
+    pub fn address_range(&self) -> Range<u64> {
+        match self {
+            MemoryRegion::Ram(region) => region.range.clone(),
+            MemoryRegion::Generic(region) => region.range.clone(),
+            MemoryRegion::Nvm(region) => region.range.clone(),
+        }
+    }
+
+    /// Get the widths of the accesses this memory region tolerates.
+    ///
+    /// Non-volatile memory is always reported as [`AccessWidth::Any`].
+    pub fn access_width(&self) -> AccessWidth {
+        match self {
+            MemoryRegion::Ram(region) => region.access_width,
+            MemoryRegion::Generic(region) => region.access_width,
+            MemoryRegion::Nvm(_) => AccessWidth::Any,
+        }
+    }
 }
 
 #[cfg(test)]
@@ -2,8 +2,8 @@
 
 use super::{
     instructions::aarch32::{
-        build_bx, build_ldc, build_mcr, build_mov, build_mrc, build_mrs, build_stc, build_vmov,
-        build_vmrs,
+        build_bx, build_dsb, build_isb, build_ldc, build_mcr, build_mov, build_mrc, build_mrs,
+        build_stc, build_vmov, build_vmrs,
     },
     registers::{
         aarch32::{
@@ -29,6 +29,7 @@ use anyhow::Result;
 use num_traits::Zero;
 use std::{
     mem::size_of,
+    ops::Range,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -304,6 +305,37 @@ impl<'probe> Armv7a<'probe> {
     fn set_core_status(&mut self, new_status: CoreStatus) {
         super::update_core_status(&mut self.memory, &mut self.state.current_state, new_status);
     }
+
+    /// Cleans the data cache lines covering `range` to the point of unification, and
+    /// invalidates the instruction cache and branch predictor.
+    fn sync_instruction_cache(&mut self, range: Range<u64>) -> Result<(), Error> {
+        // Save r0
+        self.prepare_r0_for_clobber()?;
+
+        // MRC p15, 0, r0, c0, c0, 1 ; Read CTR
+        self.execute_instruction(build_mrc(15, 0, 0, 0, 0, 1))?;
+        // MCR p14, 0, r0, c0, c5, 0 ; Write DBGDTRTXint Register
+        let ctr = self.execute_instruction_with_result(build_mcr(14, 0, 0, 0, 5, 0))?;
+        let line_size = 4u64 << ((ctr >> 16) & 0xF);
+
+        let start = range.start & !(line_size - 1);
+        for line in (start..range.end).step_by(line_size as usize) {
+            self.set_r0(valid_32bit_address(line)?)?;
+
+            // MCR p15, 0, r0, c7, c11, 1 ; DCCMVAU
+            self.execute_instruction(build_mcr(15, 0, 0, 7, 11, 1))?;
+        }
+        self.execute_instruction(build_dsb())?;
+
+        // MCR p15, 0, r0, c7, c5, 0 ; ICIALLU
+        self.execute_instruction(build_mcr(15, 0, 0, 7, 5, 0))?;
+        // MCR p15, 0, r0, c7, c5, 6 ; BPIALL
+        self.execute_instruction(build_mcr(15, 0, 0, 7, 5, 6))?;
+        self.execute_instruction(build_dsb())?;
+        self.execute_instruction(build_isb())?;
+
+        Ok(())
+    }
 }
 
 impl<'probe> CoreInterface for Armv7a<'probe> {
@@ -958,6 +990,14 @@ impl<'probe> MemoryInterface for Armv7a<'probe> {
         // Nothing to do - this runs through the CPU which automatically handles any caching
         Ok(())
     }
+
+    fn code_barrier(&mut self, range: Range<u64>) -> Result<(), Error> {
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        self.sync_instruction_cache(range)
+    }
 }
 
 #[cfg(test)]
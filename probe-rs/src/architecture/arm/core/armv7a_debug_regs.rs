@@ -1,6 +1,9 @@
 //! Debug register definitions
 
-use crate::{core::BreakpointCause, memory_mapped_bitfield_register, HaltReason};
+use crate::{
+    core::{BreakpointCause, ExceptionCause},
+    memory_mapped_bitfield_register, HaltReason,
+};
 
 memory_mapped_bitfield_register! {
     /// DBGDSCR - Debug Status and Control Registers
@@ -221,17 +224,17 @@ impl Dbgdscr {
                 // Breakpoint debug event
                 0b0001 => HaltReason::Breakpoint(BreakpointCause::Hardware),
                 // Async watchpoint debug event
-                0b0010 => HaltReason::Watchpoint,
+                0b0010 => HaltReason::Watchpoint(None),
                 // BKPT instruction
                 0b0011 => HaltReason::Breakpoint(BreakpointCause::Software),
                 // External halt request
                 0b0100 => HaltReason::External,
                 // Vector catch
-                0b0101 => HaltReason::Exception,
+                0b0101 => HaltReason::Exception(ExceptionCause::Unknown),
                 // OS Unlock vector catch
-                0b1000 => HaltReason::Exception,
+                0b1000 => HaltReason::Exception(ExceptionCause::Unknown),
                 // Sync watchpoint debug event
-                0b1010 => HaltReason::Watchpoint,
+                0b1010 => HaltReason::Watchpoint(None),
                 // All other values are reserved
                 _ => HaltReason::Unknown,
             }
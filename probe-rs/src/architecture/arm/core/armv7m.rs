@@ -1,7 +1,7 @@
 //! Register types and the core interface for armv7-M
 
 use super::{
-    cortex_m::Mvfr0,
+    cortex_m::{DwtVersion, Mvfr0},
     registers::cortex_m::{
         CORTEX_M_CORE_REGISTERS, CORTEX_M_WITH_FP_CORE_REGISTERS, FP, PC, RA, SP,
     },
@@ -13,8 +13,9 @@ use crate::{
         sequences::ArmDebugSequence, ArmError,
     },
     core::{
-        Architecture, CoreInformation, CoreInterface, CoreRegisters, CoreStatus, HaltReason,
-        MemoryMappedRegister, RegisterId, RegisterValue, VectorCatchCondition,
+        step_repeatedly, Architecture, CoreInformation, CoreInterface, CoreRegisters, CoreStatus,
+        HaltReason, MemoryMappedRegister, RegisterId, RegisterValue, ResetCause, StepOptions,
+        VectorCatchCondition, Watchpoint,
     },
     error::Error,
     memory::valid_32bit_address,
@@ -24,6 +25,7 @@ use anyhow::{anyhow, Result};
 use bitfield::bitfield;
 use std::{
     mem::size_of,
+    ops::Range,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -645,6 +647,70 @@ impl<'probe> Armv7m<'probe> {
     fn set_core_status(&mut self, new_status: CoreStatus) {
         super::update_core_status(&mut self.memory, &mut self.state.current_state, new_status);
     }
+
+    /// Steps one instruction, masking interrupts during the step if `mask_interrupts` is set.
+    fn step_once(&mut self, mask_interrupts: bool) -> Result<CoreInformation, Error> {
+        // First check if we stopped on a breakpoint, because this requires special handling before we can continue.
+        let pc_before_step = self.read_core_reg(self.program_counter().into())?;
+        let was_breakpoint = if matches!(
+            self.state.current_state,
+            CoreStatus::Halted(HaltReason::Breakpoint(_))
+        ) {
+            self.enable_breakpoints(false)?;
+            true
+        } else {
+            false
+        };
+
+        let mut dhcsr = Dhcsr(self.memory.read_word_32(Dhcsr::get_mmio_address())?);
+
+        // Follow the rules of the ... ARMv7-M Architecture reference, C1.6 Debug System Registers - DHCSR, with respect to setting maskints
+        if !dhcsr.c_debugen() {
+            tracing::warn!("Attempting to STEP while DHCSR->C_DEBUGEN is false");
+        }
+        if dhcsr.c_maskints() != mask_interrupts {
+            // A set C_MASKINTS must be reset to false when we run() again.
+            dhcsr.set_c_maskints(mask_interrupts);
+            dhcsr.enable_write();
+            self.memory
+                .write_word_32(Dhcsr::get_mmio_address(), dhcsr.into())?;
+            self.memory.flush()?;
+        }
+
+        // Leave halted state.
+        // Step one instruction.
+        dhcsr.set_c_step(true);
+        dhcsr.set_c_halt(false);
+        dhcsr.enable_write();
+        self.memory
+            .write_word_32(Dhcsr::get_mmio_address(), dhcsr.into())?;
+        self.memory.flush()?;
+
+        self.wait_for_core_halted(Duration::from_millis(100))?;
+
+        // Try to read the new program counter.
+        let mut pc_after_step = self.read_core_reg(self.program_counter().into())?;
+
+        // Re-enable breakpoints before we continue.
+        if was_breakpoint {
+            // If we were stopped on a software breakpoint, then we need to manually advance the PC, or else we will be stuck here forever.
+            if pc_before_step == pc_after_step
+                && !self
+                    .hw_breakpoints()?
+                    .contains(&pc_before_step.try_into().ok())
+            {
+                tracing::debug!("Encountered a breakpoint instruction @ {}. We need to manually advance the program counter to the next instruction.", pc_after_step);
+                // Advance the program counter by the architecture specific byte size of the BKPT instruction.
+                pc_after_step.increment_address(2)?;
+                self.write_core_reg(self.program_counter().into(), pc_after_step)?;
+            }
+            self.enable_breakpoints(true)?;
+        }
+
+        Ok(CoreInformation {
+            pc: pc_after_step.try_into()?,
+        })
+    }
 }
 
 impl<'probe> CoreInterface for Armv7m<'probe> {
@@ -669,6 +735,7 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
 
     fn status(&mut self) -> Result<CoreStatus, Error> {
         let dhcsr = Dhcsr(self.memory.read_word_32(Dhcsr::get_mmio_address())?);
+        self.state.observe_reset_flag(dhcsr.s_reset_st());
 
         if dhcsr.s_lockup() {
             tracing::error!(
@@ -827,64 +894,12 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
     }
 
     fn step(&mut self) -> Result<CoreInformation, Error> {
-        // First check if we stopped on a breakpoint, because this requires special handling before we can continue.
-        let pc_before_step = self.read_core_reg(self.program_counter().into())?;
-        let was_breakpoint = if matches!(
-            self.state.current_state,
-            CoreStatus::Halted(HaltReason::Breakpoint(_))
-        ) {
-            self.enable_breakpoints(false)?;
-            true
-        } else {
-            false
-        };
-
-        let mut dhcsr = Dhcsr(self.memory.read_word_32(Dhcsr::get_mmio_address())?);
-
-        // Follow the rules of the ... ARMv7-M Architecture reference, C1.6 Debug System Registers - DHCSR, with respect to setting maskints
-        if !dhcsr.c_debugen() {
-            tracing::warn!("Attempting to STEP while DHCSR->C_DEBUGEN is false");
-        }
-        if !dhcsr.c_maskints() {
-            dhcsr.set_c_maskints(true); // This must be reset to false when we run() again.
-            dhcsr.enable_write();
-            self.memory
-                .write_word_32(Dhcsr::get_mmio_address(), dhcsr.into())?;
-            self.memory.flush()?;
-        }
-
-        // Leave halted state.
-        // Step one instruction.
-        dhcsr.set_c_step(true);
-        dhcsr.set_c_halt(false);
-        dhcsr.enable_write();
-        self.memory
-            .write_word_32(Dhcsr::get_mmio_address(), dhcsr.into())?;
-        self.memory.flush()?;
-
-        self.wait_for_core_halted(Duration::from_millis(100))?;
-
-        // Try to read the new program counter.
-        let mut pc_after_step = self.read_core_reg(self.program_counter().into())?;
-
-        // Re-enable breakpoints before we continue.
-        if was_breakpoint {
-            // If we were stopped on a software breakpoint, then we need to manually advance the PC, or else we will be stuck here forever.
-            if pc_before_step == pc_after_step
-                && !self
-                    .hw_breakpoints()?
-                    .contains(&pc_before_step.try_into().ok())
-            {
-                tracing::debug!("Encountered a breakpoint instruction @ {}. We need to manually advance the program counter to the next instruction.", pc_after_step);
-                // Advance the program counter by the architecture specific byte size of the BKPT instruction.
-                pc_after_step.increment_address(2)?;
-                self.write_core_reg(self.program_counter().into(), pc_after_step)?;
-            }
-            self.enable_breakpoints(true)?;
-        }
+        self.step_once(true)
+    }
 
-        Ok(CoreInformation {
-            pc: pc_after_step.try_into()?,
+    fn step_with_options(&mut self, options: StepOptions) -> Result<CoreInformation, Error> {
+        step_repeatedly(self, options, |core| {
+            core.step_once(options.mask_interrupts())
         })
     }
 
@@ -1011,6 +1026,35 @@ impl<'probe> CoreInterface for Armv7m<'probe> {
         Ok(())
     }
 
+    fn take_reset_cause(&mut self) -> Result<Option<ResetCause>, Error> {
+        Ok(self.state.take_reset_cause())
+    }
+
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        super::cortex_m::available_watchpoint_units(&mut *self.memory)
+    }
+
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, Error> {
+        super::cortex_m::hw_watchpoints(&mut *self.memory, DwtVersion::V1)
+    }
+
+    fn set_hw_watchpoint(
+        &mut self,
+        unit_index: usize,
+        watchpoint: Watchpoint,
+    ) -> Result<(), Error> {
+        super::cortex_m::set_hw_watchpoint(
+            &mut *self.memory,
+            DwtVersion::V1,
+            unit_index,
+            watchpoint,
+        )
+    }
+
+    fn clear_hw_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        super::cortex_m::clear_hw_watchpoint(&mut *self.memory, unit_index)
+    }
+
     fn registers(&self) -> &'static CoreRegisters {
         if self.state.fp_present {
             &CORTEX_M_WITH_FP_CORE_REGISTERS
@@ -1223,6 +1267,10 @@ impl<'probe> MemoryInterface for Armv7m<'probe> {
     fn flush(&mut self) -> Result<(), Error> {
         self.memory.flush().map_err(From::<ArmError>::from)
     }
+
+    fn code_barrier(&mut self, range: Range<u64>) -> Result<(), Error> {
+        super::cortex_m::code_barrier(&mut *self.memory, range)
+    }
 }
 
 #[test]
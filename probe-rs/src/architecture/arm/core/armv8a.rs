@@ -3,7 +3,10 @@
 use super::{
     instructions::{
         aarch64,
-        thumb2::{build_ldr, build_mcr, build_mrc, build_str, build_vmov, build_vmrs},
+        thumb2::{
+            build_dsb, build_isb, build_ldr, build_mcr, build_mrc, build_str, build_vmov,
+            build_vmrs,
+        },
     },
     registers::{aarch32::AARCH32_WITH_FP_32_CORE_REGSISTERS, aarch64::AARCH64_CORE_REGSISTERS},
     CortexAState,
@@ -23,6 +26,7 @@ use crate::{
 };
 use anyhow::Result;
 use std::{
+    ops::Range,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -43,6 +47,35 @@ pub enum Armv8aError {
     DataAbort,
 }
 
+/// Ungates the halt channel of the CTI at `cti_address`, so that halt requests on the channel
+/// are passed between the core and the cross trigger matrix.
+///
+/// Halting the core gates the channel again.
+pub(crate) fn ungate_halt_channel(
+    memory: &mut dyn ArmProbe,
+    cti_address: u64,
+) -> Result<(), Error> {
+    let mut cti_gate = CtiGate(0);
+    cti_gate.set_en(0, 1);
+
+    let address = CtiGate::get_mmio_address_from_base(cti_address)?;
+    memory.write_word_32(address, cti_gate.into())?;
+
+    Ok(())
+}
+
+/// Requests a halt on the halt channel of the CTI at `cti_address`, which also halts the cores
+/// of all other CTIs with an ungated halt channel.
+pub(crate) fn pulse_halt_channel(memory: &mut dyn ArmProbe, cti_address: u64) -> Result<(), Error> {
+    let mut pulse = CtiApppulse(0);
+    pulse.set_apppulse(0, 1);
+
+    let address = CtiApppulse::get_mmio_address_from_base(cti_address)?;
+    memory.write_word_32(address, pulse.into())?;
+
+    Ok(())
+}
+
 /// When in 32-bit mode the two words have to be placed in swapped
 fn prep_instr_for_itr_32(instruction: u32) -> u32 {
     ((instruction & 0xFFFF) << 16) | ((instruction & 0xFFFF_0000) >> 16)
@@ -722,6 +755,64 @@ impl<'probe> Armv8a<'probe> {
     fn set_core_status(&mut self, new_status: CoreStatus) {
         super::update_core_status(&mut self.memory, &mut self.state.current_state, new_status);
     }
+
+    /// Cleans the data cache lines covering `range` to the point of unification, and
+    /// invalidates the instruction cache.
+    fn sync_instruction_cache_aarch32(&mut self, range: Range<u64>) -> Result<(), Error> {
+        // Save r0
+        self.prepare_for_clobber(0)?;
+
+        // MRC p15, 0, r0, c0, c0, 1 ; Read CTR
+        self.execute_instruction(build_mrc(15, 0, 0, 0, 0, 1))?;
+        // MCR p14, 0, r0, c0, c5, 0 ; Write DBGDTRTXint Register
+        let ctr = self.execute_instruction_with_result_32(build_mcr(14, 0, 0, 0, 5, 0))?;
+        let line_size = 4u64 << ((ctr >> 16) & 0xF);
+
+        let start = range.start & !(line_size - 1);
+        for line in (start..range.end).step_by(line_size as usize) {
+            self.set_reg_value(0, line)?;
+
+            // MCR p15, 0, r0, c7, c11, 1 ; DCCMVAU
+            self.execute_instruction(build_mcr(15, 0, 0, 7, 11, 1))?;
+        }
+        self.execute_instruction(build_dsb())?;
+
+        // MCR p15, 0, r0, c7, c5, 0 ; ICIALLU
+        self.execute_instruction(build_mcr(15, 0, 0, 7, 5, 0))?;
+        self.execute_instruction(build_dsb())?;
+        self.execute_instruction(build_isb())?;
+
+        Ok(())
+    }
+
+    /// Cleans the data cache lines covering `range` to the point of unification, and
+    /// invalidates the instruction cache.
+    fn sync_instruction_cache_aarch64(&mut self, range: Range<u64>) -> Result<(), Error> {
+        // Save x0
+        self.prepare_for_clobber(0)?;
+
+        // MRS X0, CTR_EL0
+        self.execute_instruction(aarch64::build_mrs(3, 3, 0, 0, 1, 0))?;
+        // MSR DBGDTRTX_EL0, X0
+        let ctr = self.execute_instruction_with_result_32(aarch64::build_msr(2, 3, 0, 5, 0, 0))?;
+        let line_size = 4u64 << ((ctr >> 16) & 0xF);
+
+        let start = range.start & !(line_size - 1);
+        for line in (start..range.end).step_by(line_size as usize) {
+            self.set_reg_value(0, line)?;
+
+            // DC CVAU, X0
+            self.execute_instruction(aarch64::build_sys(3, 7, 11, 1, 0))?;
+        }
+        self.execute_instruction(aarch64::build_dsb())?;
+
+        // IC IALLU
+        self.execute_instruction(aarch64::build_sys(0, 7, 5, 0, 31))?;
+        self.execute_instruction(aarch64::build_dsb())?;
+        self.execute_instruction(aarch64::build_isb())?;
+
+        Ok(())
+    }
 }
 
 impl<'probe> CoreInterface for Armv8a<'probe> {
@@ -773,19 +864,8 @@ impl<'probe> CoreInterface for Armv8a<'probe> {
 
     fn halt(&mut self, timeout: Duration) -> Result<CoreInformation, Error> {
         if !matches!(self.state.current_state, CoreStatus::Halted(_)) {
-            // Ungate halt CTI channel
-            let mut cti_gate = CtiGate(0);
-            cti_gate.set_en(0, 1);
-
-            let address = CtiGate::get_mmio_address_from_base(self.cti_address)?;
-            self.memory.write_word_32(address, cti_gate.into())?;
-
-            // Pulse it
-            let mut pulse = CtiApppulse(0);
-            pulse.set_apppulse(0, 1);
-
-            let address = CtiApppulse::get_mmio_address_from_base(self.cti_address)?;
-            self.memory.write_word_32(address, pulse.into())?;
+            ungate_halt_channel(&mut *self.memory, self.cti_address)?;
+            pulse_halt_channel(&mut *self.memory, self.cti_address)?;
 
             // Wait for halt
             self.wait_for_core_halted(timeout)?;
@@ -1296,6 +1376,18 @@ impl<'probe> MemoryInterface for Armv8a<'probe> {
         // Nothing to do - this runs through the CPU which automatically handles any caching
         Ok(())
     }
+
+    fn code_barrier(&mut self, range: Range<u64>) -> Result<(), Error> {
+        if range.is_empty() {
+            return Ok(());
+        }
+
+        if self.state.is_64_bit {
+            self.sync_instruction_cache_aarch64(range)
+        } else {
+            self.sync_instruction_cache_aarch32(range)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,9 @@
 //! Debug register definitions for ARMv8-A
 
-use crate::{core::BreakpointCause, memory_mapped_bitfield_register, HaltReason};
+use crate::{
+    core::{BreakpointCause, ExceptionCause},
+    memory_mapped_bitfield_register, HaltReason,
+};
 
 memory_mapped_bitfield_register! {
     /// EDSCR - Debug Status and Control Register
@@ -128,17 +131,17 @@ impl Edscr {
             0b011111 => HaltReason::Step,
             0b111011 => HaltReason::Step,
             // OS Unlock catch.
-            0b100011 => HaltReason::Exception,
+            0b100011 => HaltReason::Exception(ExceptionCause::Unknown),
             // Reset catch.
-            0b100111 => HaltReason::Exception,
+            0b100111 => HaltReason::Exception(ExceptionCause::Unknown),
             // Watchpoint
-            0b101011 => HaltReason::Watchpoint,
+            0b101011 => HaltReason::Watchpoint(None),
             // HLT instruction - causes entry into Debug state.
             0b101111 => HaltReason::Breakpoint(BreakpointCause::Software),
             // Software access to debug register.
-            0b110011 => HaltReason::Exception,
+            0b110011 => HaltReason::Exception(ExceptionCause::Unknown),
             // Exception Catch.
-            0b110111 => HaltReason::Exception,
+            0b110111 => HaltReason::Exception(ExceptionCause::Unknown),
             // All other values are reserved or running
             _ => HaltReason::Unknown,
         }
@@ -1,7 +1,7 @@
 //! Register types and the core interface for armv8-M
 
 use super::{
-    cortex_m::{IdPfr1, Mvfr0},
+    cortex_m::{DwtVersion, IdPfr1, Mvfr0},
     registers::cortex_m::{
         CORTEX_M_CORE_REGISTERS, CORTEX_M_WITH_FP_CORE_REGISTERS, FP, PC, RA, SP,
     },
@@ -12,7 +12,10 @@ use crate::{
         core::registers::cortex_m::XPSR, memory::adi_v5_memory_interface::ArmProbe,
         sequences::ArmDebugSequence, ArmError,
     },
-    core::{CoreRegisters, RegisterId, RegisterValue, VectorCatchCondition},
+    core::{
+        step_repeatedly, CoreRegisters, RegisterId, RegisterValue, ResetCause, StepOptions,
+        VectorCatchCondition, Watchpoint,
+    },
     error::Error,
     memory::valid_32bit_address,
     Architecture, CoreInformation, CoreInterface, CoreRegister, CoreStatus, CoreType, HaltReason,
@@ -22,6 +25,7 @@ use anyhow::Result;
 use bitfield::bitfield;
 use std::{
     mem::size_of,
+    ops::Range,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -87,6 +91,59 @@ impl<'probe> Armv8m<'probe> {
     fn set_core_status(&mut self, new_status: CoreStatus) {
         super::update_core_status(&mut self.memory, &mut self.state.current_state, new_status);
     }
+
+    /// Steps one instruction, masking interrupts during the step if `mask_interrupts` is set.
+    fn step_once(&mut self, mask_interrupts: bool) -> Result<CoreInformation, Error> {
+        // First check if we stopped on a breakpoint, because this requires special handling before we can continue.
+        let pc_before_step = self.read_core_reg(self.program_counter().into())?;
+        let was_breakpoint = if matches!(
+            self.state.current_state,
+            CoreStatus::Halted(HaltReason::Breakpoint(_))
+        ) {
+            self.enable_breakpoints(false)?;
+            true
+        } else {
+            false
+        };
+
+        let mut value = Dhcsr(0);
+        // Leave halted state.
+        // Step one instruction.
+        value.set_c_step(true);
+        value.set_c_halt(false);
+        value.set_c_debugen(true);
+        value.set_c_maskints(mask_interrupts);
+        value.enable_write();
+
+        self.memory
+            .write_word_32(Dhcsr::get_mmio_address(), value.into())?;
+        self.memory.flush()?;
+
+        self.wait_for_core_halted(Duration::from_millis(100))?;
+
+        // Try to read the new program counter.
+        let mut pc_after_step = self.read_core_reg(self.program_counter().into())?;
+
+        // Re-enable breakpoints before we continue.
+        if was_breakpoint {
+            // If we were stopped on a software breakpoint, then we need to manually advance the PC, or else we will be stuck here forever.
+            if pc_before_step == pc_after_step
+                && !self
+                    .hw_breakpoints()?
+                    .contains(&pc_before_step.try_into().ok())
+            {
+                tracing::debug!("Encountered a breakpoint instruction @ {}. We need to manually advance the program counter to the next instruction.", pc_after_step);
+                // Advance the program counter by the architecture specific byte size of the BKPT instruction.
+                pc_after_step.increment_address(2)?;
+                self.write_core_reg(self.program_counter().into(), pc_after_step)?;
+            }
+            self.enable_breakpoints(true)?;
+        }
+
+        Ok(CoreInformation {
+            pc: pc_after_step.try_into()?,
+        })
+    }
 }
 
 impl<'probe> CoreInterface for Armv8m<'probe> {
@@ -111,6 +168,7 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
 
     fn status(&mut self) -> Result<crate::core::CoreStatus, Error> {
         let dhcsr = Dhcsr(self.memory.read_word_32(Dhcsr::get_mmio_address())?);
+        self.state.observe_reset_flag(dhcsr.s_reset_st());
 
         if dhcsr.s_lockup() {
             tracing::warn!(
@@ -261,54 +319,12 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
     }
 
     fn step(&mut self) -> Result<CoreInformation, Error> {
-        // First check if we stopped on a breakpoint, because this requires special handling before we can continue.
-        let pc_before_step = self.read_core_reg(self.program_counter().into())?;
-        let was_breakpoint = if matches!(
-            self.state.current_state,
-            CoreStatus::Halted(HaltReason::Breakpoint(_))
-        ) {
-            self.enable_breakpoints(false)?;
-            true
-        } else {
-            false
-        };
-
-        let mut value = Dhcsr(0);
-        // Leave halted state.
-        // Step one instruction.
-        value.set_c_step(true);
-        value.set_c_halt(false);
-        value.set_c_debugen(true);
-        value.set_c_maskints(true);
-        value.enable_write();
-
-        self.memory
-            .write_word_32(Dhcsr::get_mmio_address(), value.into())?;
-        self.memory.flush()?;
-
-        self.wait_for_core_halted(Duration::from_millis(100))?;
-
-        // Try to read the new program counter.
-        let mut pc_after_step = self.read_core_reg(self.program_counter().into())?;
-
-        // Re-enable breakpoints before we continue.
-        if was_breakpoint {
-            // If we were stopped on a software breakpoint, then we need to manually advance the PC, or else we will be stuck here forever.
-            if pc_before_step == pc_after_step
-                && !self
-                    .hw_breakpoints()?
-                    .contains(&pc_before_step.try_into().ok())
-            {
-                tracing::debug!("Encountered a breakpoint instruction @ {}. We need to manually advance the program counter to the next instruction.", pc_after_step);
-                // Advance the program counter by the architecture specific byte size of the BKPT instruction.
-                pc_after_step.increment_address(2)?;
-                self.write_core_reg(self.program_counter().into(), pc_after_step)?;
-            }
-            self.enable_breakpoints(true)?;
-        }
+        self.step_once(true)
+    }
 
-        Ok(CoreInformation {
-            pc: pc_after_step.try_into()?,
+    fn step_with_options(&mut self, options: StepOptions) -> Result<CoreInformation, Error> {
+        step_repeatedly(self, options, |core| {
+            core.step_once(options.mask_interrupts())
         })
     }
 
@@ -400,6 +416,35 @@ impl<'probe> CoreInterface for Armv8m<'probe> {
         Ok(())
     }
 
+    fn take_reset_cause(&mut self) -> Result<Option<ResetCause>, Error> {
+        Ok(self.state.take_reset_cause())
+    }
+
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        super::cortex_m::available_watchpoint_units(&mut *self.memory)
+    }
+
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, Error> {
+        super::cortex_m::hw_watchpoints(&mut *self.memory, DwtVersion::V2)
+    }
+
+    fn set_hw_watchpoint(
+        &mut self,
+        unit_index: usize,
+        watchpoint: Watchpoint,
+    ) -> Result<(), Error> {
+        super::cortex_m::set_hw_watchpoint(
+            &mut *self.memory,
+            DwtVersion::V2,
+            unit_index,
+            watchpoint,
+        )
+    }
+
+    fn clear_hw_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        super::cortex_m::clear_hw_watchpoint(&mut *self.memory, unit_index)
+    }
+
     fn registers(&self) -> &'static CoreRegisters {
         if self.state.fp_present {
             &CORTEX_M_WITH_FP_CORE_REGISTERS
@@ -625,6 +670,10 @@ impl<'probe> MemoryInterface for Armv8m<'probe> {
     fn flush(&mut self) -> Result<(), Error> {
         self.memory.flush().map_err(From::<ArmError>::from)
     }
+
+    fn code_barrier(&mut self, range: Range<u64>) -> Result<(), Error> {
+        super::cortex_m::code_barrier(&mut *self.memory, range)
+    }
 }
 
 bitfield! {
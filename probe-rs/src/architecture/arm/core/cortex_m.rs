@@ -3,10 +3,15 @@
 use crate::{
     architecture::arm::{memory::adi_v5_memory_interface::ArmProbe, ArmError},
     core::RegisterId,
+    memory::valid_32bit_address,
     memory_mapped_bitfield_register, BreakpointCause, CoreInterface, Error, HaltReason,
-    MemoryMappedRegister,
+    MemoryMappedRegister, Watchpoint, WatchpointKind,
+};
+use anyhow::anyhow;
+use std::{
+    ops::Range,
+    time::{Duration, Instant},
 };
-use std::time::{Duration, Instant};
 
 memory_mapped_bitfield_register! {
     pub struct Dhcsr(u32);
@@ -66,6 +71,32 @@ impl Cpacr {
     }
 }
 
+memory_mapped_bitfield_register! {
+    /// Configuration and Control Register
+    pub struct Ccr(u32);
+    0xE000_ED14, "CCR",
+    impl From;
+    /// Whether the instruction cache is enabled.
+    pub ic, _: 17;
+    /// Whether the data cache is enabled.
+    pub dc, _: 16;
+}
+
+memory_mapped_bitfield_register! {
+    /// Cache Type Register
+    pub struct Ctr(u32);
+    0xE000_ED7C, "CTR",
+    impl From;
+    /// Log2 of the number of words in the smallest data cache line.
+    pub dminline, _: 19, 16;
+}
+
+/// ICIALLU: Instruction cache invalidate all to the Point of Unification.
+const ICIALLU: u64 = 0xE000_EF50;
+
+/// DCCMVAU: Data cache clean by address to the Point of Unification.
+const DCCMVAU: u64 = 0xE000_EF64;
+
 memory_mapped_bitfield_register! {
     ///  Media and VFP Feature Register 0
     pub struct Mvfr0(u32);
@@ -176,6 +207,38 @@ pub(crate) fn write_core_reg(
     Ok(())
 }
 
+/// Makes code written to `range` visible to instruction fetches, see
+/// [`MemoryInterface::code_barrier`](crate::MemoryInterface::code_barrier).
+///
+/// Cleans the data cache lines covering `range` and invalidates the instruction cache, if the
+/// caches are enabled. Leaving debug state synchronizes the pipeline, so no ISB is needed.
+pub(crate) fn code_barrier(memory: &mut dyn ArmProbe, range: Range<u64>) -> Result<(), Error> {
+    if range.is_empty() {
+        return Ok(());
+    }
+
+    // CCR.IC and CCR.DC are RAZ on cores without caches.
+    let ccr = Ccr(memory.read_word_32(Ccr::get_mmio_address())?);
+
+    if ccr.dc() {
+        let ctr = Ctr(memory.read_word_32(Ctr::get_mmio_address())?);
+        let line_size = 4u64 << ctr.dminline();
+
+        let start = range.start & !(line_size - 1);
+        for line in (start..range.end).step_by(line_size as usize) {
+            memory.write_word_32(DCCMVAU, valid_32bit_address(line)?)?;
+        }
+    }
+
+    if ccr.ic() {
+        memory.write_word_32(ICIALLU, 0)?;
+    }
+
+    memory.flush()?;
+
+    Ok(())
+}
+
 /// Check if the current breakpoint is a semihosting call. Does nothing unless feature rtt is enabled.
 ///
 /// Call this if you get some kind of breakpoint. Works on ARMv6-M, ARMv7-M and ARMv8-M.
@@ -215,13 +278,176 @@ pub(crate) fn check_for_semihosting(
             tracing::info!("Semihosting found pc={pc:#x} r0={r0:#x} r1={r1:#x}");
 
             reason = HaltReason::Breakpoint(BreakpointCause::Semihosting(
-                decode_semihosting_syscall(r0, r1),
+                decode_semihosting_syscall(core, r0, r1)?,
             ));
         }
     }
     Ok(reason)
 }
 
+/// Base address of the DWT on all Cortex-M cores.
+const DWT_BASE: u64 = 0xE000_1000;
+
+/// Address of the Debug Exception and Monitor Control Register.
+const DEMCR: u64 = 0xE000_EDFC;
+
+/// DEMCR bit which enables the DWT (called DWTENA on ARMv6-M).
+const DEMCR_TRCENA: u32 = 1 << 24;
+
+/// The two programming models of the DWT comparators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DwtVersion {
+    /// ARMv6-M and ARMv7-M: the watched range is described by `DWT_MASKn`.
+    V1,
+    /// ARMv8-M: the watched range is described by `DWT_FUNCTIONn.DATAVSIZE`.
+    V2,
+}
+
+fn dwt_comp_address(unit_index: usize) -> u64 {
+    DWT_BASE + 0x20 + 0x10 * unit_index as u64
+}
+
+fn dwt_mask_address(unit_index: usize) -> u64 {
+    dwt_comp_address(unit_index) + 0x4
+}
+
+fn dwt_function_address(unit_index: usize) -> u64 {
+    dwt_comp_address(unit_index) + 0x8
+}
+
+/// Makes sure the DWT is enabled, so that its registers can be accessed.
+fn enable_dwt(memory: &mut dyn ArmProbe) -> Result<(), Error> {
+    let demcr = memory.read_word_32(DEMCR)?;
+    if demcr & DEMCR_TRCENA == 0 {
+        memory.write_word_32(DEMCR, demcr | DEMCR_TRCENA)?;
+    }
+    Ok(())
+}
+
+/// Returns the number of DWT comparators, which are used as watchpoint units.
+pub(crate) fn available_watchpoint_units(memory: &mut dyn ArmProbe) -> Result<u32, Error> {
+    enable_dwt(memory)?;
+
+    // DWT_CTRL.NUMCOMP
+    Ok(memory.read_word_32(DWT_BASE)? >> 28)
+}
+
+/// Reads the watchpoints configured in the DWT comparators.
+pub(crate) fn hw_watchpoints(
+    memory: &mut dyn ArmProbe,
+    version: DwtVersion,
+) -> Result<Vec<Option<Watchpoint>>, Error> {
+    let num_units = available_watchpoint_units(memory)? as usize;
+
+    let mut watchpoints = Vec::with_capacity(num_units);
+    for unit_index in 0..num_units {
+        let function = memory.read_word_32(dwt_function_address(unit_index))?;
+
+        let (kind, length) = match version {
+            DwtVersion::V1 => {
+                let kind = match function & 0xF {
+                    0b0101 => Some(WatchpointKind::Read),
+                    0b0110 => Some(WatchpointKind::Write),
+                    0b0111 => Some(WatchpointKind::ReadWrite),
+                    _ => None,
+                };
+                let mask = memory.read_word_32(dwt_mask_address(unit_index))? & 0x1F;
+                (kind, 1 << mask)
+            }
+            DwtVersion::V2 => {
+                // Only comparators generating a debug event are watchpoints.
+                let is_debug_event = (function >> 4) & 0b11 == 0b01;
+                let kind = match function & 0xF {
+                    0b0100 if is_debug_event => Some(WatchpointKind::ReadWrite),
+                    0b0101 if is_debug_event => Some(WatchpointKind::Write),
+                    0b0110 if is_debug_event => Some(WatchpointKind::Read),
+                    _ => None,
+                };
+                (kind, 1 << ((function >> 10) & 0b11))
+            }
+        };
+
+        let watchpoint = match kind {
+            Some(kind) => Some(Watchpoint {
+                address: memory.read_word_32(dwt_comp_address(unit_index))? as u64,
+                length,
+                kind,
+            }),
+            None => None,
+        };
+        watchpoints.push(watchpoint);
+    }
+
+    Ok(watchpoints)
+}
+
+/// Configures DWT comparator `unit_index` as a watchpoint.
+pub(crate) fn set_hw_watchpoint(
+    memory: &mut dyn ArmProbe,
+    version: DwtVersion,
+    unit_index: usize,
+    watchpoint: Watchpoint,
+) -> Result<(), Error> {
+    let address = valid_32bit_address(watchpoint.address)?;
+
+    enable_dwt(memory)?;
+
+    // Disable the comparator while it is reconfigured.
+    memory.write_word_32(dwt_function_address(unit_index), 0)?;
+    memory.write_word_32(dwt_comp_address(unit_index), address)?;
+
+    let function = match version {
+        DwtVersion::V1 => {
+            watchpoint.check_mask_range(1 << 31)?;
+
+            // The number of supported mask bits is implementation defined, check that
+            // the requested one sticks.
+            let mask = watchpoint.length.trailing_zeros();
+            memory.write_word_32(dwt_mask_address(unit_index), mask)?;
+            let readback = memory.read_word_32(dwt_mask_address(unit_index))? & 0x1F;
+            if readback != mask {
+                return Err(Error::Other(anyhow!(
+                    "Watchpoint length {} is not supported by this core",
+                    watchpoint.length
+                )));
+            }
+
+            match watchpoint.kind {
+                WatchpointKind::Read => 0b0101,
+                WatchpointKind::Write => 0b0110,
+                WatchpointKind::ReadWrite => 0b0111,
+            }
+        }
+        DwtVersion::V2 => {
+            watchpoint.check_mask_range(4)?;
+
+            let datavsize = watchpoint.length.trailing_zeros();
+            let match_ = match watchpoint.kind {
+                WatchpointKind::ReadWrite => 0b0100,
+                WatchpointKind::Write => 0b0101,
+                WatchpointKind::Read => 0b0110,
+            };
+
+            // ACTION = 0b01: generate a debug event.
+            (datavsize << 10) | (0b01 << 4) | match_
+        }
+    };
+
+    memory.write_word_32(dwt_function_address(unit_index), function)?;
+
+    Ok(())
+}
+
+/// Disables DWT comparator `unit_index`.
+pub(crate) fn clear_hw_watchpoint(
+    memory: &mut dyn ArmProbe,
+    unit_index: usize,
+) -> Result<(), Error> {
+    memory.write_word_32(dwt_function_address(unit_index), 0)?;
+
+    Ok(())
+}
+
 fn wait_for_core_register_transfer(
     memory: &mut dyn ArmProbe,
     timeout: Duration,
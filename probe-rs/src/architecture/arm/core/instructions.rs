@@ -109,6 +109,16 @@ pub(crate) mod aarch32 {
         ret
     }
 
+    /// Build a DSB SY instruction
+    pub(crate) fn build_dsb() -> u32 {
+        0b1111_0101_0111_1111_1111_0000_0100_1111
+    }
+
+    /// Build an ISB SY instruction
+    pub(crate) fn build_isb() -> u32 {
+        0b1111_0101_0111_1111_1111_0000_0110_1111
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -211,6 +221,16 @@ pub(crate) mod thumb2 {
         ret
     }
 
+    /// Build a DSB SY instruction
+    pub(crate) fn build_dsb() -> u32 {
+        0b1111_0011_1011_1111_1000_1111_0100_1111
+    }
+
+    /// Build an ISB SY instruction
+    pub(crate) fn build_isb() -> u32 {
+        0b1111_0011_1011_1111_1000_1111_0110_1111
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -280,6 +300,29 @@ pub(crate) mod aarch64 {
         ret
     }
 
+    /// Build a SYS instruction, used for the cache maintenance operations
+    pub(crate) fn build_sys(op1: u8, crn: u8, crm: u8, op2: u8, reg: u16) -> u32 {
+        let mut ret = 0b1101_0101_0000_1000_0000_0000_0000_0000;
+
+        ret |= (op1 as u32) << 16;
+        ret |= (crn as u32) << 12;
+        ret |= (crm as u32) << 8;
+        ret |= (op2 as u32) << 5;
+        ret |= reg as u32;
+
+        ret
+    }
+
+    /// Build a DSB SY instruction
+    pub(crate) fn build_dsb() -> u32 {
+        0b1101_0101_0000_0011_0011_1111_1001_1111
+    }
+
+    /// Build an ISB SY instruction
+    pub(crate) fn build_isb() -> u32 {
+        0b1101_0101_0000_0011_0011_1111_1101_1111
+    }
+
     pub(crate) fn build_str(reg_target: u16, reg_source: u16, imm: u16) -> u32 {
         let mut ret = 0b1111_1000_0000_0000_0000_0100_0000_0000;
 
@@ -386,5 +429,14 @@ pub(crate) mod aarch64 {
             // MOV x3, v2.d[1]
             assert_eq!(0x4E183C43, instr);
         }
+
+        #[test]
+        fn gen_sys_instruction() {
+            // DC CVAU, x2
+            assert_eq!(0xD50B7B22, build_sys(3, 7, 11, 1, 2));
+
+            // IC IALLU
+            assert_eq!(0xD508751F, build_sys(0, 7, 5, 0, 31));
+        }
     }
 }
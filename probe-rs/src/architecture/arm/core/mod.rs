@@ -1,7 +1,7 @@
 //! The different ARM core implementations with all constants and custom handling.
 
 use crate::{
-    core::{BreakpointCause, RegisterValue},
+    core::{BreakpointCause, ExceptionCause, RegisterValue, ResetCause},
     memory_mapped_bitfield_register, CoreStatus, HaltReason,
 };
 
@@ -89,18 +89,18 @@ impl Dfsr {
             if self.bkpt() {
                 HaltReason::Breakpoint(BreakpointCause::Unknown)
             } else {
-                HaltReason::Multiple
+                HaltReason::Multiple(self.0)
             }
         } else if self.bkpt() {
             HaltReason::Breakpoint(BreakpointCause::Unknown)
         } else if self.external() {
             HaltReason::External
         } else if self.dwttrap() {
-            HaltReason::Watchpoint
+            HaltReason::Watchpoint(None)
         } else if self.halted() {
             HaltReason::Request
         } else if self.vcatch() {
-            HaltReason::Exception
+            HaltReason::Exception(ExceptionCause::Unknown)
         } else {
             // We check that exactly one bit is set, so we should hit one of the cases above.
             panic!("This should not happen. Please open a bug report.")
@@ -135,6 +135,11 @@ pub struct CortexMState {
     current_state: CoreStatus,
 
     fp_present: bool,
+
+    /// Whether DHCSR reported a reset which was not yet taken by
+    /// [`CoreInterface::take_reset_cause`](crate::CoreInterface::take_reset_cause). The flag in
+    /// DHCSR is cleared when it is read, so it is kept here.
+    reset_detected: bool,
 }
 
 impl CortexMState {
@@ -144,6 +149,7 @@ impl CortexMState {
             hw_breakpoints_enabled: false,
             current_state: CoreStatus::Unknown,
             fp_present: false,
+            reset_detected: false,
         }
     }
 
@@ -154,6 +160,16 @@ impl CortexMState {
     fn initialized(&self) -> bool {
         self.initialized
     }
+
+    /// Notes the S_RESET_ST flag of a DHCSR value, which is cleared by the read.
+    fn observe_reset_flag(&mut self, s_reset_st: bool) {
+        self.reset_detected |= s_reset_st;
+    }
+
+    /// Returns the cause of the reset observed since the last call, if any.
+    fn take_reset_cause(&mut self) -> Option<ResetCause> {
+        std::mem::take(&mut self.reset_detected).then_some(ResetCause::Core)
+    }
 }
 
 /// The state cache of a Cortex-A core.
@@ -232,193 +232,289 @@ pub static AARCH64_CORE_REGSISTERS_SET: &[CoreRegister] = &[
     CoreRegister {
         roles: &[RegisterRole::Core("v0"), RegisterRole::FloatingPoint],
         id: RegisterId(34),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v1"), RegisterRole::FloatingPoint],
         id: RegisterId(35),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v2"), RegisterRole::FloatingPoint],
         id: RegisterId(36),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v3"), RegisterRole::FloatingPoint],
         id: RegisterId(37),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v4"), RegisterRole::FloatingPoint],
         id: RegisterId(38),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v5"), RegisterRole::FloatingPoint],
         id: RegisterId(39),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v6"), RegisterRole::FloatingPoint],
         id: RegisterId(40),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v7"), RegisterRole::FloatingPoint],
         id: RegisterId(41),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v8"), RegisterRole::FloatingPoint],
         id: RegisterId(42),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v9"), RegisterRole::FloatingPoint],
         id: RegisterId(43),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v10"), RegisterRole::FloatingPoint],
         id: RegisterId(44),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v11"), RegisterRole::FloatingPoint],
         id: RegisterId(45),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v12"), RegisterRole::FloatingPoint],
         id: RegisterId(46),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v13"), RegisterRole::FloatingPoint],
         id: RegisterId(47),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v14"), RegisterRole::FloatingPoint],
         id: RegisterId(48),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v15"), RegisterRole::FloatingPoint],
         id: RegisterId(49),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v16"), RegisterRole::FloatingPoint],
         id: RegisterId(50),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v17"), RegisterRole::FloatingPoint],
         id: RegisterId(51),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v18"), RegisterRole::FloatingPoint],
         id: RegisterId(52),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v19"), RegisterRole::FloatingPoint],
         id: RegisterId(53),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v20"), RegisterRole::FloatingPoint],
         id: RegisterId(54),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v21"), RegisterRole::FloatingPoint],
         id: RegisterId(55),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v22"), RegisterRole::FloatingPoint],
         id: RegisterId(56),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v23"), RegisterRole::FloatingPoint],
         id: RegisterId(57),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v24"), RegisterRole::FloatingPoint],
         id: RegisterId(58),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v25"), RegisterRole::FloatingPoint],
         id: RegisterId(59),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v26"), RegisterRole::FloatingPoint],
         id: RegisterId(60),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v27"), RegisterRole::FloatingPoint],
         id: RegisterId(61),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v28"), RegisterRole::FloatingPoint],
         id: RegisterId(62),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v29"), RegisterRole::FloatingPoint],
         id: RegisterId(63),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v30"), RegisterRole::FloatingPoint],
         id: RegisterId(64),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
         roles: &[RegisterRole::Core("v31"), RegisterRole::FloatingPoint],
         id: RegisterId(65),
-        data_type: RegisterDataType::FloatingPoint(128),
+        data_type: RegisterDataType::Vector {
+            lane_bits: 64,
+            lanes: 2,
+        },
         unwind_rule: UnwindRule::Clear,
     },
     CoreRegister {
@@ -488,10 +488,12 @@ impl RiscvCommunicationInterface {
 
         self.state.num_harts = num_harts;
 
-        // Select hart 0 again
+        // Select hart 0 again, and acknowledge the resets before the connection, so that they
+        // are not reported as unexpected.
         let mut control = Dmcontrol(0);
         control.set_hartsel(0);
         control.set_dmactive(true);
+        control.set_ackhavereset(true);
 
         self.write_dm_register(control)?;
 
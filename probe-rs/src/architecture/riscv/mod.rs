@@ -5,7 +5,9 @@
 use self::registers::*;
 use crate::{
     core::{
-        Architecture, BreakpointCause, CoreInformation, CoreRegisters, RegisterId, RegisterValue,
+        step_repeatedly, Architecture, BreakpointCause, BreakpointUnits, CoreInformation,
+        CoreRegisters, ExceptionCause, RegisterId, RegisterValue, ResetCause, StepOptions,
+        Watchpoint, WatchpointKind,
     },
     memory::valid_32bit_address,
     memory_mapped_bitfield_register, CoreInterface, CoreRegister, CoreStatus, CoreType, Error,
@@ -116,6 +118,89 @@ impl<'probe> Riscv32<'probe> {
         }
     }
 
+    /// Returns the watchpoint configured in the currently selected trigger, if any.
+    fn selected_trigger_watchpoint(&mut self) -> Result<Option<Watchpoint>, crate::Error> {
+        let tdata1 = 0x7a1;
+        let tdata2 = 0x7a2;
+
+        let tdata_value = Mcontrol(self.read_csr(tdata1)?);
+
+        // The trigger must be active in at least a single mode
+        let trigger_any_mode_active = tdata_value.m() || tdata_value.s() || tdata_value.u();
+
+        let kind = match (tdata_value.load(), tdata_value.store()) {
+            (true, true) => WatchpointKind::ReadWrite,
+            (true, false) => WatchpointKind::Read,
+            (false, true) => WatchpointKind::Write,
+            (false, false) => return Ok(None),
+        };
+
+        // Only data address triggers which enter debug mode are watchpoints.
+        if tdata_value.type_() != 0b10
+            || tdata_value.action() != 1
+            || tdata_value.select()
+            || tdata_value.execute()
+            || !trigger_any_mode_active
+        {
+            return Ok(None);
+        }
+
+        let value = self.read_csr(tdata2)? as u64;
+
+        let (address, length) = match tdata_value.match_() {
+            // Exact address match
+            0 => (value, 1),
+            // NAPOT: the number of trailing ones encodes the size of the range.
+            1 => {
+                let length = 1 << (value.trailing_ones() + 1);
+                (value & !(length - 1), length)
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(Watchpoint {
+            address,
+            length,
+            kind,
+        }))
+    }
+
+    /// Checks if one of the watchpoint triggers has fired. Not all implementations
+    /// support the `hit` bit, in which case this returns `false`.
+    fn watchpoint_hit(&mut self) -> Result<bool, crate::Error> {
+        let tselect = 0x7a0;
+        let tdata1 = 0x7a1;
+
+        for unit_index in 0..self.available_breakpoint_units()? {
+            self.write_csr(tselect, unit_index)?;
+
+            let tdata_value = Mcontrol(self.read_csr(tdata1)?);
+            if tdata_value.hit() && self.selected_trigger_watchpoint()?.is_some() {
+                return Ok(true);
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Clears the `hit` bit of all triggers, so that the next halt is attributed correctly.
+    fn clear_trigger_hits(&mut self) -> Result<(), crate::Error> {
+        let tselect = 0x7a0;
+        let tdata1 = 0x7a1;
+
+        for unit_index in 0..self.available_breakpoint_units()? {
+            self.write_csr(tselect, unit_index)?;
+
+            let mut tdata_value = Mcontrol(self.read_csr(tdata1)?);
+            if tdata_value.type_() == 0b10 && tdata_value.hit() {
+                tdata_value.set_hit(false);
+                self.write_csr(tdata1, tdata_value.0)?;
+            }
+        }
+
+        Ok(())
+    }
+
     /// Check if the current breakpoint is a semihosting call. Does nothing unless feature rtt is enabled.
     fn check_for_semihosting(
         old_reason: HaltReason,
@@ -162,12 +247,161 @@ impl<'probe> Riscv32<'probe> {
                 tracing::info!("Semihosting found pc={pc:#x} a0={a0:#x} a1={a1:#x}");
 
                 reason = HaltReason::Breakpoint(BreakpointCause::Semihosting(
-                    decode_semihosting_syscall(a0, a1),
+                    decode_semihosting_syscall(core, a0, a1)?,
                 ));
             }
         }
         Ok(reason)
     }
+
+    /// Enumerates the triggers of the core, returning their number.
+    fn enumerate_triggers(&mut self) -> Result<u32, crate::Error> {
+        tracing::debug!("Determining number of HW breakpoints supported");
+
+        let tselect = 0x7a0;
+        let tdata1 = 0x7a1;
+        let tinfo = 0x7a4;
+
+        let mut tselect_index = 0;
+
+        // These steps follow the debug specification 0.13, section 5.1 Enumeration
+        loop {
+            tracing::debug!("Trying tselect={}", tselect_index);
+            if let Err(e) = self.write_csr(tselect, tselect_index) {
+                match e {
+                    RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception) => break,
+                    other_error => return Err(other_error.into()),
+                }
+            }
+
+            let readback = self.read_csr(tselect)?;
+
+            if readback != tselect_index {
+                break;
+            }
+
+            match self.read_csr(tinfo) {
+                Ok(tinfo_val) => {
+                    if tinfo_val & 0xffff == 1 {
+                        // Trigger doesn't exist, break the loop
+                        break;
+                    } else {
+                        tracing::info!(
+                            "Discovered trigger with index {} and type {}",
+                            tselect_index,
+                            tinfo_val & 0xffff
+                        );
+                    }
+                }
+                Err(RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception)) => {
+                    // An exception means we have to read tdata1 to discover the type
+                    let tdata_val = self.read_csr(tdata1)?;
+
+                    // Read the mxl field from the misa register (see RISC-V Privileged Spec, 3.1.1)
+                    let misa_value = Misa(self.read_csr(0x301)?);
+                    let xlen = u32::pow(2, misa_value.mxl() + 4);
+
+                    let trigger_type = tdata_val >> (xlen - 4);
+
+                    if trigger_type == 0 {
+                        break;
+                    }
+
+                    tracing::info!(
+                        "Discovered trigger with index {} and type {}",
+                        tselect_index,
+                        trigger_type,
+                    );
+                }
+                Err(other) => return Err(other.into()),
+            }
+
+            tselect_index += 1;
+        }
+
+        tracing::debug!("Target supports {} breakpoints.", tselect_index);
+
+        Ok(tselect_index)
+    }
+
+    /// Returns the bookkeeping of the breakpoint triggers, enumerating them on first use.
+    fn breakpoint_units(&mut self) -> Result<&mut BreakpointUnits, crate::Error> {
+        if self.state.breakpoints.is_none() {
+            let count = self.enumerate_triggers()?;
+            self.state.breakpoints = Some(BreakpointUnits::new(count as usize));
+        }
+
+        Ok(self.state.breakpoints.as_mut().unwrap())
+    }
+
+    /// Steps one instruction, masking interrupts during the step if `mask_interrupts` is set.
+    fn step_once(
+        &mut self,
+        mask_interrupts: bool,
+    ) -> Result<crate::core::CoreInformation, crate::Error> {
+        let halt_reason = self.status()?;
+        let flashing_done = self.hw_breakpoints_enabled();
+        if matches!(
+            halt_reason,
+            CoreStatus::Halted(HaltReason::Breakpoint(
+                BreakpointCause::Software | BreakpointCause::Semihosting(_)
+            ))
+        ) && flashing_done
+        {
+            // If we are halted on a software breakpoint AND we have passed the flashing operation, we can skip the single step and manually advance the dpc.
+            let mut debug_pc = self.read_core_reg(RegisterId(0x7b1))?;
+            // Advance the dpc by the size of the EBREAK (ebreak or c.ebreak) instruction.
+            if matches!(self.instruction_set()?, InstructionSet::RV32C) {
+                debug_pc.increment_address(2)?;
+            } else {
+                debug_pc.increment_address(4)?;
+            }
+
+            self.write_core_reg(RegisterId(0x7b1), debug_pc)?;
+            return Ok(CoreInformation {
+                pc: debug_pc.try_into()?,
+            });
+        } else if matches!(
+            halt_reason,
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
+        ) {
+            // If we are halted on a hardware breakpoint.
+            self.enable_breakpoints(false)?;
+        }
+
+        let mut dcsr = Dcsr(self.read_core_reg(RegisterId(0x7b0))?.try_into()?);
+        // Set it up, so that the next `self.run()` will only do a single step
+        dcsr.set_step(true);
+        // Disable any interrupts during single step, unless they may be taken.
+        dcsr.set_stepie(!mask_interrupts);
+        dcsr.set_stopcount(true);
+        self.write_csr(0x7b0, dcsr.0)?;
+
+        // Now we can resume the core for the single step.
+        self.resume_core()?;
+        self.wait_for_core_halted(Duration::from_millis(100))?;
+
+        let pc = self.read_core_reg(RegisterId(0x7b1))?;
+
+        // clear step request
+        let mut dcsr = Dcsr(self.read_core_reg(RegisterId(0x7b0))?.try_into()?);
+        dcsr.set_step(false);
+        //Re-enable interrupts for single step.
+        dcsr.set_stepie(true);
+        dcsr.set_stopcount(false);
+        self.write_csr(0x7b0, dcsr.0)?;
+
+        // Re-enable breakpoints before we continue.
+        if matches!(
+            halt_reason,
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
+        ) {
+            // If we are halted on a hardware breakpoint.
+            self.enable_breakpoints(true)?;
+        }
+
+        Ok(CoreInformation { pc: pc.try_into()? })
+    }
 }
 
 impl<'probe> CoreInterface for Riscv32<'probe> {
@@ -211,13 +445,14 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
                     // TODO: Add testcase to probe-rs-debugger-test to validate semihosting exit/abort work and unknown semihosting operations are skipped
                 }
                 // Trigger module caused halt
+                2 if self.watchpoint_hit()? => HaltReason::Watchpoint(None),
                 2 => HaltReason::Breakpoint(BreakpointCause::Hardware),
                 // Debugger requested a halt
                 3 => HaltReason::Request,
                 // Core halted after single step
                 4 => HaltReason::Step,
                 // Core halted directly after reset
-                5 => HaltReason::Exception,
+                5 => HaltReason::Exception(ExceptionCause::Unknown),
                 // Reserved for future use in specification
                 _ => HaltReason::Unknown,
             };
@@ -271,6 +506,8 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         // Before we run, we always perform a single instruction step, to account for possible breakpoints that might get us stuck on the current instruction.
         self.step()?;
 
+        self.clear_trigger_hits()?;
+
         // resume the core.
         self.resume_core()?;
 
@@ -366,68 +603,16 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
     }
 
     fn step(&mut self) -> Result<crate::core::CoreInformation, crate::Error> {
-        let halt_reason = self.status()?;
-        let flashing_done = self.state.hw_breakpoints_enabled;
-        if matches!(
-            halt_reason,
-            CoreStatus::Halted(HaltReason::Breakpoint(
-                BreakpointCause::Software | BreakpointCause::Semihosting(_)
-            ))
-        ) && flashing_done
-        {
-            // If we are halted on a software breakpoint AND we have passed the flashing operation, we can skip the single step and manually advance the dpc.
-            let mut debug_pc = self.read_core_reg(RegisterId(0x7b1))?;
-            // Advance the dpc by the size of the EBREAK (ebreak or c.ebreak) instruction.
-            if matches!(self.instruction_set()?, InstructionSet::RV32C) {
-                debug_pc.increment_address(2)?;
-            } else {
-                debug_pc.increment_address(4)?;
-            }
-
-            self.write_core_reg(RegisterId(0x7b1), debug_pc)?;
-            return Ok(CoreInformation {
-                pc: debug_pc.try_into()?,
-            });
-        } else if matches!(
-            halt_reason,
-            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
-        ) {
-            // If we are halted on a hardware breakpoint.
-            self.enable_breakpoints(false)?;
-        }
-
-        let mut dcsr = Dcsr(self.read_core_reg(RegisterId(0x7b0))?.try_into()?);
-        // Set it up, so that the next `self.run()` will only do a single step
-        dcsr.set_step(true);
-        // Disable any interrupts during single step.
-        dcsr.set_stepie(false);
-        dcsr.set_stopcount(true);
-        self.write_csr(0x7b0, dcsr.0)?;
-
-        // Now we can resume the core for the single step.
-        self.resume_core()?;
-        self.wait_for_core_halted(Duration::from_millis(100))?;
-
-        let pc = self.read_core_reg(RegisterId(0x7b1))?;
-
-        // clear step request
-        let mut dcsr = Dcsr(self.read_core_reg(RegisterId(0x7b0))?.try_into()?);
-        dcsr.set_step(false);
-        //Re-enable interrupts for single step.
-        dcsr.set_stepie(true);
-        dcsr.set_stopcount(false);
-        self.write_csr(0x7b0, dcsr.0)?;
-
-        // Re-enable breakpoints before we continue.
-        if matches!(
-            halt_reason,
-            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Hardware))
-        ) {
-            // If we are halted on a hardware breakpoint.
-            self.enable_breakpoints(true)?;
-        }
+        self.step_once(true)
+    }
 
-        Ok(CoreInformation { pc: pc.try_into()? })
+    fn step_with_options(
+        &mut self,
+        options: StepOptions,
+    ) -> Result<crate::core::CoreInformation, crate::Error> {
+        step_repeatedly(self, options, |core| {
+            core.step_once(options.mask_interrupts())
+        })
     }
 
     fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, crate::Error> {
@@ -446,78 +631,11 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
     }
 
     fn available_breakpoint_units(&mut self) -> Result<u32, crate::Error> {
-        // TODO: This should probably only be done once, when initialising
-
-        tracing::debug!("Determining number of HW breakpoints supported");
-
-        let tselect = 0x7a0;
-        let tdata1 = 0x7a1;
-        let tinfo = 0x7a4;
-
-        let mut tselect_index = 0;
-
-        // These steps follow the debug specification 0.13, section 5.1 Enumeration
-        loop {
-            tracing::debug!("Trying tselect={}", tselect_index);
-            if let Err(e) = self.write_csr(tselect, tselect_index) {
-                match e {
-                    RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception) => break,
-                    other_error => return Err(other_error.into()),
-                }
-            }
-
-            let readback = self.read_csr(tselect)?;
-
-            if readback != tselect_index {
-                break;
-            }
-
-            match self.read_csr(tinfo) {
-                Ok(tinfo_val) => {
-                    if tinfo_val & 0xffff == 1 {
-                        // Trigger doesn't exist, break the loop
-                        break;
-                    } else {
-                        tracing::info!(
-                            "Discovered trigger with index {} and type {}",
-                            tselect_index,
-                            tinfo_val & 0xffff
-                        );
-                    }
-                }
-                Err(RiscvError::AbstractCommand(AbstractCommandErrorKind::Exception)) => {
-                    // An exception means we have to read tdata1 to discover the type
-                    let tdata_val = self.read_csr(tdata1)?;
-
-                    // Read the mxl field from the misa register (see RISC-V Privileged Spec, 3.1.1)
-                    let misa_value = Misa(self.read_csr(0x301)?);
-                    let xlen = u32::pow(2, misa_value.mxl() + 4);
-
-                    let trigger_type = tdata_val >> (xlen - 4);
-
-                    if trigger_type == 0 {
-                        break;
-                    }
-
-                    tracing::info!(
-                        "Discovered trigger with index {} and type {}",
-                        tselect_index,
-                        trigger_type,
-                    );
-                }
-                Err(other) => return Err(other.into()),
-            }
-
-            tselect_index += 1;
-        }
-
-        tracing::debug!("Target supports {} breakpoints.", tselect_index);
-
-        Ok(tselect_index)
+        Ok(self.breakpoint_units()?.len() as u32)
     }
 
     /// See docs on the [`CoreInterface::hw_breakpoints`] trait
-    /// NOTE: For riscv, this assumes that only execution breakpoints are used.
+    /// NOTE: Triggers used as watchpoints are reported by [`CoreInterface::hw_watchpoints`] instead.
     fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
         let tselect = 0x7a0;
         let tdata1 = 0x7a1;
@@ -537,15 +655,12 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             // The trigger must be active in at least a single mode
             let trigger_any_mode_active = tdata_value.m() || tdata_value.s() || tdata_value.u();
 
-            let trigger_any_action_enabled =
-                tdata_value.execute() || tdata_value.store() || tdata_value.load();
-
             // Only return if the trigger if it is for an execution debug action in all modes.
             if tdata_value.type_() == 0b10
                 && tdata_value.action() == 1
                 && tdata_value.match_() == 0
                 && trigger_any_mode_active
-                && trigger_any_action_enabled
+                && tdata_value.execute()
             {
                 let breakpoint = self.read_csr(tdata2)?;
                 breakpoints.push(Some(breakpoint as u64));
@@ -588,7 +703,7 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
             }
         }
 
-        self.state.hw_breakpoints_enabled = state;
+        self.breakpoint_units()?.set_enabled(state);
         Ok(())
     }
 
@@ -643,6 +758,8 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         self.write_csr(tdata1, instruction_breakpoint.0)?;
         self.write_csr(tdata2, addr)?;
 
+        self.breakpoint_units()?.set(bp_unit_index, addr as u64)?;
+
         Ok(())
     }
 
@@ -655,9 +772,121 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
         self.write_csr(tdata1, 0)?;
         self.write_csr(tdata2, 0)?;
 
+        self.breakpoint_units()?.clear(unit_index);
+
+        Ok(())
+    }
+
+    fn restore_breakpoints(&mut self) -> Result<(), crate::Error> {
+        // A reset clears the triggers, and disables halting on ebreak.
+        self.debug_on_sw_breakpoint(true)?;
+
+        let configured = match &self.state.breakpoints {
+            Some(units) => units.configured().collect::<Vec<_>>(),
+            None => return Ok(()),
+        };
+        for (unit, address) in configured {
+            self.set_hw_breakpoint(unit, address)?;
+        }
+
+        Ok(())
+    }
+
+    fn take_reset_cause(&mut self) -> Result<Option<ResetCause>, crate::Error> {
+        let status: Dmstatus = self.interface.read_dm_register()?;
+        if !status.anyhavereset() {
+            return Ok(None);
+        }
+
+        let mut dmcontrol = Dmcontrol(0);
+        dmcontrol.set_dmactive(true);
+        dmcontrol.set_ackhavereset(true);
+        self.interface.write_dm_register(dmcontrol)?;
+
+        Ok(Some(ResetCause::Core))
+    }
+
+    fn available_watchpoint_units(&mut self) -> Result<u32, crate::Error> {
+        self.available_breakpoint_units()
+    }
+
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, crate::Error> {
+        let tselect = 0x7a0;
+
+        let mut watchpoints = vec![];
+        for unit_index in 0..self.available_watchpoint_units()? {
+            self.write_csr(tselect, unit_index)?;
+            watchpoints.push(self.selected_trigger_watchpoint()?);
+        }
+
+        Ok(watchpoints)
+    }
+
+    fn set_hw_watchpoint(
+        &mut self,
+        unit_index: usize,
+        watchpoint: Watchpoint,
+    ) -> Result<(), crate::Error> {
+        watchpoint.check_mask_range(1 << 31)?;
+        let addr = valid_32bit_address(watchpoint.address)?;
+
+        let tselect = 0x7a0;
+        let tdata1 = 0x7a1;
+        let tdata2 = 0x7a2;
+
+        self.write_csr(tselect, unit_index as u32)?;
+
+        // verify the trigger has the correct type
+        let tdata_value = Mcontrol(self.read_csr(tdata1)?);
+        let trigger_type = tdata_value.type_();
+        if trigger_type != 0b10 {
+            return Err(RiscvError::UnexpectedTriggerType(trigger_type).into());
+        }
+
+        let mut data_watchpoint = Mcontrol(0);
+
+        // Enter debug mode
+        data_watchpoint.set_action(1);
+        data_watchpoint.set_dmode(true);
+        data_watchpoint.set_m(true);
+        data_watchpoint.set_u(true);
+
+        // Match the address of the access
+        data_watchpoint.set_select(false);
+
+        data_watchpoint.set_load(matches!(
+            watchpoint.kind,
+            WatchpointKind::Read | WatchpointKind::ReadWrite
+        ));
+        data_watchpoint.set_store(matches!(
+            watchpoint.kind,
+            WatchpointKind::Write | WatchpointKind::ReadWrite
+        ));
+
+        let tdata2_value = if watchpoint.length == 1 {
+            // Match exactly the value in tdata2
+            data_watchpoint.set_match(0);
+            addr
+        } else {
+            // Match a naturally aligned power-of-two range
+            data_watchpoint.set_match(1);
+            addr | ((watchpoint.length as u32 / 2) - 1)
+        };
+
+        self.write_csr(tdata1, data_watchpoint.0)?;
+        self.write_csr(tdata2, tdata2_value)?;
+
         Ok(())
     }
 
+    fn clear_hw_watchpoint(&mut self, unit_index: usize) -> Result<(), crate::Error> {
+        self.clear_hw_breakpoint(unit_index)
+    }
+
+    fn watchpoints_share_breakpoint_units(&self) -> bool {
+        true
+    }
+
     fn registers(&self) -> &'static CoreRegisters {
         &RISCV_CORE_REGSISTERS
     }
@@ -679,7 +908,10 @@ impl<'probe> CoreInterface for Riscv32<'probe> {
     }
 
     fn hw_breakpoints_enabled(&self) -> bool {
-        self.state.hw_breakpoints_enabled
+        self.state
+            .breakpoints
+            .as_ref()
+            .is_some_and(BreakpointUnits::is_enabled)
     }
 
     fn debug_on_sw_breakpoint(&mut self, enabled: bool) -> Result<(), crate::error::Error> {
@@ -836,8 +1068,9 @@ impl<'probe> MemoryInterface for Riscv32<'probe> {
 #[derive(Debug)]
 /// Flags used to control the [`SpecificCoreState`](crate::core::SpecificCoreState) for RiscV architecture
 pub struct RiscVState {
-    /// A flag to remember whether we want to use hw_breakpoints during stepping of the core.
-    hw_breakpoints_enabled: bool,
+    /// The breakpoint triggers, and whether we want to use them during stepping of the core.
+    /// `None` until the triggers are enumerated.
+    breakpoints: Option<BreakpointUnits>,
 
     /// Store the value of the `hasresethaltreq` bit of the `dmcstatus` register.
     hasresethaltreq: Option<bool>,
@@ -846,7 +1079,7 @@ pub struct RiscVState {
 impl RiscVState {
     pub(crate) fn new() -> Self {
         Self {
-            hw_breakpoints_enabled: false,
+            breakpoints: None,
             hasresethaltreq: None,
         }
     }
@@ -0,0 +1,473 @@
+//! Disassembly of Xtensa LX code, see [`Core::disassemble`](crate::Core::disassemble).
+//!
+//! The instructions of the core ISA and the Code Density, Windowed Register, Loop, Boolean,
+//! 32-bit Multiply, Integer Divide and Miscellaneous Operations Options are decoded. Everything
+//! else is shown as `.byte`.
+
+use super::instruction_length;
+use crate::architecture::xtensa::arch::{SpecialRegister, UserRegister};
+use crate::DisassembledInstruction;
+
+/// The constants of the BEQI, BNEI, BLTI and BGEI instructions.
+const B4CONST: [i32; 16] = [-1, 1, 2, 3, 4, 5, 6, 7, 8, 10, 12, 16, 32, 64, 128, 256];
+
+/// The constants of the BLTUI and BGEUI instructions.
+const B4CONSTU: [u32; 16] = [
+    32768, 65536, 2, 3, 4, 5, 6, 7, 8, 10, 12, 16, 32, 64, 128, 256,
+];
+
+/// Decodes the instructions in `code`, which was read from `address`. A partial instruction at
+/// the end of `code` is not decoded.
+pub fn disassemble(address: u64, code: &[u8], code_density: bool) -> Vec<DisassembledInstruction> {
+    let mut instructions = Vec::new();
+
+    let mut offset = 0;
+    while let Some(&first_byte) = code.get(offset) {
+        let length = instruction_length(first_byte, code_density);
+        let Some(bytes) = code.get(offset..offset + length) else {
+            break;
+        };
+
+        let pc = address + offset as u64;
+        let decoded = if length == 2 {
+            decode_narrow(pc, u16::from_le_bytes([bytes[0], bytes[1]]))
+        } else {
+            decode_wide(pc, u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]))
+        };
+        let decoded = decoded.unwrap_or_else(|| Decoded {
+            mnemonic: ".byte",
+            operands: bytes
+                .iter()
+                .map(|byte| format!("{byte:#04x}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+            target: None,
+        });
+
+        instructions.push(DisassembledInstruction {
+            address: pc,
+            bytes: bytes.to_vec(),
+            mnemonic: decoded.mnemonic.to_string(),
+            operands: decoded.operands,
+            target: decoded.target,
+        });
+        offset += length;
+    }
+
+    instructions
+}
+
+struct Decoded {
+    mnemonic: &'static str,
+    operands: String,
+    target: Option<u64>,
+}
+
+fn op(mnemonic: &'static str, operands: String) -> Option<Decoded> {
+    Some(Decoded {
+        mnemonic,
+        operands,
+        target: None,
+    })
+}
+
+/// An instruction which branches to, calls or loads from `target`, which is the last operand.
+fn to(mnemonic: &'static str, operands: String, target: u64) -> Option<Decoded> {
+    let operands = if operands.is_empty() {
+        format!("{target:#x}")
+    } else {
+        format!("{operands}, {target:#x}")
+    };
+
+    Some(Decoded {
+        mnemonic,
+        operands,
+        target: Some(target),
+    })
+}
+
+/// Sign-extends the `bits` wide `value`.
+fn sext(value: u32, bits: u32) -> i64 {
+    let shift = 32 - bits;
+    i64::from(((value << shift) as i32) >> shift)
+}
+
+fn offset(pc: u64, offset: i64) -> u64 {
+    pc.wrapping_add_signed(offset) & 0xFFFF_FFFF
+}
+
+fn special_register(number: u32) -> String {
+    match SpecialRegister::try_from(number as u8) {
+        Ok(register) => format!("{register:?}").to_lowercase(),
+        Err(_) => number.to_string(),
+    }
+}
+
+fn user_register(number: u32) -> String {
+    match UserRegister::try_from(number as u8) {
+        Ok(register) => format!("{register:?}").to_lowercase(),
+        Err(_) => number.to_string(),
+    }
+}
+
+fn decode_narrow(pc: u64, word: u16) -> Option<Decoded> {
+    let word = u32::from(word);
+    let t = (word >> 4) & 0xF;
+    let s = (word >> 8) & 0xF;
+    let r = (word >> 12) & 0xF;
+
+    match word & 0xF {
+        0x8 => op("l32i.n", format!("a{t}, a{s}, {}", r * 4)),
+        0x9 => op("s32i.n", format!("a{t}, a{s}, {}", r * 4)),
+        0xA => op("add.n", format!("a{r}, a{s}, a{t}")),
+        0xB => {
+            let immediate = if t == 0 { -1 } else { t as i32 };
+            op("addi.n", format!("a{r}, a{s}, {immediate}"))
+        }
+        0xC if t & 0x8 == 0 => {
+            // The immediate ranges from -32 to 95.
+            let immediate = ((t & 0x7) << 4 | r) as i32;
+            let immediate = if immediate >= 96 {
+                immediate - 128
+            } else {
+                immediate
+            };
+            op("movi.n", format!("a{s}, {immediate}"))
+        }
+        0xC => {
+            let mnemonic = if t & 0x4 == 0 { "beqz.n" } else { "bnez.n" };
+            let immediate = (t & 0x3) << 4 | r;
+            to(
+                mnemonic,
+                format!("a{s}"),
+                offset(pc, 4 + i64::from(immediate)),
+            )
+        }
+        0xD => match (r, s, t) {
+            (0x0, _, _) => op("mov.n", format!("a{t}, a{s}")),
+            (0xF, 0, 0) => op("ret.n", String::new()),
+            (0xF, 0, 1) => op("retw.n", String::new()),
+            (0xF, _, 2) => op("break.n", format!("{s}")),
+            (0xF, 0, 3) => op("nop.n", String::new()),
+            (0xF, 0, 6) => op("ill.n", String::new()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn decode_wide(pc: u64, word: u32) -> Option<Decoded> {
+    let t = (word >> 4) & 0xF;
+    let s = (word >> 8) & 0xF;
+    let r = (word >> 12) & 0xF;
+    let imm8 = (word >> 16) & 0xFF;
+
+    match word & 0xF {
+        0x0 => decode_qrst(word),
+        0x1 => {
+            // The offset is extended with ones, so literals are always below the instruction.
+            let literal = (0xFFFF_0000 | word >> 8) << 2;
+            let base = (pc + 3) & !3;
+            to(
+                "l32r",
+                format!("a{t}"),
+                offset(base, i64::from(literal as i32)),
+            )
+        }
+        0x2 => decode_lsai(word),
+        0x3 => {
+            let mnemonic = match r {
+                0x0 => "lsi",
+                0x4 => "ssi",
+                0x8 => "lsiu",
+                0xC => "ssiu",
+                _ => return None,
+            };
+            op(mnemonic, format!("f{t}, a{s}, {}", imm8 * 4))
+        }
+        0x5 => {
+            let mnemonic = ["call0", "call4", "call8", "call12"][(word >> 4) as usize & 0x3];
+            let target = offset(pc & !3, (sext(word >> 6, 18) << 2) + 4);
+            to(mnemonic, String::new(), target)
+        }
+        0x6 => decode_si(pc, word),
+        0x7 => {
+            let target = offset(pc, 4 + sext(imm8, 8));
+            let (mnemonic, operands) = match r {
+                0x0 => ("bnone", format!("a{s}, a{t}")),
+                0x1 => ("beq", format!("a{s}, a{t}")),
+                0x2 => ("blt", format!("a{s}, a{t}")),
+                0x3 => ("bltu", format!("a{s}, a{t}")),
+                0x4 => ("ball", format!("a{s}, a{t}")),
+                0x5 => ("bbc", format!("a{s}, a{t}")),
+                0x6 | 0x7 => ("bbci", format!("a{s}, {}", (r & 1) << 4 | t)),
+                0x8 => ("bany", format!("a{s}, a{t}")),
+                0x9 => ("bne", format!("a{s}, a{t}")),
+                0xA => ("bge", format!("a{s}, a{t}")),
+                0xB => ("bgeu", format!("a{s}, a{t}")),
+                0xC => ("bnall", format!("a{s}, a{t}")),
+                0xD => ("bbs", format!("a{s}, a{t}")),
+                _ => ("bbsi", format!("a{s}, {}", (r & 1) << 4 | t)),
+            };
+            to(mnemonic, operands, target)
+        }
+        _ => None,
+    }
+}
+
+fn decode_qrst(word: u32) -> Option<Decoded> {
+    let t = (word >> 4) & 0xF;
+    let s = (word >> 8) & 0xF;
+    let r = (word >> 12) & 0xF;
+    let op1 = (word >> 16) & 0xF;
+    let op2 = (word >> 20) & 0xF;
+
+    let rrr = |mnemonic| op(mnemonic, format!("a{r}, a{s}, a{t}"));
+
+    match (op1, op2) {
+        (0x0, 0x0) => decode_st0(word),
+        (0x0, 0x1) => rrr("and"),
+        (0x0, 0x2) if s == t => op("mov", format!("a{r}, a{s}")),
+        (0x0, 0x2) => rrr("or"),
+        (0x0, 0x3) => rrr("xor"),
+        (0x0, 0x4) => match r {
+            0x0 => op("ssr", format!("a{s}")),
+            0x1 => op("ssl", format!("a{s}")),
+            0x2 => op("ssa8l", format!("a{s}")),
+            0x3 => op("ssa8b", format!("a{s}")),
+            0x4 => op("ssai", format!("{}", (t & 1) << 4 | s)),
+            0x8 => op("rotw", format!("{}", sext(t, 4))),
+            0xE => op("nsa", format!("a{t}, a{s}")),
+            0xF => op("nsau", format!("a{t}, a{s}")),
+            _ => None,
+        },
+        (0x0, 0x6) if s == 0 => op("neg", format!("a{r}, a{t}")),
+        (0x0, 0x6) if s == 1 => op("abs", format!("a{r}, a{t}")),
+        (0x0, 0x8) => rrr("add"),
+        (0x0, 0x9) => rrr("addx2"),
+        (0x0, 0xA) => rrr("addx4"),
+        (0x0, 0xB) => rrr("addx8"),
+        (0x0, 0xC) => rrr("sub"),
+        (0x0, 0xD) => rrr("subx2"),
+        (0x0, 0xE) => rrr("subx4"),
+        (0x0, 0xF) => rrr("subx8"),
+
+        (0x1, 0x0 | 0x1) => op("slli", format!("a{r}, a{s}, {}", 32 - ((op2 & 1) << 4 | t))),
+        (0x1, 0x2 | 0x3) => op("srai", format!("a{r}, a{t}, {}", (op2 & 1) << 4 | s)),
+        (0x1, 0x4) => op("srli", format!("a{r}, a{t}, {s}")),
+        (0x1, 0x6) => op("xsr", format!("a{t}, {}", special_register(r << 4 | s))),
+        (0x1, 0x8) => rrr("src"),
+        (0x1, 0x9) if s == 0 => op("srl", format!("a{r}, a{t}")),
+        (0x1, 0xA) if t == 0 => op("sll", format!("a{r}, a{s}")),
+        (0x1, 0xB) if s == 0 => op("sra", format!("a{r}, a{t}")),
+        (0x1, 0xC) => rrr("mul16u"),
+        (0x1, 0xD) => rrr("mul16s"),
+
+        (0x2, 0x8) => rrr("mull"),
+        (0x2, 0xA) => rrr("muluh"),
+        (0x2, 0xB) => rrr("mulsh"),
+        (0x2, 0xC) => rrr("quou"),
+        (0x2, 0xD) => rrr("quos"),
+        (0x2, 0xE) => rrr("remu"),
+        (0x2, 0xF) => rrr("rems"),
+
+        (0x3, 0x0) => op("rsr", format!("a{t}, {}", special_register(r << 4 | s))),
+        (0x3, 0x1) => op("wsr", format!("a{t}, {}", special_register(r << 4 | s))),
+        (0x3, 0x2) => op("sext", format!("a{r}, a{s}, {}", t + 7)),
+        (0x3, 0x3) => op("clamps", format!("a{r}, a{s}, {}", t + 7)),
+        (0x3, 0x4) => rrr("min"),
+        (0x3, 0x5) => rrr("max"),
+        (0x3, 0x6) => rrr("minu"),
+        (0x3, 0x7) => rrr("maxu"),
+        (0x3, 0x8) => rrr("moveqz"),
+        (0x3, 0x9) => rrr("movnez"),
+        (0x3, 0xA) => rrr("movltz"),
+        (0x3, 0xB) => rrr("movgez"),
+        (0x3, 0xE) => op("rur", format!("a{r}, {}", user_register(s << 4 | t))),
+        (0x3, 0xF) => op("wur", format!("a{t}, {}", user_register(r << 4 | s))),
+
+        (0x4 | 0x5, _) => op(
+            "extui",
+            format!("a{r}, a{t}, {}, {}", (op1 & 1) << 4 | s, op2 + 1),
+        ),
+        _ => None,
+    }
+}
+
+fn decode_st0(word: u32) -> Option<Decoded> {
+    let t = (word >> 4) & 0xF;
+    let s = (word >> 8) & 0xF;
+    let r = (word >> 12) & 0xF;
+
+    match r {
+        0x0 => match (t >> 2, t & 0x3) {
+            (0, 0) => op("ill", String::new()),
+            (2, 0) => op("ret", String::new()),
+            (2, 1) => op("retw", String::new()),
+            (2, 2) => op("jx", format!("a{s}")),
+            (3, n) => op(
+                ["callx0", "callx4", "callx8", "callx12"][n as usize],
+                format!("a{s}"),
+            ),
+            _ => None,
+        },
+        0x1 => op("movsp", format!("a{t}, a{s}")),
+        0x2 => {
+            let mnemonic = match t {
+                0x0 => "isync",
+                0x1 => "rsync",
+                0x2 => "esync",
+                0x3 => "dsync",
+                0x8 => "excw",
+                0xC => "memw",
+                0xD => "extw",
+                0xF => "nop",
+                _ => return None,
+            };
+            op(mnemonic, String::new())
+        }
+        0x3 => match (t, s) {
+            (0, 0) => op("rfe", String::new()),
+            (0, 1) => op("rfue", String::new()),
+            (0, 2) => op("rfde", String::new()),
+            (0, 4) => op("rfwo", String::new()),
+            (0, 5) => op("rfwu", String::new()),
+            (1, _) => op("rfi", format!("{s}")),
+            _ => None,
+        },
+        0x4 => op("break", format!("{s}, {t}")),
+        0x5 if s == 0 => op("syscall", String::new()),
+        0x5 if s == 1 => op("simcall", String::new()),
+        0x6 => op("rsil", format!("a{t}, {s}")),
+        0x7 => op("waiti", format!("{s}")),
+        _ => None,
+    }
+}
+
+fn decode_lsai(word: u32) -> Option<Decoded> {
+    let t = (word >> 4) & 0xF;
+    let s = (word >> 8) & 0xF;
+    let r = (word >> 12) & 0xF;
+    let imm8 = (word >> 16) & 0xFF;
+
+    let memory = |mnemonic, scale| op(mnemonic, format!("a{t}, a{s}, {}", imm8 * scale));
+
+    match r {
+        0x0 => memory("l8ui", 1),
+        0x1 => memory("l16ui", 2),
+        0x2 => memory("l32i", 4),
+        0x4 => memory("s8i", 1),
+        0x5 => memory("s16i", 2),
+        0x6 => memory("s32i", 4),
+        0x9 => memory("l16si", 2),
+        0xA => op("movi", format!("a{t}, {}", sext(s << 8 | imm8, 12))),
+        0xB => memory("l32ai", 4),
+        0xC => op("addi", format!("a{t}, a{s}, {}", sext(imm8, 8))),
+        0xD => op("addmi", format!("a{t}, a{s}, {}", sext(imm8, 8) * 256)),
+        0xE => memory("s32c1i", 4),
+        0xF => memory("s32ri", 4),
+        _ => None,
+    }
+}
+
+fn decode_si(pc: u64, word: u32) -> Option<Decoded> {
+    let n = (word >> 4) & 0x3;
+    let m = (word >> 6) & 0x3;
+    let s = (word >> 8) & 0xF;
+    let r = (word >> 12) & 0xF;
+    let imm8 = (word >> 16) & 0xFF;
+    let imm12 = word >> 12;
+
+    let branch8 = offset(pc, 4 + sext(imm8, 8));
+
+    match (n, m) {
+        (0, _) => to("j", String::new(), offset(pc, 4 + sext(word >> 6, 18))),
+        (1, _) => {
+            let mnemonic = ["beqz", "bnez", "bltz", "bgez"][m as usize];
+            to(mnemonic, format!("a{s}"), offset(pc, 4 + sext(imm12, 12)))
+        }
+        (2, _) => {
+            let mnemonic = ["beqi", "bnei", "blti", "bgei"][m as usize];
+            to(mnemonic, format!("a{s}, {}", B4CONST[r as usize]), branch8)
+        }
+        (3, 0) => op("entry", format!("a{s}, {}", imm12 * 8)),
+        (3, 1) => {
+            let loop_end = offset(pc, 4 + i64::from(imm8));
+            match r {
+                0x0 => to("bf", format!("b{s}"), branch8),
+                0x1 => to("bt", format!("b{s}"), branch8),
+                0x8 => to("loop", format!("a{s}"), loop_end),
+                0x9 => to("loopnez", format!("a{s}"), loop_end),
+                0xA => to("loopgtz", format!("a{s}"), loop_end),
+                _ => None,
+            }
+        }
+        (3, 2) => to("bltui", format!("a{s}, {}", B4CONSTU[r as usize]), branch8),
+        (3, _) => to("bgeui", format!("a{s}, {}", B4CONSTU[r as usize]), branch8),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listing(address: u64, code: &[u8]) -> Vec<String> {
+        disassemble(address, code, true)
+            .iter()
+            .map(|instruction| {
+                format!("{} {}", instruction.mnemonic, instruction.operands)
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn function_prologue_and_epilogue() {
+        let code = [
+            0x36, 0x41, 0x00, // entry a1, 32
+            0x12, 0xc1, 0xf0, // addi a1, a1, -16
+            0x2d, 0x03, // mov.n a2, a3
+            0x7c, 0xf2, // movi.n a2, -1
+            0x28, 0x11, // l32i.n a2, a1, 4
+            0x0d, 0xf0, // ret.n
+        ];
+
+        assert_eq!(
+            listing(0x4000_0000, &code),
+            [
+                "entry a1, 32",
+                "addi a1, a1, -16",
+                "mov.n a2, a3",
+                "movi.n a2, -1",
+                "l32i.n a2, a1, 4",
+                "ret.n",
+            ]
+        );
+    }
+
+    #[test]
+    fn branch_targets() {
+        // call8 with an offset of one word, and j to itself.
+        let code = [0x65, 0x00, 0x00, 0x06, 0xff, 0xff];
+        let instructions = disassemble(0x4000_0002, &code, true);
+
+        assert_eq!(instructions[0].mnemonic, "call8");
+        assert_eq!(instructions[0].target, Some(0x4000_0008));
+        assert_eq!(instructions[1].mnemonic, "j");
+        assert_eq!(instructions[1].target, Some(0x4000_0005));
+    }
+
+    #[test]
+    fn without_code_density() {
+        // Without the Code Density Option, the first byte starts a 24-bit instruction, and the
+        // partial instruction at the end is not decoded.
+        let code = [0x2d, 0x03, 0x00, 0x2d];
+        let instructions = disassemble(0x0, &code, false);
+
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(instructions[0].len(), 3);
+        assert_eq!(instructions[0].mnemonic, ".byte");
+    }
+}
@@ -1,5 +1,6 @@
-use crate::architecture::xtensa::arch::{CpuRegister, SpecialRegister};
+use crate::architecture::xtensa::arch::{CpuRegister, FpuRegister, SpecialRegister, UserRegister};
 
+pub mod disassemble;
 pub mod format;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
@@ -12,17 +13,43 @@ pub enum Instruction {
     /// Note: this is an illegal instruction when the processor is not in On-Chip Debug Mode
     Sddr32P(CpuRegister),
 
+    /// Loads 8 bits from the address in `as` offset by a constant into `at`, zero-extended.
+    ///
+    /// This instruction can not access InstrRAM.
+    L8ui(CpuRegister, CpuRegister, u8),
+
     /// Stores 8 bits from `at` to the address in `as` offset by a constant.
     ///
     /// This instruction can not access InstrRAM.
     S8i(CpuRegister, CpuRegister, u8),
 
+    /// Stores 16 bits from `at` to the address in `as` offset by a constant, which must be even.
+    ///
+    /// This instruction can not access InstrRAM.
+    S16i(CpuRegister, CpuRegister, u8),
+
     /// Reads `SpecialRegister` into `CpuRegister`
     Rsr(SpecialRegister, CpuRegister),
 
     /// Writes `CpuRegister` into `SpecialRegister`
     Wsr(SpecialRegister, CpuRegister),
 
+    /// Reads `UserRegister` into `CpuRegister`
+    Rur(UserRegister, CpuRegister),
+
+    /// Writes `CpuRegister` into `UserRegister`
+    Wur(UserRegister, CpuRegister),
+
+    /// Moves `FpuRegister` into `CpuRegister`
+    ///
+    /// Note: this instruction raises an exception if the coprocessor is not enabled in CPENABLE
+    Rfr(FpuRegister, CpuRegister),
+
+    /// Moves `CpuRegister` into `FpuRegister`
+    ///
+    /// Note: this instruction raises an exception if the coprocessor is not enabled in CPENABLE
+    Wfr(FpuRegister, CpuRegister),
+
     /// Invalidates the I-Cache at the address in `CpuRegister` + offset.
     ///
     /// The offset will be divided by 4 and has a maximum value of 1020.
@@ -33,11 +60,82 @@ pub enum Instruction {
     /// The offset will be divided by 4 and has a maximum value of 1020.
     Dhwbi(CpuRegister, u32),
 
+    /// Waits until all preceding instruction cache changes and stores to instruction memory have
+    /// completed, and discards any prefetched instructions.
+    Isync,
+
+    /// Rotates the register window by the given number of 4-register units.
+    ///
+    /// The rotation must be between -8 and 7.
+    Rotw(i8),
+
     /// Returns the Core to the Running state
     Rfdo(u8),
 
     /// Generates a debug exception
     Break(u8, u8),
+
+    /// Generates a debug exception, narrow encoding
+    BreakN(u8),
+}
+
+/// Returns the length of an instruction in bytes, based on its first byte.
+///
+/// Instructions with op0 between 8 and 13 are narrow (16-bit) instructions of the Code Density
+/// Option, everything else is treated as a 24-bit instruction. Without the Code Density Option,
+/// every instruction is 24 bits long.
+pub const fn instruction_length(first_byte: u8, code_density: bool) -> usize {
+    match first_byte & 0x0F {
+        0x8..=0xD if code_density => 2,
+        _ => 3,
+    }
+}
+
+/// Returns the breakpoint instruction which replaces the instruction starting with `first_byte`.
+///
+/// The breakpoint must not be longer than the instruction it replaces, which may be the last one
+/// of a loop body or be followed by a branch target.
+pub const fn software_breakpoint(first_byte: u8, code_density: bool) -> Instruction {
+    match instruction_length(first_byte, code_density) {
+        2 => Instruction::BreakN(1),
+        _ => Instruction::Break(1, 15),
+    }
+}
+
+/// An instruction read from memory.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct DecodedInstruction {
+    /// The length of the instruction in bytes.
+    pub length: usize,
+
+    /// The instruction, if it is one of the few the debugger needs to recognize.
+    pub instruction: Option<Instruction>,
+}
+
+/// Decodes the instruction that starts with `bytes`. Narrow instructions only use the first
+/// two bytes.
+pub fn decode(bytes: [u8; 3], code_density: bool) -> DecodedInstruction {
+    let length = instruction_length(bytes[0], code_density);
+
+    let instruction = if length == 2 {
+        let word = u16::from_le_bytes([bytes[0], bytes[1]]);
+
+        // 1111 s 0010 1101
+        (word & 0xF0FF == 0xF02D).then_some(Instruction::BreakN((word >> 8) as u8 & 0x0F))
+    } else {
+        let word = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], 0]);
+
+        // 0000 0000 0100 s t 0000
+        (word & 0xFFF00F == 0x004000).then_some(Instruction::Break(
+            (word >> 8) as u8 & 0x0F,
+            (word >> 4) as u8 & 0x0F,
+        ))
+    };
+
+    DecodedInstruction {
+        length,
+        instruction,
+    }
 }
 
 /// The architecture supports multi-word instructions. This enum represents the different encodings
@@ -55,7 +153,13 @@ impl Instruction {
             Instruction::Sddr32P(src) => 0x0070F0 | (src as u32 & 0x0F) << 8,
             Instruction::Rsr(sr, t) => format::rsr(0x030000, sr as u8, t as u8),
             Instruction::Wsr(sr, t) => format::rsr(0x130000, sr as u8, t as u8),
+            Instruction::Rur(ur, r) => format::rur(0xE30000, ur as u8, r as u8),
+            Instruction::Wur(ur, t) => format::rsr(0xF30000, ur as u8, t as u8),
+            Instruction::Rfr(fs, r) => format::rrr(0xFA0000, r as u8, fs as u8, 4),
+            Instruction::Wfr(fr, s) => format::rrr(0xFA0000, fr as u8, s as u8, 5),
+            Instruction::L8ui(t, s, offset) => format::rri8(0x000002, t as u8, s as u8, offset),
             Instruction::S8i(t, s, offset) => format::rri8(0x004002, t as u8, s as u8, offset),
+            Instruction::S16i(t, s, offset) => format::rri8(0x005002, t as u8, s as u8, offset / 2),
             Instruction::Ihi(src, offset) => {
                 format::rri8(0x0070E2, 0, src as u8, (offset / 4) as u8)
             }
@@ -66,10 +170,21 @@ impl Instruction {
                 // 0000 0000 0100 s t 0000
                 format::rrr(0x000000, 4, s, t)
             }
+            Instruction::Isync => 0x002000,
+            Instruction::Rotw(n) => 0x408000 | (n as u32 & 0x0F) << 4,
             Instruction::Rfdo(_) => 0xF1E000,
+            Instruction::BreakN(s) => {
+                // 1111 s 0010 1101
+                0xF02D | (s as u32 & 0x0F) << 8
+            }
         };
 
-        (3, word)
+        let bytes = match self {
+            Instruction::BreakN(_) => 2,
+            _ => 3,
+        };
+
+        (bytes, word)
     }
 
     pub fn encode_into_vec(self, vec: &mut Vec<u8>) {
@@ -83,3 +198,117 @@ impl Instruction {
         InstructionEncoding::Narrow(narrow)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(instruction: Instruction) -> [u8; 3] {
+        let mut bytes = vec![];
+        instruction.encode_into_vec(&mut bytes);
+        bytes.resize(3, 0);
+        bytes.try_into().unwrap()
+    }
+
+    #[test]
+    fn decode_breakpoints() {
+        let break_n = encode(Instruction::BreakN(1));
+        assert_eq!(
+            decode(break_n, true),
+            DecodedInstruction {
+                length: 2,
+                instruction: Some(Instruction::BreakN(1)),
+            }
+        );
+
+        let break_ = encode(Instruction::Break(1, 15));
+        assert_eq!(
+            decode(break_, true),
+            DecodedInstruction {
+                length: 3,
+                instruction: Some(Instruction::Break(1, 15)),
+            }
+        );
+    }
+
+    #[test]
+    fn decode_other_instructions() {
+        // RSR a0, DEBUGCAUSE
+        let rsr = encode(Instruction::Rsr(
+            SpecialRegister::DebugCause,
+            CpuRegister::A0,
+        ));
+        assert_eq!(decode(rsr, true).instruction, None);
+        assert_eq!(decode(rsr, true).length, 3);
+
+        // MOV.N a2, a3
+        let mov_n = [0x2d, 0x03, 0x00];
+        assert_eq!(decode(mov_n, true).length, 2);
+        assert_eq!(decode(mov_n, false).length, 3);
+    }
+
+    #[test]
+    fn encode_narrow_stores() {
+        // S8I a4, a3, 3
+        assert_eq!(
+            encode(Instruction::S8i(CpuRegister::A4, CpuRegister::A3, 3)),
+            [0x42, 0x43, 0x03]
+        );
+        // S16I a4, a3, 2
+        assert_eq!(
+            encode(Instruction::S16i(CpuRegister::A4, CpuRegister::A3, 2)),
+            [0x42, 0x53, 0x01]
+        );
+    }
+
+    #[test]
+    fn software_breakpoint_fits_the_replaced_instruction() {
+        // MOV.N a2, a3
+        assert_eq!(software_breakpoint(0x2d, true), Instruction::BreakN(1));
+        // ENTRY a1, 32
+        assert_eq!(software_breakpoint(0x36, true), Instruction::Break(1, 15));
+        // Every narrow opcode
+        for op0 in 0x8..=0xD {
+            assert_eq!(software_breakpoint(op0, true), Instruction::BreakN(1));
+        }
+
+        // Without the Code Density Option, every instruction is 24 bits long
+        assert_eq!(software_breakpoint(0x2d, false), Instruction::Break(1, 15));
+
+        assert_eq!(encode(Instruction::BreakN(1)), [0x2d, 0xf1, 0x00]);
+        assert_eq!(encode(Instruction::Break(1, 15)), [0xf0, 0x41, 0x00]);
+    }
+
+    #[test]
+    fn encode_isync() {
+        assert_eq!(encode(Instruction::Isync), [0x00, 0x20, 0x00]);
+    }
+
+    #[test]
+    fn encode_user_register_accesses() {
+        // RUR a2, FCR
+        assert_eq!(
+            encode(Instruction::Rur(UserRegister::Fcr, CpuRegister::A2)),
+            [0x80, 0x2E, 0xE3]
+        );
+        // WUR a3, FSR
+        assert_eq!(
+            encode(Instruction::Wur(UserRegister::Fsr, CpuRegister::A3)),
+            [0x30, 0xE9, 0xF3]
+        );
+    }
+
+    #[test]
+    fn encode_floating_point_register_accesses() {
+        // RFR a2, f3
+        assert_eq!(
+            encode(Instruction::Rfr(FpuRegister::F3, CpuRegister::A2)),
+            [0x40, 0x23, 0xFA]
+        );
+        // WFR f1, a4
+        assert_eq!(
+            encode(Instruction::Wfr(FpuRegister::F1, CpuRegister::A4)),
+            [0x50, 0x14, 0xFA]
+        );
+    }
+}
@@ -11,6 +11,8 @@ pub mod instruction;
 pub enum Register {
     Cpu(CpuRegister),
     Special(SpecialRegister),
+    Fpu(FpuRegister),
+    User(UserRegister),
 
     /// Program counter. The physical register depends on the debug level.
     CurrentPc,
@@ -65,6 +67,75 @@ impl TryFrom<u8> for CpuRegister {
     }
 }
 
+/// Floating point registers, available if the core implements the Floating-Point Coprocessor
+/// Option.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum FpuRegister {
+    F0 = 0,
+    F1 = 1,
+    F2 = 2,
+    F3 = 3,
+    F4 = 4,
+    F5 = 5,
+    F6 = 6,
+    F7 = 7,
+    F8 = 8,
+    F9 = 9,
+    F10 = 10,
+    F11 = 11,
+    F12 = 12,
+    F13 = 13,
+    F14 = 14,
+    F15 = 15,
+}
+
+impl TryFrom<u8> for FpuRegister {
+    type Error = XtensaError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::F0),
+            1 => Ok(Self::F1),
+            2 => Ok(Self::F2),
+            3 => Ok(Self::F3),
+            4 => Ok(Self::F4),
+            5 => Ok(Self::F5),
+            6 => Ok(Self::F6),
+            7 => Ok(Self::F7),
+            8 => Ok(Self::F8),
+            9 => Ok(Self::F9),
+            10 => Ok(Self::F10),
+            11 => Ok(Self::F11),
+            12 => Ok(Self::F12),
+            13 => Ok(Self::F13),
+            14 => Ok(Self::F14),
+            15 => Ok(Self::F15),
+            _ => Err(XtensaError::RegisterNotAvailable),
+        }
+    }
+}
+
+/// User registers, accessed using the RUR/WUR instructions.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+pub enum UserRegister {
+    /// Floating point control register
+    Fcr = 232,
+    /// Floating point status register
+    Fsr = 233,
+}
+
+impl TryFrom<u8> for UserRegister {
+    type Error = XtensaError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            v if v == Self::Fcr as u8 => Ok(Self::Fcr),
+            v if v == Self::Fsr as u8 => Ok(Self::Fsr),
+            _ => Err(XtensaError::RegisterNotAvailable),
+        }
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 pub enum SpecialRegister {
     Lbeg = 0,
@@ -250,6 +321,37 @@ impl SpecialRegister {
     pub const Interrupt: Self = Self::IntSet;
 }
 
+/// Returns a description of an EXCCAUSE value, and whether EXCVADDR holds the faulting address
+/// for it.
+pub fn describe_exception_cause(cause: u32) -> (&'static str, bool) {
+    match cause {
+        0 => ("IllegalInstruction", false),
+        1 => ("Syscall", false),
+        2 => ("InstructionFetchError", true),
+        3 => ("LoadStoreError", true),
+        4 => ("Level1Interrupt", false),
+        5 => ("Alloca", false),
+        6 => ("IntegerDivideByZero", false),
+        8 => ("Privileged", false),
+        9 => ("LoadStoreAlignment", true),
+        12 => ("InstrPIFDataError", true),
+        13 => ("LoadStorePIFDataError", true),
+        14 => ("InstrPIFAddrError", true),
+        15 => ("LoadStorePIFAddrError", true),
+        16 => ("InstTLBMiss", true),
+        17 => ("InstTLBMultiHit", true),
+        18 => ("InstFetchPrivilege", true),
+        20 => ("InstFetchProhibited", true),
+        24 => ("LoadStoreTLBMiss", true),
+        25 => ("LoadStoreTLBMultiHit", true),
+        26 => ("LoadStorePrivilege", true),
+        28 => ("LoadProhibited", true),
+        29 => ("StoreProhibited", true),
+        32..=39 => ("CoprocessorDisabled", false),
+        _ => ("Reserved", false),
+    }
+}
+
 impl TryFrom<RegisterId> for Register {
     type Error = XtensaError;
 
@@ -259,6 +361,8 @@ impl TryFrom<RegisterId> for Register {
         match (group, id) {
             (0, id) => Ok(Self::Cpu(CpuRegister::try_from(id)?)),
             (1, id) => Ok(Self::Special(SpecialRegister::try_from(id)?)),
+            (2, id) => Ok(Self::Fpu(FpuRegister::try_from(id)?)),
+            (3, id) => Ok(Self::User(UserRegister::try_from(id)?)),
             (0xFF, 0) => Ok(Self::CurrentPc),
             (0xFF, 1) => Ok(Self::CurrentPs),
             _ => Err(XtensaError::RegisterNotAvailable),
@@ -277,3 +381,15 @@ impl From<SpecialRegister> for Register {
         Self::Special(value)
     }
 }
+
+impl From<FpuRegister> for Register {
+    fn from(value: FpuRegister) -> Self {
+        Self::Fpu(value)
+    }
+}
+
+impl From<UserRegister> for Register {
+    fn from(value: UserRegister) -> Self {
+        Self::User(value)
+    }
+}
@@ -5,18 +5,30 @@
 
 use std::{
     collections::HashMap,
+    ops::Range,
     time::{Duration, Instant},
 };
 
+use probe_rs_target::{AccessWidth, AddressTranslation};
+
 use crate::{
     architecture::xtensa::arch::{
-        instruction::Instruction, CpuRegister, Register, SpecialRegister,
+        instruction::Instruction, CpuRegister, FpuRegister, Register, SpecialRegister, UserRegister,
+    },
+    memory::check_alignment,
+    probe::{
+        retry::{ErrorClass, RetryPolicy},
+        telemetry::Telemetry,
+        JTAGAccess,
     },
-    probe::JTAGAccess,
-    DebugProbeError, Error as ProbeRsError, MemoryInterface,
+    DebugProbeError, Error as ProbeRsError, MemoryInterface, Probe, ResetCause,
 };
 
-use super::xdm::{Error as XdmError, Xdm};
+use super::{
+    sequences::Watchdog,
+    trax::XtensaTrace,
+    xdm::{DebugRegisterError, Error as XdmError, Xdm},
+};
 
 /// Possible Xtensa errors
 #[derive(thiserror::Error, Debug)]
@@ -31,12 +43,143 @@ pub enum XtensaError {
     // TODO: maybe we could be a bit more specific
     #[error("The operation has timed out")]
     Timeout,
+    /// The debug module is powered down, e.g. because the chip is in deep sleep.
+    #[error("The debug module is powered down. The chip may be in a low-power state.")]
+    Sleeping,
+    /// The operation executes instructions on the core, which is running.
+    #[error("The core must be halted for this operation.")]
+    NotHalted,
     /// The connected target is not an Xtensa device.
     #[error("Connected target is not an Xtensa device.")]
     NoXtensaTarget,
     /// The requested register is not available.
     #[error("The requested register is not available.")]
     RegisterNotAvailable,
+    /// The memory region only allows aligned 32-bit accesses.
+    #[error("The {len} byte access at {address:#010x} is not supported by the memory region, which only allows aligned 32-bit accesses.")]
+    UnsupportedAccessWidth {
+        /// The start address of the access.
+        address: u64,
+        /// The length of the access in bytes.
+        len: usize,
+    },
+    /// Resetting the core failed.
+    #[error("Resetting the core failed while {phase}.")]
+    ResetFailed {
+        /// The step of the reset sequence that failed.
+        phase: ResetPhase,
+        /// The underlying error.
+        #[source]
+        source: Box<XtensaError>,
+    },
+    /// An instruction executed by [`XtensaCommunicationInterface::execute_program`] raised an
+    /// exception.
+    #[error(
+        "Instruction {index} of the program raised exception {cause} (EXCVADDR: {address:#010x})."
+    )]
+    ProgramException {
+        /// The index of the failing instruction in the program.
+        index: usize,
+        /// The value of EXCCAUSE.
+        cause: u32,
+        /// The value of EXCVADDR.
+        address: u32,
+    },
+    /// The debug interrupt level is not supported by Xtensa cores.
+    #[error("Invalid debug level {0}, the debug level must be between 2 and 7.")]
+    InvalidDebugLevel(u8),
+    /// The number of breakpoint units is not supported by Xtensa cores.
+    #[error("Invalid number of breakpoint units {0}, Xtensa cores have at most 2.")]
+    InvalidBreakUnitCount(u8),
+    /// The flash is encrypted, so it can't be erased by the debugger.
+    #[error("The flash is encrypted. Erasing it would only allow encrypted images to be written afterwards, which probe-rs can't produce.")]
+    FlashEncrypted,
+    /// The write protection of the flash could not be removed.
+    #[error("The flash is write protected, and removing the protection failed with status {0}.")]
+    FlashLocked(u32),
+    /// A ROM routine called to access the flash reported an error.
+    #[error("The ROM routine {routine} failed with status {status}.")]
+    RomRoutineFailed {
+        /// The name of the routine.
+        routine: &'static str,
+        /// The status returned by the routine.
+        status: u32,
+    },
+    /// The breakpoint unit does not exist.
+    #[error("Breakpoint unit {0} does not exist.")]
+    InvalidBreakpointUnit(usize),
+    /// A software breakpoint can't be set in code executed from flash, which can't be rewritten.
+    #[error("Unable to set a software breakpoint at {address:#010x}: the code is executed from flash (offset {flash_offset:#x}), which can't be rewritten on this chip.")]
+    BreakpointInFlash {
+        /// The address of the breakpoint.
+        address: u64,
+        /// The offset in flash the address is mapped to.
+        flash_offset: u64,
+    },
+    /// A software breakpoint can't be set because the memory ignored the write.
+    #[error("Unable to set a software breakpoint at {0:#010x}: the memory is not writable.")]
+    BreakpointNotWritable(u64),
+    /// The trace unit has no memory to store a trace in.
+    #[error("The trace unit has no trace memory.")]
+    NoTraceMemory,
+    /// The probe can't direct its accesses to the TAP of a core.
+    #[error("The probe can't select the JTAG TAP {tap} of core {core}. This chip needs a probe which supports scan chains with multiple TAPs.")]
+    JtagTapNotSelectable {
+        /// The index of the core.
+        core: usize,
+        /// The position of the TAP of the core in the scan chain.
+        tap: usize,
+    },
+}
+
+/// A step of the Xtensa reset sequence, reported by [`XtensaError::ResetFailed`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetPhase {
+    /// Asserting the reset signal.
+    Assert,
+    /// Releasing the reset signal.
+    Deassert,
+    /// Waiting for the core to halt after the reset.
+    Halt,
+    /// Resuming the core after the reset.
+    Resume,
+}
+
+impl std::fmt::Display for ResetPhase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ResetPhase::Assert => "asserting the reset",
+            ResetPhase::Deassert => "releasing the reset",
+            ResetPhase::Halt => "waiting for the core to halt",
+            ResetPhase::Resume => "resuming the core",
+        })
+    }
+}
+
+impl XtensaError {
+    pub(crate) fn reset_failed(phase: ResetPhase) -> impl FnOnce(XtensaError) -> XtensaError {
+        move |source| XtensaError::ResetFailed {
+            phase,
+            source: Box::new(source),
+        }
+    }
+
+    /// Returns the class of the error, if it is transient and the failed operation may succeed
+    /// when it is repeated.
+    pub fn error_class(&self) -> Option<ErrorClass> {
+        match self {
+            XtensaError::DebugProbe(error) => error.error_class(),
+            XtensaError::XdmError(
+                XdmError::Xdm(DebugRegisterError::Busy)
+                | XdmError::ExecBusy
+                | XdmError::ExecOverrun,
+            ) => Some(ErrorClass::Busy),
+            XtensaError::XdmError(XdmError::Xdm(
+                DebugRegisterError::Error | DebugRegisterError::Unexpected,
+            )) => Some(ErrorClass::Transfer),
+            _ => None,
+        }
+    }
 }
 
 impl From<XtensaError> for DebugProbeError {
@@ -59,7 +202,6 @@ impl From<XtensaError> for ProbeRsError {
 }
 
 #[derive(Clone, Copy)]
-#[allow(unused)]
 pub(super) enum DebugLevel {
     L2 = 2,
     L3 = 3,
@@ -69,6 +211,22 @@ pub(super) enum DebugLevel {
     L7 = 7,
 }
 
+impl TryFrom<u8> for DebugLevel {
+    type Error = XtensaError;
+
+    fn try_from(level: u8) -> Result<Self, Self::Error> {
+        match level {
+            2 => Ok(DebugLevel::L2),
+            3 => Ok(DebugLevel::L3),
+            4 => Ok(DebugLevel::L4),
+            5 => Ok(DebugLevel::L5),
+            6 => Ok(DebugLevel::L6),
+            7 => Ok(DebugLevel::L7),
+            _ => Err(XtensaError::InvalidDebugLevel(level)),
+        }
+    }
+}
+
 impl DebugLevel {
     pub fn pc(self) -> SpecialRegister {
         match self {
@@ -93,6 +251,19 @@ impl DebugLevel {
     }
 }
 
+/// Controls how single-stepping interacts with interrupts.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum StepMode {
+    /// Interrupts are masked while stepping, so a step always lands on the next instruction
+    /// of the interrupted code.
+    #[default]
+    OverInterrupts,
+
+    /// Interrupts are left unmasked while stepping. If an interrupt is pending, the step will
+    /// land on the first instruction of the interrupt handler.
+    IntoInterrupts,
+}
+
 struct XtensaCommunicationInterfaceState {
     /// Pairs of (register, value).
     saved_registers: HashMap<Register, u32>,
@@ -100,6 +271,23 @@ struct XtensaCommunicationInterfaceState {
     print_exception_cause: bool,
 
     is_halted: bool,
+
+    step_mode: StepMode,
+
+    /// Whether the Floating-Point Coprocessor is implemented. `None` if not yet detected.
+    fpu_present: Option<bool>,
+}
+
+impl XtensaCommunicationInterfaceState {
+    fn new() -> Self {
+        Self {
+            saved_registers: Default::default(),
+            print_exception_cause: true,
+            is_halted: false,
+            step_mode: StepMode::default(),
+            fpu_present: None,
+        }
+    }
 }
 
 /// A interface that implements controls for Xtensa cores.
@@ -107,27 +295,84 @@ struct XtensaCommunicationInterfaceState {
 pub struct XtensaCommunicationInterface {
     /// The Xtensa debug module
     xdm: Xdm,
+    /// The state of the currently selected core.
     state: XtensaCommunicationInterfaceState,
+    /// The state of the cores that are not currently selected, keyed by core index.
+    inactive_cores: HashMap<usize, XtensaCommunicationInterfaceState>,
+    /// The TAPs of all cores of the chip.
+    core_taps: Vec<usize>,
 
     hw_breakpoint_num: u32,
+    hw_watchpoint_num: u32,
     debug_level: DebugLevel,
+
+    /// The number of physical address registers (NAREG).
+    physical_register_num: u8,
+
+    /// Whether the cores implement the Windowed Register Option.
+    windowed_registers: bool,
+
+    /// Whether the cores implement the Floating-Point Coprocessor, if declared by the target
+    /// description. Otherwise it is detected for each core.
+    fpu_config: Option<bool>,
+
+    /// Memory regions that need cache maintenance after they are written.
+    cached_regions: Vec<CachedRegion>,
+
+    /// The access widths that memory regions tolerate.
+    access_regions: Vec<(Range<u64>, AccessWidth)>,
+
+    /// Address ranges that are accessed at a different address than the one requested.
+    address_translations: Vec<AddressTranslation>,
+
+    /// Whether the debugger may stop the watchdogs of the chip.
+    stop_watchdogs: bool,
+
+    /// Whether encrypted flash read through the cache is decrypted.
+    flash_decryption: bool,
+
+    /// The watchdogs stopped while the cores are halted, along with their original configuration.
+    /// `None` if the watchdogs are not suspended.
+    suspended_watchdogs: Option<Vec<(Watchdog, u32)>>,
+
+    /// How memory accesses that fail because of transient errors are retried.
+    retry_policy: RetryPolicy,
 }
 
 impl XtensaCommunicationInterface {
+    /// The maximum number of words read in a single JTAG batch.
+    const READ_BATCH_WORDS: usize = 256;
+
+    /// Returns the number of words read in the next batch when `remaining` bytes are left to be
+    /// read, and whether the last read of the batch loads the next word.
+    fn next_read_batch(remaining: usize) -> (usize, bool) {
+        let words = remaining.div_ceil(4).min(Self::READ_BATCH_WORDS);
+        // Only load the next word if it will be read in a subsequent batch.
+        (words, words * 4 < remaining)
+    }
+
     /// Create the Xtensa communication interface using the underlying probe driver
     pub fn new(probe: Box<dyn JTAGAccess>) -> Result<Self, (Box<dyn JTAGAccess>, DebugProbeError)> {
         let xdm = Xdm::new(probe).map_err(|(probe, e)| (probe, e.into()))?;
 
         let mut s = Self {
             xdm,
-            state: XtensaCommunicationInterfaceState {
-                saved_registers: Default::default(),
-                print_exception_cause: true,
-                is_halted: false,
-            },
-            // TODO chip-specific configuration
+            state: XtensaCommunicationInterfaceState::new(),
+            inactive_cores: HashMap::new(),
+            core_taps: vec![0],
             hw_breakpoint_num: 2,
+            hw_watchpoint_num: 2,
             debug_level: DebugLevel::L6,
+            physical_register_num: 64,
+            windowed_registers: true,
+            fpu_config: None,
+            cached_regions: Vec::new(),
+            access_regions: Vec::new(),
+            address_translations: Vec::new(),
+            flash_decryption: true,
+            stop_watchdogs: true,
+            suspended_watchdogs: None,
+            retry_policy: RetryPolicy::default(),
         };
 
         match s.init() {
@@ -142,10 +387,499 @@ impl XtensaCommunicationInterface {
         Ok(())
     }
 
+    /// Destruct the interface and return the stored probe driver.
+    pub fn close(self) -> Probe {
+        Probe::from_attached_probe(self.xdm.free().into_probe())
+    }
+
     pub fn available_breakpoint_units(&self) -> u32 {
         self.hw_breakpoint_num
     }
 
+    pub fn available_watchpoint_units(&self) -> u32 {
+        self.hw_watchpoint_num
+    }
+
+    /// Returns the JTAG clock in kHz. The clock may be lower than the one configured, if accesses
+    /// to the debug module failed at that clock.
+    pub fn jtag_speed_khz(&self) -> u32 {
+        self.xdm.probe.speed_khz()
+    }
+
+    /// Sets the JTAG clock, lowering it further until the debug module can be accessed reliably.
+    /// Returns the effective clock in kHz.
+    pub fn set_jtag_speed(&mut self, speed_khz: u32) -> Result<u32, XtensaError> {
+        self.xdm.set_speed(speed_khz)
+    }
+
+    /// Selects whether the JTAG clock is lowered automatically when accesses to the debug module
+    /// fail. Enabled by default.
+    pub fn set_speed_backoff(&mut self, enabled: bool) {
+        self.xdm.speed_backoff = enabled;
+    }
+
+    /// Reads the raw value of the Nexus register at `address` of the selected core's debug module.
+    ///
+    /// This bypasses the bookkeeping of the interface, and is meant for prototyping features
+    /// that are not supported yet. The addresses are listed in the Xtensa Debug Guide.
+    pub fn read_raw_nexus_register(&mut self, address: u8) -> Result<u32, XtensaError> {
+        if address > 0x7f {
+            return Err(XtensaError::RegisterNotAvailable);
+        }
+
+        self.xdm.read_raw_nexus_register(address)
+    }
+
+    /// Writes `value` to the Nexus register at `address` of the selected core's debug module.
+    ///
+    /// This bypasses the bookkeeping of the interface, so writes that change the state of the
+    /// debug module or the core, e.g. to DCRSET or DIR0EXEC, can leave the interface with a
+    /// wrong idea of the state of the core. Halt and resume the core through the interface
+    /// afterwards, to bring it back in sync.
+    pub fn write_raw_nexus_register(&mut self, address: u8, value: u32) -> Result<(), XtensaError> {
+        if address > 0x7f {
+            return Err(XtensaError::RegisterNotAvailable);
+        }
+
+        self.xdm.write_raw_nexus_register(address, value)
+    }
+
+    /// Reads the raw value of the power status register (PWRSTAT) of the selected core.
+    pub fn read_raw_power_status(&mut self) -> Result<u8, XtensaError> {
+        self.xdm.read_raw_power_status()
+    }
+
+    /// Writes `value` to the power control register (PWRCTL) of the selected core, and returns
+    /// its previous value.
+    ///
+    /// PWRCTL can't be read without writing it, as the value shifted in replaces it. Clearing
+    /// its wake-up bits may power down the debug module, after which the interface has to be
+    /// created again.
+    pub fn write_raw_power_control(&mut self, value: u8) -> Result<u8, XtensaError> {
+        self.xdm.write_raw_power_control(value)
+    }
+
+    /// Sets how memory accesses that fail because of transient errors, like glitches on the JTAG
+    /// lines, are retried before the error is reported.
+    pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+        self.retry_policy = policy;
+    }
+
+    /// Enables or disables adaptive clocking, if the probe supports it. With adaptive clocking,
+    /// the target paces the JTAG clock through RTCK, which keeps long connections stable.
+    pub fn set_adaptive_clocking(&mut self, enabled: bool) -> Result<(), XtensaError> {
+        self.xdm.probe.set_adaptive_clocking(enabled)?;
+        if !enabled {
+            // The fixed clock may be too fast for the connection.
+            self.xdm.negotiate_speed()?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the telemetry of the operations done through this interface.
+    pub fn telemetry(&self) -> &Telemetry {
+        &self.xdm.telemetry
+    }
+
+    /// Returns the telemetry of the operations done through this interface, e.g. to enable it.
+    pub fn telemetry_mut(&mut self) -> &mut Telemetry {
+        &mut self.xdm.telemetry
+    }
+
+    /// Returns the TRAX trace unit of the selected core.
+    pub fn trace(&mut self) -> XtensaTrace<'_> {
+        XtensaTrace::new(&mut self.xdm)
+    }
+
+    /// Returns the index of the core that is currently being debugged.
+    pub fn selected_core(&self) -> usize {
+        self.xdm.selected_core()
+    }
+
+    /// Selects the core that all further operations are performed on.
+    ///
+    /// `core` is the index of the core's TAP in the scan chain, e.g. 0 for the PRO CPU
+    /// and 1 for the APP CPU of the ESP32 and ESP32-S3.
+    pub fn select_core(&mut self, core: usize) -> Result<(), XtensaError> {
+        let previous = self.xdm.selected_core();
+        if core == previous {
+            return Ok(());
+        }
+
+        self.xdm.select_core(core)?;
+
+        let state = self
+            .inactive_cores
+            .remove(&core)
+            .unwrap_or_else(XtensaCommunicationInterfaceState::new);
+        let previous_state = std::mem::replace(&mut self.state, state);
+        self.inactive_cores.insert(previous, previous_state);
+
+        tracing::debug!("Selected core {}", core);
+
+        Ok(())
+    }
+
+    /// Configures the TAPs of all cores of the chip, usually taken from the target description.
+    pub fn set_core_taps(&mut self, taps: Vec<usize>) {
+        self.core_taps = taps;
+    }
+
+    /// Halts the running cores other than the selected one within `timeout`, and returns their
+    /// TAPs. Cores which are powered down are left alone, as they don't execute any code.
+    ///
+    /// The cores are only halted, without touching their registers, so that
+    /// [`Self::resume_cores`] lets them continue where they stopped.
+    pub fn halt_other_cores(&mut self, timeout: Duration) -> Result<Vec<usize>, XtensaError> {
+        let selected = self.selected_core();
+
+        let mut halted = Vec::new();
+        let result = (|| {
+            for tap in self.core_taps.clone() {
+                if tap == selected {
+                    continue;
+                }
+
+                self.select_core(tap)?;
+                if self.is_sleeping()? || self.is_halted()? {
+                    continue;
+                }
+
+                self.halt()?;
+                halted.push(tap);
+                self.wait_until_halted(timeout)?;
+            }
+
+            Ok(())
+        })();
+        let reselected = self.select_core(selected);
+
+        match result.and(reselected) {
+            Ok(()) => Ok(halted),
+            Err(error) => {
+                if let Err(resume_error) = self.resume_cores(&halted) {
+                    tracing::warn!("Failed to resume the other cores: {}", resume_error);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Resumes the cores at `taps`, e.g. those halted by [`Self::halt_other_cores`], and keeps
+    /// the selected core selected.
+    pub fn resume_cores(&mut self, taps: &[usize]) -> Result<(), XtensaError> {
+        let selected = self.selected_core();
+
+        let mut result = Ok(());
+        for &tap in taps {
+            result = result.and(self.select_core(tap).and_then(|_| self.resume()));
+        }
+
+        result.and(self.select_core(selected))
+    }
+
+    /// Raises INTLEVEL of the halted core to mask every interrupt below the debug level, e.g.
+    /// so that no interrupt handler runs while the core executes a routine of the ROM.
+    ///
+    /// PS is not restored afterwards, so save it before.
+    pub(super) fn mask_interrupts(&mut self) -> Result<(), XtensaError> {
+        let mut ps = self.read_register::<ProgramStatus>()?;
+        ps.set_intlevel(self.debug_level as u32 - 1);
+        self.write_register(ps)
+    }
+
+    /// Connects the selected core to the halt cross-trigger network.
+    ///
+    /// When enabled on every core, halting or resuming one core halts or resumes all of them.
+    pub fn set_cross_halt(&mut self, enabled: bool) -> Result<(), XtensaError> {
+        self.xdm.set_cross_trigger(enabled)
+    }
+
+    /// Returns whether the selected core is connected to the halt cross-trigger network.
+    pub fn cross_halt_enabled(&mut self) -> Result<bool, XtensaError> {
+        self.xdm.cross_trigger_enabled()
+    }
+
+    /// Halts the cores at the TAPs `cores` at the same time, by halting the first one with all
+    /// of them connected to the halt cross-trigger network.
+    ///
+    /// This only requests the halt. Afterwards, the cores are connected to the network as they
+    /// were before, and the selected core is one of `cores`.
+    pub fn halt_simultaneously(&mut self, cores: &[usize]) -> Result<(), XtensaError> {
+        let Some(&first) = cores.first() else {
+            return Ok(());
+        };
+
+        let mut disconnected = Vec::new();
+        let result = (|| {
+            for &core in cores {
+                self.select_core(core)?;
+                if !self.cross_halt_enabled()? {
+                    self.set_cross_halt(true)?;
+                    disconnected.push(core);
+                }
+            }
+
+            self.select_core(first)?;
+            self.halt()
+        })();
+
+        // Disconnect the cores again also if connecting or halting them failed, so that they
+        // don't keep halting each other.
+        let mut restored = Ok(());
+        for core in disconnected {
+            let disconnect = self
+                .select_core(core)
+                .and_then(|_| self.set_cross_halt(false));
+            restored = restored.and(disconnect);
+        }
+
+        result.and(restored)
+    }
+
+    /// Selects whether [`step`](Self::step) steps over or into pending interrupts.
+    pub fn set_step_mode(&mut self, mode: StepMode) {
+        self.state.step_mode = mode;
+    }
+
+    /// Returns the currently selected [`StepMode`].
+    pub fn step_mode(&self) -> StepMode {
+        self.state.step_mode
+    }
+
+    /// Selects the interrupt level of debug exceptions, which must match the `DEBUGLEVEL` the
+    /// cores were configured with. Defaults to 6, which is used by the Espressif chips.
+    pub fn set_debug_level(&mut self, level: u8) -> Result<(), XtensaError> {
+        self.debug_level = DebugLevel::try_from(level)?;
+        Ok(())
+    }
+
+    /// Sets the number of instruction breakpoint units (`NIBREAK`) and data breakpoint units
+    /// (`NDBREAK`) the cores were configured with. Both default to 2, the maximum.
+    pub fn set_break_units(&mut self, ibreak: u8, dbreak: u8) -> Result<(), XtensaError> {
+        for count in [ibreak, dbreak] {
+            if count > 2 {
+                return Err(XtensaError::InvalidBreakUnitCount(count));
+            }
+        }
+
+        self.hw_breakpoint_num = ibreak as u32;
+        self.hw_watchpoint_num = dbreak as u32;
+        Ok(())
+    }
+
+    /// Selects whether the cores implement the Windowed Register Option. Without it, the cores
+    /// only have the 16 address registers visible to the program.
+    pub fn set_windowed_registers(&mut self, windowed: bool) {
+        self.windowed_registers = windowed;
+        self.physical_register_num = if windowed { 64 } else { 16 };
+    }
+
+    /// Returns whether the cores implement the Windowed Register Option.
+    pub fn windowed_registers(&self) -> bool {
+        self.windowed_registers
+    }
+
+    /// Declares whether the cores implement the Floating-Point Coprocessor, instead of probing
+    /// each core for it.
+    pub fn set_fpu_present(&mut self, present: Option<bool>) {
+        self.fpu_config = present;
+    }
+
+    /// Configures which memory regions need cache maintenance after the debugger writes them.
+    ///
+    /// Regions that are not listed are assumed to be coherent.
+    pub fn set_cached_regions(&mut self, regions: Vec<CachedRegion>) {
+        self.cached_regions = regions;
+    }
+
+    /// Returns the memory regions that need cache maintenance after writes.
+    pub fn cached_regions(&self) -> &[CachedRegion] {
+        &self.cached_regions
+    }
+
+    /// Records whether encrypted flash read through the cache is decrypted.
+    pub fn set_flash_decryption(&mut self, enabled: bool) {
+        self.flash_decryption = enabled;
+    }
+
+    /// Returns whether encrypted flash read through the cache is decrypted.
+    pub fn flash_decryption(&self) -> bool {
+        self.flash_decryption
+    }
+
+    /// Selects whether the debugger may stop the watchdogs of the chip. If not, the chip may be
+    /// reset by a watchdog while it is halted.
+    pub fn set_stop_watchdogs(&mut self, enabled: bool) {
+        self.stop_watchdogs = enabled;
+    }
+
+    /// Permanently disables the `watchdogs`, e.g. after connecting or resetting the chip.
+    pub fn disable_watchdogs(&mut self, watchdogs: &[Watchdog]) -> Result<(), ProbeRsError> {
+        if !self.stop_watchdogs || watchdogs.is_empty() {
+            return Ok(());
+        }
+
+        tracing::info!("Disabling watchdogs...");
+
+        // Watchdogs that are disabled must not be re-enabled when the cores resume.
+        self.suspended_watchdogs = None;
+
+        for watchdog in watchdogs {
+            let config = self.read_word_32(watchdog.config)?;
+            self.write_watchdog_config(watchdog, watchdog.stopped(config))?;
+        }
+
+        Ok(())
+    }
+
+    /// Stops the `watchdogs` while the cores are halted, saving their configuration so that
+    /// [`resume_watchdogs`](Self::resume_watchdogs) can restore it.
+    ///
+    /// Does nothing if the watchdogs are already suspended.
+    pub fn suspend_watchdogs(&mut self, watchdogs: &[Watchdog]) -> Result<(), ProbeRsError> {
+        if !self.stop_watchdogs || self.suspended_watchdogs.is_some() {
+            return Ok(());
+        }
+
+        let mut suspended = Vec::new();
+        for watchdog in watchdogs {
+            let config = self.read_word_32(watchdog.config)?;
+            let stopped = watchdog.stopped(config);
+            if stopped == config {
+                // The watchdog is not running.
+                continue;
+            }
+
+            tracing::debug!("Suspending watchdog at {:#010x}", watchdog.config);
+            self.write_watchdog_config(watchdog, stopped)?;
+            suspended.push((*watchdog, config));
+        }
+        self.suspended_watchdogs = Some(suspended);
+
+        Ok(())
+    }
+
+    /// Restores the watchdogs stopped by [`suspend_watchdogs`](Self::suspend_watchdogs), feeding
+    /// them first so that the time spent halted does not count towards their timeout.
+    pub fn resume_watchdogs(&mut self) -> Result<(), ProbeRsError> {
+        let suspended = self.suspended_watchdogs.take().unwrap_or_default();
+        for (watchdog, config) in suspended {
+            tracing::debug!("Resuming watchdog at {:#010x}", watchdog.config);
+
+            self.write_word_32(watchdog.write_protect, watchdog.unlock_key)?;
+            if let Some((feed, value)) = watchdog.feed {
+                self.write_word_32(feed, value)?;
+            }
+            self.write_word_32(watchdog.config, config)?;
+            self.write_word_32(watchdog.write_protect, 0)?;
+        }
+
+        Ok(())
+    }
+
+    fn write_watchdog_config(
+        &mut self,
+        watchdog: &Watchdog,
+        config: u32,
+    ) -> Result<(), ProbeRsError> {
+        self.write_word_32(watchdog.write_protect, watchdog.unlock_key)?; // write protection off
+        self.write_word_32(watchdog.config, config)?;
+        self.write_word_32(watchdog.write_protect, 0)?; // write protection on
+
+        Ok(())
+    }
+
+    /// Configures the access widths that memory regions tolerate, usually taken from the target's
+    /// memory map.
+    ///
+    /// Memory is always read in 32-bit words. Narrow writes use halfword and byte stores in
+    /// [`AccessWidth::Any`] regions, and are emulated with a read-modify-write of the containing
+    /// word elsewhere. Regions that are not listed are treated as [`AccessWidth::Word32`].
+    pub fn set_access_widths(&mut self, regions: Vec<(Range<u64>, AccessWidth)>) {
+        self.access_regions = regions;
+    }
+
+    /// Configures address ranges that are accessed at a different address than the one
+    /// requested, usually taken from the target description.
+    pub fn set_address_translations(&mut self, translations: Vec<AddressTranslation>) {
+        self.address_translations = translations;
+    }
+
+    /// Returns the address at which `address` is accessed.
+    pub fn translate_address(&self, address: u64) -> u64 {
+        self.address_translations
+            .iter()
+            .find_map(|translation| translation.translate(address))
+            .unwrap_or(address)
+    }
+
+    /// Splits an access to `len` bytes at `address` into pieces that are translated as a whole,
+    /// as pairs of the translated address and the range of the accessed bytes.
+    fn translated_accesses(&self, address: u64, len: usize) -> Vec<(u64, Range<usize>)> {
+        let end = address + len as u64;
+
+        let mut accesses = Vec::new();
+        let mut start = address;
+        while start < end {
+            let translation = self
+                .address_translations
+                .iter()
+                .find(|translation| translation.range.contains(&start));
+
+            let piece_end = match translation {
+                Some(translation) => translation.range.end,
+                None => self
+                    .address_translations
+                    .iter()
+                    .map(|translation| translation.range.start)
+                    .filter(|&range_start| range_start > start)
+                    .min()
+                    .unwrap_or(end),
+            }
+            .min(end);
+
+            accesses.push((
+                self.translate_address(start),
+                (start - address) as usize..(piece_end - address) as usize,
+            ));
+            start = piece_end;
+        }
+
+        accesses
+    }
+
+    /// Returns the access width policy of the memory region containing `address`.
+    fn access_width(&self, address: u64) -> AccessWidth {
+        self.access_regions
+            .iter()
+            .find(|(range, _)| range.contains(&address))
+            .map(|(_, width)| *width)
+            .unwrap_or(AccessWidth::Word32)
+    }
+
+    /// Returns an error if an access to `len` bytes at `address` is not word aligned, but touches
+    /// a region that only allows aligned 32-bit accesses.
+    fn check_access_width(&self, address: u64, len: usize) -> Result<(), XtensaError> {
+        if address % 4 == 0 && len % 4 == 0 {
+            return Ok(());
+        }
+
+        let access = address..address + len as u64;
+        let strict = self.access_regions.iter().any(|(range, width)| {
+            *width == AccessWidth::Word32Strict
+                && range.start < access.end
+                && access.start < range.end
+        });
+
+        if strict {
+            return Err(XtensaError::UnsupportedAccessWidth { address, len });
+        }
+
+        Ok(())
+    }
+
     pub fn halt_on_reset(&mut self, en: bool) -> Result<(), XtensaError> {
         self.xdm.halt_on_reset(en);
         Ok(())
@@ -169,42 +903,214 @@ impl XtensaCommunicationInterface {
         Ok(())
     }
 
-    pub fn reset(&mut self) -> Result<(), XtensaError> {
-        match self.reset_and_halt(Duration::from_millis(500)) {
-            Ok(_) => {
-                self.resume()?;
-
-                Ok(())
-            }
-            Err(error) => Err(XtensaError::DebugProbe(DebugProbeError::Other(
-                anyhow::anyhow!("Error during reset: {:?}", error),
-            ))),
+    /// Restores the state the debugger changed in order to operate the core, without resuming it.
+    ///
+    /// This disarms the instruction counter used for stepping, and restores the registers that
+    /// were clobbered by the debugger. Nothing needs restoring if the core is running.
+    pub fn restore_core_state(&mut self) -> Result<(), XtensaError> {
+        if !self.is_halted()? {
+            return Ok(());
         }
+
+        self.write_registers(&[
+            (Register::Special(SpecialRegister::ICountLevel), 0),
+            (Register::Special(SpecialRegister::ICount), 0),
+        ])?;
+        self.restore_registers()
+    }
+
+    pub fn reset(&mut self) -> Result<(), XtensaError> {
+        self.reset_and_halt(Duration::from_millis(500))?;
+        self.resume()
+            .map_err(XtensaError::reset_failed(ResetPhase::Resume))
     }
 
     pub fn reset_and_halt(&mut self, timeout: Duration) -> Result<(), XtensaError> {
-        self.xdm.target_reset_assert()?;
+        self.measured("reset_and_halt", |this| {
+            this.xdm
+                .target_reset_assert()
+                .map_err(XtensaError::reset_failed(ResetPhase::Assert))?;
+            this.reset_deassert_and_halt(timeout)
+        })
+    }
+
+    /// Releases the probe's reset line and halts the core before it executes the first
+    /// instruction after the reset.
+    pub fn reset_deassert_and_halt(&mut self, timeout: Duration) -> Result<(), XtensaError> {
         self.xdm.halt_on_reset(true);
-        self.xdm.target_reset_deassert()?;
-        self.wait_for_core_halted(timeout)?;
+        self.xdm
+            .target_reset_deassert()
+            .map_err(XtensaError::reset_failed(ResetPhase::Deassert))?;
+        self.wait_for_core_halted(timeout)
+            .map_err(XtensaError::reset_failed(ResetPhase::Halt))?;
         self.xdm.halt_on_reset(false);
 
         // TODO: this is only necessary to run code, so this might not be the best place
-        self.write_register_untyped(Register::CurrentPs, 0x40021)?;
+        self.write_register(ProgramStatus::code_execution())?;
 
         Ok(())
     }
 
+    /// Resets the system by setting the bits of `value` in the chip's reset control register at
+    /// `address`, and halts the core before it executes the first instruction after the reset.
+    ///
+    /// The other bits of the register are kept, as they may control other parts of the chip,
+    /// e.g. whether the other cores are stalled.
+    ///
+    /// Unlike [`reset_and_halt`](Self::reset_and_halt), this does not rely on the probe's reset
+    /// line, and it keeps the debug module powered through the reset.
+    pub fn reset_system_and_halt(
+        &mut self,
+        timeout: Duration,
+        address: u64,
+        value: u32,
+    ) -> Result<(), XtensaError> {
+        if !self.is_halted()? {
+            self.halt()?;
+            self.wait_for_core_halted(timeout)?;
+        }
+
+        let mut current = [0; 4];
+        self.read_memory(address, &mut current)?;
+        let value = u32::from_le_bytes(current) | value;
+
+        // Request a debug interrupt so that the core stops right after the reset.
+        self.xdm.halt()?;
+
+        // The reset may take effect before the write completes, so its result is meaningless.
+        if let Err(error) = self.write_memory(address, &value.to_le_bytes()) {
+            tracing::debug!("Error while triggering the system reset: {:?}", error);
+        }
+
+        // Registers saved before the reset are stale now, so they must not be restored.
+        self.state.saved_registers.clear();
+        self.state.is_halted = false;
+        self.xdm.clear_exec_exception()?;
+
+        self.wait_for_core_halted(timeout)
+            .map_err(XtensaError::reset_failed(ResetPhase::Halt))
+    }
+
+    /// Returns what was reset along with the selected core since the last call, if it was reset,
+    /// and clears the reset flags of the power status register.
+    pub fn take_reset_cause(&mut self) -> Result<Option<ResetCause>, XtensaError> {
+        let status = self.xdm.take_reset_flags()?;
+
+        Ok(if status.debug_was_reset() {
+            Some(ResetCause::System)
+        } else if status.core_was_reset() {
+            Some(ResetCause::Core)
+        } else {
+            None
+        })
+    }
+
+    /// Returns whether the chip is in a low-power state, in which the debug module or the
+    /// selected core is powered down.
+    pub fn is_sleeping(&mut self) -> Result<bool, XtensaError> {
+        let status = self.xdm.power_status()?;
+
+        Ok(!status.debug_domain_on() || !status.core_domain_on() || !self.xdm.is_initialized())
+    }
+
+    /// Waits up to `timeout` for the chip to wake up from a low-power state, and initializes the
+    /// debug module if it was powered down until then.
+    pub fn wait_for_wake_up(&mut self, timeout: Duration) -> Result<(), XtensaError> {
+        let start = Instant::now();
+        loop {
+            let status = self.xdm.power_status()?;
+            if status.debug_domain_on() && status.core_domain_on() {
+                break;
+            }
+            if start.elapsed() > timeout {
+                return Err(XtensaError::Sleeping);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        self.xdm.ensure_initialized()
+    }
+
+    /// Power-cycles the chip, or pulses its reset line if the probe can't switch the chip's power,
+    /// and halts the core once its debug module is reachable again.
+    ///
+    /// Unlike the other resets, this also resets the power domains of the chip, which recovers it
+    /// from sleep states that power down the debug module. The core runs for a short time before
+    /// it can be halted.
+    pub fn power_on_reset_and_halt(&mut self, timeout: Duration) -> Result<(), XtensaError> {
+        /// How long the chip is kept without power, for its supply to drain.
+        const POWER_OFF_TIME: Duration = Duration::from_millis(200);
+        /// How long the reset line is asserted if the probe can't switch the chip's power.
+        const RESET_PULSE_TIME: Duration = Duration::from_millis(10);
+
+        self.measured("power_on_reset_and_halt", |this| {
+            let probe = &mut this.xdm.probe;
+            let reset = if probe.supports_target_power() {
+                probe.target_power_cycle(POWER_OFF_TIME)
+            } else {
+                probe.target_reset_pulse(RESET_PULSE_TIME)
+            };
+            reset
+                .map_err(XtensaError::from)
+                .map_err(XtensaError::reset_failed(ResetPhase::Assert))?;
+
+            // Everything the debugger knew about the cores is stale now.
+            this.state.saved_registers.clear();
+            this.state.is_halted = false;
+            for state in this.inactive_cores.values_mut() {
+                state.saved_registers.clear();
+                state.is_halted = false;
+            }
+            this.suspended_watchdogs = None;
+
+            this.xdm
+                .reinit()
+                .map_err(XtensaError::reset_failed(ResetPhase::Deassert))?;
+
+            this.halt()?;
+            this.wait_for_core_halted(timeout)
+                .map_err(XtensaError::reset_failed(ResetPhase::Halt))?;
+
+            // TODO: this is only necessary to run code, so this might not be the best place
+            this.write_register(ProgramStatus::code_execution())?;
+
+            Ok(())
+        })
+    }
+
     pub fn halt(&mut self) -> Result<(), XtensaError> {
         tracing::debug!("Halting core");
-        self.xdm.halt()
+        self.measured("halt", |this| this.xdm.halt())
     }
 
     pub fn is_halted(&mut self) -> Result<bool, XtensaError> {
         self.xdm.is_halted()
     }
 
+    /// Samples the PC of the running core without halting it.
+    pub fn sample_pc(&mut self) -> Result<u32, XtensaError> {
+        self.xdm.read_debug_pc()
+    }
+
     pub fn wait_for_core_halted(&mut self, timeout: Duration) -> Result<(), XtensaError> {
+        self.measured("wait_for_core_halted", |this| {
+            this.wait_for_core_halted_impl(timeout)
+        })
+    }
+
+    fn wait_for_core_halted_impl(&mut self, timeout: Duration) -> Result<(), XtensaError> {
+        self.wait_until_halted(timeout)?;
+
+        // Force a low INTLEVEL
+        // TODO: do this only if we set a breakpoint or watchpoint or single step
+        let mut ps = self.read_register::<ProgramStatus>()?;
+        ps.set_intlevel(1);
+        self.write_register(ps)?;
+
+        Ok(())
+    }
+
+    fn wait_until_halted(&mut self, timeout: Duration) -> Result<(), XtensaError> {
         let now = Instant::now();
         while !self.is_halted()? {
             if now.elapsed() > timeout {
@@ -216,15 +1122,36 @@ impl XtensaCommunicationInterface {
         tracing::debug!("Core halted");
         self.state.is_halted = true;
 
-        // Force a low INTLEVEL
-        // TODO: do this only if we set a breakpoint or watchpoint or single step
-        let old_ps = self.read_register_untyped(Register::CurrentPs)?;
-        self.write_register_untyped(Register::CurrentPs, (old_ps & !0xF) | 0x1)?;
-
         Ok(())
     }
 
     pub fn step(&mut self) -> Result<(), XtensaError> {
+        self.measured("step", |this| this.step_impl())
+    }
+
+    fn step_impl(&mut self) -> Result<(), XtensaError> {
+        let old_ps = self.read_register::<ProgramStatus>()?;
+
+        // Raise INTLEVEL to mask every interrupt below the debug level, so that ICOUNT can only
+        // trigger on the next instruction of the interrupted code.
+        let masked_ps = (self.state.step_mode == StepMode::OverInterrupts)
+            .then(|| old_ps.masked_for_step(self.debug_level));
+        if let Some(masked_ps) = masked_ps {
+            self.write_register(masked_ps)?;
+        }
+
+        let stepped = self.step_instruction();
+
+        // Restore ICOUNT and PS also if the step failed, unless the core is out of reach.
+        if stepped.is_err() && !self.is_halted().unwrap_or(false) {
+            return stepped;
+        }
+        let restored = self.restore_after_step(old_ps, masked_ps);
+
+        stepped.and(restored)
+    }
+
+    fn step_instruction(&mut self) -> Result<(), XtensaError> {
         self.write_register_untyped(
             Register::Special(SpecialRegister::ICountLevel),
             self.debug_level as u32,
@@ -234,23 +1161,90 @@ impl XtensaCommunicationInterface {
         self.write_register_untyped(Register::Special(SpecialRegister::ICount), -2_i32 as u32)?;
 
         self.resume()?;
-        self.wait_for_core_halted(Duration::from_millis(100))?;
+        self.wait_until_halted(Duration::from_millis(100))
+    }
 
+    fn restore_after_step(
+        &mut self,
+        old_ps: ProgramStatus,
+        masked_ps: Option<ProgramStatus>,
+    ) -> Result<(), XtensaError> {
         // Avoid stopping again
         self.write_register_untyped(
             Register::Special(SpecialRegister::ICount),
             self.debug_level as u32 + 1,
         )?;
 
-        Ok(())
+        let new_ps = self.read_register::<ProgramStatus>()?;
+        match new_ps.restored_after_step(old_ps, masked_ps) {
+            Some(ps) => self.write_register(ps),
+            None => Ok(()),
+        }
     }
 
     pub fn resume(&mut self) -> Result<(), XtensaError> {
         tracing::debug!("Resuming core");
         self.state.is_halted = false;
-        self.xdm.resume()?;
+        self.measured("resume", |this| this.xdm.resume())
+    }
 
-        Ok(())
+    /// Runs `op`, recording it as `operation` in the telemetry.
+    fn measured<R>(
+        &mut self,
+        operation: &'static str,
+        op: impl FnOnce(&mut Self) -> Result<R, XtensaError>,
+    ) -> Result<R, XtensaError> {
+        let start = self.xdm.telemetry.start();
+        let result = op(self);
+        self.xdm.telemetry.record(operation, start, result.is_ok());
+        result
+    }
+
+    /// Executes `op`, and repeats it as configured by the retry policy while it fails with
+    /// transient errors. `op` must be safe to repeat after it failed part way through.
+    fn retried<R>(
+        &mut self,
+        mut op: impl FnMut(&mut Self) -> Result<R, XtensaError>,
+    ) -> Result<R, XtensaError> {
+        let mut retries = 0;
+        loop {
+            let error = match op(self) {
+                Ok(result) => return Ok(result),
+                Err(error) => error,
+            };
+
+            let backoff = error
+                .error_class()
+                .and_then(|class| self.retry_policy.backoff(class, retries));
+            let Some(backoff) = backoff else {
+                return Err(error);
+            };
+
+            retries += 1;
+            tracing::debug!("Retry {} after transient error: {}", retries, error);
+            std::thread::sleep(backoff);
+
+            // The failed attempt may have left the debug module with a pending exception.
+            if let Err(error) = self.xdm.clear_exec_exception() {
+                tracing::debug!("Failed to clear the debug module status: {}", error);
+            }
+        }
+    }
+
+    /// Executes `op` if the core is halted, and returns [`XtensaError::NotHalted`] otherwise.
+    ///
+    /// Memory is accessed by executing instructions on the core, which is only possible in debug
+    /// mode. The core is never halted implicitly here, see
+    /// [`Session::set_implicit_halts`](crate::Session::set_implicit_halts) instead.
+    fn halted_access<R>(
+        &mut self,
+        op: impl FnOnce(&mut Self) -> Result<R, XtensaError>,
+    ) -> Result<R, XtensaError> {
+        if !self.is_halted()? {
+            return Err(XtensaError::NotHalted);
+        }
+
+        op(self)
     }
 
     fn read_cpu_register(&mut self, register: CpuRegister) -> Result<u32, XtensaError> {
@@ -295,14 +1289,266 @@ impl XtensaCommunicationInterface {
         Ok(())
     }
 
-    fn write_cpu_register(&mut self, register: CpuRegister, value: u32) -> Result<(), XtensaError> {
-        tracing::debug!("Writing {:x} to register: {:?}", value, register);
+    /// Enables the Floating-Point Coprocessor (CP0) in CPENABLE. Returns the save key to restore
+    /// the original CPENABLE value.
+    fn enable_fpu(&mut self) -> Result<Option<Register>, XtensaError> {
+        let key = self.save_register(SpecialRegister::CpEnable)?;
+
+        let cpenable = self.read_special_register(SpecialRegister::CpEnable)?;
+        if cpenable & 0x1 == 0 {
+            self.write_special_register(SpecialRegister::CpEnable, cpenable | 0x1)?;
+        }
+
+        Ok(key)
+    }
+
+    fn read_fpu_register(&mut self, register: FpuRegister) -> Result<u32, XtensaError> {
+        let save_key = self.save_register(CpuRegister::A3)?;
+        let cpenable_key = self.enable_fpu()?;
+
+        // Move floating point register into the scratch register
+        self.execute_instruction(Instruction::Rfr(register, CpuRegister::A3))?;
+
+        // Read the scratch register
+        let result = self.read_cpu_register(CpuRegister::A3)?;
+
+        self.restore_register(cpenable_key)?;
+        self.restore_register(save_key)?;
+
+        Ok(result)
+    }
+
+    fn write_fpu_register(&mut self, register: FpuRegister, value: u32) -> Result<(), XtensaError> {
+        tracing::debug!("Writing FPU register: {:?}", register);
+        let save_key = self.save_register(CpuRegister::A3)?;
+        let cpenable_key = self.enable_fpu()?;
+
+        self.write_cpu_register(CpuRegister::A3, value)?;
+
+        // scratch -> target floating point register
+        self.execute_instruction(Instruction::Wfr(register, CpuRegister::A3))?;
+
+        self.restore_register(cpenable_key)?;
+        self.restore_register(save_key)?;
+
+        Ok(())
+    }
+
+    fn read_user_register(&mut self, register: UserRegister) -> Result<u32, XtensaError> {
+        let save_key = self.save_register(CpuRegister::A3)?;
+        let cpenable_key = self.enable_fpu()?;
+
+        // Read user register into the scratch register
+        self.execute_instruction(Instruction::Rur(register, CpuRegister::A3))?;
+
+        // Read the scratch register
+        let result = self.read_cpu_register(CpuRegister::A3)?;
+
+        self.restore_register(cpenable_key)?;
+        self.restore_register(save_key)?;
+
+        Ok(result)
+    }
+
+    fn write_user_register(
+        &mut self,
+        register: UserRegister,
+        value: u32,
+    ) -> Result<(), XtensaError> {
+        tracing::debug!("Writing user register: {:?}", register);
+        let save_key = self.save_register(CpuRegister::A3)?;
+        let cpenable_key = self.enable_fpu()?;
+
+        self.write_cpu_register(CpuRegister::A3, value)?;
+
+        // scratch -> target user register
+        self.execute_instruction(Instruction::Wur(register, CpuRegister::A3))?;
+
+        self.restore_register(cpenable_key)?;
+        self.restore_register(save_key)?;
+
+        Ok(())
+    }
+
+    /// Returns whether a previous call to [`detect_fpu`](Self::detect_fpu) found the
+    /// Floating-Point Coprocessor.
+    pub fn fpu_present(&self) -> bool {
+        self.fpu_config.or(self.state.fpu_present) == Some(true)
+    }
+
+    /// Returns whether the core implements the Floating-Point Coprocessor Option.
+    ///
+    /// The first call probes the core by trying to read FCR, so the core must be halted.
+    pub fn detect_fpu(&mut self) -> Result<bool, XtensaError> {
+        if let Some(present) = self.fpu_config.or(self.state.fpu_present) {
+            return Ok(present);
+        }
+
+        let present = match self.read_user_register(UserRegister::Fcr) {
+            Ok(_) => true,
+            Err(XtensaError::XdmError(XdmError::ExecExeception)) => {
+                // The failed access left the scratch registers modified.
+                self.restore_registers()?;
+                false
+            }
+            Err(error) => return Err(error),
+        };
+
+        tracing::debug!("Floating-Point Coprocessor present: {}", present);
+        self.state.fpu_present = Some(present);
+
+        Ok(present)
+    }
+
+    fn write_cpu_register(&mut self, register: CpuRegister, value: u32) -> Result<(), XtensaError> {
+        tracing::debug!("Writing {:x} to register: {:?}", value, register);
+
+        self.xdm.write_ddr(value)?;
+        self.xdm
+            .execute_instruction(Instruction::Rsr(SpecialRegister::Ddr, register))?;
+
+        Ok(())
+    }
+
+    /// Returns the number of physical address registers.
+    pub fn physical_register_count(&self) -> u8 {
+        self.physical_register_num
+    }
+
+    /// Reads the register window state.
+    pub fn register_window(&mut self) -> Result<RegisterWindow, XtensaError> {
+        Ok(RegisterWindow {
+            base: self.read_special_register(SpecialRegister::Windowbase)?,
+            start: self.read_special_register(SpecialRegister::Windowstart)?,
+        })
+    }
+
+    /// Rotates the register window so that the physical register `index` becomes visible, then
+    /// calls `access` with the logical register it is mapped to.
+    fn with_physical_register<T>(
+        &mut self,
+        index: u8,
+        access: impl FnOnce(&mut Self, CpuRegister) -> Result<T, XtensaError>,
+    ) -> Result<T, XtensaError> {
+        if index >= self.physical_register_num {
+            return Err(XtensaError::RegisterNotAvailable);
+        }
+
+        // Without register windows, the physical registers are the logical ones.
+        if !self.windowed_registers {
+            return access(self, CpuRegister::try_from(index)?);
+        }
+
+        let window_base = self.read_special_register(SpecialRegister::Windowbase)?;
+        let (rotation, logical) =
+            RegisterWindow::rotation_for(window_base, index, self.physical_register_num);
+
+        // While the window is rotated, only access the logical register directly through DDR.
+        // Saved scratch registers refer to the original window.
+        if rotation != 0 {
+            self.execute_instruction(Instruction::Rotw(rotation))?;
+        }
+
+        let result = access(self, logical);
+
+        if rotation != 0 {
+            self.execute_instruction(Instruction::Rotw(-rotation))?;
+        }
+
+        result
+    }
+
+    /// Reads a physical address register, regardless of the current register window.
+    pub fn read_physical_register(&mut self, index: u8) -> Result<u32, XtensaError> {
+        self.with_physical_register(index, |this, register| this.read_cpu_register(register))
+    }
+
+    /// Writes a physical address register, regardless of the current register window.
+    pub fn write_physical_register(&mut self, index: u8, value: u32) -> Result<(), XtensaError> {
+        self.with_physical_register(index, |this, register| {
+            this.write_cpu_register(register, value)
+        })
+    }
+
+    /// Reads all physical address registers.
+    ///
+    /// The registers clobbered by the debugger are restored first, so that the program's values
+    /// are read.
+    pub fn read_register_file(&mut self) -> Result<Vec<u32>, XtensaError> {
+        self.restore_registers()?;
+
+        (0..self.physical_register_num)
+            .map(|index| self.read_physical_register(index))
+            .collect()
+    }
+
+    /// Writes all physical address registers, e.g. with values read by
+    /// [`Self::read_register_file`].
+    ///
+    /// The registers clobbered by the debugger are restored first, so that restoring them when
+    /// the core resumes doesn't undo the write.
+    pub fn write_register_file(&mut self, values: &[u32]) -> Result<(), XtensaError> {
+        self.restore_registers()?;
+
+        for (index, value) in values.iter().enumerate() {
+            self.write_physical_register(index as u8, *value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Computes the stack contents that spilling the live register windows would produce.
+    ///
+    /// Frames of the windowed ABI save their caller's registers on the stack only when the
+    /// register file overflows. This returns the values of the callers' registers that are still
+    /// held in the register file, along with where they would be saved, without modifying memory.
+    pub fn window_spill_areas(&mut self) -> Result<Vec<SpillArea>, XtensaError> {
+        let window = self.register_window()?;
+        let live = window.live_frames(self.physical_register_num);
+
+        let mut areas = Vec::new();
+        for (index, pair) in live.windows(2).enumerate() {
+            let (callee, caller) = (pair[0], pair[1]);
+
+            // The caller's a0-a3 are saved in the 16 bytes below the callee's stack pointer.
+            let callee_sp = self.read_physical_register(callee * 4 + 1)?;
+            let mut base = Vec::with_capacity(4);
+            for register in 0..4 {
+                base.push(self.read_physical_register(caller * 4 + register)?);
+            }
+            areas.push(SpillArea {
+                address: callee_sp.wrapping_sub(16) as u64,
+                words: base,
+            });
+
+            // The rest of a call8/call12 caller's window is saved at the top of its own frame,
+            // which ends at the stack pointer of its caller.
+            let window_units = window.distance(caller, callee, self.physical_register_num);
+            if !(2..=3).contains(&window_units) {
+                continue;
+            }
+
+            let outer_sp = match live.get(index + 2) {
+                Some(&outer) => self.read_physical_register(outer * 4 + 1)?,
+                None => {
+                    let caller_sp = self.read_physical_register(caller * 4 + 1)?;
+                    let mut bytes = [0; 4];
+                    self.read_memory(caller_sp.wrapping_sub(12) as u64, &mut bytes)?;
+                    u32::from_le_bytes(bytes)
+                }
+            };
 
-        self.xdm.write_ddr(value)?;
-        self.xdm
-            .execute_instruction(Instruction::Rsr(SpecialRegister::Ddr, register))?;
+            let mut extra = Vec::with_capacity(4 * (window_units as usize - 1));
+            for register in 4..window_units * 4 {
+                extra.push(self.read_physical_register(caller * 4 + register)?);
+            }
+            areas.push(SpillArea {
+                address: outer_sp.wrapping_sub(16 + 16 * (window_units as u32 - 1)) as u64,
+                words: extra,
+            });
+        }
 
-        Ok(())
+        Ok(areas)
     }
 
     fn debug_execution_error_impl(&mut self, status: XdmError) -> Result<(), XtensaError> {
@@ -347,6 +1593,40 @@ impl XtensaCommunicationInterface {
         status
     }
 
+    /// Executes `program` on the core, one instruction at a time.
+    ///
+    /// The instructions run in On-Chip Debug mode, on the current register state, and the
+    /// registers they modify are left modified. Wrap the call in [`Self::with_scratch_registers`]
+    /// to restore them afterwards. The core must be halted, otherwise [`XtensaError::NotHalted`]
+    /// is returned.
+    ///
+    /// Execution stops at the first instruction that raises an exception, which is reported as
+    /// [`XtensaError::ProgramException`].
+    pub fn execute_program(&mut self, program: &[Instruction]) -> Result<(), XtensaError> {
+        self.halted_access(|this| {
+            for (index, instruction) in program.iter().enumerate() {
+                match this.execute_instruction(*instruction) {
+                    Ok(()) => {}
+                    Err(XtensaError::XdmError(XdmError::ExecExeception)) => {
+                        let values = this.read_registers(&[
+                            SpecialRegister::ExcCause.into(),
+                            SpecialRegister::ExcVaddr.into(),
+                        ])?;
+
+                        return Err(XtensaError::ProgramException {
+                            index,
+                            cause: values[0],
+                            address: values[1],
+                        });
+                    }
+                    Err(error) => return Err(error),
+                }
+            }
+
+            Ok(())
+        })
+    }
+
     fn read_ddr_and_execute(&mut self) -> Result<u32, XtensaError> {
         let status = self.xdm.read_ddr_and_execute();
         if let Err(XtensaError::XdmError(err)) = status {
@@ -369,6 +1649,10 @@ impl XtensaCommunicationInterface {
         Ok(R::from_u32(value))
     }
 
+    pub fn write_register<R: TypedRegister>(&mut self, value: R) -> Result<(), XtensaError> {
+        self.write_register_untyped(R::register(), value.to_u32())
+    }
+
     pub fn read_register_untyped(
         &mut self,
         register: impl Into<Register>,
@@ -376,6 +1660,8 @@ impl XtensaCommunicationInterface {
         match register.into() {
             Register::Cpu(register) => self.read_cpu_register(register),
             Register::Special(register) => self.read_special_register(register),
+            Register::Fpu(register) => self.read_fpu_register(register),
+            Register::User(register) => self.read_user_register(register),
             Register::CurrentPc => self.read_special_register(self.debug_level.pc()),
             Register::CurrentPs => self.read_special_register(self.debug_level.ps()),
         }
@@ -389,11 +1675,194 @@ impl XtensaCommunicationInterface {
         match register.into() {
             Register::Cpu(register) => self.write_cpu_register(register, value),
             Register::Special(register) => self.write_special_register(register, value),
+            Register::Fpu(register) => self.write_fpu_register(register, value),
+            Register::User(register) => self.write_user_register(register, value),
             Register::CurrentPc => self.write_special_register(self.debug_level.pc(), value),
             Register::CurrentPs => self.write_special_register(self.debug_level.ps(), value),
         }
     }
 
+    /// Returns the special register that backs `register`, if it is one.
+    fn as_special_register(&self, register: Register) -> Option<SpecialRegister> {
+        match register {
+            Register::Special(register) => Some(register),
+            Register::CurrentPc => Some(self.debug_level.pc()),
+            Register::CurrentPs => Some(self.debug_level.ps()),
+            _ => None,
+        }
+    }
+
+    /// Reads multiple registers.
+    ///
+    /// The instructions and DDR accesses for all CPU and special registers are sent to the probe
+    /// in a single JTAG transaction, which is much faster than reading the registers one by one.
+    /// FPU and user registers are read individually.
+    pub fn read_registers(&mut self, registers: &[Register]) -> Result<Vec<u32>, XtensaError> {
+        self.measured("read_registers", |this| this.read_registers_impl(registers))
+    }
+
+    fn read_registers_impl(&mut self, registers: &[Register]) -> Result<Vec<u32>, XtensaError> {
+        let mut values = vec![0; registers.len()];
+
+        // Save the scratch register before queueing anything, as this executes the queue.
+        let uses_scratch = registers
+            .iter()
+            .any(|register| self.as_special_register(*register).is_some());
+        let key = if uses_scratch {
+            self.save_register(CpuRegister::A3)?
+        } else {
+            None
+        };
+
+        // CPU registers are read first, reading special registers clobbers the scratch register.
+        let mut positions = vec![];
+        let mut readers = vec![];
+        for (i, register) in registers.iter().enumerate() {
+            if let Register::Cpu(register) = register {
+                self.xdm.schedule_execute_instruction(Instruction::Wsr(
+                    SpecialRegister::Ddr,
+                    *register,
+                ));
+                positions.push(i);
+                readers.push(self.xdm.schedule_read_ddr());
+            }
+        }
+        for (i, register) in registers.iter().enumerate() {
+            if let Some(register) = self.as_special_register(*register) {
+                self.xdm
+                    .schedule_execute_instruction(Instruction::Rsr(register, CpuRegister::A3));
+                self.xdm.schedule_execute_instruction(Instruction::Wsr(
+                    SpecialRegister::Ddr,
+                    CpuRegister::A3,
+                ));
+                positions.push(i);
+                readers.push(self.xdm.schedule_read_ddr());
+            }
+        }
+
+        match self.xdm.execute_queued(readers) {
+            Ok(words) => {
+                for (i, word) in positions.into_iter().zip(words) {
+                    values[i] = word;
+                }
+            }
+            Err(XtensaError::XdmError(XdmError::ExecOverrun)) => {
+                // The core did not keep up with the batch, fall back to waiting for every
+                // instruction to complete.
+                tracing::debug!("Batched register read overrun, falling back to polled reads");
+                for i in positions {
+                    values[i] = self.read_register_untyped(registers[i])?;
+                }
+            }
+            Err(XtensaError::XdmError(err)) => {
+                self.debug_execution_error(err)?;
+                return Err(XtensaError::XdmError(err));
+            }
+            Err(error) => return Err(error),
+        }
+
+        self.restore_register(key)?;
+
+        for (i, register) in registers.iter().enumerate() {
+            if matches!(register, Register::Fpu(_) | Register::User(_)) {
+                values[i] = self.read_register_untyped(*register)?;
+            }
+        }
+
+        Ok(values)
+    }
+
+    /// Writes multiple registers.
+    ///
+    /// Like [`read_registers`](Self::read_registers), the CPU and special registers are written
+    /// in as few JTAG transactions as possible.
+    pub fn write_registers(&mut self, registers: &[(Register, u32)]) -> Result<(), XtensaError> {
+        self.measured("write_registers", |this| {
+            this.write_registers_impl(registers)
+        })
+    }
+
+    fn write_registers_impl(&mut self, registers: &[(Register, u32)]) -> Result<(), XtensaError> {
+        let special_registers = registers
+            .iter()
+            .filter_map(|(register, value)| {
+                self.as_special_register(*register)
+                    .map(|register| (register, *value))
+            })
+            .collect::<Vec<_>>();
+
+        // Special registers are written first, as restoring the scratch register afterwards would
+        // undo a write to A3.
+        if !special_registers.is_empty() {
+            let key = self.save_register(CpuRegister::A3)?;
+
+            for (register, value) in special_registers.iter().copied() {
+                self.xdm.schedule_write_ddr(value);
+                self.xdm.schedule_execute_instruction(Instruction::Rsr(
+                    SpecialRegister::Ddr,
+                    CpuRegister::A3,
+                ));
+                self.xdm
+                    .schedule_execute_instruction(Instruction::Wsr(register, CpuRegister::A3));
+            }
+
+            self.execute_queued_writes(&special_registers, |this, (register, value)| {
+                this.write_special_register(register, value)
+            })?;
+
+            self.restore_register(key)?;
+        }
+
+        let cpu_registers = registers
+            .iter()
+            .filter_map(|(register, value)| match register {
+                Register::Cpu(register) => Some((*register, *value)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        for (register, value) in cpu_registers.iter().copied() {
+            self.xdm.schedule_write_ddr(value);
+            self.xdm
+                .schedule_execute_instruction(Instruction::Rsr(SpecialRegister::Ddr, register));
+        }
+
+        self.execute_queued_writes(&cpu_registers, |this, (register, value)| {
+            this.write_cpu_register(register, value)
+        })?;
+
+        for (register, value) in registers.iter().copied() {
+            if matches!(register, Register::Fpu(_) | Register::User(_)) {
+                self.write_register_untyped(register, value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Executes queued register writes, repeating them one by one if the core could not keep up.
+    fn execute_queued_writes<T: Copy>(
+        &mut self,
+        writes: &[T],
+        mut write: impl FnMut(&mut Self, T) -> Result<(), XtensaError>,
+    ) -> Result<(), XtensaError> {
+        match self.xdm.execute_queued(vec![]) {
+            Ok(_) => Ok(()),
+            Err(XtensaError::XdmError(XdmError::ExecOverrun)) => {
+                tracing::debug!("Batched register write overrun, falling back to polled writes");
+                for item in writes.iter().copied() {
+                    write(self, item)?;
+                }
+                Ok(())
+            }
+            Err(XtensaError::XdmError(err)) => {
+                self.debug_execution_error(err)?;
+                Err(XtensaError::XdmError(err))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
     pub fn save_register(
         &mut self,
         register: impl Into<Register>,
@@ -439,6 +1908,62 @@ impl XtensaCommunicationInterface {
         Ok(())
     }
 
+    /// Runs `op` with the CPU `registers` leased as scratch registers.
+    ///
+    /// The registers that are not saved yet are read in a single batch before `op` runs, and
+    /// written back in a single batch once it completes, even if it fails. Registers that are
+    /// already leased by an enclosing operation are left to that operation, so nested accesses
+    /// don't save and restore them again.
+    ///
+    /// This is meant to be combined with [`Self::execute_program`], to run instructions that
+    /// clobber registers without disturbing the state of the halted program.
+    pub fn with_scratch_registers<R>(
+        &mut self,
+        registers: &[CpuRegister],
+        op: impl FnOnce(&mut Self) -> Result<R, XtensaError>,
+    ) -> Result<R, XtensaError> {
+        let leased = registers
+            .iter()
+            .map(|register| Register::Cpu(*register))
+            .filter(|register| !self.state.saved_registers.contains_key(register))
+            .collect::<Vec<_>>();
+
+        if !leased.is_empty() {
+            tracing::debug!("Saving registers: {:?}", leased);
+            let values = self.read_registers(&leased)?;
+            self.state
+                .saved_registers
+                .extend(leased.iter().copied().zip(values));
+        }
+
+        let result = op(self);
+
+        if !leased.is_empty() {
+            tracing::debug!("Restoring registers: {:?}", leased);
+            let values = leased
+                .iter()
+                .filter_map(|register| {
+                    let value = self.state.saved_registers.get(register)?;
+                    Some((*register, *value))
+                })
+                .collect::<Vec<_>>();
+
+            let restored = self.write_registers(&values);
+            if restored.is_ok() {
+                for register in &leased {
+                    self.state.saved_registers.remove(register);
+                }
+            }
+
+            // Report the error of the operation itself, if there was one.
+            let value = result?;
+            restored?;
+            return Ok(value);
+        }
+
+        result
+    }
+
     fn restore_registers(&mut self) -> Result<(), XtensaError> {
         tracing::debug!("Restoring registers");
 
@@ -481,15 +2006,32 @@ impl XtensaCommunicationInterface {
         Ok(())
     }
 
-    fn read_memory(&mut self, address: u64, mut dst: &mut [u8]) -> Result<(), XtensaError> {
+    fn read_memory(&mut self, address: u64, dst: &mut [u8]) -> Result<(), XtensaError> {
+        self.measured("read_memory", |this| {
+            this.retried(|this| this.read_memory_impl(address, dst))
+        })
+    }
+
+    fn read_memory_impl(&mut self, address: u64, dst: &mut [u8]) -> Result<(), XtensaError> {
         tracing::debug!("Reading {} bytes from address {:08x}", dst.len(), address);
-        if dst.is_empty() {
-            return Ok(());
+
+        for (address, range) in self.translated_accesses(address, dst.len()) {
+            let dst = &mut dst[range];
+            self.check_access_width(address, dst.len())?;
+
+            self.with_scratch_registers(&[CpuRegister::A3], |this| {
+                this.read_memory_leased(address, dst)
+            })?;
         }
 
+        Ok(())
+    }
+
+    /// Reads memory through the scratch register, which must be leased by the caller.
+    fn read_memory_leased(&mut self, address: u64, mut dst: &mut [u8]) -> Result<(), XtensaError> {
         // Write aligned address to the scratch register
-        let key = self.save_register(CpuRegister::A3)?;
-        self.write_cpu_register(CpuRegister::A3, address as u32 & !0x3)?;
+        let mut word_address = address as u32 & !0x3;
+        self.write_cpu_register(CpuRegister::A3, word_address)?;
 
         // Read from address in the scratch register
         self.execute_instruction(Instruction::Lddr32P(CpuRegister::A3))?;
@@ -511,25 +2053,43 @@ impl XtensaCommunicationInterface {
 
             dst[..bytes_to_copy].copy_from_slice(&word[offset..][..bytes_to_copy]);
             dst = &mut dst[bytes_to_copy..];
+            word_address += 4;
+        }
 
-            if dst.is_empty() {
-                return Ok(());
+        while !dst.is_empty() {
+            let (words, execute_last) = Self::next_read_batch(dst.len());
+
+            match self.xdm.read_ddr_and_execute_batch(words, execute_last) {
+                Ok(batch) => {
+                    for word in batch {
+                        copy_word(&mut dst, word);
+                    }
+                }
+                Err(XtensaError::XdmError(XdmError::ExecOverrun)) => {
+                    // The core did not keep up with the batched reads, repeat this batch and
+                    // wait for every load to complete.
+                    tracing::debug!(
+                        "Batched read overrun at {:08x}, falling back to polled reads",
+                        word_address
+                    );
+                    self.write_cpu_register(CpuRegister::A3, word_address)?;
+                    self.execute_instruction(Instruction::Lddr32P(CpuRegister::A3))?;
+
+                    for i in 0..words {
+                        let word = if i + 1 < words || execute_last {
+                            self.read_ddr_and_execute()?
+                        } else {
+                            self.xdm.read_ddr()?
+                        };
+                        copy_word(&mut dst, word);
+                    }
+                }
+                Err(error) => return Err(error),
             }
-        }
 
-        while dst.len() > 4 {
-            let word = self.read_ddr_and_execute()?.to_le_bytes();
-            dst[..4].copy_from_slice(&word);
-            dst = &mut dst[4..];
+            word_address += words as u32 * 4;
         }
 
-        let remaining_bytes = dst.len();
-
-        let word = self.xdm.read_ddr()?;
-        dst.copy_from_slice(&word.to_le_bytes()[..remaining_bytes]);
-
-        self.restore_register(key)?;
-
         Ok(())
     }
 
@@ -538,37 +2098,100 @@ impl XtensaCommunicationInterface {
             return Ok(());
         }
 
-        let key = self.save_register(CpuRegister::A3)?;
+        if data.len() < 4 && self.access_width(address as u64) == AccessWidth::Any {
+            // Store aligned halfwords and the remaining bytes individually, so that the
+            // surrounding bytes are not accessed. The offsets are relative to the containing
+            // word, so that halfword offsets stay even.
+            return self.with_scratch_registers(&[CpuRegister::A3, CpuRegister::A4], |this| {
+                this.write_register_untyped(CpuRegister::A3, address & !0x3)?;
+
+                let mut offset = address as usize % 4;
+                let mut data = data;
+                while !data.is_empty() {
+                    if offset % 2 == 0 && data.len() >= 2 {
+                        let halfword = u16::from_le_bytes([data[0], data[1]]);
+                        this.write_register_untyped(CpuRegister::A4, halfword as u32)?;
+                        this.execute_instruction(Instruction::S16i(
+                            CpuRegister::A4,
+                            CpuRegister::A3,
+                            offset as u8,
+                        ))?;
+                        data = &data[2..];
+                        offset += 2;
+                    } else {
+                        this.write_register_untyped(CpuRegister::A4, data[0] as u32)?;
+                        this.execute_instruction(Instruction::S8i(
+                            CpuRegister::A4,
+                            CpuRegister::A3,
+                            offset as u8,
+                        ))?;
+                        data = &data[1..];
+                        offset += 1;
+                    }
+                }
+
+                Ok(())
+            });
+        }
 
         let offset = address as usize % 4;
         let aligned_address = address & !0x3;
 
-        // Read the aligned word
+        // Read the aligned word, unless it is replaced entirely
         let mut word = [0; 4];
-        self.read_memory(aligned_address as u64, &mut word)?;
+        if data.len() < 4 {
+            self.read_memory(aligned_address as u64, &mut word)?;
+        }
 
         // Replace the written bytes. This will also panic if the input is crossing a word boundary
         word[offset..][..data.len()].copy_from_slice(data);
 
         // Write the word back
-        self.write_register_untyped(CpuRegister::A3, aligned_address)?;
-        self.xdm.write_ddr(u32::from_le_bytes(word))?;
-        self.execute_instruction(Instruction::Sddr32P(CpuRegister::A3))?;
-        self.restore_register(key)?;
-
-        Ok(())
+        self.with_scratch_registers(&[CpuRegister::A3], |this| {
+            this.write_register_untyped(CpuRegister::A3, aligned_address)?;
+            this.xdm.write_ddr(u32::from_le_bytes(word))?;
+            this.execute_instruction(Instruction::Sddr32P(CpuRegister::A3))
+        })
     }
 
     fn write_memory(&mut self, address: u64, data: &[u8]) -> Result<(), XtensaError> {
+        self.measured("write_memory", |this| {
+            this.retried(|this| this.write_memory_impl(address, data))
+        })
+    }
+
+    fn write_memory_impl(&mut self, address: u64, data: &[u8]) -> Result<(), XtensaError> {
         tracing::debug!("Writing {} bytes to address {:08x}", data.len(), address);
-        if data.is_empty() {
-            return Ok(());
+
+        for (address, range) in self.translated_accesses(address, data.len()) {
+            self.write_translated(address, &data[range])?;
         }
 
-        let key = self.save_register(CpuRegister::A3)?;
+        Ok(())
+    }
+
+    /// Writes memory at an address that is already translated.
+    fn write_translated(&mut self, address: u64, data: &[u8]) -> Result<(), XtensaError> {
+        self.check_access_width(address, data.len())?;
+
+        // Lease the register used for byte stores up front, if the head or tail may need them,
+        // so that it is saved and restored along with the address register.
+        let scratch: &[CpuRegister] = if self.access_width(address) == AccessWidth::Any
+            && (address % 4 != 0 || data.len() % 4 != 0)
+        {
+            &[CpuRegister::A3, CpuRegister::A4]
+        } else {
+            &[CpuRegister::A3]
+        };
 
-        let address = address as u32;
+        self.with_scratch_registers(scratch, |this| {
+            this.write_memory_leased(address as u32, data)?;
+            this.sync_caches(address..address + data.len() as u64)
+        })
+    }
 
+    /// Writes memory through the scratch register, which must be leased by the caller.
+    fn write_memory_leased(&mut self, address: u32, data: &[u8]) -> Result<(), XtensaError> {
         let mut addr = address;
         let mut buffer = data;
 
@@ -584,7 +2207,6 @@ impl XtensaCommunicationInterface {
 
         if buffer.len() > 4 {
             // Prepare store instruction
-            self.save_register(CpuRegister::A3)?;
             self.write_register_untyped(CpuRegister::A3, addr)?;
 
             self.xdm
@@ -608,14 +2230,120 @@ impl XtensaCommunicationInterface {
             self.write_memory_unaligned8(addr, buffer)?;
         }
 
-        self.restore_register(key)?;
+        Ok(())
+    }
 
-        // TODO: implement cache flushing on CPUs that need it.
+    /// Reads the 8 or 32-bit value at `address` with exactly one load.
+    ///
+    /// Unlike [`read_memory`](Self::read_memory), the access is not retried and no following word
+    /// is loaded, so that reads with side effects happen exactly once. Byte loads can not access
+    /// InstrRAM.
+    fn read_exact(&mut self, address: u64, width: usize) -> Result<u32, XtensaError> {
+        let address = self.translate_address(address);
+        self.check_access_width(address, width)?;
+
+        self.measured("read_exact", |this| {
+            if width == 4 {
+                this.with_scratch_registers(&[CpuRegister::A3], |this| {
+                    this.write_cpu_register(CpuRegister::A3, address as u32)?;
+                    this.execute_instruction(Instruction::Lddr32P(CpuRegister::A3))?;
+                    this.xdm.read_ddr()
+                })
+            } else {
+                this.with_scratch_registers(&[CpuRegister::A3, CpuRegister::A4], |this| {
+                    this.write_cpu_register(CpuRegister::A3, address as u32)?;
+                    this.execute_instruction(Instruction::L8ui(
+                        CpuRegister::A4,
+                        CpuRegister::A3,
+                        0,
+                    ))?;
+                    this.read_cpu_register(CpuRegister::A4)
+                })
+            }
+        })
+    }
 
-        Ok(())
+    /// Writes the 8 or 32-bit `value` to `address` with exactly one store.
+    ///
+    /// Unlike [`write_memory`](Self::write_memory), the access is not retried and the surrounding
+    /// bytes are not read and written back. Byte stores can not access InstrRAM.
+    fn write_exact(&mut self, address: u64, width: usize, value: u32) -> Result<(), XtensaError> {
+        let address = self.translate_address(address);
+        self.check_access_width(address, width)?;
+
+        self.measured("write_exact", |this| {
+            if width == 4 {
+                this.with_scratch_registers(&[CpuRegister::A3], |this| {
+                    this.write_cpu_register(CpuRegister::A3, address as u32)?;
+                    this.xdm.write_ddr(value)?;
+                    this.execute_instruction(Instruction::Sddr32P(CpuRegister::A3))
+                })
+            } else {
+                this.with_scratch_registers(&[CpuRegister::A3, CpuRegister::A4], |this| {
+                    this.write_cpu_register(CpuRegister::A3, address as u32)?;
+                    this.write_cpu_register(CpuRegister::A4, value)?;
+                    this.execute_instruction(Instruction::S8i(CpuRegister::A4, CpuRegister::A3, 0))
+                })
+            }
+        })
+    }
+
+    /// Writes back and invalidates cache lines covering `written`, so that the core observes
+    /// data and instructions written by the debugger.
+    fn sync_caches(&mut self, written: Range<u64>) -> Result<(), XtensaError> {
+        let lines = self
+            .cached_regions
+            .iter()
+            .filter_map(|region| {
+                let lines = region.lines_to_sync(&written)?;
+                Some((region.clone(), lines))
+            })
+            .collect::<Vec<_>>();
+
+        if lines.is_empty() {
+            return Ok(());
+        }
+
+        self.with_scratch_registers(&[CpuRegister::A3], |this| {
+            for (region, lines) in lines {
+                for line in lines.step_by(region.line_size as usize) {
+                    this.write_cpu_register(CpuRegister::A3, line as u32)?;
+
+                    if region.data_writeback {
+                        this.execute_instruction(Instruction::Dhwbi(CpuRegister::A3, 0))?;
+                    }
+                    if region.instruction_invalidate {
+                        this.execute_instruction(Instruction::Ihi(CpuRegister::A3, 0))?;
+                    }
+                }
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Makes the code written to `range` visible to instruction fetches.
+    ///
+    /// The cache lines are already maintained as they are written, but the memory may also
+    /// have been changed behind the cache, e.g. by a flash algorithm.
+    fn code_barrier_impl(&mut self, range: Range<u64>) -> Result<(), XtensaError> {
+        let len = range.end.saturating_sub(range.start) as usize;
+        for (address, piece) in self.translated_accesses(range.start, len) {
+            self.sync_caches(address..address + piece.len() as u64)?;
+        }
+
+        self.execute_instruction(Instruction::Isync)
     }
 }
 
+/// Copies the little-endian bytes of `word` to the start of `dst` and advances `dst`.
+fn copy_word(dst: &mut &mut [u8], word: u32) {
+    let len = dst.len().min(4);
+    let (head, tail) = std::mem::take(dst).split_at_mut(len);
+    head.copy_from_slice(&word.to_le_bytes()[..len]);
+    *dst = tail;
+}
+
 /// DataType
 ///
 /// # Safety
@@ -637,7 +2365,7 @@ fn as_bytes_mut<T: DataType>(data: &mut [T]) -> &mut [u8] {
 
 impl MemoryInterface for XtensaCommunicationInterface {
     fn read(&mut self, address: u64, dst: &mut [u8]) -> Result<(), crate::Error> {
-        self.read_memory(address, dst)?;
+        self.halted_access(|this| this.read_memory(address, dst))?;
 
         Ok(())
     }
@@ -679,7 +2407,7 @@ impl MemoryInterface for XtensaCommunicationInterface {
     }
 
     fn write(&mut self, address: u64, data: &[u8]) -> Result<(), crate::Error> {
-        self.write_memory(address, data)?;
+        self.halted_access(|this| this.write_memory(address, data))?;
 
         Ok(())
     }
@@ -696,6 +2424,32 @@ impl MemoryInterface for XtensaCommunicationInterface {
         self.write(address, &[data])
     }
 
+    fn read_word_32_exact(&mut self, address: u64) -> Result<u32, crate::Error> {
+        check_alignment(address, 4)?;
+        let value = self.halted_access(|this| this.read_exact(address, 4))?;
+
+        Ok(value)
+    }
+
+    fn read_word_8_exact(&mut self, address: u64) -> Result<u8, crate::Error> {
+        let value = self.halted_access(|this| this.read_exact(address, 1))?;
+
+        Ok(value as u8)
+    }
+
+    fn write_word_32_exact(&mut self, address: u64, data: u32) -> Result<(), crate::Error> {
+        check_alignment(address, 4)?;
+        self.halted_access(|this| this.write_exact(address, 4, data))?;
+
+        Ok(())
+    }
+
+    fn write_word_8_exact(&mut self, address: u64, data: u8) -> Result<(), crate::Error> {
+        self.halted_access(|this| this.write_exact(address, 1, data as u32))?;
+
+        Ok(())
+    }
+
     fn write_64(&mut self, address: u64, data: &[u64]) -> anyhow::Result<(), crate::Error> {
         self.write_8(address, as_bytes(data))
     }
@@ -715,11 +2469,131 @@ impl MemoryInterface for XtensaCommunicationInterface {
     fn flush(&mut self) -> anyhow::Result<(), crate::Error> {
         Ok(())
     }
+
+    fn code_barrier(&mut self, range: Range<u64>) -> Result<(), crate::Error> {
+        self.halted_access(|this| this.code_barrier_impl(range))?;
+
+        Ok(())
+    }
+}
+
+/// A memory region that is accessed through a cache, and the maintenance it needs after the
+/// debugger writes to it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedRegion {
+    /// The address range of the region.
+    pub range: Range<u64>,
+    /// The cache line size in bytes. Must be a power of two.
+    pub line_size: u32,
+    /// Write back and invalidate the data cache lines (DHWBI).
+    pub data_writeback: bool,
+    /// Invalidate the instruction cache lines (IHI).
+    pub instruction_invalidate: bool,
+}
+
+impl CachedRegion {
+    /// Returns the line-aligned address range of this region that overlaps `written`, if any.
+    fn lines_to_sync(&self, written: &Range<u64>) -> Option<Range<u64>> {
+        let start = written.start.max(self.range.start);
+        let end = written.end.min(self.range.end);
+
+        if start >= end {
+            return None;
+        }
+
+        let mask = !(self.line_size as u64 - 1);
+
+        Some(start & mask..end)
+    }
+}
+
+/// Register values that would be written to the stack if the register windows were spilled.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct SpillArea {
+    /// The address of the first saved register.
+    pub address: u64,
+    /// The saved registers, in ascending address order.
+    pub words: Vec<u32>,
+}
+
+impl SpillArea {
+    /// Replaces the bytes of `data`, which was read from `address`, that this area covers.
+    pub fn apply(&self, address: u64, data: &mut [u8]) {
+        for (index, word) in self.words.iter().enumerate() {
+            let word_address = self.address + index as u64 * 4;
+
+            for (offset, byte) in word.to_le_bytes().into_iter().enumerate() {
+                let byte_address = word_address + offset as u64;
+                if let Some(target) = byte_address
+                    .checked_sub(address)
+                    .and_then(|index| data.get_mut(index as usize))
+                {
+                    *target = byte;
+                }
+            }
+        }
+    }
+}
+
+/// The state of the windowed register file.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RegisterWindow {
+    /// WINDOWBASE: the index of the current window, in units of 4 registers.
+    pub base: u32,
+    /// WINDOWSTART: one bit per 4-register unit that marks the start of a live window.
+    pub start: u32,
+}
+
+impl RegisterWindow {
+    /// Returns the physical address register that the logical register `logical` maps to.
+    pub fn physical_index(&self, logical: CpuRegister, physical_register_num: u8) -> u8 {
+        ((self.base * 4 + logical as u32) % physical_register_num as u32) as u8
+    }
+
+    /// Returns the start of the live windows (in 4-register units), from the current window to
+    /// the oldest one.
+    pub fn live_frames(&self, physical_register_num: u8) -> Vec<u8> {
+        let units = physical_register_num as u32 / 4;
+        let base = self.base % units;
+
+        let mut frames = vec![base as u8];
+        for offset in 1..units {
+            let unit = (base + units - offset) % units;
+            if self.start & (1 << unit) != 0 {
+                frames.push(unit as u8);
+            }
+        }
+
+        frames
+    }
+
+    /// Returns the number of 4-register units from the window at `from` up to the one at `to`.
+    fn distance(&self, from: u8, to: u8, physical_register_num: u8) -> u8 {
+        let units = physical_register_num / 4;
+        (to + units - from) % units
+    }
+
+    /// Computes the window rotation (in 4-register units) that makes the physical register
+    /// `index` visible, and the logical register it is visible as.
+    fn rotation_for(window_base: u32, index: u8, physical_register_num: u8) -> (i8, CpuRegister) {
+        let units = physical_register_num as i32 / 4;
+
+        let mut rotation = (index as i32 / 4 - window_base as i32).rem_euclid(units);
+        if rotation >= 8 {
+            // ROTW only accepts a signed 4 bit immediate
+            rotation -= units;
+        }
+
+        let logical = CpuRegister::try_from(index % 4).unwrap();
+
+        (rotation as i8, logical)
+    }
 }
 
 pub trait TypedRegister {
     fn register() -> Register;
     fn from_u32(value: u32) -> Self;
+    fn to_u32(&self) -> u32;
 }
 
 bitfield::bitfield! {
@@ -744,4 +2618,280 @@ impl TypedRegister for DebugCause {
     fn from_u32(value: u32) -> Self {
         Self(value)
     }
+
+    fn to_u32(&self) -> u32 {
+        self.0
+    }
+}
+
+bitfield::bitfield! {
+    /// The processor state register, PS.
+    #[derive(Copy, Clone, PartialEq, Eq)]
+    pub struct ProgramStatus(u32);
+    impl Debug;
+
+    /// The interrupt level. Interrupts at this level and below are masked.
+    pub intlevel,  set_intlevel : 3, 0;
+    /// Exception mode, set while an exception is handled. Masks level-1 interrupts.
+    pub excm,      set_excm     : 4;
+    /// User vector mode. Exceptions are taken to the user vector instead of the kernel vector.
+    pub user_mode, set_user_mode: 5;
+    /// The privilege level, if the MMU is configured.
+    pub ring,      set_ring     : 7, 6;
+    /// The old window base, saved by window overflow and underflow exceptions.
+    pub owb,       set_owb      : 11, 8;
+    /// The window increment of the last call, used by ENTRY.
+    pub callinc,   set_callinc  : 17, 16;
+    /// Window overflow detection enable.
+    pub woe,       set_woe      : 18;
+}
+
+impl ProgramStatus {
+    /// The state the debugger runs code on the core in: with the register window enabled, in
+    /// user vector mode, and with level-1 interrupts masked.
+    pub fn code_execution() -> Self {
+        let mut ps = Self(0);
+        ps.set_intlevel(1);
+        ps.set_user_mode(true);
+        ps.set_woe(true);
+        ps
+    }
+
+    /// Returns this PS with every interrupt below `debug_level` masked, to step an instruction
+    /// without entering an interrupt handler.
+    fn masked_for_step(mut self, debug_level: DebugLevel) -> Self {
+        self.set_intlevel(debug_level as u32 - 1);
+        self
+    }
+
+    /// Returns the PS to write after an instruction was stepped from `old_ps`, given that this
+    /// is the PS the instruction left, or `None` if it is kept as it is.
+    ///
+    /// `masked_ps` is the PS the instruction was stepped with, if interrupts were masked.
+    fn restored_after_step(mut self, old_ps: Self, masked_ps: Option<Self>) -> Option<Self> {
+        match masked_ps {
+            // Restore the original INTLEVEL but keep any other PS changes the instruction made.
+            // An INTLEVEL changed by the instruction itself, e.g. by RSIL, WSR.PS or RFI, is kept.
+            Some(masked_ps) if self.intlevel() == masked_ps.intlevel() => {
+                self.set_intlevel(old_ps.intlevel());
+            }
+            Some(_) => return None,
+            // Force a low INTLEVEL, as after any other halt.
+            None => self.set_intlevel(1),
+        }
+        Some(self)
+    }
+}
+
+impl From<u32> for ProgramStatus {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<ProgramStatus> for u32 {
+    fn from(value: ProgramStatus) -> Self {
+        value.0
+    }
+}
+
+impl TypedRegister for ProgramStatus {
+    fn register() -> Register {
+        Register::CurrentPs
+    }
+
+    fn from_u32(value: u32) -> Self {
+        Self(value)
+    }
+
+    fn to_u32(&self) -> u32 {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn register_window_rotation() {
+        // Registers in the current window do not need a rotation
+        assert_eq!(RegisterWindow::rotation_for(0, 2, 64), (0, CpuRegister::A2));
+        assert_eq!(
+            RegisterWindow::rotation_for(3, 13, 64),
+            (0, CpuRegister::A1)
+        );
+
+        // Rotations wrap around the physical register file
+        assert_eq!(
+            RegisterWindow::rotation_for(15, 0, 64),
+            (1, CpuRegister::A0)
+        );
+        assert_eq!(
+            RegisterWindow::rotation_for(0, 63, 64),
+            (-1, CpuRegister::A3)
+        );
+        assert_eq!(
+            RegisterWindow::rotation_for(0, 32, 64),
+            (-8, CpuRegister::A0)
+        );
+    }
+
+    #[test]
+    fn program_status_fields() {
+        assert_eq!(u32::from(ProgramStatus::code_execution()), 0x40021);
+
+        let mut ps = ProgramStatus::from(0x0006_0f1f);
+        assert_eq!(ps.intlevel(), 0xf);
+        assert!(ps.excm());
+        assert!(!ps.user_mode());
+        assert_eq!(ps.owb(), 0xf);
+        assert_eq!(ps.callinc(), 2);
+        assert!(ps.woe());
+
+        ps.set_intlevel(1);
+        assert_eq!(u32::from(ps), 0x0006_0f11);
+    }
+
+    #[test]
+    fn register_window_live_frames() {
+        // Frames at units 1, 3 and 5 are live, the current window is at unit 5.
+        let window = RegisterWindow {
+            base: 5,
+            start: 0b10_1010,
+        };
+        assert_eq!(window.live_frames(64), vec![5, 3, 1]);
+        assert_eq!(window.distance(3, 5, 64), 2);
+
+        // Live frames wrap around the register file.
+        let window = RegisterWindow {
+            base: 1,
+            start: 0b1000_0000_0000_0011,
+        };
+        assert_eq!(window.live_frames(64), vec![1, 0, 15]);
+        assert_eq!(window.distance(15, 0, 64), 1);
+    }
+
+    #[test]
+    fn spill_area_apply() {
+        let area = SpillArea {
+            address: 0x1004,
+            words: vec![0x4433_2211, 0x8877_6655],
+        };
+
+        let mut data = [0; 8];
+        area.apply(0x1000, &mut data);
+        assert_eq!(data, [0, 0, 0, 0, 0x11, 0x22, 0x33, 0x44]);
+
+        let mut data = [0; 4];
+        area.apply(0x100A, &mut data);
+        assert_eq!(data, [0x77, 0x88, 0, 0]);
+    }
+
+    #[test]
+    fn register_window_physical_index() {
+        let window = RegisterWindow { base: 15, start: 0 };
+
+        assert_eq!(window.physical_index(CpuRegister::A0, 64), 60);
+        assert_eq!(window.physical_index(CpuRegister::A4, 64), 0);
+    }
+
+    #[test]
+    fn read_batches() {
+        let batch = XtensaCommunicationInterface::next_read_batch;
+
+        // The last batch does not load a word past the end
+        assert_eq!(batch(4), (1, false));
+        assert_eq!(batch(6), (2, false));
+        assert_eq!(batch(1024), (256, false));
+
+        // Larger reads are split, and each batch loads the first word of the next one
+        assert_eq!(batch(1025), (256, true));
+        assert_eq!(batch(4096), (256, true));
+    }
+
+    #[test]
+    fn batched_words_are_assembled_in_order() {
+        let mut buffer = [0u8; 10];
+        let mut dst = &mut buffer[..];
+
+        for word in [0x4433_2211, 0x8877_6655, 0xCCBB_AA99] {
+            copy_word(&mut dst, word);
+        }
+
+        assert!(dst.is_empty());
+        assert_eq!(
+            buffer,
+            [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xAA]
+        );
+    }
+
+    #[test]
+    fn step_masks_interrupts_below_the_debug_level() {
+        let mut ps = ProgramStatus::code_execution();
+        ps.set_intlevel(0);
+
+        let masked = ps.masked_for_step(DebugLevel::L6);
+        assert_eq!(masked.intlevel(), 5);
+        assert_eq!(masked.0 & !0xF, ps.0 & !0xF);
+    }
+
+    #[test]
+    fn step_restores_the_interrupt_level() {
+        let mut old_ps = ProgramStatus::code_execution();
+        old_ps.set_intlevel(2);
+        let masked_ps = old_ps.masked_for_step(DebugLevel::L6);
+
+        // The original INTLEVEL is restored, other changes of the instruction are kept
+        let mut new_ps = masked_ps;
+        new_ps.set_excm(true);
+        let restored = new_ps.restored_after_step(old_ps, Some(masked_ps)).unwrap();
+        assert_eq!(restored.intlevel(), 2);
+        assert!(restored.excm());
+
+        // An INTLEVEL set by the instruction itself is kept
+        let mut new_ps = masked_ps;
+        new_ps.set_intlevel(3);
+        assert_eq!(new_ps.restored_after_step(old_ps, Some(masked_ps)), None);
+
+        // Without masking, INTLEVEL is lowered as after any other halt
+        let restored = old_ps.restored_after_step(old_ps, None).unwrap();
+        assert_eq!(restored.intlevel(), 1);
+    }
+
+    #[test]
+    fn cached_region_lines_to_sync() {
+        let region = CachedRegion {
+            range: 0x1000..0x2000,
+            line_size: 32,
+            data_writeback: true,
+            instruction_invalidate: false,
+        };
+
+        // Writes are extended to the start of their first cache line
+        assert_eq!(
+            region.lines_to_sync(&(0x1024..0x1028)),
+            Some(0x1020..0x1028)
+        );
+        assert_eq!(
+            region.lines_to_sync(&(0x1040..0x1080)),
+            Some(0x1040..0x1080)
+        );
+
+        // Writes are clamped to the region
+        assert_eq!(
+            region.lines_to_sync(&(0x0F00..0x1010)),
+            Some(0x1000..0x1010)
+        );
+        assert_eq!(
+            region.lines_to_sync(&(0x1FF0..0x2100)),
+            Some(0x1FE0..0x2000)
+        );
+        assert_eq!(region.lines_to_sync(&(0..0x3000)), Some(0x1000..0x2000));
+
+        // Writes outside of the region need no maintenance
+        assert_eq!(region.lines_to_sync(&(0x0F00..0x1000)), None);
+        assert_eq!(region.lines_to_sync(&(0x2000..0x2004)), None);
+        assert_eq!(region.lines_to_sync(&(0x1010..0x1010)), None);
+    }
 }
@@ -0,0 +1,316 @@
+//! Reading the eFuses of the Espressif chips, see [`Session::read_efuses`].
+//!
+//! [`Session::read_efuses`]: crate::Session::read_efuses
+
+use crate::{Core, MemoryInterface};
+
+/// A block of eFuses, as read from the read registers of the eFuse controller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfuseBlock {
+    /// The name of the block, e.g. `BLOCK0`.
+    pub name: &'static str,
+    /// The address of the first read register of the block.
+    pub address: u64,
+    /// The number of 32-bit words in the block.
+    pub words: usize,
+}
+
+/// A named field in an eFuse block, e.g. the factory MAC address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EfuseField {
+    /// The name of the field, as used by ESP-IDF.
+    pub name: &'static str,
+    /// The index of the block in [`EfuseLayout::blocks`].
+    pub block: usize,
+    /// The offset of the least significant bit of the field in the block.
+    pub offset: u32,
+    /// The number of bits of the field, at most 64.
+    pub bits: u32,
+}
+
+/// The revision of a chip, e.g. v0.2.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChipRevision {
+    /// The major revision.
+    pub major: u32,
+    /// The minor revision.
+    pub minor: u32,
+}
+
+impl std::fmt::Display for ChipRevision {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "v{}.{}", self.major, self.minor)
+    }
+}
+
+/// Where the eFuses of a chip are read, and how they are decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct EfuseLayout {
+    /// The eFuse blocks of the chip, in the order of their block numbers.
+    pub blocks: &'static [EfuseBlock],
+    /// The fields decoded by [`Efuses::field`].
+    pub fields: &'static [EfuseField],
+    /// The field counting how often flash encryption was toggled. Flash encryption is enabled
+    /// while an odd number of its bits is set.
+    pub crypt_cnt: &'static str,
+    /// Decodes the revision of the chip.
+    pub revision: fn(&Efuses) -> ChipRevision,
+}
+
+/// The eFuses of a chip, as read by [`Session::read_efuses`](crate::Session::read_efuses).
+///
+/// eFuses read as zero while they are read protected.
+#[derive(Debug, Clone)]
+pub struct Efuses {
+    layout: &'static EfuseLayout,
+    blocks: Vec<Vec<u32>>,
+}
+
+impl Efuses {
+    /// Reads all eFuse blocks of `layout` through `core`.
+    pub(crate) fn read(
+        core: &mut Core<'_>,
+        layout: &'static EfuseLayout,
+    ) -> Result<Self, crate::Error> {
+        let mut blocks = Vec::with_capacity(layout.blocks.len());
+        for block in layout.blocks {
+            let mut words = vec![0; block.words];
+            core.read_32(block.address, &mut words)?;
+            blocks.push(words);
+        }
+
+        Ok(Self { layout, blocks })
+    }
+
+    /// The layout the eFuses were read with.
+    pub fn layout(&self) -> &'static EfuseLayout {
+        self.layout
+    }
+
+    /// The words of the block `index`, or `None` if the chip has no such block.
+    pub fn block(&self, index: usize) -> Option<&[u32]> {
+        self.blocks.get(index).map(Vec::as_slice)
+    }
+
+    /// Returns the value of the field `name`, or `None` if the layout has no such field.
+    pub fn field(&self, name: &str) -> Option<u64> {
+        let field = self
+            .layout
+            .fields
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(name))?;
+
+        Some(self.field_value(field))
+    }
+
+    /// Iterates over the names and values of all fields of the layout.
+    pub fn fields(&self) -> impl Iterator<Item = (&'static str, u64)> + '_ {
+        self.layout
+            .fields
+            .iter()
+            .map(|field| (field.name, self.field_value(field)))
+    }
+
+    /// The factory MAC address.
+    pub fn mac_address(&self) -> [u8; 6] {
+        // The first byte of the address is stored in the most significant bits.
+        let value = self.field("MAC").unwrap_or_default();
+        let bytes = value.to_be_bytes();
+        [bytes[2], bytes[3], bytes[4], bytes[5], bytes[6], bytes[7]]
+    }
+
+    /// The revision of the chip.
+    pub fn chip_revision(&self) -> ChipRevision {
+        (self.layout.revision)(self)
+    }
+
+    /// Whether the flash is encrypted.
+    pub fn flash_encryption_enabled(&self) -> bool {
+        let crypt_cnt = self.field(self.layout.crypt_cnt).unwrap_or_default();
+        crypt_cnt.count_ones() % 2 == 1
+    }
+
+    fn field_value(&self, field: &EfuseField) -> u64 {
+        let words = self.block(field.block).unwrap_or_default();
+
+        let mut value = 0;
+        for bit in 0..field.bits.min(64) {
+            let index = field.offset + bit;
+            let set = words
+                .get(index as usize / 32)
+                .is_some_and(|word| word >> (index % 32) & 1 == 1);
+            value |= u64::from(set) << bit;
+        }
+        value
+    }
+}
+
+const fn block(name: &'static str, address: u64, words: usize) -> EfuseBlock {
+    EfuseBlock {
+        name,
+        address,
+        words,
+    }
+}
+
+const fn field(name: &'static str, block: usize, offset: u32, bits: u32) -> EfuseField {
+    EfuseField {
+        name,
+        block,
+        offset,
+        bits,
+    }
+}
+
+/// The blocks of the eFuse controller of the ESP32-S2, ESP32-S3 and later chips, whose read
+/// registers start at `base + 0x2c`.
+const fn efuse_blocks_v2(base: u64) -> [EfuseBlock; 11] {
+    [
+        block("BLOCK0", base + 0x2c, 6),
+        block("BLOCK_SYS_DATA", base + 0x44, 6),
+        block("BLOCK_SYS_DATA1", base + 0x5c, 8),
+        block("BLOCK_USR_DATA", base + 0x7c, 8),
+        block("BLOCK_KEY0", base + 0x9c, 8),
+        block("BLOCK_KEY1", base + 0xbc, 8),
+        block("BLOCK_KEY2", base + 0xdc, 8),
+        block("BLOCK_KEY3", base + 0xfc, 8),
+        block("BLOCK_KEY4", base + 0x11c, 8),
+        block("BLOCK_KEY5", base + 0x13c, 8),
+        block("BLOCK_SYS_DATA2", base + 0x15c, 8),
+    ]
+}
+
+/// The eFuses of the ESP32.
+pub const ESP32_EFUSES: EfuseLayout = EfuseLayout {
+    blocks: &[
+        block("BLOCK0", 0x3ff5a000, 7),
+        block("BLOCK1", 0x3ff5a038, 8),
+        block("BLOCK2", 0x3ff5a058, 8),
+        block("BLOCK3", 0x3ff5a078, 8),
+    ],
+    fields: &[
+        field("WR_DIS", 0, 0, 16),
+        field("RD_DIS", 0, 16, 4),
+        field("FLASH_CRYPT_CNT", 0, 20, 7),
+        field("MAC", 0, 32, 48),
+        field("MAC_CRC", 0, 80, 8),
+        field("CHIP_VER_REV1", 0, 111, 1),
+        field("CHIP_VER_REV2", 0, 180, 1),
+        field("WAFER_VERSION_MINOR", 0, 184, 2),
+        field("CODING_SCHEME", 0, 192, 2),
+        field("ABS_DONE_0", 0, 196, 1),
+        field("JTAG_DISABLE", 0, 198, 1),
+    ],
+    crypt_cnt: "FLASH_CRYPT_CNT",
+    revision: esp32_revision,
+};
+
+/// The ESP32 v3 is only told apart from the v2 by a register outside of the eFuses, so it is
+/// reported as v2.
+fn esp32_revision(efuses: &Efuses) -> ChipRevision {
+    let rev1 = efuses.field("CHIP_VER_REV1").unwrap_or_default();
+    let rev2 = efuses.field("CHIP_VER_REV2").unwrap_or_default();
+
+    ChipRevision {
+        major: match (rev1, rev2) {
+            (1, 1) => 2,
+            (1, 0) => 1,
+            _ => 0,
+        },
+        minor: efuses.field("WAFER_VERSION_MINOR").unwrap_or_default() as u32,
+    }
+}
+
+/// The eFuses of the ESP32-S2.
+pub const ESP32S2_EFUSES: EfuseLayout = EfuseLayout {
+    blocks: &efuse_blocks_v2(0x3f41a000),
+    fields: &[
+        field("WR_DIS", 0, 0, 32),
+        field("RD_DIS", 0, 32, 7),
+        field("SPI_BOOT_CRYPT_CNT", 0, 82, 3),
+        field("MAC", 1, 0, 48),
+        field("WAFER_VERSION_MINOR_LO", 1, 114, 3),
+        field("WAFER_VERSION_MAJOR", 1, 146, 2),
+        field("WAFER_VERSION_MINOR_HI", 1, 148, 1),
+    ],
+    crypt_cnt: "SPI_BOOT_CRYPT_CNT",
+    revision: split_minor_revision,
+};
+
+/// The eFuses of the ESP32-S3.
+pub const ESP32S3_EFUSES: EfuseLayout = EfuseLayout {
+    blocks: &efuse_blocks_v2(0x60007000),
+    fields: &[
+        field("WR_DIS", 0, 0, 32),
+        field("RD_DIS", 0, 32, 7),
+        field("SPI_BOOT_CRYPT_CNT", 0, 82, 3),
+        field("MAC", 1, 0, 48),
+        field("WAFER_VERSION_MINOR_LO", 1, 114, 3),
+        field("WAFER_VERSION_MINOR_HI", 1, 183, 1),
+        field("WAFER_VERSION_MAJOR", 1, 184, 2),
+    ],
+    crypt_cnt: "SPI_BOOT_CRYPT_CNT",
+    revision: split_minor_revision,
+};
+
+/// Decodes the revision of chips which store the minor revision in two fields.
+fn split_minor_revision(efuses: &Efuses) -> ChipRevision {
+    let value = |name| efuses.field(name).unwrap_or_default() as u32;
+
+    ChipRevision {
+        major: value("WAFER_VERSION_MAJOR"),
+        minor: value("WAFER_VERSION_MINOR_HI") << 3 | value("WAFER_VERSION_MINOR_LO"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn esp32_fields_are_decoded() {
+        let mut block0 = vec![0; 7];
+        // FLASH_CRYPT_CNT with one bit set, and the MAC 24:0a:c4:01:02:03.
+        block0[0] = 1 << 20;
+        block0[1] = 0xc401_0203;
+        block0[2] = 0x240a;
+        // CHIP_VER_REV1 and CHIP_VER_REV2, and minor revision 1.
+        block0[3] = 1 << 15;
+        block0[5] = 1 << 20 | 1 << 24;
+
+        let efuses = Efuses {
+            layout: &ESP32_EFUSES,
+            blocks: vec![block0, vec![0; 8], vec![0; 8], vec![0; 8]],
+        };
+
+        assert_eq!(efuses.mac_address(), [0x24, 0x0a, 0xc4, 0x01, 0x02, 0x03]);
+        assert_eq!(efuses.chip_revision(), ChipRevision { major: 2, minor: 1 });
+        assert!(efuses.flash_encryption_enabled());
+        assert_eq!(efuses.field("flash_crypt_cnt"), Some(1));
+        assert_eq!(efuses.field("KEY_PURPOSE_0"), None);
+    }
+
+    #[test]
+    fn split_minor_revision_is_combined() {
+        let mut sys_data = vec![0; 6];
+        // WAFER_VERSION_MINOR_LO = 2, WAFER_VERSION_MINOR_HI = 1, WAFER_VERSION_MAJOR = 1.
+        sys_data[3] = 2 << 18;
+        sys_data[5] = 1 << 23 | 1 << 24;
+
+        let mut blocks = vec![vec![0; 8]; ESP32S3_EFUSES.blocks.len()];
+        blocks[1] = sys_data;
+        let efuses = Efuses {
+            layout: &ESP32S3_EFUSES,
+            blocks,
+        };
+
+        assert_eq!(
+            efuses.chip_revision(),
+            ChipRevision {
+                major: 1,
+                minor: 10
+            }
+        );
+        assert!(!efuses.flash_encryption_enabled());
+    }
+}
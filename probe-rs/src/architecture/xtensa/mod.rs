@@ -1,18 +1,32 @@
 //! All the interface bits for Xtensa.
 
-use std::time::Duration;
+use std::{ops::Range, sync::Arc, time::Duration};
 
 use probe_rs_target::{Architecture, CoreType, InstructionSet};
 
 use crate::{
     architecture::xtensa::{
-        arch::{Register, SpecialRegister},
-        communication_interface::DebugCause,
-        registers::{FP, PC, RA, SP, XTENSA_CORE_REGSISTERS},
+        arch::{
+            describe_exception_cause,
+            instruction::{
+                decode, disassemble, software_breakpoint, DecodedInstruction, Instruction,
+            },
+            CpuRegister, Register, SpecialRegister,
+        },
+        communication_interface::{
+            DebugCause, ProgramStatus, ResetPhase, SpillArea, StepMode, XtensaError,
+        },
+        registers::{FP, PC, RA, SP, XTENSA_CORE_REGSISTERS, XTENSA_WITH_FP_CORE_REGSISTERS},
+        sequences::{ExternalMemory, RomFlashRoutines, RomSectorRoutines, XtensaDebugSequence},
     },
-    core::registers::{CoreRegisters, RegisterId, RegisterValue},
-    BreakpointCause, CoreInformation, CoreInterface, CoreRegister, CoreStatus, Error, HaltReason,
-    MemoryInterface,
+    core::{
+        registers::{CoreContext, CoreRegisters, RegisterId, RegisterValue},
+        step_repeatedly, BreakpointUnits, StepOptions,
+    },
+    memory::valid_32bit_address,
+    BreakpointCause, CoreInformation, CoreInterface, CoreRegister, CoreStatus,
+    DisassembledInstruction, Error, ExceptionCause, HaltReason, MemoryAttributes, MemoryInterface,
+    ResetCause, SemihostingCommand, Watchpoint, WatchpointKind,
 };
 
 use self::communication_interface::XtensaCommunicationInterface;
@@ -21,60 +35,120 @@ pub mod arch; // TODO: this module probably shouldn't be public but it's used in
 mod xdm;
 
 pub mod communication_interface;
+pub mod efuse;
 pub(crate) mod registers;
 pub(crate) mod sequences;
+pub mod trax;
 
 #[derive(Debug)]
 /// Flags used to control the [`SpecificCoreState`](crate::core::SpecificCoreState) for Xtensa
 /// architecture.
 pub struct XtensaState {
-    breakpoints_enabled: bool,
-    breakpoint_set: [bool; 2],
+    /// The hardware breakpoints. Kept so that the breakpoints can be reprogrammed after a reset
+    /// cleared them.
+    breakpoints: BreakpointUnits,
 
     /// Whether the PC was written since we last halted. Used to avoid incrementing the PC on
     /// resume.
     pc_written: bool,
+
+    /// Software breakpoints, used once all hardware breakpoint units are in use.
+    software_breakpoints: Vec<Option<SoftwareBreakpoint>>,
+
+    /// Callers' registers that are still held in the register file. Memory reads are patched with
+    /// these, so that the stack can be unwound. `None` if not yet read since the core halted.
+    spill_areas: Option<Vec<SpillArea>>,
+
+    /// Whether the core implements the Xtensa NX instruction set variant.
+    nx: bool,
+
+    /// Whether the core implements the Code Density Option, with its 16-bit instructions.
+    code_density: bool,
+
+    /// Whether the core implements the Loop Option, with its zero-overhead loops.
+    loops: bool,
 }
 
 impl XtensaState {
-    /// Creates a new [`XtensaState`].
-    pub(crate) fn new() -> Self {
+    /// The number of software breakpoints reported in addition to the hardware units.
+    const SOFTWARE_BREAKPOINT_SLOTS: usize = 16;
+
+    /// Creates a new [`XtensaState`]. `nx` selects the Xtensa NX instruction set variant,
+    /// `code_density` and `loops` whether the core implements the Code Density Option and the
+    /// Loop Option.
+    pub(crate) fn new(nx: bool, code_density: bool, loops: bool) -> Self {
         Self {
-            breakpoints_enabled: false,
-            breakpoint_set: [false; 2],
+            breakpoints: BreakpointUnits::new(Xtensa::IBREAKA_REGS.len()),
             pc_written: false,
+            software_breakpoints: vec![None; Self::SOFTWARE_BREAKPOINT_SLOTS],
+            spill_areas: None,
+            nx,
+            code_density,
+            loops,
         }
     }
 
-    fn breakpoint_mask(&self) -> u32 {
-        self.breakpoint_set
+    fn software_breakpoint_at(&self, address: u64) -> Option<&SoftwareBreakpoint> {
+        self.software_breakpoints
             .iter()
-            .enumerate()
-            .fold(0, |acc, (i, &set)| if set { acc | (1 << i) } else { acc })
+            .flatten()
+            .find(|breakpoint| breakpoint.address == address)
     }
 }
 
+/// A breakpoint instruction patched into memory.
+#[derive(Debug, Clone)]
+struct SoftwareBreakpoint {
+    address: u64,
+    /// The instruction bytes that were replaced.
+    original: Vec<u8>,
+    /// The breakpoint instruction bytes.
+    patch: Vec<u8>,
+    /// The offset in flash the breakpoint was written to, if the code is executed from flash.
+    flash_offset: Option<u64>,
+}
+
 /// An interface to operate Xtensa cores.
 pub struct Xtensa<'probe> {
     interface: &'probe mut XtensaCommunicationInterface,
     state: &'probe mut XtensaState,
+    sequence: Arc<dyn XtensaDebugSequence>,
     id: usize,
 }
 
 impl<'probe> Xtensa<'probe> {
     const IBREAKA_REGS: [SpecialRegister; 2] =
         [SpecialRegister::IBreakA0, SpecialRegister::IBreakA1];
+    const DBREAKA_REGS: [SpecialRegister; 2] =
+        [SpecialRegister::DBreakA0, SpecialRegister::DBreakA1];
+
+    /// The offset of the double exception vector from VECBASE, in the vector layout of the
+    /// Espressif chips.
+    const DOUBLE_EXCEPTION_VECTOR: u32 = 0x3C0;
+    /// The size of the double exception vector.
+    const DOUBLE_EXCEPTION_VECTOR_SIZE: u32 = 0x40;
+    const DBREAKC_REGS: [SpecialRegister; 2] =
+        [SpecialRegister::DBreakC0, SpecialRegister::DBreakC1];
+
+    /// DBREAKC: break on loads.
+    const DBREAKC_LOAD: u32 = 1 << 30;
+    /// DBREAKC: break on stores.
+    const DBREAKC_STORE: u32 = 1 << 31;
+    /// DBREAKC: the address bits which are compared. Watched ranges are at most 64 bytes.
+    const DBREAKC_MASK: u32 = 0x3F;
 
     /// Create a new Xtensa interface.
     pub fn new(
         interface: &'probe mut XtensaCommunicationInterface,
         state: &'probe mut XtensaState,
+        sequence: Arc<dyn XtensaDebugSequence>,
         id: usize,
     ) -> Self {
         Self {
             interface,
             id,
             state,
+            sequence,
         }
     }
 
@@ -84,30 +158,469 @@ impl<'probe> Xtensa<'probe> {
         Ok(CoreInformation { pc: pc.try_into()? })
     }
 
-    fn skip_breakpoint_instruction(&mut self) -> Result<(), Error> {
+    /// Moves the PC past the BREAK instruction the core halted on, unless the PC was written since.
+    /// Returns whether the instruction was skipped.
+    fn skip_breakpoint_instruction(&mut self) -> Result<bool, Error> {
         if !self.state.pc_written {
             let debug_cause = self.interface.read_register::<DebugCause>()?;
 
-            let pc_increment = if debug_cause.break_instruction() {
-                3
-            } else if debug_cause.break_n_instruction() {
-                2
-            } else {
-                0
-            };
+            if debug_cause.break_instruction() || debug_cause.break_n_instruction() {
+                let pc: u64 = self
+                    .read_core_reg(self.program_counter().into())?
+                    .try_into()?;
+
+                // Software breakpoints set by us are stepped over by executing the original
+                // instruction instead.
+                if self.state.software_breakpoint_at(pc).is_some() {
+                    return Ok(false);
+                }
+
+                // Skip the instruction that is actually at the PC, e.g. a wide BREAK on a core
+                // without the Code Density Option. Fall back to the debug cause if the
+                // instruction is not recognized.
+                let length = match self.instruction_at(pc)? {
+                    DecodedInstruction {
+                        length,
+                        instruction: Some(Instruction::Break(..) | Instruction::BreakN(..)),
+                    } => length,
+                    _ if debug_cause.break_n_instruction() => 2,
+                    _ => 3,
+                };
 
-            if pc_increment > 0 {
                 // Step through the breakpoint
-                let mut pc = self.read_core_reg(self.program_counter().into())?;
+                let next_pc = self.next_instruction_address(pc as u32, length as u32)?;
 
-                pc.increment_address(pc_increment)?;
+                self.write_core_reg(self.program_counter().into(), RegisterValue::U32(next_pc))?;
 
-                self.write_core_reg(self.program_counter().into(), pc)?;
+                return Ok(true);
             }
         }
 
+        Ok(false)
+    }
+
+    /// Decodes the semihosting call the core halted on. Does nothing unless feature rtt is enabled.
+    ///
+    /// Xtensa has no standard for semihosting, so this follows OpenOCD and ESP-IDF, which use
+    /// `BREAK 1, 14` as the trap, with the operation in a2 and its parameter in a3.
+    fn semihosting_command(&mut self) -> Result<Option<SemihostingCommand>, Error> {
+        let mut command = None;
+
+        #[cfg(feature = "rtt")]
+        {
+            use crate::rtt::decode_semihosting_syscall;
+            let pc: u64 = self
+                .read_core_reg(self.program_counter().into())?
+                .try_into()?;
+
+            if let Some(Instruction::Break(1, 14)) = self.instruction_at(pc)?.instruction {
+                let a2 = self.interface.read_register_untyped(CpuRegister::A2)?;
+                let a3 = self.interface.read_register_untyped(CpuRegister::A3)?;
+                tracing::info!("Semihosting found pc={pc:#x} a2={a2:#x} a3={a3:#x}");
+
+                command = Some(decode_semihosting_syscall(self, a2, a3)?);
+            }
+        }
+
+        Ok(command)
+    }
+
+    /// Reads and decodes the instruction at `address`.
+    fn instruction_at(&mut self, address: u64) -> Result<DecodedInstruction, Error> {
+        let mut bytes = [0; 3];
+        self.interface.read(address, &mut bytes)?;
+
+        Ok(decode(bytes, self.state.code_density))
+    }
+
+    /// Returns the address of the instruction that follows the `length` bytes long instruction at
+    /// `pc`, for instructions that are skipped instead of executed.
+    ///
+    /// If the instruction is the last one of a zero-overhead loop body, this follows the loopback
+    /// to LBEG, and decrements LCOUNT like the hardware would.
+    fn next_instruction_address(&mut self, pc: u32, length: u32) -> Result<u32, Error> {
+        let next = pc.wrapping_add(length);
+
+        if !self.state.loops {
+            return Ok(next);
+        }
+
+        let [ps, lbeg, lend, lcount] = self.interface.read_registers(&[
+            Register::CurrentPs,
+            Register::Special(SpecialRegister::Lbeg),
+            Register::Special(SpecialRegister::Lend),
+            Register::Special(SpecialRegister::Lcount),
+        ])?[..] else {
+            unreachable!("read_registers returns a value for each register");
+        };
+
+        // The loopback is disabled while PS.EXCM is set.
+        if next != lend || lcount == 0 || ProgramStatus::from(ps).excm() {
+            return Ok(next);
+        }
+
+        self.interface
+            .write_register_untyped(SpecialRegister::Lcount, lcount - 1)?;
+
+        Ok(lbeg)
+    }
+
+    /// If the core is halted on one of our software breakpoints, temporarily restores the
+    /// original instruction and executes it. Returns whether a step was performed.
+    fn step_over_software_breakpoint(&mut self) -> Result<bool, Error> {
+        let pc = self.read_core_reg(self.program_counter().into())?;
+        let Some(breakpoint) = self.state.software_breakpoint_at(pc.try_into()?).cloned() else {
+            return Ok(false);
+        };
+
+        let range = breakpoint.address..breakpoint.address + breakpoint.patch.len() as u64;
+
+        self.interface
+            .write(breakpoint.address, &breakpoint.original)?;
+        self.interface.code_barrier(range.clone())?;
+        let result = self.interface.step();
+        self.interface
+            .write(breakpoint.address, &breakpoint.patch)?;
+        self.interface.code_barrier(range)?;
+        result?;
+
+        Ok(true)
+    }
+
+    /// Decodes EXCCAUSE and EXCVADDR if the core was halted while handling an exception.
+    ///
+    /// A double exception, raised while the core was already handling one, is recognized by the
+    /// PC being in the double exception vector, and reported with the PC saved in DEPC.
+    fn exception_cause(&mut self) -> Result<Option<ExceptionCause>, Error> {
+        let [ps, vecbase, pc] = self.interface.read_registers(&[
+            Register::CurrentPs,
+            Register::Special(SpecialRegister::VecBase),
+            Register::CurrentPc,
+        ])?[..] else {
+            unreachable!("read_registers returns a value for each register");
+        };
+
+        let double_exception = Self::in_double_exception_vector(vecbase, pc);
+
+        // PS.EXCM is set while the core is handling an exception.
+        if !double_exception && !ProgramStatus::from(ps).excm() {
+            return Ok(None);
+        }
+
+        let cause = self
+            .interface
+            .read_register_untyped(SpecialRegister::ExcCause)?;
+        let (_, has_address) = describe_exception_cause(cause);
+
+        let address = if has_address {
+            let address = self
+                .interface
+                .read_register_untyped(SpecialRegister::ExcVaddr)?;
+            Some(address as u64)
+        } else {
+            None
+        };
+
+        let depc = if double_exception {
+            let depc = self
+                .interface
+                .read_register_untyped(SpecialRegister::Depc)?;
+            Some(depc as u64)
+        } else {
+            None
+        };
+
+        Ok(Some(exception_cause_from(cause, address, depc)))
+    }
+
+    /// Returns whether `pc` is in the double exception vector, relative to `vecbase`.
+    fn in_double_exception_vector(vecbase: u32, pc: u32) -> bool {
+        let double_vector = vecbase.wrapping_add(Self::DOUBLE_EXCEPTION_VECTOR);
+        pc.wrapping_sub(double_vector) < Self::DOUBLE_EXCEPTION_VECTOR_SIZE
+    }
+
+    /// Patches `data`, which was read from `address`, so that the stack reads as if the live
+    /// register windows were spilled.
+    fn apply_spill_areas(&mut self, address: u64, data: &mut [u8]) {
+        // NX cores have no register windows.
+        if self.state.nx || !self.interface.windowed_registers() {
+            return;
+        }
+
+        if self.state.spill_areas.is_none() {
+            match self.interface.window_spill_areas() {
+                Ok(areas) => self.state.spill_areas = Some(areas),
+                Err(error) => {
+                    tracing::debug!("Failed to read the live register windows: {:?}", error);
+                    return;
+                }
+            }
+        }
+
+        for area in self.state.spill_areas.iter().flatten() {
+            area.apply(address, data);
+        }
+    }
+
+    fn hw_breakpoint_units(&self) -> usize {
+        (self.interface.available_breakpoint_units() as usize).min(self.state.breakpoints.len())
+    }
+
+    fn set_software_breakpoint(&mut self, slot: usize, address: u64) -> Result<(), Error> {
+        let Some(existing) = self.state.software_breakpoints.get(slot) else {
+            return Err(Error::Xtensa(XtensaError::InvalidBreakpointUnit(
+                self.hw_breakpoint_units() + slot,
+            )));
+        };
+        let existing = existing.as_ref().map(|breakpoint| breakpoint.address);
+        if existing == Some(address) {
+            return Ok(());
+        }
+        self.clear_software_breakpoint(slot)?;
+
+        let flash_offset = self.sequence.xip_flash_address(self.interface, address)?;
+
+        let mut original = [0; 3];
+        self.interface.read(address, &mut original)?;
+
+        let mut patch = Vec::with_capacity(3);
+        software_breakpoint(original[0], self.state.code_density).encode_into_vec(&mut patch);
+        let original = original[..patch.len()].to_vec();
+
+        self.write_code(address, flash_offset, &patch)?;
+
+        // Memory that is not writable (e.g. ROM) silently ignores the patch.
+        let mut readback = vec![0; patch.len()];
+        self.interface.read(address, &mut readback)?;
+        if readback != patch {
+            self.write_code(address, flash_offset, &original)?;
+            return Err(Error::Xtensa(XtensaError::BreakpointNotWritable(address)));
+        }
+
+        self.state.software_breakpoints[slot] = Some(SoftwareBreakpoint {
+            address,
+            original,
+            patch,
+            flash_offset,
+        });
+
+        Ok(())
+    }
+
+    fn clear_software_breakpoint(&mut self, slot: usize) -> Result<(), Error> {
+        let Some(breakpoint) = self.state.software_breakpoints.get_mut(slot) else {
+            return Err(Error::Xtensa(XtensaError::InvalidBreakpointUnit(
+                self.hw_breakpoint_units() + slot,
+            )));
+        };
+        if let Some(breakpoint) = breakpoint.take() {
+            self.write_code(
+                breakpoint.address,
+                breakpoint.flash_offset,
+                &breakpoint.original,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes `data` to the code at `address`, which is executed from flash at `flash_offset` if
+    /// it is `Some`.
+    fn write_code(
+        &mut self,
+        address: u64,
+        flash_offset: Option<u64>,
+        data: &[u8],
+    ) -> Result<(), Error> {
+        match flash_offset {
+            // Writes through the cache are ignored or cause a bus error, so the flash itself is
+            // rewritten.
+            Some(flash_offset) => self.write_flash(address, flash_offset, data)?,
+            None => self.interface.write(address, data)?,
+        }
+
+        self.interface
+            .code_barrier(address..address + data.len() as u64)
+    }
+
+    /// Writes `data` to the flash at `flash_offset`, which `address` is mapped to, by erasing and
+    /// rewriting its sector with the ROM routines of the chip.
+    ///
+    /// The halted core calls the routines on the stack of [`RomFlashRoutines`], and its registers
+    /// and the scratch RAM are restored afterwards. The other cores are halted in the meantime,
+    /// as the cache can't fetch code while the flash is busy.
+    fn write_flash(&mut self, address: u64, flash_offset: u64, data: &[u8]) -> Result<(), Error> {
+        let not_writable = || {
+            Error::Xtensa(XtensaError::BreakpointInFlash {
+                address,
+                flash_offset,
+            })
+        };
+
+        let Some(routines) = self.sequence.rom_flash_routines() else {
+            return Err(not_writable());
+        };
+        let Some(sector_routines) = routines.sector else {
+            return Err(not_writable());
+        };
+
+        // Encrypted flash is decrypted by the cache, so the sector can't be written back as read.
+        if self.sequence.flash_encryption_enabled(self.interface)? {
+            return Err(not_writable());
+        }
+
+        let Some(offset_in_sector) = RomFlashRoutines::offset_in_sector(flash_offset, data.len())
+        else {
+            return Err(not_writable());
+        };
+
+        let sector_address = address - offset_in_sector;
+        let mut sector = vec![0; RomFlashRoutines::SECTOR_SIZE as usize];
+        self.interface.read(sector_address, &mut sector)?;
+        let start = offset_in_sector as usize;
+        sector[start..start + data.len()].copy_from_slice(data);
+
+        let scratch_address = routines.stack_pointer - RomFlashRoutines::SCRATCH_SIZE;
+        let mut scratch = vec![0; RomFlashRoutines::SCRATCH_SIZE as usize];
+        self.interface.read(scratch_address, &mut scratch)?;
+
+        let halted_cores = self
+            .interface
+            .halt_other_cores(Duration::from_millis(100))?;
+
+        let result = self.rewrite_flash_sector(
+            &routines,
+            &sector_routines,
+            sector_address,
+            flash_offset - offset_in_sector,
+            &sector,
+        );
+        let restored = self.interface.write(scratch_address, &scratch);
+        let resumed = self.interface.resume_cores(&halted_cores);
+
+        // Report the error of the routines, if there was one.
+        result?;
+        restored?;
+        resumed?;
         Ok(())
     }
+
+    /// Replaces the sector of flash at `sector_offset`, which is mapped to `sector_address`, with
+    /// `contents`.
+    fn rewrite_flash_sector(
+        &mut self,
+        routines: &RomFlashRoutines,
+        sector_routines: &RomSectorRoutines,
+        sector_address: u64,
+        sector_offset: u64,
+        contents: &[u8],
+    ) -> Result<(), Error> {
+        let sector_size = RomFlashRoutines::SECTOR_SIZE;
+        let buffer = routines.sector_buffer();
+        self.interface.write(buffer, contents)?;
+
+        let status = self.call_rom_routine(
+            routines,
+            sector_routines.erase_sector,
+            &[sector_offset / sector_size],
+        )?;
+        RomFlashRoutines::check_status("esp_rom_spiflash_erase_sector", status)?;
+
+        let status = self.call_rom_routine(
+            routines,
+            sector_routines.write,
+            &[sector_offset, buffer, sector_size],
+        )?;
+        RomFlashRoutines::check_status("esp_rom_spiflash_write", status)?;
+
+        let status = self.call_rom_routine(
+            routines,
+            sector_routines.invalidate_cache,
+            &[sector_address, sector_size],
+        )?;
+        RomFlashRoutines::check_status("Cache_Invalidate_Addr", status)
+    }
+
+    /// Calls the ROM routine at `address` on the halted core with the integer `arguments`, on the
+    /// stack of `routines`, and returns its return value.
+    ///
+    /// Like [`Core::call_function`](crate::Core::call_function), the routine returns to the
+    /// address the core is halted at, and the registers are restored afterwards. Interrupts are
+    /// masked while the routine runs, as their handlers may execute from the flash.
+    fn call_rom_routine(
+        &mut self,
+        routines: &RomFlashRoutines,
+        address: u64,
+        arguments: &[u64],
+    ) -> Result<u64, Error> {
+        let context = self.save_context()?;
+
+        let result = self.call_rom_routine_impl(routines, address, arguments);
+        let restored = self.restore_context(&context);
+
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+
+    fn call_rom_routine_impl(
+        &mut self,
+        routines: &RomFlashRoutines,
+        address: u64,
+        arguments: &[u64],
+    ) -> Result<u64, Error> {
+        let stack_pointer = valid_32bit_address(routines.stack_pointer)?;
+        self.write_core_reg(SP.id(), RegisterValue::U32(stack_pointer))?;
+
+        let return_address = self.read_core_reg(PC.id())?.try_into()?;
+        self.prepare_function_call(address, return_address, arguments)?;
+        self.interface.mask_interrupts()?;
+
+        let info = self.run_to_return(return_address, RomFlashRoutines::TIMEOUT)?;
+        if info.pc != return_address {
+            return Err(Error::GenericCoreError(format!(
+                "The ROM routine at {address:#010x} did not return, the core halted at {:#010x}",
+                info.pc
+            )));
+        }
+
+        self.function_call_result()?.try_into()
+    }
+
+    /// Runs the core until it reaches `return_address`, or until `timeout` elapses.
+    ///
+    /// Unlike [`CoreInterface::run_to`], this always uses the first hardware breakpoint unit and
+    /// never a software breakpoint, which would have to be written with the ROM routines. The
+    /// other units are disabled in the meantime, and all of them are restored afterwards.
+    fn run_to_return(
+        &mut self,
+        return_address: u64,
+        timeout: Duration,
+    ) -> Result<CoreInformation, Error> {
+        if self.hw_breakpoint_units() == 0 {
+            return Err(Error::GenericCoreError(
+                "The ROM routines can't be called without a hardware breakpoint unit".to_string(),
+            ));
+        }
+
+        let address = valid_32bit_address(return_address)?;
+        self.interface
+            .write_register_untyped(Self::IBREAKA_REGS[0], address)?;
+        self.interface
+            .write_register_untyped(SpecialRegister::IBreakEnable, 1)?;
+
+        let result = self.interface.resume().map_err(Error::from).and_then(|_| {
+            match self.wait_for_core_halted(timeout) {
+                Ok(()) => self.core_info(),
+                Err(error) => self.halt(timeout).and(Err(error)),
+            }
+        });
+        let restored = self.restore_breakpoints();
+
+        let info = result?;
+        restored?;
+        Ok(info)
+    }
 }
 
 impl<'probe> MemoryInterface for Xtensa<'probe> {
@@ -116,27 +629,49 @@ impl<'probe> MemoryInterface for Xtensa<'probe> {
     }
 
     fn read_word_64(&mut self, address: u64) -> Result<u64, Error> {
-        self.interface.read_word_64(address)
+        let mut data = [0; 8];
+        self.read_8(address, &mut data)?;
+        Ok(u64::from_le_bytes(data))
     }
 
     fn read_word_32(&mut self, address: u64) -> Result<u32, Error> {
-        self.interface.read_word_32(address)
+        let mut data = [0; 4];
+        self.read_8(address, &mut data)?;
+        Ok(u32::from_le_bytes(data))
     }
 
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
-        self.interface.read_word_8(address)
+        let mut data = [0; 1];
+        self.read_8(address, &mut data)?;
+        Ok(data[0])
     }
 
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), Error> {
-        self.interface.read_64(address, data)
+        let mut bytes = vec![0; data.len() * 8];
+        self.read_8(address, &mut bytes)?;
+        for (word, bytes) in data.iter_mut().zip(bytes.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Ok(())
     }
 
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
-        self.interface.read_32(address, data)
+        let mut bytes = vec![0; data.len() * 4];
+        self.read_8(address, &mut bytes)?;
+        for (word, bytes) in data.iter_mut().zip(bytes.chunks_exact(4)) {
+            *word = u32::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Ok(())
     }
 
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
-        self.interface.read_8(address, data)
+        self.read(address, data)
+    }
+
+    fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.interface.read(address, data)?;
+        self.apply_spill_areas(address, data);
+        Ok(())
     }
 
     fn write_word_64(&mut self, address: u64, data: u64) -> Result<(), Error> {
@@ -167,6 +702,22 @@ impl<'probe> MemoryInterface for Xtensa<'probe> {
         self.interface.write(address, data)
     }
 
+    fn read_word_32_exact(&mut self, address: u64) -> Result<u32, Error> {
+        self.interface.read_word_32_exact(address)
+    }
+
+    fn read_word_8_exact(&mut self, address: u64) -> Result<u8, Error> {
+        self.interface.read_word_8_exact(address)
+    }
+
+    fn write_word_32_exact(&mut self, address: u64, data: u32) -> Result<(), Error> {
+        self.interface.write_word_32_exact(address, data)
+    }
+
+    fn write_word_8_exact(&mut self, address: u64, data: u8) -> Result<(), Error> {
+        self.interface.write_word_8_exact(address, data)
+    }
+
     fn supports_8bit_transfers(&self) -> Result<bool, Error> {
         self.interface.supports_8bit_transfers()
     }
@@ -174,6 +725,10 @@ impl<'probe> MemoryInterface for Xtensa<'probe> {
     fn flush(&mut self) -> Result<(), Error> {
         self.interface.flush()
     }
+
+    fn code_barrier(&mut self, range: Range<u64>) -> Result<(), Error> {
+        self.interface.code_barrier(range)
+    }
 }
 
 impl<'probe> CoreInterface for Xtensa<'probe> {
@@ -197,7 +752,14 @@ impl<'probe> CoreInterface for Xtensa<'probe> {
     }
 
     fn status(&mut self) -> Result<CoreStatus, Error> {
+        if self.interface.is_sleeping()? {
+            return Ok(CoreStatus::Sleeping);
+        }
+
         if self.interface.is_halted()? {
+            // The core may have halted on its own, e.g. on a breakpoint.
+            self.sequence.on_halt(self.interface)?;
+
             let debug_cause = self.interface.read_register::<DebugCause>()?;
 
             let is_icount_exception = debug_cause.icount_exception();
@@ -215,7 +777,7 @@ impl<'probe> CoreInterface for Xtensa<'probe> {
                 + is_debug_interrupt as u8;
 
             if count > 1 {
-                return Ok(CoreStatus::Halted(HaltReason::Multiple));
+                return Ok(CoreStatus::Halted(HaltReason::Multiple(debug_cause.0)));
             }
 
             if is_icount_exception {
@@ -229,19 +791,37 @@ impl<'probe> CoreInterface for Xtensa<'probe> {
             }
 
             if is_break_instruction || is_break_n_instruction {
+                if let Some(command) = self.semihosting_command()? {
+                    return Ok(CoreStatus::Halted(HaltReason::Breakpoint(
+                        BreakpointCause::Semihosting(command),
+                    )));
+                }
+
+                // The double exception handler stops the core with a BREAK instruction, as the
+                // exception can't be recovered from.
+                if let Some(cause @ ExceptionCause::DoubleFault { .. }) = self.exception_cause()? {
+                    return Ok(CoreStatus::Halted(HaltReason::Exception(cause)));
+                }
+
                 return Ok(CoreStatus::Halted(HaltReason::Breakpoint(
                     BreakpointCause::Software,
                 )));
             }
 
             if is_dbreak_exception {
-                return Ok(CoreStatus::Halted(HaltReason::Watchpoint));
+                return Ok(CoreStatus::Halted(HaltReason::Watchpoint(Some(
+                    debug_cause.dbreak_num() as usize,
+                ))));
             }
 
             if is_debug_interrupt {
                 return Ok(CoreStatus::Halted(HaltReason::Request));
             }
 
+            if let Some(cause) = self.exception_cause()? {
+                return Ok(CoreStatus::Halted(HaltReason::Exception(cause)));
+            }
+
             Ok(CoreStatus::Halted(HaltReason::Unknown))
         } else {
             Ok(CoreStatus::Running)
@@ -251,33 +831,123 @@ impl<'probe> CoreInterface for Xtensa<'probe> {
     fn halt(&mut self, timeout: Duration) -> Result<CoreInformation, Error> {
         self.interface.halt()?;
         self.interface.wait_for_core_halted(timeout)?;
+        self.sequence.on_halt(self.interface)?;
 
         self.core_info()
     }
 
     fn run(&mut self) -> Result<(), Error> {
+        self.prepare_run()?;
+        self.run_prepared()
+    }
+
+    fn prepare_run(&mut self) -> Result<(), Error> {
+        self.state.spill_areas = None;
         self.skip_breakpoint_instruction()?;
+        self.step_over_software_breakpoint()?;
+        self.interface.resume_watchdogs()?;
+        Ok(())
+    }
+
+    fn run_prepared(&mut self) -> Result<(), Error> {
         Ok(self.interface.resume()?)
     }
 
+    fn memory_attributes(&mut self, address: u64) -> Result<MemoryAttributes, Error> {
+        let address = self.interface.translate_address(address);
+        let flash_offset = match self.sequence.translate_address(self.interface, address)? {
+            Some((ExternalMemory::Flash, offset)) => offset,
+            Some((ExternalMemory::Psram, offset)) => {
+                return Ok(MemoryAttributes {
+                    psram_offset: Some(offset),
+                    ..MemoryAttributes::default()
+                })
+            }
+            None => return Ok(MemoryAttributes::default()),
+        };
+
+        let encrypted = self.sequence.flash_encryption_enabled(self.interface)?;
+
+        Ok(MemoryAttributes {
+            flash_offset: Some(flash_offset),
+            psram_offset: None,
+            encrypted,
+            ciphertext: encrypted && !self.interface.flash_decryption(),
+        })
+    }
+
+    fn set_flash_decryption(&mut self, enabled: bool) -> Result<(), Error> {
+        self.sequence
+            .set_flash_decryption(self.interface, enabled)?;
+        self.interface.set_flash_decryption(enabled);
+
+        Ok(())
+    }
+
+    fn sample_pc(&mut self) -> Result<Option<u64>, Error> {
+        // DEBUGPC is only meaningful while the core runs.
+        if self.interface.is_halted()? {
+            return Ok(None);
+        }
+
+        Ok(Some(self.interface.sample_pc()? as u64))
+    }
+
     fn reset(&mut self) -> Result<(), Error> {
-        Ok(self.interface.reset()?)
+        self.state.spill_areas = None;
+        self.interface.reset_and_halt(Duration::from_millis(500))?;
+
+        // The reset cleared the breakpoint registers.
+        self.restore_breakpoints()?;
+
+        self.interface
+            .resume()
+            .map_err(XtensaError::reset_failed(ResetPhase::Resume))?;
+
+        Ok(())
     }
 
     fn reset_and_halt(&mut self, timeout: Duration) -> Result<CoreInformation, Error> {
-        self.interface.reset_and_halt(timeout)?;
+        self.state.spill_areas = None;
+        self.sequence
+            .reset_system_and_halt(self.interface, timeout)?;
+        self.sequence.on_reset(self.interface)?;
+
+        // The reset cleared the breakpoint registers.
+        self.restore_breakpoints()?;
 
         self.core_info()
     }
 
     fn step(&mut self) -> Result<CoreInformation, Error> {
-        self.skip_breakpoint_instruction()?;
-        self.interface.step()?;
+        self.state.spill_areas = None;
+        if self.skip_breakpoint_instruction()? {
+            // Skipping the BREAK instruction completes the step. The PC is marked as written, so
+            // that the instruction is not skipped again.
+            return self.core_info();
+        }
+        if !self.step_over_software_breakpoint()? {
+            self.interface.step()?;
+        }
         self.state.pc_written = false;
 
         self.core_info()
     }
 
+    fn step_with_options(&mut self, options: StepOptions) -> Result<CoreInformation, Error> {
+        let step_mode = self.interface.step_mode();
+        self.interface.set_step_mode(if options.mask_interrupts() {
+            StepMode::OverInterrupts
+        } else {
+            StepMode::IntoInterrupts
+        });
+
+        let result = step_repeatedly(self, options, |core| core.step());
+
+        self.interface.set_step_mode(step_mode);
+        result
+    }
+
     fn read_core_reg(&mut self, address: RegisterId) -> Result<RegisterValue, Error> {
         let register = Register::try_from(address)?;
         let value = self.interface.read_register_untyped(register)?;
@@ -285,6 +955,17 @@ impl<'probe> CoreInterface for Xtensa<'probe> {
         Ok(RegisterValue::U32(value))
     }
 
+    fn read_core_regs(&mut self, addresses: &[RegisterId]) -> Result<Vec<RegisterValue>, Error> {
+        let registers = addresses
+            .iter()
+            .map(|address| Register::try_from(*address))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let values = self.interface.read_registers(&registers)?;
+
+        Ok(values.into_iter().map(RegisterValue::U32).collect())
+    }
+
     fn write_core_reg(&mut self, address: RegisterId, value: RegisterValue) -> Result<(), Error> {
         let value: u32 = value.try_into()?;
 
@@ -295,76 +976,316 @@ impl<'probe> CoreInterface for Xtensa<'probe> {
         let register = Register::try_from(address)?;
         self.interface.write_register_untyped(register, value)?;
 
+        // Writing the register file may change the contents of the live windows.
+        self.state.spill_areas = None;
+
         Ok(())
     }
 
-    fn available_breakpoint_units(&mut self) -> Result<u32, Error> {
-        Ok(self.interface.available_breakpoint_units())
+    /// Saves the whole physical register file instead of the logical address registers, so that
+    /// the callers' windows held in the register file survive code that rotates the window.
+    fn save_context(&mut self) -> Result<CoreContext, Error> {
+        self.interface.detect_fpu()?;
+
+        let register_file = self.interface.read_register_file()?;
+
+        let addresses = self
+            .registers()
+            .all_registers()
+            .map(|register| register.id())
+            .filter(|address| !matches!(Register::try_from(*address), Ok(Register::Cpu(_))))
+            .collect::<Vec<_>>();
+        let values = self.read_core_regs(&addresses)?;
+        let registers = addresses.into_iter().zip(values).collect();
+
+        Ok(CoreContext::new(registers).with_register_file(register_file))
     }
 
-    fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
-        let mut breakpoints = Vec::with_capacity(self.available_breakpoint_units()? as usize);
+    fn restore_context(&mut self, context: &CoreContext) -> Result<(), Error> {
+        let registers = context
+            .registers()
+            .map(|(address, value)| Ok((Register::try_from(address)?, value.try_into()?)))
+            .collect::<Result<Vec<(Register, u32)>, Error>>()?;
 
-        let enabled_breakpoints = self
-            .interface
-            .read_register_untyped(Register::Special(SpecialRegister::IBreakEnable))?;
-
-        for i in 0..self.available_breakpoint_units()? as usize {
-            let is_enabled = enabled_breakpoints & (1 << i) != 0;
-            let breakpoint = if is_enabled {
-                let address = self
-                    .interface
-                    .read_register_untyped(Register::Special(Self::IBREAKA_REGS[i]))?;
-
-                Some(address as u64)
-            } else {
-                None
-            };
+        // WINDOWBASE and PS are restored before the address registers. Writing WINDOWBASE rotates
+        // the logical registers, so the scratch register restored after writing special
+        // registers ends up in the wrong physical register, which is then overwritten.
+        self.interface.write_registers(&registers)?;
+        self.interface
+            .write_register_file(context.register_file())?;
 
-            breakpoints.push(breakpoint)
+        self.state.pc_written = true;
+        self.state.spill_areas = None;
+
+        Ok(())
+    }
+
+    /// Sets up a call following the windowed ABI, as if the halted code executed CALL4: the
+    /// function's ENTRY instruction rotates the window by PS.CALLINC, so the arguments in a6-a11
+    /// become the function's a2-a7, and the return address in a4 becomes its a0.
+    fn prepare_function_call(
+        &mut self,
+        address: u64,
+        return_address: u64,
+        arguments: &[u64],
+    ) -> Result<(), Error> {
+        const ARGUMENT_REGISTERS: [CpuRegister; 6] = [
+            CpuRegister::A6,
+            CpuRegister::A7,
+            CpuRegister::A8,
+            CpuRegister::A9,
+            CpuRegister::A10,
+            CpuRegister::A11,
+        ];
+
+        if !self.interface.windowed_registers() {
+            return Err(Error::GenericCoreError(
+                "Functions can only be called on cores with register windows".to_string(),
+            ));
+        }
+
+        if arguments.len() > ARGUMENT_REGISTERS.len() {
+            return Err(Error::GenericCoreError(format!(
+                "Functions can be called with at most {} arguments",
+                ARGUMENT_REGISTERS.len()
+            )));
+        }
+
+        let address = valid_32bit_address(address)?;
+        let return_address = valid_32bit_address(return_address)?;
+
+        // RETW takes the two most significant bits of the return address from the PC.
+        if (address ^ return_address) & 0xC000_0000 != 0 {
+            return Err(Error::GenericCoreError(format!(
+                "The function at {address:#010x} can't return to {return_address:#010x}, which is in another 1GB region"
+            )));
         }
 
+        let [ps, window_base, sp] = self.interface.read_registers(&[
+            Register::CurrentPs,
+            Register::Special(SpecialRegister::Windowbase),
+            Register::Cpu(CpuRegister::A1),
+        ])?[..] else {
+            unreachable!("read_registers returns a value for each register");
+        };
+
+        // Keep INTLEVEL and UM, enable window overflow exceptions, clear EXCM, and set CALLINC
+        // to 1.
+        let old_ps = ProgramStatus::from(ps);
+        let mut ps = ProgramStatus::from(0);
+        ps.set_intlevel(old_ps.intlevel());
+        ps.set_user_mode(old_ps.user_mode());
+        ps.set_woe(true);
+        ps.set_callinc(1);
+
+        let mut registers = vec![
+            (Register::CurrentPs, ps.into()),
+            // Only the current frame is marked live, so that the function doesn't spill the
+            // callers' windows. They are part of the saved context, and restored afterwards.
+            (
+                Register::Special(SpecialRegister::Windowstart),
+                1 << window_base,
+            ),
+            (Register::CurrentPc, address),
+            // The window increment of CALL4 is stored in the two most significant bits.
+            (
+                Register::Cpu(CpuRegister::A4),
+                (1 << 30) | (return_address & 0x3FFF_FFFF),
+            ),
+            (Register::Cpu(CpuRegister::A5), sp),
+        ];
+        for (register, argument) in ARGUMENT_REGISTERS.into_iter().zip(arguments) {
+            let argument = u32::try_from(*argument).map_err(|_| {
+                Error::GenericCoreError(format!("Argument {argument:#x} does not fit a register"))
+            })?;
+            registers.push((Register::Cpu(register), argument));
+        }
+
+        self.interface.write_registers(&registers)?;
+
+        self.state.pc_written = true;
+        self.state.spill_areas = None;
+
+        Ok(())
+    }
+
+    fn function_call_result(&mut self) -> Result<RegisterValue, Error> {
+        // The function's a2 is the caller's a6 once RETW rotated the window back.
+        let value = self.interface.read_register_untyped(CpuRegister::A6)?;
+
+        Ok(RegisterValue::U32(value))
+    }
+
+    /// Returns the number of hardware breakpoint units, followed by a number of software
+    /// breakpoint slots. Software breakpoints are used once the hardware units are exhausted.
+    fn available_breakpoint_units(&mut self) -> Result<u32, Error> {
+        Ok((self.hw_breakpoint_units() + self.state.software_breakpoints.len()) as u32)
+    }
+
+    fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, Error> {
+        let hardware_units = self.hw_breakpoint_units();
+        let mut breakpoints = self.state.breakpoints.addresses()[..hardware_units].to_vec();
+
+        breakpoints.extend(
+            self.state
+                .software_breakpoints
+                .iter()
+                .map(|breakpoint| breakpoint.as_ref().map(|bp| bp.address)),
+        );
+
         Ok(breakpoints)
     }
 
     fn enable_breakpoints(&mut self, state: bool) -> Result<(), Error> {
-        self.state.breakpoints_enabled = state;
-        let mask = self.state.breakpoint_mask();
+        self.state.breakpoints.set_enabled(state);
 
-        self.interface
-            .write_register_untyped(SpecialRegister::IBreakEnable, if state { mask } else { 0 })?;
+        self.interface.write_register_untyped(
+            SpecialRegister::IBreakEnable,
+            self.state.breakpoints.enable_mask(),
+        )?;
 
         Ok(())
     }
 
     fn set_hw_breakpoint(&mut self, unit_index: usize, addr: u64) -> Result<(), Error> {
-        self.state.breakpoint_set[unit_index] = true;
+        if unit_index >= self.hw_breakpoint_units() {
+            return self.set_software_breakpoint(unit_index - self.hw_breakpoint_units(), addr);
+        }
+
+        let address = valid_32bit_address(addr)?;
+        self.state.breakpoints.set(unit_index, addr)?;
         self.interface
-            .write_register_untyped(Self::IBREAKA_REGS[unit_index], addr as u32)?;
+            .write_register_untyped(Self::IBREAKA_REGS[unit_index], address)?;
 
-        if self.state.breakpoints_enabled {
-            let mask = self.state.breakpoint_mask();
-            self.interface
-                .write_register_untyped(SpecialRegister::IBreakEnable, mask)?;
+        if self.state.breakpoints.is_enabled() {
+            self.interface.write_register_untyped(
+                SpecialRegister::IBreakEnable,
+                self.state.breakpoints.enable_mask(),
+            )?;
         }
 
         Ok(())
     }
 
     fn clear_hw_breakpoint(&mut self, unit_index: usize) -> Result<(), Error> {
-        self.state.breakpoint_set[unit_index] = false;
+        if unit_index >= self.hw_breakpoint_units() {
+            return self.clear_software_breakpoint(unit_index - self.hw_breakpoint_units());
+        }
+
+        self.state.breakpoints.clear(unit_index);
+
+        if self.state.breakpoints.is_enabled() {
+            self.interface.write_register_untyped(
+                SpecialRegister::IBreakEnable,
+                self.state.breakpoints.enable_mask(),
+            )?;
+        }
 
-        if self.state.breakpoints_enabled {
-            let mask = self.state.breakpoint_mask();
+        Ok(())
+    }
+
+    fn restore_breakpoints(&mut self) -> Result<(), Error> {
+        let configured = self.state.breakpoints.configured().collect::<Vec<_>>();
+        for (unit, address) in configured {
             self.interface
-                .write_register_untyped(SpecialRegister::IBreakEnable, mask)?;
+                .write_register_untyped(Self::IBREAKA_REGS[unit], address as u32)?;
         }
 
+        self.interface.write_register_untyped(
+            SpecialRegister::IBreakEnable,
+            self.state.breakpoints.enable_mask(),
+        )?;
+
+        Ok(())
+    }
+
+    fn take_reset_cause(&mut self) -> Result<Option<ResetCause>, Error> {
+        Ok(self.interface.take_reset_cause()?)
+    }
+
+    fn available_watchpoint_units(&mut self) -> Result<u32, Error> {
+        Ok(self.interface.available_watchpoint_units())
+    }
+
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, Error> {
+        let mut watchpoints = vec![];
+
+        for i in 0..self.interface.available_watchpoint_units() as usize {
+            let control = self
+                .interface
+                .read_register_untyped(Register::Special(Self::DBREAKC_REGS[i]))?;
+
+            let kind = match (
+                control & Self::DBREAKC_LOAD != 0,
+                control & Self::DBREAKC_STORE != 0,
+            ) {
+                (true, true) => WatchpointKind::ReadWrite,
+                (true, false) => WatchpointKind::Read,
+                (false, true) => WatchpointKind::Write,
+                (false, false) => {
+                    watchpoints.push(None);
+                    continue;
+                }
+            };
+
+            let address = self
+                .interface
+                .read_register_untyped(Register::Special(Self::DBREAKA_REGS[i]))?;
+            let length = 1 << (!control & Self::DBREAKC_MASK).count_ones();
+
+            watchpoints.push(Some(Watchpoint {
+                address: address as u64,
+                length,
+                kind,
+            }));
+        }
+
+        Ok(watchpoints)
+    }
+
+    fn set_hw_watchpoint(
+        &mut self,
+        unit_index: usize,
+        watchpoint: Watchpoint,
+    ) -> Result<(), Error> {
+        watchpoint.check_mask_range(Self::DBREAKC_MASK as u64 + 1)?;
+        let address = valid_32bit_address(watchpoint.address)?;
+
+        let mut control = Self::DBREAKC_MASK & !(watchpoint.length as u32 - 1);
+        if matches!(
+            watchpoint.kind,
+            WatchpointKind::Read | WatchpointKind::ReadWrite
+        ) {
+            control |= Self::DBREAKC_LOAD;
+        }
+        if matches!(
+            watchpoint.kind,
+            WatchpointKind::Write | WatchpointKind::ReadWrite
+        ) {
+            control |= Self::DBREAKC_STORE;
+        }
+
+        self.interface
+            .write_register_untyped(Self::DBREAKA_REGS[unit_index], address)?;
+        self.interface
+            .write_register_untyped(Self::DBREAKC_REGS[unit_index], control)?;
+
+        Ok(())
+    }
+
+    fn clear_hw_watchpoint(&mut self, unit_index: usize) -> Result<(), Error> {
+        self.interface
+            .write_register_untyped(Self::DBREAKC_REGS[unit_index], 0)?;
+
         Ok(())
     }
 
     fn registers(&self) -> &'static CoreRegisters {
-        &XTENSA_CORE_REGSISTERS
+        if self.interface.fpu_present() {
+            &XTENSA_WITH_FP_CORE_REGSISTERS
+        } else {
+            &XTENSA_CORE_REGSISTERS
+        }
     }
 
     fn program_counter(&self) -> &'static CoreRegister {
@@ -384,7 +1305,51 @@ impl<'probe> CoreInterface for Xtensa<'probe> {
     }
 
     fn hw_breakpoints_enabled(&self) -> bool {
-        self.state.breakpoints_enabled
+        self.state.breakpoints.is_enabled()
+    }
+
+    /// The registers used for stepping, breakpoints and watchpoints, and PS, which sets up the
+    /// window and interrupt level the debugger executes instructions with.
+    fn run_control_registers(&self) -> &'static [RegisterId] {
+        const fn special(register: SpecialRegister) -> RegisterId {
+            RegisterId(0x0100 | register as u16)
+        }
+
+        const REGISTERS: [RegisterId; 10] = [
+            special(SpecialRegister::Ps),
+            special(SpecialRegister::ICount),
+            special(SpecialRegister::ICountLevel),
+            special(SpecialRegister::IBreakEnable),
+            special(SpecialRegister::IBreakA0),
+            special(SpecialRegister::IBreakA1),
+            special(SpecialRegister::DBreakA0),
+            special(SpecialRegister::DBreakA1),
+            special(SpecialRegister::DBreakC0),
+            special(SpecialRegister::DBreakC1),
+        ];
+
+        &REGISTERS
+    }
+
+    /// The window registers, which locate the callers' frames in the register file, and PS,
+    /// whose CALLINC field tells how far the window rotated for the current call. Cores without
+    /// register windows have none.
+    fn frame_registers(&self) -> &'static [RegisterId] {
+        const fn special(register: SpecialRegister) -> RegisterId {
+            RegisterId(0x0100 | register as u16)
+        }
+
+        const REGISTERS: [RegisterId; 3] = [
+            special(SpecialRegister::Windowbase),
+            special(SpecialRegister::Windowstart),
+            special(SpecialRegister::Ps),
+        ];
+
+        if self.interface.windowed_registers() {
+            &REGISTERS
+        } else {
+            &[]
+        }
     }
 
     fn architecture(&self) -> Architecture {
@@ -396,18 +1361,45 @@ impl<'probe> CoreInterface for Xtensa<'probe> {
     }
 
     fn instruction_set(&mut self) -> Result<InstructionSet, Error> {
-        // TODO: NX exists, too
-        Ok(InstructionSet::Xtensa)
+        if self.state.nx {
+            Ok(InstructionSet::XtensaNx)
+        } else {
+            Ok(InstructionSet::Xtensa)
+        }
+    }
+
+    fn disassemble(
+        &mut self,
+        address: u64,
+        code: &[u8],
+    ) -> Result<Vec<DisassembledInstruction>, Error> {
+        if self.state.nx {
+            // The NX variant uses a different encoding.
+            return Err(Error::NotImplemented("disassembly of Xtensa NX code"));
+        }
+
+        Ok(disassemble::disassemble(
+            address,
+            code,
+            self.state.code_density,
+        ))
     }
 
     fn fpu_support(&mut self) -> Result<bool, Error> {
-        // TODO: ESP32 and ESP32-S3 have FPU
-        Ok(false)
+        Ok(self.interface.detect_fpu()?)
     }
 
     fn floating_point_register_count(&mut self) -> Result<usize, Error> {
-        // TODO: ESP32 and ESP32-S3 have FPU
-        Ok(0)
+        if self.interface.detect_fpu()? {
+            Ok(16)
+        } else {
+            Ok(0)
+        }
+    }
+
+    fn requires_halted_memory_access(&self) -> bool {
+        // Memory is accessed by executing instructions in debug mode.
+        true
     }
 
     fn reset_catch_set(&mut self) -> Result<(), Error> {
@@ -419,7 +1411,93 @@ impl<'probe> CoreInterface for Xtensa<'probe> {
     }
 
     fn debug_core_stop(&mut self) -> Result<(), Error> {
+        self.sequence.on_detach(self.interface)?;
         self.interface.leave_ocd_mode()?;
         Ok(())
     }
+
+    fn on_session_stop(&mut self) -> Result<(), Error> {
+        self.interface.restore_core_state()?;
+        Ok(())
+    }
+}
+
+/// Returns the exception cause for EXCCAUSE `cause`, with the faulting `address` from EXCVADDR,
+/// and the PC saved in DEPC if the exception is a double exception.
+fn exception_cause_from(cause: u32, address: Option<u64>, depc: Option<u64>) -> ExceptionCause {
+    let (description, _) = describe_exception_cause(cause);
+
+    match depc {
+        Some(pc) => ExceptionCause::DoubleFault {
+            cause,
+            description,
+            address,
+            pc,
+        },
+        None => ExceptionCause::Fault {
+            cause,
+            description,
+            address,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exception_cause_descriptions() {
+        assert_eq!(describe_exception_cause(0), ("IllegalInstruction", false));
+        assert_eq!(describe_exception_cause(6), ("IntegerDivideByZero", false));
+        assert_eq!(describe_exception_cause(9), ("LoadStoreAlignment", true));
+        assert_eq!(describe_exception_cause(28), ("LoadProhibited", true));
+        assert_eq!(describe_exception_cause(29), ("StoreProhibited", true));
+        assert_eq!(describe_exception_cause(32), ("CoprocessorDisabled", false));
+        assert_eq!(describe_exception_cause(39), ("CoprocessorDisabled", false));
+        assert_eq!(describe_exception_cause(7), ("Reserved", false));
+        assert_eq!(describe_exception_cause(40), ("Reserved", false));
+    }
+
+    #[test]
+    fn double_exception_vector() {
+        let in_vector = Xtensa::in_double_exception_vector;
+
+        assert!(in_vector(0x4000_0000, 0x4000_03C0));
+        assert!(in_vector(0x4000_0000, 0x4000_03FF));
+        assert!(!in_vector(0x4000_0000, 0x4000_03BF));
+        assert!(!in_vector(0x4000_0000, 0x4000_0400));
+    }
+
+    #[test]
+    fn decode_exception_causes() {
+        let fault = exception_cause_from(29, Some(0x3FFB_0000), None);
+        assert_eq!(
+            fault,
+            ExceptionCause::Fault {
+                cause: 29,
+                description: "StoreProhibited",
+                address: Some(0x3FFB_0000),
+            }
+        );
+        assert_eq!(
+            fault.to_string(),
+            "StoreProhibited (29) at address 0x3ffb0000"
+        );
+
+        let double_fault = exception_cause_from(0, None, Some(0x4008_1234));
+        assert_eq!(
+            double_fault,
+            ExceptionCause::DoubleFault {
+                cause: 0,
+                description: "IllegalInstruction",
+                address: None,
+                pc: 0x4008_1234,
+            }
+        );
+        assert_eq!(
+            double_fault.to_string(),
+            "double exception at 0x40081234: IllegalInstruction (0)"
+        );
+    }
 }
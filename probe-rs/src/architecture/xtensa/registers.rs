@@ -47,4 +47,391 @@ pub const FP: CoreRegister = CoreRegister {
 pub(crate) static XTENSA_CORE_REGSISTERS: Lazy<CoreRegisters> =
     Lazy::new(|| CoreRegisters::new(XTENSA_REGISTERS_SET.iter().collect()));
 
-static XTENSA_REGISTERS_SET: &[CoreRegister] = &[RA, PC, SP, FP];
+pub(crate) static XTENSA_WITH_FP_CORE_REGSISTERS: Lazy<CoreRegisters> = Lazy::new(|| {
+    CoreRegisters::new(
+        XTENSA_REGISTERS_SET
+            .iter()
+            .chain(XTENSA_FP_REGISTERS_SET)
+            .collect(),
+    )
+});
+
+/// The logical address registers of the current window, followed by the program counter and the
+/// special registers that are present on all supported cores.
+///
+/// The special registers follow the order in which GDB numbers them for Xtensa cores, so that
+/// target descriptions generated from this list match xt-gdb's expectations.
+///
+/// The address registers are listed in DWARF register number order. None of them has an argument
+/// or return value role: functions (e.g. flash algorithms) are called from the debugger following
+/// the windowed ABI, where these registers depend on the window increment, see
+/// [`prepare_function_call`](crate::CoreInterface::prepare_function_call).
+static XTENSA_REGISTERS_SET: &[CoreRegister] = &[
+    RA,
+    SP,
+    CoreRegister {
+        roles: &[RegisterRole::Core("a2")],
+        id: crate::RegisterId(0x0002),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a3")],
+        id: crate::RegisterId(0x0003),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a4")],
+        id: crate::RegisterId(0x0004),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a5")],
+        id: crate::RegisterId(0x0005),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a6")],
+        id: crate::RegisterId(0x0006),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    FP,
+    CoreRegister {
+        roles: &[RegisterRole::Core("a8")],
+        id: crate::RegisterId(0x0008),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a9")],
+        id: crate::RegisterId(0x0009),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a10")],
+        id: crate::RegisterId(0x000A),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a11")],
+        id: crate::RegisterId(0x000B),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a12")],
+        id: crate::RegisterId(0x000C),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a13")],
+        id: crate::RegisterId(0x000D),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a14")],
+        id: crate::RegisterId(0x000E),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("a15")],
+        id: crate::RegisterId(0x000F),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    PC,
+    CoreRegister {
+        roles: &[RegisterRole::Core("lbeg")],
+        id: crate::RegisterId(0x0100),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("lend")],
+        id: crate::RegisterId(0x0101),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("lcount")],
+        id: crate::RegisterId(0x0102),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("sar")],
+        id: crate::RegisterId(0x0103),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("windowbase")],
+        id: crate::RegisterId(0x0148),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("windowstart")],
+        id: crate::RegisterId(0x0149),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("ps")],
+        id: crate::RegisterId(0xFF01),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("epc1")],
+        id: crate::RegisterId(0x01B1),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("epc2")],
+        id: crate::RegisterId(0x01B2),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("epc3")],
+        id: crate::RegisterId(0x01B3),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("epc4")],
+        id: crate::RegisterId(0x01B4),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("epc5")],
+        id: crate::RegisterId(0x01B5),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("epc6")],
+        id: crate::RegisterId(0x01B6),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("epc7")],
+        id: crate::RegisterId(0x01B7),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("eps2")],
+        id: crate::RegisterId(0x01C2),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("eps3")],
+        id: crate::RegisterId(0x01C3),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("eps4")],
+        id: crate::RegisterId(0x01C4),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("eps5")],
+        id: crate::RegisterId(0x01C5),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("eps6")],
+        id: crate::RegisterId(0x01C6),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("eps7")],
+        id: crate::RegisterId(0x01C7),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("excsave1")],
+        id: crate::RegisterId(0x01D1),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("excsave2")],
+        id: crate::RegisterId(0x01D2),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("excsave3")],
+        id: crate::RegisterId(0x01D3),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("excsave4")],
+        id: crate::RegisterId(0x01D4),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("excsave5")],
+        id: crate::RegisterId(0x01D5),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("excsave6")],
+        id: crate::RegisterId(0x01D6),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("excsave7")],
+        id: crate::RegisterId(0x01D7),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("exccause")],
+        id: crate::RegisterId(0x01E8),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("excvaddr")],
+        id: crate::RegisterId(0x01EE),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+];
+
+static XTENSA_FP_REGISTERS_SET: &[CoreRegister] = &[
+    CoreRegister {
+        roles: &[RegisterRole::Core("f0"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0200),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f1"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0201),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f2"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0202),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f3"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0203),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f4"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0204),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f5"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0205),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f6"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0206),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f7"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0207),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f8"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0208),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f9"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x0209),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f10"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x020A),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f11"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x020B),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f12"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x020C),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f13"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x020D),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f14"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x020E),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("f15"), RegisterRole::FloatingPoint],
+        id: crate::RegisterId(0x020F),
+        data_type: RegisterDataType::FloatingPoint(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("fcr")],
+        id: crate::RegisterId(0x03E8),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+    CoreRegister {
+        roles: &[RegisterRole::Core("fsr"), RegisterRole::FloatingPointStatus],
+        id: crate::RegisterId(0x03E9),
+        data_type: RegisterDataType::UnsignedInteger(32),
+        unwind_rule: UnwindRule::Clear,
+    },
+];
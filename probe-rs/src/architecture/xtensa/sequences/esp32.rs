@@ -1,14 +1,45 @@
 //! Sequence for the ESP32.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use probe_rs_target::Chip;
 
-use super::XtensaDebugSequence;
+use super::{app_cpu_stall_values, RomFlashRoutines, Watchdog, XtensaDebugSequence};
 use crate::{
-    architecture::xtensa::communication_interface::XtensaCommunicationInterface, MemoryInterface,
+    architecture::xtensa::{
+        communication_interface::XtensaCommunicationInterface,
+        efuse::{EfuseLayout, ESP32_EFUSES},
+    },
+    MemoryInterface,
 };
 
+const RTC_CNTL_BASE: u64 = 0x3ff48000;
+const RTC_CNTL_OPTIONS0: u64 = RTC_CNTL_BASE;
+const RTC_CNTL_SW_CPU_STALL: u64 = RTC_CNTL_BASE | 0xac;
+
+const OPTIONS0_SW_SYS_RST: u32 = 1 << 31;
+
+/// EFUSE_BLK0_RDATA0_REG, which holds FLASH_CRYPT_CNT.
+const EFUSE_BLK0_RDATA0: u64 = 0x3ff5a000;
+const FLASH_CRYPT_CNT_SHIFT: u32 = 20;
+const FLASH_CRYPT_CNT_MASK: u32 = 0x7f;
+
+/// The flash routines of the ROM, from the linker script of the ROM in ESP-IDF.
+const ROM_FLASH_ROUTINES: RomFlashRoutines = RomFlashRoutines {
+    attach: 0x40062a6c,
+    unlock: 0x400628b0,
+    erase_chip: 0x40062c14,
+    sector: None,
+    stack_pointer: 0x3ffe0000,
+};
+
+/// The watchdogs that reset the chip while it is halted.
+const WATCHDOGS: [Watchdog; 3] = [
+    Watchdog::esp_timer_group(0x3ff5f000),
+    Watchdog::esp_timer_group(0x3ff60000),
+    Watchdog::esp_rtc(RTC_CNTL_BASE, 0x8c, 0xa0, 0xa4),
+];
+
 /// The debug sequence implementation for the ESP32.
 #[derive(Debug)]
 pub struct ESP32 {}
@@ -18,36 +49,106 @@ impl ESP32 {
     pub fn create(_chip: &Chip) -> Arc<dyn XtensaDebugSequence> {
         Arc::new(Self {})
     }
+
+    /// Stalls or releases the APP CPU through the RTC controller.
+    fn set_app_cpu_stall(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        stalled: bool,
+    ) -> Result<(), crate::Error> {
+        let stall = interface.read_word_32(RTC_CNTL_SW_CPU_STALL)?;
+        let options = interface.read_word_32(RTC_CNTL_OPTIONS0)?;
+
+        let (stall, options) = app_cpu_stall_values(stall, options, stalled);
+
+        interface.write_word_32(RTC_CNTL_SW_CPU_STALL, stall)?;
+        interface.write_word_32(RTC_CNTL_OPTIONS0, options)?;
+
+        Ok(())
+    }
+
+    /// Resets the system through the RTC controller, keeping the APP CPU stalled, and halts the
+    /// PRO CPU before it executes any code.
+    fn software_reset_and_halt(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        timeout: Duration,
+    ) -> Result<(), crate::Error> {
+        // The stall registers can only be accessed while the core is halted.
+        if !interface.is_halted()? {
+            interface.halt()?;
+            interface.wait_for_core_halted(timeout)?;
+        }
+
+        // Keep the APP CPU from running while the PRO CPU is being reset.
+        self.set_app_cpu_stall(interface, true)?;
+
+        interface.reset_system_and_halt(timeout, RTC_CNTL_OPTIONS0, OPTIONS0_SW_SYS_RST)?;
+
+        self.set_app_cpu_stall(interface, false)
+    }
 }
 
 impl XtensaDebugSequence for ESP32 {
     fn on_connect(&self, interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
-        tracing::info!("Disabling ESP32 watchdogs...");
-
-        // tg0 wdg
-        const TIMG0_BASE: u64 = 0x3ff5f000;
-        const TIMG0_WRITE_PROT: u64 = TIMG0_BASE | 0x64;
-        const TIMG0_WDTCONFIG0: u64 = TIMG0_BASE | 0x48;
-        interface.write_word_32(TIMG0_WRITE_PROT, 0x50D83AA1)?; // write protection off
-        interface.write_word_32(TIMG0_WDTCONFIG0, 0x0)?;
-        interface.write_word_32(TIMG0_WRITE_PROT, 0x0)?; // write protection on
-
-        // tg1 wdg
-        const TIMG1_BASE: u64 = 0x3ff60000;
-        const TIMG1_WRITE_PROT: u64 = TIMG1_BASE | 0x64;
-        const TIMG1_WDTCONFIG0: u64 = TIMG1_BASE | 0x48;
-        interface.write_word_32(TIMG1_WRITE_PROT, 0x50D83AA1)?; // write protection off
-        interface.write_word_32(TIMG1_WDTCONFIG0, 0x0)?;
-        interface.write_word_32(TIMG1_WRITE_PROT, 0x0)?; // write protection on
-
-        // rtc wdg
-        const RTC_CNTL_BASE: u64 = 0x3ff48000;
-        const RTC_WRITE_PROT: u64 = RTC_CNTL_BASE | 0xa4;
-        const RTC_WDTCONFIG0: u64 = RTC_CNTL_BASE | 0x8c;
-        interface.write_word_32(RTC_WRITE_PROT, 0x50D83AA1)?; // write protection off
-        interface.write_word_32(RTC_WDTCONFIG0, 0x0)?;
-        interface.write_word_32(RTC_WRITE_PROT, 0x0)?; // write protection on
+        interface.disable_watchdogs(self.watchdogs())
+    }
+
+    fn on_reset(&self, interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
+        // The reset re-enables the watchdogs, which would reset the chip while it is halted.
+        interface.disable_watchdogs(self.watchdogs())
+    }
+
+    fn reset_system_and_halt(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        timeout: Duration,
+    ) -> Result<(), crate::Error> {
+        if let Err(error) = self.software_reset_and_halt(interface, timeout) {
+            // The debug module can't be reached while the chip is in deep sleep, and only a
+            // power-on reset wakes the chip up.
+            tracing::warn!("System reset failed, trying a power-on reset: {}", error);
+            return self.power_on_reset_and_halt(interface, timeout);
+        }
 
         Ok(())
     }
+
+    fn flash_encryption_enabled(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+    ) -> Result<bool, crate::Error> {
+        let efuse = interface.read_word_32(EFUSE_BLK0_RDATA0)?;
+        let crypt_cnt = (efuse >> FLASH_CRYPT_CNT_SHIFT) & FLASH_CRYPT_CNT_MASK;
+
+        // Flash encryption is enabled while an odd number of bits is set.
+        Ok(crypt_cnt.count_ones() % 2 == 1)
+    }
+
+    fn rom_flash_routines(&self) -> Option<RomFlashRoutines> {
+        Some(ROM_FLASH_ROUTINES)
+    }
+
+    fn efuse_layout(&self) -> Option<&'static EfuseLayout> {
+        Some(&ESP32_EFUSES)
+    }
+
+    fn watchdogs(&self) -> &[Watchdog] {
+        &WATCHDOGS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_reset_keeps_the_app_cpu_stalled() {
+        let (_, options) = app_cpu_stall_values(0, 0, true);
+
+        // The reset bit is set on top of the current value of the options register.
+        assert_eq!(options | OPTIONS0_SW_SYS_RST, 0x8000_0002);
+        assert_eq!(RTC_CNTL_OPTIONS0, 0x3ff4_8000);
+        assert_eq!(RTC_CNTL_SW_CPU_STALL, 0x3ff4_80ac);
+    }
 }
@@ -1,14 +1,45 @@
 //! Sequence for the ESP32-S2.
 
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use probe_rs_target::Chip;
 
-use super::XtensaDebugSequence;
+use super::{RomFlashRoutines, Watchdog, XtensaDebugSequence};
 use crate::{
-    architecture::xtensa::communication_interface::XtensaCommunicationInterface, MemoryInterface,
+    architecture::xtensa::{
+        communication_interface::XtensaCommunicationInterface,
+        efuse::{EfuseLayout, ESP32S2_EFUSES},
+    },
+    MemoryInterface,
 };
 
+const RTC_CNTL_BASE: u64 = 0x3f408000;
+const RTC_CNTL_OPTIONS0: u64 = RTC_CNTL_BASE;
+
+const OPTIONS0_SW_SYS_RST: u32 = 1 << 31;
+
+/// EFUSE_RD_REPEAT_DATA1_REG, which holds SPI_BOOT_CRYPT_CNT.
+const EFUSE_RD_REPEAT_DATA1: u64 = 0x3f41a034;
+const SPI_BOOT_CRYPT_CNT_SHIFT: u32 = 18;
+const SPI_BOOT_CRYPT_CNT_MASK: u32 = 0x7;
+
+/// The flash routines of the ROM, from the linker script of the ROM in ESP-IDF.
+const ROM_FLASH_ROUTINES: RomFlashRoutines = RomFlashRoutines {
+    attach: 0x40017004,
+    unlock: 0x40016e88,
+    erase_chip: 0x40016d88,
+    sector: None,
+    stack_pointer: 0x3ffe0000,
+};
+
+/// The watchdogs that reset the chip while it is halted.
+const WATCHDOGS: [Watchdog; 4] = [
+    Watchdog::esp_timer_group(0x3f41f000),
+    Watchdog::esp_timer_group(0x3f420000),
+    Watchdog::esp_rtc(RTC_CNTL_BASE, 0x94, 0xa8, 0xac),
+    Watchdog::esp_super(RTC_CNTL_BASE | 0xb0, RTC_CNTL_BASE | 0xb4),
+];
+
 /// The debug sequence implementation for the ESP32-S2.
 #[derive(Debug)]
 pub struct ESP32S2 {}
@@ -22,32 +53,51 @@ impl ESP32S2 {
 
 impl XtensaDebugSequence for ESP32S2 {
     fn on_connect(&self, interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
-        tracing::info!("Disabling ESP32-S2 watchdogs...");
-
-        // tg0 wdg
-        const TIMG0_BASE: u64 = 0x3f41f000;
-        const TIMG0_WRITE_PROT: u64 = TIMG0_BASE | 0x64;
-        const TIMG0_WDTCONFIG0: u64 = TIMG0_BASE | 0x48;
-        interface.write_word_32(TIMG0_WRITE_PROT, 0x50D83AA1)?; // write protection off
-        interface.write_word_32(TIMG0_WDTCONFIG0, 0x0)?;
-        interface.write_word_32(TIMG0_WRITE_PROT, 0x0)?; // write protection on
-
-        // tg1 wdg
-        const TIMG1_BASE: u64 = 0x3f420000;
-        const TIMG1_WRITE_PROT: u64 = TIMG1_BASE | 0x64;
-        const TIMG1_WDTCONFIG0: u64 = TIMG1_BASE | 0x48;
-        interface.write_word_32(TIMG1_WRITE_PROT, 0x50D83AA1)?; // write protection off
-        interface.write_word_32(TIMG1_WDTCONFIG0, 0x0)?;
-        interface.write_word_32(TIMG1_WRITE_PROT, 0x0)?; // write protection on
-
-        // rtc wdg
-        const RTC_CNTL_BASE: u64 = 0x3f408000;
-        const RTC_WRITE_PROT: u64 = RTC_CNTL_BASE | 0xb0;
-        const RTC_WDTCONFIG0: u64 = RTC_CNTL_BASE | 0x94;
-        interface.write_word_32(RTC_WRITE_PROT, 0x50D83AA1)?; // write protection off
-        interface.write_word_32(RTC_WDTCONFIG0, 0x0)?;
-        interface.write_word_32(RTC_WRITE_PROT, 0x0)?; // write protection on
+        interface.disable_watchdogs(self.watchdogs())
+    }
+
+    fn on_reset(&self, interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
+        // The reset re-enables the watchdogs, which would reset the chip while it is halted.
+        interface.disable_watchdogs(self.watchdogs())
+    }
+
+    fn reset_system_and_halt(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        timeout: Duration,
+    ) -> Result<(), crate::Error> {
+        if let Err(error) =
+            interface.reset_system_and_halt(timeout, RTC_CNTL_OPTIONS0, OPTIONS0_SW_SYS_RST)
+        {
+            // The debug module can't be reached while the chip is in deep sleep, and only a
+            // power-on reset wakes the chip up.
+            tracing::warn!("System reset failed, trying a power-on reset: {}", error);
+            return self.power_on_reset_and_halt(interface, timeout);
+        }
 
         Ok(())
     }
+
+    fn flash_encryption_enabled(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+    ) -> Result<bool, crate::Error> {
+        let efuse = interface.read_word_32(EFUSE_RD_REPEAT_DATA1)?;
+        let crypt_cnt = (efuse >> SPI_BOOT_CRYPT_CNT_SHIFT) & SPI_BOOT_CRYPT_CNT_MASK;
+
+        // Flash encryption is enabled while an odd number of bits is set.
+        Ok(crypt_cnt.count_ones() % 2 == 1)
+    }
+
+    fn rom_flash_routines(&self) -> Option<RomFlashRoutines> {
+        Some(ROM_FLASH_ROUTINES)
+    }
+
+    fn efuse_layout(&self) -> Option<&'static EfuseLayout> {
+        Some(&ESP32S2_EFUSES)
+    }
+
+    fn watchdogs(&self) -> &[Watchdog] {
+        &WATCHDOGS
+    }
 }
@@ -1,14 +1,63 @@
 //! Sequence for the ESP32-S3.
 
-use std::sync::Arc;
+use std::{ops::Range, sync::Arc, time::Duration};
 
-use probe_rs_target::Chip;
+use probe_rs_target::{AddressTranslation, Chip};
 
-use super::XtensaDebugSequence;
+use super::{
+    app_cpu_stall_values, ExternalMemory, RomFlashRoutines, RomSectorRoutines, Watchdog,
+    XtensaDebugSequence,
+};
 use crate::{
-    architecture::xtensa::communication_interface::XtensaCommunicationInterface, MemoryInterface,
+    architecture::xtensa::{
+        communication_interface::XtensaCommunicationInterface,
+        efuse::{EfuseLayout, ESP32S3_EFUSES},
+    },
+    MemoryInterface,
 };
 
+const RTC_CNTL_BASE: u64 = 0x60008000;
+const RTC_CNTL_OPTIONS0: u64 = RTC_CNTL_BASE;
+const RTC_CNTL_SW_CPU_STALL: u64 = RTC_CNTL_BASE | 0xbc;
+
+const OPTIONS0_SW_SYS_RST: u32 = 1 << 31;
+
+/// EFUSE_RD_REPEAT_DATA1_REG, which holds SPI_BOOT_CRYPT_CNT.
+const EFUSE_RD_REPEAT_DATA1: u64 = 0x60007034;
+const SPI_BOOT_CRYPT_CNT_SHIFT: u32 = 18;
+const SPI_BOOT_CRYPT_CNT_MASK: u32 = 0x7;
+
+/// The flash routines of the ROM, from the linker script of the ROM in ESP-IDF.
+const ROM_FLASH_ROUTINES: RomFlashRoutines = RomFlashRoutines {
+    attach: 0x40000a3c,
+    unlock: 0x40000a20,
+    erase_chip: 0x40000870,
+    sector: Some(RomSectorRoutines {
+        erase_sector: 0x400008d0,
+        write: 0x400008e8,
+        invalidate_cache: 0x400016d4,
+    }),
+    stack_pointer: 0x3fcd0000,
+};
+
+/// The watchdogs that reset the chip while it is halted.
+const WATCHDOGS: [Watchdog; 4] = [
+    Watchdog::esp_timer_group(0x6001f000),
+    Watchdog::esp_timer_group(0x60020000),
+    Watchdog::esp_rtc(RTC_CNTL_BASE, 0x98, 0xac, 0xb0),
+    Watchdog::esp_super(RTC_CNTL_BASE | 0xb4, RTC_CNTL_BASE | 0xb8),
+];
+
+/// The instruction and data buses to external memory. Both are translated by the same MMU table.
+const EXTERNAL_BUSES: [Range<u64>; 2] = [0x42000000..0x44000000, 0x3C000000..0x3E000000];
+const EXTERNAL_BUS_SIZE: u64 = 0x2000000;
+
+const MMU_TABLE: u64 = 0x600C5000;
+const MMU_PAGE_SIZE: u64 = 0x10000;
+const MMU_ENTRY_PAGE_MASK: u32 = 0x3fff;
+const MMU_ENTRY_INVALID: u32 = 1 << 14;
+const MMU_ENTRY_SPIRAM: u32 = 1 << 15;
+
 /// The debug sequence implementation for the ESP32-S3.
 #[derive(Debug)]
 pub struct ESP32S3 {}
@@ -18,36 +67,214 @@ impl ESP32S3 {
     pub fn create(_chip: &Chip) -> Arc<dyn XtensaDebugSequence> {
         Arc::new(Self {})
     }
+
+    /// Stalls or releases the APP CPU through the RTC controller.
+    fn set_app_cpu_stall(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        stalled: bool,
+    ) -> Result<(), crate::Error> {
+        let stall = interface.read_word_32(RTC_CNTL_SW_CPU_STALL)?;
+        let options = interface.read_word_32(RTC_CNTL_OPTIONS0)?;
+
+        let (stall, options) = app_cpu_stall_values(stall, options, stalled);
+
+        interface.write_word_32(RTC_CNTL_SW_CPU_STALL, stall)?;
+        interface.write_word_32(RTC_CNTL_OPTIONS0, options)?;
+
+        Ok(())
+    }
+
+    /// Resets the system through the RTC controller, keeping the APP CPU stalled, and halts the
+    /// PRO CPU before it executes any code.
+    fn software_reset_and_halt(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        timeout: Duration,
+    ) -> Result<(), crate::Error> {
+        // The stall registers can only be accessed while the core is halted.
+        if !interface.is_halted()? {
+            interface.halt()?;
+            interface.wait_for_core_halted(timeout)?;
+        }
+
+        // Keep the APP CPU from running while the PRO CPU is being reset.
+        self.set_app_cpu_stall(interface, true)?;
+
+        interface.reset_system_and_halt(timeout, RTC_CNTL_OPTIONS0, OPTIONS0_SW_SYS_RST)?;
+
+        self.set_app_cpu_stall(interface, false)
+    }
 }
 
 impl XtensaDebugSequence for ESP32S3 {
     fn on_connect(&self, interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
-        tracing::info!("Disabling ESP32-S3 watchdogs...");
-
-        // tg0 wdg
-        const TIMG0_BASE: u64 = 0x6001f000;
-        const TIMG0_WRITE_PROT: u64 = TIMG0_BASE | 0x64;
-        const TIMG0_WDTCONFIG0: u64 = TIMG0_BASE | 0x48;
-        interface.write_word_32(TIMG0_WRITE_PROT, 0x50D83AA1)?; // write protection off
-        interface.write_word_32(TIMG0_WDTCONFIG0, 0x0)?;
-        interface.write_word_32(TIMG0_WRITE_PROT, 0x0)?; // write protection on
-
-        // tg1 wdg
-        const TIMG1_BASE: u64 = 0x60020000;
-        const TIMG1_WRITE_PROT: u64 = TIMG1_BASE | 0x64;
-        const TIMG1_WDTCONFIG0: u64 = TIMG1_BASE | 0x48;
-        interface.write_word_32(TIMG1_WRITE_PROT, 0x50D83AA1)?; // write protection off
-        interface.write_word_32(TIMG1_WDTCONFIG0, 0x0)?;
-        interface.write_word_32(TIMG1_WRITE_PROT, 0x0)?; // write protection on
-
-        // rtc wdg
-        const RTC_CNTL_BASE: u64 = 0x60008000;
-        const RTC_WRITE_PROT: u64 = RTC_CNTL_BASE | 0xa4;
-        const RTC_WDTCONFIG0: u64 = RTC_CNTL_BASE | 0x98;
-        interface.write_word_32(RTC_WRITE_PROT, 0x50D83AA1)?; // write protection off
-        interface.write_word_32(RTC_WDTCONFIG0, 0x0)?;
-        interface.write_word_32(RTC_WRITE_PROT, 0x0)?; // write protection on
+        interface.disable_watchdogs(self.watchdogs())
+    }
+
+    fn on_reset(&self, interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
+        // The reset re-enables the watchdogs, which would reset the chip while it is halted.
+        interface.disable_watchdogs(self.watchdogs())
+    }
+
+    fn reset_system_and_halt(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        timeout: Duration,
+    ) -> Result<(), crate::Error> {
+        if let Err(error) = self.software_reset_and_halt(interface, timeout) {
+            // The debug module can't be reached while the chip is in deep sleep, and only a
+            // power-on reset wakes the chip up.
+            tracing::warn!("System reset failed, trying a power-on reset: {}", error);
+            return self.power_on_reset_and_halt(interface, timeout);
+        }
 
         Ok(())
     }
+
+    fn translate_address(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        address: u64,
+    ) -> Result<Option<(ExternalMemory, u64)>, crate::Error> {
+        if !EXTERNAL_BUSES.iter().any(|bus| bus.contains(&address)) {
+            return Ok(None);
+        }
+
+        let offset = address % EXTERNAL_BUS_SIZE;
+        let entry = interface.read_word_32(MMU_TABLE + offset / MMU_PAGE_SIZE * 4)?;
+        if entry & MMU_ENTRY_INVALID != 0 {
+            return Ok(None);
+        }
+
+        let memory = if entry & MMU_ENTRY_SPIRAM != 0 {
+            ExternalMemory::Psram
+        } else {
+            ExternalMemory::Flash
+        };
+        let page = u64::from(entry & MMU_ENTRY_PAGE_MASK);
+        let memory_offset = page * MMU_PAGE_SIZE + offset % MMU_PAGE_SIZE;
+        Ok(Some((memory, memory_offset)))
+    }
+
+    fn flash_mappings(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+    ) -> Result<Vec<AddressTranslation>, crate::Error> {
+        let mut entries = vec![0; (EXTERNAL_BUS_SIZE / MMU_PAGE_SIZE) as usize];
+        interface.read_32(MMU_TABLE, &mut entries)?;
+
+        Ok(EXTERNAL_BUSES
+            .iter()
+            .flat_map(|bus| flash_mappings(bus.start, &entries))
+            .collect())
+    }
+
+    fn flash_encryption_enabled(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+    ) -> Result<bool, crate::Error> {
+        let efuse = interface.read_word_32(EFUSE_RD_REPEAT_DATA1)?;
+        let crypt_cnt = (efuse >> SPI_BOOT_CRYPT_CNT_SHIFT) & SPI_BOOT_CRYPT_CNT_MASK;
+
+        // Flash encryption is enabled while an odd number of bits is set.
+        Ok(crypt_cnt.count_ones() % 2 == 1)
+    }
+
+    fn rom_flash_routines(&self) -> Option<RomFlashRoutines> {
+        Some(ROM_FLASH_ROUTINES)
+    }
+
+    fn efuse_layout(&self) -> Option<&'static EfuseLayout> {
+        Some(&ESP32S3_EFUSES)
+    }
+
+    fn watchdogs(&self) -> &[Watchdog] {
+        &WATCHDOGS
+    }
+}
+
+/// Decodes the MMU `entries` of the external bus starting at `bus_start` into the ranges of the
+/// bus which map contiguous pages of the flash.
+fn flash_mappings(bus_start: u64, entries: &[u32]) -> Vec<AddressTranslation> {
+    let mut mappings: Vec<AddressTranslation> = Vec::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        if entry & (MMU_ENTRY_INVALID | MMU_ENTRY_SPIRAM) != 0 {
+            continue;
+        }
+
+        let address = bus_start + index as u64 * MMU_PAGE_SIZE;
+        let flash_offset = u64::from(entry & MMU_ENTRY_PAGE_MASK) * MMU_PAGE_SIZE;
+
+        match mappings.last_mut() {
+            Some(mapping)
+                if mapping.range.end == address
+                    && mapping.target + (address - mapping.range.start) == flash_offset =>
+            {
+                mapping.range.end += MMU_PAGE_SIZE;
+            }
+            _ => mappings.push(AddressTranslation {
+                range: address..address + MMU_PAGE_SIZE,
+                target: flash_offset,
+            }),
+        }
+    }
+
+    mappings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn system_reset_keeps_the_app_cpu_stalled() {
+        let (_, options) = app_cpu_stall_values(0, 0, true);
+
+        // The reset bit is set on top of the current value of the options register.
+        assert_eq!(options | OPTIONS0_SW_SYS_RST, 0x8000_0002);
+        assert_eq!(RTC_CNTL_OPTIONS0, 0x6000_8000);
+        assert_eq!(RTC_CNTL_SW_CPU_STALL, 0x6000_80bc);
+    }
+
+    #[test]
+    fn flash_mappings_are_decoded_from_the_mmu_table() {
+        // The bootloader maps the data of the application, and then its code from further into
+        // the flash. Unused entries are invalid, and PSRAM isn't flash.
+        let mut entries = vec![MMU_ENTRY_INVALID; 512];
+        entries[0..2].copy_from_slice(&[0x1, 0x2]);
+        entries[2] = MMU_ENTRY_SPIRAM;
+        entries[3..6].copy_from_slice(&[0x3, 0x4, 0x5]);
+        entries[6] = 0x7;
+
+        let mappings = flash_mappings(0x4200_0000, &entries);
+        assert_eq!(
+            mappings,
+            [
+                AddressTranslation {
+                    range: 0x4200_0000..0x4202_0000,
+                    target: 0x1_0000,
+                },
+                AddressTranslation {
+                    range: 0x4203_0000..0x4206_0000,
+                    target: 0x3_0000,
+                },
+                AddressTranslation {
+                    range: 0x4206_0000..0x4207_0000,
+                    target: 0x7_0000,
+                },
+            ]
+        );
+
+        let flash_address = |address| {
+            mappings
+                .iter()
+                .find_map(|mapping| mapping.translate(address))
+        };
+        assert_eq!(flash_address(0x4201_0020), Some(0x2_0020));
+        assert_eq!(flash_address(0x4202_0000), None);
+        assert_eq!(mappings[1].untranslate(0x5_0020), Some(0x4205_0020));
+        assert_eq!(mappings[1].untranslate(0x6_0000), None);
+    }
 }
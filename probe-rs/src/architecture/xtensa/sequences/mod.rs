@@ -1,11 +1,244 @@
-use std::{fmt::Debug, sync::Arc};
+use std::{fmt::Debug, sync::Arc, time::Duration};
 
-use crate::architecture::xtensa::communication_interface::XtensaCommunicationInterface;
+use probe_rs_target::{AddressTranslation, Chip};
+
+use crate::{
+    architecture::xtensa::{
+        communication_interface::{XtensaCommunicationInterface, XtensaError},
+        efuse::EfuseLayout,
+    },
+    Core, Probe,
+};
+
+use self::{esp32::ESP32, esp32s2::ESP32S2, esp32s3::ESP32S3};
 
 pub mod esp32;
 pub mod esp32s2;
 pub mod esp32s3;
 
+/// Creates the debug sequence of a chip.
+pub type XtensaSequenceFactory = fn(&Chip) -> Arc<dyn XtensaDebugSequence>;
+
+/// The chips with their own debug sequence, by their name in the target description.
+///
+/// New chips are supported by implementing [`XtensaDebugSequence`] for them, and adding them
+/// here.
+const CHIP_SEQUENCES: &[(&str, XtensaSequenceFactory)] = &[
+    ("esp32", ESP32::create),
+    ("esp32s2", ESP32S2::create),
+    ("esp32s3", ESP32S3::create),
+];
+
+/// Returns the debug sequence registered for `chip`, or `None` if the chip uses the
+/// [`DefaultXtensaSequence`].
+pub fn sequence_for_chip(chip: &Chip) -> Option<Arc<dyn XtensaDebugSequence>> {
+    CHIP_SEQUENCES
+        .iter()
+        .find(|(name, _)| chip.name.eq_ignore_ascii_case(name))
+        .map(|(_, create)| create(chip))
+}
+
+const OPTIONS0_SW_STALL_APPCPU_C0_MASK: u32 = 0x3;
+const SW_CPU_STALL_APPCPU_C1_MASK: u32 = 0x3f << 20;
+
+/// Returns the values of the RTC_CNTL_SW_CPU_STALL and RTC_CNTL_OPTIONS0 registers that stall or
+/// release the APP CPU of the ESP32 and ESP32-S3, from their current values.
+fn app_cpu_stall_values(stall: u32, options: u32, stalled: bool) -> (u32, u32) {
+    let mut stall = stall & !SW_CPU_STALL_APPCPU_C1_MASK;
+    let mut options = options & !OPTIONS0_SW_STALL_APPCPU_C0_MASK;
+    if stalled {
+        // The CPU is stalled when C1 is 0x21 and C0 is 0x2.
+        stall |= 0x21 << 20;
+        options |= 0x2;
+    }
+
+    (stall, options)
+}
+
+/// A watchdog timer, which resets the chip unless it is fed regularly.
+///
+/// Watchdogs keep counting while the cores are halted, so they are stopped by the debugger.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Watchdog {
+    /// The write protection register of the watchdog.
+    pub write_protect: u64,
+    /// The value that unlocks the write protection.
+    pub unlock_key: u32,
+    /// The configuration register of the watchdog.
+    pub config: u64,
+    /// The bits of the configuration register that are cleared to stop the watchdog.
+    pub stop_clear: u32,
+    /// The bits of the configuration register that are set to stop the watchdog.
+    pub stop_set: u32,
+    /// The register that feeds the watchdog, and the value to write to it, if it can be fed.
+    pub feed: Option<(u64, u32)>,
+}
+
+impl Watchdog {
+    /// The key that unlocks the watchdog registers of Espressif timer groups and RTC controllers.
+    const ESP_UNLOCK_KEY: u32 = 0x50D83AA1;
+
+    /// The main system watchdog of the Espressif timer group at `base`.
+    pub const fn esp_timer_group(base: u64) -> Self {
+        Self {
+            write_protect: base | 0x64,
+            unlock_key: Self::ESP_UNLOCK_KEY,
+            config: base | 0x48,
+            stop_clear: u32::MAX,
+            stop_set: 0,
+            feed: Some((base | 0x60, 1)),
+        }
+    }
+
+    /// The RTC watchdog of an Espressif chip, given the offsets of its registers from the RTC
+    /// controller at `base`.
+    pub const fn esp_rtc(base: u64, config: u64, feed: u64, write_protect: u64) -> Self {
+        Self {
+            write_protect: base | write_protect,
+            unlock_key: Self::ESP_UNLOCK_KEY,
+            config: base | config,
+            stop_clear: u32::MAX,
+            stop_set: 0,
+            feed: Some((base | feed, 1 << 31)),
+        }
+    }
+
+    /// The super watchdog of an Espressif chip, which is stopped by letting the hardware feed it.
+    pub const fn esp_super(config: u64, write_protect: u64) -> Self {
+        Self {
+            write_protect,
+            unlock_key: 0x8F1D312A,
+            config,
+            stop_clear: 0,
+            // SWD_AUTO_FEED_EN
+            stop_set: 1 << 31,
+            feed: None,
+        }
+    }
+
+    /// Returns the configuration that stops the watchdog, based on its current `config`.
+    pub fn stopped(&self, config: u32) -> u32 {
+        (config & !self.stop_clear) | self.stop_set
+    }
+}
+
+/// External memory that is mapped into the address space through the cache MMU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExternalMemory {
+    /// The SPI flash the firmware is executed from.
+    Flash,
+    /// External PSRAM.
+    Psram,
+}
+
+/// The ROM routines of a chip which rewrite single sectors of the SPI flash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RomSectorRoutines {
+    /// `esp_rom_spiflash_erase_sector(sector)`.
+    pub erase_sector: u64,
+    /// `esp_rom_spiflash_write(offset, data, length)`.
+    pub write: u64,
+    /// `Cache_Invalidate_Addr(address, length)`, which drops the rewritten flash from the cache.
+    pub invalidate_cache: u64,
+}
+
+/// The ROM routines of a chip which access the SPI flash, used to erase the flash without a
+/// flash algorithm, and to insert software breakpoints into code executed from flash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RomFlashRoutines {
+    /// `esp_rom_spiflash_attach(ishspi, legacy)`, which configures the flash pins.
+    pub attach: u64,
+    /// `esp_rom_spiflash_unlock()`, which clears the write protection of the flash.
+    pub unlock: u64,
+    /// `esp_rom_spiflash_erase_chip()`.
+    pub erase_chip: u64,
+    /// The routines which rewrite single sectors, if they are known.
+    pub sector: Option<RomSectorRoutines>,
+    /// The stack pointer the routines are called with, in RAM that the ROM doesn't use.
+    pub stack_pointer: u64,
+}
+
+impl RomFlashRoutines {
+    /// The size of the sectors of the SPI flash, the smallest unit which can be erased.
+    pub const SECTOR_SIZE: u64 = 0x1000;
+
+    /// The size of the pages of the SPI flash, the largest unit which can be written at once.
+    pub const PAGE_SIZE: u32 = 0x100;
+
+    /// The RAM below the stack pointer which holds the stack of the routines, and the sector
+    /// passed to the write routine at its start.
+    ///
+    /// While a program is being debugged, this RAM is saved before the routines are called and
+    /// restored afterwards.
+    pub const SCRATCH_SIZE: u64 = 2 * Self::SECTOR_SIZE;
+
+    /// The time to wait for the routines other than the chip erase.
+    pub(crate) const TIMEOUT: Duration = Duration::from_secs(1);
+
+    /// The time to wait for the chip erase, which takes several minutes on large flash chips.
+    const ERASE_TIMEOUT: Duration = Duration::from_secs(300);
+
+    /// Erases the whole flash by calling the routines on `core`, which must be halted right
+    /// after a reset.
+    pub(crate) fn erase_all(&self, core: &mut Core<'_>) -> Result<(), crate::Error> {
+        core.write_core_reg(core.stack_pointer().id(), self.stack_pointer)?;
+
+        // The attach routine returns nothing, so a2 holds no status afterwards.
+        core.call_function(self.attach, &[0, 0], Self::TIMEOUT)?;
+
+        // The unlock fails if the status register of the flash is locked, e.g. by its SRP bits.
+        let status = core.call_function(self.unlock, &[], Self::TIMEOUT)?;
+        if status != 0 {
+            return Err(XtensaError::FlashLocked(status as u32).into());
+        }
+
+        tracing::info!("Erasing the flash with the ROM routines, this may take a while...");
+        self.call(
+            core,
+            "esp_rom_spiflash_erase_chip",
+            self.erase_chip,
+            &[],
+            Self::ERASE_TIMEOUT,
+        )
+    }
+
+    /// Returns the offset of `flash_offset` in its sector, or `None` if the `len` bytes at
+    /// `flash_offset` don't fit into that sector.
+    pub(crate) fn offset_in_sector(flash_offset: u64, len: usize) -> Option<u64> {
+        let offset_in_sector = flash_offset % Self::SECTOR_SIZE;
+        (offset_in_sector + len as u64 <= Self::SECTOR_SIZE).then_some(offset_in_sector)
+    }
+
+    /// The address of the sector buffer in the [scratch RAM](Self::SCRATCH_SIZE).
+    pub(crate) fn sector_buffer(&self) -> u64 {
+        self.stack_pointer - Self::SCRATCH_SIZE
+    }
+
+    fn call(
+        &self,
+        core: &mut Core<'_>,
+        routine: &'static str,
+        address: u64,
+        arguments: &[u64],
+        timeout: Duration,
+    ) -> Result<(), crate::Error> {
+        let status = core.call_function(address, arguments, timeout)?;
+        Self::check_status(routine, status)
+    }
+
+    /// Turns the `status` returned by `routine` into an error, unless it reports success.
+    pub(crate) fn check_status(routine: &'static str, status: u64) -> Result<(), crate::Error> {
+        match status {
+            0 => Ok(()),
+            status => Err(XtensaError::RomRoutineFailed {
+                routine,
+                status: status as u32,
+            }
+            .into()),
+        }
+    }
+}
+
 /// A interface to operate debug sequences for Xtensa targets.
 ///
 /// Should be implemented on a custom handle for chips that require special sequence code.
@@ -17,6 +250,168 @@ pub trait XtensaDebugSequence: Send + Sync + Debug {
     ) -> Result<(), crate::Error> {
         Ok(())
     }
+
+    /// Executed when a core halted, either on request or on its own.
+    ///
+    /// The default implementation suspends the [`watchdogs`](Self::watchdogs), so that they
+    /// don't reset the chip while it is halted.
+    fn on_halt(&self, interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
+        interface.suspend_watchdogs(self.watchdogs())
+    }
+
+    /// Executed after [`reset_system_and_halt`](Self::reset_system_and_halt) reset the chip.
+    fn on_reset(&self, _interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    /// Executed when the debugger detaches from the chip, before the cores leave debug mode.
+    ///
+    /// The default implementation restores the watchdogs suspended by [`on_halt`](Self::on_halt).
+    fn on_detach(&self, interface: &mut XtensaCommunicationInterface) -> Result<(), crate::Error> {
+        interface.resume_watchdogs()
+    }
+
+    /// Asserts the reset of the chip before the probe attaches, when connecting under reset.
+    ///
+    /// The default implementation uses the probe's reset line.
+    fn reset_hardware_assert(&self, probe: &mut Probe) -> Result<(), crate::Error> {
+        probe.target_reset_assert()?;
+
+        Ok(())
+    }
+
+    /// Releases the reset asserted by [`reset_hardware_assert`](Self::reset_hardware_assert),
+    /// and halts the core before it executes any code.
+    ///
+    /// The default implementation uses the probe's reset line.
+    fn reset_hardware_deassert(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        timeout: Duration,
+    ) -> Result<(), crate::Error> {
+        interface.reset_deassert_and_halt(timeout)?;
+
+        Ok(())
+    }
+
+    /// Resets the system and halts the core before it executes any code.
+    ///
+    /// The default implementation uses the probe's reset line.
+    fn reset_system_and_halt(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        timeout: Duration,
+    ) -> Result<(), crate::Error> {
+        interface.reset_and_halt(timeout)?;
+
+        Ok(())
+    }
+
+    /// Power-cycles the chip, or pulses its reset line if the probe can't switch the chip's power,
+    /// and halts the core once the debug module is reachable again.
+    ///
+    /// This is the only reset that recovers the chip from sleep states that power down the debug
+    /// module. The default implementation runs [`on_connect`](Self::on_connect) again afterwards,
+    /// since the chip lost its configuration.
+    fn power_on_reset_and_halt(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        timeout: Duration,
+    ) -> Result<(), crate::Error> {
+        interface.power_on_reset_and_halt(timeout)?;
+
+        self.on_connect(interface)
+    }
+
+    /// Translates `address` to the external memory it is mapped to by the cache MMU, and the
+    /// offset in that memory, by consulting the MMU table of the chip.
+    ///
+    /// The default implementation assumes that the chip has no MMU.
+    fn translate_address(
+        &self,
+        _interface: &mut XtensaCommunicationInterface,
+        _address: u64,
+    ) -> Result<Option<(ExternalMemory, u64)>, crate::Error> {
+        Ok(None)
+    }
+
+    /// Translates `address` to an offset into the external flash, if the address is mapped to
+    /// the flash by the cache MMU.
+    ///
+    /// Memory mapped from flash can't be written through the cache, so breakpoint instructions
+    /// can't be inserted there. The default implementation uses
+    /// [`translate_address`](Self::translate_address).
+    fn xip_flash_address(
+        &self,
+        interface: &mut XtensaCommunicationInterface,
+        address: u64,
+    ) -> Result<Option<u64>, crate::Error> {
+        match self.translate_address(interface, address)? {
+            Some((ExternalMemory::Flash, offset)) => Ok(Some(offset)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the address ranges which the cache MMU currently maps to the external flash, and
+    /// the flash offsets they are mapped to, by consulting the MMU table of the chip.
+    ///
+    /// ELF segments linked to these ranges are written to the flash they are mapped to. The
+    /// default implementation assumes that the chip has no MMU.
+    fn flash_mappings(
+        &self,
+        _interface: &mut XtensaCommunicationInterface,
+    ) -> Result<Vec<AddressTranslation>, crate::Error> {
+        Ok(Vec::new())
+    }
+
+    /// Returns whether the contents of external flash are encrypted.
+    ///
+    /// The default implementation assumes that flash is not encrypted.
+    fn flash_encryption_enabled(
+        &self,
+        _interface: &mut XtensaCommunicationInterface,
+    ) -> Result<bool, crate::Error> {
+        Ok(false)
+    }
+
+    /// Selects whether encrypted flash read through the cache is decrypted.
+    ///
+    /// The default implementation can't disable decryption.
+    fn set_flash_decryption(
+        &self,
+        _interface: &mut XtensaCommunicationInterface,
+        enabled: bool,
+    ) -> Result<(), crate::Error> {
+        if enabled {
+            Ok(())
+        } else {
+            Err(crate::Error::NotImplemented(
+                "reading encrypted flash contents",
+            ))
+        }
+    }
+
+    /// Returns the ROM routines which erase the flash, if the chip has them.
+    ///
+    /// The default implementation assumes that the flash can't be erased without a flash
+    /// algorithm.
+    fn rom_flash_routines(&self) -> Option<RomFlashRoutines> {
+        None
+    }
+
+    /// Returns where the eFuses of the chip are read, and how they are decoded.
+    ///
+    /// The default implementation assumes that the chip has no eFuses.
+    fn efuse_layout(&self) -> Option<&'static EfuseLayout> {
+        None
+    }
+
+    /// Returns the watchdogs of the chip, which are stopped while the core is halted.
+    ///
+    /// The default implementation assumes that the chip has no watchdogs.
+    fn watchdogs(&self) -> &[Watchdog] {
+        &[]
+    }
 }
 
 /// The default sequences that is used for Xtensa chips that do not specify a specific sequence.
@@ -31,3 +426,51 @@ impl DefaultXtensaSequence {
 }
 
 impl XtensaDebugSequence for DefaultXtensaSequence {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_in_sector() {
+        assert_eq!(RomFlashRoutines::offset_in_sector(0x1_0000, 3), Some(0));
+        assert_eq!(RomFlashRoutines::offset_in_sector(0x1_0123, 3), Some(0x123));
+        // The last bytes of a sector.
+        assert_eq!(RomFlashRoutines::offset_in_sector(0x1_0FFD, 3), Some(0xFFD));
+        // A patch that crosses into the next sector can't be written with a single sector.
+        assert_eq!(RomFlashRoutines::offset_in_sector(0x1_0FFE, 3), None);
+        assert_eq!(RomFlashRoutines::offset_in_sector(0x1_0FFF, 2), None);
+    }
+
+    #[test]
+    fn app_cpu_stall() {
+        assert_eq!(app_cpu_stall_values(0, 0, true), (0x0210_0000, 0x2));
+        // The other fields of the registers are kept.
+        assert_eq!(
+            app_cpu_stall_values(0xFFFF_FFFF, 0x8000_0001, true),
+            (0xFE1F_FFFF, 0x8000_0002)
+        );
+        assert_eq!(
+            app_cpu_stall_values(0xFE1F_FFFF, 0x8000_0002, false),
+            (0xFC0F_FFFF, 0x8000_0000)
+        );
+    }
+
+    #[test]
+    fn sector_buffer_is_at_the_start_of_the_scratch_ram() {
+        let routines = RomFlashRoutines {
+            attach: 0,
+            unlock: 0,
+            erase_chip: 0,
+            sector: None,
+            stack_pointer: 0x3FCE_0000,
+        };
+
+        assert_eq!(routines.sector_buffer(), 0x3FCE_0000 - 0x2000);
+        // The stack of the routines grows down towards the buffer, and gets the other sector.
+        assert!(
+            routines.sector_buffer() + RomFlashRoutines::SECTOR_SIZE
+                <= routines.stack_pointer - RomFlashRoutines::SECTOR_SIZE
+        );
+    }
+}
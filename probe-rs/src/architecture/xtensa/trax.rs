@@ -0,0 +1,401 @@
+//! Support for the TRAX trace unit of Xtensa cores.
+//!
+//! TRAX records the program flow of a core into a circular trace memory, using compressed
+//! Nexus-style messages. Tracing stops after a configurable trigger, e.g. a PC match or the core
+//! halting, so that the instruction history leading up to the trigger can be read out.
+
+use std::time::{Duration, Instant};
+
+use super::{communication_interface::XtensaError, xdm::Xdm};
+
+// TRAX registers, accessed through NAR/NDR.
+const TRAXREG_ID: u8 = 0x00;
+const TRAXREG_CTRL: u8 = 0x01;
+const TRAXREG_STAT: u8 = 0x02;
+const TRAXREG_DATA: u8 = 0x03;
+const TRAXREG_ADDR: u8 = 0x04;
+const TRAXREG_TRIGGERPC: u8 = 0x05;
+const TRAXREG_PCMATCHCTRL: u8 = 0x06;
+const TRAXREG_DELAYCNT: u8 = 0x07;
+const TRAXREG_MEMADDRSTART: u8 = 0x08;
+const TRAXREG_MEMADDREND: u8 = 0x09;
+
+/// The number of trace memory words read in a single JTAG batch.
+const READ_BATCH_WORDS: usize = 256;
+
+bitfield::bitfield! {
+    #[derive(Copy, Clone)]
+    struct TraxControl(u32);
+    impl Debug;
+
+    /// Trace enable. Tracing starts on a 0 -> 1 transition.
+    tren,  set_tren:  0;
+    /// Trace stop.
+    trstp, set_trstp: 1;
+    /// Stop on a PC match.
+    pcmen, set_pcmen: 2;
+    /// Stop when the processor trigger fires, i.e. the core enters debug mode.
+    ptien, set_ptien: 4;
+    /// Trace memory enable.
+    tmen,  set_tmen:  7;
+    /// Unit of the post-stop-trigger delay. 0: trace memory words, 1: instructions.
+    cntu,  set_cntu:  9;
+    /// Sends a synchronization message every 2^(9 - SMPER) messages. 0 disables them.
+    u8, smper, set_smper: 14, 12;
+    /// Halt the core when the trace stop completes.
+    ptows, set_ptows: 17;
+}
+
+bitfield::bitfield! {
+    #[derive(Copy, Clone)]
+    struct TraxStatus(u32);
+    impl Debug;
+
+    /// Trace active.
+    tract, _: 0;
+    /// The stop trigger fired.
+    trig,  _: 1;
+    /// The stop was triggered by a PC match.
+    pcmtg, _: 2;
+    /// The stop was triggered by the processor trigger.
+    ptitg, _: 4;
+    /// The trace memory is 2^MEMSZ bytes large.
+    u8, memsz, _: 12, 8;
+}
+
+bitfield::bitfield! {
+    #[derive(Copy, Clone)]
+    struct TraxAddress(u32);
+    impl Debug;
+
+    /// The next trace memory word to be written.
+    taddr, _: 20, 0;
+    /// The number of times the trace memory wrapped around.
+    twrap, _: 30, 21;
+    /// `twrap` overflowed.
+    twsat, _: 31;
+}
+
+/// Stops the trace when the program counter matches an address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcMatch {
+    /// The address to match.
+    pub address: u32,
+    /// The number of low address bits to ignore, to match a range of addresses.
+    pub ignored_bits: u8,
+}
+
+/// How long to keep tracing after the stop trigger fired.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceDelay {
+    /// Stop after this many words have been written to the trace memory.
+    Words(u32),
+    /// Stop after this many instructions have been executed.
+    Instructions(u32),
+}
+
+impl Default for TraceDelay {
+    fn default() -> Self {
+        Self::Words(0)
+    }
+}
+
+/// Configuration of a trace capture.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TraceConfig {
+    /// Stop the trace when the program counter matches.
+    pub stop_pc: Option<PcMatch>,
+    /// Stop the trace when the core halts.
+    pub stop_on_halt: bool,
+    /// How long to keep tracing after the stop trigger fired.
+    pub delay: TraceDelay,
+    /// Halt the core once the trace has stopped.
+    pub halt_on_stop: bool,
+}
+
+/// The state of the trace unit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceStatus {
+    /// The trace is still being captured.
+    pub active: bool,
+    /// The stop trigger fired.
+    pub triggered: bool,
+    /// The trace was stopped by a PC match.
+    pub stopped_by_pc_match: bool,
+    /// The trace was stopped by the core halting.
+    pub stopped_by_halt: bool,
+    /// The size of the trace memory, in bytes.
+    pub memory_size: usize,
+}
+
+/// A TRAX trace unit.
+///
+/// Obtained through [`XtensaCommunicationInterface::trace`](super::communication_interface::XtensaCommunicationInterface::trace).
+pub struct XtensaTrace<'a> {
+    xdm: &'a mut Xdm,
+}
+
+impl<'a> XtensaTrace<'a> {
+    pub(super) fn new(xdm: &'a mut Xdm) -> Self {
+        Self { xdm }
+    }
+
+    /// Reads the TRAXID register, which identifies the version and configuration of the trace unit.
+    pub fn id(&mut self) -> Result<u32, XtensaError> {
+        self.xdm.read_trax_register(TRAXREG_ID)
+    }
+
+    /// Returns the state of the trace unit.
+    pub fn status(&mut self) -> Result<TraceStatus, XtensaError> {
+        let status = TraxStatus(self.xdm.read_trax_register(TRAXREG_STAT)?);
+
+        Ok(TraceStatus {
+            active: status.tract(),
+            triggered: status.trig(),
+            stopped_by_pc_match: status.pcmtg(),
+            stopped_by_halt: status.ptitg(),
+            memory_size: 1 << status.memsz(),
+        })
+    }
+
+    /// Starts capturing a new trace, discarding the previous one.
+    pub fn start(&mut self, config: &TraceConfig) -> Result<(), XtensaError> {
+        // Turn off the trace unit, so that a new trace can be started.
+        self.xdm.write_trax_register(TRAXREG_CTRL, 0)?;
+
+        let memory_words = self.status()?.memory_size as u32 / 4;
+        let last_word = memory_words
+            .checked_sub(1)
+            .ok_or(XtensaError::NoTraceMemory)?;
+        self.xdm.write_trax_register(TRAXREG_MEMADDRSTART, 0)?;
+        self.xdm
+            .write_trax_register(TRAXREG_MEMADDREND, last_word)?;
+        self.xdm.write_trax_register(TRAXREG_ADDR, 0)?;
+
+        let mut control = TraxControl(0);
+        control.set_tren(true);
+        control.set_tmen(true);
+        // Synchronize every 256 messages, so that a wrapped trace can be decoded.
+        control.set_smper(1);
+        control.set_ptien(config.stop_on_halt);
+        control.set_ptows(config.halt_on_stop);
+
+        if let Some(stop_pc) = config.stop_pc {
+            self.xdm
+                .write_trax_register(TRAXREG_PCMATCHCTRL, stop_pc.ignored_bits as u32 & 0x1F)?;
+            self.xdm
+                .write_trax_register(TRAXREG_TRIGGERPC, stop_pc.address)?;
+            control.set_pcmen(true);
+        }
+
+        let delay = match config.delay {
+            TraceDelay::Words(words) => words,
+            TraceDelay::Instructions(instructions) => {
+                control.set_cntu(true);
+                instructions
+            }
+        };
+        self.xdm.write_trax_register(TRAXREG_DELAYCNT, delay)?;
+
+        self.xdm.write_trax_register(TRAXREG_CTRL, control.0)
+    }
+
+    /// Stops the trace.
+    pub fn stop(&mut self) -> Result<(), XtensaError> {
+        let mut control = TraxControl(self.xdm.read_trax_register(TRAXREG_CTRL)?);
+        if !control.tren() {
+            return Ok(());
+        }
+
+        control.set_trstp(true);
+        self.xdm.write_trax_register(TRAXREG_CTRL, control.0)
+    }
+
+    /// Reads the captured trace data, oldest first. A running trace is stopped first.
+    ///
+    /// The data can be decoded with [`decode_messages`].
+    pub fn read(&mut self) -> Result<Vec<u8>, XtensaError> {
+        let mut status = self.status()?;
+        if status.active {
+            self.stop()?;
+
+            let start = Instant::now();
+            while status.active {
+                if start.elapsed() > Duration::from_millis(100) {
+                    return Err(XtensaError::Timeout);
+                }
+                status = self.status()?;
+            }
+        }
+
+        let address = TraxAddress(self.xdm.read_trax_register(TRAXREG_ADDR)?);
+
+        // Read the whole trace memory from the start.
+        self.xdm.write_trax_register(TRAXREG_ADDR, 0)?;
+
+        let memory_words = status.memory_size / 4;
+        if memory_words == 0 {
+            return Err(XtensaError::NoTraceMemory);
+        }
+        let mut data = Vec::with_capacity(status.memory_size);
+        while data.len() < status.memory_size {
+            let words = (memory_words - data.len() / 4).min(READ_BATCH_WORDS);
+            for word in self.xdm.read_trax_register_repeated(TRAXREG_DATA, words)? {
+                data.extend_from_slice(&word.to_le_bytes());
+            }
+        }
+
+        // The trace memory is a ring buffer, the oldest data follows the write address once it
+        // wrapped around.
+        let write_offset = (address.taddr() as usize * 4).min(data.len());
+        if address.twrap() > 0 || address.twsat() {
+            data.rotate_left(write_offset);
+        } else {
+            data.truncate(write_offset);
+        }
+
+        Ok(data)
+    }
+}
+
+/// A message decoded from the trace data.
+///
+/// Messages consist of a 6-bit TCODE, which identifies the type of the message, followed by
+/// packet fields. Only the end of variable-length fields is marked in the data, so fixed-length
+/// fields are merged into the following variable-length field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceMessage {
+    /// The type of the message.
+    pub tcode: u8,
+    /// The fields of the message, following the TCODE.
+    pub fields: Vec<u64>,
+}
+
+/// Splits trace data into messages.
+///
+/// Each byte carries 6 bits of message data and 2 bits which mark the end of a field or message.
+/// Data before the first end of a message is skipped, as it may be the tail of a message that
+/// was partially overwritten.
+pub fn decode_messages(data: &[u8]) -> Vec<TraceMessage> {
+    const MSEO_NORMAL: u8 = 0b00;
+    const MSEO_END_OF_FIELD: u8 = 0b01;
+    const MSEO_END_OF_MESSAGE: u8 = 0b11;
+
+    let mut messages = vec![];
+
+    let mut synchronized = false;
+    let mut fields = vec![];
+    let mut field = 0u64;
+    let mut field_bits = 0;
+
+    for &byte in data {
+        let mseo = byte & 0b11;
+        let mdo = (byte >> 2) as u64;
+
+        if !synchronized {
+            synchronized = mseo == MSEO_END_OF_MESSAGE;
+            continue;
+        }
+
+        if field_bits < 64 {
+            field |= mdo << field_bits;
+        }
+        field_bits += 6;
+
+        match mseo {
+            MSEO_NORMAL => {}
+            MSEO_END_OF_FIELD => {
+                fields.push(field);
+                field = 0;
+                field_bits = 0;
+            }
+            MSEO_END_OF_MESSAGE => {
+                // A single all-ones byte is an idle marker.
+                let is_idle = fields.is_empty() && field_bits == 6 && mdo == 0x3F;
+                if !is_idle {
+                    fields.push(field);
+
+                    let tcode = (fields[0] & 0x3F) as u8;
+                    fields[0] >>= 6;
+                    messages.push(TraceMessage {
+                        tcode,
+                        fields: std::mem::take(&mut fields),
+                    });
+                }
+                field = 0;
+                field_bits = 0;
+            }
+            _ => {
+                // Reserved, the data is corrupted. Wait for the next message.
+                synchronized = false;
+                fields.clear();
+                field = 0;
+                field_bits = 0;
+            }
+        }
+    }
+
+    messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn byte(mdo: u8, mseo: u8) -> u8 {
+        (mdo << 2) | mseo
+    }
+
+    #[test]
+    fn decode_skips_partial_message_and_idle() {
+        let data = [
+            // Tail of a partially overwritten message
+            byte(0x12, 0b00),
+            byte(0x01, 0b11),
+            // Idle
+            0xFF,
+            // TCODE 3, I-CNT 5
+            byte(3, 0b00),
+            byte(5, 0b11),
+            // TCODE 4, B-TYPE 1 and I-CNT 0x41, U-ADDR 0x7
+            byte(4, 0b00),
+            byte(0x05, 0b00),
+            byte(0x01, 0b01),
+            byte(0x07, 0b11),
+        ];
+
+        assert_eq!(
+            decode_messages(&data),
+            vec![
+                TraceMessage {
+                    tcode: 3,
+                    fields: vec![5],
+                },
+                TraceMessage {
+                    tcode: 4,
+                    fields: vec![0x05 | (0x01 << 6), 0x07],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn decode_resynchronizes_after_reserved_marker() {
+        let data = [
+            0xFF,
+            byte(3, 0b00),
+            byte(1, 0b10),
+            byte(9, 0b11),
+            byte(3, 0b00),
+            byte(2, 0b11),
+        ];
+
+        assert_eq!(
+            decode_messages(&data),
+            vec![TraceMessage {
+                tcode: 3,
+                fields: vec![2],
+            }]
+        );
+    }
+}
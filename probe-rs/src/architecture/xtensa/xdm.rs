@@ -1,6 +1,9 @@
 #![allow(unused)] // FIXME remove after testing
 
-use std::fmt::Debug;
+use std::{
+    fmt::Debug,
+    time::{Duration, Instant},
+};
 
 use crate::{
     architecture::{
@@ -8,8 +11,8 @@ use crate::{
         xtensa::arch::instruction::{self, Instruction, InstructionEncoding},
     },
     probe::{
-        CommandResult, DeferredResultIndex, DeferredResultSet, JTAGAccess, JtagCommandQueue,
-        JtagWriteCommand,
+        telemetry::Telemetry, CommandResult, DeferredResultIndex, DeferredResultSet, JTAGAccess,
+        JtagCommandQueue, JtagWriteCommand,
     },
     DebugProbeError,
 };
@@ -26,6 +29,8 @@ const NARADR_DDREXEC: u8 = 0x46;
 const NARADR_DIR0EXEC: u8 = 0x47;
 // Assume we only support 16-24b instructions for now
 const NARADR_DIR0: u8 = 0x48;
+// The PC of the running core, sampled when read
+const NARADR_DEBUGPC: u8 = 0x0F;
 
 #[derive(Clone, Copy, PartialEq, Debug)]
 enum TapInstruction {
@@ -96,6 +101,9 @@ pub enum DebugRegisterError {
     #[error("Register-specific error")]
     Error,
 
+    #[error("Register access did not complete")]
+    Busy,
+
     #[error("Unexpected value")]
     Unexpected,
 }
@@ -141,8 +149,19 @@ pub struct Xdm {
 
     halt_on_reset: bool,
 
+    /// The index of the TAP (and thus the core) that is currently selected.
+    selected_core: usize,
+    /// The cores whose debug module has already been powered up and enabled.
+    initialized_cores: Vec<usize>,
+
     queue: JtagCommandQueue,
     result: DeferredResultSet,
+
+    /// Timing and transfer counts of the operations done through this debug module.
+    pub(super) telemetry: Telemetry,
+
+    /// Whether the JTAG clock is lowered when accesses to the debug module fail.
+    pub(super) speed_backoff: bool,
 }
 
 impl Xdm {
@@ -152,6 +171,9 @@ impl Xdm {
         // fixed to 5 bits for now
         probe.set_ir_len(5);
 
+        // The probe may have been pointed at the TAP of the first core, if other TAPs precede it.
+        let selected_core = probe.selected_jtag_tap();
+
         let mut x = Self {
             probe,
             device_id: 0,
@@ -160,17 +182,103 @@ impl Xdm {
 
             halt_on_reset: false,
 
+            selected_core,
+            initialized_cores: vec![selected_core],
+
             queue: JtagCommandQueue::new(),
             result: DeferredResultSet::new(),
+
+            telemetry: Telemetry::default(),
+
+            speed_backoff: true,
         };
 
-        if let Err(e) = x.init() {
-            return Err((x.free(), e));
+        match x.init() {
+            Ok(()) => {}
+            // The debug module is initialized once the chip wakes up.
+            Err(XtensaError::Sleeping) => x.initialized_cores.clear(),
+            Err(e) => return Err((x.free(), e)),
         }
 
         Ok(x)
     }
 
+    /// Returns the index of the currently selected core.
+    pub(super) fn selected_core(&self) -> usize {
+        self.selected_core
+    }
+
+    /// Directs all further accesses to the debug module of the core behind the TAP at `core`.
+    ///
+    /// The debug module of a core is initialized the first time it is selected.
+    pub(super) fn select_core(&mut self, core: usize) -> Result<(), XtensaError> {
+        if core == self.selected_core {
+            return Ok(());
+        }
+
+        // Make sure nothing queued for the previous core ends up on the new one.
+        self.execute()?;
+
+        self.probe.select_jtag_tap(core)?;
+        self.selected_core = core;
+        self.last_instruction = None;
+
+        if !self.initialized_cores.contains(&core) {
+            self.init()?;
+            self.initialized_cores.push(core);
+        }
+
+        Ok(())
+    }
+
+    /// Initializes the debug module of the selected core again, after the chip lost power. The
+    /// debug modules of the other cores are initialized again when they are selected.
+    pub(super) fn reinit(&mut self) -> Result<(), XtensaError> {
+        self.last_instruction = None;
+        self.initialized_cores.clear();
+        self.ensure_initialized()
+    }
+
+    /// Returns whether the debug module of the selected core has been initialized. It can't be
+    /// initialized while it is powered down.
+    pub(super) fn is_initialized(&self) -> bool {
+        self.initialized_cores.contains(&self.selected_core)
+    }
+
+    /// Initializes the debug module of the selected core, if it was powered down when the core
+    /// was selected.
+    pub(super) fn ensure_initialized(&mut self) -> Result<(), XtensaError> {
+        if !self.is_initialized() {
+            self.init()?;
+            self.initialized_cores.push(self.selected_core);
+        }
+
+        Ok(())
+    }
+
+    /// Reads the status of the power domains of the selected core.
+    pub(super) fn power_status(&mut self) -> Result<PowerStatus, XtensaError> {
+        self.pwr_read(PowerDevice::PowerStat).map(PowerStatus)
+    }
+
+    /// Reads the status of the power domains of the selected core, and clears its sticky reset
+    /// flags.
+    pub(super) fn take_reset_flags(&mut self) -> Result<PowerStatus, XtensaError> {
+        let status = self.power_status()?;
+
+        if status.core_was_reset() || status.debug_was_reset() {
+            let mut clear = PowerStatus(0);
+            clear.set_core_was_reset(true);
+            clear.set_debug_was_reset(true);
+            self.pwr_write(PowerDevice::PowerStat, clear.0)?;
+        }
+
+        Ok(status)
+    }
+
+    /// How long the debug domain is given to power up, before the chip is considered asleep.
+    const POWER_UP_TIMEOUT: Duration = Duration::from_millis(100);
+
     fn init(&mut self) -> Result<(), XtensaError> {
         let mut pwr_control = PowerControl(0);
 
@@ -182,13 +290,18 @@ impl Xdm {
         self.pwr_write(PowerDevice::PowerControl, pwr_control.0)?;
 
         tracing::trace!("Waiting for power domain to turn on");
-        loop {
-            let bits = self.pwr_read(PowerDevice::PowerStat)?;
-            if PowerStatus(bits).debug_domain_on() {
-                break;
+        let start = Instant::now();
+        while !self.power_status()?.debug_domain_on() {
+            // The debug domain stays powered down while the chip is in deep sleep.
+            if start.elapsed() > Self::POWER_UP_TIMEOUT {
+                return Err(XtensaError::Sleeping);
             }
         }
 
+        // The reset flags report the power-up of the debug module, which is not a reset of
+        // interest.
+        self.take_reset_flags()?;
+
         // Set JTAG_DEBUG_USE separately to ensure it doesn't get reset by a previous write.
         // We don't reset anything but this is a good practice to avoid sneaky issues.
         pwr_control.set_jtag_debug_use(true);
@@ -197,6 +310,9 @@ impl Xdm {
         // enable the debug module
         self.dbg_write(NARADR_DCRSET, 1)?;
 
+        let speed_khz = self.negotiate_speed()?;
+        tracing::debug!("Accessing the debug module at {} kHz", speed_khz);
+
         // read the device_id
         let device_id = self.dbg_read(NARADR_OCDID)?;
 
@@ -229,6 +345,94 @@ impl Xdm {
         Ok(())
     }
 
+    /// The lowest JTAG clock in kHz that the clock is lowered to when accesses fail.
+    const MIN_SPEED_KHZ: u32 = 100;
+
+    /// Sets the JTAG clock, then lowers it until the debug module can be accessed reliably.
+    /// Returns the effective clock in kHz.
+    pub(super) fn set_speed(&mut self, speed_khz: u32) -> Result<u32, XtensaError> {
+        self.probe.set_speed(speed_khz)?;
+        self.negotiate_speed()
+    }
+
+    /// Lowers the JTAG clock until the debug module can be accessed reliably. Returns the
+    /// effective clock in kHz.
+    pub(super) fn negotiate_speed(&mut self) -> Result<u32, XtensaError> {
+        loop {
+            match self.check_link() {
+                Ok(()) => return Ok(self.probe.speed_khz()),
+                Err(error @ XtensaError::XdmError(Error::Xdm(_))) => {
+                    if !self.back_off() {
+                        return Err(error);
+                    }
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Reads OCDID twice, checking that both reads complete and agree.
+    fn check_link(&mut self) -> Result<(), XtensaError> {
+        let first = self.schedule_dbg_read(NARADR_OCDID);
+        let (first_status, second) = self.schedule_dbg_read_with_status(NARADR_OCDID);
+        let second_status = self.schedule_read_dbg_status();
+
+        self.execute()?;
+
+        let first = self.result.take(first).unwrap().as_u32();
+        let second = self.result.take(second).unwrap().as_u32();
+        if [first, second].iter().all(|id| *id == 0 || *id == !0) {
+            return Err(DebugProbeError::TargetNotFound.into());
+        }
+
+        for status in [first_status, second_status] {
+            match parse_register_status(self.result.take(status).unwrap().as_u8())? {
+                DebugRegisterStatus::Ok => {}
+                DebugRegisterStatus::Busy => return Err(DebugRegisterError::Busy.into()),
+                DebugRegisterStatus::Error => return Err(DebugRegisterError::Error.into()),
+            }
+        }
+
+        if first != second {
+            return Err(DebugRegisterError::Unexpected.into());
+        }
+
+        Ok(())
+    }
+
+    /// Halves the JTAG clock if back-off is enabled and the clock is above
+    /// [`MIN_SPEED_KHZ`](Self::MIN_SPEED_KHZ). Returns whether the clock was lowered.
+    fn back_off(&mut self) -> bool {
+        let speed_khz = self.probe.speed_khz();
+        if !self.speed_backoff || speed_khz / 2 < Self::MIN_SPEED_KHZ {
+            return false;
+        }
+
+        // Some probes can't change their clock, and report the same speed afterwards.
+        if self.probe.set_speed(speed_khz / 2).is_err() || self.probe.speed_khz() >= speed_khz {
+            return false;
+        }
+
+        tracing::warn!(
+            "Debug module accesses failed at {} kHz, lowering the JTAG clock to {} kHz",
+            speed_khz,
+            self.probe.speed_khz()
+        );
+
+        true
+    }
+
+    /// Parses the status of a register access. Failed accesses lower the JTAG clock, so that
+    /// the accesses that follow are more likely to succeed.
+    fn register_status(&mut self, byte: u8) -> Result<DebugRegisterStatus, XtensaError> {
+        let status = parse_register_status(byte);
+        if matches!(status, Ok(DebugRegisterStatus::Error) | Err(_)) {
+            self.back_off();
+        }
+
+        Ok(status?)
+    }
+
     pub fn clear_exec_exception(&mut self) -> Result<(), XtensaError> {
         self.write_nexus_register({
             let mut status = DebugStatus(0);
@@ -242,6 +446,7 @@ impl Xdm {
     }
 
     fn tap_write(&mut self, instr: TapInstruction, data: u32) -> Result<u32, DebugProbeError> {
+        self.telemetry.count_jtag_transfers(1);
         let capture = self
             .probe
             .write_register(instr.code(), &data.to_le_bytes(), instr.bits())?;
@@ -250,6 +455,7 @@ impl Xdm {
     }
 
     fn tap_read(&mut self, instr: TapInstruction) -> Result<u32, DebugProbeError> {
+        self.telemetry.count_jtag_transfers(1);
         let capture = self.probe.read_register(instr.code(), instr.bits())?;
 
         Ok(instr.capture_to_u32(&capture))
@@ -257,6 +463,7 @@ impl Xdm {
 
     fn execute(&mut self) -> Result<(), XtensaError> {
         let queue = std::mem::take(&mut self.queue);
+        self.telemetry.count_jtag_transfers(queue.len());
         match self.probe.write_register_batch(&queue) {
             Ok(result) => self.result = result,
             Err(err) => match err.error {
@@ -293,9 +500,19 @@ impl Xdm {
 
     /// Perform an access to a register
     fn schedule_dbg_read(&mut self, address: u8) -> DeferredResultIndex {
+        let (_, reader) = self.schedule_dbg_read_with_status(address);
+        reader
+    }
+
+    /// Perform an access to a register. In addition to the register value, returns the status of
+    /// the previous access, which is captured when the register address is shifted in.
+    fn schedule_dbg_read_with_status(
+        &mut self,
+        address: u8,
+    ) -> (DeferredResultIndex, DeferredResultIndex) {
         let regdata = address << 1;
 
-        self.queue.schedule(JtagWriteCommand {
+        let status = self.queue.schedule(JtagWriteCommand {
             address: TapInstruction::Nar.code(),
             data: regdata.to_le_bytes().to_vec(),
             len: TapInstruction::Nar.bits(),
@@ -306,7 +523,7 @@ impl Xdm {
             },
         });
 
-        self.queue.schedule(JtagWriteCommand {
+        let reader = self.queue.schedule(JtagWriteCommand {
             address: TapInstruction::Ndr.code(),
             data: vec![0; 4],
             len: TapInstruction::Ndr.bits(),
@@ -315,7 +532,9 @@ impl Xdm {
                     TapInstruction::Ndr.capture_to_u32(&capture),
                 ))
             },
-        })
+        });
+
+        (status, reader)
     }
 
     /// Perform an access to a register
@@ -378,7 +597,38 @@ impl Xdm {
 
         let res = self.result.take(reader).unwrap().as_u8();
 
-        Ok(parse_register_status(res)?)
+        self.register_status(res)
+    }
+
+    /// Reads the Nexus register at `address`, without any of the bookkeeping done by the typed
+    /// accessors.
+    pub(super) fn read_raw_nexus_register(&mut self, address: u8) -> Result<u32, XtensaError> {
+        self.dbg_read(address)
+    }
+
+    /// Writes `value` to the Nexus register at `address`, without any of the bookkeeping done by
+    /// the typed accessors.
+    pub(super) fn write_raw_nexus_register(
+        &mut self,
+        address: u8,
+        value: u32,
+    ) -> Result<(), XtensaError> {
+        self.dbg_write(address, value)?;
+
+        // The write may have replaced the instruction in DIR0.
+        self.last_instruction = None;
+
+        Ok(())
+    }
+
+    /// Writes `value` to PWRCTL, returning the previous value.
+    pub(super) fn write_raw_power_control(&mut self, value: u8) -> Result<u8, XtensaError> {
+        self.pwr_write(PowerDevice::PowerControl, value)
+    }
+
+    /// Reads PWRSTAT.
+    pub(super) fn read_raw_power_status(&mut self) -> Result<u8, XtensaError> {
+        self.pwr_read(PowerDevice::PowerStat)
     }
 
     fn pwr_write(&mut self, dev: PowerDevice, value: u8) -> Result<u8, XtensaError> {
@@ -419,6 +669,65 @@ impl Xdm {
         Ok(())
     }
 
+    /// Samples the PC of the running core.
+    pub(super) fn read_debug_pc(&mut self) -> Result<u32, XtensaError> {
+        let reader = self.schedule_dbg_read(NARADR_DEBUGPC);
+
+        self.dbg_status()?;
+
+        Ok(self.result.take(reader).unwrap().as_u32())
+    }
+
+    /// Reads a register of the TRAX trace unit.
+    pub(super) fn read_trax_register(&mut self, address: u8) -> Result<u32, XtensaError> {
+        let reader = self.schedule_dbg_read(address);
+
+        self.dbg_status()?;
+
+        Ok(self.result.take(reader).unwrap().as_u32())
+    }
+
+    /// How long a write to a TRAX register may keep the debug register interface busy.
+    const TRAX_WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+    /// Writes a register of the TRAX trace unit.
+    pub(super) fn write_trax_register(
+        &mut self,
+        address: u8,
+        value: u32,
+    ) -> Result<(), XtensaError> {
+        self.schedule_dbg_write(address, value);
+
+        let start = Instant::now();
+        while self.dbg_status()? == DebugRegisterStatus::Busy {
+            if start.elapsed() > Self::TRAX_WRITE_TIMEOUT {
+                return Err(XtensaError::Timeout);
+            }
+            tracing::trace!("Waiting for write to complete");
+        }
+
+        Ok(())
+    }
+
+    /// Reads a TRAX register `count` times in a single JTAG batch. Used to read the trace memory,
+    /// as the trace address is incremented by every read of the trace data register.
+    pub(super) fn read_trax_register_repeated(
+        &mut self,
+        address: u8,
+        count: usize,
+    ) -> Result<Vec<u32>, XtensaError> {
+        let readers = (0..count)
+            .map(|_| self.schedule_dbg_read(address))
+            .collect::<Vec<_>>();
+
+        self.dbg_status()?;
+
+        Ok(readers
+            .into_iter()
+            .map(|reader| self.result.take(reader).unwrap().as_u32())
+            .collect())
+    }
+
     pub(super) fn status(&mut self) -> Result<DebugStatus, XtensaError> {
         self.read_nexus_register::<DebugStatus>()
     }
@@ -428,24 +737,66 @@ impl Xdm {
         loop {
             let status = self.status()?;
 
-            if status.exec_overrun() {
-                return Err(Error::ExecOverrun.into());
-            }
-            if status.exec_exception() {
-                // TODO: we probably don't want to clear all clearable status bits.
-                self.write_nexus_register(status);
-                // TODO: we also probably don't want to crash if an exception happens here
-                return Err(Error::ExecExeception.into());
+            if self.exec_finished(status)? {
+                return Ok(());
             }
+        }
+    }
 
-            if !status.exec_busy() {
-                if status.exec_done() {
-                    return Ok(());
-                }
+    /// Checks the execution state in `status`. Returns whether the last instruction has finished.
+    fn exec_finished(&mut self, status: DebugStatus) -> Result<bool, XtensaError> {
+        if status.exec_overrun() {
+            return Err(Error::ExecOverrun.into());
+        }
+        if status.exec_exception() {
+            // TODO: we probably don't want to clear all clearable status bits.
+            self.write_nexus_register(status);
+            // TODO: we also probably don't want to crash if an exception happens here
+            return Err(Error::ExecExeception.into());
+        }
 
+        if !status.exec_busy() {
+            if !status.exec_done() {
                 tracing::warn!("Instruction ignored: {:?}", self.last_instruction.unwrap());
-                return Ok(());
             }
+
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Writes a register that executes the instruction in DIR, and waits for the instruction to
+    /// complete.
+    ///
+    /// The write and the first completion check are sent to the probe in a single JTAG batch.
+    /// Only if the instruction has not completed by then, DSR is polled.
+    fn write_and_execute(&mut self, address: u8, value: u32) -> Result<(), XtensaError> {
+        self.schedule_dbg_write(address, value);
+        let (write_status, status) = self.schedule_dbg_read_with_status(DebugStatus::ADDRESS);
+        let read_status = self.schedule_read_dbg_status();
+
+        self.execute()?;
+
+        let write_status = self.result.take(write_status).unwrap().as_u8();
+        let write_status = self.register_status(write_status)?;
+        let read_status = self.result.take(read_status).unwrap().as_u8();
+        let read_status = self.register_status(read_status)?;
+        let status = DebugStatus::from_bits(self.result.take(status).unwrap().as_u32())?;
+
+        if write_status == DebugRegisterStatus::Busy || read_status == DebugRegisterStatus::Busy {
+            // The status read raced the write, so its value can't be trusted.
+            while self.dbg_status()? == DebugRegisterStatus::Busy {
+                tracing::trace!("Waiting for write to complete");
+            }
+
+            return self.wait_for_exec_done();
+        }
+
+        if self.exec_finished(status)? {
+            Ok(())
+        } else {
+            self.wait_for_exec_done()
         }
     }
 
@@ -473,6 +824,28 @@ impl Xdm {
         Ok(())
     }
 
+    /// Enables or disables the BreakIn/BreakOut signals of the selected core.
+    ///
+    /// When enabled on all cores, a core entering the Stopped state also stops
+    /// the others, and they are resumed together.
+    pub(super) fn set_cross_trigger(&mut self, enabled: bool) -> Result<(), XtensaError> {
+        let mut control = DebugControlBits(0);
+
+        control.set_break_in_en(true);
+        control.set_break_out_en(true);
+
+        if enabled {
+            self.write_nexus_register(DebugControlSet(control))
+        } else {
+            self.write_nexus_register(DebugControlClear(control))
+        }
+    }
+
+    pub(super) fn cross_trigger_enabled(&mut self) -> Result<bool, XtensaError> {
+        let reg = self.read_nexus_register::<DebugControlSet>()?;
+        Ok(reg.0.break_in_en() && reg.0.break_out_en())
+    }
+
     pub(super) fn is_in_ocd_mode(&mut self) -> Result<bool, XtensaError> {
         let reg = self.read_nexus_register::<DebugControlSet>()?;
         Ok(reg.0.enable_ocd())
@@ -550,11 +923,9 @@ impl Xdm {
 
         match instruction.encode() {
             InstructionEncoding::Narrow(inst) => {
-                self.write_nexus_register(DebugInstructionAndExecRegister(inst))?;
+                self.write_and_execute(DebugInstructionAndExecRegister::ADDRESS, inst)
             }
         }
-
-        self.wait_for_exec_done()
     }
 
     pub fn read_ddr(&mut self) -> Result<u32, XtensaError> {
@@ -580,17 +951,94 @@ impl Xdm {
         Ok(reg.bits())
     }
 
+    /// Reads `count` words from DDR in a single JTAG batch. Every read except the last one also
+    /// executes the instruction in DIR. The last read executes it only if `execute_last` is set.
+    ///
+    /// Instruction completion is not polled between reads. If the core could not keep up,
+    /// [`Error::ExecOverrun`] is returned and the read data must be discarded.
+    pub fn read_ddr_and_execute_batch(
+        &mut self,
+        count: usize,
+        execute_last: bool,
+    ) -> Result<Vec<u32>, XtensaError> {
+        if let Some(instruction) = self.last_instruction {
+            tracing::debug!(
+                "Executing instruction via batched DDREXEC reads: {:?}",
+                instruction
+            );
+        }
+
+        let readers = (0..count)
+            .map(|i| self.schedule_dbg_read(batch_read_address(i, count, execute_last)))
+            .collect::<Vec<_>>();
+
+        self.execute_queued(readers)
+    }
+
+    /// Queues the execution of `instruction`, without waiting for it to complete.
+    ///
+    /// The queue is sent to the probe by [`Xdm::execute_queued`].
+    pub(super) fn schedule_execute_instruction(&mut self, instruction: Instruction) {
+        tracing::debug!("Queueing instruction: {:?}", instruction);
+        self.last_instruction = Some(instruction);
+
+        match instruction.encode() {
+            InstructionEncoding::Narrow(inst) => {
+                self.schedule_dbg_write(NARADR_DIR0EXEC, inst);
+            }
+        }
+    }
+
+    /// Queues a read of DDR. The value is returned by [`Xdm::execute_queued`].
+    pub(super) fn schedule_read_ddr(&mut self) -> DeferredResultIndex {
+        self.schedule_dbg_read(NARADR_DDR)
+    }
+
+    /// Queues a write of DDR.
+    pub(super) fn schedule_write_ddr(&mut self, ddr: u32) {
+        self.schedule_dbg_write(NARADR_DDR, ddr);
+    }
+
+    /// Sends the queued commands to the probe in a single batch, and returns the values read
+    /// by `readers`.
+    ///
+    /// Instruction completion is only checked at the end of the batch. If the core could not keep
+    /// up, [`Error::ExecOverrun`] is returned and the read data must be discarded.
+    pub(super) fn execute_queued(
+        &mut self,
+        readers: Vec<DeferredResultIndex>,
+    ) -> Result<Vec<u32>, XtensaError> {
+        self.execute()?;
+
+        let words = readers
+            .into_iter()
+            .map(|reader| self.result.take(reader).unwrap().as_u32())
+            .collect();
+
+        if let Err(error) = self.wait_for_exec_done() {
+            if let XtensaError::XdmError(Error::ExecOverrun) = error {
+                self.write_nexus_register({
+                    let mut status = DebugStatus(0);
+
+                    status.set_exec_overrun(true);
+
+                    status
+                })?;
+            }
+
+            return Err(error);
+        }
+
+        Ok(words)
+    }
+
     pub fn write_ddr_and_execute(&mut self, ddr: u32) -> Result<(), XtensaError> {
         if let Some(instruction) = self.last_instruction {
             tracing::debug!("Executing instruction via DDREXEC write: {:?}", instruction);
         } else {
             tracing::warn!("Writing DDREXEC without instruction");
         }
-        self.write_nexus_register(DebugDataAndExecRegister(ddr))?;
-
-        self.wait_for_exec_done()?;
-
-        Ok(())
+        self.write_and_execute(DebugDataAndExecRegister::ADDRESS, ddr)
     }
 
     pub fn target_reset_assert(&mut self) -> Result<(), XtensaError> {
@@ -856,3 +1304,35 @@ impl NexusRegister for DebugInstructionAndExecRegister {
         self.0
     }
 }
+
+/// Returns the register the read at `index` of a batch of `count` reads accesses. Every read
+/// except the last one executes the instruction in DIR, the last one only if `execute_last` is
+/// set.
+fn batch_read_address(index: usize, count: usize, execute_last: bool) -> u8 {
+    if index + 1 < count || execute_last {
+        NARADR_DDREXEC
+    } else {
+        NARADR_DDR
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn batched_reads_execute_all_but_the_last_instruction() {
+        let addresses = |count, execute_last| {
+            (0..count)
+                .map(|i| batch_read_address(i, count, execute_last))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(addresses(1, false), [NARADR_DDR]);
+        assert_eq!(
+            addresses(3, false),
+            [NARADR_DDREXEC, NARADR_DDREXEC, NARADR_DDR]
+        );
+        assert_eq!(addresses(2, true), [NARADR_DDREXEC, NARADR_DDREXEC]);
+    }
+}
@@ -4,6 +4,7 @@ pub mod cargo_embed;
 pub mod cargo_flash;
 pub mod chip;
 pub mod dap_server;
+pub mod data_trace;
 pub mod debug;
 pub mod download;
 pub mod erase;
@@ -11,6 +12,7 @@ pub mod gdb;
 pub mod info;
 pub mod itm;
 pub mod list;
+pub mod peripheral;
 pub mod profile;
 pub mod read;
 pub mod reset;
@@ -28,7 +28,7 @@ use std::{
 use time::{OffsetDateTime, UtcOffset};
 
 use self::rttui::channel::DataFormat;
-use crate::util::{build_artifact, common_options::CargoOptions, logging};
+use crate::util::{build_artifact, common_options::CargoOptions, logging, with_memory_access};
 
 #[derive(Debug, clap::Parser)]
 #[command(after_long_help = CargoOptions::help_message("cargo-embed"))]
@@ -385,7 +385,9 @@ fn main_try(mut args: Vec<OsString>, offset: UtcOffset) -> Result<()> {
                 let mut session_handle = session.lock().unwrap();
                 let mut core = session_handle.core(0)?;
 
-                app.poll_rtt(&mut core, offset)?;
+                with_memory_access(&mut core, |core| {
+                    app.poll_rtt(core, offset).map_err(anyhow::Error::from)
+                })?;
 
                 app.render();
                 if app.handle_event(&mut core) {
@@ -519,7 +521,10 @@ fn rtt_attach(
             let memory_map = session_handle.target().memory_map.clone();
             let mut core = session_handle.core(0)?;
 
-            match Rtt::attach_region(&mut core, &memory_map, rtt_region) {
+            let attached = with_memory_access(&mut core, |core| {
+                Rtt::attach_region(core, &memory_map, rtt_region)
+            });
+            match attached {
                 Ok(rtt) => return Ok(rtt),
                 Err(e) => last_error = Some(e),
             }
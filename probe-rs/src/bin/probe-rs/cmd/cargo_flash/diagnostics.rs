@@ -6,6 +6,7 @@ use std::fmt::Write;
 use bytesize::ByteSize;
 
 use probe_rs::{
+    architecture::xtensa::communication_interface::{ResetPhase, XtensaError},
     config::MemoryRegion,
     config::{RegistryError, TargetDescriptionSource},
     flashing::{FileDownloadError, FlashError},
@@ -193,13 +194,13 @@ pub(crate) fn render_diagnostics(error: OperationError) {
             error.to_string(),
             vec![],
         ),
-        OperationError::TargetResetFailed(_e) =>  (
+        OperationError::TargetResetFailed(e) =>  (
             error.to_string(),
-            vec![],
+            reset_hints(e),
         ),
-        OperationError::TargetResetHaltFailed(_e) => (
+        OperationError::TargetResetHaltFailed(e) => (
             error.to_string(),
-            vec![],
+            reset_hints(e),
         ),
         OperationError::CliArgument(_e) => (
             error.to_string(),
@@ -232,6 +233,22 @@ pub(crate) fn render_diagnostics(error: OperationError) {
     let _ = stderr.flush();
 }
 
+fn reset_hints(error: &ProbeRsError) -> Vec<String> {
+    match error {
+        ProbeRsError::Xtensa(XtensaError::ResetFailed { phase, .. }) => match phase {
+            ResetPhase::Assert | ResetPhase::Deassert => vec![
+                "The probe could not drive the reset line. Make sure the reset pin of the chip is connected to the probe.".into(),
+            ],
+            ResetPhase::Halt => vec![
+                "The core did not halt after the reset. Retrying might help.".into(),
+                "A hard reset during attaching might help. Run with `--connect-under-reset` to enable this feature.".into(),
+            ],
+            ResetPhase::Resume => vec![],
+        },
+        _ => vec![],
+    }
+}
+
 fn generate_flash_error_hints(
     error: &FlashError,
     target: &Target,
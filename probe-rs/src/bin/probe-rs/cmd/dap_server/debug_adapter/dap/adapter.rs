@@ -177,9 +177,10 @@ impl<P: ProtocolAdapter> DebugAdapter<P> {
         let mut result_buffer = vec![];
         let large_read_byte_count = 8usize;
         let mut fast_buff = vec![0u8; large_read_byte_count];
-        // Read as many large chunks as possible.
+        // Read as many large chunks as possible. Memory outside of the memory map is not read,
+        // as the bus errors can leave the debug module in a bad state.
         while num_bytes_unread > 0 {
-            if let Ok(()) = target_core.core.read(address, &mut fast_buff) {
+            if let Ok(()) = target_core.core.read_mapped(address, &mut fast_buff) {
                 result_buffer.extend_from_slice(&fast_buff);
                 address += large_read_byte_count as u64;
                 num_bytes_unread -= large_read_byte_count;
@@ -189,8 +190,9 @@ impl<P: ProtocolAdapter> DebugAdapter<P> {
         }
         // Read the remaining bytes one by one.
         while num_bytes_unread > 0 {
-            if let Ok(good_byte) = target_core.core.read_word_8(address) {
-                result_buffer.push(good_byte);
+            let mut good_byte = [0];
+            if let Ok(()) = target_core.core.read_mapped(address, &mut good_byte) {
+                result_buffer.push(good_byte[0]);
                 address += 1;
                 num_bytes_unread -= 1;
             } else {
@@ -1,4 +1,4 @@
-use probe_rs::{CoreStatus, HaltReason};
+use probe_rs::{CoreStatus, ExceptionCause, HaltReason};
 
 pub(crate) trait DapStatus {
     fn short_long_status(&self, program_counter: Option<u64>) -> (&'static str, String);
@@ -26,11 +26,19 @@ impl DapStatus for CoreStatus {
                         }
                     ),
                 ),
-                HaltReason::Exception => (
+                HaltReason::Exception(ExceptionCause::Unknown) => (
                     "exception",
                     "Core halted due to an exception, e.g. interupt handler".to_string(),
                 ),
-                HaltReason::Watchpoint => (
+                HaltReason::Exception(cause) => (
+                    "exception",
+                    format!("Core halted due to an exception: {cause}"),
+                ),
+                HaltReason::Watchpoint(Some(unit)) => (
+                    "data breakpoint",
+                    format!("Core halted due to watchpoint {unit}"),
+                ),
+                HaltReason::Watchpoint(None) => (
                     "data breakpoint",
                     "Core halted due to a watchpoint or data breakpoint".to_string(),
                 ),
@@ -165,6 +165,7 @@ pub(crate) fn variable_cache_from_svd<P: ProtocolAdapter>(
             register_variable.variable_node_type = VariableNodeType::SvdRegister;
             register_variable.memory_location =
                 VariableLocation::Address(peripheral.base_address + register.address_offset as u64);
+            register_variable.byte_size = register.properties.size.map(|size| size as u64 / 8);
             let mut register_has_restricted_read = false;
             if register.read_action.is_some()
                 || (if let Some(register_access) = register.properties.access {
@@ -195,6 +196,7 @@ pub(crate) fn variable_cache_from_svd<P: ProtocolAdapter>(
                 );
                 field_variable.variable_node_type = VariableNodeType::SvdField;
                 field_variable.memory_location = register_variable.memory_location.clone();
+                field_variable.byte_size = register_variable.byte_size;
                 // For SVD fields, we overload the range_lower_bound and range_upper_bound as the bit range LSB and MSB.
                 field_variable.range_lower_bound = field.bit_offset() as i64;
                 field_variable.range_upper_bound = (field.bit_offset() + field.bit_width()) as i64;
@@ -14,7 +14,10 @@ use crate::cmd::dap_server::{
     server::debug_rtt,
     DebuggerError,
 };
-use crate::util::rtt::{self, ChannelMode, DataFormat, RttActiveTarget};
+use crate::util::{
+    rtt::{self, ChannelMode, DataFormat, RttActiveTarget},
+    with_memory_access,
+};
 use anyhow::{anyhow, Result};
 use probe_rs::{
     debug::{debug_info::DebugInfo, ColumnType, ObjectRef, VerifiedBreakpoint},
@@ -188,8 +191,10 @@ impl<'p> CoreHandle<'p> {
                 )
             })
             .and_then(|scan_region| {
-                Rtt::attach_region(&mut self.core, target_memory_map, &scan_region)
-                    .map_err(|error| anyhow!("Error attempting to attach to RTT: {}", error))
+                with_memory_access(&mut self.core, |core| {
+                    Rtt::attach_region(core, target_memory_map, &scan_region)
+                })
+                .map_err(|error| anyhow!("Error attempting to attach to RTT: {}", error))
             })
             .and_then(|rtt| {
                 tracing::info!("RTT initialized.");
@@ -8,6 +8,7 @@ use crate::cmd::dap_server::{
     },
     DebuggerError,
 };
+use crate::util::with_memory_access;
 use anyhow::{anyhow, Result};
 use probe_rs::{
     config::TargetSelector,
@@ -283,8 +284,15 @@ impl SessionData {
             if core_config.rtt_config.enabled {
                 if let Some(core_rtt) = &mut target_core.core_data.rtt_connection {
                     // We should poll the target for rtt data, and if any RTT data was processed, we clear the flag.
-                    if core_rtt.process_rtt_data(debug_adapter, &mut target_core.core) {
-                        suggest_delay_required = false;
+                    let had_data = with_memory_access(&mut target_core.core, |core| {
+                        Ok::<_, probe_rs::Error>(core_rtt.process_rtt_data(debug_adapter, core))
+                    });
+                    match had_data {
+                        Ok(true) => suggest_delay_required = false,
+                        Ok(false) => {}
+                        Err(error) => {
+                            tracing::warn!("Failed to halt the core to poll RTT: {error}")
+                        }
                     }
                 } else if debug_adapter.configuration_is_done() {
                     // We have not yet reached the point in the target application where the RTT buffers are initialized,
@@ -0,0 +1,125 @@
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use probe_rs::{Lister, RegisterId, TraceWatchpoint, WatchpointKind};
+
+use crate::util::{common_options::ProbeOptions, parse_u64};
+use crate::CoreOptions;
+
+/// Log every access to a memory location, using a watchpoint which resumes the core after
+/// recording it.
+///
+/// e.g. probe-rs data-trace 0x20000010 --register a0 --register ra
+///      Prints the program counter, the stored value and the values of a0 and ra
+///      whenever the word at 0x20000010 is written.
+///
+/// Each hit halts the core briefly, so this is only suitable for locations which are
+/// accessed a few hundred times per second at most.
+#[derive(clap::Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct Cmd {
+    #[clap(flatten)]
+    shared: CoreOptions,
+
+    #[clap(flatten)]
+    common: ProbeOptions,
+
+    /// The address of the traced memory
+    #[clap(value_parser = parse_u64)]
+    address: u64,
+
+    /// The number of traced bytes
+    #[clap(long, default_value = "4", value_parser = parse_u64)]
+    length: u64,
+
+    /// The kind of access that is traced
+    #[clap(long, value_enum, default_value = "write")]
+    on: AccessKind,
+
+    /// A register which is recorded on every access, by name. Can be given multiple times.
+    #[clap(long = "register")]
+    registers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum AccessKind {
+    Read,
+    Write,
+    Access,
+}
+
+impl From<AccessKind> for WatchpointKind {
+    fn from(kind: AccessKind) -> Self {
+        match kind {
+            AccessKind::Read => WatchpointKind::Read,
+            AccessKind::Write => WatchpointKind::Write,
+            AccessKind::Access => WatchpointKind::ReadWrite,
+        }
+    }
+}
+
+impl Cmd {
+    pub fn run(self, lister: &Lister) -> anyhow::Result<()> {
+        let (mut session, _probe_options) = self.common.simple_attach(lister)?;
+
+        let mut watchpoint = TraceWatchpoint::new(self.address, self.length, self.on.into())
+            .with_core(self.shared.core)
+            .with_memory(self.address..self.address + self.length);
+
+        let mut names = Vec::new();
+        {
+            let mut core = session.core(self.shared.core)?;
+            for name in &self.registers {
+                let register = core
+                    .registers()
+                    .all_registers()
+                    .find(|register| register.name().eq_ignore_ascii_case(name))
+                    .ok_or_else(|| anyhow!("The core has no register named {name}"))?;
+                names.push((register.id(), register.name()));
+                watchpoint = watchpoint.with_register(register.id());
+            }
+
+            if core.core_halted()? {
+                core.run()?;
+            }
+        }
+
+        session.add_trace_watchpoint(watchpoint)?;
+
+        let start = Instant::now();
+        let mut dropped = 0;
+        loop {
+            session.poll_trace_watchpoints()?;
+
+            for record in session.take_data_trace() {
+                let time = record.time.duration_since(start).as_secs_f64();
+                print!("{time:>10.6} pc={:#010x}", record.pc);
+                for (address, data) in &record.memory {
+                    print!(" [{address:#010x}]=");
+                    for byte in data {
+                        print!("{byte:02x}");
+                    }
+                }
+                for (id, value) in &record.registers {
+                    print!(" {}={value}", register_name(&names, *id));
+                }
+                println!();
+            }
+
+            if session.data_trace_dropped() != dropped {
+                dropped = session.data_trace_dropped();
+                tracing::warn!("{dropped} trace records were dropped");
+            }
+
+            sleep(Duration::from_millis(1));
+        }
+    }
+}
+
+fn register_name(names: &[(RegisterId, &'static str)], id: RegisterId) -> &'static str {
+    names
+        .iter()
+        .find(|(register, _)| *register == id)
+        .map_or("?", |(_, name)| name)
+}
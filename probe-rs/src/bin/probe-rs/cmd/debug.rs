@@ -7,6 +7,7 @@ use capstone::{
     arch::arm::ArchMode as armArchMode, arch::arm64::ArchMode as aarch64ArchMode,
     arch::riscv::ArchMode as riscvArchMode, prelude::*, Capstone, Endian,
 };
+use indicatif::{ProgressBar, ProgressStyle};
 use num_traits::Num;
 use parse_int::parse;
 use probe_rs::architecture::arm::ap::AccessPortError;
@@ -17,7 +18,7 @@ use probe_rs::DebugProbeError;
 use probe_rs::Lister;
 use probe_rs::{
     debug::{debug_info::DebugInfo, registers::DebugRegisters, stack_frame::StackFrame},
-    Core, CoreType, InstructionSet, MemoryInterface, RegisterValue,
+    Core, CoreType, InstructionSet, MemoryInterface, RegisterDataType, RegisterValue,
 };
 use rustyline::DefaultEditor;
 
@@ -198,7 +199,9 @@ impl DebugCli {
                             capstone::arch::riscv::ArchExtraMode::RiscVC,
                         ))
                         .build(),
-                    InstructionSet::Xtensa => Err(capstone::Error::UnsupportedArch),
+                    InstructionSet::Xtensa | InstructionSet::XtensaNx => {
+                        Err(capstone::Error::UnsupportedArch)
+                    }
                 }
                 .map_err(|err| anyhow!("Error creating capstone: {:?}", err))?;
 
@@ -620,6 +623,30 @@ impl DebugCli {
             },
         });
 
+        cli.add_command(Command {
+            name: "reg",
+            help_text: "Read a register by name (e.g. pc, sp, a0), or write it if a value is given",
+
+            function: |cli_data, args| {
+                let name = args.first().ok_or(CliError::MissingArgument)?;
+
+                if args.len() > 1 {
+                    let register = cli_data.core.register_by_name(name)?;
+                    let value: u64 = get_int_argument(args, 1)?;
+                    if register.size_in_bits() <= 32 {
+                        cli_data.core.write_core_reg(register, value as u32)?;
+                    } else {
+                        cli_data.core.write_core_reg(register, value)?;
+                    }
+                } else {
+                    let value = cli_data.core.read_register_by_name(name)?;
+                    println!("{name}: {value:#}");
+                }
+
+                Ok(CliState::Continue)
+            },
+        });
+
         cli.add_command(Command {
             name: "fp_regs",
             help_text: "Show floating point register values",
@@ -635,32 +662,23 @@ impl DebugCli {
                         for register in registers {
                             let value: RegisterValue = cli_data.core.read_core_reg(register)?;
 
-                            // Print out the register every way it can be interpretted.
-                            // For example, with a u128:
-                            // * Raw value as a u128
-                            // * Value cast as a f64
-                            // * Value cast as a f32
-                            println!("{:10}: {:#}", register.name(), value);
-
-                            if matches!(value, RegisterValue::U128(_) | RegisterValue::U64(_)) {
-                                let data: u128 = value.try_into()?;
-                                let bytes = (data as u64).to_le_bytes();
-                                let fp_data = f64::from_le_bytes(bytes);
-
-                                println!("{:>10}: {:#}", "[as f64]", fp_data);
-                            }
-
-                            if matches!(
-                                value,
-                                RegisterValue::U128(_)
-                                    | RegisterValue::U64(_)
-                                    | RegisterValue::U32(_)
-                            ) {
-                                let data: u128 = value.try_into()?;
-                                let bytes = (data as u32).to_le_bytes();
-                                let fp_data = f32::from_le_bytes(bytes);
-
-                                println!("{:>10}: {:#}", "[as f32]", fp_data);
+                            match register.data_type() {
+                                RegisterDataType::Vector { lane_bits, lanes } => {
+                                    println!("{:10}: {:#}", register.name(), value);
+                                    for (lane, lane_value) in
+                                        value.vector_lanes(lane_bits, lanes).iter().enumerate()
+                                    {
+                                        println!("{:>10}: {:#}", format!("[{lane}]"), lane_value);
+                                    }
+                                }
+                                data_type => {
+                                    println!(
+                                        "{:10}: {:#} ({:#})",
+                                        register.name(),
+                                        value.to_typed(&data_type),
+                                        value
+                                    );
+                                }
                             }
 
                             println!();
@@ -795,7 +813,7 @@ impl DebugCli {
 
         cli.add_command(Command {
             name: "dump",
-            help_text: "Dump the core memory & registers",
+            help_text: "Dump the core memory & registers, as an ELF core file if the path ends with .elf",
 
             function: |cli_data, args| {
                 let mut args = args.to_vec();
@@ -845,7 +863,25 @@ impl DebugCli {
 
                 println!("Dumping core");
 
-                cli_data.core.dump(ranges)?.store(location)?;
+                let total = ranges.iter().map(|range| range.end - range.start).sum();
+                let progress = ProgressBar::new(total);
+                progress.set_style(
+                    ProgressStyle::default_bar()
+                        .template("[{elapsed_precise}] [{wide_bar}] {bytes:>8}/{total_bytes:>8} @ {bytes_per_sec:>10} (eta {eta:3})")
+                        .expect("Error in progress bar creation. This is a bug, please report it."),
+                );
+
+                let dump = cli_data.core.dump_with_progress(ranges, &mut |transfer| {
+                    progress.set_position(transfer.transferred as u64);
+                    true
+                });
+                progress.finish_and_clear();
+
+                if location.extension().is_some_and(|extension| extension == "elf") {
+                    dump?.store_elf(location)?;
+                } else {
+                    dump?.store(location)?;
+                }
 
                 println!("Done.");
 
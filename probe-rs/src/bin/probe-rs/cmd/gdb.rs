@@ -1,9 +1,13 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 use std::time::Duration;
 
-use probe_rs::Lister;
+use probe_rs::{Lister, Session};
+use time::UtcOffset;
 
 use crate::util::common_options::ProbeOptions;
+use crate::util::{rtt, with_memory_access};
 
 #[derive(clap::Parser)]
 pub struct Cmd {
@@ -20,12 +24,16 @@ pub struct Cmd {
     )]
     reset_halt: bool,
 
+    /// Print the RTT output of the firmware in the given ELF file while the GDB stub is running.
+    #[clap(long, value_name = "ELF")]
+    rtt: Option<PathBuf>,
+
     #[clap(flatten)]
     common: ProbeOptions,
 }
 
 impl Cmd {
-    pub fn run(self, lister: &Lister) -> anyhow::Result<()> {
+    pub fn run(self, lister: &Lister, timestamp_offset: UtcOffset) -> anyhow::Result<()> {
         let (mut session, _probe_options) = self.common.simple_attach(lister)?;
 
         if self.reset_halt {
@@ -52,11 +60,93 @@ impl Cmd {
 
         let session = Mutex::new(session);
 
-        if let Err(e) = probe_rs::gdb_server::run(&session, instances.iter()) {
-            eprintln!("During the execution of GDB an error was encountered:");
-            eprintln!("{e:?}");
-        }
+        std::thread::scope(|scope| {
+            let gdb = scope.spawn(|| probe_rs::gdb_server::run(&session, instances.iter()));
+
+            if let Some(elf) = self.rtt.as_deref() {
+                // GDB and RTT take turns on the session, RTT only holds it while it polls.
+                if let Err(e) = poll_rtt(&session, elf, timestamp_offset, || gdb.is_finished()) {
+                    eprintln!("Printing RTT output failed: {e:?}");
+                }
+            }
+
+            if let Err(e) = gdb.join().expect("the GDB stub thread panicked") {
+                eprintln!("During the execution of GDB an error was encountered:");
+                eprintln!("{e:?}");
+            }
+        });
 
         Ok(())
     }
 }
+
+/// Prints the RTT output of the target until `done` returns true.
+///
+/// The firmware may not have set up its control block yet, e.g. because GDB keeps the core halted
+/// after a reset, so attaching is retried on every poll until it succeeds.
+fn poll_rtt(
+    session: &Mutex<Session>,
+    elf: &Path,
+    timestamp_offset: UtcOffset,
+    done: impl Fn() -> bool,
+) -> anyhow::Result<()> {
+    let mut rtt_config = rtt::RttConfig::default();
+    rtt_config.channels.push(rtt::RttChannelConfig {
+        channel_number: Some(0),
+        ..Default::default()
+    });
+
+    let mut stdout = std::io::stdout();
+    let mut target_rtt = None;
+
+    while !done() {
+        let mut had_rtt_data = false;
+
+        {
+            let mut session = session.lock().unwrap();
+            let memory_map = session.target().memory_map.clone();
+            let scan_regions = session.target().rtt_scan_regions.clone();
+
+            // Errors accessing the core are usually transient, e.g. while GDB resets the target,
+            // so they don't stop the polling.
+            match session.core(0) {
+                Ok(mut core) => match &mut target_rtt {
+                    None => match rtt::attach_to_rtt(
+                        &mut core,
+                        &memory_map,
+                        &scan_regions,
+                        elf,
+                        &rtt_config,
+                        timestamp_offset,
+                        None,
+                    ) {
+                        Ok(attached) => target_rtt = Some(attached),
+                        Err(error) => log::debug!("{:?} RTT attach error", error),
+                    },
+                    Some(target_rtt) => match with_memory_access(&mut core, |core| {
+                        target_rtt.poll_rtt_fallible(core)
+                    }) {
+                        Ok(polled) => {
+                            for (_ch, data) in polled {
+                                had_rtt_data |= !data.is_empty();
+                                stdout.write_all(data.as_bytes())?;
+                            }
+                        }
+                        Err(error) => log::debug!("{:?} RTT poll error", error),
+                    },
+                },
+                Err(error) => log::debug!("{:?} RTT core access error", error),
+            }
+        }
+
+        // Same polling frequencies as `probe-rs run`, which also leaves the session to the GDB
+        // stub most of the time.
+        if had_rtt_data {
+            std::thread::sleep(Duration::from_millis(1));
+        } else {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    Ok(())
+}
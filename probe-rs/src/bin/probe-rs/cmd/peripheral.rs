@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context};
+use probe_rs::{read_peripheral_register, register_bits, Lister};
+use svd_parser::{
+    svd::{Access, Register},
+    Config,
+};
+
+use crate::util::common_options::ProbeOptions;
+use crate::util::with_memory_access;
+use crate::CoreOptions;
+
+/// Read peripheral registers described by a CMSIS-SVD file, decoded into their fields
+///
+/// e.g. probe-rs peripheral --chip esp32s3 esp32s3.svd TIMG0.T0CONFIG
+///      Reads the T0CONFIG register of the TIMG0 peripheral
+///
+///      probe-rs peripheral --chip esp32s3 esp32s3.svd TIMG0
+///      Reads all registers of the TIMG0 peripheral
+///
+/// Registers are read with aligned 32-bit accesses, which all peripheral
+/// buses support. Registers whose read has side effects are not read.
+#[derive(clap::Parser)]
+#[clap(verbatim_doc_comment)]
+pub struct Cmd {
+    #[clap(flatten)]
+    shared: CoreOptions,
+
+    #[clap(flatten)]
+    probe_options: ProbeOptions,
+
+    /// The SVD file describing the peripherals of the chip
+    svd: PathBuf,
+
+    /// The peripheral, or one of its registers as PERIPHERAL.REGISTER
+    path: String,
+}
+
+impl Cmd {
+    pub fn run(self, lister: &Lister) -> anyhow::Result<()> {
+        let xml = std::fs::read_to_string(&self.svd)
+            .with_context(|| format!("Failed to read the SVD file {}", self.svd.display()))?;
+        let device =
+            svd_parser::parse_with_config(&xml, &Config::default().expand(true).ignore_enums(true))
+                .with_context(|| format!("Failed to parse the SVD file {}", self.svd.display()))?;
+
+        let (peripheral_name, register_name) = match self.path.split_once('.') {
+            Some((peripheral, register)) => (peripheral, Some(register)),
+            None => (self.path.as_str(), None),
+        };
+
+        let peripheral = device
+            .peripherals
+            .iter()
+            .find(|peripheral| peripheral.name.eq_ignore_ascii_case(peripheral_name))
+            .ok_or_else(|| anyhow!("The SVD file describes no peripheral {peripheral_name}"))?;
+
+        let registers = peripheral
+            .all_registers()
+            .filter(|register| {
+                register_name.map_or(true, |name| register.name.eq_ignore_ascii_case(name))
+            })
+            .collect::<Vec<_>>();
+        if registers.is_empty() {
+            return Err(match register_name {
+                Some(name) => anyhow!("The peripheral {} has no register {name}", peripheral.name),
+                None => anyhow!("The peripheral {} has no registers", peripheral.name),
+            });
+        }
+
+        let (mut session, _probe_options) = self.probe_options.simple_attach(lister)?;
+        let mut core = session.core(self.shared.core)?;
+
+        with_memory_access(&mut core, |core| {
+            for register in registers {
+                let address = peripheral.base_address + register.address_offset as u64;
+                let name = format!("{}.{}", peripheral.name, register.name);
+
+                if !is_readable(register) {
+                    println!("{name} @ {address:#010x}: not read, reading it has side effects");
+                    continue;
+                }
+
+                let width = register.properties.size.unwrap_or(32).min(32);
+                let value = read_peripheral_register(core, address, width)?;
+                println!(
+                    "{name} @ {address:#010x} = {value:#0digits$x}",
+                    digits = width as usize / 4 + 2
+                );
+
+                for field in register.fields() {
+                    let offset = field.bit_offset();
+                    let field_value = register_bits(value, offset, field.bit_width());
+                    println!(
+                        "    {} [{}..{}] = {field_value:#x}",
+                        field.name,
+                        offset,
+                        offset + field.bit_width()
+                    );
+                }
+            }
+
+            Ok::<_, anyhow::Error>(())
+        })
+    }
+}
+
+/// Returns whether `register` can be read without side effects, e.g. clearing its flags.
+fn is_readable(register: &Register) -> bool {
+    let write_only = matches!(
+        register.properties.access,
+        Some(Access::WriteOnly | Access::WriteOnce)
+    );
+
+    !write_only
+        && register.read_action.is_none()
+        && register.fields().all(|field| field.read_action.is_none())
+}
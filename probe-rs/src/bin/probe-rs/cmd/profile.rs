@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::ops::ControlFlow;
 use std::path::Path;
 use std::time::Duration;
 
@@ -60,6 +61,13 @@ pub enum ProfileMethod {
         /// The desired baud rate of the SWO output.
         baud: u32,
     },
+    /// Sample the PC without halting the core, where the architecture allows
+    #[clap(name = "pc-sampling")]
+    PcSampling {
+        /// The interval between samples in microseconds, 0 samples as fast as possible.
+        #[clap(long, default_value_t = 0)]
+        interval_us: u64,
+    },
 }
 
 impl core::fmt::Display for ProfileMethod {
@@ -113,6 +121,20 @@ impl ProfileCmd {
                     }
                 }
             }
+            ProfileMethod::PcSampling { interval_us } => {
+                let mut core = session.core(self.core)?;
+                info!("Attached to Core {}", self.core);
+
+                let interval = Duration::from_micros(interval_us);
+                reads = core.profile(interval, |pc| {
+                    *samples.entry(pc as u32).or_insert(1) += 1;
+                    if Instant::now() - start > duration {
+                        ControlFlow::Break(())
+                    } else {
+                        ControlFlow::Continue(())
+                    }
+                })?;
+            }
             ProfileMethod::Itm { clk, baud } => {
                 let sink = TraceSink::Swo(SwoConfig::new(clk).set_baud(baud));
                 session.setup_tracing(self.core, sink)?;
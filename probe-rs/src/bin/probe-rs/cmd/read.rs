@@ -1,6 +1,7 @@
 use probe_rs::{Lister, MemoryInterface};
 
 use crate::util::common_options::{ProbeOptions, ReadWriteBitWidth, ReadWriteOptions};
+use crate::util::with_memory_access;
 use crate::CoreOptions;
 
 /// Read from target memory address
@@ -14,7 +15,11 @@ use crate::CoreOptions;
 ///     00000000 00000000 (32-bit)
 ///     0000000000000000 0000000000000000 (64-bit)
 ///
-/// NOTE: Only supports RAM addresses
+/// With --partial, words that can't be read are shown as question marks
+/// instead of failing the whole read.
+///
+/// NOTE: Only supports RAM addresses. Memory outside of the target's memory map
+///       is only read with --force.
 #[derive(clap::Parser)]
 #[clap(verbatim_doc_comment)]
 pub struct Cmd {
@@ -29,6 +34,14 @@ pub struct Cmd {
 
     /// Number of words to read from the target
     words: u64,
+
+    /// Read the memory even if it is not part of the target's memory map
+    #[clap(long)]
+    force: bool,
+
+    /// Keep reading past words that can't be read, e.g. faulting peripheral registers
+    #[clap(long)]
+    partial: bool,
 }
 
 impl Cmd {
@@ -37,10 +50,40 @@ impl Cmd {
         let mut core = session.core(self.shared.core)?;
         let words = self.words as usize;
 
+        if !self.force {
+            let bytes = self.words * self.read_write_options.width as u64 / 8;
+            let address = self.read_write_options.address;
+            core.check_mapped(address..address + bytes)?;
+        }
+
+        if self.partial {
+            let width = self.read_write_options.width as usize / 8;
+            let mut data = vec![0; words * width];
+            let valid = with_memory_access(&mut core, |core| {
+                core.read_partial(self.read_write_options.address, &mut data, width)
+            })?;
+            let digits = width * 2;
+            for (word, valid) in data.chunks(width).zip(valid) {
+                if valid {
+                    let mut bytes = [0; 8];
+                    bytes[..width].copy_from_slice(word);
+                    let value = u64::from_le_bytes(bytes);
+                    print!("{value:0digits$x} ");
+                } else {
+                    print!("{} ", "?".repeat(digits));
+                }
+            }
+            println!();
+
+            return Ok(());
+        }
+
         match self.read_write_options.width {
             ReadWriteBitWidth::B8 => {
                 let mut values = vec![0; words];
-                core.read_8(self.read_write_options.address, &mut values)?;
+                with_memory_access(&mut core, |core| {
+                    core.read_8(self.read_write_options.address, &mut values)
+                })?;
                 for val in values {
                     print!("{:02x} ", val);
                 }
@@ -48,7 +91,9 @@ impl Cmd {
             }
             ReadWriteBitWidth::B32 => {
                 let mut values = vec![0; words];
-                core.read_32(self.read_write_options.address, &mut values)?;
+                with_memory_access(&mut core, |core| {
+                    core.read_32(self.read_write_options.address, &mut values)
+                })?;
                 for val in values {
                     print!("{:08x} ", val);
                 }
@@ -56,7 +101,9 @@ impl Cmd {
             }
             ReadWriteBitWidth::B64 => {
                 let mut values = vec![0; words];
-                core.read_64(self.read_write_options.address, &mut values)?;
+                with_memory_access(&mut core, |core| {
+                    core.read_64(self.read_write_options.address, &mut values)
+                })?;
                 for val in values {
                     print!("{:016x} ", val);
                 }
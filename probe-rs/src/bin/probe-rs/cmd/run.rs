@@ -1,6 +1,6 @@
 use std::io::Write;
 use std::ops::Range;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
@@ -8,8 +8,8 @@ use std::time::Duration;
 use anyhow::{anyhow, Result};
 use probe_rs::debug::{DebugInfo, DebugRegisters};
 use probe_rs::{
-    exception_handler_for_core, BreakpointCause, Core, CoreInterface, Error, HaltReason, Lister,
-    SemihostingCommand, VectorCatchCondition,
+    exception_handler_for_core, BreakpointCause, Core, CoreInterface, Error, ExceptionCause,
+    HaltReason, Lister, PanicInspector, ResetCatch, SemihostingCommand, VectorCatchCondition,
 };
 use probe_rs_target::MemoryRegion;
 use signal_hook::consts::signal;
@@ -18,10 +18,29 @@ use time::UtcOffset;
 use crate::util::common_options::{BinaryDownloadOptions, ProbeOptions};
 use crate::util::flash::{build_loader, run_flash_download};
 use crate::util::rtt::{self, RttConfig};
+use crate::util::{parse_u64, with_memory_access};
 use crate::FormatOptions;
 
 const RTT_RETRIES: usize = 10;
 
+/// The program on the target exited with a failure code through semihosting.
+///
+/// `probe-rs run` exits with this code, so tests running on the target can fail a CI job.
+#[derive(Debug, thiserror::Error)]
+#[error("Semihosting indicates exit with failure code: {0:#08x} ({0})")]
+pub(crate) struct TargetExitCode(pub u64);
+
+impl TargetExitCode {
+    /// The exit code of the process. Codes which don't fit into the exit status of all
+    /// platforms, or which would be truncated to zero, are reported as 1.
+    pub(crate) fn process_exit_code(&self) -> i32 {
+        match self.0 {
+            code @ 1..=255 => code as i32,
+            _ => 1,
+        }
+    }
+}
+
 #[derive(clap::Parser)]
 pub struct Cmd {
     #[clap(flatten)]
@@ -58,9 +77,19 @@ pub struct Cmd {
     #[arg(long)]
     pub catch_hardfault: bool,
 
+    /// Where the core stops after the reset that follows the download, before it is resumed:
+    /// `first-instruction`, `entry` for the entry point of the ELF file, or an address.
+    #[arg(long, value_parser = parse_reset_catch, default_value = "first-instruction")]
+    pub(crate) reset_catch: ResetCatchArg,
+
     /// Scan the memory to find the RTT control block
     #[clap(long)]
     pub(crate) rtt_scan_memory: bool,
+
+    /// Write the registers and RAM of the core to this ELF core file if it halts because of a
+    /// fault
+    #[clap(long)]
+    pub(crate) core_dump: Option<PathBuf>,
 }
 
 impl Cmd {
@@ -83,10 +112,21 @@ impl Cmd {
                 loader,
                 self.chip_erase,
             )?;
+            let catch = match self.reset_catch {
+                ResetCatchArg::FirstInstruction => ResetCatch::FirstInstruction,
+                ResetCatchArg::Entry => ResetCatch::Address(elf_entry_point(path)?),
+                ResetCatchArg::Address(address) => ResetCatch::Address(address),
+            };
+
             // reset the core to leave it in a consistent state after flashing
             session
                 .core(0)?
-                .reset_and_halt(Duration::from_millis(100))?;
+                .reset_and_halt_with_catch(Duration::from_millis(100), catch)?;
+        }
+
+        match PanicInspector::from_elf(&std::fs::read(path)?) {
+            Ok(inspector) => session.add_halt_inspector(inspector),
+            Err(error) => tracing::warn!("Panics will not be detected: {error}"),
         }
 
         let memory_map = session.target().memory_map.clone();
@@ -113,7 +153,7 @@ impl Cmd {
         }
         core.run()?;
 
-        run_loop(
+        let result = run_loop(
             &mut core,
             &memory_map,
             &rtt_scan_regions,
@@ -122,12 +162,45 @@ impl Cmd {
             self.always_print_stacktrace,
             self.no_location,
             self.log_format.as_deref(),
-        )?;
+            self.core_dump.as_deref(),
+        );
+        drop(core);
+
+        if result.is_err() {
+            for inspection in session.inspect_halt(0)? {
+                println!("{inspection}");
+            }
+        }
+
+        result
+    }
+}
+
+/// Where the core stops after the reset that follows the download.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ResetCatchArg {
+    FirstInstruction,
+    Entry,
+    Address(u64),
+}
 
-        Ok(())
+fn parse_reset_catch(input: &str) -> Result<ResetCatchArg> {
+    match input {
+        "first-instruction" => Ok(ResetCatchArg::FirstInstruction),
+        "entry" => Ok(ResetCatchArg::Entry),
+        address => parse_u64(address)
+            .map(ResetCatchArg::Address)
+            .map_err(|_| anyhow!("expected `first-instruction`, `entry` or an address")),
     }
 }
 
+/// Reads the entry point of the ELF file at `path`.
+fn elf_entry_point(path: &Path) -> Result<u64> {
+    let buffer = std::fs::read(path)?;
+    let binary = goblin::elf::Elf::parse(&buffer)?;
+    Ok(binary.entry)
+}
+
 /// Print all RTT messages and a stacktrace when the core stops due to an
 /// exception or when ctrl + c is pressed.
 ///
@@ -142,6 +215,7 @@ fn run_loop(
     always_print_stacktrace: bool,
     no_location: bool,
     log_format: Option<&str>,
+    core_dump: Option<&Path>,
 ) -> Result<(), anyhow::Error> {
     let mut rtt_config = rtt::RttConfig::default();
     rtt_config.channels.push(rtt::RttChannelConfig {
@@ -211,9 +285,15 @@ fn run_loop(
             )) => Ok(()),
             HaltReason::Breakpoint(BreakpointCause::Semihosting(
                 SemihostingCommand::ExitError { code },
-            )) => Err(anyhow!(
-                "Semihosting indicates exit with failure code: {code:#08x} ({code})"
-            )),
+            )) => Err(TargetExitCode(code).into()),
+            HaltReason::Exception(
+                cause @ (ExceptionCause::Fault { .. } | ExceptionCause::DoubleFault { .. }),
+            ) => {
+                if let Some(core_dump) = core_dump {
+                    store_core_dump(core, memory_map, core_dump)?;
+                }
+                Err(anyhow!("CPU halted due to an exception: {cause}"))
+            }
             _ => Err(anyhow!("CPU halted unexpectedly.")),
         },
     };
@@ -228,6 +308,22 @@ fn run_loop(
     result
 }
 
+/// Stores the registers and RAM of the halted core as an ELF core file at `path`.
+fn store_core_dump(core: &mut Core<'_>, memory_map: &[MemoryRegion], path: &Path) -> Result<()> {
+    let ram = memory_map
+        .iter()
+        .filter_map(|region| match region {
+            MemoryRegion::Ram(ram) => Some(ram.range.clone()),
+            _ => None,
+        })
+        .collect();
+
+    core.dump(ram)?.store_elf(path)?;
+    println!("Stored a core dump at {}", path.display());
+
+    Ok(())
+}
+
 /// Prints the stacktrace of the current execution state.
 fn print_stacktrace(core: &mut impl CoreInterface, path: &Path) -> Result<(), anyhow::Error> {
     let Some(debug_info) = DebugInfo::from_file(path).ok() else {
@@ -295,7 +391,7 @@ fn poll_rtt(
 ) -> Result<bool, anyhow::Error> {
     let mut had_data = false;
     if let Some(rtta) = rtta {
-        for (_ch, data) in rtta.poll_rtt_fallible(core)? {
+        for (_ch, data) in with_memory_access(core, |core| rtta.poll_rtt_fallible(core))? {
             if !data.is_empty() {
                 had_data = true;
             }
@@ -335,3 +431,15 @@ fn attach_to_rtt(
     log::error!("Failed to attach to RTT continuing...");
     None
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_exit_codes() {
+        assert_eq!(TargetExitCode(3).process_exit_code(), 3);
+        assert_eq!(TargetExitCode(256).process_exit_code(), 1);
+        assert_eq!(TargetExitCode(0x20023).process_exit_code(), 1);
+    }
+}
@@ -1,7 +1,7 @@
 use probe_rs::{Lister, MemoryInterface};
 
 use crate::util::common_options::{ProbeOptions, ReadWriteBitWidth, ReadWriteOptions};
-use crate::util::parse_u64;
+use crate::util::{parse_u64, with_memory_access};
 use crate::CoreOptions;
 
 /// Write to target memory address
@@ -46,7 +46,9 @@ impl Cmd {
                     }
                     bvalues.push(*val as u8);
                 }
-                core.write_8(self.read_write_options.address, &bvalues)?;
+                with_memory_access(&mut core, |core| {
+                    core.write_8(self.read_write_options.address, &bvalues)
+                })?;
             }
             ReadWriteBitWidth::B32 => {
                 let mut bvalues = Vec::new();
@@ -60,10 +62,14 @@ impl Cmd {
                     }
                     bvalues.push(*val as u32);
                 }
-                core.write_32(self.read_write_options.address, &bvalues)?;
+                with_memory_access(&mut core, |core| {
+                    core.write_32(self.read_write_options.address, &bvalues)
+                })?;
             }
             ReadWriteBitWidth::B64 => {
-                core.write_64(self.read_write_options.address, &self.values)?;
+                with_memory_access(&mut core, |core| {
+                    core.write_64(self.read_write_options.address, &self.values)
+                })?;
             }
         }
 
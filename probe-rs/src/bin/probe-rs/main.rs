@@ -77,6 +77,9 @@ enum Subcommand {
     /// Trace a memory location on the target
     #[clap(name = "trace")]
     Trace(cmd::trace::Cmd),
+    /// Log the accesses to a memory location, using a watchpoint
+    #[clap(name = "data-trace")]
+    DataTrace(cmd::data_trace::Cmd),
     /// Configure and monitor ITM trace packets from the target.
     #[clap(name = "itm")]
     Itm(cmd::itm::Cmd),
@@ -87,6 +90,8 @@ enum Subcommand {
     Profile(cmd::profile::ProfileCmd),
     Read(cmd::read::Cmd),
     Write(cmd::write::Cmd),
+    /// Read peripheral registers described by an SVD file
+    Peripheral(cmd::peripheral::Cmd),
 }
 
 /// Shared options for core selection, shared between commands
@@ -332,7 +337,7 @@ fn main() -> Result<()> {
         Subcommand::DapServer { .. } => unreachable!(), // handled above.
         Subcommand::List(cmd) => cmd.run(&lister),
         Subcommand::Info(cmd) => cmd.run(&lister),
-        Subcommand::Gdb(cmd) => cmd.run(&lister),
+        Subcommand::Gdb(cmd) => cmd.run(&lister, utc_offset),
         Subcommand::Reset(cmd) => cmd.run(&lister),
         Subcommand::Debug(cmd) => cmd.run(&lister),
         Subcommand::Download(cmd) => cmd.run(&lister),
@@ -340,17 +345,30 @@ fn main() -> Result<()> {
         Subcommand::Attach(cmd) => cmd.run(&lister, utc_offset),
         Subcommand::Erase(cmd) => cmd.run(&lister),
         Subcommand::Trace(cmd) => cmd.run(&lister),
+        Subcommand::DataTrace(cmd) => cmd.run(&lister),
         Subcommand::Itm(cmd) => cmd.run(&lister),
         Subcommand::Chip(cmd) => cmd.run(),
         Subcommand::Benchmark(cmd) => cmd.run(&lister),
         Subcommand::Profile(cmd) => cmd.run(&lister),
         Subcommand::Read(cmd) => cmd.run(&lister),
         Subcommand::Write(cmd) => cmd.run(&lister),
+        Subcommand::Peripheral(cmd) => cmd.run(&lister),
     };
 
     if let Some(ref log_path) = log_path {
         tracing::info!("Wrote log to {:?}", log_path);
     }
 
+    // Pass the exit code of the target on, e.g. to fail a CI job when tests on the target fail.
+    if let Some(exit_code) = result
+        .as_ref()
+        .err()
+        .and_then(|error| error.downcast_ref::<cmd::run::TargetExitCode>())
+    {
+        eprintln!("Error: {exit_code}");
+        drop(_append_guard);
+        std::process::exit(exit_code.process_exit_code());
+    }
+
     result
 }
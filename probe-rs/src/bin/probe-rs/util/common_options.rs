@@ -33,15 +33,15 @@
 //! ```
 use super::ArtifactError;
 
-use std::{fs::File, path::Path, path::PathBuf};
+use std::{fs::File, path::Path, path::PathBuf, time::Duration};
 
 use crate::util::parse_u64;
 use clap;
 use probe_rs::{
     config::{RegistryError, TargetSelector},
     flashing::{FileDownloadError, FlashError},
-    DebugProbeError, DebugProbeSelector, FakeProbe, Lister, Permissions, Probe, Session, Target,
-    WireProtocol,
+    AttachMethod, AttachOptions, DebugProbeError, DebugProbeSelector, FakeProbe, Lister,
+    Permissions, Probe, Session, Target, WakePolicy, WireProtocol,
 };
 use serde::{Deserialize, Serialize};
 
@@ -95,6 +95,9 @@ pub struct BinaryDownloadOptions {
     /// After flashing, read back all the flashed data to verify it has been written correctly.
     #[arg(long)]
     pub verify: bool,
+    /// Read back the flash first, and only erase and program the sectors that changed.
+    #[arg(long)]
+    pub incremental: bool,
 }
 
 /// Supported bit-widths for read/write commands (not every device may support each width).
@@ -120,6 +123,18 @@ pub struct ReadWriteOptions {
     pub address: u64,
 }
 
+/// What to do when the chip is asleep while attaching.
+#[derive(Debug, Default, Copy, Clone, clap::ValueEnum)]
+pub enum WakeMode {
+    /// Fail to attach.
+    #[default]
+    Fail,
+    /// Wait for the chip to wake up on its own.
+    Wait,
+    /// Wake the chip up with a power-on reset.
+    Force,
+}
+
 /// Common options and logic when interfacing with a [Probe].
 #[derive(clap::Parser, Debug)]
 pub struct ProbeOptions {
@@ -151,6 +166,29 @@ pub struct ProbeOptions {
     /// firmware, to be erased even when it has read-only protection.
     #[arg(long)]
     pub allow_erase_all: bool,
+    /// Use this flag to leave the watchdogs of the chip running while its cores are
+    /// halted. The chip may reset itself while it is being debugged.
+    #[arg(long)]
+    pub keep_watchdogs: bool,
+    /// Use this flag to only observe the chip: its cores are not halted or reset, and its
+    /// memory is not written.
+    #[arg(long)]
+    pub read_only: bool,
+    /// Use this flag to attach without halting the cores. Operations which need a halted core
+    /// fail until it is halted.
+    #[arg(long, conflicts_with = "connect_under_reset")]
+    pub non_intrusive: bool,
+    /// What to do when the chip is asleep while attaching: fail, wait for it to wake up, or
+    /// force it to wake up with a power-on reset.
+    #[arg(long, value_enum, ignore_case = true, default_value = "fail")]
+    pub wake: WakeMode,
+    /// How long to wait for the chip to wake up with '--wake wait', in milliseconds.
+    #[arg(long, default_value = "10000")]
+    pub wake_timeout: u64,
+    /// Use this flag to read back all data written to memory, and fail if it differs from
+    /// the written data.
+    #[arg(long)]
+    pub verify_writes: bool,
 }
 
 impl ProbeOptions {
@@ -301,20 +339,39 @@ impl LoadedProbeOptions {
         probe: Probe,
         target: TargetSelector,
     ) -> Result<Session, OperationError> {
-        let mut permissions = Permissions::new();
+        let mut permissions = if self.0.read_only {
+            Permissions::read_only()
+        } else {
+            Permissions::new()
+        };
         if self.0.allow_erase_all {
             permissions = permissions.allow_erase_all();
         }
 
-        let session = if self.0.connect_under_reset {
-            probe.attach_under_reset(target, permissions)
-        } else {
-            probe.attach(target, permissions)
+        let mut options = AttachOptions::new().wake_on_attach(match self.0.wake {
+            WakeMode::Fail => WakePolicy::Fail,
+            WakeMode::Wait => WakePolicy::Wait(Duration::from_millis(self.0.wake_timeout)),
+            WakeMode::Force => WakePolicy::Force,
+        });
+        if self.0.keep_watchdogs {
+            options = options.keep_watchdogs();
         }
-        .map_err(|error| OperationError::AttachingFailed {
-            source: error,
-            connect_under_reset: self.0.connect_under_reset,
-        })?;
+
+        let method = if self.0.connect_under_reset {
+            AttachMethod::UnderReset
+        } else if self.0.non_intrusive {
+            AttachMethod::NonIntrusive
+        } else {
+            AttachMethod::Normal
+        };
+
+        let mut session = probe
+            .attach_with_options(target, method, permissions, options)
+            .map_err(|error| OperationError::AttachingFailed {
+                source: error,
+                connect_under_reset: self.0.connect_under_reset,
+            })?;
+        session.set_write_verification(self.0.verify_writes);
 
         Ok(session)
     }
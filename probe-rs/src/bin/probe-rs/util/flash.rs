@@ -44,6 +44,7 @@ pub fn run_flash_download(
     options.do_chip_erase = do_chip_erase;
     options.disable_double_buffering = download_options.disable_double_buffering;
     options.verify = download_options.verify;
+    options.incremental = download_options.incremental;
 
     if !download_options.disable_progressbars {
         // Create progress bars.
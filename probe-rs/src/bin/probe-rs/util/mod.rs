@@ -6,13 +6,30 @@ pub mod rtt;
 use anyhow::Result;
 
 use cargo_metadata::Message;
+use probe_rs::Core;
 
 use std::process::{Command, Stdio};
 
 use std::path::PathBuf;
+use std::time::Duration;
 use std::{num::ParseIntError, path::Path};
 use thiserror::Error;
 
+/// Runs `op`, which accesses the memory of `core`.
+///
+/// Cores whose memory can only be accessed while halted are halted once for all accesses of `op`,
+/// instead of around every single one, and resumed afterwards if they were running.
+pub fn with_memory_access<'probe, R, E: From<probe_rs::Error>>(
+    core: &mut Core<'probe>,
+    op: impl FnOnce(&mut Core<'probe>) -> Result<R, E>,
+) -> Result<R, E> {
+    if core.requires_halted_memory_access() {
+        core.with_halted(Duration::from_millis(100), op)
+    } else {
+        op(core)
+    }
+}
+
 pub fn parse_u32(input: &str) -> Result<u32, ParseIntError> {
     parse_int::parse(input)
 }
@@ -45,7 +45,9 @@ pub fn attach_to_rtt(
         }
     }
 
-    match Rtt::attach_region(core, memory_map, &rtt_header_address) {
+    match super::with_memory_access(core, |core| {
+        Rtt::attach_region(core, memory_map, &rtt_header_address)
+    }) {
         Ok(rtt) => {
             log::info!("RTT initialized.");
             let app =
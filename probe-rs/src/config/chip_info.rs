@@ -6,12 +6,36 @@ use crate::architecture::arm::ArmChipInfo;
 /// For ARM-based chips, the function [ArmProbeInterface::read_from_rom_table] is
 /// used to read the information from the target.
 ///
+/// For other chips, the IDCODEs of the TAPs on the JTAG chain are used, see [JtagChipInfo].
+///
 /// [ArmProbeInterface::read_from_rom_table]: crate::architecture::arm::communication_interface::ArmProbeInterface::read_from_rom_table
 #[derive(Debug)]
 pub(crate) enum ChipInfo {
     /// ARM specific information for chip
     /// auto-detection. See [ArmChipInfo].
     Arm(ArmChipInfo),
+    /// Information read from the JTAG chain. See [JtagChipInfo].
+    Jtag(JtagChipInfo),
+}
+
+/// The IDCODEs found on the JTAG chain, and the values of the chip detect
+/// registers of all chips which match them.
+#[derive(Debug)]
+pub(crate) struct JtagChipInfo {
+    /// The IDCODEs of the TAPs, in scan chain order.
+    pub idcodes: Vec<u32>,
+    /// The address and value of each detect register which could be read.
+    pub registers: Vec<(u64, u32)>,
+}
+
+impl JtagChipInfo {
+    /// Returns the value read from the detect register at `address`, if any.
+    pub fn register(&self, address: u64) -> Option<u32> {
+        self.registers
+            .iter()
+            .find(|(register, _)| *register == address)
+            .map(|(_, value)| *value)
+    }
 }
 
 impl From<ArmChipInfo> for ChipInfo {
@@ -19,3 +43,9 @@ impl From<ArmChipInfo> for ChipInfo {
         ChipInfo::Arm(info)
     }
 }
+
+impl From<JtagChipInfo> for ChipInfo {
+    fn from(info: JtagChipInfo) -> Self {
+        ChipInfo::Jtag(info)
+    }
+}
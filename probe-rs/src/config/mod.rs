@@ -27,8 +27,8 @@ mod registry;
 mod target;
 
 pub use probe_rs_target::{
-    Chip, ChipFamily, Core, CoreType, FlashProperties, GenericRegion, InstructionSet, MemoryRange,
-    MemoryRegion, NvmRegion, PageInfo, RamRegion, RawFlashAlgorithm, ScanChainElement,
+    AccessWidth, Chip, ChipFamily, Core, CoreType, FlashProperties, GenericRegion, InstructionSet,
+    MemoryRange, MemoryRegion, NvmRegion, PageInfo, RamRegion, RawFlashAlgorithm, ScanChainElement,
     SectorDescription, SectorInfo, TargetDescriptionSource,
 };
 
@@ -39,6 +39,6 @@ pub use registry::{
 pub use target::{DebugSequence, Target, TargetParseError, TargetSelector};
 
 // Crate-internal API
-pub(crate) use chip_info::ChipInfo;
-pub(crate) use registry::get_target_by_chip_info;
+pub(crate) use chip_info::{ChipInfo, JtagChipInfo};
+pub(crate) use registry::{get_detect_registers_by_idcodes, get_target_by_chip_info};
 pub(crate) use target::CoreExt;
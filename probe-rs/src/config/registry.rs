@@ -3,7 +3,7 @@
 use super::{Chip, ChipFamily, ChipInfo, Core, Target, TargetDescriptionSource};
 use crate::config::CoreType;
 use once_cell::sync::Lazy;
-use probe_rs_target::{BinaryFormat, CoreAccessOptions, RiscvCoreAccessOptions};
+use probe_rs_target::{Architecture, BinaryFormat, CoreAccessOptions, RiscvCoreAccessOptions};
 use std::io::Read;
 use std::sync::{Arc, Mutex};
 
@@ -24,6 +24,10 @@ pub enum RegistryError {
     /// no matching chip was found in the registry.
     #[error("The connected chip could not automatically be determined.")]
     ChipAutodetectFailed,
+    /// When searching for a chip based on information read from the target,
+    /// several chips were found which match it.
+    #[error("The connected chip could be any of {}. Please select one of them.", .0.join(", "))]
+    ChipAutodetectAmbiguous(Vec<String>),
     /// A core type contained in a target description is not supported
     /// in probe-rs.
     #[error("The core type '{0}' is not supported in probe-rs.")]
@@ -40,6 +44,12 @@ pub enum RegistryError {
     /// One of the RTT scan ranges is not enclosed in exactly one RAM region.
     #[error("Chip's RTT scan region {:#010x}..{:#010x} is not enclosed by any single RAM region.", .0.start, .0.end)]
     InvalidRttScanRange(std::ops::Range<u64>),
+    /// The scratch region is not enclosed in exactly one RAM region.
+    #[error("Chip's scratch region {:#010x}..{:#010x} is not enclosed by any single RAM region.", .0.start, .0.end)]
+    InvalidScratchRegion(std::ops::Range<u64>),
+    /// A flash mapping is not translated into exactly one NVM region.
+    #[error("Chip's flash mapping {:#010x}..{:#010x} is not translated into any single NVM region.", .0.start, .0.end)]
+    InvalidFlashMapping(std::ops::Range<u64>),
 }
 
 fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
@@ -110,6 +120,9 @@ fn add_generic_targets(vec: &mut Vec<ChipFamily>) {
                 flash_algorithms: vec![],
                 rtt_scan_ranges: None,
                 scan_chain: Some(vec![]),
+                scratch_region: None,
+                flash_mappings: vec![],
+                detect_register: None,
                 default_binary_format: Some(BinaryFormat::Raw),
             }],
             flash_algorithms: vec![],
@@ -194,7 +207,8 @@ impl Registry {
                     }
                 }
             }
-            if partial_matches.len() > 1 {
+            // A chip whose name is a prefix of others, e.g. `esp32`, is selected by its full name.
+            if exact_matches == 0 && partial_matches.len() > 1 {
                 tracing::warn!(
                     "Ignoring ambiguous matches for specified chip name {}",
                     name,
@@ -325,11 +339,84 @@ impl Registry {
                         return Err(RegistryError::ChipAutodetectFailed);
                     }
                 }
+                ChipInfo::Jtag(chip_info) => {
+                    let mut identified_chips = self.get_chips_by_idcodes(&chip_info.idcodes);
+
+                    // Chips sharing their IDCODEs are told apart by their detect register. Chips
+                    // whose register could not be read are kept as candidates.
+                    identified_chips.retain(|(_, chip)| {
+                        let Some(register) = &chip.detect_register else {
+                            return true;
+                        };
+                        chip_info
+                            .register(register.address)
+                            .map_or(true, |value| register.values.contains(&value))
+                    });
+
+                    match identified_chips.len() {
+                        0 => {
+                            tracing::debug!("Found no chip matching {:x?}", chip_info);
+                            return Err(RegistryError::ChipAutodetectFailed);
+                        }
+                        1 => identified_chips.pop().unwrap(),
+                        _ => {
+                            return Err(RegistryError::ChipAutodetectAmbiguous(
+                                identified_chips
+                                    .iter()
+                                    .map(|(_, chip)| chip.name.clone())
+                                    .collect(),
+                            ));
+                        }
+                    }
+                }
             }
         };
         self.get_target(family, chip)
     }
 
+    /// Returns the chips whose scan chain specifies IDCODEs which match `idcodes`.
+    fn get_chips_by_idcodes(&self, idcodes: &[u32]) -> Vec<(&ChipFamily, &Chip)> {
+        let mut chips = Vec::new();
+
+        for family in &self.families {
+            for chip in family.variants() {
+                let Some(scan_chain) = &chip.scan_chain else {
+                    continue;
+                };
+                if scan_chain.len() != idcodes.len()
+                    || scan_chain.iter().all(|element| element.idcode.is_none())
+                {
+                    continue;
+                }
+
+                let matches = scan_chain.iter().zip(idcodes).all(|(element, idcode)| {
+                    element.idcode.map_or(true, |expected| expected == *idcode)
+                });
+                if matches {
+                    chips.push((family, chip));
+                }
+            }
+        }
+
+        chips
+    }
+
+    fn get_detect_registers_by_idcodes(&self, idcodes: &[u32]) -> Vec<(u64, Architecture)> {
+        let mut registers = Vec::new();
+
+        for (_, chip) in self.get_chips_by_idcodes(idcodes) {
+            let (Some(register), Some(core)) = (&chip.detect_register, chip.cores.first()) else {
+                continue;
+            };
+            let register = (register.address, core.core_type.architecture());
+            if !registers.contains(&register) {
+                registers.push(register);
+            }
+        }
+
+        registers
+    }
+
     fn get_target(&self, family: &ChipFamily, chip: &Chip) -> Result<Target, RegistryError> {
         // The validity of the given `ChipFamily` is checked in the constructor.
         Target::new(family, &chip.name)
@@ -390,6 +477,15 @@ pub(crate) fn get_target_by_chip_info(chip_info: ChipInfo) -> Result<Target, Reg
     REGISTRY.lock().unwrap().get_target_by_chip_info(chip_info)
 }
 
+/// Returns the detect registers of all chips matching the JTAG `idcodes`, together with the
+/// architecture of the chip they are read with.
+pub(crate) fn get_detect_registers_by_idcodes(idcodes: &[u32]) -> Vec<(u64, Architecture)> {
+    REGISTRY
+        .lock()
+        .unwrap()
+        .get_detect_registers_by_idcodes(idcodes)
+}
+
 /// Parse a target description and add the contained targets
 /// to the internal target registry.
 ///
@@ -444,6 +540,7 @@ fn match_name_prefix(pattern: &str, name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::JtagChipInfo;
     use probe_rs_target::get_ir_lengths;
     use std::fs::File;
     type TestResult = Result<(), RegistryError>;
@@ -492,6 +589,82 @@ mod tests {
         assert!(registry.get_target_by_name("nrf51822_Xxaa").is_ok());
     }
 
+    #[test]
+    fn try_fetch_prefix_of_others() {
+        let registry = Registry::from_builtin_families();
+        // ok: `esp32` is a prefix of `esp32s3`, but an exact match
+        assert_eq!(registry.get_target_by_name("esp32").unwrap().name, "esp32");
+        assert_eq!(
+            registry.get_target_by_name("esp32s3").unwrap().name,
+            "esp32s3"
+        );
+    }
+
+    #[test]
+    fn flash_mappings() {
+        let registry = Registry::from_builtin_families();
+        let target = registry.get_target_by_name("esp32s3").unwrap();
+
+        let flash_address = |address| {
+            target
+                .flash_mappings
+                .iter()
+                .find_map(|mapping| mapping.translate(address))
+        };
+        assert_eq!(flash_address(0x4201_0020), Some(0x1_0020));
+        assert_eq!(flash_address(0x3C02_0000), Some(0x2_0000));
+        assert_eq!(flash_address(0x3FC8_8000), None);
+
+        let mapping = &target.flash_mappings[0];
+        assert_eq!(mapping.untranslate(0x1_0020), Some(0x4201_0020));
+        assert_eq!(mapping.untranslate(0x200_0000), None);
+    }
+
+    #[test]
+    fn xtensa_flash_regions() {
+        let registry = Registry::from_builtin_families();
+        let target = registry.get_target_by_name("esp32s3").unwrap();
+
+        let regions = target.flash_regions();
+        let flash = regions
+            .iter()
+            .find(|info| info.region.range.start == 0)
+            .unwrap();
+        assert_eq!(flash.algorithm, None);
+        assert_eq!(flash.page_size, 0x100);
+        assert_eq!(flash.min_sector_size(), Some(0x1000));
+        assert!(flash.chip_erase);
+    }
+
+    #[test]
+    fn autodetect_by_idcodes() {
+        let registry = Registry::from_builtin_families();
+
+        let target = registry
+            .get_target_by_chip_info(ChipInfo::Jtag(JtagChipInfo {
+                idcodes: vec![0x0000_5c25],
+                registers: vec![],
+            }))
+            .unwrap();
+        assert_eq!(target.name, "esp32c3");
+
+        // The detect register rules out chips with the same IDCODEs.
+        assert!(matches!(
+            registry.get_target_by_chip_info(ChipInfo::Jtag(JtagChipInfo {
+                idcodes: vec![0x1200_34e5, 0x1200_34e5],
+                registers: vec![(0x4000_1000, 0x0000_0009)],
+            })),
+            Ok(target) if target.name == "esp32s3"
+        ));
+        assert!(matches!(
+            registry.get_target_by_chip_info(ChipInfo::Jtag(JtagChipInfo {
+                idcodes: vec![0x1200_34e5, 0x1200_34e5],
+                registers: vec![(0x4000_1000, 0x0000_0002)],
+            })),
+            Err(RegistryError::ChipAutodetectFailed)
+        ));
+    }
+
     #[test]
     fn validate_generic_targets() {
         let mut families = vec![];
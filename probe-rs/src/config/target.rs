@@ -24,13 +24,10 @@ use crate::architecture::{
         esp32c2::ESP32C2, esp32c3::ESP32C3, esp32c6::ESP32C6, esp32h2::ESP32H2,
         DefaultRiscvSequence, RiscvDebugSequence,
     },
-    xtensa::sequences::{
-        esp32::ESP32, esp32s2::ESP32S2, esp32s3::ESP32S3, DefaultXtensaSequence,
-        XtensaDebugSequence,
-    },
+    xtensa::sequences::{self as xtensa_sequences, DefaultXtensaSequence, XtensaDebugSequence},
 };
-use crate::flashing::FlashLoader;
-use probe_rs_target::{Architecture, BinaryFormat, ChipFamily, MemoryRange};
+use crate::flashing::{FlashLoader, FlashRegionInfo};
+use probe_rs_target::{AddressTranslation, Architecture, BinaryFormat, ChipFamily, MemoryRange};
 use std::sync::Arc;
 
 /// This describes a complete target with a fixed chip model and variant.
@@ -59,6 +56,14 @@ pub struct Target {
     /// manually in the target.yaml file. It is used by some probes to determine
     /// the number devices in the scan chain and their ir lengths.
     pub scan_chain: Option<Vec<ScanChainElement>>,
+    /// The RAM range which the debugger may use for its own code and data, if any.
+    ///
+    /// It is enclosed in exactly one RAM region from `memory_map`. Chunks of it are loaned out
+    /// with [`Session::borrow_scratch`](crate::Session::borrow_scratch).
+    pub scratch_region: Option<std::ops::Range<u64>>,
+    /// Address ranges through which the program reads the flash, with the flash addresses they
+    /// map to. Used to place ELF segments linked to these ranges in the flash.
+    pub flash_mappings: Vec<AddressTranslation>,
     /// The default executable format for the target.
     pub default_format: BinaryFormat,
 }
@@ -136,12 +141,8 @@ impl Target {
             || chip.name.starts_with("EFR32ZG2")
         {
             DebugSequence::Arm(EFM32xG2::create())
-        } else if chip.name.eq_ignore_ascii_case("esp32") {
-            DebugSequence::Xtensa(ESP32::create(chip))
-        } else if chip.name.eq_ignore_ascii_case("esp32s2") {
-            DebugSequence::Xtensa(ESP32S2::create(chip))
-        } else if chip.name.eq_ignore_ascii_case("esp32s3") {
-            DebugSequence::Xtensa(ESP32S3::create(chip))
+        } else if let Some(sequence) = xtensa_sequences::sequence_for_chip(chip) {
+            DebugSequence::Xtensa(sequence)
         } else if chip.name.eq_ignore_ascii_case("esp32c2") {
             DebugSequence::Riscv(ESP32C2::create(chip))
         } else if chip.name.eq_ignore_ascii_case("esp32c3") {
@@ -228,6 +229,28 @@ impl Target {
             }
         };
 
+        if let Some(scratch_region) = &chip.scratch_region {
+            let enclosed = chip.memory_map.iter().any(|region| match region {
+                MemoryRegion::Ram(region) => region.range.contains_range(scratch_region),
+                _ => false,
+            });
+            if !enclosed || scratch_region.is_empty() {
+                return Err(RegistryError::InvalidScratchRegion(scratch_region.clone()));
+            }
+        }
+
+        for mapping in &chip.flash_mappings {
+            let start = mapping.target;
+            let end = start + (mapping.range.end - mapping.range.start);
+            let enclosed = chip.memory_map.iter().any(|region| match region {
+                MemoryRegion::Nvm(region) => region.range.contains_range(&(start..end)),
+                _ => false,
+            });
+            if !enclosed || mapping.range.is_empty() {
+                return Err(RegistryError::InvalidFlashMapping(mapping.range.clone()));
+            }
+        }
+
         Ok(Target {
             name: chip.name.clone(),
             cores: chip.cores.clone(),
@@ -237,6 +260,8 @@ impl Target {
             debug_sequence,
             rtt_scan_regions,
             scan_chain: chip.scan_chain.clone(),
+            scratch_region: chip.scratch_region.clone(),
+            flash_mappings: chip.flash_mappings.clone(),
             default_format: chip.default_binary_format.clone().unwrap_or_default(),
         })
     }
@@ -275,6 +300,43 @@ impl Target {
     /// to program its non-volatile memory.
     pub fn flash_loader(&self) -> FlashLoader {
         FlashLoader::new(self.memory_map.clone(), self.source.clone())
+            .with_flash_mappings(self.flash_mappings.clone())
+    }
+
+    /// Describes the flash layout of each NVM region: the flash algorithm, the sectors, and the
+    /// page size.
+    ///
+    /// Regions which can't be programmed, because no flash algorithm fits them and the chip
+    /// can't be programmed otherwise, are left out.
+    pub fn flash_regions(&self) -> Vec<FlashRegionInfo> {
+        let sequence_erase = match &self.debug_sequence {
+            DebugSequence::Arm(sequence) => sequence.debug_erase_sequence().is_some(),
+            DebugSequence::Xtensa(sequence) => sequence.rom_flash_routines().is_some(),
+            DebugSequence::Riscv(_) => false,
+        };
+
+        self.memory_map
+            .iter()
+            .filter_map(|region| match region {
+                MemoryRegion::Nvm(region) => Some(region),
+                _ => None,
+            })
+            .filter_map(|region| {
+                match FlashLoader::get_flash_algorithm_for_region(region, self) {
+                    Ok(algorithm) => Some(FlashRegionInfo::from_algorithm(
+                        region,
+                        &algorithm.name,
+                        &algorithm.flash_properties,
+                        sequence_erase || algorithm.pc_erase_all.is_some(),
+                    )),
+                    // Xtensa chips without a flash algorithm are erased through their ROM.
+                    Err(_) if self.architecture() == Architecture::Xtensa && sequence_erase => {
+                        Some(FlashRegionInfo::from_rom(region))
+                    }
+                    Err(_) => None,
+                }
+            })
+            .collect()
     }
 
     /// Returns a [RawFlashAlgorithm] by name.
@@ -12,37 +12,73 @@ use crate::{
             sequences::ArmDebugSequence,
         },
         riscv::registers::RISCV_CORE_REGSISTERS,
-        xtensa::registers::XTENSA_CORE_REGSISTERS,
+        xtensa::{
+            registers::{XTENSA_CORE_REGSISTERS, XTENSA_WITH_FP_CORE_REGSISTERS},
+            sequences::XtensaDebugSequence,
+            XtensaState,
+        },
     },
     config::DebugSequence,
     debug::{DebugRegister, DebugRegisters},
-    error, CoreType, Error, InstructionSet, MemoryInterface, Target,
+    error,
+    session::MissingPermissions,
+    CoreType, Error, InstructionSet, MemoryAttributes, MemoryInterface, Permissions, Target,
+    TransferProgress,
 };
 use anyhow::anyhow;
 pub use probe_rs_target::{Architecture, CoreAccessOptions};
 use probe_rs_target::{
-    ArmCoreAccessOptions, MemoryRange, RiscvCoreAccessOptions, XtensaCoreAccessOptions,
+    ArmCoreAccessOptions, MemoryRange, MemoryRegion, RiscvCoreAccessOptions,
+    XtensaCoreAccessOptions,
 };
 use scroll::Pread;
 use std::{
+    borrow::Cow,
     collections::HashMap,
     fs::OpenOptions,
     mem::size_of_val,
-    ops::Range,
+    ops::{ControlFlow, Range},
     path::{Path, PathBuf},
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+mod breakpoint_units;
+pub mod checkpoint;
+pub mod core_events;
 pub mod core_state;
 pub mod core_status;
+pub mod disassembly;
+mod elf_dump;
+pub mod halt_inspector;
+pub mod heap;
+pub mod memory_hooks;
 pub mod memory_mapped_registers;
 pub mod registers;
-
+pub mod self_test;
+pub mod trace_watchpoints;
+pub mod triage;
+pub mod watches;
+
+pub(crate) use breakpoint_units::{free_breakpoint_unit, BreakpointUnits};
+pub use checkpoint::{Checkpoint, ExecutionHistory, ReverseStop};
+pub(crate) use core_events::CoreEvents;
+pub use core_events::{CoreEvent, CoreEventPoller, ResetCause};
 pub use core_state::*;
 pub use core_status::*;
+pub use disassembly::DisassembledInstruction;
+pub use halt_inspector::{HaltInspection, HaltInspector, PanicInspector, StackGuard};
+pub use heap::{AllocatorLayout, HeapStats, TargetHeap};
+pub(crate) use memory_hooks::{HookWord, MemoryHooks};
+pub use memory_hooks::{MemoryHook, RedactedRange, WriteProtectedRange};
 pub use memory_mapped_registers::MemoryMappedRegister;
 pub use registers::*;
+pub use self_test::{SelfTestCheck, SelfTestOutcome, SelfTestReport};
+pub(crate) use trace_watchpoints::TraceWatchpoints;
+pub use trace_watchpoints::{DataTraceRecord, TraceWatchpoint, TraceWatchpointId};
+pub use triage::{MemorySnapshot, TriageFrame, TriageRegister, TriageReport};
+pub(crate) use watches::Watches;
+pub use watches::{Watch, WatchEvent, WatchId, WatchPoller, WatchType, WatchValue};
 
 /// An struct for storing the current state of a core.
 #[derive(Debug, Clone)]
@@ -51,6 +87,215 @@ pub struct CoreInformation {
     pub pc: u64,
 }
 
+/// An operation which needs a halted core, and is therefore unavailable on running cores which
+/// are not halted implicitly, see [`Session::set_implicit_halts`](crate::Session::set_implicit_halts).
+///
+/// See [`Session::unavailable_until_halted`](crate::Session::unavailable_until_halted).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HaltingOperation {
+    /// Reading and writing core registers.
+    CoreRegisters,
+    /// Reading and writing memory, on cores which can only access it while halted.
+    Memory,
+    /// Setting and clearing hardware breakpoints, on cores which can only access them while
+    /// halted.
+    Breakpoints,
+    /// The parts of attaching that were deferred, like the connect sequence of the target.
+    DeferredAttach,
+}
+
+impl std::fmt::Display for HaltingOperation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            HaltingOperation::CoreRegisters => "Accessing core registers",
+            HaltingOperation::Memory => "Accessing memory",
+            HaltingOperation::Breakpoints => "Setting breakpoints",
+            HaltingOperation::DeferredAttach => "Completing the attach",
+        })
+    }
+}
+
+/// How [`Core::step_with_options`] steps through the code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepOptions {
+    mask_interrupts: bool,
+    step_over_breakpoints: bool,
+    count: u32,
+}
+
+impl Default for StepOptions {
+    fn default() -> Self {
+        Self {
+            mask_interrupts: true,
+            step_over_breakpoints: true,
+            count: 1,
+        }
+    }
+}
+
+impl StepOptions {
+    /// Selects whether interrupts are masked during each step, which is the default.
+    ///
+    /// If they are not masked and an interrupt is pending, a step lands on the first instruction
+    /// of the interrupt handler instead of the next instruction of the interrupted code.
+    pub fn with_masked_interrupts(mut self, mask_interrupts: bool) -> Self {
+        self.mask_interrupts = mask_interrupts;
+        self
+    }
+
+    /// Selects whether stepping continues over hardware breakpoints, which is the default.
+    ///
+    /// If not, stepping stops early when it reaches an instruction with a hardware breakpoint.
+    /// A breakpoint at the instruction the core is halted on is always stepped over.
+    pub fn with_breakpoints_stepped_over(mut self, step_over_breakpoints: bool) -> Self {
+        self.step_over_breakpoints = step_over_breakpoints;
+        self
+    }
+
+    /// Sets the number of instructions to step, at least one.
+    pub fn with_count(mut self, count: u32) -> Self {
+        self.count = count.max(1);
+        self
+    }
+
+    /// Whether interrupts are masked during each step.
+    pub fn mask_interrupts(&self) -> bool {
+        self.mask_interrupts
+    }
+
+    /// Whether stepping continues over hardware breakpoints.
+    pub fn step_over_breakpoints(&self) -> bool {
+        self.step_over_breakpoints
+    }
+
+    /// The number of instructions to step.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+}
+
+/// Steps `options.count()` instructions with `step`, stopping early at hardware breakpoints
+/// unless they are stepped over.
+pub(crate) fn step_repeatedly<C: CoreInterface + ?Sized>(
+    core: &mut C,
+    options: StepOptions,
+    mut step: impl FnMut(&mut C) -> Result<CoreInformation, error::Error>,
+) -> Result<CoreInformation, error::Error> {
+    let breakpoints = if options.step_over_breakpoints {
+        Vec::new()
+    } else {
+        core.hw_breakpoints()?.into_iter().flatten().collect()
+    };
+
+    let mut info = step(core)?;
+    for _ in 1..options.count {
+        if breakpoints.contains(&info.pc) {
+            break;
+        }
+        info = step(core)?;
+    }
+
+    Ok(info)
+}
+
+/// The registers needed to unwind the current stack frame of a core, as read by
+/// [`Core::read_frame_registers`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct FrameRegisters {
+    /// The program counter.
+    pub pc: u64,
+    /// The stack pointer.
+    pub sp: u64,
+    /// The frame pointer.
+    pub fp: u64,
+    /// The return address, a.k.a. link register.
+    pub ra: u64,
+    /// The architecture specific registers needed to unwind the frame, e.g. WINDOWBASE and
+    /// WINDOWSTART on Xtensa, in the order of [`CoreInterface::frame_registers`].
+    pub extra: Vec<(registers::RegisterId, RegisterValue)>,
+}
+
+/// What [`Core::run_with_timeout`] does with the core when it does not halt in time.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+pub enum TimeoutPolicy {
+    /// Leave the core running.
+    #[default]
+    KeepRunning,
+    /// Halt the core, so that its state can be inspected.
+    Halt,
+}
+
+/// How [`Core::write_core_reg`] handles writes to the registers that probe-rs relies on to
+/// control the core, like the instruction counter used for stepping on Xtensa.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum RegisterWritePolicy {
+    /// Write the register, and log a warning.
+    #[default]
+    Warn,
+    /// Refuse to write the register.
+    Deny,
+    /// Write the register.
+    Allow,
+}
+
+/// Where [`Core::reset_and_halt_with_catch`] halts the core after the reset.
+#[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
+pub enum ResetCatch {
+    /// Halt before the first instruction after the reset is executed.
+    #[default]
+    FirstInstruction,
+    /// Let the core run until it reaches `address`, e.g. the end of the ROM bootloader or the
+    /// entry point of the application.
+    Address(u64),
+}
+
+/// The kind of memory access that triggers a watchpoint.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum WatchpointKind {
+    /// Halt when the watched memory is read.
+    Read,
+    /// Halt when the watched memory is written.
+    Write,
+    /// Halt on any access to the watched memory.
+    ReadWrite,
+}
+
+/// A hardware watchpoint, halting the core when a range of memory is accessed.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Watchpoint {
+    /// The start address of the watched memory range.
+    pub address: u64,
+    /// The length of the watched memory range, in bytes.
+    pub length: u64,
+    /// The kind of access that triggers the watchpoint.
+    pub kind: WatchpointKind,
+}
+
+impl Watchpoint {
+    /// Checks that the watched range can be expressed as an address and a mask,
+    /// i.e. the length is a power of two no larger than `max_length` and the
+    /// address is aligned to the length.
+    pub(crate) fn check_mask_range(&self, max_length: u64) -> Result<(), Error> {
+        if !self.length.is_power_of_two() || self.length > max_length {
+            return Err(Error::Other(anyhow!(
+                "Unsupported watchpoint length {}, the length must be a power of two and at most {} bytes",
+                self.length,
+                max_length
+            )));
+        }
+
+        if self.address % self.length != 0 {
+            return Err(Error::Other(anyhow!(
+                "The watchpoint address {:#010x} is not aligned to its length {}",
+                self.address,
+                self.length
+            )));
+        }
+
+        Ok(())
+    }
+}
+
 /// A generic interface to control a MCU core.
 pub trait CoreInterface: MemoryInterface {
     /// Numerical ID of the core. Can be used as an argument to `Session::core()`.
@@ -74,6 +319,19 @@ pub trait CoreInterface: MemoryInterface {
     /// Continue to execute instructions.
     fn run(&mut self) -> Result<(), error::Error>;
 
+    /// Does the work needed before the halted core can continue to execute instructions, so
+    /// that [`CoreInterface::run_prepared`] can resume it with as little delay as possible.
+    ///
+    /// Running the core is equivalent to calling this, followed by `run_prepared`.
+    fn prepare_run(&mut self) -> Result<(), error::Error> {
+        Ok(())
+    }
+
+    /// Continue to execute instructions, after [`CoreInterface::prepare_run`].
+    fn run_prepared(&mut self) -> Result<(), error::Error> {
+        self.run()
+    }
+
     /// Reset the core, and then continue to execute instructions. If the core
     /// should be halted after reset, use the [`reset_and_halt`] function.
     ///
@@ -89,6 +347,19 @@ pub trait CoreInterface: MemoryInterface {
     /// Steps one instruction and then enters halted state again.
     fn step(&mut self) -> Result<CoreInformation, error::Error>;
 
+    /// Steps through the code as selected by `options`, and then enters halted state again.
+    ///
+    /// Cores which can't take interrupts during a step only support masked interrupts.
+    fn step_with_options(&mut self, options: StepOptions) -> Result<CoreInformation, error::Error> {
+        if !options.mask_interrupts() {
+            return Err(error::Error::NotImplemented(
+                "stepping with unmasked interrupts",
+            ));
+        }
+
+        step_repeatedly(self, options, |core| core.step())
+    }
+
     /// Read the value of a core register.
     fn read_core_reg(
         &mut self,
@@ -102,6 +373,121 @@ pub trait CoreInterface: MemoryInterface {
         value: registers::RegisterValue,
     ) -> Result<(), error::Error>;
 
+    /// Read the values of multiple core registers.
+    ///
+    /// Architectures can override this if they can read multiple registers more efficiently
+    /// than one by one.
+    fn read_core_regs(
+        &mut self,
+        addresses: &[registers::RegisterId],
+    ) -> Result<Vec<registers::RegisterValue>, error::Error> {
+        addresses
+            .iter()
+            .map(|address| self.read_core_reg(*address))
+            .collect()
+    }
+
+    /// Takes a snapshot of the registers of the halted core, to be restored with
+    /// [`restore_context`](CoreInterface::restore_context).
+    ///
+    /// The default implementation saves all core registers, and the floating point registers if
+    /// the core has an FPU. Architectures with state that is not visible through core registers
+    /// have to override this.
+    fn save_context(&mut self) -> Result<CoreContext, error::Error> {
+        let registers = self.registers();
+
+        let mut addresses = registers
+            .core_registers()
+            .map(|register| register.id())
+            .collect::<Vec<_>>();
+        if self.fpu_support()? {
+            let fpu_registers = registers.fpu_registers().into_iter().flatten();
+            addresses.extend(fpu_registers.chain(registers.fpsr()).map(|r| r.id()));
+        }
+
+        let values = self.read_core_regs(&addresses)?;
+        let registers = addresses.into_iter().zip(values).collect();
+
+        Ok(CoreContext::new(registers))
+    }
+
+    /// Restores the registers saved by [`save_context`](CoreInterface::save_context).
+    fn restore_context(&mut self, context: &CoreContext) -> Result<(), error::Error> {
+        for (address, value) in context.registers() {
+            self.write_core_reg(address, value)?;
+        }
+
+        Ok(())
+    }
+
+    /// Sets up the registers of the halted core to call the function at `address` with the
+    /// integer `arguments`, returning to `return_address`. Running the core is left to the
+    /// caller.
+    ///
+    /// The default implementation passes the arguments in the argument registers, and the return
+    /// address in the return address register, as the ARM and RISC-V calling conventions do.
+    fn prepare_function_call(
+        &mut self,
+        address: u64,
+        return_address: u64,
+        arguments: &[u64],
+    ) -> Result<(), error::Error> {
+        let registers = self.registers();
+
+        for (index, argument) in arguments.iter().enumerate() {
+            let Some(register) = registers.get_argument_register(index) else {
+                return Err(Error::GenericCoreError(format!(
+                    "Functions can be called with at most {index} arguments"
+                )));
+            };
+            self.write_core_reg(register.id(), RegisterValue::U64(*argument))?;
+        }
+
+        // Thumb code is returned to with the lowest bit of the address set.
+        let return_address = if self.instruction_set()? == InstructionSet::Thumb2 {
+            return_address | 1
+        } else {
+            return_address
+        };
+
+        self.write_core_reg(self.return_address().id(), return_address.into())?;
+        self.write_core_reg(self.program_counter().id(), address.into())
+    }
+
+    /// Reads the return value of a function called with
+    /// [`prepare_function_call`](CoreInterface::prepare_function_call), once it returned.
+    fn function_call_result(&mut self) -> Result<RegisterValue, error::Error> {
+        let register = self.registers().result_register(0);
+        self.read_core_reg(register.id())
+    }
+
+    /// Samples the program counter of the running core without halting it.
+    ///
+    /// Returns `None` if the architecture can't sample the PC of the core in its current state,
+    /// in which case the core is halted for the read. The default implementation always returns
+    /// `None`.
+    fn sample_pc(&mut self) -> Result<Option<u64>, error::Error> {
+        Ok(None)
+    }
+
+    /// Returns the registers that probe-rs relies on to control the core, e.g. to step it or to
+    /// set breakpoints. Writes to them through [`Core::write_core_reg`] are subject to the
+    /// [`RegisterWritePolicy`] of the core.
+    ///
+    /// The default implementation returns no registers.
+    fn run_control_registers(&self) -> &'static [registers::RegisterId] {
+        &[]
+    }
+
+    /// Returns the registers besides the program counter, stack pointer, frame pointer and
+    /// return address that are needed to unwind a stack frame of the core, see
+    /// [`Core::read_frame_registers`].
+    ///
+    /// The default implementation returns no registers.
+    fn frame_registers(&self) -> &'static [registers::RegisterId] {
+        &[]
+    }
+
     /// Returns all the available breakpoint units of the core.
     fn available_breakpoint_units(&mut self) -> Result<u32, error::Error>;
 
@@ -119,6 +505,126 @@ pub trait CoreInterface: MemoryInterface {
     /// Clears the breakpoint configured in unit `unit_index`.
     fn clear_hw_breakpoint(&mut self, unit_index: usize) -> Result<(), error::Error>;
 
+    /// Reprograms the configured breakpoints into the core, for cores whose breakpoint
+    /// registers are cleared by a reset.
+    ///
+    /// [`reset`](Self::reset) and [`reset_and_halt`](Self::reset_and_halt) already restore the
+    /// breakpoints. The default implementation does nothing, for cores that keep their
+    /// breakpoints across resets.
+    fn restore_breakpoints(&mut self) -> Result<(), error::Error> {
+        Ok(())
+    }
+
+    /// Returns what was reset along with the core, if the core was reset since the last call,
+    /// and clears the sticky reset flags of the core.
+    ///
+    /// Resets done through this interface are reported as well. The default implementation
+    /// never detects a reset.
+    fn take_reset_cause(&mut self) -> Result<Option<ResetCause>, error::Error> {
+        Ok(None)
+    }
+
+    /// Returns the number of hardware watchpoint units of the core.
+    fn available_watchpoint_units(&mut self) -> Result<u32, error::Error> {
+        Ok(0)
+    }
+
+    /// Read the configured hardware watchpoints.
+    /// A value of None in any position of the Vector indicates that the position is unset/available.
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, error::Error> {
+        Ok(vec![])
+    }
+
+    /// Sets a watchpoint using unit `unit_index`.
+    fn set_hw_watchpoint(
+        &mut self,
+        _unit_index: usize,
+        _watchpoint: Watchpoint,
+    ) -> Result<(), error::Error> {
+        Err(Error::NotImplemented("watchpoints"))
+    }
+
+    /// Clears the watchpoint configured in unit `unit_index`.
+    fn clear_hw_watchpoint(&mut self, _unit_index: usize) -> Result<(), error::Error> {
+        Err(Error::NotImplemented("watchpoints"))
+    }
+
+    /// Returns `true` if watchpoints and breakpoints are allocated from the same
+    /// hardware units, e.g. RISC-V triggers. A unit used by one of them is then
+    /// not available to the other.
+    fn watchpoints_share_breakpoint_units(&self) -> bool {
+        false
+    }
+
+    /// Runs the core until it reaches `address`, and returns with the core halted.
+    ///
+    /// A free breakpoint unit is claimed for `address` while the core runs. If none is free, the
+    /// core is single-stepped until it reaches `address` instead. The breakpoint configuration is
+    /// restored before returning.
+    ///
+    /// The core may halt before it reaches `address`, e.g. on another breakpoint. The returned
+    /// program counter tells where it halted. If the core does not halt within `timeout`, it is
+    /// halted and the timeout error is returned.
+    fn run_to(&mut self, address: u64, timeout: Duration) -> Result<CoreInformation, error::Error> {
+        let breakpoints = self.hw_breakpoints()?;
+
+        // An existing breakpoint at the address halts the core, and is kept afterwards.
+        let unit_index = if breakpoints.contains(&Some(address)) {
+            None
+        } else {
+            let watchpoints = if self.watchpoints_share_breakpoint_units() {
+                self.hw_watchpoints()?
+            } else {
+                vec![]
+            };
+
+            let Some(free_unit) = free_breakpoint_unit(&breakpoints, &watchpoints) else {
+                tracing::debug!(
+                    "No free breakpoint unit to run to {:#010x}, single-stepping instead",
+                    address
+                );
+
+                let start = Instant::now();
+                loop {
+                    let info = self.step()?;
+                    if info.pc == address {
+                        return Ok(info);
+                    }
+                    if start.elapsed() > timeout {
+                        return Err(Error::Timeout);
+                    }
+                }
+            };
+
+            Some(free_unit)
+        };
+
+        let breakpoints_enabled = self.hw_breakpoints_enabled();
+        if !breakpoints_enabled {
+            self.enable_breakpoints(true)?;
+        }
+        if let Some(unit_index) = unit_index {
+            self.set_hw_breakpoint(unit_index, address)?;
+        }
+
+        self.run()?;
+        let result = match self.wait_for_core_halted(timeout) {
+            Ok(()) => self
+                .read_core_reg(self.program_counter().id())
+                .and_then(|pc| Ok(CoreInformation { pc: pc.try_into()? })),
+            Err(error) => self.halt(timeout).and(Err(error)),
+        };
+
+        if let Some(unit_index) = unit_index {
+            self.clear_hw_breakpoint(unit_index)?;
+        }
+        if !breakpoints_enabled {
+            self.enable_breakpoints(false)?;
+        }
+
+        result
+    }
+
     /// Returns a list of all the registers of this core.
     fn registers(&self) -> &'static registers::CoreRegisters;
 
@@ -164,6 +670,46 @@ pub trait CoreInterface: MemoryInterface {
     /// decision for some core types.
     fn floating_point_register_count(&mut self) -> Result<usize, crate::error::Error>;
 
+    /// Returns how the memory at `address` is accessed, e.g. whether it is external flash mapped
+    /// through the cache.
+    ///
+    /// The default implementation reports plain memory.
+    fn memory_attributes(&mut self, _address: u64) -> Result<MemoryAttributes, Error> {
+        Ok(MemoryAttributes::default())
+    }
+
+    /// Selects whether reads of encrypted flash through the cache return decrypted data, or the
+    /// ciphertext stored in flash.
+    ///
+    /// The default implementation only supports decrypted reads.
+    fn set_flash_decryption(&mut self, enabled: bool) -> Result<(), Error> {
+        if enabled {
+            Ok(())
+        } else {
+            Err(Error::NotImplemented("reading encrypted flash contents"))
+        }
+    }
+
+    /// Decodes the instructions in `code`, which was read from `address`.
+    ///
+    /// A partial instruction at the end of `code` is not decoded. The default implementation
+    /// returns [`Error::NotImplemented`].
+    fn disassemble(
+        &mut self,
+        _address: u64,
+        _code: &[u8],
+    ) -> Result<Vec<DisassembledInstruction>, Error> {
+        Err(Error::NotImplemented("disassembly"))
+    }
+
+    /// Returns whether the memory of the core can only be accessed while it is halted.
+    ///
+    /// Such cores are halted and resumed around every memory access made while they run. The
+    /// default implementation returns `false`.
+    fn requires_halted_memory_access(&self) -> bool {
+        false
+    }
+
     /// Set the reset catch setting.
     ///
     /// This configures the core to halt after a reset.
@@ -179,7 +725,11 @@ pub trait CoreInterface: MemoryInterface {
     /// Called when we stop debugging a core.
     fn debug_core_stop(&mut self) -> Result<(), Error>;
 
-    /// Called during session stop to do any pending cleanup
+    /// Called during session stop to do any pending cleanup.
+    ///
+    /// Implementations restore the state the debugger changed in order to operate the core, like
+    /// clobbered registers and stepping configuration, without resuming the core. It is called
+    /// before [`debug_core_stop`](CoreInterface::debug_core_stop).
     fn on_session_stop(&mut self) -> Result<(), Error> {
         Ok(())
     }
@@ -275,7 +825,13 @@ impl CoreDump {
                 }
             }
             CoreType::Riscv => &RISCV_CORE_REGSISTERS,
-            CoreType::Xtensa => &XTENSA_CORE_REGSISTERS,
+            CoreType::Xtensa => {
+                if self.fpu_support {
+                    &XTENSA_WITH_FP_CORE_REGSISTERS
+                } else {
+                    &XTENSA_CORE_REGSISTERS
+                }
+            }
         };
 
         let mut debug_registers = Vec::<DebugRegister>::new();
@@ -444,6 +1000,12 @@ pub enum CoreDumpError {
     /// Decoding the coredump MessagePack failed.
     #[error("Decoding the coredump MessagePack failed.")]
     DecodingCoreDump(rmp_serde::decode::Error),
+    /// Writing the coredump as an ELF core file failed.
+    #[error("Writing the coredump as an ELF core file failed.")]
+    EncodingElf(std::io::Error),
+    /// The core can't be stored in an ELF core file.
+    #[error("Cores of type {0:?} can't be stored in an ELF core file.")]
+    UnsupportedElfCore(CoreType),
 }
 
 impl<'probe> MemoryInterface for Core<'probe> {
@@ -452,59 +1014,166 @@ impl<'probe> MemoryInterface for Core<'probe> {
     }
 
     fn read_word_64(&mut self, address: u64) -> Result<u64, Error> {
-        self.inner.read_word_64(address)
+        self.hook_before_read(address, 8)?;
+        let mut word = [self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.read_word_64(address)
+        })?];
+        self.hook_after_read(address, &mut word);
+        Ok(word[0])
     }
 
     fn read_word_32(&mut self, address: u64) -> Result<u32, Error> {
-        self.inner.read_word_32(address)
+        self.hook_before_read(address, 4)?;
+        let mut word = [self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.read_word_32(address)
+        })?];
+        self.hook_after_read(address, &mut word);
+        Ok(word[0])
     }
 
     fn read_word_8(&mut self, address: u64) -> Result<u8, Error> {
-        self.inner.read_word_8(address)
+        self.hook_before_read(address, 1)?;
+        let mut word = [self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.read_word_8(address)
+        })?];
+        self.hook_after_read(address, &mut word);
+        Ok(word[0])
     }
 
     fn read_64(&mut self, address: u64, data: &mut [u64]) -> Result<(), Error> {
-        self.inner.read_64(address, data)
+        self.hook_before_read(address, size_of_val(data))?;
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.read_64(address, data)
+        })?;
+        self.hook_after_read(address, data);
+        Ok(())
     }
 
     fn read_32(&mut self, address: u64, data: &mut [u32]) -> Result<(), Error> {
-        self.inner.read_32(address, data)
+        self.hook_before_read(address, size_of_val(data))?;
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.read_32(address, data)
+        })?;
+        self.hook_after_read(address, data);
+        Ok(())
     }
 
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
-        self.inner.read_8(address, data)
+        self.hook_before_read(address, data.len())?;
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.read_8(address, data)
+        })?;
+        self.hook_after_read(address, data);
+        Ok(())
     }
 
     fn read(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
-        self.inner.read(address, data)
+        self.hook_before_read(address, data.len())?;
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.read(address, data)
+        })?;
+        self.hook_after_read(address, data);
+        Ok(())
     }
 
     fn write_word_64(&mut self, addr: u64, data: u64) -> Result<(), Error> {
-        self.inner.write_word_64(addr, data)
+        self.check_memory_write()?;
+        let data = self.hook_before_write(addr, &[data])?[0];
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.write_word_64(addr, data)?;
+            core.verify_write(addr, &data.to_le_bytes())
+        })
     }
 
     fn write_word_32(&mut self, addr: u64, data: u32) -> Result<(), Error> {
-        self.inner.write_word_32(addr, data)
+        self.check_memory_write()?;
+        let data = self.hook_before_write(addr, &[data])?[0];
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.write_word_32(addr, data)?;
+            core.verify_write(addr, &data.to_le_bytes())
+        })
     }
 
     fn write_word_8(&mut self, addr: u64, data: u8) -> Result<(), Error> {
-        self.inner.write_word_8(addr, data)
+        self.check_memory_write()?;
+        let data = self.hook_before_write(addr, &[data])?[0];
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.write_word_8(addr, data)?;
+            core.verify_write(addr, &[data])
+        })
     }
 
     fn write_64(&mut self, addr: u64, data: &[u64]) -> Result<(), Error> {
-        self.inner.write_64(addr, data)
+        self.check_memory_write()?;
+        let data = self.hook_before_write(addr, data)?;
+        let bytes: Vec<u8> = data.iter().flat_map(|word| word.to_le_bytes()).collect();
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.write_64(addr, &data)?;
+            core.verify_write(addr, &bytes)
+        })
     }
 
     fn write_32(&mut self, addr: u64, data: &[u32]) -> Result<(), Error> {
-        self.inner.write_32(addr, data)
+        self.check_memory_write()?;
+        let data = self.hook_before_write(addr, data)?;
+        let bytes: Vec<u8> = data.iter().flat_map(|word| word.to_le_bytes()).collect();
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.write_32(addr, &data)?;
+            core.verify_write(addr, &bytes)
+        })
     }
 
     fn write_8(&mut self, addr: u64, data: &[u8]) -> Result<(), Error> {
-        self.inner.write_8(addr, data)
+        self.check_memory_write()?;
+        let data = self.hook_before_write(addr, data)?;
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.write_8(addr, &data)?;
+            core.verify_write(addr, &data)
+        })
     }
 
     fn write(&mut self, addr: u64, data: &[u8]) -> Result<(), Error> {
-        self.inner.write(addr, data)
+        self.check_memory_write()?;
+        let data = self.hook_before_write(addr, data)?;
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.write(addr, &data)?;
+            core.verify_write(addr, &data)
+        })
+    }
+
+    fn read_word_32_exact(&mut self, address: u64) -> Result<u32, Error> {
+        self.hook_before_read(address, 4)?;
+        let mut word = [self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.read_word_32_exact(address)
+        })?];
+        self.hook_after_read(address, &mut word);
+        Ok(word[0])
+    }
+
+    fn read_word_8_exact(&mut self, address: u64) -> Result<u8, Error> {
+        self.hook_before_read(address, 1)?;
+        let mut word = [self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.read_word_8_exact(address)
+        })?];
+        self.hook_after_read(address, &mut word);
+        Ok(word[0])
+    }
+
+    // Exact writes are not verified, as reading the register back may have side effects.
+    fn write_word_32_exact(&mut self, addr: u64, data: u32) -> Result<(), Error> {
+        self.check_memory_write()?;
+        let data = self.hook_before_write(addr, &[data])?[0];
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.write_word_32_exact(addr, data)
+        })
+    }
+
+    fn write_word_8_exact(&mut self, addr: u64, data: u8) -> Result<(), Error> {
+        self.check_memory_write()?;
+        let data = self.hook_before_write(addr, &[data])?[0];
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.write_word_8_exact(addr, data)
+        })
     }
 
     fn supports_8bit_transfers(&self) -> Result<bool, error::Error> {
@@ -514,6 +1183,12 @@ impl<'probe> MemoryInterface for Core<'probe> {
     fn flush(&mut self) -> Result<(), Error> {
         self.inner.flush()
     }
+
+    fn code_barrier(&mut self, range: Range<u64>) -> Result<(), Error> {
+        self.halted_if_required(HaltingOperation::Memory, |core| {
+            core.inner.code_barrier(range)
+        })
+    }
 }
 
 /// A struct containing key information about an exception.
@@ -613,6 +1288,9 @@ pub fn exception_handler_for_core(core_type: CoreType) -> Box<dyn ExceptionInter
     }
 }
 
+/// How long [`Core`] waits for a running core to halt, when it halts the core implicitly.
+const IMPLICIT_HALT_TIMEOUT: Duration = Duration::from_millis(100);
+
 /// Generic core handle representing a physical core on an MCU.
 ///
 /// This should be considered as a temporary view of the core which locks the debug probe driver to as single consumer by borrowing it.
@@ -621,6 +1299,14 @@ pub fn exception_handler_for_core(core_type: CoreType) -> Box<dyn ExceptionInter
 /// to allow potential other shareholders of the session struct to grab a core handle too.
 pub struct Core<'probe> {
     inner: Box<dyn CoreInterface + 'probe>,
+    events: Option<&'probe mut CoreEvents>,
+    register_cache: Option<&'probe mut RegisterCache>,
+    register_write_guard: Option<&'probe mut RegisterWriteGuard>,
+    memory_map: &'probe [MemoryRegion],
+    verify_writes: bool,
+    permissions: Option<&'probe Permissions>,
+    implicit_halts: bool,
+    memory_hooks: Option<&'probe MemoryHooks>,
 }
 
 impl<'probe> Core<'probe> {
@@ -633,82 +1319,323 @@ impl<'probe> Core<'probe> {
     pub(crate) fn new(core: impl CoreInterface + 'probe) -> Core<'probe> {
         Self {
             inner: Box::new(core),
+            events: None,
+            register_cache: None,
+            register_write_guard: None,
+            memory_map: &[],
+            verify_writes: false,
+            permissions: None,
+            implicit_halts: false,
+            memory_hooks: None,
         }
     }
 
-    /// Creates a new [`CoreState`]
-    pub(crate) fn create_state(
-        id: usize,
-        options: CoreAccessOptions,
-        target: &Target,
-        core_type: CoreType,
-    ) -> CombinedCoreState {
-        let specific_state = SpecificCoreState::from_core_type(core_type);
+    /// Reports state changes of this core to `events`.
+    pub(crate) fn with_events(mut self, events: &'probe mut CoreEvents) -> Self {
+        self.events = Some(events);
+        self
+    }
 
-        match options {
-            CoreAccessOptions::Arm(options) => {
-                let DebugSequence::Arm(sequence) = target.debug_sequence.clone() else {
-                    panic!(
-                        "Mismatch between sequence and core kind. This is a bug, please report it."
-                    );
-                };
+    /// Caches register values read with [`Core::read_core_reg_cached`] in `cache`.
+    pub(crate) fn with_register_cache(mut self, cache: &'probe mut RegisterCache) -> Self {
+        self.register_cache = Some(cache);
+        self
+    }
 
-                let core_state = CoreState::new(ResolvedCoreOptions::Arm { sequence, options });
+    /// Checks writes to the registers used for run control against `guard`.
+    pub(crate) fn with_register_write_guard(
+        mut self,
+        guard: &'probe mut RegisterWriteGuard,
+    ) -> Self {
+        self.register_write_guard = Some(guard);
+        self
+    }
 
-                CombinedCoreState {
-                    id,
-                    core_state,
-                    specific_state,
-                }
-            }
-            CoreAccessOptions::Riscv(options) => {
-                let core_state = CoreState::new(ResolvedCoreOptions::Riscv { options });
-                CombinedCoreState {
-                    id,
-                    core_state,
-                    specific_state,
-                }
-            }
-            CoreAccessOptions::Xtensa(options) => {
-                let core_state = CoreState::new(ResolvedCoreOptions::Xtensa { options });
-                CombinedCoreState {
-                    id,
-                    core_state,
-                    specific_state,
-                }
-            }
-        }
+    /// Checks memory accesses done with [`Core::read_mapped`] against `memory_map`.
+    pub(crate) fn with_memory_map(mut self, memory_map: &'probe [MemoryRegion]) -> Self {
+        self.memory_map = memory_map;
+        self
     }
 
-    /// Returns the ID of this core.
-    pub fn id(&self) -> usize {
-        self.inner.id()
+    /// Reads back the data written to memory if `enabled`, and reports differences as errors.
+    pub(crate) fn with_write_verification(mut self, enabled: bool) -> Self {
+        self.verify_writes = enabled;
+        self
     }
 
-    /// Wait until the core is halted. If the core does not halt on its own,
-    /// a [`DebugProbeError::Timeout`](crate::DebugProbeError::Timeout) error will be returned.
-    #[tracing::instrument(skip(self))]
-    pub fn wait_for_core_halted(&mut self, timeout: Duration) -> Result<(), error::Error> {
-        self.inner.wait_for_core_halted(timeout)
+    /// Rejects intrusive operations which are not allowed by `permissions`.
+    pub(crate) fn with_permissions(mut self, permissions: &'probe Permissions) -> Self {
+        self.permissions = Some(permissions);
+        self
     }
 
-    /// Check if the core is halted. If the core does not halt on its own,
-    /// a [`DebugProbeError::Timeout`](crate::DebugProbeError::Timeout) error will be returned.
-    pub fn core_halted(&mut self) -> Result<bool, error::Error> {
-        self.inner.core_halted()
+    /// Halts the core around accesses which need it, like memory accesses on Xtensa, if `enabled`
+    /// and the permissions allow halting. Otherwise such accesses fail with [`Error::NotHalted`]
+    /// while the core runs.
+    pub(crate) fn with_implicit_halts(mut self, enabled: bool) -> Self {
+        self.implicit_halts = enabled;
+        self
     }
 
-    /// Try to halt the core. This function ensures the core is actually halted, and
-    /// returns a [`DebugProbeError::Timeout`](crate::DebugProbeError::Timeout) otherwise.
-    #[tracing::instrument(skip(self))]
-    pub fn halt(&mut self, timeout: Duration) -> Result<CoreInformation, error::Error> {
-        self.inner.halt(timeout)
+    /// Passes memory accesses through `hooks`, which may veto or transform them.
+    pub(crate) fn with_memory_hooks(mut self, hooks: &'probe MemoryHooks) -> Self {
+        self.memory_hooks = Some(hooks);
+        self
     }
 
-    /// Continue to execute instructions.
-    #[tracing::instrument(skip(self))]
-    pub fn run(&mut self) -> Result<(), error::Error> {
-        self.inner.run()
+    fn hook_before_read(&self, address: u64, len: usize) -> Result<(), Error> {
+        match self.memory_hooks {
+            Some(hooks) => hooks.before_read(self.id(), address, len),
+            None => Ok(()),
+        }
+    }
+
+    fn hook_after_read<W: HookWord>(&self, address: u64, data: &mut [W]) {
+        if let Some(hooks) = self.memory_hooks {
+            hooks.after_read(self.id(), address, data);
+        }
+    }
+
+    /// Returns the data that is written instead of `data`.
+    fn hook_before_write<'data, W: HookWord>(
+        &self,
+        address: u64,
+        data: &'data [W],
+    ) -> Result<Cow<'data, [W]>, Error> {
+        match self.memory_hooks {
+            Some(hooks) => hooks.before_write(self.id(), address, data),
+            None => Ok(Cow::Borrowed(data)),
+        }
+    }
+
+    /// Checks that the memory hooks allow erasing `range` to `erased` bytes.
+    pub(crate) fn hook_before_erase(&self, range: Range<u64>, erased: u8) -> Result<(), Error> {
+        match self.memory_hooks {
+            Some(hooks) => hooks.before_erase(self.id(), range, erased),
+            None => Ok(()),
+        }
+    }
+
+    /// Runs `op`, which needs a halted core if the core can only access memory while halted.
+    ///
+    /// A running core is halted around `op` if implicit halts are enabled and the permissions
+    /// allow halting. Otherwise [`Error::NotHalted`] is returned without running `op`.
+    fn halted_if_required<R>(
+        &mut self,
+        operation: HaltingOperation,
+        op: impl FnOnce(&mut Self) -> Result<R, Error>,
+    ) -> Result<R, Error> {
+        if !self.inner.requires_halted_memory_access() || self.inner.core_halted()? {
+            return op(self);
+        }
+
+        let halt_permitted = self.check_permission(Permissions::halt).is_ok();
+        if !self.implicit_halts || !halt_permitted {
+            return Err(Error::NotHalted(operation));
+        }
+
+        self.with_halted(IMPLICIT_HALT_TIMEOUT, op)
+    }
+
+    /// Returns [`Error::MissingPermissions`] if the permissions don't allow `check`.
+    fn check_permission(
+        &self,
+        check: impl FnOnce(&Permissions) -> Result<(), MissingPermissions>,
+    ) -> Result<(), Error> {
+        match self.permissions {
+            Some(permissions) => Ok(check(permissions)?),
+            None => Ok(()),
+        }
+    }
+
+    fn check_memory_write(&self) -> Result<(), Error> {
+        self.check_permission(Permissions::write_memory)
+    }
+
+    /// Reads back `data` written to `address`, if write verification is enabled, and returns
+    /// the first difference as [`Error::WriteVerificationFailed`].
+    fn verify_write(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
+        if !self.verify_writes {
+            return Ok(());
+        }
+
+        let mut read_back = vec![0; data.len()];
+        self.inner.read(address, &mut read_back)?;
+
+        let mismatch = data
+            .iter()
+            .zip(&read_back)
+            .position(|(expected, actual)| expected != actual);
+        match mismatch {
+            Some(offset) => Err(Error::WriteVerificationFailed {
+                address: address + offset as u64,
+                expected: data[offset],
+                actual: read_back[offset],
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Reports the observed status of the core to the event subscribers.
+    fn observe_status(&mut self, status: CoreStatus) {
+        if !status.is_halted() {
+            self.invalidate_register_cache();
+        }
+
+        let id = self.id();
+        if let Some(events) = self.events.as_mut() {
+            events.observe_status(id, status);
+        }
+    }
+
+    /// Reports a reset of the core to the event subscribers.
+    fn observe_reset(&mut self) {
+        self.invalidate_register_cache();
+
+        // The reset was ours, so it must not be reported as unexpected later on.
+        if let Err(error) = self.inner.take_reset_cause() {
+            tracing::debug!("Failed to clear the reset flags: {error}");
+        }
+
+        let id = self.id();
+        if let Some(events) = self.events.as_mut() {
+            events.reset(id);
+        }
+    }
+
+    /// Creates a new [`CoreState`]
+    pub(crate) fn create_state(
+        id: usize,
+        options: CoreAccessOptions,
+        target: &Target,
+        core_type: CoreType,
+    ) -> CombinedCoreState {
+        match options {
+            CoreAccessOptions::Arm(options) => {
+                let DebugSequence::Arm(sequence) = target.debug_sequence.clone() else {
+                    panic!(
+                        "Mismatch between sequence and core kind. This is a bug, please report it."
+                    );
+                };
+
+                let core_state = CoreState::new(ResolvedCoreOptions::Arm { sequence, options });
+                let specific_state = SpecificCoreState::from_core_type(core_type);
+
+                CombinedCoreState {
+                    id,
+                    core_state,
+                    specific_state,
+                }
+            }
+            CoreAccessOptions::Riscv(options) => {
+                let core_state = CoreState::new(ResolvedCoreOptions::Riscv { options });
+                let specific_state = SpecificCoreState::from_core_type(core_type);
+                CombinedCoreState {
+                    id,
+                    core_state,
+                    specific_state,
+                }
+            }
+            CoreAccessOptions::Xtensa(options) => {
+                let DebugSequence::Xtensa(sequence) = target.debug_sequence.clone() else {
+                    panic!(
+                        "Mismatch between sequence and core kind. This is a bug, please report it."
+                    );
+                };
+
+                let specific_state = SpecificCoreState::Xtensa(XtensaState::new(
+                    options.nx,
+                    !options.no_code_density,
+                    !options.no_loops,
+                ));
+                let core_state = CoreState::new(ResolvedCoreOptions::Xtensa { sequence, options });
+                CombinedCoreState {
+                    id,
+                    core_state,
+                    specific_state,
+                }
+            }
+        }
+    }
+
+    /// Returns the ID of this core.
+    pub fn id(&self) -> usize {
+        self.inner.id()
+    }
+
+    /// Wait until the core is halted. If the core does not halt on its own,
+    /// a [`DebugProbeError::Timeout`](crate::DebugProbeError::Timeout) error will be returned.
+    #[tracing::instrument(skip(self))]
+    pub fn wait_for_core_halted(&mut self, timeout: Duration) -> Result<(), error::Error> {
+        self.inner.wait_for_core_halted(timeout)
+    }
+
+    /// Check if the core is halted. If the core does not halt on its own,
+    /// a [`DebugProbeError::Timeout`](crate::DebugProbeError::Timeout) error will be returned.
+    pub fn core_halted(&mut self) -> Result<bool, error::Error> {
+        self.inner.core_halted()
+    }
+
+    /// Try to halt the core. This function ensures the core is actually halted, and
+    /// returns a [`DebugProbeError::Timeout`](crate::DebugProbeError::Timeout) otherwise.
+    #[tracing::instrument(skip(self))]
+    pub fn halt(&mut self, timeout: Duration) -> Result<CoreInformation, error::Error> {
+        self.check_permission(Permissions::halt)?;
+        let info = self.inner.halt(timeout)?;
+        self.invalidate_register_cache();
+        self.observe_status(CoreStatus::Halted(HaltReason::Request));
+        Ok(info)
+    }
+
+    /// Continue to execute instructions.
+    #[tracing::instrument(skip(self))]
+    pub fn run(&mut self) -> Result<(), error::Error> {
+        self.inner.run()?;
+        self.observe_status(CoreStatus::Running);
+        Ok(())
+    }
+
+    /// Prepares the core to be resumed by [`Core::run_prepared`], e.g. to resume several cores
+    /// at once.
+    pub(crate) fn prepare_run(&mut self) -> Result<(), error::Error> {
+        self.inner.prepare_run()
+    }
+
+    /// Continue to execute instructions, after [`Core::prepare_run`].
+    pub(crate) fn run_prepared(&mut self) -> Result<(), error::Error> {
+        self.inner.run_prepared()?;
+        self.observe_status(CoreStatus::Running);
+        Ok(())
+    }
+
+    /// Continue to execute instructions, and wait until the core halts or locks up.
+    ///
+    /// Returns the status the core stopped in. If that does not happen within `timeout`,
+    /// [`Error::Timeout`] is returned, after applying `policy` to the core.
+    #[tracing::instrument(skip(self))]
+    pub fn run_with_timeout(
+        &mut self,
+        timeout: Duration,
+        policy: TimeoutPolicy,
+    ) -> Result<CoreStatus, error::Error> {
+        self.run()?;
+
+        let start = Instant::now();
+        loop {
+            let status = self.status()?;
+            if matches!(status, CoreStatus::Halted(_) | CoreStatus::LockedUp) {
+                return Ok(status);
+            }
+
+            if start.elapsed() > timeout {
+                if policy == TimeoutPolicy::Halt {
+                    self.halt(Duration::from_millis(100))?;
+                }
+                return Err(Error::Timeout);
+            }
+
+            std::thread::sleep(Duration::from_millis(1));
+        }
     }
 
     /// Reset the core, and then continue to execute instructions. If the core
@@ -717,7 +1644,11 @@ impl<'probe> Core<'probe> {
     /// [`reset_and_halt`]: Core::reset_and_halt
     #[tracing::instrument(skip(self))]
     pub fn reset(&mut self) -> Result<(), error::Error> {
-        self.inner.reset()
+        self.check_permission(Permissions::reset)?;
+        self.inner.reset()?;
+        self.observe_reset();
+        self.observe_status(CoreStatus::Running);
+        Ok(())
     }
 
     /// Reset the core, and then immediately halt. To continue execution after
@@ -726,19 +1657,104 @@ impl<'probe> Core<'probe> {
     /// [`reset`]: Core::reset
     #[tracing::instrument(skip(self))]
     pub fn reset_and_halt(&mut self, timeout: Duration) -> Result<CoreInformation, error::Error> {
-        self.inner.reset_and_halt(timeout)
+        self.check_permission(Permissions::reset)?;
+        self.check_permission(Permissions::halt)?;
+        let info = self.inner.reset_and_halt(timeout)?;
+        self.observe_reset();
+        self.observe_status(CoreStatus::Halted(HaltReason::Request));
+        Ok(info)
+    }
+
+    /// Reset the core, and then halt it at the point selected by `catch`.
+    ///
+    /// To reach a [`ResetCatch::Address`], the core is halted on its first instruction, and then
+    /// runs up to the address using a hardware breakpoint. This needs a free breakpoint unit, and
+    /// the core halts early if it hits another breakpoint on the way.
+    #[tracing::instrument(skip(self))]
+    pub fn reset_and_halt_with_catch(
+        &mut self,
+        timeout: Duration,
+        catch: ResetCatch,
+    ) -> Result<CoreInformation, error::Error> {
+        let info = self.reset_and_halt(timeout)?;
+
+        let ResetCatch::Address(address) = catch else {
+            return Ok(info);
+        };
+        if info.pc == address {
+            return Ok(info);
+        }
+
+        let breakpoint_was_set = self.inner.hw_breakpoints()?.contains(&Some(address));
+        self.set_hw_breakpoint(address)?;
+
+        let result = self.run_with_timeout(timeout, TimeoutPolicy::Halt);
+        let cleared = if breakpoint_was_set {
+            Ok(())
+        } else {
+            self.clear_hw_breakpoint(address)
+        };
+
+        // Report the error of running to the address, if there was one.
+        result?;
+        cleared?;
+
+        let pc = self.read_core_reg(self.program_counter().id())?;
+        Ok(CoreInformation { pc })
     }
 
     /// Steps one instruction and then enters halted state again.
     #[tracing::instrument(skip(self))]
     pub fn step(&mut self) -> Result<CoreInformation, error::Error> {
-        self.inner.step()
+        let info = self.inner.step()?;
+        self.invalidate_register_cache();
+        self.observe_status(CoreStatus::Halted(HaltReason::Step));
+        Ok(info)
+    }
+
+    /// Steps through the code as selected by `options`, and then enters halted state again.
+    ///
+    /// Unlike [`Core::step`], this can step several instructions, stop at breakpoints or let
+    /// interrupts be taken.
+    #[tracing::instrument(skip(self))]
+    pub fn step_with_options(
+        &mut self,
+        options: StepOptions,
+    ) -> Result<CoreInformation, error::Error> {
+        let info = self.inner.step_with_options(options)?;
+        self.invalidate_register_cache();
+        self.observe_status(CoreStatus::Halted(HaltReason::Step));
+        Ok(info)
     }
 
     /// Returns the current status of the core.
     #[tracing::instrument(skip(self))]
     pub fn status(&mut self) -> Result<CoreStatus, error::Error> {
-        self.inner.status()
+        let status = self.inner.status()?;
+        self.check_unexpected_reset()?;
+        self.observe_status(status);
+        Ok(status)
+    }
+
+    /// Checks whether the core was reset by something other than probe-rs, e.g. a watchdog,
+    /// reporting the reset to the event subscribers and reprogramming the breakpoints.
+    fn check_unexpected_reset(&mut self) -> Result<(), error::Error> {
+        let Some(cause) = self.inner.take_reset_cause()? else {
+            return Ok(());
+        };
+
+        let id = self.id();
+        tracing::warn!("Core {id} was reset unexpectedly ({cause:?})");
+        self.invalidate_register_cache();
+        if let Some(events) = self.events.as_mut() {
+            events.unexpected_reset(id, cause);
+        }
+
+        if let Err(error) = self.inner.restore_breakpoints() {
+            tracing::warn!("Failed to restore the breakpoints after the reset: {error}");
+        }
+
+        Ok(())
     }
 
     /// Read the value of a core register.
@@ -768,7 +1784,9 @@ impl<'probe> Core<'probe> {
 
         tracing::Span::current().record("address", format!("{address:?}"));
 
-        let value = self.inner.read_core_reg(address)?;
+        let value = self.halted_if_required(HaltingOperation::CoreRegisters, |core| {
+            core.inner.read_core_reg(address)
+        })?;
 
         value.try_into().into_crate_error()
     }
@@ -789,7 +1807,137 @@ impl<'probe> Core<'probe> {
     {
         let address = address.into();
 
-        self.inner.write_core_reg(address, value.into())
+        self.check_register_write(address)?;
+        self.halted_if_required(HaltingOperation::CoreRegisters, |core| {
+            // Writing a register may change the value of others, e.g. of banked registers.
+            core.invalidate_register_cache();
+
+            core.inner.write_core_reg(address, value.into())
+        })
+    }
+
+    /// Applies the [`RegisterWritePolicy`] to a write of the register at `address`.
+    fn check_register_write(&self, address: registers::RegisterId) -> Result<(), error::Error> {
+        if !self.inner.run_control_registers().contains(&address) {
+            return Ok(());
+        }
+
+        let policy = match self.register_write_guard.as_ref() {
+            Some(guard) => guard.policy(address),
+            None => RegisterWritePolicy::default(),
+        };
+
+        match policy {
+            RegisterWritePolicy::Allow => Ok(()),
+            RegisterWritePolicy::Warn => {
+                tracing::warn!(
+                    "Register {:#06x} is used by probe-rs to control the core, writing it may break stepping and breakpoints",
+                    address.0
+                );
+                Ok(())
+            }
+            RegisterWritePolicy::Deny => Err(Error::GenericCoreError(format!(
+                "Register {:#06x} is used by probe-rs to control the core, take ownership of it to write it",
+                address.0
+            ))),
+        }
+    }
+
+    /// Selects how [`Core::write_core_reg`] handles writes to the registers that probe-rs relies
+    /// on to control the core. The setting is kept for the lifetime of the session.
+    pub fn set_register_write_policy(&mut self, policy: RegisterWritePolicy) {
+        if let Some(guard) = self.register_write_guard.as_mut() {
+            guard.set_policy(policy);
+        }
+    }
+
+    /// Takes ownership of a register that probe-rs relies on to control the core, so that it is
+    /// written regardless of the [`RegisterWritePolicy`].
+    ///
+    /// The caller is then responsible for the register: probe-rs may still overwrite it, e.g.
+    /// when stepping, and its own run control may misbehave with the values written.
+    pub fn take_register_ownership(&mut self, address: impl Into<registers::RegisterId>) {
+        if let Some(guard) = self.register_write_guard.as_mut() {
+            guard.take_ownership(address.into());
+        }
+    }
+
+    /// Hands a register taken with [`Core::take_register_ownership`] back to probe-rs.
+    pub fn release_register_ownership(&mut self, address: impl Into<registers::RegisterId>) {
+        if let Some(guard) = self.register_write_guard.as_mut() {
+            guard.release_ownership(address.into());
+        }
+    }
+
+    /// Read the value of a core register, reusing the value read earlier since the core halted.
+    ///
+    /// The cached values are forgotten when the core is resumed, stepped, reset or a register is
+    /// written through probe-rs. If registers are changed in other ways, call
+    /// [`Core::invalidate_register_cache`] afterwards, or disable caching with
+    /// [`Core::set_register_caching`].
+    ///
+    /// See [`Core::read_core_reg`] for the supported types.
+    pub fn read_core_reg_cached<T>(
+        &mut self,
+        address: impl Into<registers::RegisterId>,
+    ) -> Result<T, error::Error>
+    where
+        registers::RegisterValue: TryInto<T>,
+        Result<T, <registers::RegisterValue as TryInto<T>>::Error>: RegisterValueResultExt<T>,
+    {
+        let address = address.into();
+
+        let cached = self
+            .register_cache
+            .as_ref()
+            .and_then(|cache| cache.get(address));
+
+        let value = match cached {
+            Some(value) => value,
+            None => {
+                let value = self.inner.read_core_reg(address)?;
+                if let Some(cache) = self.register_cache.as_mut() {
+                    cache.insert(address, value);
+                }
+                value
+            }
+        };
+
+        value.try_into().into_crate_error()
+    }
+
+    /// Takes a snapshot of the registers of the core, which must be halted.
+    ///
+    /// Running code on the target, like flash algorithms or functions called from the host,
+    /// clobbers the registers of the halted program. Save the context before, and restore it
+    /// with [`Core::restore_context`] afterwards, so that the program can continue as if it had
+    /// not been interrupted.
+    pub fn save_context(&mut self) -> Result<CoreContext, error::Error> {
+        self.inner.save_context()
+    }
+
+    /// Restores the registers saved by [`Core::save_context`]. The core must be halted.
+    pub fn restore_context(&mut self, context: &CoreContext) -> Result<(), error::Error> {
+        self.invalidate_register_cache();
+
+        self.inner.restore_context(context)
+    }
+
+    /// Forgets the register values cached by [`Core::read_core_reg_cached`].
+    pub fn invalidate_register_cache(&mut self) {
+        if let Some(cache) = self.register_cache.as_mut() {
+            cache.invalidate();
+        }
+    }
+
+    /// Enables or disables caching of register values by [`Core::read_core_reg_cached`].
+    ///
+    /// Caching is enabled by default. Disable it if registers are modified without going through
+    /// probe-rs, e.g. by another debugger. The setting is kept for the lifetime of the session.
+    pub fn set_register_caching(&mut self, enabled: bool) {
+        if let Some(cache) = self.register_cache.as_mut() {
+            cache.set_enabled(enabled);
+        }
     }
 
     /// Returns all the available breakpoint units of the core.
@@ -813,6 +1961,30 @@ impl<'probe> Core<'probe> {
         self.inner.registers()
     }
 
+    /// Reads the register named `name`, which can be any name accepted by
+    /// [`CoreRegisters::by_name`](registers::CoreRegisters::by_name), like `pc` or `a0`.
+    pub fn read_register_by_name(&mut self, name: &str) -> Result<RegisterValue, error::Error> {
+        let register = self.register_by_name(name)?;
+        self.read_core_reg(register)
+    }
+
+    /// Writes `value` to the register named `name`, see [`Core::read_register_by_name`].
+    pub fn write_register_by_name(
+        &mut self,
+        name: &str,
+        value: impl Into<RegisterValue>,
+    ) -> Result<(), error::Error> {
+        let register = self.register_by_name(name)?;
+        self.write_core_reg(register, value)
+    }
+
+    /// Returns the register named `name`, see [`Core::read_register_by_name`].
+    pub fn register_by_name(&self, name: &str) -> Result<&'static CoreRegister, error::Error> {
+        self.registers()
+            .by_name(name)
+            .ok_or_else(|| error::Error::Register(format!("Unknown register `{name}`")))
+    }
+
     /// Returns the program counter register.
     pub fn program_counter(&self) -> &'static CoreRegister {
         self.inner.program_counter()
@@ -833,19 +2005,56 @@ impl<'probe> Core<'probe> {
         self.inner.return_address()
     }
 
+    /// Reads the registers needed to unwind the current stack frame.
+    ///
+    /// The registers are read with a single [`CoreInterface::read_core_regs`] call, which
+    /// architectures with a batched transport complete in far fewer round trips than reading
+    /// the registers one by one.
+    pub fn read_frame_registers(&mut self) -> Result<FrameRegisters, error::Error> {
+        let extra = self.inner.frame_registers();
+
+        let mut ids = vec![
+            self.program_counter().id(),
+            self.stack_pointer().id(),
+            self.frame_pointer().id(),
+            self.return_address().id(),
+        ];
+        ids.extend_from_slice(extra);
+
+        let values = self.inner.read_core_regs(&ids)?;
+        if values.len() != ids.len() {
+            return Err(Error::Register(format!(
+                "Read {} registers instead of {}",
+                values.len(),
+                ids.len()
+            )));
+        }
+
+        Ok(FrameRegisters {
+            pc: values[0].try_into()?,
+            sp: values[1].try_into()?,
+            fp: values[2].try_into()?,
+            ra: values[3].try_into()?,
+            extra: extra
+                .iter()
+                .copied()
+                .zip(values[4..].iter().copied())
+                .collect(),
+        })
+    }
+
     /// Find the index of the next available HW breakpoint comparator.
     fn find_free_breakpoint_comparator_index(&mut self) -> Result<usize, error::Error> {
-        let mut next_available_hw_breakpoint = 0;
-        for breakpoint in self.inner.hw_breakpoints()? {
-            if breakpoint.is_none() {
-                return Ok(next_available_hw_breakpoint);
-            } else {
-                next_available_hw_breakpoint += 1;
-            }
-        }
-        Err(error::Error::Other(anyhow!(
-            "No available hardware breakpoints"
-        )))
+        let watchpoints = if self.inner.watchpoints_share_breakpoint_units() {
+            self.inner.hw_watchpoints()?
+        } else {
+            vec![]
+        };
+
+        let breakpoints = self.inner.hw_breakpoints()?;
+
+        free_breakpoint_unit(&breakpoints, &watchpoints)
+            .ok_or_else(|| error::Error::Other(anyhow!("No available hardware breakpoints")))
     }
 
     /// Set a hardware breakpoint
@@ -856,6 +2065,13 @@ impl<'probe> Core<'probe> {
     /// and can be queried using the `get_available_breakpoint_units` function.
     #[tracing::instrument(skip(self))]
     pub fn set_hw_breakpoint(&mut self, address: u64) -> Result<(), error::Error> {
+        self.check_permission(Permissions::halt)?;
+        self.halted_if_required(HaltingOperation::Breakpoints, |core| {
+            core.set_hw_breakpoint_impl(address)
+        })
+    }
+
+    fn set_hw_breakpoint_impl(&mut self, address: u64) -> Result<(), error::Error> {
         if !self.inner.hw_breakpoints_enabled() {
             self.enable_breakpoints(true)?;
         }
@@ -888,6 +2104,12 @@ impl<'probe> Core<'probe> {
     /// This function will try to clear a hardware breakpoint at `address` if there exists a breakpoint at that address.
     #[tracing::instrument(skip(self))]
     pub fn clear_hw_breakpoint(&mut self, address: u64) -> Result<(), error::Error> {
+        self.halted_if_required(HaltingOperation::Breakpoints, |core| {
+            core.clear_hw_breakpoint_impl(address)
+        })
+    }
+
+    fn clear_hw_breakpoint_impl(&mut self, address: u64) -> Result<(), error::Error> {
         let bp_position = self
             .inner
             .hw_breakpoints()?
@@ -925,6 +2147,252 @@ impl<'probe> Core<'probe> {
         Ok(())
     }
 
+    /// Reprograms the configured breakpoints into the core after a reset that was not done
+    /// through this [`Core`], e.g. by a debug sequence or the reset pin.
+    #[tracing::instrument(skip(self))]
+    pub fn restore_breakpoints(&mut self) -> Result<(), error::Error> {
+        self.inner.restore_breakpoints()
+    }
+
+    /// Runs the core until it reaches `address`, and returns with the core halted.
+    ///
+    /// See [`CoreInterface::run_to`] for details.
+    #[tracing::instrument(skip(self))]
+    pub fn run_to(&mut self, address: u64, timeout: Duration) -> Result<CoreInformation, Error> {
+        let result = self.inner.run_to(address, timeout);
+        self.invalidate_register_cache();
+        self.status()?;
+        result
+    }
+
+    /// Calls the function at `address` on the halted core with the integer `arguments`, and
+    /// returns its return value.
+    ///
+    /// Only arguments passed in registers are supported. The function returns to the address
+    /// the core is halted at, where it is caught with a breakpoint. The registers of the core are
+    /// restored afterwards, also if the function does not return within `timeout`.
+    #[tracing::instrument(skip(self))]
+    pub fn call_function(
+        &mut self,
+        address: u64,
+        arguments: &[u64],
+        timeout: Duration,
+    ) -> Result<u64, Error> {
+        let context = self.save_context()?;
+
+        let result = self.call_function_impl(address, arguments, timeout);
+        let restored = self.restore_context(&context);
+
+        // Report the error of the call itself, if there was one.
+        let value = result?;
+        restored?;
+        Ok(value)
+    }
+
+    /// Sets up the registers of the halted core to call the function at `address` with the
+    /// integer `arguments`, returning to `return_address`, following the calling convention of
+    /// the core. Running the core is left to the caller.
+    pub(crate) fn prepare_function_call(
+        &mut self,
+        address: u64,
+        return_address: u64,
+        arguments: &[u64],
+    ) -> Result<(), Error> {
+        let result = self
+            .inner
+            .prepare_function_call(address, return_address, arguments);
+        self.invalidate_register_cache();
+        result
+    }
+
+    /// Reads the return value of a function called with [`Core::prepare_function_call`], once it
+    /// returned.
+    pub(crate) fn function_call_result(&mut self) -> Result<u64, Error> {
+        self.inner.function_call_result()?.try_into()
+    }
+
+    fn call_function_impl(
+        &mut self,
+        address: u64,
+        arguments: &[u64],
+        timeout: Duration,
+    ) -> Result<u64, Error> {
+        let return_address = self.read_core_reg(self.program_counter().id())?;
+
+        self.prepare_function_call(address, return_address, arguments)?;
+
+        let info = self.run_to(return_address, timeout)?;
+        if info.pc != return_address {
+            return Err(Error::GenericCoreError(format!(
+                "The function at {address:#010x} did not return, the core halted at {:#010x}",
+                info.pc
+            )));
+        }
+
+        self.function_call_result()
+    }
+
+    /// Samples the program counter of the core.
+    ///
+    /// Running cores are sampled without halting them where the architecture allows, e.g.
+    /// through DEBUGPC on Xtensa. Other cores are halted for the duration of the read.
+    pub fn sample_pc(&mut self) -> Result<u64, Error> {
+        if let Some(pc) = self.inner.sample_pc()? {
+            return Ok(pc);
+        }
+
+        let pc = self.program_counter().id();
+        if self.core_halted()? {
+            return self.read_core_reg(pc);
+        }
+
+        self.halt(Duration::from_millis(10))?;
+        let sample = self.read_core_reg(pc);
+        let resumed = self.run();
+
+        let sample = sample?;
+        resumed?;
+        Ok(sample)
+    }
+
+    /// Profiles the firmware by sampling the program counter every `interval`, and passing the
+    /// samples to `consumer` until it returns [`ControlFlow::Break`].
+    ///
+    /// A zero `interval` samples as fast as the probe allows. Returns the number of samples
+    /// taken. See [`Core::sample_pc`] for how the samples are taken.
+    pub fn profile(
+        &mut self,
+        interval: Duration,
+        mut consumer: impl FnMut(u64) -> ControlFlow<()>,
+    ) -> Result<u64, Error> {
+        let mut samples = 0;
+        loop {
+            let started = Instant::now();
+
+            let pc = self.sample_pc()?;
+            samples += 1;
+            if consumer(pc).is_break() {
+                return Ok(samples);
+            }
+
+            if let Some(remaining) = interval.checked_sub(started.elapsed()) {
+                std::thread::sleep(remaining);
+            }
+        }
+    }
+
+    /// Returns the number of hardware watchpoint units of the core.
+    pub fn available_watchpoint_units(&mut self) -> Result<u32, error::Error> {
+        self.inner.available_watchpoint_units()
+    }
+
+    /// Returns the hardware watchpoints of the core, indexed by watchpoint unit.
+    ///
+    /// A value of `None` indicates that the unit is unset/available.
+    pub fn hw_watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, error::Error> {
+        self.inner.hw_watchpoints()
+    }
+
+    /// Find the index of the next available HW watchpoint unit.
+    fn find_free_watchpoint_unit_index(&mut self) -> Result<usize, error::Error> {
+        let breakpoints = if self.inner.watchpoints_share_breakpoint_units() {
+            self.inner.hw_breakpoints()?
+        } else {
+            vec![]
+        };
+
+        for (unit_index, watchpoint) in self.inner.hw_watchpoints()?.into_iter().enumerate() {
+            let used_by_breakpoint = matches!(breakpoints.get(unit_index), Some(Some(_)));
+            if watchpoint.is_none() && !used_by_breakpoint {
+                return Ok(unit_index);
+            }
+        }
+
+        Err(error::Error::Other(anyhow!(
+            "No available hardware watchpoints"
+        )))
+    }
+
+    /// Set a hardware watchpoint
+    ///
+    /// This function will try to set a hardware watchpoint on the `length` bytes at `address`,
+    /// halting the core when they are accessed in the way described by `kind`. An existing
+    /// watchpoint at `address` is replaced.
+    ///
+    /// The amount of hardware watchpoints which are supported is chip specific,
+    /// and can be queried using the `available_watchpoint_units` function. Most
+    /// architectures also require `length` to be a power of two, with `address` aligned to it.
+    #[tracing::instrument(skip(self))]
+    pub fn set_hw_watchpoint(
+        &mut self,
+        address: u64,
+        length: u64,
+        kind: WatchpointKind,
+    ) -> Result<(), error::Error> {
+        self.check_permission(Permissions::halt)?;
+
+        let unit_index = match self
+            .inner
+            .hw_watchpoints()?
+            .iter()
+            .position(|wp| matches!(wp, Some(wp) if wp.address == address))
+        {
+            Some(unit_index) => unit_index,
+            None => self.find_free_watchpoint_unit_index()?,
+        };
+
+        tracing::debug!(
+            "Trying to set HW watchpoint #{} at address {:#08x} ({} bytes, {:?})",
+            unit_index,
+            address,
+            length,
+            kind
+        );
+
+        self.inner.set_hw_watchpoint(
+            unit_index,
+            Watchpoint {
+                address,
+                length,
+                kind,
+            },
+        )
+    }
+
+    /// Clear a hardware watchpoint
+    ///
+    /// This function will try to clear the hardware watchpoint at `address` if there exists a watchpoint at that address.
+    #[tracing::instrument(skip(self))]
+    pub fn clear_hw_watchpoint(&mut self, address: u64) -> Result<(), error::Error> {
+        let unit_index = self
+            .inner
+            .hw_watchpoints()?
+            .iter()
+            .position(|wp| matches!(wp, Some(wp) if wp.address == address));
+
+        match unit_index {
+            Some(unit_index) => self.inner.clear_hw_watchpoint(unit_index),
+            None => Err(error::Error::Other(anyhow!(
+                "No watchpoint found at address {:#010x}",
+                address
+            ))),
+        }
+    }
+
+    /// Clear all hardware watchpoints
+    ///
+    /// This function will clear all HW watchpoints which are configured on the target,
+    /// regardless if they are set by probe-rs.
+    #[tracing::instrument(skip(self))]
+    pub fn clear_all_hw_watchpoints(&mut self) -> Result<(), error::Error> {
+        for (unit_index, watchpoint) in self.inner.hw_watchpoints()?.into_iter().enumerate() {
+            if watchpoint.is_some() {
+                self.inner.clear_hw_watchpoint(unit_index)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Returns the architecture of the core.
     pub fn architecture(&self) -> Architecture {
         self.inner.architecture()
@@ -955,6 +2423,121 @@ impl<'probe> Core<'probe> {
         self.inner.floating_point_register_count()
     }
 
+    /// Returns whether the memory of the core can only be accessed while it is halted.
+    ///
+    /// Use [`Core::with_halted`] to halt such a core once for a group of accesses, instead of
+    /// around every single one.
+    pub fn requires_halted_memory_access(&self) -> bool {
+        self.inner.requires_halted_memory_access()
+    }
+
+    /// Runs `op` with the core halted.
+    ///
+    /// If the core is running, it is halted within `timeout` before `op` runs, and resumed
+    /// afterwards, even if `op` fails. A core that is already halted is left halted.
+    #[tracing::instrument(skip(self, op))]
+    pub fn with_halted<R, E: From<Error>>(
+        &mut self,
+        timeout: Duration,
+        op: impl FnOnce(&mut Self) -> Result<R, E>,
+    ) -> Result<R, E> {
+        if self.core_halted()? {
+            return op(self);
+        }
+
+        self.halt(timeout)?;
+
+        let result = op(self);
+        let resumed = self.run();
+
+        // Report the error of the operation itself, if there was one.
+        let value = result?;
+        resumed?;
+        Ok(value)
+    }
+
+    /// Checks that `range` is fully covered by the memory map of the target.
+    ///
+    /// Returns [`Error::MemoryNotMapped`] otherwise. If the core was not created by a
+    /// [`Session`](crate::Session), no memory map is known and every range is accepted.
+    pub fn check_mapped(&self, range: Range<u64>) -> Result<(), Error> {
+        if self.memory_map.is_empty() {
+            return Ok(());
+        }
+
+        let mut address = range.start;
+        while address < range.end {
+            let Some(region) = self
+                .memory_map
+                .iter()
+                .map(MemoryRegion::address_range)
+                .find(|region| region.contains(&address))
+            else {
+                return Err(Error::MemoryNotMapped { range });
+            };
+            address = region.end;
+        }
+
+        Ok(())
+    }
+
+    /// Reads a block of 8 bit words at `address`, after checking that the memory is mapped.
+    ///
+    /// Unlike [`MemoryInterface::read`], this does not access memory outside of the target's
+    /// memory map, which can cause bus errors that some debug modules, like the Xtensa XDM,
+    /// do not recover from easily. Use [`MemoryInterface::read`] to access such memory anyway.
+    pub fn read_mapped(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        self.check_mapped(address..address + data.len() as u64)?;
+        self.read(address, data)
+    }
+
+    /// Returns how the memory at `address` is accessed, e.g. whether it is external flash that
+    /// is mapped through the cache, and whether reads from it return decrypted data.
+    pub fn memory_attributes(&mut self, address: u64) -> Result<MemoryAttributes, Error> {
+        self.inner.memory_attributes(address)
+    }
+
+    /// Reads a block of 8 bit words at `address`, like [`MemoryInterface::read`], and returns how
+    /// the memory at `address` was accessed.
+    ///
+    /// The attributes describe the start of the block. Blocks that cross into memory mapped
+    /// differently have to be split by the caller.
+    pub fn read_with_attributes(
+        &mut self,
+        address: u64,
+        data: &mut [u8],
+    ) -> Result<MemoryAttributes, Error> {
+        let attributes = self.memory_attributes(address)?;
+        self.read(address, data)?;
+
+        Ok(attributes)
+    }
+
+    /// Selects whether reads of encrypted flash through the cache return decrypted data, which
+    /// is the default, or the ciphertext stored in flash.
+    ///
+    /// Not all chips can switch off decryption, in which case [`Error::NotImplemented`] is
+    /// returned.
+    pub fn set_flash_decryption(&mut self, enabled: bool) -> Result<(), Error> {
+        self.inner.set_flash_decryption(enabled)
+    }
+
+    /// Reads the memory in `range` and decodes the instructions in it, e.g. to show the code
+    /// around the program counter.
+    ///
+    /// `range` has to start at an instruction. An instruction which doesn't end within `range`
+    /// is not decoded. The memory is read like any other access of the core, so memory hooks
+    /// apply to it.
+    pub fn disassemble(
+        &mut self,
+        range: Range<u64>,
+    ) -> Result<Vec<DisassembledInstruction>, Error> {
+        let mut code = vec![0; range.end.saturating_sub(range.start) as usize];
+        self.read(range.start, &mut code)?;
+
+        self.inner.disassemble(range.start, &code)
+    }
+
     pub(crate) fn reset_catch_clear(&mut self) -> Result<(), Error> {
         self.inner.reset_catch_clear()
     }
@@ -963,6 +2546,10 @@ impl<'probe> Core<'probe> {
         self.inner.debug_core_stop()
     }
 
+    pub(crate) fn on_session_stop(&mut self) -> Result<(), Error> {
+        self.inner.on_session_stop()
+    }
+
     /// Enables vector catching for the given `condition`
     pub fn enable_vector_catch(&mut self, condition: VectorCatchCondition) -> Result<(), Error> {
         self.inner.enable_vector_catch(condition)
@@ -979,6 +2566,19 @@ impl<'probe> Core<'probe> {
     ///
     /// * `ranges`: Memory ranges that should be dumped.
     pub fn dump(&mut self, ranges: Vec<Range<u64>>) -> Result<CoreDump, Error> {
+        self.dump_with_progress(ranges, &mut |_| true)
+    }
+
+    /// Dumps core info with the current state, like [`Core::dump`], calling `progress` while
+    /// the memory ranges are read.
+    ///
+    /// The reported progress covers all ranges together. The dump is cancelled if `progress`
+    /// returns `false`, in which case [`Error::Cancelled`] is returned.
+    pub fn dump_with_progress(
+        &mut self,
+        ranges: Vec<Range<u64>>,
+        progress: &mut dyn FnMut(TransferProgress) -> bool,
+    ) -> Result<CoreDump, Error> {
         let instruction_set = self.instruction_set()?;
         let core_type = self.core_type();
         let supports_native_64bit_access = self.supports_native_64bit_access();
@@ -991,10 +2591,22 @@ impl<'probe> Core<'probe> {
             registers.insert(register.id(), value);
         }
 
+        let total = ranges
+            .iter()
+            .map(|range| (range.end - range.start) as usize)
+            .sum();
+        let mut done = 0;
+
         let mut data = Vec::new();
         for range in ranges {
             let mut values = vec![0; (range.end - range.start) as usize];
-            self.read_8(range.start, &mut values)?;
+            self.read_8_with_progress(range.start, &mut values, &mut |range_progress| {
+                progress(TransferProgress {
+                    transferred: done + range_progress.transferred,
+                    total,
+                })
+            })?;
+            done += values.len();
             data.push((range, values));
         }
 
@@ -1035,6 +2647,14 @@ impl<'probe> CoreInterface for Core<'probe> {
         self.run()
     }
 
+    fn prepare_run(&mut self) -> Result<(), error::Error> {
+        self.prepare_run()
+    }
+
+    fn run_prepared(&mut self) -> Result<(), error::Error> {
+        self.run_prepared()
+    }
+
     fn reset(&mut self) -> Result<(), error::Error> {
         self.reset()
     }
@@ -1047,6 +2667,10 @@ impl<'probe> CoreInterface for Core<'probe> {
         self.step()
     }
 
+    fn step_with_options(&mut self, options: StepOptions) -> Result<CoreInformation, error::Error> {
+        self.step_with_options(options)
+    }
+
     fn read_core_reg(
         &mut self,
         address: registers::RegisterId,
@@ -1062,6 +2686,56 @@ impl<'probe> CoreInterface for Core<'probe> {
         self.write_core_reg(address, value)
     }
 
+    fn read_core_regs(
+        &mut self,
+        addresses: &[registers::RegisterId],
+    ) -> Result<Vec<registers::RegisterValue>, error::Error> {
+        self.inner.read_core_regs(addresses)
+    }
+
+    fn save_context(&mut self) -> Result<CoreContext, error::Error> {
+        self.save_context()
+    }
+
+    fn restore_context(&mut self, context: &CoreContext) -> Result<(), error::Error> {
+        self.restore_context(context)
+    }
+
+    fn prepare_function_call(
+        &mut self,
+        address: u64,
+        return_address: u64,
+        arguments: &[u64],
+    ) -> Result<(), error::Error> {
+        self.invalidate_register_cache();
+        self.inner
+            .prepare_function_call(address, return_address, arguments)
+    }
+
+    fn function_call_result(&mut self) -> Result<RegisterValue, error::Error> {
+        self.inner.function_call_result()
+    }
+
+    fn sample_pc(&mut self) -> Result<Option<u64>, error::Error> {
+        self.sample_pc().map(Some)
+    }
+
+    fn run_control_registers(&self) -> &'static [registers::RegisterId] {
+        self.inner.run_control_registers()
+    }
+
+    fn frame_registers(&self) -> &'static [registers::RegisterId] {
+        self.inner.frame_registers()
+    }
+
+    fn memory_attributes(&mut self, address: u64) -> Result<MemoryAttributes, Error> {
+        self.memory_attributes(address)
+    }
+
+    fn set_flash_decryption(&mut self, enabled: bool) -> Result<(), Error> {
+        self.set_flash_decryption(enabled)
+    }
+
     fn available_breakpoint_units(&mut self) -> Result<u32, error::Error> {
         self.available_breakpoint_units()
     }
@@ -1082,6 +2756,42 @@ impl<'probe> CoreInterface for Core<'probe> {
         self.clear_all_hw_breakpoints()
     }
 
+    fn restore_breakpoints(&mut self) -> Result<(), error::Error> {
+        self.restore_breakpoints()
+    }
+
+    fn take_reset_cause(&mut self) -> Result<Option<ResetCause>, error::Error> {
+        self.inner.take_reset_cause()
+    }
+
+    fn run_to(&mut self, address: u64, timeout: Duration) -> Result<CoreInformation, Error> {
+        self.run_to(address, timeout)
+    }
+
+    fn available_watchpoint_units(&mut self) -> Result<u32, error::Error> {
+        self.available_watchpoint_units()
+    }
+
+    fn hw_watchpoints(&mut self) -> Result<Vec<Option<Watchpoint>>, error::Error> {
+        self.hw_watchpoints()
+    }
+
+    fn set_hw_watchpoint(
+        &mut self,
+        unit_index: usize,
+        watchpoint: Watchpoint,
+    ) -> Result<(), error::Error> {
+        self.inner.set_hw_watchpoint(unit_index, watchpoint)
+    }
+
+    fn clear_hw_watchpoint(&mut self, unit_index: usize) -> Result<(), error::Error> {
+        self.inner.clear_hw_watchpoint(unit_index)
+    }
+
+    fn watchpoints_share_breakpoint_units(&self) -> bool {
+        self.inner.watchpoints_share_breakpoint_units()
+    }
+
     fn registers(&self) -> &'static registers::CoreRegisters {
         self.registers()
     }
@@ -1126,6 +2836,10 @@ impl<'probe> CoreInterface for Core<'probe> {
         self.floating_point_register_count()
     }
 
+    fn requires_halted_memory_access(&self) -> bool {
+        self.requires_halted_memory_access()
+    }
+
     fn reset_catch_set(&mut self) -> Result<(), Error> {
         todo!()
     }
@@ -1148,6 +2862,7 @@ pub enum ResolvedCoreOptions {
         options: RiscvCoreAccessOptions,
     },
     Xtensa {
+        sequence: Arc<dyn XtensaDebugSequence>,
         options: XtensaCoreAccessOptions,
     },
 }
@@ -1161,7 +2876,11 @@ impl std::fmt::Debug for ResolvedCoreOptions {
                 .field("options", options)
                 .finish(),
             Self::Riscv { options } => f.debug_struct("Riscv").field("options", options).finish(),
-            Self::Xtensa { options } => f.debug_struct("Xtensa").field("options", options).finish(),
+            Self::Xtensa { options, .. } => f
+                .debug_struct("Xtensa")
+                .field("sequence", &"<XtensaDebugSequence>")
+                .field("options", options)
+                .finish(),
         }
     }
 }
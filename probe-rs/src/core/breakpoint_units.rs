@@ -0,0 +1,129 @@
+//! Bookkeeping of the hardware breakpoint units of a core.
+
+use crate::{Error, Watchpoint};
+
+/// The breakpoint units of a core, along with the addresses they are configured for.
+///
+/// Architectures whose breakpoint registers can't be read back, or are cleared by a reset, keep
+/// their configuration here, so that it can be reported and reprogrammed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct BreakpointUnits {
+    enabled: bool,
+    units: Vec<Option<u64>>,
+}
+
+impl BreakpointUnits {
+    /// Creates the bookkeeping for `count` units, all of them free and disabled.
+    pub fn new(count: usize) -> Self {
+        Self {
+            enabled: false,
+            units: vec![None; count],
+        }
+    }
+
+    /// Returns the number of units.
+    pub fn len(&self) -> usize {
+        self.units.len()
+    }
+
+    /// Returns whether breakpoints are enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables the breakpoints, without changing their configuration.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// Returns the addresses the units are configured for, by unit. `None` marks a free unit.
+    pub fn addresses(&self) -> &[Option<u64>] {
+        &self.units
+    }
+
+    /// Returns the configured units along with their addresses.
+    pub fn configured(&self) -> impl Iterator<Item = (usize, u64)> + '_ {
+        self.units
+            .iter()
+            .enumerate()
+            .filter_map(|(unit, address)| Some((unit, (*address)?)))
+    }
+
+    /// Configures `unit` for `address`.
+    pub fn set(&mut self, unit: usize, address: u64) -> Result<(), Error> {
+        let Some(slot) = self.units.get_mut(unit) else {
+            return Err(Error::GenericCoreError(format!(
+                "Breakpoint unit {unit} does not exist, the core has {} units",
+                self.units.len()
+            )));
+        };
+
+        *slot = Some(address);
+        Ok(())
+    }
+
+    /// Frees `unit`, returning the address it was configured for.
+    pub fn clear(&mut self, unit: usize) -> Option<u64> {
+        self.units.get_mut(unit)?.take()
+    }
+
+    /// Returns the mask of the units to enable in hardware: the configured units, or none if
+    /// breakpoints are disabled.
+    pub fn enable_mask(&self) -> u32 {
+        if !self.enabled {
+            return 0;
+        }
+
+        self.configured()
+            .fold(0, |mask, (unit, _)| mask | (1 << unit))
+    }
+}
+
+/// Returns the first breakpoint unit that is neither configured in `breakpoints`, nor used by one
+/// of the `watchpoints` on cores where they share the units.
+pub(crate) fn free_breakpoint_unit(
+    breakpoints: &[Option<u64>],
+    watchpoints: &[Option<Watchpoint>],
+) -> Option<usize> {
+    (0..breakpoints.len()).find(|&unit| {
+        breakpoints[unit].is_none() && !matches!(watchpoints.get(unit), Some(Some(_)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WatchpointKind;
+
+    #[test]
+    fn enable_mask_covers_configured_units() {
+        let mut units = BreakpointUnits::new(3);
+        units.set(0, 0x4000_0000).unwrap();
+        units.set(2, 0x4000_0010).unwrap();
+        assert_eq!(units.enable_mask(), 0);
+
+        units.set_enabled(true);
+        assert_eq!(units.enable_mask(), 0b101);
+
+        assert_eq!(units.clear(0), Some(0x4000_0000));
+        assert_eq!(units.enable_mask(), 0b100);
+        assert!(units.set(3, 0x4000_0020).is_err());
+    }
+
+    #[test]
+    fn free_unit_skips_units_used_by_watchpoints() {
+        let watchpoint = Watchpoint {
+            address: 0x2000_0000,
+            length: 4,
+            kind: WatchpointKind::Write,
+        };
+
+        let breakpoints = [Some(0x100), None, None];
+        assert_eq!(free_breakpoint_unit(&breakpoints, &[]), Some(1));
+        assert_eq!(
+            free_breakpoint_unit(&breakpoints, &[None, Some(watchpoint)]),
+            Some(2)
+        );
+        assert_eq!(free_breakpoint_unit(&[Some(0x100)], &[]), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! Checkpoints of halted cores, which give debuggers reverse stepping and reverse continuing.
+
+use std::ops::Range;
+
+use anyhow::anyhow;
+
+use super::{CoreContext, CoreInformation};
+use crate::{Core, Error, MemoryInterface};
+
+/// The granularity in which memory is compared and restored.
+const PAGE_SIZE: u64 = 1024;
+
+/// The number of checkpoints an [`ExecutionHistory`] keeps, before dropping the oldest.
+const MAX_CHECKPOINTS: usize = 16;
+
+/// The registers and memory of a halted core at one point of its execution.
+#[derive(Debug, Clone)]
+pub struct Checkpoint {
+    context: CoreContext,
+    pages: Vec<(u64, Vec<u8>)>,
+}
+
+impl Checkpoint {
+    /// Takes a checkpoint of the halted `core`, including the memory in `ranges`.
+    ///
+    /// Code and peripherals are not part of the checkpoint, so `ranges` usually covers the RAM
+    /// of the program.
+    pub fn take(core: &mut Core<'_>, ranges: &[Range<u64>]) -> Result<Self, Error> {
+        let context = core.save_context()?;
+
+        let mut pages = Vec::new();
+        for range in ranges {
+            let mut start = range.start;
+            while start < range.end {
+                let end = ((start / PAGE_SIZE + 1) * PAGE_SIZE).min(range.end);
+                let mut data = vec![0; (end - start) as usize];
+                core.read(start, &mut data)?;
+                pages.push((start, data));
+                start = end;
+            }
+        }
+
+        Ok(Self { context, pages })
+    }
+
+    /// Restores the registers and memory of the halted `core` to the checkpoint.
+    ///
+    /// Only the pages of memory that changed since the checkpoint was taken are written. Returns
+    /// the number of pages that were restored.
+    pub fn restore(&self, core: &mut Core<'_>) -> Result<usize, Error> {
+        let mut restored = 0;
+        let mut current = Vec::new();
+        for (address, data) in &self.pages {
+            current.resize(data.len(), 0);
+            core.read(*address, &mut current)?;
+            if current != *data {
+                core.write(*address, data)?;
+                restored += 1;
+            }
+        }
+
+        core.restore_context(&self.context)?;
+
+        Ok(restored)
+    }
+}
+
+/// Where [`ExecutionHistory::reverse_step`] or [`ExecutionHistory::reverse_continue`] stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReverseStop {
+    /// The core went back one instruction.
+    Step,
+    /// The core went back to a breakpoint at the given address.
+    Breakpoint(u64),
+    /// The core went back to a checkpoint, before which the execution is unknown.
+    Checkpoint,
+}
+
+/// A checkpoint and the number of instructions that were executed after it.
+#[derive(Debug)]
+struct Segment {
+    checkpoint: Checkpoint,
+    /// The number of steps until the next checkpoint, or the current position for the last
+    /// checkpoint. `None` if the core was resumed, and the number is unknown.
+    steps: Option<usize>,
+}
+
+/// Executes a core in reverse, by restoring the nearest checkpoint and executing forward again.
+///
+/// Only instructions executed by [`ExecutionHistory::step`] are counted. Once the core is
+/// resumed, the execution since the last checkpoint is unknown, and going back returns to that
+/// checkpoint. Peripherals are not restored, so this works best for code that only works on
+/// RAM.
+#[derive(Debug)]
+pub struct ExecutionHistory {
+    ranges: Vec<Range<u64>>,
+    segments: Vec<Segment>,
+}
+
+impl ExecutionHistory {
+    /// Creates a history whose checkpoints include the memory in `ranges`.
+    pub fn new(ranges: Vec<Range<u64>>) -> Self {
+        Self {
+            ranges,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Takes a checkpoint of the halted `core` at its current position.
+    pub fn checkpoint(&mut self, core: &mut Core<'_>) -> Result<(), Error> {
+        let checkpoint = Checkpoint::take(core, &self.ranges)?;
+
+        if self.segments.len() == MAX_CHECKPOINTS {
+            self.segments.remove(0);
+        }
+        self.segments.push(Segment {
+            checkpoint,
+            steps: Some(0),
+        });
+
+        Ok(())
+    }
+
+    /// Executes a single instruction, which can be reversed.
+    pub fn step(&mut self, core: &mut Core<'_>) -> Result<CoreInformation, Error> {
+        let information = core.step()?;
+
+        if let Some(Segment {
+            steps: Some(steps), ..
+        }) = self.segments.last_mut()
+        {
+            *steps += 1;
+        }
+
+        Ok(information)
+    }
+
+    /// Records that the core was resumed, after which the executed instructions are unknown.
+    pub fn resumed(&mut self) {
+        if let Some(segment) = self.segments.last_mut() {
+            segment.steps = None;
+        }
+    }
+
+    /// Goes back a single instruction.
+    pub fn reverse_step(&mut self, core: &mut Core<'_>) -> Result<ReverseStop, Error> {
+        loop {
+            let checkpoints = self.segments.len();
+            let Some(segment) = self.segments.last_mut() else {
+                return Err(Error::Other(anyhow!("No checkpoint was taken")));
+            };
+
+            match segment.steps {
+                Some(0) if checkpoints > 1 => {
+                    // The position is the start of the last checkpoint, so go back to the
+                    // previous one.
+                    self.segments.pop();
+                }
+                Some(0) | None => {
+                    segment.checkpoint.restore(core)?;
+                    segment.steps = Some(0);
+                    return Ok(ReverseStop::Checkpoint);
+                }
+                Some(steps) => {
+                    segment.checkpoint.restore(core)?;
+                    for _ in 1..steps {
+                        core.step()?;
+                    }
+                    segment.steps = Some(steps - 1);
+                    return Ok(ReverseStop::Step);
+                }
+            }
+        }
+    }
+
+    /// Goes back to the last position where the program counter was at one of `breakpoints`.
+    ///
+    /// Every checkpoint on the way is replayed twice, once to find the breakpoint, and once to
+    /// return to it.
+    pub fn reverse_continue(
+        &mut self,
+        core: &mut Core<'_>,
+        breakpoints: &[u64],
+    ) -> Result<ReverseStop, Error> {
+        loop {
+            let Some(segment) = self.segments.last_mut() else {
+                return Err(Error::Other(anyhow!("No checkpoint was taken")));
+            };
+
+            let Some(steps) = segment.steps else {
+                segment.checkpoint.restore(core)?;
+                segment.steps = Some(0);
+                return Ok(ReverseStop::Checkpoint);
+            };
+
+            // Find the last breakpoint before the current position.
+            segment.checkpoint.restore(core)?;
+            let mut hit = None;
+            for step in 0..steps {
+                if step > 0 {
+                    core.step()?;
+                }
+                let pc: u64 = core.read_core_reg(core.program_counter())?;
+                if breakpoints.contains(&pc) {
+                    hit = Some((step, pc));
+                }
+            }
+
+            if let Some((step, pc)) = hit {
+                segment.checkpoint.restore(core)?;
+                for _ in 0..step {
+                    core.step()?;
+                }
+                segment.steps = Some(step);
+                return Ok(ReverseStop::Breakpoint(pc));
+            }
+
+            if self.segments.len() == 1 {
+                self.segments[0].steps = Some(0);
+                return Ok(ReverseStop::Checkpoint);
+            }
+            self.segments.pop();
+        }
+    }
+}
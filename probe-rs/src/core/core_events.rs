@@ -0,0 +1,233 @@
+//! Notifications about state changes of cores.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use super::{CoreStatus, HaltReason};
+use crate::Session;
+
+/// A state change of a core, as reported to the subscribers of
+/// [`Session::subscribe_core_events`](crate::Session::subscribe_core_events).
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum CoreEvent {
+    /// The core halted.
+    Halted {
+        /// The index of the core.
+        core: usize,
+        /// The reason why the core halted.
+        reason: HaltReason,
+    },
+    /// The core started running.
+    Running {
+        /// The index of the core.
+        core: usize,
+    },
+    /// The core was reset.
+    Reset {
+        /// The index of the core.
+        core: usize,
+    },
+    /// The core was reset by something other than probe-rs, e.g. a watchdog or a brownout.
+    ///
+    /// The breakpoints of the core are reprogrammed, if the reset cleared them.
+    UnexpectedReset {
+        /// The index of the core.
+        core: usize,
+        /// What was reset along with the core.
+        cause: ResetCause,
+    },
+}
+
+/// What was reset along with a core, as reported by [`CoreEvent::UnexpectedReset`].
+///
+/// Which of these a core can tell apart depends on its architecture. The actual source of the
+/// reset, e.g. a watchdog, is chip specific.
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub enum ResetCause {
+    /// Only the core was reset, while the debug logic kept running, e.g. by a watchdog, a
+    /// software reset or the reset pin.
+    Core,
+    /// The debug logic was reset along with the core, e.g. by a brownout or a power cycle.
+    System,
+}
+
+/// Distributes [`CoreEvent`]s to the subscribers of a session.
+#[derive(Debug, Default)]
+pub(crate) struct CoreEvents {
+    subscribers: Vec<Sender<CoreEvent>>,
+
+    /// The last status observed for each core, used to detect transitions.
+    last_status: HashMap<usize, CoreStatus>,
+}
+
+impl CoreEvents {
+    /// Adds a new subscriber.
+    pub fn subscribe(&mut self) -> Receiver<CoreEvent> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Reports a reset of `core`.
+    pub fn reset(&mut self, core: usize) {
+        self.last_status.remove(&core);
+        self.emit(CoreEvent::Reset { core });
+    }
+
+    /// Reports a reset of `core` which was not done by probe-rs.
+    pub fn unexpected_reset(&mut self, core: usize, cause: ResetCause) {
+        self.last_status.remove(&core);
+        self.emit(CoreEvent::UnexpectedReset { core, cause });
+    }
+
+    /// Records the observed status of `core`, and emits an event if the core changed its state.
+    pub fn observe_status(&mut self, core: usize, status: CoreStatus) {
+        if self.last_status.insert(core, status) == Some(status) {
+            return;
+        }
+
+        match status {
+            CoreStatus::Halted(reason) => self.emit(CoreEvent::Halted { core, reason }),
+            CoreStatus::Running => self.emit(CoreEvent::Running { core }),
+            // Sleeping cores are still running, and the remaining states carry no information.
+            CoreStatus::Sleeping | CoreStatus::LockedUp | CoreStatus::Unknown => {}
+        }
+    }
+
+    /// Sends `event` to all subscribers, dropping the ones which went away.
+    fn emit(&mut self, event: CoreEvent) {
+        tracing::trace!("Core event: {:?}", event);
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event).is_ok());
+    }
+}
+
+/// A background thread polling the cores of a session for state changes.
+///
+/// Created by [`Session::spawn_core_event_poller`]. The thread is stopped when the poller is dropped.
+#[derive(Debug)]
+pub struct CoreEventPoller {
+    events: Receiver<CoreEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl CoreEventPoller {
+    pub(crate) fn spawn(session: Arc<Mutex<Session>>, interval: Duration) -> Self {
+        let events = session.lock().unwrap().subscribe_core_events();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = std::thread::spawn({
+            let stop = stop.clone();
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    {
+                        let Ok(mut session) = session.lock() else {
+                            break;
+                        };
+
+                        if let Err(error) = session.poll_core_events() {
+                            tracing::warn!("Failed to poll the core status: {:?}", error);
+                        }
+                    }
+
+                    std::thread::sleep(interval);
+                }
+            }
+        });
+
+        Self {
+            events,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// The received core events.
+    ///
+    /// Use [`Receiver::recv`] to block until a core changes its state.
+    pub fn events(&self) -> &Receiver<CoreEvent> {
+        &self.events
+    }
+}
+
+impl Drop for CoreEventPoller {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_transitions_are_reported() {
+        let mut events = CoreEvents::default();
+        let receiver = events.subscribe();
+
+        events.observe_status(0, CoreStatus::Running);
+        events.observe_status(0, CoreStatus::Running);
+        events.observe_status(0, CoreStatus::Halted(HaltReason::Request));
+        events.observe_status(0, CoreStatus::Halted(HaltReason::Request));
+        events.observe_status(0, CoreStatus::Halted(HaltReason::Step));
+        events.reset(0);
+        events.observe_status(0, CoreStatus::Running);
+
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                CoreEvent::Running { core: 0 },
+                CoreEvent::Halted {
+                    core: 0,
+                    reason: HaltReason::Request
+                },
+                CoreEvent::Halted {
+                    core: 0,
+                    reason: HaltReason::Step
+                },
+                CoreEvent::Reset { core: 0 },
+                CoreEvent::Running { core: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn status_is_reported_again_after_an_unexpected_reset() {
+        let mut events = CoreEvents::default();
+        let receiver = events.subscribe();
+
+        events.observe_status(0, CoreStatus::Running);
+        events.unexpected_reset(0, ResetCause::Core);
+        events.observe_status(0, CoreStatus::Running);
+
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                CoreEvent::Running { core: 0 },
+                CoreEvent::UnexpectedReset {
+                    core: 0,
+                    cause: ResetCause::Core
+                },
+                CoreEvent::Running { core: 0 },
+            ]
+        );
+    }
+
+    #[test]
+    fn dropped_subscribers_are_removed() {
+        let mut events = CoreEvents::default();
+        drop(events.subscribe());
+
+        events.observe_status(0, CoreStatus::Running);
+
+        assert!(events.subscribers.is_empty());
+    }
+}
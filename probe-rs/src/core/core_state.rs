@@ -8,8 +8,9 @@ use crate::{
         riscv::{communication_interface::RiscvCommunicationInterface, RiscVState},
         xtensa::{communication_interface::XtensaCommunicationInterface, XtensaState},
     },
-    Core, CoreType, Error,
+    Core, CoreType, Error, RegisterId, RegisterValue, RegisterWritePolicy,
 };
+use std::collections::{HashMap, HashSet};
 
 use super::ResolvedCoreOptions;
 
@@ -46,7 +47,7 @@ impl CombinedCoreState {
             }
         };
 
-        Ok(match &mut self.specific_state {
+        let core = match &mut self.specific_state {
             SpecificCoreState::Armv6m(s) => Core::new(
                 crate::architecture::arm::armv6m::Armv6m::new(memory, s, debug_sequence, self.id)?,
             ),
@@ -80,7 +81,11 @@ impl CombinedCoreState {
                     "Core architecture and Probe mismatch.",
                 ))
             }
-        })
+        };
+
+        Ok(core
+            .with_register_cache(&mut self.core_state.register_cache)
+            .with_register_write_guard(&mut self.core_state.register_write_guard))
     }
 
     pub(crate) fn enable_arm_debug(
@@ -129,7 +134,7 @@ impl CombinedCoreState {
         &'probe mut self,
         interface: &'probe mut RiscvCommunicationInterface,
     ) -> Result<Core<'probe>, Error> {
-        Ok(match &mut self.specific_state {
+        let core = match &mut self.specific_state {
             SpecificCoreState::Riscv(s) => Core::new(crate::architecture::riscv::Riscv32::new(
                 interface, s, self.id,
             )),
@@ -138,23 +143,68 @@ impl CombinedCoreState {
                     "Core architecture and Probe mismatch.",
                 ))
             }
-        })
+        };
+
+        Ok(core
+            .with_register_cache(&mut self.core_state.register_cache)
+            .with_register_write_guard(&mut self.core_state.register_write_guard))
     }
 
     pub(crate) fn attach_xtensa<'probe>(
         &'probe mut self,
         interface: &'probe mut XtensaCommunicationInterface,
     ) -> Result<Core<'probe>, Error> {
-        Ok(match &mut self.specific_state {
-            SpecificCoreState::Xtensa(s) => Core::new(crate::architecture::xtensa::Xtensa::new(
-                interface, s, self.id,
-            )),
+        let (sequence, tap) = match &self.core_state.core_access_options {
+            ResolvedCoreOptions::Xtensa { sequence, options } => {
+                // Each Xtensa core sits behind its own TAP, by default in the order the cores
+                // are listed.
+                (sequence.clone(), options.jtag_tap.unwrap_or(self.id))
+            }
             _ => {
                 return Err(Error::UnableToOpenProbe(
                     "Core architecture and Probe mismatch.",
                 ))
             }
-        })
+        };
+
+        let core = match &mut self.specific_state {
+            SpecificCoreState::Xtensa(s) => {
+                interface.select_core(tap)?;
+
+                Core::new(crate::architecture::xtensa::Xtensa::new(
+                    interface, s, sequence, self.id,
+                ))
+            }
+            _ => {
+                return Err(Error::UnableToOpenProbe(
+                    "Core architecture and Probe mismatch.",
+                ))
+            }
+        };
+
+        Ok(core
+            .with_register_cache(&mut self.core_state.register_cache)
+            .with_register_write_guard(&mut self.core_state.register_write_guard))
+    }
+
+    /// Returns the index of the TAP of an Xtensa core, or `None` for other cores.
+    pub(crate) fn xtensa_tap(&self) -> Option<usize> {
+        match &self.core_state.core_access_options {
+            ResolvedCoreOptions::Xtensa { options, .. } => {
+                Some(options.jtag_tap.unwrap_or(self.id))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the base address of the CTI of an ARMv8-A core, or `None` for other cores.
+    pub(crate) fn armv8a_cti_base(&self) -> Option<u64> {
+        match (&self.core_state.core_access_options, &self.specific_state) {
+            (ResolvedCoreOptions::Arm { options, .. }, SpecificCoreState::Armv8a(_)) => {
+                options.cti_base
+            }
+            _ => None,
+        }
     }
 
     /// Get the memory AP for this core.
@@ -172,6 +222,12 @@ impl CombinedCoreState {
 pub struct CoreState {
     /// Information needed to access the core
     core_access_options: ResolvedCoreOptions,
+
+    /// Register values read since the core last halted.
+    pub(crate) register_cache: RegisterCache,
+
+    /// How writes to the registers used for run control are handled.
+    pub(crate) register_write_guard: RegisterWriteGuard,
 }
 
 impl CoreState {
@@ -179,6 +235,8 @@ impl CoreState {
     pub fn new(core_access_options: ResolvedCoreOptions) -> Self {
         Self {
             core_access_options,
+            register_cache: RegisterCache::new(),
+            register_write_guard: RegisterWriteGuard::default(),
         }
     }
 
@@ -202,6 +260,76 @@ impl CoreState {
     }
 }
 
+/// Core register values, which are valid until the core is resumed, stepped or reset.
+#[derive(Debug)]
+pub(crate) struct RegisterCache {
+    enabled: bool,
+    values: HashMap<RegisterId, RegisterValue>,
+}
+
+impl RegisterCache {
+    fn new() -> Self {
+        Self {
+            enabled: true,
+            values: HashMap::new(),
+        }
+    }
+
+    /// Enables or disables caching. Disabling the cache also clears it.
+    pub(crate) fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.values.clear();
+    }
+
+    pub(crate) fn get(&self, register: RegisterId) -> Option<RegisterValue> {
+        self.values.get(&register).copied()
+    }
+
+    pub(crate) fn insert(&mut self, register: RegisterId, value: RegisterValue) {
+        if self.enabled {
+            self.values.insert(register, value);
+        }
+    }
+
+    /// Forgets all cached values.
+    pub(crate) fn invalidate(&mut self) {
+        self.values.clear();
+    }
+}
+
+/// Checks writes to the registers that probe-rs relies on to control the core against the
+/// [`RegisterWritePolicy`] of the core.
+#[derive(Debug, Default)]
+pub(crate) struct RegisterWriteGuard {
+    policy: RegisterWritePolicy,
+    /// Registers the user took ownership of, which are written regardless of the policy.
+    owned: HashSet<RegisterId>,
+}
+
+impl RegisterWriteGuard {
+    pub(crate) fn set_policy(&mut self, policy: RegisterWritePolicy) {
+        self.policy = policy;
+    }
+
+    pub(crate) fn take_ownership(&mut self, register: RegisterId) {
+        self.owned.insert(register);
+    }
+
+    pub(crate) fn release_ownership(&mut self, register: RegisterId) {
+        self.owned.remove(&register);
+    }
+
+    /// Returns the policy that applies to writing `register`, which probe-rs relies on to control
+    /// the core.
+    pub(crate) fn policy(&self, register: RegisterId) -> RegisterWritePolicy {
+        if self.owned.contains(&register) {
+            RegisterWritePolicy::Allow
+        } else {
+            self.policy
+        }
+    }
+}
+
 /// The architecture specific core state.
 #[derive(Debug)]
 pub enum SpecificCoreState {
@@ -233,7 +361,7 @@ impl SpecificCoreState {
             CoreType::Armv8a => SpecificCoreState::Armv8a(CortexAState::new()),
             CoreType::Armv8m => SpecificCoreState::Armv8m(CortexMState::new()),
             CoreType::Riscv => SpecificCoreState::Riscv(RiscVState::new()),
-            CoreType::Xtensa => SpecificCoreState::Xtensa(XtensaState::new()),
+            CoreType::Xtensa => SpecificCoreState::Xtensa(XtensaState::new(false, true, true)),
         }
     }
 
@@ -23,10 +23,20 @@ impl CoreStatus {
     pub fn is_running(&self) -> bool {
         self == &Self::Running
     }
+
+    /// Returns the exit code, if the core halted because the program exited through semihosting.
+    pub fn exit_code(&self) -> Option<u64> {
+        match self {
+            CoreStatus::Halted(HaltReason::Breakpoint(BreakpointCause::Semihosting(command))) => {
+                command.exit_code()
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Indicates the operation the target would like the debugger to perform.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
 pub enum SemihostingCommand {
     /// The target indicates that it completed successfully and no-longer wishes
     /// to run.
@@ -44,8 +54,20 @@ pub enum SemihostingCommand {
     },
 }
 
+impl SemihostingCommand {
+    /// Returns the exit code of the program, which is zero for [`SemihostingCommand::ExitSuccess`],
+    /// or `None` if the command is not an exit.
+    pub fn exit_code(&self) -> Option<u64> {
+        match self {
+            SemihostingCommand::ExitSuccess => Some(0),
+            SemihostingCommand::ExitError { code } => Some(*code),
+            SemihostingCommand::Unknown { .. } => None,
+        }
+    }
+}
+
 /// When the core halts due to a breakpoint request, some architectures will allow us to distinguish between a software and hardware breakpoint.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
 pub enum BreakpointCause {
     /// We encountered a hardware breakpoint.
     Hardware,
@@ -62,21 +84,25 @@ pub enum BreakpointCause {
 }
 
 /// The reason why a core was halted.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
 pub enum HaltReason {
     /// Multiple reasons for a halt.
     ///
     /// This can happen for example when a single instruction
     /// step ends up on a breakpoint, after which both breakpoint and step / request
     /// are set.
-    Multiple,
+    ///
+    /// The payload is the raw value of the architecture specific register that reports
+    /// the causes, i.e. DFSR on ARMv(x)-M and DEBUGCAUSE on Xtensa.
+    Multiple(u32),
     /// Core halted due to a breakpoint. The cause is `Unknown` if we cannot distinguish between a hardware and software breakpoint.
     Breakpoint(BreakpointCause),
     /// Core halted due to an exception, e.g. an
     /// an interrupt.
-    Exception,
-    /// Core halted due to a data watchpoint
-    Watchpoint,
+    Exception(ExceptionCause),
+    /// Core halted due to a data watchpoint. The payload is the index of the watchpoint unit
+    /// that triggered, if the architecture reports it.
+    Watchpoint(Option<usize>),
     /// Core halted after single step
     Step,
     /// Core halted because of a debugger request
@@ -89,6 +115,64 @@ pub enum HaltReason {
     Unknown,
 }
 
+/// Details about an exception that halted the core.
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize)]
+pub enum ExceptionCause {
+    /// The architecture does not report details about the exception.
+    Unknown,
+    /// The core reported the cause of the exception.
+    Fault {
+        /// The architecture specific exception cause code.
+        cause: u32,
+        /// A short description of the exception cause.
+        description: &'static str,
+        /// The address that caused the exception, if the exception is related to a memory access.
+        address: Option<u64>,
+    },
+    /// The core raised an exception while it was already handling one, which it can't recover
+    /// from.
+    DoubleFault {
+        /// The architecture specific exception cause code of the second exception.
+        cause: u32,
+        /// A short description of the exception cause.
+        description: &'static str,
+        /// The address that caused the exception, if the exception is related to a memory access.
+        address: Option<u64>,
+        /// The address of the instruction that raised the second exception.
+        pc: u64,
+    },
+}
+
+impl std::fmt::Display for ExceptionCause {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExceptionCause::Unknown => write!(f, "unknown exception"),
+            ExceptionCause::Fault {
+                cause,
+                description,
+                address: Some(address),
+            } => write!(f, "{description} ({cause}) at address {address:#010x}"),
+            ExceptionCause::Fault {
+                cause,
+                description,
+                address: None,
+            } => write!(f, "{description} ({cause})"),
+            ExceptionCause::DoubleFault {
+                cause,
+                description,
+                address,
+                pc,
+            } => {
+                write!(f, "double exception at {pc:#010x}: {description} ({cause})")?;
+                if let Some(address) = address {
+                    write!(f, " at address {address:#010x}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
 /// When a core hits an exception, we halt the core.
 ///
 /// `VectorCatchCondition` describes which event exactly should trigger a halt.
@@ -0,0 +1,47 @@
+//! Instructions decoded by [`Core::disassemble`](crate::Core::disassemble).
+
+use std::fmt;
+
+/// An instruction decoded from the memory of a core.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisassembledInstruction {
+    /// The address of the instruction.
+    pub address: u64,
+    /// The encoding of the instruction.
+    pub bytes: Vec<u8>,
+    /// The mnemonic of the instruction, e.g. `l32i.n`, or `.byte` if it could not be decoded.
+    pub mnemonic: String,
+    /// The operands of the instruction, separated by commas, e.g. `a2, a1, 4`.
+    pub operands: String,
+    /// The address the instruction branches to, calls or loads from, if it is encoded in the
+    /// instruction.
+    pub target: Option<u64>,
+}
+
+impl DisassembledInstruction {
+    /// The length of the instruction in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Returns `true` if the instruction has no bytes, which decoded instructions never have.
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+}
+
+impl fmt::Display for DisassembledInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:#010x}: ", self.address)?;
+        for byte in &self.bytes {
+            write!(f, "{byte:02x}")?;
+        }
+        // Pad the encoding to the longest instruction of the supported architectures.
+        let padding = 8usize.saturating_sub(self.bytes.len() * 2);
+        write!(f, "{:padding$} {}", "", self.mnemonic)?;
+        if !self.operands.is_empty() {
+            write!(f, " {}", self.operands)?;
+        }
+        Ok(())
+    }
+}
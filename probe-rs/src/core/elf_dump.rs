@@ -0,0 +1,210 @@
+//! Export of [`CoreDump`]s as ELF core files, which GDB and esp-coredump can load.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use super::{CoreDump, CoreDumpError};
+use crate::{CoreType, RegisterId};
+
+const ET_CORE: u16 = 4;
+const EM_ARM: u16 = 40;
+const EM_XTENSA: u16 = 94;
+const EM_RISCV: u16 = 243;
+
+const PT_LOAD: u32 = 1;
+const PT_NOTE: u32 = 4;
+const NT_PRSTATUS: u32 = 1;
+
+const ELF_HEADER_SIZE: usize = 52;
+const PROGRAM_HEADER_SIZE: usize = 32;
+
+/// The size of the fields of `elf_prstatus` that precede the registers, i.e. the signal, process
+/// and time information, which is left empty.
+const PRSTATUS_HEADER_SIZE: usize = 72;
+
+impl CoreDump {
+    /// Writes the dump as an ELF core file, which GDB and esp-coredump can load.
+    ///
+    /// The registers are stored in a `NT_PRSTATUS` note, in the layout of the Linux register set
+    /// of the architecture, and every memory range in a loadable segment. Only 32-bit cores are
+    /// supported.
+    pub fn write_elf(&self, mut writer: impl Write) -> Result<(), CoreDumpError> {
+        let (machine, registers) = self.elf_registers()?;
+
+        let mut prstatus = vec![0; PRSTATUS_HEADER_SIZE];
+        for register in registers {
+            prstatus.extend_from_slice(&register.to_le_bytes());
+        }
+        // pr_fpvalid
+        prstatus.extend_from_slice(&0u32.to_le_bytes());
+
+        let mut note = Vec::new();
+        note.extend_from_slice(&5u32.to_le_bytes());
+        note.extend_from_slice(&(prstatus.len() as u32).to_le_bytes());
+        note.extend_from_slice(&NT_PRSTATUS.to_le_bytes());
+        note.extend_from_slice(b"CORE\0\0\0\0");
+        note.extend_from_slice(&prstatus);
+
+        let segments = 1 + self.data.len();
+        let mut offset = ELF_HEADER_SIZE + segments * PROGRAM_HEADER_SIZE;
+
+        let mut elf = Vec::with_capacity(offset);
+        elf.extend_from_slice(&[0x7f, b'E', b'L', b'F', 1, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        elf.extend_from_slice(&ET_CORE.to_le_bytes());
+        elf.extend_from_slice(&machine.to_le_bytes());
+        // e_version, e_entry, e_phoff, e_shoff and e_flags
+        for field in [1, 0, ELF_HEADER_SIZE as u32, 0, 0] {
+            elf.extend_from_slice(&field.to_le_bytes());
+        }
+        // e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum and e_shstrndx
+        for field in [ELF_HEADER_SIZE, PROGRAM_HEADER_SIZE, segments, 0, 0, 0] {
+            elf.extend_from_slice(&(field as u16).to_le_bytes());
+        }
+
+        let mut program_header = |kind: u32, address: u64, size: usize, flags: u32| {
+            // p_type, p_offset, p_vaddr, p_paddr, p_filesz, p_memsz, p_flags and p_align
+            let memory_size = if kind == PT_LOAD { size } else { 0 };
+            for field in [
+                kind,
+                offset as u32,
+                address as u32,
+                address as u32,
+                size as u32,
+                memory_size as u32,
+                flags,
+                if kind == PT_LOAD { 1 } else { 4 },
+            ] {
+                elf.extend_from_slice(&field.to_le_bytes());
+            }
+            offset += size;
+        };
+
+        program_header(PT_NOTE, 0, note.len(), 0);
+        for (range, _) in &self.data {
+            // Readable, writable and executable.
+            program_header(PT_LOAD, range.start, (range.end - range.start) as usize, 7);
+        }
+
+        elf.extend_from_slice(&note);
+        for (_, data) in &self.data {
+            elf.extend_from_slice(data);
+        }
+
+        writer.write_all(&elf).map_err(CoreDumpError::EncodingElf)
+    }
+
+    /// Stores the dump as an ELF core file at `path`, see [`CoreDump::write_elf`].
+    pub fn store_elf(&self, path: &Path) -> Result<(), CoreDumpError> {
+        let file = File::create(path).map_err(|e| {
+            CoreDumpError::CoreDumpFileWrite(e, dunce::canonicalize(path).unwrap_or_default())
+        })?;
+
+        self.write_elf(file)
+    }
+
+    /// Returns the ELF machine of the core, and its registers in the layout of `pr_reg`.
+    fn elf_registers(&self) -> Result<(u16, Vec<u32>), CoreDumpError> {
+        let register = |id: u16| -> u32 {
+            self.registers
+                .get(&RegisterId(id))
+                .and_then(|value| (*value).try_into().ok())
+                .unwrap_or(0)
+        };
+
+        match self.core_type {
+            CoreType::Armv6m
+            | CoreType::Armv7a
+            | CoreType::Armv7m
+            | CoreType::Armv7em
+            | CoreType::Armv8m => {
+                // r0 to r15, CPSR, and ORIG_r0, which has no meaning here.
+                let mut registers: Vec<_> = (0..=16).map(register).collect();
+                registers.push(0);
+
+                Ok((EM_ARM, registers))
+            }
+            CoreType::Riscv => {
+                // The PC, followed by x1 to x31.
+                let registers = std::iter::once(register(0x7b1))
+                    .chain((0x1001..=0x101f).map(register))
+                    .collect();
+
+                Ok((EM_RISCV, registers))
+            }
+            CoreType::Xtensa => {
+                const LBEG: u16 = 0x0100;
+                const LEND: u16 = 0x0101;
+                const LCOUNT: u16 = 0x0102;
+                const SAR: u16 = 0x0103;
+
+                // PC, PS, LBEG, LEND, LCOUNT, SAR, WINDOWSTART, WINDOWBASE, THREADPTR, 55
+                // reserved words, and the 64 physical address registers. Only the logical
+                // registers of the current window are dumped, so they are stored as the first
+                // physical registers, with the current window at the start of the register file.
+                let mut registers = vec![
+                    register(0xFF00),
+                    register(0xFF01),
+                    register(LBEG),
+                    register(LEND),
+                    register(LCOUNT),
+                    register(SAR),
+                    1,
+                    0,
+                ];
+                registers.resize(64, 0);
+                registers.extend((0x0000..=0x000F).map(register));
+                registers.resize(128, 0);
+
+                Ok((EM_XTENSA, registers))
+            }
+            CoreType::Armv8a => Err(CoreDumpError::UnsupportedElfCore(self.core_type)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{InstructionSet, RegisterValue};
+
+    #[test]
+    fn arm_core_file_layout() {
+        let dump = CoreDump {
+            registers: HashMap::from([
+                (RegisterId(0), RegisterValue::U32(0x1234)),
+                (RegisterId(15), RegisterValue::U32(0x0800_0100)),
+            ]),
+            data: vec![(0x2000_0000..0x2000_0004, vec![1, 2, 3, 4])],
+            instruction_set: InstructionSet::Thumb2,
+            supports_native_64bit_access: false,
+            core_type: CoreType::Armv7m,
+            fpu_support: false,
+            floating_point_register_count: None,
+        };
+
+        let mut elf = Vec::new();
+        dump.write_elf(&mut elf).unwrap();
+
+        let note_offset = ELF_HEADER_SIZE + 2 * PROGRAM_HEADER_SIZE;
+        let prstatus_size = PRSTATUS_HEADER_SIZE + 18 * 4 + 4;
+        assert_eq!(elf.len(), note_offset + 20 + prstatus_size + 4);
+        assert_eq!(&elf[18..20], &EM_ARM.to_le_bytes());
+
+        let registers = note_offset + 20 + PRSTATUS_HEADER_SIZE;
+        assert_eq!(&elf[registers..registers + 4], &0x1234u32.to_le_bytes());
+        assert_eq!(
+            &elf[registers + 15 * 4..registers + 16 * 4],
+            &0x0800_0100u32.to_le_bytes()
+        );
+
+        // The memory follows the note, and its segment points at it.
+        assert_eq!(&elf[elf.len() - 4..], &[1, 2, 3, 4]);
+        let load_header = ELF_HEADER_SIZE + PROGRAM_HEADER_SIZE;
+        let load_offset =
+            u32::from_le_bytes(elf[load_header + 4..load_header + 8].try_into().unwrap());
+        assert_eq!(load_offset as usize, elf.len() - 4);
+    }
+}
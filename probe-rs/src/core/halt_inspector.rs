@@ -0,0 +1,285 @@
+//! Classification of halts by inspecting the state of the halted core.
+
+use std::fmt;
+use std::ops::Range;
+
+use object::{Object, ObjectSymbol, SymbolKind};
+
+use super::{FrameRegisters, HaltReason, Watchpoint, WatchpointKind};
+use crate::{Core, Error, MemoryInterface};
+
+/// Classifies why a core halted, beyond what the debug hardware reports.
+///
+/// Inspectors are registered with
+/// [`Session::add_halt_inspector`](crate::Session::add_halt_inspector), and run by
+/// [`Session::inspect_halt`](crate::Session::inspect_halt).
+pub trait HaltInspector: fmt::Debug + Send + Sync {
+    /// Inspects the halted `core`, which reported `reason` for the halt.
+    ///
+    /// Returns `None` if the halt is not one this inspector recognizes.
+    fn inspect(
+        &self,
+        core: &mut Core<'_>,
+        reason: HaltReason,
+    ) -> Result<Option<HaltInspection>, Error>;
+}
+
+/// What a [`HaltInspector`] found out about a halt.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HaltInspection {
+    /// What kind of stop was detected, e.g. `panic`.
+    pub kind: &'static str,
+    /// A description of the stop, e.g. the panic message.
+    pub summary: String,
+    /// Further information as pairs of names and values.
+    pub details: Vec<(String, String)>,
+}
+
+impl fmt::Display for HaltInspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.kind, self.summary)?;
+        for (name, value) in &self.details {
+            write!(f, "\n    {name}: {value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Functions that are only called when the firmware gives up.
+const PANIC_FUNCTIONS: &[&str] = &["rust_begin_unwind", "panic_abort", "abort"];
+
+/// The longest abort message of ESP-IDF that is read.
+const MAX_ABORT_DETAILS: usize = 256;
+
+/// Detects Rust panics and ESP-IDF aborts, using the symbols of the firmware.
+///
+/// A panic is detected if the core halted in, or right after calling, one of the panic handlers.
+/// ESP-IDF records the reason for an abort in `g_panic_abort_details`, which is reported as the
+/// message.
+#[derive(Debug, Clone, Default)]
+pub struct PanicInspector {
+    functions: Vec<(String, Range<u64>)>,
+    abort_flag: Option<u64>,
+    abort_details: Option<u64>,
+}
+
+impl PanicInspector {
+    /// Looks up the panic handlers in the ELF file `data`.
+    pub fn from_elf(data: &[u8]) -> Result<Self, object::read::Error> {
+        let file = object::File::parse(data)?;
+
+        let mut inspector = Self::default();
+        for symbol in file.symbols() {
+            let Ok(name) = symbol.name() else {
+                continue;
+            };
+
+            match name {
+                "g_panic_abort" => inspector.abort_flag = Some(symbol.address()),
+                "g_panic_abort_details" => inspector.abort_details = Some(symbol.address()),
+                _ if symbol.kind() == SymbolKind::Text && PANIC_FUNCTIONS.contains(&name) => {
+                    // Clear the Thumb bit of ARM functions.
+                    let start = symbol.address() & !1;
+                    let end = start + symbol.size().max(1);
+                    inspector.functions.push((name.to_string(), start..end));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(inspector)
+    }
+
+    /// Returns the panic handler containing `address`.
+    fn function_at(&self, address: u64) -> Option<&str> {
+        self.functions
+            .iter()
+            .find(|(_, range)| range.contains(&address))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// Reads the abort message of ESP-IDF, if the firmware aborted.
+    fn abort_message(&self, core: &mut Core<'_>) -> Result<Option<String>, Error> {
+        let (Some(flag), Some(details)) = (self.abort_flag, self.abort_details) else {
+            return Ok(None);
+        };
+
+        if core.read_word_8(flag)? == 0 {
+            return Ok(None);
+        }
+
+        let address = core.read_word_32(details)?;
+        if address == 0 {
+            return Ok(Some("abort() was called".to_string()));
+        }
+
+        let mut message = Vec::new();
+        let mut chunk = [0; 32];
+        while message.len() < MAX_ABORT_DETAILS {
+            core.read_8(address as u64 + message.len() as u64, &mut chunk)?;
+            match chunk.iter().position(|&byte| byte == 0) {
+                Some(end) => {
+                    message.extend_from_slice(&chunk[..end]);
+                    break;
+                }
+                None => message.extend_from_slice(&chunk),
+            }
+        }
+
+        Ok(Some(String::from_utf8_lossy(&message).into_owned()))
+    }
+}
+
+impl HaltInspector for PanicInspector {
+    fn inspect(
+        &self,
+        core: &mut Core<'_>,
+        _reason: HaltReason,
+    ) -> Result<Option<HaltInspection>, Error> {
+        if let Some(message) = self.abort_message(core)? {
+            return Ok(Some(HaltInspection {
+                kind: "abort",
+                summary: message,
+                details: Vec::new(),
+            }));
+        }
+
+        let FrameRegisters { pc, ra, .. } = core.read_frame_registers()?;
+
+        let function = self.function_at(pc).or_else(|| self.function_at(ra & !1));
+        Ok(function.map(|function| HaltInspection {
+            kind: "panic",
+            summary: format!("the firmware panicked in `{function}`"),
+            details: vec![("pc".to_string(), format!("{pc:#010x}"))],
+        }))
+    }
+}
+
+/// Symbols marking the lowest address of the stack, which grows down towards it.
+const STACK_END_SYMBOLS: &[&str] = &["_stack_end", "_stack_end_cpu0"];
+
+/// The number of bytes at the end of the stack which are watched by a [`StackGuard`].
+const STACK_GUARD_LENGTH: u64 = 4;
+
+/// Detects stack overflows with a hardware watchpoint on the lowest word of the stack.
+///
+/// Once armed, the core halts when the last word of the stack is written, before the memory
+/// below the stack is corrupted. As an inspector, the guard reports these halts as a stack
+/// overflow if the stack pointer reached the guard, so writes to the guard by other code are
+/// not mistaken for one. [`Session::enable_stack_guard`](crate::Session::enable_stack_guard)
+/// does both.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StackGuard {
+    address: u64,
+}
+
+impl StackGuard {
+    /// Creates a guard for a stack whose lowest address is `stack_end`.
+    pub fn new(stack_end: u64) -> Self {
+        Self {
+            address: stack_end.next_multiple_of(STACK_GUARD_LENGTH),
+        }
+    }
+
+    /// Looks up the end of the stack in the ELF file `data`, using the `_stack_end` symbol of
+    /// `cortex-m-rt` or the `_stack_end_cpu0` symbol of `esp-hal`.
+    ///
+    /// Returns `None` if the firmware defines neither symbol.
+    pub fn from_elf(data: &[u8]) -> Result<Option<Self>, object::read::Error> {
+        let file = object::File::parse(data)?;
+
+        let stack_end = STACK_END_SYMBOLS.iter().find_map(|name| {
+            file.symbols()
+                .find(|symbol| symbol.name().is_ok_and(|symbol_name| symbol_name == *name))
+                .map(|symbol| symbol.address())
+        });
+
+        Ok(stack_end.map(Self::new))
+    }
+
+    /// The address of the watched word.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    /// Sets a watchpoint on the guard, which halts `core` when the guard is written.
+    pub fn arm(&self, core: &mut Core<'_>) -> Result<(), Error> {
+        core.set_hw_watchpoint(self.address, STACK_GUARD_LENGTH, WatchpointKind::Write)
+    }
+
+    /// Clears the watchpoint set by [`StackGuard::arm`].
+    pub fn disarm(&self, core: &mut Core<'_>) -> Result<(), Error> {
+        core.clear_hw_watchpoint(self.address)
+    }
+
+    /// Returns whether the watchpoint of the guard is set in `unit`, or in any unit if the
+    /// architecture doesn't report which watchpoint triggered.
+    fn is_armed(&self, core: &mut Core<'_>, unit: Option<usize>) -> Result<bool, Error> {
+        let watchpoints = core.hw_watchpoints()?;
+        let is_guard = |watchpoint: &Option<Watchpoint>| matches!(watchpoint, Some(watchpoint) if watchpoint.address == self.address);
+
+        Ok(match unit {
+            Some(unit) => watchpoints.get(unit).is_some_and(is_guard),
+            None => watchpoints.iter().any(is_guard),
+        })
+    }
+}
+
+impl HaltInspector for StackGuard {
+    fn inspect(
+        &self,
+        core: &mut Core<'_>,
+        reason: HaltReason,
+    ) -> Result<Option<HaltInspection>, Error> {
+        let unit = match reason {
+            HaltReason::Watchpoint(unit) => unit,
+            HaltReason::Multiple(_) => None,
+            _ => return Ok(None),
+        };
+
+        if !self.is_armed(core, unit)? {
+            return Ok(None);
+        }
+
+        // The stack is written at or above the stack pointer, so the stack can only have
+        // written the guard if the stack pointer is below its end.
+        let FrameRegisters { pc, sp, .. } = core.read_frame_registers()?;
+        if sp >= self.address + STACK_GUARD_LENGTH {
+            return Ok(None);
+        }
+
+        Ok(Some(HaltInspection {
+            kind: "stack overflow",
+            summary: format!("the stack grew into its guard at {:#010x}", self.address),
+            details: vec![
+                ("pc".to_string(), format!("{pc:#010x}")),
+                ("sp".to_string(), format!("{sp:#010x}")),
+            ],
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inspection_display() {
+        let inspection = HaltInspection {
+            kind: "panic",
+            summary: "the firmware panicked in `rust_begin_unwind`".to_string(),
+            details: vec![("pc".to_string(), "0x08000100".to_string())],
+        };
+
+        assert_eq!(
+            inspection.to_string(),
+            "panic: the firmware panicked in `rust_begin_unwind`\n    pc: 0x08000100"
+        );
+    }
+
+    #[test]
+    fn stack_guard_is_inside_the_stack() {
+        assert_eq!(StackGuard::new(0x2000_0000).address(), 0x2000_0000);
+        assert_eq!(StackGuard::new(0x3ffb_0002).address(), 0x3ffb_0004);
+    }
+}
@@ -0,0 +1,231 @@
+//! Heap usage of the firmware, read from the state of its allocator, see [`TargetHeap`].
+
+use anyhow::anyhow;
+use object::{Object, ObjectSymbol};
+
+use crate::{Core, Error, MemoryInterface};
+
+/// The most free blocks that are walked before the free list is considered corrupted.
+const MAX_FREE_BLOCKS: usize = 4096;
+
+/// Where an allocator keeps its state, relative to the address of its static and to the free
+/// blocks of the heap. All fields are 32-bit words.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocatorLayout {
+    /// The offset of the number of allocated bytes.
+    pub used: u64,
+    /// The offset of the start address of the heap.
+    pub bottom: u64,
+    /// The offset of the end address of the heap.
+    pub top: u64,
+    /// The offset of the pointer to the first free block, which is zero if there is none.
+    pub first_free: u64,
+    /// The offset of the size of a free block, within the block.
+    pub free_size: u64,
+    /// The offset of the pointer to the next free block, within the block.
+    pub free_next: u64,
+}
+
+impl AllocatorLayout {
+    /// The heap of `linked_list_allocator` in a `Mutex<RefCell<_>>`, on 32-bit targets.
+    ///
+    /// This is the `LlffHeap` of `embedded-alloc`, which was called `Heap` before 0.6, and the
+    /// `EspHeap` of `esp-alloc` before 0.5. Rust doesn't guarantee the layout of these types,
+    /// so a custom layout can be needed for other compiler versions.
+    pub const LINKED_LIST_ALLOCATOR: Self = Self {
+        used: 4,
+        first_free: 12,
+        bottom: 16,
+        top: 20,
+        free_size: 0,
+        free_next: 4,
+    };
+}
+
+/// A snapshot of the usage of a heap, as read by [`TargetHeap::stats`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct HeapStats {
+    /// The size of the heap in bytes.
+    pub size: u64,
+    /// The number of allocated bytes, as counted by the allocator.
+    pub used: u64,
+    /// The number of bytes in free blocks.
+    pub free: u64,
+    /// The number of free blocks.
+    pub free_blocks: usize,
+    /// The size of the largest free block, which bounds the largest allocation that can
+    /// succeed.
+    pub largest_free_block: u64,
+}
+
+impl HeapStats {
+    /// The fragmentation of the free memory, from 0.0 if it is a single block, towards 1.0 as
+    /// it is split into smaller blocks.
+    pub fn fragmentation(&self) -> f64 {
+        if self.free == 0 {
+            return 0.0;
+        }
+
+        1.0 - self.largest_free_block as f64 / self.free as f64
+    }
+}
+
+/// The heap of the firmware, described by the static of its allocator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetHeap {
+    address: u64,
+    layout: AllocatorLayout,
+}
+
+impl TargetHeap {
+    /// Describes the allocator static at `address`, whose state is laid out as in `layout`.
+    pub fn new(address: u64, layout: AllocatorLayout) -> Self {
+        Self { address, layout }
+    }
+
+    /// Looks up the allocator static `path` in the ELF file `data`, e.g. `HEAP` or
+    /// `firmware::HEAP`.
+    ///
+    /// Unmangled symbols and symbols mangled with the legacy scheme of Rust are matched by the
+    /// end of their path. Returns `None` if no symbol matches.
+    pub fn from_elf(
+        data: &[u8],
+        path: &str,
+        layout: AllocatorLayout,
+    ) -> Result<Option<Self>, object::read::Error> {
+        let file = object::File::parse(data)?;
+
+        let suffix = format!("::{path}");
+        let address = file
+            .symbols()
+            .find(|symbol| {
+                symbol.name().is_ok_and(|name| {
+                    let symbol_path = symbol_path(name);
+                    symbol_path == path || symbol_path.ends_with(&suffix)
+                })
+            })
+            .map(|symbol| symbol.address());
+
+        Ok(address.map(|address| Self::new(address, layout)))
+    }
+
+    /// The address of the allocator static.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    /// Reads the state of the allocator through `core`, and walks the free blocks of the heap.
+    ///
+    /// The core may be running, in which case the allocator can change the heap while it is
+    /// read. Free lists changed in the middle of the walk are detected in most cases, and
+    /// reported as an error. Reading the stats again usually succeeds.
+    pub fn stats(&self, core: &mut Core<'_>) -> Result<HeapStats, Error> {
+        let layout = &self.layout;
+        let mut read = |address: u64| core.read_word_32(address).map(u64::from);
+
+        let used = read(self.address + layout.used)?;
+        let bottom = read(self.address + layout.bottom)?;
+        let top = read(self.address + layout.top)?;
+
+        let mut stats = HeapStats {
+            size: top.saturating_sub(bottom),
+            used,
+            free: 0,
+            free_blocks: 0,
+            largest_free_block: 0,
+        };
+
+        // The free blocks are sorted by address, and lie within the heap.
+        let mut block = read(self.address + layout.first_free)?;
+        let mut previous_end = bottom;
+        while block != 0 {
+            if stats.free_blocks == MAX_FREE_BLOCKS || block < previous_end || block >= top {
+                return Err(inconsistent_free_list(block));
+            }
+
+            let size = read(block + layout.free_size)?;
+            if block + size > top {
+                return Err(inconsistent_free_list(block));
+            }
+
+            stats.free += size;
+            stats.free_blocks += 1;
+            stats.largest_free_block = stats.largest_free_block.max(size);
+
+            previous_end = block + size;
+            block = read(block + layout.free_next)?;
+        }
+
+        Ok(stats)
+    }
+}
+
+fn inconsistent_free_list(block: u64) -> Error {
+    Error::Other(anyhow!(
+        "The free list of the heap is inconsistent at {block:#010x}, it was either changed while it was read or is corrupted"
+    ))
+}
+
+/// Returns the path of the symbol `name` mangled with the legacy scheme of Rust, without its
+/// hash, e.g. `firmware::HEAP` for `_ZN8firmware4HEAP17h0123456789abcdefE`. Other names are
+/// returned unchanged.
+fn symbol_path(name: &str) -> String {
+    let Some(mut rest) = name.strip_prefix("_ZN") else {
+        return name.to_string();
+    };
+
+    let mut segments = Vec::new();
+    while let Some(digits) = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .filter(|&end| end > 0)
+    {
+        let Ok(length) = rest[..digits].parse::<usize>() else {
+            break;
+        };
+        let Some(segment) = rest.get(digits..digits + length) else {
+            break;
+        };
+
+        segments.push(segment);
+        rest = &rest[digits + length..];
+    }
+
+    if segments
+        .last()
+        .is_some_and(|hash| hash.len() == 17 && hash.starts_with('h'))
+    {
+        segments.pop();
+    }
+
+    segments.join("::")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn symbol_paths() {
+        assert_eq!(
+            symbol_path("_ZN8firmware4HEAP17h0123456789abcdefE"),
+            "firmware::HEAP"
+        );
+        assert_eq!(symbol_path("HEAP"), "HEAP");
+    }
+
+    #[test]
+    fn fragmentation() {
+        let mut stats = HeapStats {
+            size: 1024,
+            used: 512,
+            free: 512,
+            free_blocks: 1,
+            largest_free_block: 512,
+        };
+        assert_eq!(stats.fragmentation(), 0.0);
+
+        stats.free_blocks = 4;
+        stats.largest_free_block = 128;
+        assert_eq!(stats.fragmentation(), 0.75);
+    }
+}
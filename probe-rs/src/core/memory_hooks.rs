@@ -0,0 +1,263 @@
+//! Hooks which veto or transform the memory accesses of the cores, see
+//! [`Session::add_memory_hook`].
+//!
+//! [`Session::add_memory_hook`]: crate::Session::add_memory_hook
+
+use std::borrow::Cow;
+use std::fmt;
+use std::ops::Range;
+use std::sync::Arc;
+
+use crate::Error;
+
+/// Intercepts the memory accesses done through the [`Core`](crate::Core)s of a session, e.g. to
+/// redact secrets from reads or to protect a bootloader from writes.
+///
+/// Hooks are registered with [`Session::add_memory_hook`](crate::Session::add_memory_hook), and
+/// apply to all architectures. Accesses are described as little endian bytes, regardless of the
+/// width of the access.
+pub trait MemoryHook: fmt::Debug + Send + Sync {
+    /// Called before `len` bytes at `address` of the core `core` are read. Returning an error
+    /// vetoes the read.
+    fn before_read(&self, _core: usize, _address: u64, _len: usize) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Called with the `data` read from `address` of the core `core`, which may be modified
+    /// before it is returned to the caller.
+    fn after_read(&self, _core: usize, _address: u64, _data: &mut [u8]) {}
+
+    /// Called before `data` is written to `address` of the core `core`. Returning an error
+    /// vetoes the write, and modifying `data` changes what is written.
+    ///
+    /// This is also called before flash is programmed, with the flash address of the page, and
+    /// before flash is erased, with the erased bytes of the range.
+    fn before_write(&self, _core: usize, _address: u64, _data: &mut [u8]) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Returns the offsets of the bytes of `range` within the `len` bytes at `address`.
+fn overlap(range: &Range<u64>, address: u64, len: usize) -> Option<Range<usize>> {
+    let start = range.start.max(address);
+    let end = range.end.min(address + len as u64);
+
+    (start < end).then(|| (start - address) as usize..(end - address) as usize)
+}
+
+/// Replaces the data read from a memory range, e.g. OTP memory or eFuses holding keys.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedactedRange {
+    range: Range<u64>,
+    fill: u8,
+}
+
+impl RedactedRange {
+    /// Creates a hook which reads the bytes in `range` as zeros.
+    pub fn new(range: Range<u64>) -> Self {
+        Self { range, fill: 0 }
+    }
+
+    /// Sets the value the redacted bytes are read as.
+    pub fn with_fill(mut self, fill: u8) -> Self {
+        self.fill = fill;
+        self
+    }
+}
+
+impl MemoryHook for RedactedRange {
+    fn after_read(&self, _core: usize, address: u64, data: &mut [u8]) {
+        if let Some(redacted) = overlap(&self.range, address, data.len()) {
+            data[redacted].fill(self.fill);
+        }
+    }
+}
+
+/// Rejects writes to a memory range, e.g. the bootloader of the chip.
+///
+/// This includes programming and erasing flash in the range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WriteProtectedRange {
+    range: Range<u64>,
+}
+
+impl WriteProtectedRange {
+    /// Creates a hook which rejects writes that touch `range`.
+    pub fn new(range: Range<u64>) -> Self {
+        Self { range }
+    }
+}
+
+impl MemoryHook for WriteProtectedRange {
+    fn before_write(&self, _core: usize, address: u64, data: &mut [u8]) -> Result<(), Error> {
+        match overlap(&self.range, address, data.len()) {
+            Some(protected) => Err(Error::MemoryAccessDenied {
+                address: address + protected.start as u64,
+                reason: format!("{:#010x?} is write protected", self.range),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A word of a memory access, which is passed to the hooks as little endian bytes.
+pub(crate) trait HookWord: Copy {
+    fn to_bytes(words: &[Self]) -> Vec<u8>;
+    fn from_bytes(bytes: &[u8], words: &mut [Self]);
+}
+
+macro_rules! hook_word {
+    ($($word:ty),*) => {
+        $(
+            impl HookWord for $word {
+                fn to_bytes(words: &[Self]) -> Vec<u8> {
+                    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+                }
+
+                fn from_bytes(bytes: &[u8], words: &mut [Self]) {
+                    for (word, chunk) in words
+                        .iter_mut()
+                        .zip(bytes.chunks_exact(std::mem::size_of::<$word>()))
+                    {
+                        *word = <$word>::from_le_bytes(chunk.try_into().unwrap());
+                    }
+                }
+            }
+        )*
+    };
+}
+
+hook_word!(u8, u32, u64);
+
+/// The memory hooks of a session, which are applied in the order they were added.
+#[derive(Debug, Default)]
+pub(crate) struct MemoryHooks {
+    hooks: Vec<Arc<dyn MemoryHook>>,
+}
+
+impl MemoryHooks {
+    /// Adds a hook, which sees the accesses after the hooks added before it.
+    pub fn add(&mut self, hook: Arc<dyn MemoryHook>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn before_read(&self, core: usize, address: u64, len: usize) -> Result<(), Error> {
+        for hook in &self.hooks {
+            hook.before_read(core, address, len)?;
+        }
+        Ok(())
+    }
+
+    pub fn after_read<W: HookWord>(&self, core: usize, address: u64, data: &mut [W]) {
+        if self.hooks.is_empty() {
+            return;
+        }
+
+        let mut bytes = W::to_bytes(data);
+        for hook in &self.hooks {
+            hook.after_read(core, address, &mut bytes);
+        }
+        W::from_bytes(&bytes, data);
+    }
+
+    /// Returns the data that is written instead of `data`.
+    pub fn before_write<'data, W: HookWord>(
+        &self,
+        core: usize,
+        address: u64,
+        data: &'data [W],
+    ) -> Result<Cow<'data, [W]>, Error> {
+        if self.hooks.is_empty() {
+            return Ok(Cow::Borrowed(data));
+        }
+
+        let mut bytes = W::to_bytes(data);
+        for hook in &self.hooks {
+            hook.before_write(core, address, &mut bytes)?;
+        }
+
+        let mut written = data.to_vec();
+        W::from_bytes(&bytes, &mut written);
+        Ok(Cow::Owned(written))
+    }
+
+    /// Checks that `range` may be erased, by passing it to the hooks as a write of `erased`
+    /// bytes.
+    pub fn before_erase(&self, core: usize, range: Range<u64>, erased: u8) -> Result<(), Error> {
+        // Check large ranges in chunks, to not allocate the size of the whole flash.
+        const CHUNK_SIZE: u64 = 0x1_0000;
+
+        if self.hooks.is_empty() {
+            return Ok(());
+        }
+
+        let mut address = range.start;
+        while address < range.end {
+            let len = CHUNK_SIZE.min(range.end - address) as usize;
+            for hook in &self.hooks {
+                hook.before_write(core, address, &mut vec![erased; len])?;
+            }
+            address += len as u64;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hooks() -> MemoryHooks {
+        let mut hooks = MemoryHooks::default();
+        hooks.add(Arc::new(RedactedRange::new(0x1002..0x1006).with_fill(0xFF)));
+        hooks.add(Arc::new(WriteProtectedRange::new(0x0..0x1000)));
+        hooks
+    }
+
+    #[test]
+    fn reads_are_redacted() {
+        let hooks = hooks();
+
+        let mut words = [0x1111_1111u32, 0x2222_2222];
+        hooks.after_read(0, 0x1000, &mut words);
+        assert_eq!(words, [0xFFFF_1111, 0x2222_FFFF]);
+
+        let mut bytes = [0u8; 2];
+        hooks.after_read(0, 0x1006, &mut bytes);
+        assert_eq!(bytes, [0, 0]);
+    }
+
+    #[test]
+    fn protected_writes_are_rejected() {
+        let hooks = hooks();
+
+        let error = hooks.before_write(0, 0xFFC, &[0u32, 0]).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::MemoryAccessDenied { address: 0xFFC, .. }
+        ));
+
+        let written = hooks.before_write(0, 0x1000, &[1u64]).unwrap();
+        assert_eq!(&written[..], [1]);
+    }
+
+    #[test]
+    fn protected_erases_are_rejected() {
+        let hooks = hooks();
+
+        let error = hooks.before_erase(0, 0x0..0x40_0000, 0xFF).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::MemoryAccessDenied { address: 0, .. }
+        ));
+
+        let error = hooks.before_erase(0, 0xF00..0x2000, 0xFF).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::MemoryAccessDenied { address: 0xF00, .. }
+        ));
+
+        hooks.before_erase(0, 0x1000..0x40_0000, 0xFF).unwrap();
+    }
+}
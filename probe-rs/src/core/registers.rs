@@ -15,6 +15,13 @@ pub enum RegisterDataType {
     UnsignedInteger(usize),
     /// Floating point data, with size in bits encapsulated.
     FloatingPoint(usize),
+    /// A SIMD vector of floating point lanes.
+    Vector {
+        /// The size of each lane, in bits.
+        lane_bits: usize,
+        /// The number of lanes.
+        lanes: usize,
+    },
 }
 
 /// This is used to label the register with a specific role that it plays during program execution and exception handling.
@@ -168,6 +175,7 @@ impl CoreRegister {
         match self.data_type() {
             RegisterDataType::UnsignedInteger(size_in_bits) => size_in_bits,
             RegisterDataType::FloatingPoint(size_in_bits) => size_in_bits,
+            RegisterDataType::Vector { lane_bits, lanes } => lane_bits * lanes,
         }
     }
 
@@ -236,6 +244,10 @@ pub enum RegisterValue {
     U64(u64),
     /// 128-bit unsigned integer, often used with SIMD / FP
     U128(u128),
+    /// 32-bit floating point value
+    F32(f32),
+    /// 64-bit floating point value
+    F64(f64),
 }
 
 impl RegisterValue {
@@ -278,6 +290,10 @@ impl RegisterValue {
                     )))
                 }
             }
+            RegisterValue::F32(_) | RegisterValue::F64(_) => Err(Error::Other(anyhow!(
+                "Floating point register value {} is not an address",
+                self
+            ))),
         }
     }
 
@@ -287,15 +303,62 @@ impl RegisterValue {
             RegisterValue::U32(register_value) => *register_value == u32::MAX,
             RegisterValue::U64(register_value) => *register_value == u64::MAX,
             RegisterValue::U128(register_value) => *register_value == u128::MAX,
+            RegisterValue::F32(register_value) => *register_value == f32::MAX,
+            RegisterValue::F64(register_value) => *register_value == f64::MAX,
         }
     }
 
     /// A helper function to determine if the contained register value is zero.
     pub fn is_zero(&self) -> bool {
-        matches!(
-            self,
-            RegisterValue::U32(0) | RegisterValue::U64(0) | RegisterValue::U128(0)
-        )
+        self.raw_bits() == 0
+    }
+
+    /// Returns the bits of the value as stored in the register.
+    fn raw_bits(&self) -> u128 {
+        match self {
+            RegisterValue::U32(value) => *value as u128,
+            RegisterValue::U64(value) => *value as u128,
+            RegisterValue::U128(value) => *value,
+            RegisterValue::F32(value) => value.to_bits() as u128,
+            RegisterValue::F64(value) => value.to_bits() as u128,
+        }
+    }
+
+    /// Interprets the value as the natural type of a register with the given data type, e.g.
+    /// as a float for floating point registers.
+    ///
+    /// Values of integer registers, and of data types without a matching representation, are
+    /// returned unchanged.
+    pub fn to_typed(self, data_type: &RegisterDataType) -> RegisterValue {
+        match data_type {
+            RegisterDataType::FloatingPoint(32) => {
+                RegisterValue::F32(f32::from_bits(self.raw_bits() as u32))
+            }
+            RegisterDataType::FloatingPoint(64) => {
+                RegisterValue::F64(f64::from_bits(self.raw_bits() as u64))
+            }
+            _ => self,
+        }
+    }
+
+    /// Splits the value into `lanes` lanes of `lane_bits` bits each, the first lane holding
+    /// the least significant bits. 32 and 64-bit lanes are interpreted as floats.
+    pub fn vector_lanes(&self, lane_bits: usize, lanes: usize) -> Vec<RegisterValue> {
+        let raw = self.raw_bits();
+        let mask = u128::MAX
+            .checked_shr(128u32.saturating_sub(lane_bits as u32))
+            .unwrap_or(0);
+
+        (0..lanes)
+            .map(|lane| {
+                let bits = raw.checked_shr((lane * lane_bits) as u32).unwrap_or(0) & mask;
+                match lane_bits {
+                    32 => RegisterValue::F32(f32::from_bits(bits as u32)),
+                    64 => RegisterValue::F64(f64::from_bits(bits as u64)),
+                    _ => RegisterValue::U128(bits),
+                }
+            })
+            .collect()
     }
 }
 
@@ -308,33 +371,21 @@ impl Default for RegisterValue {
 
 impl PartialOrd for RegisterValue {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        let self_value = match self {
-            RegisterValue::U32(self_value) => *self_value as u128,
-            RegisterValue::U64(self_value) => *self_value as u128,
-            RegisterValue::U128(self_value) => *self_value,
-        };
-        let other_value = match other {
-            RegisterValue::U32(other_value) => *other_value as u128,
-            RegisterValue::U64(other_value) => *other_value as u128,
-            RegisterValue::U128(other_value) => *other_value,
-        };
-        self_value.partial_cmp(&other_value)
+        match (self, other) {
+            (RegisterValue::F32(self_value), RegisterValue::F32(other_value)) => {
+                self_value.partial_cmp(other_value)
+            }
+            (RegisterValue::F64(self_value), RegisterValue::F64(other_value)) => {
+                self_value.partial_cmp(other_value)
+            }
+            _ => self.raw_bits().partial_cmp(&other.raw_bits()),
+        }
     }
 }
 
 impl PartialEq for RegisterValue {
     fn eq(&self, other: &Self) -> bool {
-        let self_value = match self {
-            RegisterValue::U32(self_value) => *self_value as u128,
-            RegisterValue::U64(self_value) => *self_value as u128,
-            RegisterValue::U128(self_value) => *self_value,
-        };
-        let other_value = match other {
-            RegisterValue::U32(other_value) => *other_value as u128,
-            RegisterValue::U64(other_value) => *other_value as u128,
-            RegisterValue::U128(other_value) => *other_value,
-        };
-        self_value == other_value
+        self.raw_bits() == other.raw_bits()
     }
 }
 
@@ -344,6 +395,8 @@ impl core::fmt::Display for RegisterValue {
             RegisterValue::U32(register_value) => write!(f, "{register_value:#010x}"),
             RegisterValue::U64(register_value) => write!(f, "{register_value:#018x}"),
             RegisterValue::U128(register_value) => write!(f, "{register_value:#034x}"),
+            RegisterValue::F32(register_value) => write!(f, "{register_value}"),
+            RegisterValue::F64(register_value) => write!(f, "{register_value}"),
         }
     }
 }
@@ -366,6 +419,18 @@ impl From<u128> for RegisterValue {
     }
 }
 
+impl From<f32> for RegisterValue {
+    fn from(val: f32) -> Self {
+        Self::F32(val)
+    }
+}
+
+impl From<f64> for RegisterValue {
+    fn from(val: f64) -> Self {
+        Self::F64(val)
+    }
+}
+
 impl TryInto<u32> for RegisterValue {
     type Error = crate::Error;
 
@@ -378,6 +443,11 @@ impl TryInto<u32> for RegisterValue {
             Self::U128(v) => v
                 .try_into()
                 .map_err(|_| crate::Error::Other(anyhow!("Value '{}' too large for u32", v))),
+            Self::F32(v) => Ok(v.to_bits()),
+            Self::F64(v) => Err(crate::Error::Other(anyhow!(
+                "Value '{}' too large for u32",
+                v
+            ))),
         }
     }
 }
@@ -392,6 +462,8 @@ impl TryInto<u64> for RegisterValue {
             Self::U128(v) => v
                 .try_into()
                 .map_err(|_| crate::Error::Other(anyhow!("Value '{}' too large for u64", v))),
+            Self::F32(v) => Ok(v.to_bits().into()),
+            Self::F64(v) => Ok(v.to_bits()),
         }
     }
 }
@@ -404,6 +476,38 @@ impl TryInto<u128> for RegisterValue {
             Self::U32(v) => Ok(v.into()),
             Self::U64(v) => Ok(v.into()),
             Self::U128(v) => Ok(v),
+            Self::F32(v) => Ok(v.to_bits().into()),
+            Self::F64(v) => Ok(v.to_bits().into()),
+        }
+    }
+}
+
+impl TryInto<f32> for RegisterValue {
+    type Error = crate::Error;
+
+    fn try_into(self) -> Result<f32, Self::Error> {
+        match self {
+            Self::F32(v) => Ok(v),
+            Self::U32(v) => Ok(f32::from_bits(v)),
+            other => Err(crate::Error::Other(anyhow!(
+                "Value '{}' is not a 32-bit float",
+                other
+            ))),
+        }
+    }
+}
+
+impl TryInto<f64> for RegisterValue {
+    type Error = crate::Error;
+
+    fn try_into(self) -> Result<f64, Self::Error> {
+        match self {
+            Self::F64(v) => Ok(v),
+            Self::U64(v) => Ok(f64::from_bits(v)),
+            other => Err(crate::Error::Other(anyhow!(
+                "Value '{}' is not a 64-bit float",
+                other
+            ))),
         }
     }
 }
@@ -429,6 +533,50 @@ impl<T> RegisterValueResultExt<T> for Result<T, Infallible> {
     }
 }
 
+/// A snapshot of the registers of a halted core, taken with
+/// [`Core::save_context`](crate::Core::save_context).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoreContext {
+    registers: Vec<(RegisterId, RegisterValue)>,
+
+    /// The contents of a register file that is only partially visible through core registers,
+    /// like the physical address registers of windowed Xtensa cores.
+    register_file: Vec<u32>,
+}
+
+impl CoreContext {
+    /// Creates a snapshot holding the given register values.
+    pub fn new(registers: Vec<(RegisterId, RegisterValue)>) -> Self {
+        Self {
+            registers,
+            register_file: Vec::new(),
+        }
+    }
+
+    pub(crate) fn with_register_file(mut self, register_file: Vec<u32>) -> Self {
+        self.register_file = register_file;
+        self
+    }
+
+    /// Returns the saved registers along with their values.
+    pub fn registers(&self) -> impl Iterator<Item = (RegisterId, RegisterValue)> + '_ {
+        self.registers.iter().copied()
+    }
+
+    /// Returns the saved value of `register`, or `None` if it is not part of the snapshot.
+    pub fn register(&self, register: impl Into<RegisterId>) -> Option<RegisterValue> {
+        let register = register.into();
+        self.registers
+            .iter()
+            .find(|(id, _)| *id == register)
+            .map(|(_, value)| *value)
+    }
+
+    pub(crate) fn register_file(&self) -> &[u32] {
+        &self.register_file
+    }
+}
+
 /// A static array of all the registers ([`CoreRegister`]) that apply to a specific architecture.
 #[derive(Debug, PartialEq)]
 pub struct CoreRegisters(Vec<&'static CoreRegister>);
@@ -562,6 +710,38 @@ impl CoreRegisters {
             .cloned()
     }
 
+    /// Finds a register by name, ignoring case.
+    ///
+    /// Besides the names of the registers, like `R0`, `x10` or `a2`, this accepts the ABI names
+    /// of RISC-V registers, and the architecture independent aliases `pc`, `sp`, `fp`, `ra`, `lr`
+    /// and `psr`.
+    pub fn by_name(&self, name: &str) -> Option<&CoreRegister> {
+        let named = self.0.iter().find(|r| {
+            r.roles.iter().any(|role| match role {
+                RegisterRole::Core(n) | RegisterRole::Argument(n) | RegisterRole::Other(n) => {
+                    n.eq_ignore_ascii_case(name)
+                }
+                _ => false,
+            })
+        });
+
+        let alias = match name.to_ascii_lowercase().as_str() {
+            "pc" => Some(RegisterRole::ProgramCounter),
+            "sp" => Some(RegisterRole::StackPointer),
+            "fp" => Some(RegisterRole::FramePointer),
+            "ra" | "lr" => Some(RegisterRole::ReturnAddress),
+            "psr" => Some(RegisterRole::ProcessorStatus),
+            _ => None,
+        };
+
+        named
+            .or_else(|| {
+                let alias = alias?;
+                self.0.iter().find(|r| r.register_has_role(alias))
+            })
+            .cloned()
+    }
+
     /// The fpu status register.
     pub fn fpsr(&self) -> Option<&CoreRegister> {
         self.0
@@ -602,3 +782,51 @@ impl CoreRegisters {
             .nth(index)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_by_name() {
+        use crate::architecture::riscv::registers::RISCV_CORE_REGSISTERS;
+        use crate::architecture::xtensa::registers::XTENSA_CORE_REGSISTERS;
+
+        let id = |registers: &CoreRegisters, name| registers.by_name(name).map(|r| r.id());
+
+        assert_eq!(id(&RISCV_CORE_REGSISTERS, "x10"), Some(RegisterId(0x100a)));
+        assert_eq!(id(&RISCV_CORE_REGSISTERS, "A0"), Some(RegisterId(0x100a)));
+        assert_eq!(id(&RISCV_CORE_REGSISTERS, "sp"), Some(RegisterId(0x1002)));
+        assert_eq!(id(&RISCV_CORE_REGSISTERS, "ra"), Some(RegisterId(0x1001)));
+
+        assert_eq!(id(&XTENSA_CORE_REGSISTERS, "a0"), Some(RegisterId(0x0000)));
+        assert_eq!(id(&XTENSA_CORE_REGSISTERS, "ra"), Some(RegisterId(0x0000)));
+        assert_eq!(id(&XTENSA_CORE_REGSISTERS, "pc"), Some(RegisterId(0xff00)));
+        assert_eq!(id(&XTENSA_CORE_REGSISTERS, "ps"), Some(RegisterId(0xff01)));
+        assert_eq!(id(&XTENSA_CORE_REGSISTERS, "lr"), Some(RegisterId(0x0000)));
+        assert_eq!(id(&XTENSA_CORE_REGSISTERS, "nonexistent"), None);
+    }
+
+    #[test]
+    fn typed_register_values() {
+        let raw = RegisterValue::U32(1.5f32.to_bits());
+        let typed = raw.to_typed(&RegisterDataType::FloatingPoint(32));
+
+        assert!(matches!(typed, RegisterValue::F32(value) if value == 1.5));
+        assert_eq!(typed, raw);
+        assert_eq!(TryInto::<u32>::try_into(typed).unwrap(), 1.5f32.to_bits());
+
+        let integer = RegisterValue::U32(7).to_typed(&RegisterDataType::UnsignedInteger(32));
+        assert!(matches!(integer, RegisterValue::U32(7)));
+    }
+
+    #[test]
+    fn vector_lanes() {
+        let bits = (2.0f64.to_bits() as u128) << 64 | 1.0f64.to_bits() as u128;
+        let lanes = RegisterValue::U128(bits).vector_lanes(64, 2);
+
+        assert!(
+            matches!(lanes[..], [RegisterValue::F64(a), RegisterValue::F64(b)] if a == 1.0 && b == 2.0)
+        );
+    }
+}
@@ -0,0 +1,240 @@
+//! Smoke tests of the debug functionality of a core, run by [`Session::self_test`].
+//!
+//! [`Session::self_test`]: crate::Session::self_test
+
+use std::{fmt, ops::Range, time::Duration};
+
+use crate::{Core, CoreInterface, Error, MemoryInterface, RegisterValue};
+
+/// How long the core may take to halt.
+const HALT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// The pattern written to registers and memory.
+const PATTERN: u32 = 0xa55a_c33c;
+
+/// The number of bytes of scratch memory used by the memory check.
+pub(crate) const MEMORY_CHECK_SIZE: u64 = 16;
+
+/// The result of a single check of a [`SelfTestReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum SelfTestOutcome {
+    /// The check passed.
+    Passed,
+    /// The check failed, for the given reason.
+    Failed(String),
+    /// The check was not run, for the given reason.
+    Skipped(String),
+}
+
+/// A single check of a [`SelfTestReport`].
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestCheck {
+    /// What was checked.
+    pub name: &'static str,
+    /// The result of the check.
+    pub outcome: SelfTestOutcome,
+}
+
+/// The results of a smoke test of a core, to be rendered with [`fmt::Display`] or serialized.
+#[derive(Debug, Clone, Serialize)]
+pub struct SelfTestReport {
+    /// The index of the core.
+    pub core: usize,
+    /// The checks in the order they were run.
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Returns `true` if no check failed.
+    pub fn passed(&self) -> bool {
+        !self
+            .checks
+            .iter()
+            .any(|check| matches!(check.outcome, SelfTestOutcome::Failed(_)))
+    }
+
+    /// Runs the checks on `core`, using `scratch` for the memory checks if given.
+    ///
+    /// The core is reset and halted by the last check.
+    pub(crate) fn run(core: &mut Core<'_>, scratch: Option<Range<u64>>) -> Self {
+        let mut report = Self {
+            core: core.id(),
+            checks: Vec::new(),
+        };
+
+        report.check("halt", core.halt(HALT_TIMEOUT).map(drop));
+        if !report.passed() {
+            for name in ["registers", "memory", "breakpoints", "step", "reset"] {
+                report.skip(name, "the core could not be halted");
+            }
+            return report;
+        }
+
+        report.check("registers", check_registers(core));
+        match scratch {
+            Some(scratch) => report.check("memory", check_memory(core, scratch.start)),
+            None => report.skip("memory", "the target has no scratch region"),
+        }
+        report.check("breakpoints", check_breakpoints(core));
+        report.check("step", check_step(core));
+        report.check("reset", check_reset(core));
+
+        report
+    }
+
+    fn check(&mut self, name: &'static str, result: Result<(), Error>) {
+        let outcome = match result {
+            Ok(()) => SelfTestOutcome::Passed,
+            Err(error) => SelfTestOutcome::Failed(error.to_string()),
+        };
+        self.checks.push(SelfTestCheck { name, outcome });
+    }
+
+    fn skip(&mut self, name: &'static str, reason: &str) {
+        self.checks.push(SelfTestCheck {
+            name,
+            outcome: SelfTestOutcome::Skipped(reason.to_string()),
+        });
+    }
+}
+
+fn mismatch(what: impl fmt::Display, expected: impl fmt::Debug, actual: impl fmt::Debug) -> Error {
+    Error::Other(anyhow::anyhow!(
+        "{what}: wrote {expected:x?}, but read back {actual:x?}"
+    ))
+}
+
+/// Writes a pattern to the argument registers and reads it back, restoring the original values.
+fn check_registers(core: &mut Core<'_>) -> Result<(), Error> {
+    let registers = core.registers();
+
+    for index in 0..2 {
+        let Some(register) = registers.get_argument_register(index) else {
+            break;
+        };
+
+        let original: RegisterValue = core.read_core_reg(register.id())?;
+        let expected = PATTERN.rotate_left(index as u32 * 8);
+        core.write_core_reg(register.id(), expected)?;
+        let actual: u32 = core.read_core_reg(register.id())?;
+        core.write_core_reg(register.id(), original)?;
+
+        if actual != expected {
+            return Err(mismatch(register.name(), expected, actual));
+        }
+    }
+
+    Ok(())
+}
+
+/// Writes a pattern to memory at all alignments and reads it back.
+fn check_memory(core: &mut Core<'_>, address: u64) -> Result<(), Error> {
+    core.write_word_32(address, PATTERN)?;
+    let word = core.read_word_32(address)?;
+    if word != PATTERN {
+        return Err(mismatch(
+            format_args!("Word at {address:#010x}"),
+            PATTERN,
+            word,
+        ));
+    }
+
+    for offset in 0..4 {
+        for len in [1, 2, 3, 4, 5, 8] {
+            let address = address + offset;
+            let expected = (0..len)
+                .map(|i| (PATTERN >> (8 * ((offset as usize + i) % 4))) as u8 ^ len as u8)
+                .collect::<Vec<_>>();
+
+            core.write_8(address, &expected)?;
+            let mut actual = vec![0; len];
+            core.read_8(address, &mut actual)?;
+
+            if actual != expected {
+                return Err(mismatch(
+                    format_args!("{len} bytes at {address:#010x}"),
+                    expected,
+                    actual,
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets and clears a hardware breakpoint at the program counter.
+fn check_breakpoints(core: &mut Core<'_>) -> Result<(), Error> {
+    if core.available_breakpoint_units()? == 0 {
+        return Err(Error::Other(anyhow::anyhow!(
+            "The core has no hardware breakpoint units"
+        )));
+    }
+
+    let pc: u64 = core.read_core_reg(core.program_counter().id())?;
+    core.set_hw_breakpoint(pc)?;
+    let set = core.hw_breakpoints()?.contains(&Some(pc));
+    core.clear_hw_breakpoint(pc)?;
+    let cleared = !core.hw_breakpoints()?.contains(&Some(pc));
+
+    if !set || !cleared {
+        return Err(Error::Other(anyhow::anyhow!(
+            "The breakpoint at {pc:#010x} was not {}",
+            if set { "cleared" } else { "set" }
+        )));
+    }
+
+    Ok(())
+}
+
+/// Steps a single instruction and checks that the program counter moved.
+fn check_step(core: &mut Core<'_>) -> Result<(), Error> {
+    let pc: u64 = core.read_core_reg(core.program_counter().id())?;
+    let stepped = core.step()?;
+
+    if !core.core_halted()? {
+        return Err(Error::Other(anyhow::anyhow!(
+            "The core did not halt after the step"
+        )));
+    }
+    if stepped.pc == pc {
+        return Err(Error::Other(anyhow::anyhow!(
+            "The program counter stayed at {pc:#010x}"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Resets the core and checks that it halts.
+fn check_reset(core: &mut Core<'_>) -> Result<(), Error> {
+    core.reset_and_halt(HALT_TIMEOUT)?;
+
+    if !core.core_halted()? {
+        return Err(Error::Other(anyhow::anyhow!(
+            "The core did not halt after the reset"
+        )));
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for SelfTestOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelfTestOutcome::Passed => write!(f, "passed"),
+            SelfTestOutcome::Failed(reason) => write!(f, "FAILED: {reason}"),
+            SelfTestOutcome::Skipped(reason) => write!(f, "skipped: {reason}"),
+        }
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Self test of core {}:", self.core)?;
+        for check in &self.checks {
+            writeln!(f, "    {:<12} {}", check.name, check.outcome)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+//! Lightweight data tracing with watchpoints that resume the core automatically, see
+//! [`Session::add_trace_watchpoint`].
+//!
+//! [`Session::add_trace_watchpoint`]: crate::Session::add_trace_watchpoint
+
+use std::collections::VecDeque;
+use std::ops::Range;
+use std::time::Instant;
+
+use super::{RegisterId, RegisterValue, Watchpoint, WatchpointKind};
+use crate::{Architecture, Core, Error, MemoryInterface};
+
+/// The number of records kept by the session, unless set with
+/// [`Session::set_data_trace_capacity`](crate::Session::set_data_trace_capacity).
+const DEFAULT_CAPACITY: usize = 4096;
+
+/// A watchpoint which, instead of stopping the core, records a set of registers and memory
+/// ranges and lets the core continue.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceWatchpoint {
+    core: usize,
+    watchpoint: Watchpoint,
+    registers: Vec<RegisterId>,
+    memory: Vec<Range<u64>>,
+}
+
+impl TraceWatchpoint {
+    /// Creates a trace watchpoint on the `length` bytes at `address` of core 0, which records
+    /// the program counter when the memory is accessed as described by `kind`.
+    pub fn new(address: u64, length: u64, kind: WatchpointKind) -> Self {
+        Self {
+            core: 0,
+            watchpoint: Watchpoint {
+                address,
+                length,
+                kind,
+            },
+            registers: Vec::new(),
+            memory: Vec::new(),
+        }
+    }
+
+    /// Sets the core whose accesses are traced.
+    pub fn with_core(mut self, core: usize) -> Self {
+        self.core = core;
+        self
+    }
+
+    /// Adds a register which is read on every hit.
+    pub fn with_register(mut self, register: impl Into<RegisterId>) -> Self {
+        self.registers.push(register.into());
+        self
+    }
+
+    /// Adds a memory range which is read on every hit, e.g. the watched variable itself.
+    pub fn with_memory(mut self, range: Range<u64>) -> Self {
+        self.memory.push(range);
+        self
+    }
+
+    /// The core whose accesses are traced.
+    pub fn core(&self) -> usize {
+        self.core
+    }
+
+    /// The hardware watchpoint which triggers the trace.
+    pub fn watchpoint(&self) -> Watchpoint {
+        self.watchpoint
+    }
+
+    /// The registers which are read on every hit.
+    pub fn registers(&self) -> &[RegisterId] {
+        &self.registers
+    }
+
+    /// The memory ranges which are read on every hit.
+    pub fn memory(&self) -> &[Range<u64>] {
+        &self.memory
+    }
+
+    /// Records the state of `core`, which halted at a hit of this trace watchpoint, and resumes
+    /// the core.
+    pub(crate) fn record_hit(
+        &self,
+        id: TraceWatchpointId,
+        core: &mut Core<'_>,
+    ) -> Result<DataTraceRecord, Error> {
+        let time = Instant::now();
+        let pc: u64 = core.read_core_reg(core.program_counter().id())?;
+
+        let mut registers = Vec::new();
+        for &register in &self.registers {
+            match core.read_core_reg::<RegisterValue>(register) {
+                Ok(value) => registers.push((register, value)),
+                Err(error) => tracing::debug!("Failed to read register {register:?}: {error}"),
+            }
+        }
+
+        let mut memory = Vec::new();
+        for range in &self.memory {
+            let mut data = vec![0; range.end.saturating_sub(range.start) as usize];
+            match core.read(range.start, &mut data) {
+                Ok(()) => memory.push((range.start, data)),
+                Err(error) => tracing::debug!("Failed to read {range:#x?}: {error}"),
+            }
+        }
+
+        // ARM cores halt after the access. The other architectures halt before it, and would
+        // hit the watchpoint again if they were resumed right away.
+        if core.architecture() != Architecture::Arm {
+            let Watchpoint {
+                address,
+                length,
+                kind,
+            } = self.watchpoint;
+            core.clear_hw_watchpoint(address)?;
+            let stepped = core.step();
+            core.set_hw_watchpoint(address, length, kind)?;
+            stepped?;
+        }
+        core.run()?;
+
+        Ok(DataTraceRecord {
+            id,
+            core: self.core,
+            time,
+            pc,
+            registers,
+            memory,
+        })
+    }
+}
+
+/// Identifies a trace watchpoint added with
+/// [`Session::add_trace_watchpoint`](crate::Session::add_trace_watchpoint).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TraceWatchpointId(usize);
+
+/// The state of the core at a hit of a [`TraceWatchpoint`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DataTraceRecord {
+    /// The trace watchpoint which was hit.
+    pub id: TraceWatchpointId,
+    /// The index of the core.
+    pub core: usize,
+    /// When the hit was observed by the host.
+    pub time: Instant,
+    /// The program counter at the hit.
+    pub pc: u64,
+    /// The values of the configured registers, skipping the ones that could not be read.
+    pub registers: Vec<(RegisterId, RegisterValue)>,
+    /// The contents of the configured memory ranges, skipping the ones that could not be read.
+    pub memory: Vec<(u64, Vec<u8>)>,
+}
+
+/// The trace watchpoints of a session, and the records of their hits.
+#[derive(Debug)]
+pub(crate) struct TraceWatchpoints {
+    entries: Vec<(TraceWatchpointId, TraceWatchpoint)>,
+    next_id: usize,
+    records: VecDeque<DataTraceRecord>,
+    capacity: usize,
+    dropped: u64,
+}
+
+impl Default for TraceWatchpoints {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            next_id: 0,
+            records: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            dropped: 0,
+        }
+    }
+}
+
+impl TraceWatchpoints {
+    /// Adds a new trace watchpoint. The hardware watchpoint must already be set.
+    pub fn add(&mut self, watchpoint: TraceWatchpoint) -> TraceWatchpointId {
+        let id = TraceWatchpointId(self.next_id);
+        self.next_id += 1;
+        self.entries.push((id, watchpoint));
+        id
+    }
+
+    /// Removes the trace watchpoint `id`, returning it if it existed.
+    pub fn remove(&mut self, id: TraceWatchpointId) -> Option<TraceWatchpoint> {
+        let index = self.entries.iter().position(|(entry, _)| *entry == id)?;
+        Some(self.entries.remove(index).1)
+    }
+
+    /// Returns `true` if no trace watchpoint is set.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The cores with at least one trace watchpoint.
+    pub fn cores(&self) -> Vec<usize> {
+        let mut cores = self
+            .entries
+            .iter()
+            .map(|(_, watchpoint)| watchpoint.core)
+            .collect::<Vec<_>>();
+        cores.sort_unstable();
+        cores.dedup();
+        cores
+    }
+
+    /// Finds the trace watchpoint which halted `core`, given the hardware watchpoints of the
+    /// core and the triggering unit if the core reported it.
+    ///
+    /// If the core doesn't report the unit, the halt is only attributed to a trace watchpoint
+    /// if no other watchpoint is set, so that halts caused by e.g. a GDB client are not
+    /// swallowed.
+    pub fn triggered(
+        &self,
+        core: usize,
+        hw_watchpoints: &[Option<Watchpoint>],
+        unit: Option<usize>,
+    ) -> Option<(TraceWatchpointId, &TraceWatchpoint)> {
+        let find = |watchpoint: &Watchpoint| {
+            self.entries
+                .iter()
+                .find(|(_, entry)| entry.core == core && entry.watchpoint == *watchpoint)
+                .map(|(id, entry)| (*id, entry))
+        };
+
+        if let Some(unit) = unit {
+            return find(hw_watchpoints.get(unit)?.as_ref()?);
+        }
+
+        let mut set = hw_watchpoints.iter().flatten();
+        match (set.next(), set.next()) {
+            (Some(watchpoint), None) => find(watchpoint),
+            _ => None,
+        }
+    }
+
+    /// Stores a record, dropping the oldest one if the buffer is full.
+    pub fn push(&mut self, record: DataTraceRecord) {
+        tracing::trace!("Data trace record: {:x?}", record);
+        if self.records.len() >= self.capacity {
+            self.records.pop_front();
+            self.dropped += 1;
+        }
+        self.records.push_back(record);
+    }
+
+    /// Removes and returns the stored records, oldest first.
+    pub fn take_records(&mut self) -> Vec<DataTraceRecord> {
+        self.records.drain(..).collect()
+    }
+
+    /// The number of records dropped because the buffer was full.
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// Sets the number of records which are kept, dropping the oldest ones if needed.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        while self.records.len() > self.capacity {
+            self.records.pop_front();
+            self.dropped += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: TraceWatchpointId, pc: u64) -> DataTraceRecord {
+        DataTraceRecord {
+            id,
+            core: 0,
+            time: Instant::now(),
+            pc,
+            registers: Vec::new(),
+            memory: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn hits_are_attributed_to_trace_watchpoints() {
+        let mut trace = TraceWatchpoints::default();
+        let traced = TraceWatchpoint::new(0x2000_0000, 4, WatchpointKind::Write);
+        let id = trace.add(traced.clone());
+
+        let other = Watchpoint {
+            address: 0x2000_0100,
+            length: 4,
+            kind: WatchpointKind::Read,
+        };
+        let units = [Some(traced.watchpoint()), Some(other)];
+
+        assert_eq!(
+            trace.triggered(0, &units, Some(0)).map(|hit| hit.0),
+            Some(id)
+        );
+        assert!(trace.triggered(0, &units, Some(1)).is_none());
+        assert!(trace.triggered(1, &units, Some(0)).is_none());
+
+        // Without the unit, the hit is ambiguous while another watchpoint is set.
+        assert!(trace.triggered(0, &units, None).is_none());
+        assert_eq!(
+            trace
+                .triggered(0, &[Some(traced.watchpoint()), None], None)
+                .map(|hit| hit.0),
+            Some(id)
+        );
+    }
+
+    #[test]
+    fn oldest_records_are_dropped() {
+        let mut trace = TraceWatchpoints::default();
+        let id = trace.add(TraceWatchpoint::new(0x2000_0000, 4, WatchpointKind::Write));
+        trace.set_capacity(2);
+
+        for pc in 0..3 {
+            trace.push(record(id, pc));
+        }
+
+        let pcs = trace
+            .take_records()
+            .iter()
+            .map(|record| record.pc)
+            .collect::<Vec<_>>();
+        assert_eq!(pcs, [1, 2]);
+        assert_eq!(trace.dropped(), 1);
+        assert!(trace.take_records().is_empty());
+    }
+}
@@ -0,0 +1,216 @@
+//! Crash reports of halted cores, collected by [`Session::triage`].
+//!
+//! [`Session::triage`]: crate::Session::triage
+
+use std::fmt;
+
+use super::{exception_handler_for_core, HaltInspection, HaltReason, RegisterValue};
+use crate::debug::{DebugInfo, DebugRegisters};
+use crate::{Core, CoreStatus, CoreType, Error, MemoryInterface};
+
+/// The number of bytes of code read before and after the program counter.
+const CODE_CONTEXT: u64 = 32;
+
+/// The number of bytes of stack read from the stack pointer upwards.
+const STACK_CONTEXT: u64 = 256;
+
+/// A crash report of a halted core, to be rendered with [`fmt::Display`] or serialized.
+#[derive(Debug, Clone, Serialize)]
+pub struct TriageReport {
+    /// The index of the core.
+    pub core: usize,
+    /// The type of the core.
+    pub core_type: CoreType,
+    /// Why the core halted, including the exception cause if the architecture reports it.
+    pub halt_reason: HaltReason,
+    /// The exception the core is handling, as decoded from architecture specific registers like
+    /// xPSR and CFSR on ARM, if any.
+    pub exception: Option<String>,
+    /// What the registered halt inspectors found out about the halt.
+    pub inspections: Vec<HaltInspection>,
+    /// The values of all registers that could be read.
+    pub registers: Vec<TriageRegister>,
+    /// The call stack, innermost frame first. Empty if no debug info was given.
+    pub backtrace: Vec<TriageFrame>,
+    /// The code around the program counter, if it could be read.
+    pub code: Option<MemorySnapshot>,
+    /// The top of the stack, if it could be read.
+    pub stack: Option<MemorySnapshot>,
+}
+
+/// The value of a register in a [`TriageReport`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TriageRegister {
+    /// The name of the register.
+    pub name: &'static str,
+    /// The value of the register.
+    pub value: RegisterValue,
+}
+
+/// A frame of the backtrace in a [`TriageReport`].
+#[derive(Debug, Clone, Serialize)]
+pub struct TriageFrame {
+    /// The name of the function.
+    pub function: String,
+    /// The program counter in the frame.
+    pub pc: u64,
+    /// The source file and line of the program counter, if known.
+    pub location: Option<String>,
+}
+
+/// A block of target memory in a [`TriageReport`].
+#[derive(Debug, Clone, Serialize)]
+pub struct MemorySnapshot {
+    /// The address of the first byte.
+    pub address: u64,
+    /// The memory contents.
+    pub data: Vec<u8>,
+}
+
+impl MemorySnapshot {
+    fn read(core: &mut Core<'_>, address: u64, size: u64) -> Option<Self> {
+        let mut data = vec![0; size as usize];
+        match core.read(address, &mut data) {
+            Ok(()) => Some(Self { address, data }),
+            Err(error) => {
+                tracing::debug!("Failed to read {size} bytes at {address:#010x}: {error}");
+                None
+            }
+        }
+    }
+}
+
+impl TriageReport {
+    /// Collects the report of the halted `core`.
+    pub(crate) fn collect(
+        core: &mut Core<'_>,
+        inspections: Vec<HaltInspection>,
+        debug_info: Option<&DebugInfo>,
+    ) -> Result<Self, Error> {
+        let CoreStatus::Halted(halt_reason) = core.status()? else {
+            return Err(Error::Other(anyhow::anyhow!(
+                "The core must be halted to collect a crash report"
+            )));
+        };
+
+        let mut registers = Vec::new();
+        for register in core.registers().all_registers() {
+            match core.read_core_reg::<RegisterValue>(register.id()) {
+                Ok(value) => registers.push(TriageRegister {
+                    name: register.name(),
+                    value,
+                }),
+                Err(error) => tracing::debug!("Failed to read {}: {error}", register.name()),
+            }
+        }
+
+        let exception_handler = exception_handler_for_core(core.core_type());
+        let debug_registers = DebugRegisters::from_core(core);
+        let exception = match exception_handler.exception_details(core, &debug_registers) {
+            Ok(details) => details.map(|details| details.description),
+            Err(error) => {
+                tracing::debug!("Failed to decode the exception: {error}");
+                None
+            }
+        };
+
+        let backtrace = match debug_info {
+            Some(debug_info) => {
+                let instruction_set = core.instruction_set().ok();
+                debug_info
+                    .unwind(
+                        core,
+                        debug_registers,
+                        exception_handler.as_ref(),
+                        instruction_set,
+                    )?
+                    .into_iter()
+                    .map(|frame| TriageFrame {
+                        pc: frame.pc.try_into().unwrap_or_default(),
+                        location: frame.source_location.map(|location| {
+                            let file = location.file.unwrap_or_default();
+                            match location.line {
+                                Some(line) => format!("{file}:{}", line + 1),
+                                None => file,
+                            }
+                        }),
+                        function: frame.function_name,
+                    })
+                    .collect()
+            }
+            None => Vec::new(),
+        };
+
+        let pc: u64 = core.read_core_reg(core.program_counter().id())?;
+        let sp: u64 = core.read_core_reg(core.stack_pointer().id())?;
+        let code_start = pc.saturating_sub(CODE_CONTEXT);
+
+        Ok(Self {
+            core: core.id(),
+            core_type: core.core_type(),
+            halt_reason,
+            exception,
+            inspections,
+            registers,
+            backtrace,
+            code: MemorySnapshot::read(core, code_start, pc - code_start + CODE_CONTEXT),
+            stack: MemorySnapshot::read(core, sp, STACK_CONTEXT),
+        })
+    }
+}
+
+impl fmt::Display for MemorySnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, line) in self.data.chunks(16).enumerate() {
+            write!(f, "    {:#010x}:", self.address + index as u64 * 16)?;
+            for byte in line {
+                write!(f, " {byte:02x}")?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for TriageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Core {} ({:?}) halted: {:?}",
+            self.core, self.core_type, self.halt_reason
+        )?;
+        if let Some(exception) = &self.exception {
+            writeln!(f, "Exception: {exception}")?;
+        }
+        for inspection in &self.inspections {
+            writeln!(f, "{inspection}")?;
+        }
+
+        writeln!(f, "\nRegisters:")?;
+        for register in &self.registers {
+            writeln!(f, "    {:<10} {}", register.name, register.value)?;
+        }
+
+        if !self.backtrace.is_empty() {
+            writeln!(f, "\nBacktrace:")?;
+            for (index, frame) in self.backtrace.iter().enumerate() {
+                write!(f, "    {index}: {:#010x} {}", frame.pc, frame.function)?;
+                if let Some(location) = &frame.location {
+                    write!(f, " at {location}")?;
+                }
+                writeln!(f)?;
+            }
+        }
+
+        if let Some(code) = &self.code {
+            writeln!(f, "\nCode:")?;
+            write!(f, "{code}")?;
+        }
+        if let Some(stack) = &self.stack {
+            writeln!(f, "\nStack:")?;
+            write!(f, "{stack}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,385 @@
+//! Polling of memory locations, like global variables, while the cores keep running.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use object::{Object, ObjectSymbol};
+
+use crate::{Error, Session};
+
+/// How often a watch is read, unless set with [`Watch::with_interval`].
+const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The type of a watched value, which determines its size and how it is decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchType {
+    /// An unsigned 8-bit integer.
+    U8,
+    /// An unsigned 16-bit integer.
+    U16,
+    /// An unsigned 32-bit integer.
+    U32,
+    /// An unsigned 64-bit integer.
+    U64,
+    /// A signed 8-bit integer.
+    I8,
+    /// A signed 16-bit integer.
+    I16,
+    /// A signed 32-bit integer.
+    I32,
+    /// A signed 64-bit integer.
+    I64,
+    /// A 32-bit floating point number.
+    F32,
+    /// A 64-bit floating point number.
+    F64,
+    /// The given number of raw bytes.
+    Bytes(usize),
+}
+
+impl WatchType {
+    /// The number of bytes of a value of this type.
+    pub fn size(self) -> usize {
+        match self {
+            WatchType::U8 | WatchType::I8 => 1,
+            WatchType::U16 | WatchType::I16 => 2,
+            WatchType::U32 | WatchType::I32 | WatchType::F32 => 4,
+            WatchType::U64 | WatchType::I64 | WatchType::F64 => 8,
+            WatchType::Bytes(size) => size,
+        }
+    }
+
+    /// Decodes the little endian `data`, which holds [`WatchType::size`] bytes.
+    fn decode(self, data: &[u8]) -> WatchValue {
+        let mut bytes = [0; 8];
+        bytes[..data.len().min(8)].copy_from_slice(&data[..data.len().min(8)]);
+        let raw = u64::from_le_bytes(bytes);
+
+        match self {
+            WatchType::U8 | WatchType::U16 | WatchType::U32 | WatchType::U64 => {
+                WatchValue::Unsigned(raw)
+            }
+            WatchType::I8 => WatchValue::Signed(raw as i8 as i64),
+            WatchType::I16 => WatchValue::Signed(raw as i16 as i64),
+            WatchType::I32 => WatchValue::Signed(raw as i32 as i64),
+            WatchType::I64 => WatchValue::Signed(raw as i64),
+            WatchType::F32 => WatchValue::Float(f32::from_bits(raw as u32) as f64),
+            WatchType::F64 => WatchValue::Float(f64::from_bits(raw)),
+            WatchType::Bytes(_) => WatchValue::Bytes(data.to_vec()),
+        }
+    }
+}
+
+/// The decoded value of a watch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WatchValue {
+    /// The value of an unsigned integer.
+    Unsigned(u64),
+    /// The value of a signed integer.
+    Signed(i64),
+    /// The value of a floating point number.
+    Float(f64),
+    /// The raw bytes of the watch.
+    Bytes(Vec<u8>),
+}
+
+impl fmt::Display for WatchValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WatchValue::Unsigned(value) => write!(f, "{value}"),
+            WatchValue::Signed(value) => write!(f, "{value}"),
+            WatchValue::Float(value) => write!(f, "{value}"),
+            WatchValue::Bytes(bytes) => write!(f, "{bytes:02x?}"),
+        }
+    }
+}
+
+/// A memory location which is read periodically, see [`Session::add_watch`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watch {
+    core: usize,
+    address: u64,
+    watch_type: WatchType,
+    interval: Duration,
+}
+
+impl Watch {
+    /// Creates a watch of a value of type `watch_type` at `address`, read through the first core.
+    pub fn new(address: u64, watch_type: WatchType) -> Self {
+        Self {
+            core: 0,
+            address,
+            watch_type,
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+
+    /// Creates a watch of the global variable `name`, looked up in the ELF file `data`.
+    ///
+    /// Variables of 1, 2, 4 or 8 bytes are watched as unsigned integers, and all others as raw
+    /// bytes. Use [`Watch::with_type`] to read them as another type.
+    pub fn for_symbol(data: &[u8], name: &str) -> Result<Self, Error> {
+        let file = object::File::parse(data).map_err(|error| Error::Other(error.into()))?;
+
+        let Some(symbol) = file
+            .symbols()
+            .find(|symbol| symbol.name().is_ok_and(|symbol| symbol == name))
+        else {
+            return Err(Error::Other(anyhow!("The symbol `{name}` was not found")));
+        };
+
+        let watch_type = match symbol.size() {
+            1 => WatchType::U8,
+            2 => WatchType::U16,
+            4 => WatchType::U32,
+            8 => WatchType::U64,
+            size => WatchType::Bytes(size as usize),
+        };
+
+        Ok(Self::new(symbol.address(), watch_type))
+    }
+
+    /// Reads the watch through the core `core`.
+    pub fn with_core(mut self, core: usize) -> Self {
+        self.core = core;
+        self
+    }
+
+    /// Decodes the watched memory as `watch_type`.
+    pub fn with_type(mut self, watch_type: WatchType) -> Self {
+        self.watch_type = watch_type;
+        self
+    }
+
+    /// Sets how often the watch is read.
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// The index of the core the watch is read through.
+    pub fn core(&self) -> usize {
+        self.core
+    }
+
+    /// The address of the watched value.
+    pub fn address(&self) -> u64 {
+        self.address
+    }
+
+    /// The type of the watched value.
+    pub fn watch_type(&self) -> WatchType {
+        self.watch_type
+    }
+}
+
+/// Identifies a watch added with [`Session::add_watch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WatchId(usize);
+
+/// A change of a watched value, as reported to the subscribers of
+/// [`Session::subscribe_watches`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchEvent {
+    /// The watch whose value changed.
+    pub id: WatchId,
+    /// The new value.
+    pub value: WatchValue,
+    /// The value that was read before, or `None` if this is the first read of the watch.
+    pub previous: Option<WatchValue>,
+}
+
+#[derive(Debug)]
+struct WatchEntry {
+    id: WatchId,
+    watch: Watch,
+    value: Option<WatchValue>,
+    next_poll: Option<Instant>,
+}
+
+/// The watches of a session, and the subscribers to their changes.
+#[derive(Debug, Default)]
+pub(crate) struct Watches {
+    entries: Vec<WatchEntry>,
+    next_id: usize,
+    subscribers: Vec<Sender<WatchEvent>>,
+}
+
+impl Watches {
+    /// Adds a new watch, which is read at the next poll.
+    pub fn add(&mut self, watch: Watch) -> WatchId {
+        let id = WatchId(self.next_id);
+        self.next_id += 1;
+
+        self.entries.push(WatchEntry {
+            id,
+            watch,
+            value: None,
+            next_poll: None,
+        });
+
+        id
+    }
+
+    /// Removes the watch `id`, returning `false` if it didn't exist.
+    pub fn remove(&mut self, id: WatchId) -> bool {
+        let count = self.entries.len();
+        self.entries.retain(|entry| entry.id != id);
+        self.entries.len() != count
+    }
+
+    /// Adds a new subscriber.
+    pub fn subscribe(&mut self) -> Receiver<WatchEvent> {
+        let (sender, receiver) = channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Reads the watches which are due at `now` with `read`, and emits an event for every value
+    /// that changed.
+    ///
+    /// Watches that can't be read are skipped, and read again at their next interval.
+    pub fn poll(
+        &mut self,
+        now: Instant,
+        mut read: impl FnMut(&Watch, &mut [u8]) -> Result<(), Error>,
+    ) {
+        let mut events = Vec::new();
+        for entry in &mut self.entries {
+            if entry.next_poll.is_some_and(|next_poll| next_poll > now) {
+                continue;
+            }
+            entry.next_poll = Some(now + entry.watch.interval);
+
+            let mut data = vec![0; entry.watch.watch_type.size()];
+            if let Err(error) = read(&entry.watch, &mut data) {
+                tracing::warn!("Failed to read the watch {:?}: {:?}", entry.watch, error);
+                continue;
+            }
+
+            let value = entry.watch.watch_type.decode(&data);
+            if entry.value.as_ref() != Some(&value) {
+                events.push(WatchEvent {
+                    id: entry.id,
+                    previous: entry.value.replace(value.clone()),
+                    value,
+                });
+            }
+        }
+
+        for event in events {
+            tracing::trace!("Watch event: {:?}", event);
+            self.subscribers
+                .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+}
+
+/// A background thread polling the watches of a session.
+///
+/// Created by [`Session::spawn_watch_poller`]. The thread is stopped when the poller is dropped.
+#[derive(Debug)]
+pub struct WatchPoller {
+    events: Receiver<WatchEvent>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl WatchPoller {
+    pub(crate) fn spawn(session: Arc<Mutex<Session>>, interval: Duration) -> Self {
+        let events = session.lock().unwrap().subscribe_watches();
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let thread = std::thread::spawn({
+            let stop = stop.clone();
+            move || {
+                while !stop.load(Ordering::Relaxed) {
+                    {
+                        let Ok(mut session) = session.lock() else {
+                            break;
+                        };
+
+                        session.poll_watches();
+                    }
+
+                    std::thread::sleep(interval);
+                }
+            }
+        });
+
+        Self {
+            events,
+            stop,
+            thread: Some(thread),
+        }
+    }
+
+    /// The received changes of the watched values.
+    ///
+    /// Use [`Receiver::recv`] to block until a value changes.
+    pub fn events(&self) -> &Receiver<WatchEvent> {
+        &self.events
+    }
+}
+
+impl Drop for WatchPoller {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(memory: [u8; 2]) -> impl FnMut(&Watch, &mut [u8]) -> Result<(), Error> {
+        move |_, data| {
+            data.copy_from_slice(&memory[..data.len()]);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn only_changes_are_reported() {
+        let mut watches = Watches::default();
+        let receiver = watches.subscribe();
+
+        let counter = watches.add(Watch::new(0x2000_0000, WatchType::I16));
+        let slow = watches
+            .add(Watch::new(0x2000_0010, WatchType::U8).with_interval(Duration::from_secs(1)));
+
+        let start = Instant::now();
+        watches.poll(start, read([0xFF, 0xFF]));
+        watches.poll(start + Duration::from_millis(100), read([0xFF, 0xFF]));
+        watches.poll(start + Duration::from_millis(200), read([0x05, 0x00]));
+
+        assert_eq!(
+            receiver.try_iter().collect::<Vec<_>>(),
+            vec![
+                WatchEvent {
+                    id: counter,
+                    value: WatchValue::Signed(-1),
+                    previous: None,
+                },
+                WatchEvent {
+                    id: slow,
+                    value: WatchValue::Unsigned(0xFF),
+                    previous: None,
+                },
+                WatchEvent {
+                    id: counter,
+                    value: WatchValue::Signed(5),
+                    previous: Some(WatchValue::Signed(-1)),
+                },
+            ]
+        );
+    }
+}
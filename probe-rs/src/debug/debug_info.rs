@@ -1229,10 +1229,15 @@ fn unwind_register(
                     .core_register
                     .register_has_role(RegisterRole::FramePointer) =>
                 {
-                    register_rule_string = "FP=CFA (dwarf Undefined)".to_string();
-                    callee_frame_registers
-                        .get_frame_pointer()
-                        .and_then(|fp| fp.value)
+                    if instruction_set == Some(InstructionSet::Xtensa) {
+                        register_rule_string = "FP=[extra save area] (windowed ABI)".to_string();
+                        unwind_xtensa_frame_pointer(callee_frame_registers, memory)
+                    } else {
+                        register_rule_string = "FP=CFA (dwarf Undefined)".to_string();
+                        callee_frame_registers
+                            .get_frame_pointer()
+                            .and_then(|fp| fp.value)
+                    }
                 }
                 sp if sp
                     .core_register
@@ -1240,23 +1245,34 @@ fn unwind_register(
                 {
                     // NOTE: [ARMv7-M Architecture Reference Manual](https://developer.arm.com/documentation/ddi0403/ee), Section B.1.4.1: Treat bits [1:0] as `Should be Zero or Preserved`
                     // - Applying this logic to RISC-V has no adverse effects, since all incoming addresses are already 32-bit aligned.
-                    register_rule_string = "SP=CFA (dwarf Undefined)".to_string();
-                    unwind_cfa.map(|unwind_cfa| {
-                        if sp.is_u32() {
-                            RegisterValue::U32(unwind_cfa as u32 & !0b11)
-                        } else {
-                            RegisterValue::U64(unwind_cfa & !0b11)
-                        }
-                    })
+                    if instruction_set == Some(InstructionSet::Xtensa) {
+                        register_rule_string = "SP=[SP - 12] (windowed ABI)".to_string();
+                        unwind_xtensa_base_save_area(callee_frame_registers, memory, 1)
+                    } else {
+                        register_rule_string = "SP=CFA (dwarf Undefined)".to_string();
+                        unwind_cfa.map(|unwind_cfa| {
+                            if sp.is_u32() {
+                                RegisterValue::U32(unwind_cfa as u32 & !0b11)
+                            } else {
+                                RegisterValue::U64(unwind_cfa & !0b11)
+                            }
+                        })
+                    }
                 }
                 lr if lr
                     .core_register
                     .register_has_role(RegisterRole::ReturnAddress) =>
                 {
                     // This value is can only be used to determine the Undefined PC value. We have no way of inferring the previous frames LR until we have the PC.
-                    register_rule_string = "LR=Unknown (dwarf Undefined)".to_string();
                     *unwound_return_address = lr.value;
-                    None
+                    if instruction_set == Some(InstructionSet::Xtensa) {
+                        // With the windowed ABI, the caller's return address is saved on the stack instead.
+                        register_rule_string = "a0=[SP - 16] (windowed ABI)".to_string();
+                        unwind_xtensa_base_save_area(callee_frame_registers, memory, 0)
+                    } else {
+                        register_rule_string = "LR=Unknown (dwarf Undefined)".to_string();
+                        None
+                    }
                 }
                 pc if pc
                     .core_register
@@ -1267,6 +1283,9 @@ fn unwind_register(
                     unwound_return_address.and_then(|return_address| {
                         unwind_program_counter_register(
                             return_address,
+                            callee_frame_registers
+                                .get_program_counter()
+                                .and_then(|pc| pc.value),
                             instruction_set,
                             &mut register_rule_string,
                         )
@@ -1384,6 +1403,7 @@ fn unwind_register(
 /// Helper function to determine the program counter value for the previous frame.
 fn unwind_program_counter_register(
     return_address: RegisterValue,
+    program_counter: Option<RegisterValue>,
     instruction_set: Option<InstructionSet>,
     register_rule_string: &mut String,
 ) -> Option<RegisterValue> {
@@ -1401,6 +1421,15 @@ fn unwind_program_counter_register(
                 // when in Thumb state for certain instruction types will set the LSB to 1)
                 *register_rule_string = "PC=(unwound LR & !0b1) (dwarf Undefined)".to_string();
                 Some(RegisterValue::U32(return_address & !0b1))
+            } else if instruction_set == Some(InstructionSet::Xtensa) {
+                // The windowed ABI replaces the top two bits of the return address with the
+                // caller's window increment. The caller is in the same 1GB region as the callee.
+                *register_rule_string =
+                    "PC=(unwound a0 & 0x3FFFFFFF) | (PC & 0xC0000000) (windowed ABI)".to_string();
+                let program_counter: u32 = program_counter?.try_into().ok()?;
+                Some(RegisterValue::U32(
+                    (return_address & 0x3FFF_FFFF) | (program_counter & 0xC000_0000),
+                ))
             } else {
                 Some(RegisterValue::U32(return_address))
             }
@@ -1410,9 +1439,79 @@ fn unwind_program_counter_register(
             tracing::warn!("128 bit address space not supported");
             None
         }
+        RegisterValue::F32(_) | RegisterValue::F64(_) => {
+            tracing::warn!("The return address is not an integer: {}", return_address);
+            None
+        }
     }
 }
 
+/// Reads a word from the register save areas on the stack of an Xtensa target.
+fn read_xtensa_save_area(memory: &mut dyn MemoryInterface, address: u32) -> Option<u32> {
+    memory
+        .read_word_32(address as u64)
+        .map_err(|error| {
+            tracing::warn!(
+                "UNWIND: Failed to read the register save area at {:#010x}: {}",
+                address,
+                error
+            )
+        })
+        .ok()
+}
+
+/// Helper function to determine the caller's `a0`-`a3` for the Xtensa windowed ABI.
+///
+/// These registers are saved in the 16 bytes below the callee's stack pointer.
+fn unwind_xtensa_base_save_area(
+    callee_frame_registers: &DebugRegisters,
+    memory: &mut dyn MemoryInterface,
+    index: u32,
+) -> Option<RegisterValue> {
+    let callee_sp: u32 = callee_frame_registers
+        .get_stack_pointer()?
+        .value?
+        .try_into()
+        .ok()?;
+
+    read_xtensa_save_area(memory, callee_sp.wrapping_sub(16) + 4 * index).map(RegisterValue::U32)
+}
+
+/// Helper function to determine the caller's frame pointer (`a7`) for the Xtensa windowed ABI.
+///
+/// The top two bits of the callee's return address hold the window increment of the call. The
+/// `a4` and up of `call8` and `call12` callers are saved at the top of the caller's frame, which
+/// ends at the stack pointer of the caller's own caller.
+fn unwind_xtensa_frame_pointer(
+    callee_frame_registers: &DebugRegisters,
+    memory: &mut dyn MemoryInterface,
+) -> Option<RegisterValue> {
+    let return_address: u32 = callee_frame_registers
+        .get_return_address()?
+        .value?
+        .try_into()
+        .ok()?;
+
+    let extra_save_area_size = match return_address >> 30 {
+        2 => 32,
+        3 => 48,
+        // The caller's window does not include a7.
+        _ => return None,
+    };
+
+    let callee_sp: u32 = callee_frame_registers
+        .get_stack_pointer()?
+        .value?
+        .try_into()
+        .ok()?;
+    let caller_sp = read_xtensa_save_area(memory, callee_sp.wrapping_sub(12))?;
+    let outer_sp = read_xtensa_save_area(memory, caller_sp.wrapping_sub(12))?;
+
+    // The extra save area starts with a4.
+    read_xtensa_save_area(memory, outer_sp.wrapping_sub(extra_save_area_size) + 12)
+        .map(RegisterValue::U32)
+}
+
 /// Helper function to handle adding a signed offset to a [`RegisterValue`] address.
 /// The numerical overflow is handled based on the byte size (`address_size_in_bytes` parameter  )
 /// of the [`RegisterValue`], as opposed to just the datatype of the `address` parameter.
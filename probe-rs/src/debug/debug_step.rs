@@ -373,7 +373,7 @@ impl SteppingMode {
     }
 }
 
-/// Run the target to the desired address. If available, we will use a breakpoint, otherwise we will use single step.
+/// Run the target to the desired address, using [`CoreInterface::run_to`].
 /// Returns the program counter at the end of the step, when any of the following conditions are met:
 /// - We reach the `target_address_range.end()` (inclusive)
 /// - We reach some other legitimate halt point (e.g. the user tries to step past a series of statements, but there is another breakpoint active in that "gap")
@@ -397,27 +397,23 @@ fn run_to_address(
             core.read_core_reg(core.program_counter().id())?
                 .try_into()?,
         )
-    } else if core.set_hw_breakpoint(0, target_address).is_ok() {
-        core.run()?;
+    } else {
         // It is possible that we are stepping over long running instructions.
-        match core.wait_for_core_halted(Duration::from_millis(1000)) {
-            Ok(()) => {
-                // We have hit the target address, so all is good.
+        match core.run_to(target_address, Duration::from_millis(1000)) {
+            Ok(info) => {
                 // NOTE: It is conceivable that the core has halted, but we have not yet stepped to the target address. (e.g. the user tries to step out of a function, but there is another breakpoint active before the end of the function.)
-                //       This is a legitimate situation, so we clear the breakpoint at the target address, and pass control back to the user
-                core.clear_hw_breakpoint(0)?;
-                (
-                    core.status()?,
-                    core.read_core_reg(core.program_counter().id())?
-                        .try_into()?,
-                )
+                //       This is a legitimate situation, so we pass control back to the user
+                (core.status()?, info.pc)
             }
             Err(error) => {
-                program_counter = core.halt(Duration::from_millis(500))?.pc;
-                core.clear_hw_breakpoint(0)?;
+                program_counter = core
+                    .read_core_reg(core.program_counter().id())?
+                    .try_into()?;
                 if matches!(
                     error,
-                    crate::Error::Arm(ArmError::Timeout) | crate::Error::Riscv(RiscvError::Timeout)
+                    crate::Error::Timeout
+                        | crate::Error::Arm(ArmError::Timeout)
+                        | crate::Error::Riscv(RiscvError::Timeout)
                 ) {
                     // This is not a quick step and halt operation. Notify the user that we are not going to wait any longer, and then return the current program counter so that the debugger can show the user where the forced halt happened.
                     tracing::error!(
@@ -437,10 +433,6 @@ fn run_to_address(
                 }
             }
         }
-    } else {
-        // If we don't have breakpoints to use, we have to rely on single stepping.
-        // TODO: In theory, this could go on for a long time. Should we consider NOT allowing this kind of stepping if there are no breakpoints available?
-        step_to_address(target_address..=u64::MAX, core)?
     })
 }
 
@@ -101,17 +101,7 @@ impl DebugRegisters {
                     } else {
                         None
                     },
-                    value: match core.read_core_reg(core_register.id()) {
-                        Ok::<RegisterValue, _>(register_value) => Some(register_value),
-                        Err(e) => {
-                            tracing::warn!(
-                                "Failed to read value for register {:?}: {}",
-                                core_register,
-                                e
-                            );
-                            None
-                        }
-                    },
+                    value: None,
                 });
             } else {
                 tracing::trace!(
@@ -120,6 +110,38 @@ impl DebugRegisters {
                 );
             }
         }
+
+        // Read all registers at once where the core supports it, and fall back to reading them
+        // one by one, so that a single unreadable register doesn't fail all of them.
+        let register_ids = debug_registers
+            .iter()
+            .map(|debug_register| debug_register.core_register.id())
+            .collect::<Vec<_>>();
+        match core.read_core_regs(&register_ids) {
+            Ok(values) => {
+                for (debug_register, value) in debug_registers.iter_mut().zip(values) {
+                    debug_register.value = Some(value);
+                }
+            }
+            Err(error) => {
+                tracing::debug!("Failed to read all registers at once: {}", error);
+                for debug_register in debug_registers.iter_mut() {
+                    debug_register.value =
+                        match core.read_core_reg(debug_register.core_register.id()) {
+                            Ok::<RegisterValue, _>(register_value) => Some(register_value),
+                            Err(e) => {
+                                tracing::warn!(
+                                    "Failed to read value for register {:?}: {}",
+                                    debug_register.core_register,
+                                    e
+                                );
+                                None
+                            }
+                        };
+                }
+            }
+        }
+
         DebugRegisters(debug_registers)
     }
 
@@ -533,8 +533,10 @@ impl Variable {
         {
             // Special handling for SVD registers.
             // Because we cache the SVD structure once per sesion, we have to re-read the actual register values whenever queried.
-            match memory.read_word_32(self.memory_location.memory_address().unwrap_or(u64::MAX)) {
-                Ok(u32_value) => self.value = VariableValue::Valid(u32_value.to_le().to_string()),
+            let address = self.memory_location.memory_address().unwrap_or(u64::MAX);
+            let width = self.byte_size.map_or(32, |byte_size| byte_size as u32 * 8);
+            match crate::read_peripheral_register(memory, address, width) {
+                Ok(register_value) => self.value = VariableValue::Valid(register_value.to_string()),
                 Err(error) => {
                     self.value = VariableValue::Error(format!(
                         "Unable to read peripheral register value @ {:#010X} : {:?}",
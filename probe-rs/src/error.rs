@@ -4,7 +4,9 @@ use crate::architecture::arm::ArmError;
 use crate::architecture::riscv::communication_interface::RiscvError;
 use crate::architecture::xtensa::communication_interface::XtensaError;
 use crate::config::RegistryError;
+use crate::core::HaltingOperation;
 use crate::DebugProbeError;
+use std::ops::Range;
 
 /// The overarching error type which contains all possible errors as variants.
 #[derive(thiserror::Error, Debug)]
@@ -27,6 +29,10 @@ pub enum Error {
     /// The core with given ID does not exist.
     #[error("Core {0} does not exist")]
     CoreNotFound(usize),
+    /// The core with given ID was disabled with
+    /// [`Session::set_core_enabled`](crate::Session::set_core_enabled).
+    #[error("Core {0} is disabled")]
+    CoreDisabled(usize),
     /// The given chip does not exist.
     #[error("Unable to load specification for chip")]
     ChipNotFound(#[from] RegistryError),
@@ -56,6 +62,9 @@ pub enum Error {
     /// A timeout occurred during an operation
     #[error("A timeout occurred.")]
     Timeout,
+    /// The operation was cancelled by the caller.
+    #[error("The operation was cancelled.")]
+    Cancelled,
     /// Unaligned memory access
     #[error("Alignment error")]
     MemoryNotAligned {
@@ -64,6 +73,36 @@ pub enum Error {
         /// The required alignment in bytes (address increments).
         alignment: usize,
     },
+    /// A memory access was vetoed by a [`MemoryHook`](crate::MemoryHook).
+    #[error("Access to memory at {address:#010x} was denied: {reason}")]
+    MemoryAccessDenied {
+        /// The address at which the access was denied.
+        address: u64,
+        /// Why the access was denied.
+        reason: String,
+    },
+    /// The data read back after a memory write differs from the written data.
+    #[error(
+        "Verifying the write failed at {address:#010x}: wrote {expected:#04x}, read back {actual:#04x}"
+    )]
+    WriteVerificationFailed {
+        /// The first address at which the data differs.
+        address: u64,
+        /// The byte written to the address.
+        expected: u8,
+        /// The byte read back from the address.
+        actual: u8,
+    },
+    /// The operation would have to halt the running core, which is only done implicitly if
+    /// enabled with [`Session::set_implicit_halts`](crate::Session::set_implicit_halts).
+    #[error("{0} is not possible until the core is halted")]
+    NotHalted(HaltingOperation),
+    /// The accessed memory is not part of the target's memory map.
+    #[error("The memory range {:#010x}..{:#010x} is not mapped", .range.start, .range.end)]
+    MemoryNotMapped {
+        /// The accessed memory range.
+        range: Range<u64>,
+    },
 }
 
 impl From<ArmError> for Error {
@@ -136,6 +136,43 @@ impl FlashLayout {
         &self.data_blocks
     }
 
+    /// Removes the sectors for which `keep` returns `false`, along with their pages, fills and
+    /// data blocks.
+    pub(super) fn retain_sectors(&mut self, mut keep: impl FnMut(&FlashSector) -> bool) {
+        self.sectors.retain(|sector| keep(sector));
+
+        let sectors = &self.sectors;
+        let overlaps_sector = |address: u64, size: u64| {
+            sectors.iter().any(|sector| {
+                address < sector.address + sector.size && sector.address < address + size
+            })
+        };
+
+        // Fills refer to their page by index, so track where the kept pages end up.
+        let mut page_indices = Vec::with_capacity(self.pages.len());
+        let mut pages = Vec::new();
+        for page in std::mem::take(&mut self.pages) {
+            if overlaps_sector(page.address, page.size() as u64) {
+                page_indices.push(Some(pages.len()));
+                pages.push(page);
+            } else {
+                page_indices.push(None);
+            }
+        }
+        self.pages = pages;
+
+        self.fills
+            .retain_mut(|fill| match page_indices[fill.page_index] {
+                Some(index) => {
+                    fill.page_index = index;
+                    true
+                }
+                None => false,
+            });
+        self.data_blocks
+            .retain(|block| overlaps_sector(block.address, block.size));
+    }
+
     /// Get a visualizer for the flash layout, which can create
     /// a graphical representation of the layout.
     pub fn visualize(&self) -> FlashVisualizer {
@@ -1145,4 +1182,34 @@ mod tests {
             }
         )
     }
+
+    #[test]
+    fn retain_sectors_keeps_fills_consistent() {
+        let (region, flash_algorithm) = assemble_demo_flash1();
+        let mut flash_builder = FlashBuilder::new();
+        flash_builder.add_data(0, &[42; 5024]).unwrap();
+        flash_builder.add_data(7860, &[42; 5024]).unwrap();
+        let mut flash_layout = flash_builder
+            .build_sectors_and_pages(&region, &flash_algorithm, true)
+            .unwrap();
+
+        flash_layout.retain_sectors(|sector| sector.address() != 0x1000);
+
+        let sectors = flash_layout
+            .sectors()
+            .iter()
+            .map(|sector| sector.address())
+            .collect::<Vec<_>>();
+        assert_eq!(sectors, [0x0000, 0x2000, 0x3000]);
+        assert!(flash_layout
+            .pages()
+            .iter()
+            .all(|page| !(0x1000..0x2000).contains(&page.address())));
+
+        for fill in flash_layout.fills() {
+            let page = &flash_layout.pages()[fill.page_index()];
+            assert!((page.address()..page.address() + page.size() as u64).contains(&fill.address()));
+        }
+        assert_eq!(flash_layout.fills().len(), 4);
+    }
 }
@@ -140,6 +140,13 @@ pub struct DownloadOptions {
     pub verify: bool,
     /// Disable double buffering when loading flash.
     pub disable_double_buffering: bool,
+    /// Read back the sectors before erasing them, and skip the sectors whose contents already
+    /// match the data.
+    ///
+    /// This is much faster when most of the flash is unchanged, e.g. when re-flashing a large
+    /// image after a small change. The flash has to be readable through memory accesses, or
+    /// through the flash mappings of the target.
+    pub incremental: bool,
 }
 
 impl DownloadOptions {
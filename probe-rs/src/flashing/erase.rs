@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use probe_rs_target::{MemoryRange, MemoryRegion, NvmRegion};
 
 use crate::flashing::{flasher::Flasher, FlashError, FlashLoader};
-use crate::Session;
+use crate::{Architecture, Session};
 
 use super::FlashProgress;
 
@@ -14,6 +14,24 @@ use super::FlashProgress;
 pub fn erase_all(session: &mut Session, progress: Option<FlashProgress>) -> Result<(), FlashError> {
     tracing::debug!("Erasing all...");
 
+    // Xtensa chips are erased by their ROM, which doesn't need a flash algorithm.
+    if session.architecture() == Architecture::Xtensa && session.has_sequence_erase_all() {
+        if let Some(progress) = &progress {
+            progress.started_erasing();
+        }
+        let result = session.sequence_erase_all();
+        if let Some(progress) = &progress {
+            if result.is_ok() {
+                progress.finished_erasing();
+            } else {
+                progress.failed_erasing();
+            }
+        }
+        return result.map_err(|error| FlashError::ChipEraseFailed {
+            source: Box::new(error),
+        });
+    }
+
     let mut algos: HashMap<(String, String), Vec<NvmRegion>> = HashMap::new();
     tracing::debug!("Regions:");
     for region in &session.target().memory_map {
@@ -179,7 +179,11 @@ impl FlashAlgorithm {
         0x0477_0D1F,
     ];
 
-    const XTENSA_FLASH_BLOB_HEADER: [u32; 0] = [];
+    // Header for Xtensa Flash Algorithms. The algorithm returns here, so it must hit a breakpoint.
+    // Algorithms are called using the windowed ABI, as if by CALL4.
+    const XTENSA_FLASH_BLOB_HEADER: [u32; 1] = [
+        0x0000_41F0, // break 1, 15
+    ];
 
     /// When the target architecture is not known, and we need to allocate space for the header,
     /// this function returns the maximum size of the header of supported architectures.
@@ -332,8 +336,10 @@ impl FlashAlgorithm {
 
 #[cfg(test)]
 mod test {
-    use probe_rs_target::{FlashProperties, SectorDescription, SectorInfo};
+    use probe_rs_target::{FlashProperties, MemoryRegion, SectorDescription, SectorInfo};
 
+    use crate::architecture::xtensa::arch::instruction::disassemble::disassemble;
+    use crate::config::{get_target_by_name, DebugSequence};
     use crate::flashing::FlashAlgorithm;
 
     #[test]
@@ -502,4 +508,84 @@ mod test {
         ];
         assert_eq!(&got, expected);
     }
+
+    #[test]
+    fn xtensa_algorithms_call_the_rom_with_the_windowed_abi() {
+        for chip in ["esp32", "esp32s3"] {
+            let target = get_target_by_name(chip).unwrap();
+            let DebugSequence::Xtensa(sequence) = &target.debug_sequence else {
+                panic!("{chip} has no Xtensa debug sequence");
+            };
+            let routines = sequence.rom_flash_routines().unwrap();
+
+            let raw = target
+                .flash_algorithms
+                .iter()
+                .find(|algorithm| algorithm.default)
+                .unwrap();
+            let load_address = raw.load_address.unwrap();
+            let ram = target
+                .memory_map
+                .iter()
+                .find_map(|region| match region {
+                    MemoryRegion::Ram(ram) if ram.range.contains(&load_address) => Some(ram),
+                    _ => None,
+                })
+                .unwrap();
+            let algorithm = FlashAlgorithm::assemble_from_raw(raw, ram, &target).unwrap();
+
+            let code = algorithm
+                .instructions
+                .iter()
+                .flat_map(|word| word.to_le_bytes())
+                .collect::<Vec<_>>();
+            let offset = |address: u64| (address - algorithm.load_address) as usize;
+            let disassemble_at =
+                |address: u64| disassemble(address, &code[offset(address)..], true);
+
+            // The functions return to the breakpoint in the header.
+            let header = &disassemble_at(algorithm.load_address)[0];
+            assert_eq!(
+                (header.mnemonic.as_str(), header.operands.as_str()),
+                ("break", "1, 15")
+            );
+
+            // Returns the ROM routines a function calls, which it loads from the literal pool.
+            let called_routines = |pc: u64| {
+                let function = disassemble_at(pc);
+                assert_eq!(function[0].mnemonic, "entry", "{chip}: function at {pc:#x}");
+
+                let body = function
+                    .iter()
+                    .take_while(|instruction| instruction.mnemonic != "retw")
+                    .collect::<Vec<_>>();
+                assert!(
+                    body.len() < function.len(),
+                    "{chip}: {pc:#x} doesn't return"
+                );
+
+                body.iter()
+                    .filter(|instruction| instruction.mnemonic == "l32r")
+                    .map(|instruction| {
+                        let literal = offset(instruction.target.unwrap());
+                        u64::from(u32::from_le_bytes(
+                            code[literal..literal + 4].try_into().unwrap(),
+                        ))
+                    })
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(
+                called_routines(algorithm.pc_init.unwrap()),
+                [routines.attach, routines.unlock]
+            );
+            assert_eq!(called_routines(algorithm.pc_uninit.unwrap()), []);
+            assert_eq!(
+                called_routines(algorithm.pc_erase_all.unwrap()),
+                [routines.erase_chip]
+            );
+            assert_eq!(called_routines(algorithm.pc_erase_sector).len(), 1);
+            assert_eq!(called_routines(algorithm.pc_program_page).len(), 1);
+        }
+    }
 }
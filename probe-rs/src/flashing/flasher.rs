@@ -1,12 +1,16 @@
-use probe_rs_target::{MemoryRegion, RawFlashAlgorithm};
+use probe_rs_target::{AddressTranslation, MemoryRegion, RawFlashAlgorithm};
 use tracing::Level;
 
-use super::{FlashAlgorithm, FlashBuilder, FlashError, FlashFill, FlashPage, FlashProgress};
+use super::{
+    FlashAlgorithm, FlashBuilder, FlashError, FlashFill, FlashLayout, FlashPage, FlashProgress,
+};
 use crate::config::NvmRegion;
 use crate::flashing::encoder::FlashEncoder;
 use crate::memory::MemoryInterface;
-use crate::{core::CoreRegisters, session::Session, Core, InstructionSet};
+use crate::{core::CoreRegisters, session::Session, Core};
 use std::{
+    borrow::Cow,
+    collections::BTreeSet,
     fmt::Debug,
     time::{Duration, Instant},
 };
@@ -55,6 +59,9 @@ pub(super) struct Flasher<'session> {
     core_index: usize,
     flash_algorithm: FlashAlgorithm,
     progress: FlashProgress,
+    /// The address ranges through which the flash is read, see
+    /// [`FlashLoader::with_flash_mappings`](super::FlashLoader::with_flash_mappings).
+    flash_mappings: Vec<AddressTranslation>,
 }
 
 impl<'session> Flasher<'session> {
@@ -106,6 +113,7 @@ impl<'session> Flasher<'session> {
             core_index,
             flash_algorithm,
             progress: progress.unwrap_or(FlashProgress::new(|_| {})),
+            flash_mappings: Vec::new(),
         };
 
         this.load()?;
@@ -113,6 +121,12 @@ impl<'session> Flasher<'session> {
         Ok(this)
     }
 
+    /// Sets the address ranges through which the flash is read when checking which sectors
+    /// are unchanged.
+    pub(super) fn set_flash_mappings(&mut self, mappings: Vec<AddressTranslation>) {
+        self.flash_mappings = mappings;
+    }
+
     pub(super) fn flash_algorithm(&self) -> &FlashAlgorithm {
         &self.flash_algorithm
     }
@@ -175,6 +189,11 @@ impl<'session> Flasher<'session> {
 
         tracing::debug!("RAM contents match flashing algo blob.");
 
+        // Make sure the core fetches the algorithm, not stale cache contents.
+        let algo_end = algo.load_address + (algo.instructions.len() * 4) as u64;
+        core.code_barrier(algo.load_address..algo_end)
+            .map_err(FlashError::Core)?;
+
         Ok(())
     }
 
@@ -285,6 +304,7 @@ impl<'session> Flasher<'session> {
         restore_unwritten_bytes: bool,
         enable_double_buffering: bool,
         skip_erasing: bool,
+        incremental: bool,
     ) -> Result<(), FlashError> {
         tracing::debug!("Starting program procedure.");
         // Convert the list of flash operations into flash sectors and pages.
@@ -293,6 +313,22 @@ impl<'session> Flasher<'session> {
             &self.flash_algorithm,
             restore_unwritten_bytes,
         )?;
+
+        if incremental {
+            self.skip_unchanged_sectors(&mut flash_layout, flash_builder, restore_unwritten_bytes)?;
+
+            if flash_layout.pages().is_empty() {
+                // Still go through all phases, so that the progress is reported as complete.
+                self.progress.initialized(flash_layout);
+                self.progress.started_filling();
+                self.progress.finished_filling();
+                self.progress.started_erasing();
+                self.progress.finished_erasing();
+                self.progress.started_programming(0);
+                self.progress.finished_programming();
+                return Ok(());
+            }
+        }
         self.progress.initialized(flash_layout.clone());
 
         tracing::debug!("Double Buffering enabled: {:?}", enable_double_buffering);
@@ -324,6 +360,22 @@ impl<'session> Flasher<'session> {
         // We successfully finished filling.
         self.progress.finished_filling();
 
+        // Let the memory hooks veto or transform the pages before they are encoded.
+        for page in flash_layout.pages_mut() {
+            let address = page.address();
+            let data = self
+                .session
+                .memory_hooks()
+                .before_write(self.core_index, address, page.data())
+                .map_err(|error| FlashError::PageWrite {
+                    page_address: address,
+                    source: Box::new(error),
+                })?;
+            if let Cow::Owned(data) = data {
+                page.data_mut().copy_from_slice(&data);
+            }
+        }
+
         let flash_encoder = FlashEncoder::new(self.flash_algorithm.transfer_encoding, flash_layout);
 
         // Skip erase if necessary
@@ -342,6 +394,63 @@ impl<'session> Flasher<'session> {
         Ok(())
     }
 
+    /// Removes the sectors from `flash_layout` whose contents already are what programming them
+    /// would result in.
+    fn skip_unchanged_sectors(
+        &mut self,
+        flash_layout: &mut FlashLayout,
+        flash_builder: &FlashBuilder,
+        restore_unwritten_bytes: bool,
+    ) -> Result<(), FlashError> {
+        let erased_byte_value = self.flash_algorithm.flash_properties.erased_byte_value;
+        let mappings = self.flash_mappings.clone();
+        let sectors = flash_layout.sectors().to_vec();
+
+        let mut unchanged = BTreeSet::new();
+        self.run_verify(|active| {
+            for sector in &sectors {
+                let range = sector.address()..sector.address() + sector.size();
+
+                // Flash which is mapped into the address space through a cache can only be read
+                // through the mapping.
+                let address = mappings
+                    .iter()
+                    .find_map(|mapping| mapping.untranslate(range.start))
+                    .unwrap_or(range.start);
+                let mut current = vec![0; sector.size() as usize];
+                active
+                    .core
+                    .read(address, &mut current)
+                    .map_err(FlashError::Core)?;
+
+                // The bytes without data are erased, unless they are restored.
+                let mut expected = if restore_unwritten_bytes {
+                    current.clone()
+                } else {
+                    vec![erased_byte_value; current.len()]
+                };
+                for (address, data) in flash_builder.data_in_range(&range) {
+                    let offset = (address - range.start) as usize;
+                    expected[offset..offset + data.len()].copy_from_slice(data);
+                }
+
+                if expected == current {
+                    unchanged.insert(sector.address());
+                }
+            }
+            Ok(())
+        })?;
+
+        tracing::info!(
+            "Skipping {} of {} sectors, which are unchanged",
+            unchanged.len(),
+            sectors.len()
+        );
+        flash_layout.retain_sectors(|sector| !unchanged.contains(&sector.address()));
+
+        Ok(())
+    }
+
     /// Fills all the bytes of `current_page`.
     ///
     /// If `restore_unwritten_bytes` is `true`, all bytes of the page,
@@ -633,12 +742,14 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
         let algo = &self.flash_algorithm;
         let regs: &'static CoreRegisters = self.core.registers();
 
+        let arguments = [registers.r0, registers.r1, registers.r2, registers.r3]
+            .into_iter()
+            .map_while(|argument| argument.map(u64::from))
+            .collect::<Vec<_>>();
+        let pc = u64::from(registers.pc);
+
+        // The stack pointer is set first, as calls on Xtensa pass it on to the called function.
         let registers = [
-            (self.core.program_counter(), Some(registers.pc)),
-            (regs.argument_register(0), registers.r0),
-            (regs.argument_register(1), registers.r1),
-            (regs.argument_register(2), registers.r2),
-            (regs.argument_register(3), registers.r3),
             (
                 regs.core_register(9),
                 if init {
@@ -655,16 +766,6 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
                     None
                 },
             ),
-            (
-                self.core.return_address(),
-                // For ARM Cortex-M cores, we have to add 1 to the return address,
-                // to ensure that we stay in Thumb mode.
-                if self.core.instruction_set()? == InstructionSet::Thumb2 {
-                    Some(into_reg(algo.load_address + 1)?)
-                } else {
-                    Some(into_reg(algo.load_address)?)
-                },
-            ),
         ];
 
         for (description, value) in registers {
@@ -685,6 +786,11 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
             }
         }
 
+        // The routine returns to the breakpoint in the header of the algorithm, and takes its
+        // arguments as the calling convention of the core demands, e.g. windowed on Xtensa.
+        self.core
+            .prepare_function_call(pc, algo.load_address, &arguments)?;
+
         // Ensure RISC-V `ebreak` instructions enter debug mode,
         // this is necessary for soft breakpoints to work.
         self.core.debug_on_sw_breakpoint(true)?;
@@ -722,7 +828,6 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
     #[tracing::instrument(skip(self))]
     pub(super) fn wait_for_completion(&mut self, timeout: Duration) -> Result<u32, FlashError> {
         tracing::debug!("Waiting for routine call completion.");
-        let regs = self.core.registers();
 
         // Wait until halted state is active again.
         let start = Instant::now();
@@ -759,8 +864,8 @@ impl<'probe, O: Operation> ActiveFlasher<'probe, O> {
             return Err(FlashError::Core(crate::Error::Timeout));
         }
 
-        let r: u32 = self.core.read_core_reg(regs.result_register(0))?;
-        Ok(r)
+        let r = self.core.function_call_result()?;
+        Ok(r as u32)
     }
 
     #[cfg(feature = "rtt")]
@@ -791,6 +896,16 @@ impl<'probe> ActiveFlasher<'probe, Erase> {
         let algo = &flasher.flash_algorithm;
 
         if let Some(pc_erase_all) = algo.pc_erase_all {
+            flasher
+                .core
+                .hook_before_erase(
+                    algo.flash_properties.address_range.clone(),
+                    algo.flash_properties.erased_byte_value,
+                )
+                .map_err(|error| FlashError::ChipEraseFailed {
+                    source: Box::new(error),
+                })?;
+
             let result = flasher
                 .call_function_and_wait(
                     &Registers {
@@ -826,6 +941,21 @@ impl<'probe> ActiveFlasher<'probe, Erase> {
         tracing::info!("Erasing sector at address 0x{:08x}", address);
         let t1 = Instant::now();
 
+        let sector = self
+            .flash_algorithm
+            .sector_info(address)
+            .map(|info| info.base_address..info.base_address + info.size)
+            .unwrap_or(address..address + 1);
+        self.core
+            .hook_before_erase(
+                sector,
+                self.flash_algorithm.flash_properties.erased_byte_value,
+            )
+            .map_err(|error| FlashError::EraseFailed {
+                sector_address: address,
+                source: Box::new(error),
+            })?;
+
         let result = self
             .call_function_and_wait(
                 &Registers {
@@ -1,6 +1,7 @@
 use ihex::Record;
 use probe_rs_target::{
-    MemoryRange, MemoryRegion, NvmRegion, RawFlashAlgorithm, TargetDescriptionSource,
+    AddressTranslation, MemoryRange, MemoryRegion, NvmRegion, RawFlashAlgorithm,
+    TargetDescriptionSource,
 };
 use std::collections::HashMap;
 use std::io::{Read, Seek, SeekFrom};
@@ -27,6 +28,10 @@ pub struct FlashLoader {
     memory_map: Vec<MemoryRegion>,
     builder: FlashBuilder,
 
+    /// Address ranges through which the program reads the flash, see
+    /// [`with_flash_mappings`](Self::with_flash_mappings).
+    flash_mappings: Vec<AddressTranslation>,
+
     /// Source of the flash description,
     /// used for diagnostics.
     source: TargetDescriptionSource,
@@ -38,10 +43,38 @@ impl FlashLoader {
         Self {
             memory_map,
             builder: FlashBuilder::new(),
+            flash_mappings: Vec::new(),
             source,
         }
     }
 
+    /// Sets the address ranges through which the program reads the flash, e.g. through a cache
+    /// MMU, and the flash addresses they map to.
+    ///
+    /// ELF segments linked to these ranges are written to the translated flash address, and
+    /// read back through the mapping for verification.
+    pub fn with_flash_mappings(mut self, mappings: Vec<AddressTranslation>) -> Self {
+        self.flash_mappings = mappings;
+        self
+    }
+
+    /// Translates the address of an ELF segment to the flash address it is written to.
+    fn flash_address(&self, address: u64) -> u64 {
+        self.flash_mappings
+            .iter()
+            .find_map(|mapping| mapping.translate(address))
+            .unwrap_or(address)
+    }
+
+    /// Translates a flash address back to the address the program reads it from, if the
+    /// address is mapped.
+    fn mapped_address(&self, address: u64) -> u64 {
+        self.flash_mappings
+            .iter()
+            .find_map(|mapping| mapping.untranslate(address))
+            .unwrap_or(address)
+    }
+
     /// Check the given address range is completely covered by the memory map,
     /// possibly by multiple memory regions.
     fn check_data_in_memory_map(&mut self, range: Range<u64>) -> Result<(), FlashError> {
@@ -254,7 +287,15 @@ impl FlashLoader {
         }
 
         for data in extracted_data {
-            self.add_data(data.address.into(), data.data)?;
+            let address = self.flash_address(data.address.into());
+            if address != u64::from(data.address) {
+                tracing::info!(
+                    "Placing segment at {:#010x} at flash address {:#010x}",
+                    data.address,
+                    address
+                );
+            }
+            self.add_data(address, data.data)?;
         }
 
         Ok(())
@@ -398,6 +439,7 @@ impl FlashLoader {
                 .position(|c| c.name == core_name)
                 .unwrap();
             let mut flasher = Flasher::new(session, core, &algo, options.progress.clone())?;
+            flasher.set_flash_mappings(self.flash_mappings.clone());
 
             let mut do_chip_erase = options.do_chip_erase;
 
@@ -434,6 +476,7 @@ impl FlashLoader {
                     options.keep_unwritten_bytes,
                     do_use_double_buffering,
                     options.skip_erase || do_chip_erase,
+                    options.incremental && !do_chip_erase,
                 )?;
             }
         }
@@ -505,8 +548,10 @@ impl FlashLoader {
                 let core_index = session.target().core_index_by_name(core_name).unwrap();
                 let mut core = session.core(core_index).map_err(FlashError::Core)?;
 
+                // Mapped flash is read back through the mapping, since it is not accessible
+                // at its flash address.
                 let mut written_data = vec![0; data.len()];
-                core.read(address, &mut written_data)
+                core.read(self.mapped_address(address), &mut written_data)
                     .map_err(FlashError::Core)?;
 
                 if data != &written_data {
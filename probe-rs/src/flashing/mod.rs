@@ -51,7 +51,9 @@ mod error;
 mod flash_algorithm;
 mod flasher;
 mod loader;
+mod parallel;
 mod progress;
+mod region_info;
 mod visualizer;
 
 use builder::*;
@@ -63,5 +65,7 @@ pub use erase::*;
 pub use error::*;
 pub use flash_algorithm::*;
 pub use loader::*;
+pub use parallel::*;
 pub use progress::*;
+pub use region_info::*;
 pub use visualizer::*;
@@ -0,0 +1,237 @@
+//! Flashing several boards at once, each through its own probe.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use super::{
+    download_file_with_options, DownloadOptions, FileDownloadError, FlashProgress, Format,
+    ProgressEvent,
+};
+use crate::config::TargetSelector;
+use crate::{DebugProbeError, DebugProbeSelector, Error, Lister, Permissions};
+
+/// A board which is flashed by [`flash_parallel`].
+#[derive(Debug, Clone)]
+pub struct FlashJob {
+    probe: DebugProbeSelector,
+    target: TargetSelector,
+    path: PathBuf,
+    format: Format,
+    permissions: Permissions,
+    under_reset: bool,
+    speed: Option<u32>,
+    chip_erase: bool,
+    verify: bool,
+    incremental: bool,
+}
+
+impl FlashJob {
+    /// Creates a job which flashes the file at `path` to the `target` connected to `probe`.
+    pub fn new(
+        probe: DebugProbeSelector,
+        target: impl Into<TargetSelector>,
+        path: impl Into<PathBuf>,
+        format: Format,
+    ) -> Self {
+        Self {
+            probe,
+            target: target.into(),
+            path: path.into(),
+            format,
+            permissions: Permissions::default(),
+            under_reset: false,
+            speed: None,
+            chip_erase: false,
+            verify: false,
+            incremental: false,
+        }
+    }
+
+    /// Sets the permissions of the session.
+    pub fn with_permissions(mut self, permissions: Permissions) -> Self {
+        self.permissions = permissions;
+        self
+    }
+
+    /// Attaches to the target while holding it in reset.
+    pub fn with_under_reset(mut self, under_reset: bool) -> Self {
+        self.under_reset = under_reset;
+        self
+    }
+
+    /// Sets the protocol speed in kHz.
+    pub fn with_speed(mut self, speed_khz: u32) -> Self {
+        self.speed = Some(speed_khz);
+        self
+    }
+
+    /// Erases the whole chip before programming, see [`DownloadOptions::do_chip_erase`].
+    pub fn with_chip_erase(mut self, chip_erase: bool) -> Self {
+        self.chip_erase = chip_erase;
+        self
+    }
+
+    /// Reads back the flashed data, see [`DownloadOptions::verify`].
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
+
+    /// Only programs the changed sectors, see [`DownloadOptions::incremental`].
+    pub fn with_incremental(mut self, incremental: bool) -> Self {
+        self.incremental = incremental;
+        self
+    }
+
+    /// The probe the board is connected to.
+    pub fn probe(&self) -> &DebugProbeSelector {
+        &self.probe
+    }
+
+    fn run(
+        &self,
+        index: usize,
+        open_lock: &Mutex<()>,
+        on_progress: &Arc<ProgressHandler>,
+    ) -> Result<(), FlashJobError> {
+        // Opening probes concurrently confuses some USB stacks, so only the flashing itself
+        // runs in parallel.
+        let mut probe = {
+            let _guard = open_lock
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            Lister::new().open(self.probe.clone())?
+        };
+
+        if let Some(speed) = self.speed {
+            probe.set_speed(speed)?;
+        }
+
+        let mut session = if self.under_reset {
+            probe.attach_under_reset(self.target.clone(), self.permissions.clone())
+        } else {
+            probe.attach(self.target.clone(), self.permissions.clone())
+        }
+        .map_err(FlashJobError::Attach)?;
+
+        let on_progress = on_progress.clone();
+        let options = DownloadOptions {
+            progress: Some(FlashProgress::new(move |event| on_progress(index, event))),
+            do_chip_erase: self.chip_erase,
+            verify: self.verify,
+            incremental: self.incremental,
+            ..DownloadOptions::default()
+        };
+
+        download_file_with_options(&mut session, &self.path, self.format.clone(), options)?;
+
+        Ok(())
+    }
+}
+
+/// The handler of the progress events of all jobs, which is called with the index of the job.
+type ProgressHandler = dyn Fn(usize, ProgressEvent) + Send + Sync;
+
+/// An error of a single [`FlashJob`].
+#[derive(Debug, thiserror::Error)]
+pub enum FlashJobError {
+    /// The probe could not be opened or configured.
+    #[error("Failed to open the probe")]
+    Probe(#[from] DebugProbeError),
+    /// Attaching to the target failed.
+    #[error("Failed to attach to the target")]
+    Attach(#[source] Error),
+    /// Flashing the file failed.
+    #[error("Failed to flash the file")]
+    Download(#[from] FileDownloadError),
+    /// The job panicked.
+    #[error("The flash job panicked")]
+    Panicked,
+}
+
+/// The results of [`flash_parallel`], in the order of the jobs.
+#[derive(Debug)]
+pub struct ParallelFlashReport {
+    /// The probe and the result of each job.
+    pub results: Vec<(DebugProbeSelector, Result<(), FlashJobError>)>,
+}
+
+impl ParallelFlashReport {
+    /// Returns `true` if all jobs succeeded.
+    pub fn is_success(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// The probes and errors of the failed jobs.
+    pub fn failures(&self) -> impl Iterator<Item = (&DebugProbeSelector, &FlashJobError)> {
+        self.results
+            .iter()
+            .filter_map(|(probe, result)| result.as_ref().err().map(|error| (probe, error)))
+    }
+}
+
+/// Flashes several boards at once, each job in its own thread with its own session.
+///
+/// `on_progress` receives the progress events of all jobs, with the index of the job in
+/// `jobs`, and is called from the threads of the jobs. A failing job doesn't stop the
+/// other jobs, the result of each job is reported in the returned [`ParallelFlashReport`].
+pub fn flash_parallel(
+    jobs: &[FlashJob],
+    on_progress: impl Fn(usize, ProgressEvent) + Send + Sync + 'static,
+) -> ParallelFlashReport {
+    let open_lock = Mutex::new(());
+    let on_progress: Arc<ProgressHandler> = Arc::new(on_progress);
+
+    let results = std::thread::scope(|scope| {
+        let handles = jobs
+            .iter()
+            .enumerate()
+            .map(|(index, job)| {
+                let open_lock = &open_lock;
+                let on_progress = &on_progress;
+                scope.spawn(move || job.run(index, open_lock, on_progress))
+            })
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap_or(Err(FlashJobError::Panicked)))
+            .collect::<Vec<_>>()
+    });
+
+    ParallelFlashReport {
+        results: jobs
+            .iter()
+            .map(|job| job.probe.clone())
+            .zip(results)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_collects_failures() {
+        let probe = |serial: &str| DebugProbeSelector {
+            vendor_id: 0x1366,
+            product_id: 0x1015,
+            serial_number: Some(serial.to_string()),
+        };
+
+        let report = ParallelFlashReport {
+            results: vec![
+                (probe("1"), Ok(())),
+                (probe("2"), Err(FlashJobError::Panicked)),
+            ],
+        };
+
+        assert!(!report.is_success());
+        let failed = report
+            .failures()
+            .map(|(probe, _)| probe.serial_number.as_deref())
+            .collect::<Vec<_>>();
+        assert_eq!(failed, [Some("2")]);
+    }
+}
@@ -0,0 +1,231 @@
+use std::ops::Range;
+
+use probe_rs_target::{FlashProperties, NvmRegion, SectorDescription, SectorInfo};
+
+use crate::architecture::xtensa::sequences::RomFlashRoutines;
+
+/// The layout of the flash in an NVM region, as returned by
+/// [`Target::flash_regions`](crate::Target::flash_regions).
+///
+/// Host tools can use it to plan erases, e.g. to only erase the sectors touched by a partial
+/// update, and to report progress in the units the flash is actually written in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlashRegionInfo {
+    /// The NVM region.
+    pub region: NvmRegion,
+    /// The name of the flash algorithm which programs the region, or `None` if the region is
+    /// programmed without one, e.g. through the ROM of the chip.
+    pub algorithm: Option<String>,
+    /// The size of a programming page, the unit in which data is written.
+    pub page_size: u32,
+    /// The value of an erased byte.
+    pub erased_byte_value: u8,
+    /// The sectors of the region, as runs of equally sized sectors starting at the given offset
+    /// from the start of the region.
+    pub sectors: Vec<SectorDescription>,
+    /// Whether the whole chip can be erased at once, which is usually faster than erasing each
+    /// sector.
+    pub chip_erase: bool,
+}
+
+impl FlashRegionInfo {
+    /// Describes `region`, which is programmed by the flash algorithm `name` with the
+    /// `properties` of the algorithm.
+    pub(crate) fn from_algorithm(
+        region: &NvmRegion,
+        name: &str,
+        properties: &FlashProperties,
+        chip_erase: bool,
+    ) -> Self {
+        let algorithm_start = properties.address_range.start;
+        let mut sectors = Vec::new();
+        for (index, description) in properties.sectors.iter().enumerate() {
+            let start = algorithm_start + description.address;
+            let end = properties
+                .sectors
+                .get(index + 1)
+                .map_or(properties.address_range.end, |next| {
+                    algorithm_start + next.address
+                });
+
+            let start = start.max(region.range.start);
+            if start >= end.min(region.range.end) {
+                continue;
+            }
+            sectors.push(SectorDescription {
+                size: description.size,
+                address: start - region.range.start,
+            });
+        }
+
+        Self {
+            region: region.clone(),
+            algorithm: Some(name.to_string()),
+            page_size: properties.page_size,
+            erased_byte_value: properties.erased_byte_value,
+            sectors,
+            chip_erase,
+        }
+    }
+
+    /// Describes `region`, which is the SPI flash of a chip that is erased through its ROM, see
+    /// [`RomFlashRoutines`].
+    pub(crate) fn from_rom(region: &NvmRegion) -> Self {
+        Self {
+            region: region.clone(),
+            algorithm: None,
+            page_size: RomFlashRoutines::PAGE_SIZE,
+            erased_byte_value: 0xFF,
+            sectors: vec![SectorDescription {
+                size: RomFlashRoutines::SECTOR_SIZE,
+                address: 0,
+            }],
+            chip_erase: true,
+        }
+    }
+
+    /// Iterates over the sectors of the region.
+    pub fn iter_sectors(&self) -> impl Iterator<Item = SectorInfo> + '_ {
+        let start = self.region.range.start;
+        self.sectors
+            .iter()
+            .enumerate()
+            .filter(|(_, description)| description.size > 0)
+            .flat_map(move |(index, description)| {
+                let end = self
+                    .sectors
+                    .get(index + 1)
+                    .map_or(self.region.range.end, |next| start + next.address);
+
+                (start + description.address..end)
+                    .step_by(description.size as usize)
+                    .map(|base_address| SectorInfo {
+                        base_address,
+                        size: description.size,
+                    })
+            })
+    }
+
+    /// Returns the sector which contains `address`, if it is in the region.
+    pub fn sector_info(&self, address: u64) -> Option<SectorInfo> {
+        if !self.region.range.contains(&address) {
+            return None;
+        }
+
+        let offset = address - self.region.range.start;
+        let description = self.sectors.iter().rfind(|s| s.address <= offset)?;
+        let index = (offset - description.address) / description.size;
+
+        Some(SectorInfo {
+            base_address: self.region.range.start + description.address + index * description.size,
+            size: description.size,
+        })
+    }
+
+    /// The size of the smallest sector, i.e. the erase granularity of the region.
+    pub fn min_sector_size(&self) -> Option<u64> {
+        self.sectors
+            .iter()
+            .map(|description| description.size)
+            .min()
+    }
+
+    /// Returns the sectors that have to be erased to write `range`, in ascending order.
+    ///
+    /// Only the part of `range` within the region is considered.
+    pub fn sectors_to_erase(&self, range: Range<u64>) -> Vec<SectorInfo> {
+        self.iter_sectors()
+            .filter(|sector| {
+                sector.base_address < range.end && range.start < sector.base_address + sector.size
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn region(range: Range<u64>) -> NvmRegion {
+        NvmRegion {
+            name: None,
+            range,
+            is_boot_memory: false,
+            cores: vec!["main".to_string()],
+        }
+    }
+
+    #[test]
+    fn sectors_are_relative_to_the_region() {
+        let properties = FlashProperties {
+            address_range: 0x800_0000..0x810_0000,
+            page_size: 0x100,
+            erased_byte_value: 0xFF,
+            sectors: vec![
+                SectorDescription {
+                    size: 0x4000,
+                    address: 0x0,
+                },
+                SectorDescription {
+                    size: 0x1_0000,
+                    address: 0x1_0000,
+                },
+                SectorDescription {
+                    size: 0x2_0000,
+                    address: 0x2_0000,
+                },
+            ],
+            ..Default::default()
+        };
+
+        // The region starts in the middle of the 64 KiB sectors.
+        let info = FlashRegionInfo::from_algorithm(
+            &region(0x801_0000..0x804_0000),
+            "algo",
+            &properties,
+            false,
+        );
+        assert_eq!(
+            info.sectors,
+            [
+                SectorDescription {
+                    size: 0x1_0000,
+                    address: 0x0,
+                },
+                SectorDescription {
+                    size: 0x2_0000,
+                    address: 0x1_0000,
+                },
+            ]
+        );
+        assert_eq!(info.min_sector_size(), Some(0x1_0000));
+
+        let sectors = info
+            .iter_sectors()
+            .map(|sector| sector.base_address)
+            .collect::<Vec<_>>();
+        assert_eq!(sectors, [0x801_0000, 0x802_0000]);
+
+        assert_eq!(
+            info.sector_info(0x803_0000),
+            Some(SectorInfo {
+                base_address: 0x802_0000,
+                size: 0x2_0000,
+            })
+        );
+        assert_eq!(info.sector_info(0x804_0000), None);
+    }
+
+    #[test]
+    fn erase_plan_covers_the_range() {
+        let info = FlashRegionInfo::from_rom(&region(0x0..0x40_0000));
+
+        let plan = info.sectors_to_erase(0x1_0fff..0x1_2001);
+        let addresses = plan
+            .iter()
+            .map(|sector| sector.base_address)
+            .collect::<Vec<_>>();
+        assert_eq!(addresses, [0x1_0000, 0x1_1000, 0x1_2000]);
+        assert!(info.chip_erase);
+    }
+}
@@ -11,6 +11,9 @@ use super::target;
 
 const CONNECTION_STRING: &str = "127.0.0.1:1337";
 
+/// The longest time to wait between processing the GDB targets.
+const MAX_WAIT_TIME: Duration = Duration::from_millis(10);
+
 /// Configuration for a single GDB endpoint
 pub struct GdbInstanceConfiguration {
     /// The core type that will be sent to GDB
@@ -95,7 +98,9 @@ pub fn run<'a>(
 
     // Process every target in a loop
     loop {
-        let mut wait_time = Duration::ZERO;
+        // Sleep for the shortest time any target asked for. Starting from zero would make the loop
+        // spin and hold on to the session, starving anyone else sharing it (e.g. RTT polling).
+        let mut wait_time = MAX_WAIT_TIME;
 
         for target in targets.iter_mut() {
             wait_time = wait_time.min(target.process()?);
@@ -0,0 +1,108 @@
+use super::{GdbErrorExt, RuntimeTarget};
+use crate::WatchpointKind;
+
+use gdbstub::target::ext::breakpoints::{
+    Breakpoints, HwBreakpoint, HwBreakpointOps, HwWatchpoint, HwWatchpointOps, SwBreakpointOps,
+    WatchKind,
+};
+
+impl Breakpoints for RuntimeTarget<'_> {
+    fn support_sw_breakpoint(&mut self) -> Option<SwBreakpointOps<'_, Self>> {
+        None
+    }
+
+    fn support_hw_breakpoint(&mut self) -> Option<HwBreakpointOps<'_, Self>> {
+        Some(self)
+    }
+
+    fn support_hw_watchpoint(&mut self) -> Option<HwWatchpointOps<'_, Self>> {
+        Some(self)
+    }
+}
+
+impl HwBreakpoint for RuntimeTarget<'_> {
+    fn add_hw_breakpoint(
+        &mut self,
+        addr: u64,
+        _kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> gdbstub::target::TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.set_hw_breakpoint(addr).into_target_result()?;
+        }
+
+        Ok(true)
+    }
+
+    fn remove_hw_breakpoint(
+        &mut self,
+        addr: u64,
+        _kind: <Self::Arch as gdbstub::arch::Arch>::BreakpointKind,
+    ) -> gdbstub::target::TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.clear_hw_breakpoint(addr).into_target_result()?;
+        }
+
+        Ok(true)
+    }
+}
+
+impl HwWatchpoint for RuntimeTarget<'_> {
+    fn add_hw_watchpoint(
+        &mut self,
+        addr: u64,
+        len: u64,
+        kind: WatchKind,
+    ) -> gdbstub::target::TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.set_hw_watchpoint(addr, len, watchpoint_kind(kind))
+                .into_target_result()?;
+        }
+
+        Ok(true)
+    }
+
+    fn remove_hw_watchpoint(
+        &mut self,
+        addr: u64,
+        _len: u64,
+        _kind: WatchKind,
+    ) -> gdbstub::target::TargetResult<bool, Self> {
+        let mut session = self.session.lock().unwrap();
+
+        for core_id in &self.cores {
+            let mut core = session.core(*core_id).into_target_result()?;
+
+            core.clear_hw_watchpoint(addr).into_target_result()?;
+        }
+
+        Ok(true)
+    }
+}
+
+fn watchpoint_kind(kind: WatchKind) -> WatchpointKind {
+    match kind {
+        WatchKind::Write => WatchpointKind::Write,
+        WatchKind::Read => WatchpointKind::Read,
+        WatchKind::ReadWrite => WatchpointKind::ReadWrite,
+    }
+}
+
+pub(super) fn watch_kind(kind: WatchpointKind) -> WatchKind {
+    match kind {
+        WatchpointKind::Write => WatchKind::Write,
+        WatchpointKind::Read => WatchKind::Read,
+        WatchpointKind::ReadWrite => WatchKind::ReadWrite,
+    }
+}
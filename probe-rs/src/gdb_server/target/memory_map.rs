@@ -0,0 +1,84 @@
+use std::fmt::Write;
+use std::ops::Range;
+
+use super::RuntimeTarget;
+use crate::Target;
+
+use gdbstub::target::ext::memory_map::MemoryMap;
+use gdbstub::target::TargetResult;
+use probe_rs_target::MemoryRegion;
+
+impl MemoryMap for RuntimeTarget<'_> {
+    fn memory_map_xml(
+        &self,
+        offset: u64,
+        length: usize,
+        buf: &mut [u8],
+    ) -> TargetResult<usize, Self> {
+        let xml = {
+            let session = self.session.lock().unwrap();
+            gdb_memory_map(session.target(), self.cores[0])
+        };
+
+        let xml = xml.as_bytes();
+        let start = usize::try_from(offset).unwrap_or(usize::MAX).min(xml.len());
+        let end = start.saturating_add(length.min(buf.len())).min(xml.len());
+        let data = &xml[start..end];
+
+        buf[..data.len()].copy_from_slice(data);
+        Ok(data.len())
+    }
+}
+
+/// Builds the GDB memory map of the core `core_id` from the memory regions of the target.
+///
+/// RAM and peripherals are reported as RAM, in which GDB inserts software breakpoints. Non-volatile
+/// memory is reported as ROM, so that GDB uses hardware breakpoints for code executed from
+/// flash, which is common on Xtensa chips.
+pub(crate) fn gdb_memory_map(target: &Target, core_id: usize) -> String {
+    let core_name = &target.cores[core_id].name;
+
+    let mut xml = String::from(
+        r#"<?xml version="1.0"?>
+<!DOCTYPE memory-map PUBLIC "+//IDN gnu.org//DTD GDB Memory Map V1.0//EN" "http://sourceware.org/gdb/gdb-memory-map.dtd">
+<memory-map>
+"#,
+    );
+
+    // GDB rejects memory maps with overlapping regions.
+    let mut listed: Vec<Range<u64>> = Vec::new();
+    for region in &target.memory_map {
+        if !region.cores().contains(core_name) {
+            continue;
+        }
+
+        let range = region.address_range();
+        if listed
+            .iter()
+            .any(|other| range.start < other.end && other.start < range.end)
+        {
+            tracing::debug!(
+                "Leaving overlapping region {:#x?} out of the memory map",
+                range
+            );
+            continue;
+        }
+
+        let kind = match region {
+            MemoryRegion::Ram(_) | MemoryRegion::Generic(_) => "ram",
+            MemoryRegion::Nvm(_) => "rom",
+        };
+        writeln!(
+            xml,
+            r#"<memory type="{kind}" start="{:#x}" length="{:#x}"/>"#,
+            range.start,
+            range.end - range.start
+        )
+        .unwrap();
+
+        listed.push(range);
+    }
+
+    xml.push_str("</memory-map>");
+    xml
+}
@@ -83,29 +83,53 @@ pub mod gdb_server;
 #[warn(missing_docs)]
 mod memory;
 #[warn(missing_docs)]
+pub mod nonblocking;
+#[warn(missing_docs)]
 mod probe;
 #[warn(missing_docs)]
 #[cfg(feature = "rtt")]
 pub mod rtt;
 #[warn(missing_docs)]
 mod session;
+#[warn(missing_docs)]
+mod session_config;
 #[cfg(test)]
 mod test;
 
 pub use crate::config::{CoreType, InstructionSet, Target};
 pub use crate::core::{
-    exception_handler_for_core, Architecture, BreakpointCause, Core, CoreDump, CoreDumpError,
-    CoreInformation, CoreInterface, CoreRegister, CoreRegisters, CoreState, CoreStatus, HaltReason,
-    MemoryMappedRegister, RegisterId, RegisterRole, RegisterValue, SemihostingCommand,
-    SpecificCoreState, VectorCatchCondition,
+    exception_handler_for_core, AllocatorLayout, Architecture, BreakpointCause, Checkpoint, Core,
+    CoreContext, CoreDump, CoreDumpError, CoreEvent, CoreEventPoller, CoreInformation,
+    CoreInterface, CoreRegister, CoreRegisters, CoreState, CoreStatus, DataTraceRecord,
+    DisassembledInstruction, ExceptionCause, ExecutionHistory, FrameRegisters, HaltInspection,
+    HaltInspector, HaltReason, HaltingOperation, HeapStats, MemoryHook, MemoryMappedRegister,
+    MemorySnapshot, PanicInspector, RedactedRange, RegisterDataType, RegisterId, RegisterRole,
+    RegisterValue, RegisterWritePolicy, ResetCatch, ResetCause, ReverseStop, SelfTestCheck,
+    SelfTestOutcome, SelfTestReport, SemihostingCommand, SpecificCoreState, StackGuard,
+    StepOptions, TargetHeap, TimeoutPolicy, TraceWatchpoint, TraceWatchpointId, TriageFrame,
+    TriageRegister, TriageReport, VectorCatchCondition, Watch, WatchEvent, WatchId, WatchPoller,
+    WatchType, WatchValue, Watchpoint, WatchpointKind, WriteProtectedRange,
 };
 pub use crate::error::Error;
-pub use crate::memory::MemoryInterface;
+pub use crate::memory::{
+    crc32, read_peripheral_register, register_bits, DumpCompression, DumpOptions, DumpProgress,
+    DumpSummary, ExactWord, MemoryAttributes, MemoryInterface, MemoryUtilities, ScratchLoan,
+    TargetMemoryTools, TransferProgress,
+};
+pub use crate::probe::bitbang::{BitbangIo, BitbangProbe, PinMapping};
+pub use crate::probe::recording::{
+    JtagAccess, JtagRecorder, JtagRecording, JtagTransaction, ReplayProbe,
+};
+pub use crate::probe::retry::{ErrorClass, Retry, RetryPolicy};
+pub use crate::probe::telemetry::{OperationStats, Telemetry, TraceEntry};
 pub use crate::probe::{
     fake_probe::FakeProbe, list::Lister, AttachMethod, DebugProbe, DebugProbeError, DebugProbeInfo,
-    DebugProbeSelector, DebugProbeType, Probe, ProbeCreationError, WireProtocol,
+    DebugProbeSelector, DebugProbeType, JtagChainItem, Probe, ProbeCreationError, WireProtocol,
+};
+pub use crate::session::{AttachOptions, DetachPolicy, Permissions, Session, WakePolicy};
+pub use crate::session_config::{
+    CoreConfig, RttConfig, SessionConfig, SessionConfigError, WatchdogPolicy,
 };
-pub use crate::session::{Permissions, Session};
 
 // Exports only used in tests
 #[cfg(feature = "test")]
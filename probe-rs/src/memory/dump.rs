@@ -0,0 +1,283 @@
+//! Streaming dumps of large ranges of target memory, see [`MemoryUtilities::dump`].
+//!
+//! [`MemoryUtilities::dump`]: super::MemoryUtilities::dump
+
+use std::{
+    io::{self, Write},
+    ops::Range,
+    sync::mpsc,
+    time::{Duration, Instant},
+};
+
+use super::MemoryInterface;
+use crate::Error;
+
+/// The number of chunks which are read ahead of the writer.
+const QUEUE_DEPTH: usize = 2;
+
+/// How the memory contents are compressed before they are written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DumpCompression {
+    /// The contents are written as they are.
+    #[default]
+    None,
+    /// The contents are written as an LZ4 frame, which can be decompressed with the `lz4` tool.
+    ///
+    /// Requires the `lz4` feature.
+    #[cfg(feature = "lz4")]
+    Lz4,
+}
+
+/// Options for [`MemoryUtilities::dump`].
+///
+/// [`MemoryUtilities::dump`]: super::MemoryUtilities::dump
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DumpOptions {
+    chunk_size: usize,
+    compression: DumpCompression,
+}
+
+impl Default for DumpOptions {
+    fn default() -> Self {
+        Self {
+            chunk_size: 64 * 1024,
+            compression: DumpCompression::None,
+        }
+    }
+}
+
+impl DumpOptions {
+    /// Sets the number of bytes read from the target at once. Larger chunks are faster, but
+    /// report progress less often.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Sets how the memory contents are compressed.
+    pub fn with_compression(mut self, compression: DumpCompression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// The number of bytes read from the target at once.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// How the memory contents are compressed.
+    pub fn compression(&self) -> DumpCompression {
+        self.compression
+    }
+}
+
+/// The progress of a memory dump, reported after each chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DumpProgress {
+    /// The number of bytes read from the target so far.
+    pub transferred: u64,
+    /// The total number of bytes of the dump.
+    pub total: u64,
+    /// The time since the dump was started.
+    pub elapsed: Duration,
+}
+
+impl DumpProgress {
+    /// The average number of bytes read from the target per second.
+    pub fn bytes_per_second(&self) -> f64 {
+        bytes_per_second(self.transferred, self.elapsed)
+    }
+}
+
+/// The result of a finished memory dump.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DumpSummary {
+    /// The number of bytes read from the target.
+    pub read: u64,
+    /// The number of bytes written to the writer, after compression.
+    pub written: u64,
+    /// The duration of the dump.
+    pub elapsed: Duration,
+}
+
+impl DumpSummary {
+    /// The average number of bytes read from the target per second.
+    pub fn bytes_per_second(&self) -> f64 {
+        bytes_per_second(self.read, self.elapsed)
+    }
+}
+
+fn bytes_per_second(bytes: u64, elapsed: Duration) -> f64 {
+    bytes as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+}
+
+/// Reads `range` from `memory` and writes it to `writer`.
+///
+/// The chunks are compressed and written on a separate thread, so that the host work overlaps
+/// with the next read from the target.
+pub(crate) fn dump<M, W>(
+    memory: &mut M,
+    range: Range<u64>,
+    writer: W,
+    options: DumpOptions,
+    progress: &mut dyn FnMut(DumpProgress) -> bool,
+) -> Result<DumpSummary, Error>
+where
+    M: MemoryInterface + ?Sized,
+    W: Write + Send,
+{
+    let start = Instant::now();
+    let total = range.end.saturating_sub(range.start);
+
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::sync_channel::<Vec<u8>>(QUEUE_DEPTH);
+        let compression = options.compression;
+        let writer = scope.spawn(move || write_chunks(writer, receiver, compression));
+
+        let mut transferred = 0;
+        while transferred < total {
+            let mut chunk = vec![0; (total - transferred).min(options.chunk_size as u64) as usize];
+            memory.read(range.start + transferred, &mut chunk)?;
+            transferred += chunk.len() as u64;
+
+            // The writer only hangs up if it failed, which is reported below.
+            if sender.send(chunk).is_err() {
+                break;
+            }
+
+            let progress = progress(DumpProgress {
+                transferred,
+                total,
+                elapsed: start.elapsed(),
+            });
+            if !progress {
+                return Err(Error::Cancelled);
+            }
+        }
+        drop(sender);
+
+        let written = writer
+            .join()
+            .expect("the memory dump writer panicked")
+            .map_err(|error| {
+                Error::Other(anyhow::Error::new(error).context("Failed to write the memory dump"))
+            })?;
+
+        Ok(DumpSummary {
+            read: transferred,
+            written,
+            elapsed: start.elapsed(),
+        })
+    })
+}
+
+/// Writes the received chunks to `writer`, returning the number of bytes written.
+fn write_chunks<W: Write>(
+    writer: W,
+    chunks: mpsc::Receiver<Vec<u8>>,
+    compression: DumpCompression,
+) -> io::Result<u64> {
+    let mut writer = CountingWriter {
+        inner: writer,
+        count: 0,
+    };
+
+    match compression {
+        DumpCompression::None => {
+            for chunk in chunks {
+                writer.write_all(&chunk)?;
+            }
+        }
+        #[cfg(feature = "lz4")]
+        DumpCompression::Lz4 => {
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(&mut writer);
+            for chunk in chunks {
+                encoder.write_all(&chunk)?;
+            }
+            encoder.finish()?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(writer.count)
+}
+
+/// Counts the bytes written to the inner writer.
+struct CountingWriter<W> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::MockMemory;
+
+    #[test]
+    fn dump_in_chunks() {
+        let mut memory = MockMemory::new();
+        let words = (0..64).collect::<Vec<u32>>();
+        memory.add_word_range(0x2000_0000, &words);
+
+        let mut reports = Vec::new();
+        let mut output = Vec::new();
+        let summary = dump(
+            &mut memory,
+            0x2000_0004..0x2000_0100,
+            &mut output,
+            DumpOptions::default().with_chunk_size(100),
+            &mut |progress| {
+                reports.push(progress.transferred);
+                true
+            },
+        )
+        .unwrap();
+
+        let expected = words[1..]
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(output, expected);
+        assert_eq!(summary.read, 252);
+        assert_eq!(summary.written, 252);
+        assert_eq!(reports, [100, 200, 252]);
+    }
+
+    #[test]
+    #[cfg(feature = "lz4")]
+    fn dump_compressed() {
+        let mut memory = MockMemory::new();
+        memory.add_word_range(0x2000_0000, &[0; 1024]);
+
+        let mut output = Vec::new();
+        let summary = dump(
+            &mut memory,
+            0x2000_0000..0x2000_1000,
+            &mut output,
+            DumpOptions::default().with_compression(DumpCompression::Lz4),
+            &mut |_| true,
+        )
+        .unwrap();
+
+        assert!(summary.written < summary.read);
+        let mut decompressed = Vec::new();
+        std::io::copy(
+            &mut lz4_flex::frame::FrameDecoder::new(output.as_slice()),
+            &mut decompressed,
+        )
+        .unwrap();
+        assert_eq!(decompressed, vec![0; 4096]);
+    }
+}
@@ -0,0 +1,147 @@
+//! Accesses to peripheral registers with exactly one bus access of a given width.
+
+use super::MemoryInterface;
+use crate::Error;
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for u8 {}
+    impl Sealed for u32 {}
+}
+
+/// The width of a peripheral register accessed with [`MemoryInterface::read_word_exact`] and
+/// [`MemoryInterface::write_word_exact`].
+///
+/// This is implemented for `u8` and `u32`.
+pub trait ExactWord: Copy + sealed::Sealed {
+    /// Reads the register at `address` with exactly one access of this width.
+    fn read_exact<M: MemoryInterface + ?Sized>(memory: &mut M, address: u64)
+        -> Result<Self, Error>;
+
+    /// Writes the register at `address` with exactly one access of this width.
+    fn write_exact<M: MemoryInterface + ?Sized>(
+        memory: &mut M,
+        address: u64,
+        value: Self,
+    ) -> Result<(), Error>;
+}
+
+impl ExactWord for u8 {
+    fn read_exact<M: MemoryInterface + ?Sized>(
+        memory: &mut M,
+        address: u64,
+    ) -> Result<Self, Error> {
+        memory.read_word_8_exact(address)
+    }
+
+    fn write_exact<M: MemoryInterface + ?Sized>(
+        memory: &mut M,
+        address: u64,
+        value: Self,
+    ) -> Result<(), Error> {
+        memory.write_word_8_exact(address, value)
+    }
+}
+
+impl ExactWord for u32 {
+    fn read_exact<M: MemoryInterface + ?Sized>(
+        memory: &mut M,
+        address: u64,
+    ) -> Result<Self, Error> {
+        memory.read_word_32_exact(address)
+    }
+
+    fn write_exact<M: MemoryInterface + ?Sized>(
+        memory: &mut M,
+        address: u64,
+        value: Self,
+    ) -> Result<(), Error> {
+        memory.write_word_32_exact(address, value)
+    }
+}
+
+/// Reads the `width` bit peripheral register at `address` with an aligned 32-bit access, as some
+/// peripheral buses (e.g. on Xtensa chips) only allow those.
+///
+/// Registers narrower than a word may not be word aligned, so the register is shifted down to
+/// bit 0, and the neighbouring registers in the word are masked off.
+pub fn read_peripheral_register<M: MemoryInterface + ?Sized>(
+    memory: &mut M,
+    address: u64,
+    width: u32,
+) -> Result<u32, Error> {
+    let byte_offset = address % 4;
+    let word = memory.read_word_32(address - byte_offset)?;
+
+    Ok(register_bits(word, byte_offset as u32 * 8, width))
+}
+
+/// Returns the `width` bits of `value` starting at bit `offset`, shifted down to bit 0.
+pub fn register_bits(value: u32, offset: u32, width: u32) -> u32 {
+    let mask = u32::MAX.checked_shr(32 - width.min(32)).unwrap_or(0);
+
+    value.checked_shr(offset).unwrap_or(0) & mask
+}
+
+/// Returns [`Error::MemoryNotAligned`] if `address` is not a multiple of `alignment`.
+pub(crate) fn check_alignment(address: u64, alignment: usize) -> Result<(), Error> {
+    if address % alignment as u64 != 0 {
+        return Err(Error::MemoryNotAligned { address, alignment });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::MockMemory;
+
+    #[test]
+    fn exact_reads_must_be_aligned() {
+        let mut memory = MockMemory::new();
+        memory.add_word_range(0x4000_0000, &[0x1234_5678, 0x9abc_def0]);
+
+        assert_eq!(
+            memory.read_word_exact::<u32>(0x4000_0004).unwrap(),
+            0x9abc_def0
+        );
+        assert!(matches!(
+            memory.read_word_exact::<u32>(0x4000_0002),
+            Err(Error::MemoryNotAligned {
+                address: 0x4000_0002,
+                alignment: 4,
+            })
+        ));
+    }
+
+    #[test]
+    fn narrow_registers_are_read_from_the_containing_word() {
+        let mut memory = MockMemory::new();
+        memory.add_word_range(0x4000_0000, &[0x1234_5678]);
+
+        assert_eq!(
+            read_peripheral_register(&mut memory, 0x4000_0000, 32).unwrap(),
+            0x1234_5678
+        );
+        assert_eq!(
+            read_peripheral_register(&mut memory, 0x4000_0002, 16).unwrap(),
+            0x1234
+        );
+        assert_eq!(
+            read_peripheral_register(&mut memory, 0x4000_0001, 8).unwrap(),
+            0x56
+        );
+    }
+
+    #[test]
+    fn register_fields() {
+        assert_eq!(register_bits(0x8000_00F0, 4, 4), 0xF);
+        assert_eq!(register_bits(0x8000_00F0, 31, 1), 1);
+        assert_eq!(register_bits(0x8000_00F0, 0, 32), 0x8000_00F0);
+        // Empty fields, and fields past the end of the register, have no bits.
+        assert_eq!(register_bits(0x8000_00F0, 4, 0), 0);
+        assert_eq!(register_bits(0x8000_00F0, 32, 4), 0);
+    }
+}
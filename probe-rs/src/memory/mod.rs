@@ -1,7 +1,62 @@
+use crate::architecture::arm::ArmError;
 use crate::error::Error;
+use crate::probe::retry::ErrorClass;
 
 use anyhow::{anyhow, Result};
 use scroll::Pread;
+use std::ops::Range;
+
+mod dump;
+mod mmio;
+mod scratch;
+mod utilities;
+
+pub use dump::{DumpCompression, DumpOptions, DumpProgress, DumpSummary};
+pub(crate) use mmio::check_alignment;
+pub use mmio::{read_peripheral_register, register_bits, ExactWord};
+pub use scratch::ScratchLoan;
+pub(crate) use scratch::ScratchPool;
+pub use utilities::{crc32, MemoryUtilities, TargetMemoryTools};
+
+/// The size of the chunks that transfers with progress reporting are split into.
+const PROGRESS_CHUNK_SIZE: usize = 16 * 1024;
+
+/// The progress of a memory transfer, reported by [`MemoryInterface::read_8_with_progress`]
+/// and [`MemoryInterface::write_8_with_progress`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferProgress {
+    /// The number of bytes transferred so far.
+    pub transferred: usize,
+    /// The total number of bytes of the transfer.
+    pub total: usize,
+}
+
+/// How the memory at an address is accessed, as reported by [`Core::memory_attributes`].
+///
+/// [`Core::memory_attributes`]: crate::Core::memory_attributes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryAttributes {
+    /// The offset in external flash that the memory is mapped from through the cache and MMU,
+    /// if any.
+    pub flash_offset: Option<u64>,
+    /// The offset in external PSRAM that the memory is mapped from through the cache and MMU,
+    /// if any.
+    pub psram_offset: Option<u64>,
+    /// Whether the flash contents are encrypted.
+    pub encrypted: bool,
+    /// Whether reads return the encrypted contents. Reads through the cache return decrypted
+    /// data, unless decryption was disabled with [`Core::set_flash_decryption`].
+    ///
+    /// [`Core::set_flash_decryption`]: crate::Core::set_flash_decryption
+    pub ciphertext: bool,
+}
+
+impl MemoryAttributes {
+    /// Returns whether the memory is accessed through the cache and MMU.
+    pub fn is_cached(&self) -> bool {
+        self.flash_offset.is_some() || self.psram_offset.is_some()
+    }
+}
 
 /// An interface to be implemented for drivers that allow target memory access.
 pub trait MemoryInterface {
@@ -44,6 +99,30 @@ pub trait MemoryInterface {
     /// Read a block of 8bit words at `address`.
     fn read_8(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error>;
 
+    /// Read a block of 8bit words at `address` like [`MemoryInterface::read_8`], calling
+    /// `progress` after each chunk of the transfer.
+    ///
+    /// The transfer is cancelled if `progress` returns `false`, in which case
+    /// [`Error::Cancelled`] is returned.
+    fn read_8_with_progress(
+        &mut self,
+        address: u64,
+        data: &mut [u8],
+        progress: &mut dyn FnMut(TransferProgress) -> bool,
+    ) -> Result<(), Error> {
+        let total = data.len();
+        let mut transferred = 0;
+        for chunk in data.chunks_mut(PROGRESS_CHUNK_SIZE) {
+            self.read_8(address + transferred as u64, chunk)?;
+            transferred += chunk.len();
+
+            if !progress(TransferProgress { transferred, total }) {
+                return Err(Error::Cancelled);
+            }
+        }
+        Ok(())
+    }
+
     /// Reads bytes using 64 bit memory access. Address must be 64 bit aligned
     /// and data must be an exact multiple of 8.
     fn read_mem_64bit(&mut self, address: u64, data: &mut [u8]) -> Result<(), Error> {
@@ -110,6 +189,43 @@ pub trait MemoryInterface {
         Ok(())
     }
 
+    /// Reads a block of memory like [`MemoryInterface::read`], but keeps going past words that
+    /// can't be read, e.g. unmapped or faulting peripheral registers.
+    ///
+    /// The block is read at once first. If that fails, it is read again one `word_size` bytes
+    /// long word at a time. Returns whether each word could be read, and sets the words that
+    /// could not be read to 0. Errors of the probe itself are still returned.
+    fn read_partial(
+        &mut self,
+        address: u64,
+        data: &mut [u8],
+        word_size: usize,
+    ) -> Result<Vec<bool>, Error> {
+        let words = data.len().div_ceil(word_size);
+
+        match self.read(address, data) {
+            Ok(()) => return Ok(vec![true; words]),
+            Err(error) if is_probe_failure(&error) => return Err(error),
+            Err(error) => tracing::debug!("Reading word by word after error: {}", error),
+        }
+
+        let mut valid = Vec::with_capacity(words);
+        for (index, word) in data.chunks_mut(word_size).enumerate() {
+            let word_address = address + (index * word_size) as u64;
+            match self.read(word_address, word) {
+                Ok(()) => valid.push(true),
+                Err(error) if is_probe_failure(&error) => return Err(error),
+                Err(error) => {
+                    tracing::debug!("Failed to read {:#010x}: {}", word_address, error);
+                    word.fill(0);
+                    valid.push(false);
+                }
+            }
+        }
+
+        Ok(valid)
+    }
+
     /// Write a 64bit word at `address`.
     ///
     /// The address where the write should be performed at has to be word aligned.
@@ -142,6 +258,30 @@ pub trait MemoryInterface {
     /// Write a block of 8bit words at `address`.
     fn write_8(&mut self, address: u64, data: &[u8]) -> Result<(), Error>;
 
+    /// Write a block of 8bit words at `address` like [`MemoryInterface::write_8`], calling
+    /// `progress` after each chunk of the transfer.
+    ///
+    /// The transfer is cancelled if `progress` returns `false`, in which case
+    /// [`Error::Cancelled`] is returned. The chunks written until then stay written.
+    fn write_8_with_progress(
+        &mut self,
+        address: u64,
+        data: &[u8],
+        progress: &mut dyn FnMut(TransferProgress) -> bool,
+    ) -> Result<(), Error> {
+        let total = data.len();
+        let mut transferred = 0;
+        for chunk in data.chunks(PROGRESS_CHUNK_SIZE) {
+            self.write_8(address + transferred as u64, chunk)?;
+            transferred += chunk.len();
+
+            if !progress(TransferProgress { transferred, total }) {
+                return Err(Error::Cancelled);
+            }
+        }
+        Ok(())
+    }
+
     /// Writes bytes using 64 bit memory access. Address must be 64 bit aligned
     /// and data must be an exact multiple of 8.
     fn write_mem_64bit(&mut self, address: u64, data: &[u8]) -> Result<(), Error> {
@@ -233,6 +373,63 @@ pub trait MemoryInterface {
         Ok(())
     }
 
+    /// Reads the 32-bit peripheral register at `address` with exactly one aligned 32-bit access.
+    ///
+    /// Unlike [`MemoryInterface::read_word_32`], the access is never split into smaller accesses,
+    /// widened, retried or followed by a prefetch, so this is safe for registers whose reads have
+    /// side effects, like FIFOs. Returns [`Error::MemoryNotAligned`] if `address` is not aligned.
+    fn read_word_32_exact(&mut self, address: u64) -> Result<u32, Error> {
+        check_alignment(address, 4)?;
+        self.read_word_32(address)
+    }
+
+    /// Reads the 8-bit peripheral register at `address` with exactly one 8-bit access.
+    ///
+    /// See [`MemoryInterface::read_word_32_exact`].
+    fn read_word_8_exact(&mut self, address: u64) -> Result<u8, Error> {
+        if !self.supports_8bit_transfers()? {
+            return Err(Error::NotImplemented("exact 8-bit accesses"));
+        }
+        self.read_word_8(address)
+    }
+
+    /// Writes the 32-bit peripheral register at `address` with exactly one aligned 32-bit access.
+    ///
+    /// Unlike [`MemoryInterface::write_word_32`], the access is never split into a
+    /// read-modify-write of smaller or wider accesses, so this is safe for registers like FIFOs
+    /// or write-1-to-clear flags. Returns [`Error::MemoryNotAligned`] if `address` is not aligned.
+    fn write_word_32_exact(&mut self, address: u64, data: u32) -> Result<(), Error> {
+        check_alignment(address, 4)?;
+        self.write_word_32(address, data)
+    }
+
+    /// Writes the 8-bit peripheral register at `address` with exactly one 8-bit access.
+    ///
+    /// See [`MemoryInterface::write_word_32_exact`].
+    fn write_word_8_exact(&mut self, address: u64, data: u8) -> Result<(), Error> {
+        if !self.supports_8bit_transfers()? {
+            return Err(Error::NotImplemented("exact 8-bit accesses"));
+        }
+        self.write_word_8(address, data)
+    }
+
+    /// Reads the peripheral register at `address` with exactly one access of the width of `W`,
+    /// e.g. `memory.read_word_exact::<u32>(address)`.
+    fn read_word_exact<W: ExactWord>(&mut self, address: u64) -> Result<W, Error>
+    where
+        Self: Sized,
+    {
+        W::read_exact(self, address)
+    }
+
+    /// Writes the peripheral register at `address` with exactly one access of the width of `W`.
+    fn write_word_exact<W: ExactWord>(&mut self, address: u64, data: W) -> Result<(), Error>
+    where
+        Self: Sized,
+    {
+        W::write_exact(self, address, data)
+    }
+
     /// Returns whether the current platform supports native 8bit transfers.
     fn supports_8bit_transfers(&self) -> Result<bool, Error>;
 
@@ -243,6 +440,17 @@ pub trait MemoryInterface {
     /// can be called.  Takes no arguments, but may return failure if a batched
     /// operation fails.
     fn flush(&mut self) -> Result<(), Error>;
+
+    /// Makes code written to `range` visible to the instruction fetches of the core.
+    ///
+    /// Call this after downloading code and before executing it. Depending on the architecture,
+    /// this cleans the data cache and invalidates the instruction cache for `range`, and
+    /// synchronizes the instruction pipeline. The default implementation only flushes
+    /// outstanding writes, which suffices for cores without caches.
+    fn code_barrier(&mut self, range: Range<u64>) -> Result<(), Error> {
+        let _ = range;
+        self.flush()
+    }
 }
 
 impl<T> MemoryInterface for &mut T
@@ -309,6 +517,22 @@ where
         (*self).write(address, data)
     }
 
+    fn read_word_32_exact(&mut self, address: u64) -> Result<u32, Error> {
+        (*self).read_word_32_exact(address)
+    }
+
+    fn read_word_8_exact(&mut self, address: u64) -> Result<u8, Error> {
+        (*self).read_word_8_exact(address)
+    }
+
+    fn write_word_32_exact(&mut self, address: u64, data: u32) -> Result<(), Error> {
+        (*self).write_word_32_exact(address, data)
+    }
+
+    fn write_word_8_exact(&mut self, address: u64, data: u8) -> Result<(), Error> {
+        (*self).write_word_8_exact(address, data)
+    }
+
     fn supports_8bit_transfers(&self) -> Result<bool, Error> {
         MemoryInterface::supports_8bit_transfers(*self)
     }
@@ -316,11 +540,27 @@ where
     fn flush(&mut self) -> Result<(), Error> {
         (*self).flush()
     }
+
+    fn code_barrier(&mut self, range: Range<u64>) -> Result<(), Error> {
+        (*self).code_barrier(range)
+    }
 }
 
 // Helper functions to validate address space constraints
 
 /// Validate that an input address is valid for 32-bit only systems
+/// Returns whether `error` means that the communication with the probe failed, rather than that
+/// the accessed memory could not be read.
+fn is_probe_failure(error: &Error) -> bool {
+    let class = match error {
+        Error::Probe(error) | Error::Arm(ArmError::Probe(error)) => error.error_class(),
+        Error::Xtensa(error) => error.error_class(),
+        _ => None,
+    };
+
+    class == Some(ErrorClass::Probe)
+}
+
 pub(crate) fn valid_32bit_address(address: u64) -> Result<u32, Error> {
     let address: u32 = address
         .try_into()
@@ -0,0 +1,106 @@
+//! Loans of the debugger scratch region of a target, see [`Session::borrow_scratch`].
+//!
+//! [`Session::borrow_scratch`]: crate::Session::borrow_scratch
+
+use std::ops::Range;
+
+/// A chunk of the scratch region of the target, loaned out by [`Session::borrow_scratch`].
+///
+/// The chunk belongs to the loan until it is returned with [`Session::return_scratch`], which
+/// also restores the contents it had before. A loan which is dropped without being returned
+/// stays allocated, and its original contents are lost.
+///
+/// [`Session::borrow_scratch`]: crate::Session::borrow_scratch
+/// [`Session::return_scratch`]: crate::Session::return_scratch
+#[derive(Debug)]
+#[must_use = "the original memory contents are only restored by `Session::return_scratch`"]
+pub struct ScratchLoan {
+    pub(crate) core: usize,
+    pub(crate) range: Range<u64>,
+    pub(crate) saved: Vec<u8>,
+}
+
+impl ScratchLoan {
+    /// The start address of the loaned chunk.
+    pub fn address(&self) -> u64 {
+        self.range.start
+    }
+
+    /// The size of the loaned chunk in bytes.
+    pub fn size(&self) -> u64 {
+        self.range.end - self.range.start
+    }
+
+    /// The address range of the loaned chunk.
+    pub fn range(&self) -> Range<u64> {
+        self.range.clone()
+    }
+}
+
+/// The chunks of the scratch region which are currently loaned out.
+#[derive(Debug, Default)]
+pub(crate) struct ScratchPool {
+    /// The loaned ranges, sorted by address.
+    loans: Vec<Range<u64>>,
+}
+
+impl ScratchPool {
+    /// Allocates the first free range of `size` bytes in `region`, starting at a multiple of
+    /// `align`.
+    ///
+    /// Returns `None` if no such range is free.
+    pub fn allocate(&mut self, region: &Range<u64>, size: u64, align: u64) -> Option<Range<u64>> {
+        let align = align.max(1);
+        let mut start = region.start.next_multiple_of(align);
+
+        let mut index = 0;
+        loop {
+            let end = start.checked_add(size)?;
+            let next = self.loans.get(index);
+            let limit = next.map_or(region.end, |loan| loan.start);
+
+            if end <= limit {
+                self.loans.insert(index, start..end);
+                return Some(start..end);
+            }
+
+            let next = next?;
+            start = next.end.next_multiple_of(align);
+            index += 1;
+        }
+    }
+
+    /// Frees the loaned `range`.
+    pub fn release(&mut self, range: &Range<u64>) {
+        self.loans.retain(|loan| loan != range);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loans_do_not_overlap() {
+        let region = 0x2000_0002..0x2000_0100;
+        let mut pool = ScratchPool::default();
+
+        let first = pool.allocate(&region, 0x10, 4).unwrap();
+        assert_eq!(first, 0x2000_0004..0x2000_0014);
+
+        let second = pool.allocate(&region, 0x80, 0x40).unwrap();
+        assert_eq!(second, 0x2000_0040..0x2000_00c0);
+
+        // The gap between the first two loans is used before the end of the region.
+        let third = pool.allocate(&region, 0x20, 4).unwrap();
+        assert_eq!(third, 0x2000_0014..0x2000_0034);
+
+        assert_eq!(pool.allocate(&region, 0x80, 4), None);
+
+        pool.release(&second);
+        assert_eq!(
+            pool.allocate(&region, 0x80, 4),
+            Some(0x2000_0034..0x2000_00b4)
+        );
+    }
+}
@@ -0,0 +1,361 @@
+//! Filling, comparing and checksumming ranges of target memory.
+//!
+//! [`MemoryUtilities`] implements these operations on the host, on top of the transfers of
+//! [`MemoryInterface`]. [`TargetMemoryTools`] runs them on the target instead, by loading a small
+//! stub into RAM, so only the arguments and the result are transferred. This makes testing large
+//! memories, like external PSRAM, a matter of seconds.
+
+use std::{io::Write, ops::Range, time::Duration};
+
+use anyhow::anyhow;
+
+use super::{dump, DumpOptions, DumpProgress, DumpSummary, MemoryInterface};
+use crate::{Core, CoreType, Error};
+
+/// The number of bytes transferred at once by the host implementations.
+const CHUNK_SIZE: usize = 4096;
+
+/// Operations on ranges of memory, implemented with the transfers of [`MemoryInterface`].
+///
+/// This is implemented for all types implementing [`MemoryInterface`], like [`Core`].
+pub trait MemoryUtilities: MemoryInterface {
+    /// Fills `len` bytes at `address` with repetitions of `pattern`.
+    fn fill(&mut self, address: u64, len: usize, pattern: &[u8]) -> Result<(), Error> {
+        if pattern.is_empty() {
+            return Err(Error::Other(anyhow!("The fill pattern is empty")));
+        }
+
+        let repetitions = (CHUNK_SIZE / pattern.len()).max(1);
+        let chunk = pattern.repeat(repetitions);
+
+        let mut done = 0;
+        while done < len {
+            let size = chunk.len().min(len - done);
+            self.write(address + done as u64, &chunk[..size])?;
+            done += size;
+        }
+
+        Ok(())
+    }
+
+    /// Compares the memory at `address` with `expected`.
+    ///
+    /// Returns the address of the first byte that differs, or `None` if the memory matches.
+    fn compare(&mut self, address: u64, expected: &[u8]) -> Result<Option<u64>, Error> {
+        let mut buffer = vec![0; CHUNK_SIZE.min(expected.len())];
+        for (index, expected) in expected.chunks(CHUNK_SIZE).enumerate() {
+            let start = address + (index * CHUNK_SIZE) as u64;
+            let actual = &mut buffer[..expected.len()];
+            self.read(start, actual)?;
+
+            if let Some(offset) = actual.iter().zip(expected).position(|(a, e)| a != e) {
+                return Ok(Some(start + offset as u64));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Computes the CRC-32 (as used by Ethernet and zlib) of `len` bytes at `address`.
+    fn crc32(&mut self, address: u64, len: usize) -> Result<u32, Error> {
+        let mut buffer = vec![0; CHUNK_SIZE.min(len)];
+        let mut crc = !0;
+
+        let mut done = 0;
+        while done < len {
+            let chunk = &mut buffer[..CHUNK_SIZE.min(len - done)];
+            self.read(address + done as u64, chunk)?;
+            crc = crc32_update(crc, chunk);
+            done += chunk.len();
+        }
+
+        Ok(!crc)
+    }
+
+    /// Reads `range` in large chunks and streams it to `writer`, optionally compressed, without
+    /// holding the whole range in host memory.
+    ///
+    /// `progress` is called after each chunk with the throughput so far. The dump is cancelled
+    /// if it returns `false`, in which case [`Error::Cancelled`] is returned.
+    fn dump<W: Write + Send>(
+        &mut self,
+        range: Range<u64>,
+        writer: W,
+        options: DumpOptions,
+        progress: &mut dyn FnMut(DumpProgress) -> bool,
+    ) -> Result<DumpSummary, Error> {
+        dump::dump(self, range, writer, options, progress)
+    }
+}
+
+impl<M: MemoryInterface + ?Sized> MemoryUtilities for M {}
+
+/// Computes the CRC-32 of `data`, with the same algorithm as [`MemoryUtilities::crc32`].
+pub fn crc32(data: &[u8]) -> u32 {
+    !crc32_update(!0, data)
+}
+
+/// Feeds `data` into the (inverted) CRC-32 state `crc`.
+fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = (crc >> 1) ^ (0xEDB8_8320 & (crc & 1).wrapping_neg());
+        }
+    }
+    crc
+}
+
+/// Machine code that is loaded into the workspace, and called with [`Core::call_function`].
+struct Stub {
+    code: &'static [u8],
+    /// The offset of the function in `code`.
+    entry: u64,
+}
+
+/// `fill(address: *mut u32, words: usize, pattern: u32)`
+const THUMB_FILL: &[u8] = &[
+    0x00, 0x29, // cmp r1, #0
+    0x03, 0xd0, // beq done
+    0x02, 0x60, // loop: str r2, [r0]
+    0x04, 0x30, // adds r0, #4
+    0x01, 0x39, // subs r1, #1
+    0xf9, 0xe7, // b loop
+    0x00, 0x20, // done: movs r0, #0
+    0x70, 0x47, // bx lr
+];
+
+/// `crc32(address: *const u8, len: usize) -> u32`
+const THUMB_CRC32: &[u8] = &[
+    0x00, 0x22, // movs r2, #0
+    0xd2, 0x43, // mvns r2, r2
+    0x07, 0x4b, // ldr r3, =0xedb88320
+    0x00, 0x29, // bytes: cmp r1, #0
+    0x0a, 0xd0, // beq done
+    0x04, 0x78, // ldrb r4, [r0]
+    0x62, 0x40, // eors r2, r4
+    0x01, 0x30, // adds r0, #1
+    0x01, 0x39, // subs r1, #1
+    0x08, 0x25, // movs r5, #8
+    0x52, 0x08, // bits: lsrs r2, r2, #1
+    0x00, 0xd3, // bcc next
+    0x5a, 0x40, // eors r2, r3
+    0x01, 0x3d, // next: subs r5, #1
+    0xfa, 0xd1, // bne bits
+    0xf2, 0xe7, // b bytes
+    0xd0, 0x43, // done: mvns r0, r2
+    0x70, 0x47, // bx lr
+    0x20, 0x83, 0xb8, 0xed, // .word 0xedb88320
+];
+
+/// `fill(address: *mut u32, words: usize, pattern: u32)`
+const RISCV_FILL: &[u8] = &[
+    0x63, 0x8a, 0x05, 0x00, // loop: beqz a1, done
+    0x23, 0x20, 0xc5, 0x00, // sw a2, 0(a0)
+    0x13, 0x05, 0x45, 0x00, // addi a0, a0, 4
+    0x93, 0x85, 0xf5, 0xff, // addi a1, a1, -1
+    0x6f, 0xf0, 0x1f, 0xff, // j loop
+    0x13, 0x05, 0x00, 0x00, // done: li a0, 0
+    0x67, 0x80, 0x00, 0x00, // ret
+];
+
+/// `crc32(address: *const u8, len: usize) -> u32`
+const RISCV_CRC32: &[u8] = &[
+    0x13, 0x06, 0xf0, 0xff, // li a2, -1
+    0xb7, 0x86, 0xb8, 0xed, // lui a3, 0xedb88
+    0x93, 0x86, 0x06, 0x32, // addi a3, a3, 0x320
+    0x63, 0x8a, 0x05, 0x02, // bytes: beqz a1, done
+    0x03, 0x47, 0x05, 0x00, // lbu a4, 0(a0)
+    0x33, 0x46, 0xe6, 0x00, // xor a2, a2, a4
+    0x13, 0x05, 0x15, 0x00, // addi a0, a0, 1
+    0x93, 0x85, 0xf5, 0xff, // addi a1, a1, -1
+    0x93, 0x07, 0x80, 0x00, // li a5, 8
+    0x13, 0x77, 0x16, 0x00, // bits: andi a4, a2, 1
+    0x13, 0x56, 0x16, 0x00, // srli a2, a2, 1
+    0x63, 0x04, 0x07, 0x00, // beqz a4, next
+    0x33, 0x46, 0xd6, 0x00, // xor a2, a2, a3
+    0x93, 0x87, 0xf7, 0xff, // next: addi a5, a5, -1
+    0xe3, 0x96, 0x07, 0xfe, // bnez a5, bits
+    0x6f, 0xf0, 0x1f, 0xfd, // j bytes
+    0x13, 0x45, 0xf6, 0xff, // done: not a0, a2
+    0x67, 0x80, 0x00, 0x00, // ret
+];
+
+/// `fill(address: *mut u32, words: usize, pattern: u32)`, using the windowed ABI.
+const XTENSA_FILL: &[u8] = &[
+    0x36, 0x41, 0x00, // entry a1, 32
+    0x16, 0xb3, 0x00, // loop: beqz a3, done
+    0x42, 0x62, 0x00, // s32i a4, a2, 0
+    0x22, 0xc2, 0x04, // addi a2, a2, 4
+    0x32, 0xc3, 0xff, // addi a3, a3, -1
+    0x06, 0xfc, 0xff, // j loop
+    0x22, 0xa0, 0x00, // done: movi a2, 0
+    0x90, 0x00, 0x00, // retw
+];
+
+/// `crc32(address: *const u8, len: usize) -> u32`, using the windowed ABI. The function starts
+/// after the literal.
+const XTENSA_CRC32: &[u8] = &[
+    0x20, 0x83, 0xb8, 0xed, // .word 0xedb88320
+    0x36, 0x41, 0x00, // entry a1, 32
+    0x51, 0xfe, 0xff, // l32r a5, 0xedb88320
+    0x42, 0xaf, 0xff, // movi a4, -1
+    0x16, 0x33, 0x02, // bytes: beqz a3, done
+    0x62, 0x02, 0x00, // l8ui a6, a2, 0
+    0x60, 0x44, 0x30, // xor a4, a4, a6
+    0x22, 0xc2, 0x01, // addi a2, a2, 1
+    0x32, 0xc3, 0xff, // addi a3, a3, -1
+    0x72, 0xa0, 0x08, // movi a7, 8
+    0x40, 0x60, 0x04, // bits: extui a6, a4, 0, 1
+    0x40, 0x41, 0x91, // srli a4, a4, 1
+    0x16, 0x26, 0x00, // beqz a6, next
+    0x50, 0x44, 0x30, // xor a4, a4, a5
+    0x72, 0xc7, 0xff, // next: addi a7, a7, -1
+    0x56, 0xd7, 0xfe, // bnez a7, bits
+    0x06, 0xf6, 0xff, // j bytes
+    0x22, 0xaf, 0xff, // done: movi a2, -1
+    0x40, 0x22, 0x30, // xor a2, a2, a4
+    0x90, 0x00, 0x00, // retw
+];
+
+impl Stub {
+    fn fill(core_type: CoreType) -> Option<Self> {
+        let code = match core_type {
+            CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
+                THUMB_FILL
+            }
+            CoreType::Riscv => RISCV_FILL,
+            CoreType::Xtensa => XTENSA_FILL,
+            CoreType::Armv7a | CoreType::Armv8a => return None,
+        };
+
+        Some(Self { code, entry: 0 })
+    }
+
+    fn crc32(core_type: CoreType) -> Option<Self> {
+        let (code, entry) = match core_type {
+            CoreType::Armv6m | CoreType::Armv7m | CoreType::Armv7em | CoreType::Armv8m => {
+                (THUMB_CRC32, 0)
+            }
+            CoreType::Riscv => (RISCV_CRC32, 0),
+            CoreType::Xtensa => (XTENSA_CRC32, 4),
+            CoreType::Armv7a | CoreType::Armv8a => return None,
+        };
+
+        Some(Self { code, entry })
+    }
+}
+
+/// Runs memory operations on the target, using a small area of RAM as workspace.
+///
+/// The workspace must be word aligned, hold [`TargetMemoryTools::WORKSPACE_SIZE`] bytes, and
+/// be executable. Its contents are saved and restored around every operation, and the registers
+/// of the core are restored as well, so the core can be resumed afterwards.
+///
+/// Cores without a stub, and fills that are not word aligned, fall back to [`MemoryUtilities`].
+#[derive(Debug, Clone)]
+pub struct TargetMemoryTools {
+    workspace: u64,
+    timeout: Duration,
+}
+
+impl TargetMemoryTools {
+    /// The number of bytes of the workspace that are used.
+    pub const WORKSPACE_SIZE: usize = 64;
+
+    /// Creates tools that load their stubs to `workspace`.
+    pub fn new(workspace: u64) -> Self {
+        Self {
+            workspace,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// Sets how long a single operation may run on the target.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Fills `len` bytes at `address` with repetitions of the 32-bit `pattern`.
+    ///
+    /// The pattern is stored in little endian byte order.
+    pub fn fill(
+        &self,
+        core: &mut Core<'_>,
+        address: u64,
+        len: usize,
+        pattern: u32,
+    ) -> Result<(), Error> {
+        let stub = Stub::fill(core.core_type()).filter(|_| address % 4 == 0 && len % 4 == 0);
+        let Some(stub) = stub else {
+            return core.fill(address, len, &pattern.to_le_bytes());
+        };
+
+        self.run(core, &stub, &[address, (len / 4) as u64, pattern as u64])?;
+
+        Ok(())
+    }
+
+    /// Computes the CRC-32 of `len` bytes at `address`, like [`MemoryUtilities::crc32`].
+    pub fn crc32(&self, core: &mut Core<'_>, address: u64, len: usize) -> Result<u32, Error> {
+        let Some(stub) = Stub::crc32(core.core_type()) else {
+            return core.crc32(address, len);
+        };
+
+        let crc = self.run(core, &stub, &[address, len as u64])?;
+
+        Ok(crc as u32)
+    }
+
+    /// Checks whether the memory at `address` matches `expected`, by comparing their CRC-32.
+    pub fn compare(
+        &self,
+        core: &mut Core<'_>,
+        address: u64,
+        expected: &[u8],
+    ) -> Result<bool, Error> {
+        Ok(self.crc32(core, address, expected.len())? == crc32(expected))
+    }
+
+    /// Loads `stub` into the workspace and calls it.
+    fn run(&self, core: &mut Core<'_>, stub: &Stub, arguments: &[u64]) -> Result<u64, Error> {
+        let mut saved = vec![0; stub.code.len()];
+        core.read(self.workspace, &mut saved)?;
+        core.write(self.workspace, stub.code)?;
+
+        let result = core.call_function(self.workspace + stub.entry, arguments, self.timeout);
+
+        // Restore the workspace even if the call failed, but report the failure of the call.
+        let restored = core.write(self.workspace, &saved);
+        let value = result?;
+        restored?;
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::MockMemory;
+
+    #[test]
+    fn crc32_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn compare_and_crc32() {
+        let mut memory = MockMemory::new();
+        // Unaligned reads are done with whole words.
+        memory.add_range(0x2000_0000, b"123456789\0\0\0".to_vec());
+
+        assert_eq!(memory.crc32(0x2000_0000, 9).unwrap(), 0xCBF4_3926);
+        assert_eq!(memory.compare(0x2000_0000, b"123456789").unwrap(), None);
+        assert_eq!(
+            memory.compare(0x2000_0000, b"12x").unwrap(),
+            Some(0x2000_0002)
+        );
+    }
+}
@@ -0,0 +1,215 @@
+//! Non-blocking variants of long running memory and core operations.
+//!
+//! Large memory transfers and waiting for a core to halt block the calling thread, which stalls
+//! applications like graphical debuggers that drive probe-rs from their event loop. The futures
+//! in this module do the same work in small pieces, and yield to the executor in between.
+//!
+//! The futures don't depend on a specific runtime. Each poll transfers one chunk or checks the
+//! core once, and then asks to be polled again, so the executor can run other tasks in between.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use crate::{CoreInterface, Error, MemoryInterface};
+
+/// The number of bytes transferred by default in a single poll.
+const DEFAULT_CHUNK_SIZE: usize = 4096;
+
+/// Non-blocking variants of the [`MemoryInterface`] transfers.
+///
+/// This is implemented for all types implementing [`MemoryInterface`], like
+/// [`Core`](crate::Core).
+pub trait AsyncMemoryInterface: MemoryInterface {
+    /// Reads `data.len()` bytes from `address`, like [`MemoryInterface::read`].
+    fn read_async<'a>(&'a mut self, address: u64, data: &'a mut [u8]) -> ReadMemory<'a, Self> {
+        ReadMemory {
+            memory: self,
+            address,
+            data,
+            done: 0,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+
+    /// Writes `data` to `address`, like [`MemoryInterface::write`].
+    fn write_async<'a>(&'a mut self, address: u64, data: &'a [u8]) -> WriteMemory<'a, Self> {
+        WriteMemory {
+            memory: self,
+            address,
+            data,
+            done: 0,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+        }
+    }
+}
+
+impl<M: MemoryInterface + ?Sized> AsyncMemoryInterface for M {}
+
+/// Non-blocking variants of the [`CoreInterface`] operations that wait for the core.
+///
+/// This is implemented for all types implementing [`CoreInterface`], like
+/// [`Core`](crate::Core).
+pub trait AsyncCoreInterface: CoreInterface {
+    /// Waits until the core is halted, like [`CoreInterface::wait_for_core_halted`].
+    ///
+    /// Resolves to [`Error::Timeout`] if the core didn't halt within `timeout`.
+    fn wait_for_core_halted_async(&mut self, timeout: Duration) -> WaitForCoreHalted<'_, Self> {
+        WaitForCoreHalted {
+            core: self,
+            deadline: Instant::now() + timeout,
+        }
+    }
+}
+
+impl<C: CoreInterface + ?Sized> AsyncCoreInterface for C {}
+
+/// A read in progress, see [`AsyncMemoryInterface::read_async`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct ReadMemory<'a, M: ?Sized> {
+    memory: &'a mut M,
+    address: u64,
+    data: &'a mut [u8],
+    done: usize,
+    chunk_size: usize,
+}
+
+impl<M: ?Sized> ReadMemory<'_, M> {
+    /// Sets the number of bytes read in a single poll.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+}
+
+impl<M: MemoryInterface + ?Sized> Future for ReadMemory<'_, M> {
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        if this.done < this.data.len() {
+            let end = (this.done + this.chunk_size).min(this.data.len());
+            let address = this.address + this.done as u64;
+            if let Err(error) = this.memory.read(address, &mut this.data[this.done..end]) {
+                return Poll::Ready(Err(error));
+            }
+            this.done = end;
+        }
+
+        if this.done < this.data.len() {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+}
+
+/// A write in progress, see [`AsyncMemoryInterface::write_async`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct WriteMemory<'a, M: ?Sized> {
+    memory: &'a mut M,
+    address: u64,
+    data: &'a [u8],
+    done: usize,
+    chunk_size: usize,
+}
+
+impl<M: ?Sized> WriteMemory<'_, M> {
+    /// Sets the number of bytes written in a single poll.
+    pub fn with_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+}
+
+impl<M: MemoryInterface + ?Sized> Future for WriteMemory<'_, M> {
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        if this.done < this.data.len() {
+            let end = (this.done + this.chunk_size).min(this.data.len());
+            let address = this.address + this.done as u64;
+            if let Err(error) = this.memory.write(address, &this.data[this.done..end]) {
+                return Poll::Ready(Err(error));
+            }
+            this.done = end;
+        }
+
+        if this.done < this.data.len() {
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+
+        // Only complete once the data reached the target.
+        Poll::Ready(this.memory.flush())
+    }
+}
+
+/// Waiting for a core to halt, see [`AsyncCoreInterface::wait_for_core_halted_async`].
+#[derive(Debug)]
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitForCoreHalted<'a, C: ?Sized> {
+    core: &'a mut C,
+    deadline: Instant,
+}
+
+impl<C: CoreInterface + ?Sized> Future for WaitForCoreHalted<'_, C> {
+    type Output = Result<(), Error>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+
+        match this.core.core_halted() {
+            Ok(true) => Poll::Ready(Ok(())),
+            Ok(false) if Instant::now() >= this.deadline => Poll::Ready(Err(Error::Timeout)),
+            Ok(false) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+            Err(error) => Poll::Ready(Err(error)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::task::{Wake, Waker};
+
+    use super::*;
+    use crate::test::MockMemory;
+
+    struct NoopWaker;
+
+    impl Wake for NoopWaker {
+        fn wake(self: Arc<Self>) {}
+    }
+
+    #[test]
+    fn read_yields_between_chunks() {
+        let mut memory = MockMemory::new();
+        // Unaligned reads are done with whole words.
+        memory.add_range(0x2000_0000, (0..12).collect());
+
+        let mut data = [0; 10];
+        let mut read = memory.read_async(0x2000_0000, &mut data).with_chunk_size(4);
+
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = Context::from_waker(&waker);
+
+        let mut polls = 1;
+        while Pin::new(&mut read).poll(&mut cx).is_pending() {
+            polls += 1;
+        }
+
+        assert_eq!(polls, 3);
+        assert_eq!(data, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+}
@@ -1,4 +1,5 @@
 pub(crate) mod arm_jtag;
+pub(crate) mod bitbang;
 pub(crate) mod common;
 
 pub(crate) mod cmsisdap;
@@ -8,13 +9,19 @@ pub(crate) mod fake_probe;
 pub(crate) mod ftdi;
 pub(crate) mod jlink;
 pub(crate) mod list;
+pub(crate) mod recording;
+pub(crate) mod retry;
 pub(crate) mod stlink;
+pub(crate) mod telemetry;
 pub(crate) mod wlink;
 
 use crate::architecture::arm::ArmError;
 use crate::architecture::riscv::communication_interface::RiscvError;
 use crate::architecture::xtensa::communication_interface::XtensaCommunicationInterface;
 use crate::error::Error;
+use crate::probe::fake_probe::FakeProbe;
+use crate::probe::recording::{JtagRecorder, RecordingProbe};
+use crate::probe::retry::ErrorClass;
 use crate::{
     architecture::arm::communication_interface::UninitializedArmProbe,
     config::{RegistryError, TargetSelector},
@@ -28,12 +35,13 @@ use crate::{
         },
         riscv::communication_interface::RiscvCommunicationInterface,
     },
-    Permissions,
+    AttachOptions, Permissions,
 };
 use crate::{Lister, Session};
 use probe_rs_target::ScanChainElement;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Duration;
 use std::{convert::TryFrom, fmt};
 
 /// Used to log warnings when the measured target voltage is
@@ -168,6 +176,18 @@ pub enum DebugProbeError {
     Timeout,
 }
 
+impl DebugProbeError {
+    /// Returns the class of the error, if it is transient and the failed operation may succeed
+    /// when it is repeated.
+    pub fn error_class(&self) -> Option<ErrorClass> {
+        match self {
+            DebugProbeError::Usb(_) | DebugProbeError::Timeout => Some(ErrorClass::Probe),
+            DebugProbeError::BatchError(_) => Some(ErrorClass::Transfer),
+            _ => None,
+        }
+    }
+}
+
 /// An error during probe creation occurred.
 /// This is almost always a sign of a bad USB setup.
 /// Check UDEV rules if you are on Linux and try installing Zadig
@@ -259,7 +279,32 @@ impl Probe {
         target: impl Into<TargetSelector>,
         permissions: Permissions,
     ) -> Result<Session, Error> {
-        Session::new(self, target.into(), AttachMethod::Normal, permissions)
+        self.attach_with_options(
+            target,
+            AttachMethod::Normal,
+            permissions,
+            AttachOptions::default(),
+        )
+    }
+
+    /// Attach to the chip without halting its cores, so that a live system can be observed
+    /// without the debugger disturbing it.
+    ///
+    /// The parts of attaching which need a halted core, like running the connect sequence of the
+    /// target or clearing breakpoints, are deferred until [`Session::complete_attach`] is called.
+    /// Operations which would have to halt a running core fail with [`Error::NotHalted`]
+    /// instead, see [`Session::unavailable_until_halted`].
+    pub fn attach_non_intrusive(
+        self,
+        target: impl Into<TargetSelector>,
+        permissions: Permissions,
+    ) -> Result<Session, Error> {
+        self.attach_with_options(
+            target,
+            AttachMethod::NonIntrusive,
+            permissions,
+            AttachOptions::default(),
+        )
     }
 
     /// Attach to a target without knowing what target you have at hand.
@@ -296,9 +341,31 @@ impl Probe {
         target: impl Into<TargetSelector>,
         permissions: Permissions,
     ) -> Result<Session, Error> {
-        // The session will de-assert reset after connecting to the debug interface.
-        Session::new(self, target.into(), AttachMethod::UnderReset, permissions).map_err(|e| {
-            if matches!(e, Error::Arm(ArmError::Timeout) | Error::Riscv(RiscvError::Timeout)) {
+        self.attach_with_options(
+            target,
+            AttachMethod::UnderReset,
+            permissions,
+            AttachOptions::default(),
+        )
+    }
+
+    /// Attach to the chip with `method`, and `options` which select how the session behaves.
+    ///
+    /// [`Probe::attach`], [`Probe::attach_non_intrusive`] and [`Probe::attach_under_reset`]
+    /// attach with the default options.
+    pub fn attach_with_options(
+        self,
+        target: impl Into<TargetSelector>,
+        method: AttachMethod,
+        permissions: Permissions,
+        options: AttachOptions,
+    ) -> Result<Session, Error> {
+        // With AttachMethod::UnderReset, the session will de-assert reset after connecting to
+        // the debug interface.
+        Session::new(self, target.into(), method, permissions, options).map_err(|e| {
+            if method == AttachMethod::UnderReset
+                && matches!(e, Error::Arm(ArmError::Timeout) | Error::Riscv(RiscvError::Timeout))
+            {
                 Error::Other(
                 anyhow::anyhow!("Timeout while attaching to target under reset. This can happen if the target is not responding to the reset sequence. Ensure the chip's reset pin is connected, or try attaching without reset (`connectUnderReset = false` for DAP Clients, or remove `connect-under-reset` option from CLI options.)."))
             } else {
@@ -353,6 +420,65 @@ impl Probe {
         self.inner.target_reset_deassert()
     }
 
+    /// Asserts the reset of the target for `duration`, and releases it again.
+    ///
+    /// This is not supported on all probes.
+    pub fn target_reset_pulse(&mut self, duration: Duration) -> Result<(), DebugProbeError> {
+        tracing::debug!("Pulsing target reset for {:?}", duration);
+        self.inner.target_reset_pulse(duration)
+    }
+
+    /// Asserts the JTAG test reset (nTRST) of the target.
+    ///
+    /// This is not supported on all probes.
+    pub fn target_trst_assert(&mut self) -> Result<(), DebugProbeError> {
+        tracing::debug!("Asserting target nTRST");
+        self.inner.target_trst_assert()
+    }
+
+    /// Deasserts the JTAG test reset (nTRST) of the target.
+    ///
+    /// This is not supported on all probes.
+    pub fn target_trst_deassert(&mut self) -> Result<(), DebugProbeError> {
+        tracing::debug!("Deasserting target nTRST");
+        self.inner.target_trst_deassert()
+    }
+
+    /// Check if the probe can switch the power supply of the target.
+    pub fn supports_target_power(&self) -> bool {
+        self.inner.supports_target_power()
+    }
+
+    /// Switches the power supply of the target on or off.
+    ///
+    /// This is not supported on all probes.
+    pub fn set_target_power(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        tracing::debug!("Setting target power: {}", enabled);
+        self.inner.set_target_power(enabled)
+    }
+
+    /// Switches the power supply of the target off for `off_time`, and on again.
+    ///
+    /// This is not supported on all probes.
+    pub fn target_power_cycle(&mut self, off_time: Duration) -> Result<(), DebugProbeError> {
+        tracing::debug!("Power cycling the target");
+        self.inner.target_power_cycle(off_time)
+    }
+
+    /// Records the JTAG transactions done through this probe from now on, e.g. to replay them
+    /// with a [`ReplayProbe`](crate::ReplayProbe) in tests.
+    ///
+    /// Call this before attaching, to record the whole session. Only the transactions of the
+    /// Xtensa and RISC-V interfaces are recorded.
+    pub fn record_jtag(&mut self) -> JtagRecorder {
+        let inner = std::mem::replace(&mut self.inner, Box::new(FakeProbe::new()));
+        let probe = RecordingProbe::new(inner);
+        let recorder = probe.recorder();
+        self.inner = Box::new(probe);
+
+        recorder
+    }
+
     /// Configure protocol speed to use in kHz
     pub fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
         if !self.attached {
@@ -362,6 +488,22 @@ impl Probe {
         }
     }
 
+    /// Enable or disable adaptive clocking.
+    ///
+    /// See [`DebugProbe::set_adaptive_clocking`] for more information.
+    pub fn set_adaptive_clocking(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        if !self.attached {
+            self.inner.set_adaptive_clocking(enabled)
+        } else {
+            Err(DebugProbeError::Attached)
+        }
+    }
+
+    /// Check if the probe supports adaptive clocking.
+    pub fn supports_adaptive_clocking(&self) -> bool {
+        self.inner.supports_adaptive_clocking()
+    }
+
     /// Configure the scan chain to use for the attached target.
     ///
     /// See [`DebugProbe::set_scan_chain`] for more information and usage
@@ -474,6 +616,27 @@ impl Probe {
         self.inner.try_as_dap_probe()
     }
 
+    /// Returns the TAPs found on the JTAG chain of the attached target.
+    ///
+    /// The TAPs are listed in the order in which [`Probe::select_jtag_tap`] indexes them.
+    /// This does not work on all probes.
+    pub fn scan_jtag_chain(&mut self) -> Result<Vec<JtagChainItem>, DebugProbeError> {
+        match self.inner.try_as_jtag_probe() {
+            Some(probe) => probe.scan_chain(),
+            None => Err(DebugProbeError::InterfaceNotAvailable("JTAG")),
+        }
+    }
+
+    /// Directs all further JTAG accesses to the TAP at `index` in the scan chain.
+    ///
+    /// This does not work on all probes.
+    pub fn select_jtag_tap(&mut self, index: usize) -> Result<(), DebugProbeError> {
+        match self.inner.try_as_jtag_probe() {
+            Some(probe) => probe.select_jtag_tap(index),
+            None => Err(DebugProbeError::InterfaceNotAvailable("JTAG")),
+        }
+    }
+
     /// Try reading the target voltage of via the connected voltage pin.
     ///
     /// This does not work on all probes.
@@ -518,6 +681,30 @@ pub trait DebugProbe: Send + fmt::Debug {
     ///
     fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError>;
 
+    /// Check if the probe supports adaptive clocking, where the target paces the JTAG clock
+    /// through the RTCK signal.
+    fn supports_adaptive_clocking(&self) -> bool {
+        false
+    }
+
+    /// Enable or disable adaptive clocking.
+    ///
+    /// While adaptive clocking is enabled, the probe waits for the target to acknowledge each
+    /// clock edge, which keeps long or noisy JTAG connections stable. Calling
+    /// [`set_speed`](DebugProbe::set_speed) switches back to a fixed clock.
+    ///
+    /// If the probe does not support adaptive clocking,
+    /// `DebugProbeError::CommandNotSupportedByProbe` will be returned.
+    fn set_adaptive_clocking(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        if enabled {
+            Err(DebugProbeError::CommandNotSupportedByProbe(
+                "adaptive clocking",
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
     /// Set the JTAG scan chain information for the target under debug.
     ///
     /// This allows the probe to know which TAPs are in the scan chain and their
@@ -558,6 +745,51 @@ pub trait DebugProbe: Send + fmt::Debug {
     /// This should deassert the reset pin of the target via debug probe.
     fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError>;
 
+    /// Asserts the reset pin of the target for `duration`, and deasserts it again.
+    fn target_reset_pulse(&mut self, duration: Duration) -> Result<(), DebugProbeError> {
+        self.target_reset_assert()?;
+        std::thread::sleep(duration);
+        self.target_reset_deassert()
+    }
+
+    /// This should assert the JTAG test reset (nTRST) pin of the target via debug probe.
+    ///
+    /// If the probe can't drive nTRST, `DebugProbeError::CommandNotSupportedByProbe` will be
+    /// returned.
+    fn target_trst_assert(&mut self) -> Result<(), DebugProbeError> {
+        Err(DebugProbeError::CommandNotSupportedByProbe("nTRST control"))
+    }
+
+    /// This should deassert the JTAG test reset (nTRST) pin of the target via debug probe.
+    ///
+    /// If the probe can't drive nTRST, `DebugProbeError::CommandNotSupportedByProbe` will be
+    /// returned.
+    fn target_trst_deassert(&mut self) -> Result<(), DebugProbeError> {
+        Err(DebugProbeError::CommandNotSupportedByProbe("nTRST control"))
+    }
+
+    /// Check if the probe can switch the power supply of the target.
+    fn supports_target_power(&self) -> bool {
+        false
+    }
+
+    /// Switches the power supply the probe provides to the target on or off.
+    ///
+    /// If the probe can't switch the target's power,
+    /// `DebugProbeError::CommandNotSupportedByProbe` will be returned.
+    fn set_target_power(&mut self, _enabled: bool) -> Result<(), DebugProbeError> {
+        Err(DebugProbeError::CommandNotSupportedByProbe(
+            "target power control",
+        ))
+    }
+
+    /// Switches the power supply of the target off for `off_time`, and on again.
+    fn target_power_cycle(&mut self, off_time: Duration) -> Result<(), DebugProbeError> {
+        self.set_target_power(false)?;
+        std::thread::sleep(off_time);
+        self.set_target_power(true)
+    }
+
     /// Selects the transport protocol to be used by the debug probe.
     fn select_protocol(&mut self, protocol: WireProtocol) -> Result<(), DebugProbeError>;
 
@@ -637,6 +869,13 @@ pub trait DebugProbe: Send + fmt::Debug {
         None
     }
 
+    /// Gives access to the JTAG scan chain of the probe.
+    ///
+    /// This is only available on probes implementing [`JTAGAccess`].
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        None
+    }
+
     /// Reads the target voltage in Volts, if possible. Returns `Ok(None)`
     /// if the probe doesn’t support reading the target voltage.
     fn get_target_voltage(&mut self) -> Result<Option<f32>, DebugProbeError> {
@@ -849,6 +1088,30 @@ pub trait JTAGAccess: DebugProbe {
     /// Set the IR register length
     fn set_ir_len(&mut self, len: u32);
 
+    /// Select the TAP at `index` in the scan chain, which subsequent register
+    /// accesses will be directed to.
+    ///
+    /// Probes which can only access a single TAP accept selecting the TAP that is
+    /// already selected.
+    fn select_jtag_tap(&mut self, index: usize) -> Result<(), DebugProbeError> {
+        if index == self.selected_jtag_tap() {
+            Ok(())
+        } else {
+            Err(DebugProbeError::NotImplemented("select_jtag_tap"))
+        }
+    }
+
+    /// Returns the index of the TAP that register accesses are currently directed to.
+    fn selected_jtag_tap(&self) -> usize {
+        0
+    }
+
+    /// Returns the TAPs on the JTAG chain, with the IDCODEs and IR lengths detected by scanning
+    /// the chain when the probe attached.
+    fn scan_chain(&mut self) -> Result<Vec<JtagChainItem>, DebugProbeError> {
+        Err(DebugProbeError::NotImplemented("scan_chain"))
+    }
+
     /// Write to a JTAG register
     ///
     /// This function will perform a write to the IR register, if necessary,
@@ -893,9 +1156,11 @@ pub struct JtagWriteCommand {
 }
 
 /// Represents a Jtag Tap within the chain.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct JtagChainItem {
+    /// The IDCODE of the TAP.
     pub idcode: u32,
+    /// The length of the instruction register of the TAP.
     pub irlen: usize,
 }
 
@@ -909,6 +1174,25 @@ pub struct ChainParams {
     pub irlen: usize,
 }
 
+impl ChainParams {
+    /// Computes the parameters to access the TAP at `selected` in `chain`, with all other TAPs
+    /// in BYPASS.
+    ///
+    /// Returns `None` if the chain has no TAP at `selected`.
+    pub(crate) fn from_jtag_chain(chain: &[JtagChainItem], selected: usize) -> Option<Self> {
+        let irlen = chain.get(selected)?.irlen;
+        let (pre, post) = (&chain[..selected], &chain[selected + 1..]);
+
+        Some(Self {
+            irpre: pre.iter().map(|tap| tap.irlen).sum(),
+            irpost: post.iter().map(|tap| tap.irlen).sum(),
+            drpre: pre.len(),
+            drpost: post.len(),
+            irlen,
+        })
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub struct BatchExecutionError {
     #[source]
@@ -1092,4 +1376,8 @@ pub enum AttachMethod {
     ///
     /// This is required on targets that can remap SWD pins or disable the SWD interface in sleep.
     UnderReset,
+    /// Attach without halting or otherwise disturbing the running cores.
+    ///
+    /// Operations which need a halted core are deferred, see [`Session::complete_attach`].
+    NonIntrusive,
 }
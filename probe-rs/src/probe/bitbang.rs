@@ -0,0 +1,652 @@
+//! A JTAG and SWD driver for adapters which can only drive and sample GPIO pins.
+//!
+//! All signals are generated in software, one pin state at a time. This is much slower than a
+//! probe with a protocol engine, but allows debugging Xtensa and RISC-V targets over JTAG, and
+//! ARM targets over SWD, with very minimal hardware.
+
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+use bitvec::prelude::*;
+use probe_rs_target::ScanChainElement;
+
+use super::arm_jtag::{ProbeStatistics, RawProtocolIo, SwdSettings};
+use super::common::{extract_idcodes, extract_ir_lengths};
+use super::{ChainParams, DebugProbe, DebugProbeError, DebugProbeSelector, JTAGAccess};
+use super::{JtagChainItem, WireProtocol};
+use crate::architecture::{
+    arm::{
+        communication_interface::{DapProbe, UninitializedArmProbe},
+        ArmCommunicationInterface,
+    },
+    riscv::communication_interface::{RiscvCommunicationInterface, RiscvError},
+    xtensa::communication_interface::XtensaCommunicationInterface,
+};
+
+/// The maximum length of the IR and DR scan chains detected when attaching.
+const MAX_CHAIN_LENGTH: usize = 128;
+
+/// Raw access to the GPIO pins of a bitbang adapter.
+///
+/// The pins are numbered from 0 to 7, and the state of all pins is represented by a byte with
+/// one bit per pin.
+pub trait BitbangIo: Send + fmt::Debug {
+    /// Selects the pins which are driven by the adapter, with a 1 bit for each output.
+    ///
+    /// All other pins are inputs.
+    fn set_direction(&mut self, outputs: u8) -> io::Result<()>;
+
+    /// Drives the output pins with each of the `states` in turn.
+    ///
+    /// Returns the state of all pins sampled after each of the `states` was applied.
+    fn transfer(&mut self, states: &[u8]) -> io::Result<Vec<u8>>;
+
+    /// Sets the rate at which the states are applied, in Hz.
+    ///
+    /// Returns the rate which is actually used.
+    fn set_frequency(&mut self, frequency: u32) -> io::Result<u32>;
+}
+
+/// The pins which the JTAG and SWD signals are connected to.
+///
+/// For SWD, SWCLK is connected to the TCK pin, and SWDIO to the TMS pin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PinMapping {
+    /// The JTAG clock, or SWCLK.
+    pub tck: u8,
+    /// The JTAG mode select, or SWDIO.
+    pub tms: u8,
+    /// The JTAG data input of the target.
+    pub tdi: u8,
+    /// The JTAG data output of the target.
+    pub tdo: u8,
+    /// The active low reset of the target, if it is connected.
+    pub srst: Option<u8>,
+}
+
+impl Default for PinMapping {
+    /// The pin order used by the MPSSE engine of FTDI chips, so the same cables can be used.
+    fn default() -> Self {
+        Self {
+            tck: 0,
+            tdi: 1,
+            tdo: 2,
+            tms: 3,
+            srst: None,
+        }
+    }
+}
+
+/// A debug probe generating the JTAG or SWD signals through a [`BitbangIo`].
+#[derive(Debug)]
+pub struct BitbangProbe {
+    io: Box<dyn BitbangIo>,
+    pins: PinMapping,
+    protocol: Option<WireProtocol>,
+    speed_khz: u32,
+    idle_cycles: u8,
+
+    /// The state of the output pins between transfers.
+    state: u8,
+    /// Whether the reset of the target is asserted.
+    reset_asserted: bool,
+
+    scan_chain: Option<Vec<ScanChainElement>>,
+    /// The TAPs found when attaching.
+    taps: Vec<JtagChainItem>,
+    /// The index of the selected TAP.
+    selected_tap: usize,
+    chain_params: Option<ChainParams>,
+
+    swd_settings: SwdSettings,
+    probe_statistics: ProbeStatistics,
+}
+
+impl BitbangProbe {
+    /// Creates a probe using `io`, with the signals connected to the pins in `pins`.
+    pub fn new(io: Box<dyn BitbangIo>, pins: PinMapping) -> Self {
+        Self {
+            io,
+            pins,
+            protocol: None,
+            speed_khz: 0,
+            idle_cycles: 0,
+            state: 0,
+            reset_asserted: false,
+            scan_chain: None,
+            taps: Vec::new(),
+            selected_tap: 0,
+            chain_params: None,
+            swd_settings: SwdSettings::default(),
+            probe_statistics: ProbeStatistics::default(),
+        }
+    }
+
+    fn output_mask(&self, swdio_output: bool) -> u8 {
+        let mut outputs = 1 << self.pins.tck;
+        if swdio_output {
+            outputs |= 1 << self.pins.tms;
+        }
+        if self.protocol != Some(WireProtocol::Swd) {
+            outputs |= 1 << self.pins.tdi;
+        }
+        if let Some(srst) = self.pins.srst {
+            // The reset is only driven while asserted, so the target can pull it up itself.
+            if self.reset_asserted {
+                outputs |= 1 << srst;
+            }
+        }
+        outputs
+    }
+
+    fn set_pin(&mut self, pin: u8, level: bool) {
+        if level {
+            self.state |= 1 << pin;
+        } else {
+            self.state &= !(1 << pin);
+        }
+    }
+
+    /// Generates a clock cycle for each of `bits`, with TMS and TDI set to the given levels.
+    ///
+    /// Returns the state of the pins sampled after the falling and after the rising edge of each
+    /// cycle.
+    fn clock(&mut self, bits: &[(bool, bool)]) -> Result<Vec<(u8, u8)>, DebugProbeError> {
+        if bits.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.probe_statistics.report_io();
+
+        let mut states = Vec::with_capacity(bits.len() * 2 + 1);
+        for &(tms, tdi) in bits {
+            self.set_pin(self.pins.tck, false);
+            self.set_pin(self.pins.tms, tms);
+            self.set_pin(self.pins.tdi, tdi);
+            states.push(self.state);
+            states.push(self.state | (1 << self.pins.tck));
+        }
+        // Keep the clock low between transfers.
+        states.push(self.state);
+
+        let samples = self.io.transfer(&states).map_err(probe_error)?;
+        if samples.len() != states.len() {
+            return Err(probe_error(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the adapter returned the wrong number of samples",
+            )));
+        }
+
+        Ok(samples
+            .chunks_exact(2)
+            .map(|sample| (sample[0], sample[1]))
+            .collect())
+    }
+
+    /// Clocks out `bits`, and returns the TDO bits sampled at each rising edge of TCK.
+    fn jtag_clock(&mut self, bits: &[(bool, bool)]) -> Result<Vec<bool>, DebugProbeError> {
+        let tdo = self.pins.tdo;
+        Ok(self
+            .clock(bits)?
+            .into_iter()
+            .map(|(low, _)| low & (1 << tdo) != 0)
+            .collect())
+    }
+
+    /// Moves the TAP to Test-Logic-Reset, and then to Run-Test/Idle.
+    fn jtag_reset(&mut self) -> Result<(), DebugProbeError> {
+        let mut bits = vec![(true, false); 5];
+        bits.push((false, false));
+        self.jtag_clock(&bits)?;
+        Ok(())
+    }
+
+    /// Shifts `data` through the IR or DR, starting and ending in Run-Test/Idle.
+    ///
+    /// Returns the data shifted out of the register.
+    fn jtag_shift(&mut self, ir: bool, data: &BitSlice<u8>) -> Result<BitVec<u8>, DebugProbeError> {
+        // Select-DR-Scan, (Select-IR-Scan,) Capture and Shift.
+        let mut bits = vec![(true, false)];
+        if ir {
+            bits.push((true, false));
+        }
+        bits.extend([(false, false), (false, false)]);
+        let start = bits.len();
+
+        // The last bit is shifted while moving to Exit1.
+        bits.extend(
+            data.iter()
+                .enumerate()
+                .map(|(i, bit)| (i + 1 == data.len(), *bit)),
+        );
+        let end = bits.len();
+
+        // Update and Run-Test/Idle.
+        bits.extend([(true, false), (false, false)]);
+        if !ir {
+            bits.extend(std::iter::repeat((false, false)).take(self.idle_cycles as usize));
+        }
+
+        let tdo = self.jtag_clock(&bits)?;
+        Ok(tdo[start..end].iter().copied().collect())
+    }
+
+    /// Reads the IR or DR scan chain after a reset.
+    fn jtag_scan_register(&mut self, ir: bool) -> Result<BitVec<u8>, DebugProbeError> {
+        // Fill the chain with 0s and then with 1s. The first 1 shifted out gives the length of
+        // the chain, and the bits before it are the captured values.
+        let mut data = bitvec![u8, Lsb0; 0; MAX_CHAIN_LENGTH];
+        data.resize(MAX_CHAIN_LENGTH * 2, true);
+        let tdo = self.jtag_shift(ir, &data)?;
+
+        let (captured, filled) = tdo.split_at(MAX_CHAIN_LENGTH);
+        match filled.first_one() {
+            Some(0) => {
+                tracing::error!("JTAG scan chain is empty");
+                Err(DebugProbeError::TargetNotFound)
+            }
+            Some(length) if !captured[length..].any() => Ok(captured[..length].to_bitvec()),
+            _ => {
+                tracing::error!("JTAG scan chain either broken or too long");
+                Err(DebugProbeError::TargetNotFound)
+            }
+        }
+    }
+
+    /// Detects the TAPs in the scan chain.
+    fn jtag_scan(&mut self) -> Result<Vec<JtagChainItem>, DebugProbeError> {
+        self.jtag_reset()?;
+        let dr = self.jtag_scan_register(false)?;
+        let ir = self.jtag_scan_register(true)?;
+
+        let idcodes = extract_idcodes(&dr).map_err(probe_error)?;
+        let expected = self.scan_chain.as_ref().and_then(|chain| {
+            chain
+                .iter()
+                .map(|element| element.ir_len.map(usize::from))
+                .collect::<Option<Vec<_>>>()
+        });
+        let irlens =
+            extract_ir_lengths(&ir, idcodes.len(), expected.as_deref()).map_err(probe_error)?;
+
+        Ok(idcodes
+            .into_iter()
+            .zip(irlens)
+            .map(|(idcode, irlen)| JtagChainItem {
+                idcode: idcode.map(u32::from).unwrap_or(0),
+                irlen,
+            })
+            .collect())
+    }
+
+    /// Selects the register `address` of the selected TAP, and shifts `len` bits of `data`
+    /// through it.
+    fn jtag_transfer(
+        &mut self,
+        address: u32,
+        data: Option<&[u8]>,
+        len: usize,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        let Some(params) = self.chain_params else {
+            return Err(DebugProbeError::NotAttached);
+        };
+        if params.irlen < 32 && address >= 1 << params.irlen {
+            return Err(DebugProbeError::NotImplemented(
+                "JTAG register addresses longer than the IR",
+            ));
+        }
+
+        // All other TAPs are put in BYPASS, by shifting 1s into their IRs.
+        let mut ir = bitvec![u8, Lsb0; 1; params.irpre];
+        ir.extend(address.view_bits::<Lsb0>()[..params.irlen].iter().by_vals());
+        ir.resize(ir.len() + params.irpost, true);
+        self.jtag_shift(true, &ir)?;
+
+        let mut dr = bitvec![u8, Lsb0; 0; params.drpre];
+        match data {
+            Some(data) => dr.extend(data.view_bits::<Lsb0>()[..len].iter().by_vals()),
+            None => dr.resize(dr.len() + len, false),
+        }
+        dr.resize(dr.len() + params.drpost, false);
+        let reply = self.jtag_shift(false, &dr)?;
+
+        let mut reply = reply[params.drpre..][..len].to_bitvec();
+        reply.force_align();
+        Ok(reply.into_vec())
+    }
+}
+
+fn probe_error(error: impl std::error::Error + Send + Sync + 'static) -> DebugProbeError {
+    DebugProbeError::ProbeSpecific(Box::new(error))
+}
+
+impl DebugProbe for BitbangProbe {
+    fn new_from_selector(
+        _selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        Err(DebugProbeError::NotImplemented(
+            "opening a bitbang probe directly",
+        ))
+    }
+
+    fn get_name(&self) -> &str {
+        "Bitbang"
+    }
+
+    fn speed_khz(&self) -> u32 {
+        self.speed_khz
+    }
+
+    fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+        // Every clock cycle takes two states.
+        let frequency = self
+            .io
+            .set_frequency(speed_khz.saturating_mul(2000))
+            .map_err(|_| DebugProbeError::UnsupportedSpeed(speed_khz))?;
+        self.speed_khz = frequency / 2000;
+        Ok(self.speed_khz)
+    }
+
+    fn set_scan_chain(&mut self, scan_chain: Vec<ScanChainElement>) -> Result<(), DebugProbeError> {
+        self.scan_chain = Some(scan_chain);
+        Ok(())
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        if self.protocol.is_none() {
+            self.protocol = Some(WireProtocol::Jtag);
+        }
+
+        // Release the reset, and keep the clock low.
+        self.state = self.pins.srst.map_or(0, |srst| 1 << srst);
+        self.io
+            .set_direction(self.output_mask(true))
+            .map_err(probe_error)?;
+
+        if self.protocol == Some(WireProtocol::Swd) {
+            // The line reset is done by the ARM interface.
+            return Ok(());
+        }
+
+        let taps = self.jtag_scan()?;
+        tracing::debug!("Detected TAPs: {:x?}", taps);
+        if taps.len() > 1 {
+            tracing::warn!("More than one TAP detected, defaulting to tap0");
+        }
+
+        self.taps = taps;
+        self.select_jtag_tap(0)
+    }
+
+    fn detach(&mut self) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        self.target_reset_pulse(Duration::from_millis(10))
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        let Some(srst) = self.pins.srst else {
+            return Err(DebugProbeError::CommandNotSupportedByProbe("target reset"));
+        };
+
+        self.reset_asserted = true;
+        self.set_pin(srst, false);
+        let swdio_output = self.protocol != Some(WireProtocol::Swd);
+        self.io
+            .set_direction(self.output_mask(swdio_output))
+            .map_err(probe_error)?;
+        self.io.transfer(&[self.state]).map_err(probe_error)?;
+        Ok(())
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        let Some(srst) = self.pins.srst else {
+            return Err(DebugProbeError::CommandNotSupportedByProbe("target reset"));
+        };
+
+        self.reset_asserted = false;
+        self.set_pin(srst, true);
+        let swdio_output = self.protocol != Some(WireProtocol::Swd);
+        self.io
+            .set_direction(self.output_mask(swdio_output))
+            .map_err(probe_error)?;
+        Ok(())
+    }
+
+    fn select_protocol(&mut self, protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        self.protocol = Some(protocol);
+        Ok(())
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        self.protocol
+    }
+
+    fn has_arm_interface(&self) -> bool {
+        true
+    }
+
+    fn try_get_arm_interface<'probe>(
+        self: Box<Self>,
+    ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
+    {
+        Ok(Box::new(ArmCommunicationInterface::new(self, true)))
+    }
+
+    fn try_get_riscv_interface(
+        self: Box<Self>,
+    ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
+        match RiscvCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn has_riscv_interface(&self) -> bool {
+        true
+    }
+
+    fn try_get_xtensa_interface(
+        self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, DebugProbeError)> {
+        match XtensaCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn has_xtensa_interface(&self) -> bool {
+        true
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
+        Some(self)
+    }
+
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+}
+
+impl JTAGAccess for BitbangProbe {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        self.jtag_transfer(address, None, len as usize)
+    }
+
+    fn set_idle_cycles(&mut self, idle_cycles: u8) {
+        self.idle_cycles = idle_cycles;
+    }
+
+    fn idle_cycles(&self) -> u8 {
+        self.idle_cycles
+    }
+
+    fn set_ir_len(&mut self, _len: u32) {
+        // The IR lengths are detected when attaching.
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        self.jtag_transfer(address, Some(data), len as usize)
+    }
+
+    fn select_jtag_tap(&mut self, index: usize) -> Result<(), DebugProbeError> {
+        let Some(params) = ChainParams::from_jtag_chain(&self.taps, index) else {
+            return Err(DebugProbeError::TargetNotFound);
+        };
+
+        self.chain_params = Some(params);
+        self.selected_tap = index;
+        Ok(())
+    }
+
+    fn selected_jtag_tap(&self) -> usize {
+        self.selected_tap
+    }
+
+    fn scan_chain(&mut self) -> Result<Vec<JtagChainItem>, DebugProbeError> {
+        Ok(self.taps.clone())
+    }
+}
+
+impl RawProtocolIo for BitbangProbe {
+    fn jtag_shift_tms<M>(&mut self, tms: M, tdi: bool) -> Result<(), DebugProbeError>
+    where
+        M: IntoIterator<Item = bool>,
+    {
+        let bits: Vec<_> = tms.into_iter().map(|tms| (tms, tdi)).collect();
+        self.jtag_clock(&bits)?;
+        Ok(())
+    }
+
+    fn jtag_shift_tdi<I>(&mut self, tms: bool, tdi: I) -> Result<(), DebugProbeError>
+    where
+        I: IntoIterator<Item = bool>,
+    {
+        let bits: Vec<_> = tdi.into_iter().map(|tdi| (tms, tdi)).collect();
+        self.jtag_clock(&bits)?;
+        Ok(())
+    }
+
+    fn swd_io<D, S>(&mut self, dir: D, swdio: S) -> Result<Vec<bool>, DebugProbeError>
+    where
+        D: IntoIterator<Item = bool>,
+        S: IntoIterator<Item = bool>,
+    {
+        let bits: Vec<(bool, bool)> = dir.into_iter().zip(swdio).collect();
+        let mut result = Vec::with_capacity(bits.len());
+
+        // SWDIO is turned around between runs of outputs and inputs.
+        let mut start = 0;
+        while start < bits.len() {
+            let output = bits[start].0;
+            let end = bits[start..]
+                .iter()
+                .position(|&(dir, _)| dir != output)
+                .map_or(bits.len(), |n| start + n);
+
+            self.io
+                .set_direction(self.output_mask(output))
+                .map_err(probe_error)?;
+
+            let levels: Vec<_> = bits[start..end]
+                .iter()
+                .map(|&(_, swdio)| (swdio, false))
+                .collect();
+            // The target changes SWDIO on the rising edge, so it is sampled after it.
+            let swdio = self.pins.tms;
+            result.extend(
+                self.clock(&levels)?
+                    .into_iter()
+                    .map(|(_, high)| high & (1 << swdio) != 0),
+            );
+
+            start = end;
+        }
+
+        Ok(result)
+    }
+
+    fn swd_settings(&self) -> &SwdSettings {
+        &self.swd_settings
+    }
+
+    fn probe_statistics(&mut self) -> &mut ProbeStatistics {
+        &mut self.probe_statistics
+    }
+}
+
+impl DapProbe for BitbangProbe {}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use super::*;
+
+    /// An adapter with TDI wired to TDO, recording all states.
+    #[derive(Debug, Default)]
+    struct Loopback {
+        states: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl BitbangIo for Loopback {
+        fn set_direction(&mut self, _outputs: u8) -> io::Result<()> {
+            Ok(())
+        }
+
+        fn transfer(&mut self, states: &[u8]) -> io::Result<Vec<u8>> {
+            let pins = PinMapping::default();
+            self.states.lock().unwrap().extend_from_slice(states);
+            Ok(states
+                .iter()
+                .map(|state| state | (((state >> pins.tdi) & 1) << pins.tdo))
+                .collect())
+        }
+
+        fn set_frequency(&mut self, frequency: u32) -> io::Result<u32> {
+            Ok(frequency)
+        }
+    }
+
+    #[test]
+    fn write_register_shifts_through_selected_tap() {
+        let io = Loopback::default();
+        let states = io.states.clone();
+
+        let mut probe = BitbangProbe::new(Box::new(io), PinMapping::default());
+        probe.taps = vec![JtagChainItem {
+            idcode: 0x120034e5,
+            irlen: 5,
+        }];
+        probe.select_jtag_tap(0).unwrap();
+
+        let reply = probe.write_register(0x11, &[0xa5, 0x03], 10).unwrap();
+        assert_eq!(reply, vec![0xa5, 0x03]);
+
+        // The IR scan: Select-DR, Select-IR, Capture-IR, Shift-IR, then 5 bits ending with TMS
+        // high, Update-IR and Run-Test/Idle. Each cycle is a low and a high state.
+        let pins = PinMapping::default();
+        let tms: Vec<bool> = states.lock().unwrap()[..22]
+            .chunks(2)
+            .map(|states| states[0] & (1 << pins.tms) != 0)
+            .collect();
+        assert_eq!(
+            tms,
+            [true, true, false, false, false, false, false, false, true, true, false]
+        );
+    }
+}
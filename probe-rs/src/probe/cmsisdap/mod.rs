@@ -840,6 +840,24 @@ impl DebugProbe for CmsisDap {
         Ok(())
     }
 
+    fn target_trst_assert(&mut self) -> Result<(), DebugProbeError> {
+        let request = SWJPinsRequestBuilder::new().ntrst(false).build();
+
+        commands::send_command(&mut self.device, request).map(|v: SWJPinsResponse| {
+            tracing::info!("Pin response: {:?}", v);
+        })?;
+        Ok(())
+    }
+
+    fn target_trst_deassert(&mut self) -> Result<(), DebugProbeError> {
+        let request = SWJPinsRequestBuilder::new().ntrst(true).build();
+
+        commands::send_command(&mut self.device, request).map(|v: SWJPinsResponse| {
+            tracing::info!("Pin response: {:?}", v);
+        })?;
+        Ok(())
+    }
+
     fn get_swo_interface(&self) -> Option<&dyn SwoAccess> {
         Some(self as _)
     }
@@ -71,6 +71,12 @@ impl IdCode {
     }
 }
 
+impl From<IdCode> for u32 {
+    fn from(idcode: IdCode) -> u32 {
+        idcode.0
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ScanChainError {
     #[error("Invalid IDCODE")]
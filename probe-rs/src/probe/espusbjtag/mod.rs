@@ -41,6 +41,11 @@ pub(crate) struct EspUsbJtag {
     max_ir_address: u8,
     scan_chain: Option<Vec<ScanChainElement>>,
     chain_params: ChainParams,
+
+    /// The TAPs found during the last reset scan.
+    taps: Vec<JtagChainItem>,
+    /// The index of the TAP that is currently selected.
+    selected_tap: usize,
 }
 
 impl EspUsbJtag {
@@ -57,6 +62,30 @@ impl EspUsbJtag {
             .collect())
     }
 
+    /// Computes the chain parameters required to talk to the TAP at `selected`.
+    fn select_tap(&mut self, selected: usize) -> Result<(), DebugProbeError> {
+        let Some(params) = ChainParams::from_jtag_chain(&self.taps, selected) else {
+            return Err(DebugProbeError::Other(anyhow!(
+                "TAP {} does not exist, the scan chain contains {} TAPs",
+                selected,
+                self.taps.len()
+            )));
+        };
+
+        tracing::info!("Setting chain params: {:?}", params);
+
+        // set the max address to the max number of bits irlen can represent
+        self.max_ir_address = ((1 << params.irlen).wrapping_sub(&1)) as u8;
+        tracing::debug!("Setting max_ir_address to {}", self.max_ir_address);
+        self.chain_params = params;
+        self.selected_tap = selected;
+
+        // The IR of the newly selected TAP is unknown, force a write on the next access.
+        self.current_ir_reg = u8::MAX;
+
+        Ok(())
+    }
+
     fn reset_scan(&mut self) -> Result<(Vec<u32>, Vec<usize>), super::DebugProbeError> {
         let max_chain = 8;
 
@@ -312,6 +341,18 @@ pub struct DeferredRegisterWrite {
 }
 
 impl JTAGAccess for EspUsbJtag {
+    fn select_jtag_tap(&mut self, index: usize) -> Result<(), DebugProbeError> {
+        self.select_tap(index)
+    }
+
+    fn selected_jtag_tap(&self) -> usize {
+        self.selected_tap
+    }
+
+    fn scan_chain(&mut self) -> Result<Vec<JtagChainItem>, DebugProbeError> {
+        Ok(self.taps.clone())
+    }
+
     fn set_ir_len(&mut self, len: u32) {
         if len != 5 {
             panic!("Only IR Length of 5 is currently supported");
@@ -439,6 +480,8 @@ impl DebugProbe for EspUsbJtag {
                 drpost: 0,
                 irlen: 0,
             },
+            taps: Vec::new(),
+            selected_tap: 0,
         }))
     }
 
@@ -481,42 +524,16 @@ impl DebugProbe for EspUsbJtag {
         let taps = self.scan()?;
         tracing::info!("Found {} TAPs on reset scan", taps.len());
 
-        let selected = 0;
-        if taps.len() > 1 {
-            tracing::warn!("More than one TAP detected, defaulting to tap0")
-        }
-
-        let mut params = ChainParams {
-            irpre: 0,
-            irpost: 0,
-            drpre: 0,
-            drpost: 0,
-            irlen: 0,
-        };
-
-        let mut found = false;
-        for (index, tap) in taps.iter().enumerate() {
+        for tap in &taps {
             tracing::info!("{:?}", tap);
-            if index == selected {
-                params.irlen = tap.irlen;
-                found = true;
-            } else if found {
-                params.irpost += tap.irlen;
-                params.drpost += 1;
-            } else {
-                params.irpre += tap.irlen;
-                params.drpre += 1;
-            }
         }
 
-        tracing::info!("Setting chain params: {:?}", params);
-
-        // set the max address to the max number of bits irlen can represent
-        self.max_ir_address = ((1 << params.irlen).wrapping_sub(&1)) as u8;
-        tracing::debug!("Setting max_ir_address to {}", self.max_ir_address);
-        self.chain_params = params;
+        if taps.len() > 1 {
+            tracing::warn!("More than one TAP detected, defaulting to tap0")
+        }
 
-        Ok(())
+        self.taps = taps;
+        self.select_tap(0)
     }
 
     fn detach(&mut self) -> Result<(), crate::Error> {
@@ -591,6 +608,10 @@ impl DebugProbe for EspUsbJtag {
         Ok(None)
     }
 
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
     fn try_get_xtensa_interface(
         self: Box<Self>,
     ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, DebugProbeError)> {
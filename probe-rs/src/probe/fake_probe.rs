@@ -614,8 +614,108 @@ impl DapAccess for FakeArmInterface<Initialized> {
 
 #[cfg(test)]
 mod test {
+    use std::time::Duration;
+
+    use probe_rs_target::ScanChainElement;
+
     use super::FakeProbe;
-    use crate::Permissions;
+    use crate::{
+        architecture::xtensa::communication_interface::XtensaError,
+        probe::JTAGAccess,
+        test::{MockCore, MockMemory},
+        Core, DebugProbe, DebugProbeError, DebugProbeSelector, Error, HaltingOperation,
+        MemoryInterface, Permissions, Probe, WireProtocol,
+    };
+
+    /// A JTAG probe which can only access the first TAP of the scan chain.
+    #[derive(Debug)]
+    struct SingleTapProbe;
+
+    impl DebugProbe for SingleTapProbe {
+        fn new_from_selector(
+            _selector: impl Into<DebugProbeSelector>,
+        ) -> Result<Box<Self>, DebugProbeError> {
+            Ok(Box::new(SingleTapProbe))
+        }
+
+        fn get_name(&self) -> &str {
+            "Single TAP probe for testing"
+        }
+
+        fn speed_khz(&self) -> u32 {
+            1000
+        }
+
+        fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+            Ok(speed_khz)
+        }
+
+        fn set_scan_chain(
+            &mut self,
+            _scan_chain: Vec<ScanChainElement>,
+        ) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn attach(&mut self) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn detach(&mut self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+            Err(DebugProbeError::NotImplemented("target_reset"))
+        }
+
+        fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+            Err(DebugProbeError::NotImplemented("target_reset_assert"))
+        }
+
+        fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+            Err(DebugProbeError::NotImplemented("target_reset_deassert"))
+        }
+
+        fn select_protocol(&mut self, _protocol: WireProtocol) -> Result<(), DebugProbeError> {
+            Ok(())
+        }
+
+        fn active_protocol(&self) -> Option<WireProtocol> {
+            Some(WireProtocol::Jtag)
+        }
+
+        fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+            self
+        }
+
+        fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+            Some(self)
+        }
+    }
+
+    impl JTAGAccess for SingleTapProbe {
+        fn read_register(&mut self, _address: u32, _len: u32) -> Result<Vec<u8>, DebugProbeError> {
+            Err(DebugProbeError::NotImplemented("read_register"))
+        }
+
+        fn set_idle_cycles(&mut self, _idle_cycles: u8) {}
+
+        fn idle_cycles(&self) -> u8 {
+            0
+        }
+
+        fn set_ir_len(&mut self, _len: u32) {}
+
+        fn write_register(
+            &mut self,
+            _address: u32,
+            _data: &[u8],
+            _len: u32,
+        ) -> Result<Vec<u8>, DebugProbeError> {
+            Err(DebugProbeError::NotImplemented("write_register"))
+        }
+    }
 
     #[test]
     fn create_session_with_fake_probe() {
@@ -627,4 +727,79 @@ mod test {
             .attach("nrf51822_xxAC", Permissions::default())
             .unwrap();
     }
+
+    #[test]
+    fn read_only_session_rejects_intrusive_operations() {
+        let probe = FakeProbe::new().into_probe();
+
+        let mut session = probe
+            .attach("nrf51822_xxAC", Permissions::read_only())
+            .unwrap();
+        let mut core = session.core(0).unwrap();
+
+        assert!(matches!(
+            core.halt(Duration::from_millis(100)),
+            Err(Error::MissingPermissions(_))
+        ));
+        assert!(matches!(core.reset(), Err(Error::MissingPermissions(_))));
+        assert!(matches!(
+            core.write_word_32(0x2000_0000, 0),
+            Err(Error::MissingPermissions(_))
+        ));
+    }
+
+    #[test]
+    fn read_only_core_is_not_halted_implicitly() {
+        let mut memory = MockMemory::new();
+        memory.add_word_range(0x3FC8_8000, &[0x1234_5678]);
+        let permissions = Permissions::read_only();
+        let mut core = Core::new(MockCore::new(memory))
+            .with_permissions(&permissions)
+            .with_implicit_halts(true);
+
+        assert!(matches!(
+            core.read_word_32(0x3FC8_8000),
+            Err(Error::NotHalted(HaltingOperation::Memory))
+        ));
+        assert!(!core.core_halted().unwrap());
+    }
+
+    #[test]
+    fn implicit_halt_resumes_the_core() {
+        let mut memory = MockMemory::new();
+        memory.add_word_range(0x3FC8_8000, &[0x1234_5678]);
+        let permissions = Permissions::default();
+        let mut core = Core::new(MockCore::new(memory)).with_permissions(&permissions);
+
+        assert!(matches!(
+            core.read_word_32(0x3FC8_8000),
+            Err(Error::NotHalted(HaltingOperation::Memory))
+        ));
+
+        let mut core = core.with_implicit_halts(true);
+        assert_eq!(core.read_word_32(0x3FC8_8000).unwrap(), 0x1234_5678);
+        assert!(!core.core_halted().unwrap());
+    }
+
+    #[test]
+    fn single_tap_probe_selects_only_the_first_tap() {
+        let mut probe = Probe::from_specific_probe(Box::new(SingleTapProbe));
+
+        probe.select_jtag_tap(0).unwrap();
+        assert!(matches!(
+            probe.select_jtag_tap(1),
+            Err(DebugProbeError::NotImplemented(_))
+        ));
+    }
+
+    #[test]
+    fn attaching_fails_if_the_probe_cannot_select_the_core_taps() {
+        let probe = Probe::from_specific_probe(Box::new(SingleTapProbe));
+
+        let error = probe.attach("esp32", Permissions::default()).unwrap_err();
+        assert!(matches!(
+            error,
+            Error::Xtensa(XtensaError::JtagTapNotSelectable { core: 1, tap: 1 })
+        ));
+    }
 }
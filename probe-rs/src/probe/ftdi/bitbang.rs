@@ -0,0 +1,116 @@
+//! Synchronous bitbang mode of FTDI chips without an MPSSE engine, like the FT232R.
+
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+use super::ftdi;
+use crate::probe::bitbang::{BitbangIo, BitbangProbe, PinMapping};
+use crate::{DebugProbeError, DebugProbeSelector, ProbeCreationError};
+
+/// (VendorId, ProductId)
+pub(super) static FTDI_BITBANG_DEVICE_IDS: &[(u16, u16)] = &[
+    (0x0403, 0x6001), // FTDI Ltd. FT232R USB UART
+    (0x0403, 0x6015), // FTDI Ltd. FT230X/FT231X Basic UART
+];
+
+/// The number of states written at once.
+///
+/// In synchronous mode, the chip only applies a state when it can store the sampled pins, so
+/// more states than fit in its receive buffer can't be written before reading the samples.
+const CHUNK_SIZE: usize = 64;
+
+#[derive(Debug)]
+struct FtdiBitbang {
+    device: ftdi::Device,
+    /// The last state written, which is repeated to sample the pins after it.
+    state: u8,
+}
+
+impl FtdiBitbang {
+    fn read_samples(&mut self, size: usize) -> io::Result<Vec<u8>> {
+        let timeout = Duration::from_millis(100);
+        let mut result = Vec::new();
+
+        let t0 = Instant::now();
+        while result.len() < size {
+            if t0.elapsed() > timeout {
+                return Err(io::Error::from(io::ErrorKind::TimedOut));
+            }
+
+            self.device.read_to_end(&mut result)?;
+        }
+
+        if result.len() > size {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Read more data than expected",
+            ));
+        }
+
+        Ok(result)
+    }
+}
+
+impl BitbangIo for FtdiBitbang {
+    fn set_direction(&mut self, outputs: u8) -> io::Result<()> {
+        self.device.set_bitmode(outputs, ftdi::BitMode::SyncBb)
+    }
+
+    fn transfer(&mut self, states: &[u8]) -> io::Result<Vec<u8>> {
+        // The pins are sampled right before each state is applied, so every sample belongs to
+        // the state before it. The last state is repeated to get the sample after it.
+        let mut data = states.to_vec();
+        if let Some(last) = states.last() {
+            self.state = *last;
+        }
+        data.push(self.state);
+
+        let mut samples = Vec::with_capacity(data.len());
+        for chunk in data.chunks(CHUNK_SIZE) {
+            self.device.write_all(chunk)?;
+            samples.extend(self.read_samples(chunk.len())?);
+        }
+
+        samples.remove(0);
+        Ok(samples)
+    }
+
+    fn set_frequency(&mut self, frequency: u32) -> io::Result<u32> {
+        self.device
+            .set_baudrate(frequency.min(i32::MAX as u32) as i32)?;
+        Ok(frequency)
+    }
+}
+
+/// Opens the FTDI chip selected by `selector` in bitbang mode, if it has no MPSSE engine.
+///
+/// The JTAG signals are expected on the same pins as for the MPSSE engine.
+pub(crate) fn open(selector: &DebugProbeSelector) -> Result<Box<BitbangProbe>, DebugProbeError> {
+    let (vendor_id, product_id) = (selector.vendor_id, selector.product_id);
+
+    if !FTDI_BITBANG_DEVICE_IDS.contains(&(vendor_id, product_id)) {
+        return Err(DebugProbeError::ProbeCouldNotBeCreated(
+            ProbeCreationError::NotFound,
+        ));
+    }
+
+    let open = || -> Result<FtdiBitbang, ftdi::Error> {
+        let mut builder = ftdi::Builder::new();
+        builder.set_interface(ftdi::Interface::A)?;
+        let mut device = builder.usb_open(vendor_id, product_id)?;
+
+        device.usb_reset()?;
+        device.set_latency_timer(1)?;
+        device.set_bitmode(0, ftdi::BitMode::SyncBb)?;
+        device.usb_purge_buffers()?;
+
+        Ok(FtdiBitbang { device, state: 0 })
+    };
+    let io = open().map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))?;
+
+    tracing::debug!("opened bitbang probe: {:?}", io);
+    Ok(Box::new(BitbangProbe::new(
+        Box::new(io),
+        PinMapping::default(),
+    )))
+}
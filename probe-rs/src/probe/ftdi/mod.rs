@@ -21,6 +21,8 @@ use ftdi_impl as ftdi;
 
 mod commands;
 
+pub(crate) mod bitbang;
+
 use self::commands::{JtagCommand, WriteRegisterCommand};
 
 use super::{BatchExecutionError, ChainParams, CommandResult, JtagChainItem};
@@ -29,6 +31,10 @@ use super::{BatchExecutionError, ChainParams, CommandResult, JtagChainItem};
 pub struct JtagAdapter {
     device: ftdi::Device,
     chain_params: Option<ChainParams>,
+    /// The TAPs found during the last scan.
+    taps: Vec<JtagChainItem>,
+    /// The index of the TAP that is currently selected.
+    selected_tap: usize,
 }
 
 impl JtagAdapter {
@@ -40,6 +46,8 @@ impl JtagAdapter {
         Ok(Self {
             device,
             chain_params: None,
+            taps: Vec::new(),
+            selected_tap: 0,
         })
     }
 
@@ -307,36 +315,16 @@ impl JtagAdapter {
         Ok(targets)
     }
 
-    pub fn select_target(&mut self, idcode: u32) -> io::Result<()> {
-        let taps = self.scan()?;
-
-        let mut found = false;
-        let mut params = ChainParams {
-            irpre: 0,
-            irpost: 0,
-            drpre: 0,
-            drpost: 0,
-            irlen: 0,
-        };
-        for tap in taps {
-            if tap.idcode == idcode {
-                params.irlen = tap.irlen;
-                found = true;
-            } else if found {
-                params.irpost += tap.irlen;
-                params.drpost += 1;
-            } else {
-                params.irpre += tap.irlen;
-                params.drpre += 1;
+    /// Selects the TAP at `index` in the chain found by the last scan.
+    pub fn select_tap(&mut self, index: usize) -> io::Result<()> {
+        match ChainParams::from_jtag_chain(&self.taps, index) {
+            Some(params) => {
+                tracing::debug!("Target chain params: {:?}", params);
+                self.chain_params = Some(params);
+                self.selected_tap = index;
+                Ok(())
             }
-        }
-
-        if found {
-            tracing::debug!("Target chain params: {:?}", params);
-            self.chain_params = Some(params);
-            Ok(())
-        } else {
-            Err(io::Error::new(io::ErrorKind::NotFound, "target not found"))
+            None => Err(io::Error::new(io::ErrorKind::NotFound, "target not found")),
         }
     }
 
@@ -479,27 +467,24 @@ impl DebugProbe for FtdiProbe {
             tracing::warn!("no JTAG taps detected");
             return Err(DebugProbeError::TargetNotFound);
         }
-        if taps.len() == 1 {
-            self.adapter
-                .select_target(taps[0].idcode)
-                .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))?;
-        } else {
-            let known_idcodes = [
-                0x1000563d, // GD32VF103
-            ];
-            let idcode = taps
-                .iter()
-                .map(|tap| tap.idcode)
-                .find(|idcode| known_idcodes.iter().any(|v| v == idcode));
-            if let Some(idcode) = idcode {
-                self.adapter
-                    .select_target(idcode)
-                    .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))?;
-            } else {
-                return Err(DebugProbeError::TargetNotFound);
-            }
-        }
-        Ok(())
+
+        let known_idcodes = [
+            0x1000563d, // GD32VF103
+        ];
+        let index = taps
+            .iter()
+            .position(|tap| known_idcodes.contains(&tap.idcode))
+            .unwrap_or_else(|| {
+                if taps.len() > 1 {
+                    tracing::warn!("More than one TAP detected, defaulting to tap0");
+                }
+                0
+            });
+
+        self.adapter.taps = taps;
+        self.adapter
+            .select_tap(index)
+            .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))
     }
 
     fn detach(&mut self) -> Result<(), crate::Error> {
@@ -558,6 +543,10 @@ impl DebugProbe for FtdiProbe {
         todo!()
     }
 
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
     fn try_get_xtensa_interface(
         self: Box<Self>,
     ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, DebugProbeError)> {
@@ -756,6 +745,20 @@ impl JTAGAccess for FtdiProbe {
     fn set_ir_len(&mut self, _len: u32) {
         // The FTDI implementation automatically sets this, so no need to act on this data
     }
+
+    fn select_jtag_tap(&mut self, index: usize) -> Result<(), DebugProbeError> {
+        self.adapter
+            .select_tap(index)
+            .map_err(|e| DebugProbeError::ProbeSpecific(Box::new(e)))
+    }
+
+    fn selected_jtag_tap(&self) -> usize {
+        self.adapter.selected_tap
+    }
+
+    fn scan_chain(&mut self) -> Result<Vec<JtagChainItem>, DebugProbeError> {
+        Ok(self.adapter.taps.clone())
+    }
 }
 
 /// (VendorId, ProductId)
@@ -771,6 +774,7 @@ fn get_device_info(device: &rusb::Device<rusb::Context>) -> Option<DebugProbeInf
 
     if !FTDI_COMPAT_DEVICE_IDS
         .iter()
+        .chain(bitbang::FTDI_BITBANG_DEVICE_IDS)
         .any(|(vid, pid)| d_desc.vendor_id() == *vid && d_desc.product_id() == *pid)
     {
         return None;
@@ -457,6 +457,34 @@ impl DebugProbe for JLink {
         Ok(speed_khz)
     }
 
+    fn supports_adaptive_clocking(&self) -> bool {
+        self.handle
+            .capabilities()
+            .contains(Capability::AdaptiveClocking)
+    }
+
+    fn set_adaptive_clocking(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        if !self.supports_adaptive_clocking() {
+            return if enabled {
+                Err(DebugProbeError::CommandNotSupportedByProbe(
+                    "adaptive clocking",
+                ))
+            } else {
+                Ok(())
+            };
+        }
+
+        let speed = if enabled {
+            SpeedConfig::ADAPTIVE
+        } else {
+            SpeedConfig::khz(self.speed_khz as u16)
+                .ok_or(DebugProbeError::UnsupportedSpeed(self.speed_khz))?
+        };
+        self.handle.set_speed(speed)?;
+
+        Ok(())
+    }
+
     fn attach(&mut self) -> Result<(), super::DebugProbeError> {
         tracing::debug!("Attaching to J-Link");
 
@@ -569,6 +597,32 @@ impl DebugProbe for JLink {
         Ok(())
     }
 
+    fn target_trst_assert(&mut self) -> Result<(), DebugProbeError> {
+        self.handle.set_trst(false)?;
+        Ok(())
+    }
+
+    fn target_trst_deassert(&mut self) -> Result<(), DebugProbeError> {
+        self.handle.set_trst(true)?;
+        Ok(())
+    }
+
+    fn supports_target_power(&self) -> bool {
+        self.handle.capabilities().contains(Capability::SetKsPower)
+    }
+
+    fn set_target_power(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        if !self.supports_target_power() {
+            return Err(DebugProbeError::CommandNotSupportedByProbe(
+                "target power control",
+            ));
+        }
+
+        // The probe supplies the target through pin 19 of the JTAG connector.
+        self.handle.set_kickstart_power(enabled)?;
+        Ok(())
+    }
+
     fn try_get_riscv_interface(
         self: Box<Self>,
     ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
@@ -609,6 +663,10 @@ impl DebugProbe for JLink {
         Some(self)
     }
 
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
     fn try_get_arm_interface<'probe>(
         self: Box<Self>,
     ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
@@ -0,0 +1,602 @@
+//! Recording of JTAG transactions, and replaying them in place of a probe, so that the code
+//! driving the debug modules can be tested against traces captured on real hardware.
+
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+use std::sync::{Arc, Mutex};
+
+use anyhow::anyhow;
+use probe_rs_target::ScanChainElement;
+
+use super::{DebugProbe, DebugProbeError, DebugProbeSelector, JTAGAccess, WireProtocol};
+use crate::architecture::{
+    arm::{
+        communication_interface::{DapProbe, UninitializedArmProbe},
+        SwoAccess,
+    },
+    riscv::communication_interface::{RiscvCommunicationInterface, RiscvError},
+    xtensa::communication_interface::XtensaCommunicationInterface,
+};
+
+/// A JTAG access, without its result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum JtagAccess {
+    /// The TAP at `index` in the scan chain was selected.
+    SelectTap {
+        /// The index of the TAP.
+        index: usize,
+    },
+    /// The register selected by the IR value `address` was read.
+    Read {
+        /// The IR value.
+        address: u32,
+        /// The length of the register in bits.
+        len: u32,
+    },
+    /// `data` was shifted into the register selected by the IR value `address`.
+    Write {
+        /// The IR value.
+        address: u32,
+        /// The data shifted into the register.
+        data: Vec<u8>,
+        /// The length of the register in bits.
+        len: u32,
+    },
+}
+
+/// A JTAG access, along with its result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JtagTransaction {
+    /// The access.
+    pub access: JtagAccess,
+    /// The data shifted out of the register, or `None` if the access failed.
+    pub capture: Option<Vec<u8>>,
+}
+
+/// The JTAG transactions done through a probe, in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JtagRecording {
+    /// The recorded transactions.
+    pub transactions: Vec<JtagTransaction>,
+}
+
+impl JtagRecording {
+    /// Writes the recording to `writer`, as YAML.
+    pub fn save(&self, writer: impl Write) -> Result<(), serde_yaml::Error> {
+        serde_yaml::to_writer(writer, self)
+    }
+
+    /// Reads a recording written by [`JtagRecording::save`] from `reader`.
+    pub fn load(reader: impl Read) -> Result<Self, serde_yaml::Error> {
+        serde_yaml::from_reader(reader)
+    }
+}
+
+/// A handle to the transactions recorded by a probe, returned by
+/// [`Probe::record_jtag`](crate::Probe::record_jtag).
+///
+/// The probe keeps recording into the handle for as long as it exists.
+#[derive(Debug, Clone, Default)]
+pub struct JtagRecorder(Arc<Mutex<Vec<JtagTransaction>>>);
+
+impl JtagRecorder {
+    /// Returns the transactions recorded so far.
+    pub fn recording(&self) -> JtagRecording {
+        JtagRecording {
+            transactions: self.0.lock().unwrap().clone(),
+        }
+    }
+
+    fn push(&self, access: JtagAccess, capture: Option<Vec<u8>>) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(JtagTransaction { access, capture });
+    }
+}
+
+/// A probe that forwards everything to another probe, and records the JTAG transactions.
+#[derive(Debug)]
+pub(crate) struct RecordingProbe {
+    inner: Box<dyn DebugProbe>,
+    recorder: JtagRecorder,
+    idle_cycles: u8,
+    selected_tap: usize,
+}
+
+impl RecordingProbe {
+    pub(crate) fn new(mut inner: Box<dyn DebugProbe>) -> Self {
+        let (idle_cycles, selected_tap) = inner.try_as_jtag_probe().map_or((0, 0), |probe| {
+            (probe.idle_cycles(), probe.selected_jtag_tap())
+        });
+
+        Self {
+            inner,
+            recorder: JtagRecorder::default(),
+            idle_cycles,
+            selected_tap,
+        }
+    }
+
+    pub(crate) fn recorder(&self) -> JtagRecorder {
+        self.recorder.clone()
+    }
+
+    fn jtag(&mut self) -> Result<&mut dyn JTAGAccess, DebugProbeError> {
+        self.inner
+            .try_as_jtag_probe()
+            .ok_or(DebugProbeError::InterfaceNotAvailable("JTAG"))
+    }
+}
+
+impl DebugProbe for RecordingProbe {
+    fn new_from_selector(
+        _selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        Err(DebugProbeError::NotImplemented(
+            "opening a recording probe directly",
+        ))
+    }
+
+    fn get_name(&self) -> &str {
+        self.inner.get_name()
+    }
+
+    fn speed_khz(&self) -> u32 {
+        self.inner.speed_khz()
+    }
+
+    fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+        self.inner.set_speed(speed_khz)
+    }
+
+    fn supports_adaptive_clocking(&self) -> bool {
+        self.inner.supports_adaptive_clocking()
+    }
+
+    fn set_adaptive_clocking(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        self.inner.set_adaptive_clocking(enabled)
+    }
+
+    fn set_scan_chain(&mut self, scan_chain: Vec<ScanChainElement>) -> Result<(), DebugProbeError> {
+        self.inner.set_scan_chain(scan_chain)
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        self.inner.attach()
+    }
+
+    fn detach(&mut self) -> Result<(), crate::Error> {
+        self.inner.detach()
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        self.inner.target_reset()
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        self.inner.target_reset_assert()
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        self.inner.target_reset_deassert()
+    }
+
+    fn target_trst_assert(&mut self) -> Result<(), DebugProbeError> {
+        self.inner.target_trst_assert()
+    }
+
+    fn target_trst_deassert(&mut self) -> Result<(), DebugProbeError> {
+        self.inner.target_trst_deassert()
+    }
+
+    fn supports_target_power(&self) -> bool {
+        self.inner.supports_target_power()
+    }
+
+    fn set_target_power(&mut self, enabled: bool) -> Result<(), DebugProbeError> {
+        self.inner.set_target_power(enabled)
+    }
+
+    fn select_protocol(&mut self, protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        self.inner.select_protocol(protocol)
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        self.inner.active_protocol()
+    }
+
+    fn has_arm_interface(&self) -> bool {
+        self.inner.has_arm_interface()
+    }
+
+    // ARM probes are accessed through the DAP, so their transactions are not recorded.
+    fn try_get_arm_interface<'probe>(
+        self: Box<Self>,
+    ) -> Result<Box<dyn UninitializedArmProbe + 'probe>, (Box<dyn DebugProbe>, DebugProbeError)>
+    {
+        self.inner.try_get_arm_interface()
+    }
+
+    fn has_riscv_interface(&self) -> bool {
+        self.inner.has_riscv_interface()
+    }
+
+    fn try_get_riscv_interface(
+        self: Box<Self>,
+    ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
+        match RiscvCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn has_xtensa_interface(&self) -> bool {
+        self.inner.has_xtensa_interface()
+    }
+
+    fn try_get_xtensa_interface(
+        self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, DebugProbeError)> {
+        match XtensaCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn get_swo_interface(&self) -> Option<&dyn SwoAccess> {
+        self.inner.get_swo_interface()
+    }
+
+    fn get_swo_interface_mut(&mut self) -> Option<&mut dyn SwoAccess> {
+        self.inner.get_swo_interface_mut()
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn try_as_dap_probe(&mut self) -> Option<&mut dyn DapProbe> {
+        self.inner.try_as_dap_probe()
+    }
+
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+
+    fn get_target_voltage(&mut self) -> Result<Option<f32>, DebugProbeError> {
+        self.inner.get_target_voltage()
+    }
+}
+
+impl JTAGAccess for RecordingProbe {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        let result = self.jtag()?.read_register(address, len);
+        self.recorder.push(
+            JtagAccess::Read { address, len },
+            result.as_ref().ok().cloned(),
+        );
+
+        result
+    }
+
+    fn set_idle_cycles(&mut self, idle_cycles: u8) {
+        self.idle_cycles = idle_cycles;
+        if let Ok(probe) = self.jtag() {
+            probe.set_idle_cycles(idle_cycles);
+        }
+    }
+
+    fn idle_cycles(&self) -> u8 {
+        self.idle_cycles
+    }
+
+    fn set_ir_len(&mut self, len: u32) {
+        if let Ok(probe) = self.jtag() {
+            probe.set_ir_len(len);
+        }
+    }
+
+    fn select_jtag_tap(&mut self, index: usize) -> Result<(), DebugProbeError> {
+        let result = self.jtag()?.select_jtag_tap(index);
+        if result.is_ok() {
+            self.selected_tap = index;
+        }
+        self.recorder.push(
+            JtagAccess::SelectTap { index },
+            result.as_ref().ok().map(|_| Vec::new()),
+        );
+
+        result
+    }
+
+    fn selected_jtag_tap(&self) -> usize {
+        self.selected_tap
+    }
+
+    fn scan_chain(&mut self) -> Result<Vec<super::JtagChainItem>, DebugProbeError> {
+        self.jtag()?.scan_chain()
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        let result = self.jtag()?.write_register(address, data, len);
+        self.recorder.push(
+            JtagAccess::Write {
+                address,
+                data: data.to_vec(),
+                len,
+            },
+            result.as_ref().ok().cloned(),
+        );
+
+        result
+    }
+}
+
+/// A probe that replays a [`JtagRecording`], so that the code driving a debug module can be
+/// tested without hardware.
+///
+/// Every JTAG access must match the next recorded access, and gets its recorded result. Resets
+/// and other probe operations are accepted without being checked.
+#[derive(Debug)]
+pub struct ReplayProbe {
+    transactions: VecDeque<JtagTransaction>,
+    replayed: usize,
+    speed_khz: u32,
+    idle_cycles: u8,
+    selected_tap: usize,
+}
+
+impl ReplayProbe {
+    /// Creates a probe that replays `recording`.
+    pub fn new(recording: JtagRecording) -> Self {
+        Self {
+            transactions: recording.transactions.into(),
+            replayed: 0,
+            speed_khz: 1000,
+            idle_cycles: 0,
+            selected_tap: 0,
+        }
+    }
+
+    /// Returns the number of recorded transactions that have not been replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.transactions.len()
+    }
+
+    fn replay(&mut self, access: JtagAccess) -> Result<Vec<u8>, DebugProbeError> {
+        let index = self.replayed;
+        let Some(recorded) = self.transactions.pop_front() else {
+            return Err(anyhow!("Transaction {index} was not recorded: {access:?}").into());
+        };
+        self.replayed += 1;
+
+        if recorded.access != access {
+            return Err(anyhow!(
+                "Transaction {index} differs from the recording: expected {:?}, got {access:?}",
+                recorded.access
+            )
+            .into());
+        }
+
+        recorded
+            .capture
+            .ok_or_else(|| anyhow!("Transaction {index} failed when it was recorded").into())
+    }
+}
+
+impl DebugProbe for ReplayProbe {
+    fn new_from_selector(
+        _selector: impl Into<DebugProbeSelector>,
+    ) -> Result<Box<Self>, DebugProbeError>
+    where
+        Self: Sized,
+    {
+        Err(DebugProbeError::NotImplemented(
+            "opening a replay probe directly",
+        ))
+    }
+
+    fn get_name(&self) -> &str {
+        "Replayed JTAG recording"
+    }
+
+    fn speed_khz(&self) -> u32 {
+        self.speed_khz
+    }
+
+    fn set_speed(&mut self, speed_khz: u32) -> Result<u32, DebugProbeError> {
+        self.speed_khz = speed_khz;
+        Ok(speed_khz)
+    }
+
+    fn set_scan_chain(
+        &mut self,
+        _scan_chain: Vec<ScanChainElement>,
+    ) -> Result<(), DebugProbeError> {
+        Ok(())
+    }
+
+    fn attach(&mut self) -> Result<(), DebugProbeError> {
+        Ok(())
+    }
+
+    fn detach(&mut self) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    fn target_reset(&mut self) -> Result<(), DebugProbeError> {
+        Ok(())
+    }
+
+    fn target_reset_assert(&mut self) -> Result<(), DebugProbeError> {
+        Ok(())
+    }
+
+    fn target_reset_deassert(&mut self) -> Result<(), DebugProbeError> {
+        Ok(())
+    }
+
+    fn select_protocol(&mut self, protocol: WireProtocol) -> Result<(), DebugProbeError> {
+        match protocol {
+            WireProtocol::Jtag => Ok(()),
+            other => Err(DebugProbeError::UnsupportedProtocol(other)),
+        }
+    }
+
+    fn active_protocol(&self) -> Option<WireProtocol> {
+        Some(WireProtocol::Jtag)
+    }
+
+    fn has_riscv_interface(&self) -> bool {
+        true
+    }
+
+    fn try_get_riscv_interface(
+        self: Box<Self>,
+    ) -> Result<RiscvCommunicationInterface, (Box<dyn DebugProbe>, RiscvError)> {
+        match RiscvCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn has_xtensa_interface(&self) -> bool {
+        true
+    }
+
+    fn try_get_xtensa_interface(
+        self: Box<Self>,
+    ) -> Result<XtensaCommunicationInterface, (Box<dyn DebugProbe>, DebugProbeError)> {
+        match XtensaCommunicationInterface::new(self) {
+            Ok(interface) => Ok(interface),
+            Err((probe, err)) => Err((probe.into_probe(), err)),
+        }
+    }
+
+    fn into_probe(self: Box<Self>) -> Box<dyn DebugProbe> {
+        self
+    }
+
+    fn try_as_jtag_probe(&mut self) -> Option<&mut dyn JTAGAccess> {
+        Some(self)
+    }
+}
+
+impl JTAGAccess for ReplayProbe {
+    fn read_register(&mut self, address: u32, len: u32) -> Result<Vec<u8>, DebugProbeError> {
+        self.replay(JtagAccess::Read { address, len })
+    }
+
+    fn set_idle_cycles(&mut self, idle_cycles: u8) {
+        self.idle_cycles = idle_cycles;
+    }
+
+    fn idle_cycles(&self) -> u8 {
+        self.idle_cycles
+    }
+
+    fn set_ir_len(&mut self, _len: u32) {}
+
+    fn select_jtag_tap(&mut self, index: usize) -> Result<(), DebugProbeError> {
+        self.replay(JtagAccess::SelectTap { index })?;
+        self.selected_tap = index;
+        Ok(())
+    }
+
+    fn selected_jtag_tap(&self) -> usize {
+        self.selected_tap
+    }
+
+    fn write_register(
+        &mut self,
+        address: u32,
+        data: &[u8],
+        len: u32,
+    ) -> Result<Vec<u8>, DebugProbeError> {
+        self.replay(JtagAccess::Write {
+            address,
+            data: data.to_vec(),
+            len,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording() -> JtagRecording {
+        JtagRecording {
+            transactions: vec![
+                JtagTransaction {
+                    access: JtagAccess::SelectTap { index: 1 },
+                    capture: Some(vec![]),
+                },
+                JtagTransaction {
+                    access: JtagAccess::Write {
+                        address: 0x1c,
+                        data: vec![0x81],
+                        len: 8,
+                    },
+                    capture: Some(vec![0x00]),
+                },
+                JtagTransaction {
+                    access: JtagAccess::Read {
+                        address: 0x1d,
+                        len: 32,
+                    },
+                    capture: None,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn replay_returns_recorded_captures() {
+        let mut probe = ReplayProbe::new(recording());
+
+        probe.select_jtag_tap(1).unwrap();
+        assert_eq!(probe.selected_jtag_tap(), 1);
+        assert_eq!(probe.write_register(0x1c, &[0x81], 8).unwrap(), [0x00]);
+        assert!(probe.read_register(0x1d, 32).is_err());
+        assert_eq!(probe.remaining(), 0);
+        assert!(probe.read_register(0x1d, 32).is_err());
+    }
+
+    #[test]
+    fn replay_rejects_diverging_accesses() {
+        let mut probe = ReplayProbe::new(recording());
+
+        assert!(probe.select_jtag_tap(0).is_err());
+    }
+
+    #[test]
+    fn recording_captures_transactions() {
+        let replay = ReplayProbe::new(recording());
+        let mut probe = RecordingProbe::new(Box::new(replay));
+        let recorder = probe.recorder();
+
+        probe.select_jtag_tap(1).unwrap();
+        probe.write_register(0x1c, &[0x81], 8).unwrap();
+        probe.read_register(0x1d, 32).unwrap_err();
+
+        assert_eq!(recorder.recording(), recording());
+    }
+
+    #[test]
+    fn recording_round_trips_through_yaml() {
+        let mut file = Vec::new();
+        recording().save(&mut file).unwrap();
+
+        assert_eq!(JtagRecording::load(&file[..]).unwrap(), recording());
+    }
+}
@@ -0,0 +1,136 @@
+//! Retrying of operations that fail because of transient errors on the debug connection.
+
+use std::time::Duration;
+
+/// A class of transient errors, for which retries are configured separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorClass {
+    /// An access to a debug register did not complete in time, e.g. because the core was busy.
+    Busy,
+    /// An access to a debug register failed or returned inconsistent data, e.g. because of a
+    /// glitch on the JTAG lines.
+    Transfer,
+    /// The communication with the probe failed.
+    Probe,
+}
+
+/// How an operation that failed with a class of errors is retried.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retry {
+    /// How often the operation is repeated after it failed.
+    pub attempts: u32,
+    /// The delay before the first repetition, which is doubled for every repetition that follows.
+    pub backoff: Duration,
+}
+
+impl Retry {
+    /// Never repeat the operation.
+    pub const NEVER: Self = Self {
+        attempts: 0,
+        backoff: Duration::ZERO,
+    };
+}
+
+/// How operations that fail because of transient errors are retried, before the error is
+/// reported.
+///
+/// By default, failed register accesses are retried a few times, and probe communication errors
+/// are reported right away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    busy: Retry,
+    transfer: Retry,
+    probe: Retry,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        let register_access = Retry {
+            attempts: 3,
+            backoff: Duration::from_millis(1),
+        };
+
+        Self {
+            busy: register_access,
+            transfer: register_access,
+            probe: Retry::NEVER,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that reports every error right away.
+    pub fn never() -> Self {
+        Self {
+            busy: Retry::NEVER,
+            transfer: Retry::NEVER,
+            probe: Retry::NEVER,
+        }
+    }
+
+    /// Configures how operations that failed with `class` errors are retried.
+    #[must_use]
+    pub fn with_retry(mut self, class: ErrorClass, retry: Retry) -> Self {
+        *self.retry_mut(class) = retry;
+        self
+    }
+
+    /// Returns how operations that failed with `class` errors are retried.
+    pub fn retry(&self, class: ErrorClass) -> Retry {
+        match class {
+            ErrorClass::Busy => self.busy,
+            ErrorClass::Transfer => self.transfer,
+            ErrorClass::Probe => self.probe,
+        }
+    }
+
+    fn retry_mut(&mut self, class: ErrorClass) -> &mut Retry {
+        match class {
+            ErrorClass::Busy => &mut self.busy,
+            ErrorClass::Transfer => &mut self.transfer,
+            ErrorClass::Probe => &mut self.probe,
+        }
+    }
+
+    /// Returns the delay before repeating an operation that failed with a `class` error, after it
+    /// has already been repeated `retries` times, or `None` if the error is to be reported.
+    pub fn backoff(&self, class: ErrorClass, retries: u32) -> Option<Duration> {
+        let retry = self.retry(class);
+        if retries >= retry.attempts {
+            return None;
+        }
+
+        Some(retry.backoff.saturating_mul(1 << retries.min(16)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_until_attempts_are_exhausted() {
+        let policy = RetryPolicy::never().with_retry(
+            ErrorClass::Transfer,
+            Retry {
+                attempts: 3,
+                backoff: Duration::from_millis(2),
+            },
+        );
+
+        let delays: Vec<_> = (0..4)
+            .map(|retries| policy.backoff(ErrorClass::Transfer, retries))
+            .collect();
+        assert_eq!(
+            delays,
+            [
+                Some(Duration::from_millis(2)),
+                Some(Duration::from_millis(4)),
+                Some(Duration::from_millis(8)),
+                None
+            ]
+        );
+
+        assert_eq!(policy.backoff(ErrorClass::Busy, 0), None);
+    }
+}
@@ -0,0 +1,221 @@
+//! Instrumentation of debug transactions, to diagnose slow or failing operations.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Timing statistics of a single kind of operation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OperationStats {
+    /// How often the operation was performed.
+    pub count: u64,
+    /// How often the operation failed.
+    pub failures: u64,
+    /// The time spent in the operation, over all executions.
+    pub total_time: Duration,
+    /// The time taken by the slowest execution of the operation.
+    pub max_time: Duration,
+    /// The number of JTAG transfers done by the operation, over all executions.
+    pub jtag_transfers: u64,
+}
+
+/// A single operation in the transaction trace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// When the operation started, relative to when recording was enabled.
+    pub start: Duration,
+    /// The name of the operation.
+    pub operation: &'static str,
+    /// How long the operation took.
+    pub duration: Duration,
+    /// The number of JTAG transfers done by the operation.
+    pub jtag_transfers: u64,
+    /// Whether the operation succeeded.
+    pub success: bool,
+}
+
+/// The point at which an operation started, used to measure it once it completes.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct OperationStart {
+    at: Instant,
+    jtag_transfers: u64,
+}
+
+/// Records per-operation timing and JTAG transfer counts of a debug interface.
+///
+/// Recording is disabled by default. Once enabled with [`Telemetry::enable`], the statistics of
+/// every operation are accumulated, and the most recent operations are kept in a trace that can
+/// be attached to bug reports with [`Telemetry::write_trace`].
+#[derive(Debug)]
+pub struct Telemetry {
+    enabled: bool,
+    started: Instant,
+    operations: BTreeMap<&'static str, OperationStats>,
+    jtag_transfers: u64,
+    trace: VecDeque<TraceEntry>,
+    trace_capacity: usize,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            started: Instant::now(),
+            operations: BTreeMap::new(),
+            jtag_transfers: 0,
+            trace: VecDeque::new(),
+            trace_capacity: 0,
+        }
+    }
+}
+
+impl Telemetry {
+    /// Starts recording, discarding anything recorded before.
+    ///
+    /// The trace keeps the last `trace_capacity` operations. Pass 0 to only collect statistics.
+    pub fn enable(&mut self, trace_capacity: usize) {
+        *self = Self {
+            enabled: true,
+            trace_capacity,
+            ..Self::default()
+        };
+    }
+
+    /// Stops recording. The statistics and the trace recorded so far are kept.
+    pub fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    /// Returns whether recording is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the statistics of every operation performed since recording was enabled.
+    pub fn operations(&self) -> impl Iterator<Item = (&'static str, &OperationStats)> {
+        self.operations.iter().map(|(name, stats)| (*name, stats))
+    }
+
+    /// Returns the total number of JTAG transfers since recording was enabled.
+    pub fn jtag_transfers(&self) -> u64 {
+        self.jtag_transfers
+    }
+
+    /// Returns the most recent operations, oldest first.
+    pub fn trace(&self) -> impl Iterator<Item = &TraceEntry> {
+        self.trace.iter()
+    }
+
+    /// Writes the statistics and the trace in a human readable form.
+    pub fn write_trace(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writeln!(writer, "JTAG transfers: {}", self.jtag_transfers)?;
+
+        writeln!(writer, "Operations:")?;
+        for (name, stats) in self.operations() {
+            writeln!(
+                writer,
+                "  {name}: {} calls, {} failed, {:?} total, {:?} max, {} JTAG transfers",
+                stats.count, stats.failures, stats.total_time, stats.max_time, stats.jtag_transfers
+            )?;
+        }
+
+        writeln!(writer, "Trace:")?;
+        for entry in self.trace() {
+            writeln!(
+                writer,
+                "  {:>12.6}s {} {:?} {} JTAG transfers{}",
+                entry.start.as_secs_f64(),
+                entry.operation,
+                entry.duration,
+                entry.jtag_transfers,
+                if entry.success { "" } else { " (failed)" }
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Counts JTAG transfers done by the probe.
+    pub(crate) fn count_jtag_transfers(&mut self, transfers: usize) {
+        if self.enabled {
+            self.jtag_transfers += transfers as u64;
+        }
+    }
+
+    /// Marks the start of an operation, to be passed to [`Telemetry::record`] once it completes.
+    pub(crate) fn start(&self) -> OperationStart {
+        OperationStart {
+            at: Instant::now(),
+            jtag_transfers: self.jtag_transfers,
+        }
+    }
+
+    /// Records an operation that began at `start`, and just completed.
+    pub(crate) fn record(&mut self, operation: &'static str, start: OperationStart, success: bool) {
+        if !self.enabled {
+            return;
+        }
+
+        let duration = start.at.elapsed();
+        let jtag_transfers = self.jtag_transfers - start.jtag_transfers;
+
+        let stats = self.operations.entry(operation).or_default();
+        stats.count += 1;
+        stats.failures += u64::from(!success);
+        stats.total_time += duration;
+        stats.max_time = stats.max_time.max(duration);
+        stats.jtag_transfers += jtag_transfers;
+
+        if self.trace_capacity == 0 {
+            return;
+        }
+        if self.trace.len() == self.trace_capacity {
+            self.trace.pop_front();
+        }
+        self.trace.push_back(TraceEntry {
+            start: start.at.saturating_duration_since(self.started),
+            operation,
+            duration,
+            jtag_transfers,
+            success,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn disabled_telemetry_records_nothing() {
+        let mut telemetry = Telemetry::default();
+
+        let start = telemetry.start();
+        telemetry.count_jtag_transfers(3);
+        telemetry.record("halt", start, true);
+
+        assert_eq!(telemetry.jtag_transfers(), 0);
+        assert_eq!(telemetry.operations().count(), 0);
+    }
+
+    #[test]
+    fn trace_keeps_most_recent_operations() {
+        let mut telemetry = Telemetry::default();
+        telemetry.enable(2);
+
+        for operation in ["halt", "read_memory", "resume"] {
+            let start = telemetry.start();
+            telemetry.count_jtag_transfers(2);
+            telemetry.record(operation, start, operation != "resume");
+        }
+
+        let trace = telemetry.trace().map(|e| e.operation).collect::<Vec<_>>();
+        assert_eq!(trace, ["read_memory", "resume"]);
+
+        let stats = telemetry.operations().collect::<BTreeMap<_, _>>();
+        assert_eq!(stats["halt"].count, 1);
+        assert_eq!(stats["halt"].jtag_transfers, 2);
+        assert_eq!(stats["resume"].failures, 1);
+        assert_eq!(telemetry.jtag_transfers(), 6);
+    }
+}
@@ -114,6 +114,10 @@ impl Rtt {
     // Minimum size of the ControlBlock struct in target memory in bytes with empty arrays
     const MIN_SIZE: usize = Self::O_CHANNEL_ARRAYS;
 
+    // Memory is scanned in chunks of this size, as some cores have to be halted to access memory.
+    // Reading in chunks lets them make progress between the reads of a large region.
+    const SCAN_CHUNK_SIZE: usize = 16 * 1024;
+
     // Offsets of fields in target memory in bytes
     const O_ID: usize = 0;
     const O_MAX_UP_CHANNELS: usize = 16;
@@ -287,8 +291,16 @@ impl Rtt {
                 };
 
                 let mut mem = vec![0; range_len_usize];
-                {
-                    core.read(range.start, mem.as_mut()).ok()?;
+                for (index, chunk) in mem.chunks_mut(Self::SCAN_CHUNK_SIZE).enumerate() {
+                    let address = range.start + (index * Self::SCAN_CHUNK_SIZE) as u64;
+                    if let Err(error) = core.read(address, chunk) {
+                        tracing::warn!(
+                            "Failed to read memory at {:#010x} while scanning for RTT: {}",
+                            address,
+                            error
+                        );
+                        return None;
+                    }
                 }
 
                 match kmp::kmp_find(&Self::RTT_ID, mem.as_slice()) {
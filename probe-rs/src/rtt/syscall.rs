@@ -1,19 +1,36 @@
-use crate::SemihostingCommand;
+use crate::{CoreInterface, Error, SemihostingCommand};
 
-/// Decode a semihosting syscall. Only SYS_EXIT is supported at the moment
-pub fn decode_semihosting_syscall(operation: u32, parameter: u32) -> SemihostingCommand {
+/// Decode a semihosting syscall. Only SYS_EXIT and SYS_EXIT_EXTENDED are supported at the moment
+pub fn decode_semihosting_syscall(
+    core: &mut dyn CoreInterface,
+    operation: u32,
+    parameter: u32,
+) -> Result<SemihostingCommand, Error> {
     // This is defined by the ARM Semihosting Specification:
     // <https://github.com/ARM-software/abi-aa/blob/main/semihosting/semihosting.rst#semihosting-operations>
     const SYS_EXIT: u32 = 0x18;
+    const SYS_EXIT_EXTENDED: u32 = 0x20;
     const SYS_EXIT_ADP_STOPPED_APPLICATIONEXIT: u32 = 0x20026;
-    match (operation, parameter) {
+    let command = match (operation, parameter) {
         (SYS_EXIT, SYS_EXIT_ADP_STOPPED_APPLICATIONEXIT) => SemihostingCommand::ExitSuccess,
         (SYS_EXIT, code) => SemihostingCommand::ExitError { code: code as u64 },
+        (SYS_EXIT_EXTENDED, block) => {
+            // The parameter points to the reason and the exit code of the application.
+            let mut fields = [0; 2];
+            core.read_32(block as u64, &mut fields)?;
+            match fields {
+                [SYS_EXIT_ADP_STOPPED_APPLICATIONEXIT, 0] => SemihostingCommand::ExitSuccess,
+                [SYS_EXIT_ADP_STOPPED_APPLICATIONEXIT, code] | [code, _] => {
+                    SemihostingCommand::ExitError { code: code as u64 }
+                }
+            }
+        }
         _ => {
             tracing::warn!(
                 "Unknown semihosting operation={operation:04x} parameter={parameter:04x}"
             );
             SemihostingCommand::Unknown { operation }
         }
-    }
+    };
+    Ok(command)
 }
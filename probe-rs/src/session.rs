@@ -1,13 +1,26 @@
 use crate::architecture::arm::component::get_arm_components;
+use crate::architecture::arm::core::armv8a;
 use crate::architecture::arm::sequences::{ArmDebugSequence, DefaultArmSequence};
 use crate::architecture::arm::{ArmError, DpAddress};
 use crate::architecture::riscv::communication_interface::RiscvError;
 use crate::architecture::xtensa::communication_interface::{
-    XtensaCommunicationInterface, XtensaError,
+    CachedRegion, XtensaCommunicationInterface, XtensaError,
 };
-use crate::config::{ChipInfo, CoreExt, RegistryError, Target, TargetSelector};
-use crate::core::{Architecture, CombinedCoreState};
+use crate::architecture::xtensa::efuse::Efuses;
+use crate::architecture::xtensa::sequences::XtensaDebugSequence;
+use crate::config::{ChipInfo, CoreExt, JtagChipInfo, RegistryError, Target, TargetSelector};
+use crate::core::self_test::MEMORY_CHECK_SIZE;
+use crate::core::{
+    Architecture, CombinedCoreState, CoreEvent, CoreEventPoller, CoreEvents, CoreStatus,
+    DataTraceRecord, HaltInspection, HaltInspector, HaltReason, HaltingOperation, MemoryHook,
+    MemoryHooks, SelfTestReport, StackGuard, TraceWatchpoint, TraceWatchpointId, TraceWatchpoints,
+    TriageReport, Watch, WatchEvent, WatchId, WatchPoller, Watches,
+};
+use crate::debug::DebugInfo;
+use crate::flashing::FlashLoader;
+use crate::memory::{ScratchLoan, ScratchPool};
 use crate::probe::fake_probe::FakeProbe;
+use crate::probe::telemetry::Telemetry;
 use crate::{
     architecture::{
         arm::{
@@ -18,8 +31,13 @@ use crate::{
     },
     config::DebugSequence,
 };
-use crate::{AttachMethod, Core, CoreType, Error, Lister, Probe};
+use crate::{AttachMethod, Core, CoreInformation, CoreType, Error, Lister, MemoryInterface, Probe};
+use anyhow::anyhow;
+use probe_rs_target::{CoreAccessOptions, MemoryRegion};
 use std::ops::DerefMut;
+use std::sync::mpsc::Receiver;
+use std::sync::Mutex;
+use std::time::Instant;
 use std::{fmt, sync::Arc, time::Duration};
 
 /// The `Session` struct represents an active debug session.
@@ -46,6 +64,108 @@ pub struct Session {
     interface: ArchitectureInterface,
     cores: Vec<CombinedCoreState>,
     configured_trace_sink: Option<TraceSink>,
+    core_events: CoreEvents,
+    halt_inspectors: Vec<Arc<dyn HaltInspector>>,
+    watches: Watches,
+    trace_watchpoints: TraceWatchpoints,
+    memory_hooks: MemoryHooks,
+    scratch: ScratchPool,
+    detach_policy: DetachPolicy,
+    verify_writes: bool,
+    /// Whether cores are halted around accesses which need it, see
+    /// [`Session::set_implicit_halts`].
+    implicit_halts: bool,
+    /// Whether the session was attached with [`AttachMethod::NonIntrusive`], and never halts
+    /// cores implicitly.
+    non_intrusive: bool,
+    /// Whether the parts of attaching that need a halted core are still to be done by
+    /// [`Session::complete_attach`].
+    deferred_attach: bool,
+    /// The cores disabled with [`Session::set_core_enabled`].
+    disabled_cores: Vec<usize>,
+    permissions: Permissions,
+}
+
+/// What happens to the cores when a [`Session`] is dropped.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DetachPolicy {
+    /// Resume halted cores, and let them run without the debugger.
+    #[default]
+    Resume,
+    /// Leave halted cores halted, e.g. to attach to them again later.
+    KeepHalted,
+}
+
+/// Options of attaching a [`Session`] which select how the debugger behaves, unlike the
+/// [`Permissions`], which select what it may do.
+///
+/// ```
+/// use probe_rs::AttachOptions;
+///
+/// let options = AttachOptions::new().keep_watchdogs();
+/// ```
+#[non_exhaustive]
+#[derive(Debug, Clone, Default)]
+pub struct AttachOptions {
+    /// When set to true, the watchdogs of the chip are left running while its cores are halted
+    keep_watchdogs: bool,
+    /// What to do when the chip is asleep while attaching
+    wake_policy: WakePolicy,
+}
+
+impl AttachOptions {
+    /// Constructs the default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the session from stopping the watchdogs of the chip while its cores are halted.
+    ///
+    /// Without this, the debugger stops the watchdogs of chips that would otherwise reset
+    /// themselves while halted, and restores them when the cores resume. Sessions without the
+    /// permission to write memory never stop the watchdogs.
+    #[must_use]
+    pub fn keep_watchdogs(self) -> Self {
+        Self {
+            keep_watchdogs: true,
+            ..self
+        }
+    }
+
+    /// Select what the session does when the chip is asleep while attaching.
+    ///
+    /// By default, attaching to a sleeping chip fails. Waking the chip up with
+    /// [`WakePolicy::Force`] needs the permission to reset it.
+    #[must_use]
+    pub fn wake_on_attach(self, wake_policy: WakePolicy) -> Self {
+        Self {
+            wake_policy,
+            ..self
+        }
+    }
+
+    pub(crate) fn stop_watchdogs(&self) -> bool {
+        !self.keep_watchdogs
+    }
+
+    pub(crate) fn wake_policy(&self) -> WakePolicy {
+        self.wake_policy
+    }
+}
+
+/// What a [`Session`] does when the chip is asleep while attaching, with its debug module
+/// powered down.
+///
+/// This is only detected on Xtensa chips.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WakePolicy {
+    /// Fail to attach.
+    #[default]
+    Fail,
+    /// Wait up to the given time for the chip to wake up on its own.
+    Wait(Duration),
+    /// Wake the chip up with a power-on reset, which restarts the firmware.
+    Force,
 }
 
 pub(crate) enum ArchitectureInterface {
@@ -99,7 +219,12 @@ impl Session {
         target: TargetSelector,
         attach_method: AttachMethod,
         permissions: Permissions,
+        options: AttachOptions,
     ) -> Result<Self, Error> {
+        if attach_method == AttachMethod::UnderReset {
+            permissions.reset()?;
+        }
+
         let (probe, target) = get_target_from_selector(target, attach_method, probe)?;
 
         let cores = target
@@ -124,15 +249,50 @@ impl Session {
                 Self::attach_riscv(probe, target, attach_method, permissions, cores)?
             }
             Architecture::Xtensa => {
-                Self::attach_xtensa(probe, target, attach_method, permissions, cores)?
+                Self::attach_xtensa(probe, target, attach_method, permissions, options, cores)?
             }
         };
 
-        session.clear_all_hw_breakpoints()?;
+        if attach_method == AttachMethod::NonIntrusive {
+            // Clearing the breakpoints halts Xtensa cores, so it is deferred as well.
+            session.non_intrusive = true;
+            session.deferred_attach = true;
+        } else if session.permissions.halt().is_ok() {
+            // Without permission to halt, no breakpoints are set, so those of others are kept.
+            session.clear_all_hw_breakpoints()?;
+        }
 
         Ok(session)
     }
 
+    /// Creates a session on the attached `interface`, with every other option at its default.
+    fn from_parts(
+        target: Target,
+        interface: ArchitectureInterface,
+        cores: Vec<CombinedCoreState>,
+        permissions: Permissions,
+    ) -> Self {
+        Session {
+            target,
+            interface,
+            cores,
+            configured_trace_sink: None,
+            core_events: CoreEvents::default(),
+            halt_inspectors: Vec::new(),
+            watches: Watches::default(),
+            trace_watchpoints: TraceWatchpoints::default(),
+            memory_hooks: MemoryHooks::default(),
+            scratch: ScratchPool::default(),
+            detach_policy: DetachPolicy::default(),
+            verify_writes: false,
+            implicit_halts: false,
+            non_intrusive: false,
+            deferred_attach: false,
+            disabled_cores: Vec::new(),
+            permissions,
+        }
+    }
+
     fn attach_arm(
         mut probe: Probe,
         target: Target,
@@ -223,12 +383,12 @@ impl Session {
                 drop(reset_hardware_deassert);
             }
 
-            let mut session = Session {
+            let mut session = Session::from_parts(
                 target,
-                interface: ArchitectureInterface::Arm(interface),
+                ArchitectureInterface::Arm(interface),
                 cores,
-                configured_trace_sink: None,
-            };
+                permissions,
+            );
 
             {
                 // Wait for the core to be halted. The core should be
@@ -246,20 +406,20 @@ impl Session {
 
             Ok(session)
         } else {
-            Ok(Session {
+            Ok(Session::from_parts(
                 target,
-                interface: ArchitectureInterface::Arm(interface),
+                ArchitectureInterface::Arm(interface),
                 cores,
-                configured_trace_sink: None,
-            })
+                permissions,
+            ))
         }
     }
 
     fn attach_riscv(
         mut probe: Probe,
         target: Target,
-        _attach_method: AttachMethod,
-        _permissions: Permissions,
+        attach_method: AttachMethod,
+        permissions: Permissions,
         cores: Vec<CombinedCoreState>,
     ) -> Result<Self, Error> {
         // TODO: Handle attach under reset
@@ -279,14 +439,18 @@ impl Session {
             .try_into_riscv_interface()
             .map_err(|(_probe, err)| err)?;
 
-        let mut session = Session {
+        let mut session = Session::from_parts(
             target,
-            interface: ArchitectureInterface::Riscv(Box::new(interface)),
+            ArchitectureInterface::Riscv(Box::new(interface)),
             cores,
-            configured_trace_sink: None,
-        };
+            permissions,
+        );
 
-        {
+        if attach_method == AttachMethod::NonIntrusive {
+            return Ok(session);
+        }
+
+        if session.permissions.halt().is_ok() {
             // Todo: Add multicore support. How to deal with any cores that are not active and won't respond?
             let mut core = session.core(0)?;
 
@@ -301,8 +465,9 @@ impl Session {
     fn attach_xtensa(
         mut probe: Probe,
         target: Target,
-        _attach_method: AttachMethod,
-        _permissions: Permissions,
+        attach_method: AttachMethod,
+        permissions: Permissions,
+        options: AttachOptions,
         cores: Vec<CombinedCoreState>,
     ) -> Result<Self, Error> {
         let sequence_handle = match &target.debug_sequence {
@@ -310,28 +475,125 @@ impl Session {
             _ => unreachable!("Mismatch between architecture and sequence type!"),
         };
 
+        if AttachMethod::UnderReset == attach_method {
+            let _span = tracing::debug_span!("Asserting hardware reset").entered();
+            sequence_handle.reset_hardware_assert(&mut probe)?;
+        }
+
         if let Some(scan_chain) = target.scan_chain.clone() {
             probe.set_scan_chain(scan_chain)?;
         }
 
         probe.attach_to_unspecified()?;
 
-        let interface = probe
+        let core_taps = target
+            .cores
+            .iter()
+            .enumerate()
+            .map(|(index, core)| match &core.core_access_options {
+                CoreAccessOptions::Xtensa(options) => options.jtag_tap.unwrap_or(index),
+                _ => index,
+            })
+            .collect::<Vec<_>>();
+
+        // Other TAPs may precede the cores in the scan chain. Fail early if the probe can't
+        // select the TAP of every core, and point it at the first core before the debug module
+        // is initialized.
+        if core_taps.iter().any(|&tap| tap != 0) {
+            for (core, &tap) in core_taps.iter().enumerate().rev() {
+                match probe.select_jtag_tap(tap) {
+                    Err(crate::DebugProbeError::NotImplemented(_)) => {
+                        return Err(XtensaError::JtagTapNotSelectable { core, tap }.into());
+                    }
+                    result => result?,
+                }
+            }
+        }
+
+        let mut interface = probe
             .try_into_xtensa_interface()
             .map_err(|(_probe, err)| err)?;
 
-        let mut session = Session {
+        if interface.is_sleeping()? {
+            match options.wake_policy() {
+                WakePolicy::Fail => return Err(XtensaError::Sleeping.into()),
+                WakePolicy::Wait(timeout) => {
+                    tracing::info!("The chip is asleep, waiting for it to wake up");
+                    interface.wait_for_wake_up(timeout)?;
+                }
+                WakePolicy::Force => {
+                    permissions.reset()?;
+                    tracing::info!("The chip is asleep, waking it up with a power-on reset");
+                    sequence_handle
+                        .power_on_reset_and_halt(&mut interface, Duration::from_millis(500))?;
+                }
+            }
+        }
+
+        // Stopping the watchdogs writes to the memory of the chip.
+        interface
+            .set_stop_watchdogs(options.stop_watchdogs() && permissions.write_memory().is_ok());
+        interface.set_access_widths(
+            target
+                .memory_map
+                .iter()
+                .map(|region| (region.address_range(), region.access_width()))
+                .collect(),
+        );
+        if let Some(CoreAccessOptions::Xtensa(options)) =
+            target.cores.first().map(|core| &core.core_access_options)
+        {
+            interface.set_address_translations(options.address_translations.clone());
+            interface.set_cached_regions(
+                options
+                    .cached_regions
+                    .iter()
+                    .map(|region| CachedRegion {
+                        range: region.range.clone(),
+                        line_size: region.line_size,
+                        data_writeback: region.data_cache,
+                        instruction_invalidate: region.instruction_cache,
+                    })
+                    .collect(),
+            );
+            interface.set_windowed_registers(!options.nx && !options.no_windowed_registers);
+            interface.set_fpu_present(options.fpu);
+            interface.set_break_units(
+                options.ibreak_count.unwrap_or(2),
+                options.dbreak_count.unwrap_or(2),
+            )?;
+            if let Some(level) = options.debug_level {
+                interface.set_debug_level(level)?;
+            }
+        }
+        interface.set_core_taps(core_taps);
+
+        if AttachMethod::UnderReset == attach_method {
+            // Halt the core before it executes the first instruction, so that breakpoints can
+            // be set before any code runs.
+            let _span = tracing::debug_span!("reset_hardware_deassert").entered();
+            sequence_handle.reset_hardware_deassert(&mut interface, Duration::from_millis(500))?;
+        }
+
+        let mut session = Session::from_parts(
             target,
-            interface: ArchitectureInterface::Xtensa(Box::new(interface)),
+            ArchitectureInterface::Xtensa(Box::new(interface)),
             cores,
-            configured_trace_sink: None,
-        };
+            permissions,
+        );
 
-        {
+        if attach_method == AttachMethod::NonIntrusive {
+            return Ok(session);
+        }
+
+        if session.permissions.halt().is_ok() {
             // Todo: Add multicore support. How to deal with any cores that are not active and won't respond?
             let mut core = session.core(0)?;
 
             core.halt(Duration::from_millis(100))?;
+
+            // Detect the FPU while the core is halted, so that the register list is complete.
+            core.fpu_support()?;
         }
 
         sequence_handle.on_connect(session.get_xtensa_interface()?)?;
@@ -365,6 +627,33 @@ impl Session {
         self.cores.iter().map(|t| (t.id(), t.core_type())).collect()
     }
 
+    /// Selects whether the core `core_index` is used by this session. All cores are enabled by
+    /// default.
+    ///
+    /// Disable cores which are powered down or held in reset by the firmware. Operations on all
+    /// cores skip disabled cores, and [`Session::core`] fails for them with
+    /// [`Error::CoreDisabled`].
+    pub fn set_core_enabled(&mut self, core_index: usize, enabled: bool) -> Result<(), Error> {
+        if core_index >= self.cores.len() {
+            return Err(Error::CoreNotFound(core_index));
+        }
+
+        self.disabled_cores.retain(|&index| index != core_index);
+        if !enabled {
+            self.disabled_cores.push(core_index);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the indices of the cores which are not disabled with
+    /// [`Session::set_core_enabled`].
+    pub fn enabled_cores(&self) -> Vec<usize> {
+        (0..self.cores.len())
+            .filter(|core_index| !self.disabled_cores.contains(core_index))
+            .collect()
+    }
+
     /// Attaches to the core with the given number.
     ///
     /// ## Usage
@@ -382,11 +671,506 @@ impl Session {
     ///
     #[tracing::instrument(skip(self), name = "attach_to_core")]
     pub fn core(&mut self, core_index: usize) -> Result<Core<'_>, Error> {
+        if self.disabled_cores.contains(&core_index) {
+            return Err(Error::CoreDisabled(core_index));
+        }
+
         let combined_state = self
             .cores
             .get_mut(core_index)
             .ok_or(Error::CoreNotFound(core_index))?;
-        self.interface.attach(combined_state)
+        let core = self.interface.attach(combined_state)?;
+        Ok(core
+            .with_events(&mut self.core_events)
+            .with_memory_map(&self.target.memory_map)
+            .with_write_verification(self.verify_writes)
+            .with_permissions(&self.permissions)
+            .with_memory_hooks(&self.memory_hooks)
+            .with_implicit_halts(self.implicit_halts && !self.non_intrusive))
+    }
+
+    /// Does the parts of attaching which were deferred by
+    /// [`Probe::attach_non_intrusive`](crate::Probe::attach_non_intrusive).
+    ///
+    /// This halts the first core, runs the connect sequence of the target, e.g. to disable its
+    /// watchdogs, and clears the hardware breakpoints. Cores are still not halted implicitly
+    /// afterwards. Does nothing if there is nothing left to do.
+    pub fn complete_attach(&mut self) -> Result<(), Error> {
+        if !self.deferred_attach {
+            return Ok(());
+        }
+        self.permissions.halt()?;
+
+        let architecture = self.target.architecture();
+        {
+            let mut core = self.core(0)?;
+            core.halt(Duration::from_millis(100))?;
+
+            if architecture == Architecture::Xtensa {
+                // Detect the FPU while the core is halted, so that the register list is complete.
+                core.fpu_support()?;
+            }
+        }
+
+        match self.target.debug_sequence.clone() {
+            DebugSequence::Arm(_) => {}
+            DebugSequence::Riscv(sequence) => sequence.on_connect(self.get_riscv_interface()?)?,
+            DebugSequence::Xtensa(sequence) => sequence.on_connect(self.get_xtensa_interface()?)?,
+        }
+
+        self.clear_all_hw_breakpoints()?;
+        self.deferred_attach = false;
+
+        Ok(())
+    }
+
+    /// Returns the operations which are unavailable on the core `core_index` until it is halted.
+    ///
+    /// Core registers can't be accessed on any running core. Unless cores are halted implicitly,
+    /// see [`Session::set_implicit_halts`], cores which can only access memory while halted, like
+    /// Xtensa cores, can't access memory or breakpoints while running either.
+    pub fn unavailable_until_halted(
+        &mut self,
+        core_index: usize,
+    ) -> Result<Vec<HaltingOperation>, Error> {
+        let mut unavailable = Vec::new();
+        if self.deferred_attach {
+            unavailable.push(HaltingOperation::DeferredAttach);
+        }
+
+        let implicit_halts =
+            self.implicit_halts && !self.non_intrusive && self.permissions.halt().is_ok();
+        let mut core = self.core(core_index)?;
+        if !core.core_halted()? {
+            unavailable.push(HaltingOperation::CoreRegisters);
+
+            if !implicit_halts && core.requires_halted_memory_access() {
+                unavailable.push(HaltingOperation::Memory);
+                unavailable.push(HaltingOperation::Breakpoints);
+            }
+        }
+
+        Ok(unavailable)
+    }
+
+    /// Halts all cores, as close to simultaneously as the architecture allows, so that
+    /// multi-core firmware stops in a consistent state.
+    ///
+    /// Xtensa cores halt each other through the break network of their debug modules, and
+    /// ARMv8-A cores through the cross trigger matrix. Other cores are halted one after the
+    /// other. Returns the information of every enabled core, in the order of
+    /// [`Session::enabled_cores`].
+    pub fn halt_all_cores(&mut self, timeout: Duration) -> Result<Vec<CoreInformation>, Error> {
+        self.permissions.halt()?;
+        self.request_halt_all()?;
+
+        self.enabled_cores()
+            .into_iter()
+            .map(|core_index| self.core(core_index)?.halt(timeout))
+            .collect()
+    }
+
+    /// Resumes all halted cores, as close to simultaneously as possible.
+    ///
+    /// The work needed before a core can be resumed, e.g. stepping over a breakpoint, is done
+    /// for every core first, and then the cores are resumed right after each other.
+    pub fn resume_all_cores(&mut self) -> Result<(), Error> {
+        let mut halted_cores = Vec::new();
+        for core_index in self.enabled_cores() {
+            let mut core = self.core(core_index)?;
+            if core.core_halted()? {
+                core.prepare_run()?;
+                halted_cores.push(core_index);
+            }
+        }
+
+        for core_index in halted_cores {
+            self.core(core_index)?.run_prepared()?;
+        }
+
+        Ok(())
+    }
+
+    /// Requests all cores to halt at the same time, if the architecture supports it.
+    fn request_halt_all(&mut self) -> Result<(), Error> {
+        let enabled_cores = self.enabled_cores();
+
+        match &mut self.interface {
+            ArchitectureInterface::Xtensa(interface) => {
+                let taps: Vec<_> = enabled_cores
+                    .iter()
+                    .filter_map(|&core_index| self.cores[core_index].xtensa_tap())
+                    .collect();
+                interface.halt_simultaneously(&taps)?;
+            }
+            ArchitectureInterface::Arm(interface) => {
+                let ctis: Vec<_> = enabled_cores
+                    .iter()
+                    .map(|&core_index| &self.cores[core_index])
+                    .filter_map(|c| Some((c.arm_memory_ap(), c.armv8a_cti_base()?)))
+                    .collect();
+
+                // Halting a core gates its halt channel again.
+                for &(ap, cti_base) in &ctis {
+                    let mut memory = interface.memory_interface(ap)?;
+                    armv8a::ungate_halt_channel(&mut *memory, cti_base)?;
+                }
+
+                if let Some(&(ap, cti_base)) = ctis.first() {
+                    let mut memory = interface.memory_interface(ap)?;
+                    armv8a::pulse_halt_channel(&mut *memory, cti_base)?;
+                }
+            }
+            ArchitectureInterface::Riscv(_) => {}
+        }
+
+        Ok(())
+    }
+
+    /// Selects what happens to the cores when the session is dropped.
+    ///
+    /// Either way, the state the debugger changed in order to operate the cores is restored.
+    pub fn set_detach_policy(&mut self, policy: DetachPolicy) {
+        self.detach_policy = policy;
+    }
+
+    /// Selects whether memory written through the cores of this session is read back and
+    /// compared to the written data. Disabled by default.
+    ///
+    /// This catches writes that are silently dropped, e.g. by misconfigured caches or external
+    /// RAM, at the cost of slower writes. Writes to registers that don't read back the written
+    /// value are reported as [`Error::WriteVerificationFailed`].
+    pub fn set_write_verification(&mut self, enabled: bool) {
+        self.verify_writes = enabled;
+    }
+
+    /// Halts running cores around the accesses which need a halted core, if `enabled`.
+    ///
+    /// Cores which can only access memory while halted, like Xtensa cores, are halted and
+    /// resumed for every single memory, register or breakpoint access, which disturbs the
+    /// firmware. This is disabled by default, and such accesses fail with [`Error::NotHalted`]
+    /// instead. Use [`Core::with_halted`] to halt a core once for a group of accesses. Cores are
+    /// never halted implicitly if the permissions don't allow halting, or if the session was
+    /// attached with [`Probe::attach_non_intrusive`](crate::Probe::attach_non_intrusive).
+    pub fn set_implicit_halts(&mut self, enabled: bool) {
+        self.implicit_halts = enabled;
+    }
+
+    /// Subscribes to state changes of the cores of this session.
+    ///
+    /// An event is sent whenever a core is observed to halt, resume or reset, either through
+    /// the [`Core`] API or by [`Session::poll_core_events`]. None of the supported probes can
+    /// notify us about a halting core, so changes which are not caused by us are only noticed
+    /// by polling. Use [`Session::spawn_core_event_poller`] to let a background thread do that.
+    pub fn subscribe_core_events(&mut self) -> Receiver<CoreEvent> {
+        self.core_events.subscribe()
+    }
+
+    /// Checks the status of all cores, sending events for the cores which changed their state.
+    pub fn poll_core_events(&mut self) -> Result<(), Error> {
+        for core_index in self.enabled_cores() {
+            self.core(core_index)?.status()?;
+        }
+        Ok(())
+    }
+
+    /// Registers an inspector, which classifies halts reported by [`Session::inspect_halt`].
+    pub fn add_halt_inspector(&mut self, inspector: impl HaltInspector + 'static) {
+        self.halt_inspectors.push(Arc::new(inspector));
+    }
+
+    /// Detects stack overflows of the core `core_index` with `guard`, which uses one of the
+    /// hardware watchpoint units of the core.
+    ///
+    /// The guard is armed on the core and registered as a halt inspector, so
+    /// [`Session::inspect_halt`] reports the halts it causes as a `stack overflow`.
+    pub fn enable_stack_guard(
+        &mut self,
+        core_index: usize,
+        guard: StackGuard,
+    ) -> Result<(), Error> {
+        guard.arm(&mut self.core(core_index)?)?;
+        self.add_halt_inspector(guard);
+        Ok(())
+    }
+
+    /// Registers a hook, which may veto or transform all memory accesses done through the cores
+    /// of the session, e.g. to redact secrets or to protect a bootloader on shared boards.
+    ///
+    /// Hooks see the accesses in the order they were added. Flash that is programmed or erased
+    /// by the flashing functions is passed to the hooks at its flash address before the flash
+    /// algorithm is called. Other accesses done by the target itself, e.g. by ROM routines
+    /// called by the program, are not intercepted.
+    pub fn add_memory_hook(&mut self, hook: impl MemoryHook + 'static) {
+        self.memory_hooks.add(Arc::new(hook));
+    }
+
+    /// Returns the memory hooks of the session.
+    pub(crate) fn memory_hooks(&self) -> &MemoryHooks {
+        &self.memory_hooks
+    }
+
+    /// Runs the registered inspectors on the core `core_index`, if it is halted, and returns
+    /// what they found out about the halt.
+    ///
+    /// Inspectors that fail are skipped.
+    pub fn inspect_halt(&mut self, core_index: usize) -> Result<Vec<HaltInspection>, Error> {
+        let inspectors = self.halt_inspectors.clone();
+        let mut core = self.core(core_index)?;
+
+        let CoreStatus::Halted(reason) = core.status()? else {
+            return Ok(Vec::new());
+        };
+
+        let mut inspections = Vec::new();
+        for inspector in inspectors {
+            match inspector.inspect(&mut core, reason) {
+                Ok(Some(inspection)) => inspections.push(inspection),
+                Ok(None) => {}
+                Err(error) => {
+                    tracing::warn!("Failed to inspect the halt with {inspector:?}: {error}")
+                }
+            }
+        }
+
+        Ok(inspections)
+    }
+
+    /// Collects a crash report of the halted core `core_index`, for example after a fault.
+    ///
+    /// The report contains the registers, the decoded exception, the findings of the registered
+    /// halt inspectors, and the memory around the program counter and stack pointer. If
+    /// `debug_info` is given, it also contains a backtrace.
+    pub fn triage(
+        &mut self,
+        core_index: usize,
+        debug_info: Option<&DebugInfo>,
+    ) -> Result<TriageReport, Error> {
+        let inspections = self.inspect_halt(core_index)?;
+        let mut core = self.core(core_index)?;
+
+        TriageReport::collect(&mut core, inspections, debug_info)
+    }
+
+    /// Runs a smoke test of the debug functionality of the core `core_index`, for example when
+    /// adding support for a new chip.
+    ///
+    /// The test halts the core, round-trips values through registers and memory, sets and clears
+    /// a breakpoint, steps an instruction and finally resets the core, which is left halted.
+    /// Memory is only checked if the target has a scratch region, whose contents are restored.
+    pub fn self_test(&mut self, core_index: usize) -> Result<SelfTestReport, Error> {
+        let loan = match self.borrow_scratch(core_index, MEMORY_CHECK_SIZE, 4) {
+            Ok(loan) => Some(loan),
+            Err(error) => {
+                tracing::debug!("Not checking memory accesses: {error}");
+                None
+            }
+        };
+
+        let report = SelfTestReport::run(
+            &mut self.core(core_index)?,
+            loan.as_ref().map(ScratchLoan::range),
+        );
+
+        if let Some(loan) = loan {
+            self.return_scratch(loan)?;
+        }
+
+        Ok(report)
+    }
+
+    /// Adds a watch, whose value is read by [`Session::poll_watches`].
+    pub fn add_watch(&mut self, watch: Watch) -> WatchId {
+        self.watches.add(watch)
+    }
+
+    /// Removes the watch `id`, returning `false` if it didn't exist.
+    pub fn remove_watch(&mut self, id: WatchId) -> bool {
+        self.watches.remove(id)
+    }
+
+    /// Subscribes to changes of the watched values.
+    ///
+    /// An event is sent whenever a watch is read and its value differs from the previous read,
+    /// including the first read of a watch.
+    pub fn subscribe_watches(&mut self) -> Receiver<WatchEvent> {
+        self.watches.subscribe()
+    }
+
+    /// Reads the watches whose interval elapsed, sending events for the values which changed.
+    ///
+    /// The memory is read while the cores keep running, so this only works on targets which
+    /// allow memory access during execution, like ARM cores, or which are halted implicitly, see
+    /// [`Session::set_implicit_halts`]. Watches that can't be read are skipped.
+    pub fn poll_watches(&mut self) {
+        let mut watches = std::mem::take(&mut self.watches);
+        watches.poll(Instant::now(), |watch, data| {
+            self.core(watch.core())?.read(watch.address(), data)
+        });
+        self.watches = watches;
+    }
+
+    /// Sets a hardware watchpoint which, instead of stopping the core, records the configured
+    /// registers and memory on every hit and lets the core continue.
+    ///
+    /// Hits are handled by [`Session::poll_trace_watchpoints`], and the records are collected
+    /// with [`Session::take_data_trace`]. This gives a lightweight data trace on targets
+    /// without ETM or TRAX, at the cost of a short halt per hit.
+    pub fn add_trace_watchpoint(
+        &mut self,
+        watchpoint: TraceWatchpoint,
+    ) -> Result<TraceWatchpointId, Error> {
+        let hw_watchpoint = watchpoint.watchpoint();
+        self.core(watchpoint.core())?.set_hw_watchpoint(
+            hw_watchpoint.address,
+            hw_watchpoint.length,
+            hw_watchpoint.kind,
+        )?;
+
+        Ok(self.trace_watchpoints.add(watchpoint))
+    }
+
+    /// Removes the trace watchpoint `id` and clears its hardware watchpoint, returning `false`
+    /// if it didn't exist.
+    pub fn remove_trace_watchpoint(&mut self, id: TraceWatchpointId) -> Result<bool, Error> {
+        let Some(watchpoint) = self.trace_watchpoints.remove(id) else {
+            return Ok(false);
+        };
+
+        self.core(watchpoint.core())?
+            .clear_hw_watchpoint(watchpoint.watchpoint().address)?;
+        Ok(true)
+    }
+
+    /// Handles the cores which halted at a trace watchpoint: their state is recorded, and they
+    /// are resumed. Returns the number of handled hits.
+    ///
+    /// Cores which halted for any other reason, including watchpoints of a debugger, are left
+    /// halted.
+    pub fn poll_trace_watchpoints(&mut self) -> Result<usize, Error> {
+        if self.trace_watchpoints.is_empty() {
+            return Ok(0);
+        }
+
+        let mut trace_watchpoints = std::mem::take(&mut self.trace_watchpoints);
+        let result = self.handle_trace_watchpoint_hits(&mut trace_watchpoints);
+        self.trace_watchpoints = trace_watchpoints;
+        result
+    }
+
+    fn handle_trace_watchpoint_hits(
+        &mut self,
+        trace_watchpoints: &mut TraceWatchpoints,
+    ) -> Result<usize, Error> {
+        let mut hits = 0;
+        for core_index in trace_watchpoints.cores() {
+            if self.disabled_cores.contains(&core_index) {
+                continue;
+            }
+
+            let mut core = self.core(core_index)?;
+            let CoreStatus::Halted(HaltReason::Watchpoint(unit)) = core.status()? else {
+                continue;
+            };
+
+            let hw_watchpoints = core.hw_watchpoints()?;
+            let Some((id, watchpoint)) =
+                trace_watchpoints.triggered(core_index, &hw_watchpoints, unit)
+            else {
+                continue;
+            };
+
+            let record = watchpoint.record_hit(id, &mut core)?;
+            trace_watchpoints.push(record);
+            hits += 1;
+        }
+
+        Ok(hits)
+    }
+
+    /// Removes and returns the records of the trace watchpoint hits, oldest first.
+    pub fn take_data_trace(&mut self) -> Vec<DataTraceRecord> {
+        self.trace_watchpoints.take_records()
+    }
+
+    /// The number of trace records which were dropped because they were not taken in time.
+    pub fn data_trace_dropped(&self) -> u64 {
+        self.trace_watchpoints.dropped()
+    }
+
+    /// Sets the number of trace records which are kept until they are taken with
+    /// [`Session::take_data_trace`]. When the buffer is full, the oldest records are dropped.
+    pub fn set_data_trace_capacity(&mut self, capacity: usize) {
+        self.trace_watchpoints.set_capacity(capacity);
+    }
+
+    /// Loans out `size` bytes of the scratch region of the target, starting at a multiple of
+    /// `align`, to be used through the core `core_index`.
+    ///
+    /// The current contents of the chunk are saved, and restored when the loan is returned with
+    /// [`Session::return_scratch`]. Chunks which are loaned out at the same time never overlap.
+    pub fn borrow_scratch(
+        &mut self,
+        core_index: usize,
+        size: u64,
+        align: u64,
+    ) -> Result<ScratchLoan, Error> {
+        let Some(region) = self.target.scratch_region.clone() else {
+            return Err(Error::Other(anyhow!(
+                "The target {} has no scratch region",
+                self.target.name
+            )));
+        };
+        let Some(range) = self.scratch.allocate(&region, size, align) else {
+            return Err(Error::Other(anyhow!(
+                "The scratch region has no {size} free bytes left"
+            )));
+        };
+
+        let mut saved = vec![0; size as usize];
+        if let Err(error) = self
+            .core(core_index)
+            .and_then(|mut core| core.read(range.start, &mut saved))
+        {
+            self.scratch.release(&range);
+            return Err(error);
+        }
+
+        Ok(ScratchLoan {
+            core: core_index,
+            range,
+            saved,
+        })
+    }
+
+    /// Returns a chunk of the scratch region loaned out with [`Session::borrow_scratch`],
+    /// restoring its original contents.
+    ///
+    /// The chunk is freed even if its contents can't be restored.
+    pub fn return_scratch(&mut self, loan: ScratchLoan) -> Result<(), Error> {
+        self.scratch.release(&loan.range);
+        self.core(loan.core)?.write(loan.range.start, &loan.saved)
+    }
+
+    /// Spawns a thread which calls [`Session::poll_watches`] every `interval`, reporting the
+    /// changed values through the returned [`WatchPoller`].
+    ///
+    /// Like [`Session::spawn_core_event_poller`], the thread only locks the session while it
+    /// polls. It is stopped when the poller is dropped.
+    pub fn spawn_watch_poller(session: Arc<Mutex<Session>>, interval: Duration) -> WatchPoller {
+        WatchPoller::spawn(session, interval)
+    }
+
+    /// Spawns a thread which polls the cores every `interval`, reporting their state changes
+    /// through the returned [`CoreEventPoller`].
+    ///
+    /// The thread only locks the session while it polls, so the session can be used
+    /// for other tasks in between. It is stopped when the poller is dropped.
+    pub fn spawn_core_event_poller(
+        session: Arc<Mutex<Session>>,
+        interval: Duration,
+    ) -> CoreEventPoller {
+        CoreEventPoller::spawn(session, interval)
     }
 
     /// Read available trace data from the specified data sink.
@@ -463,6 +1247,28 @@ impl Session {
         Ok(interface)
     }
 
+    /// Returns the telemetry of the debug operations done in this session.
+    ///
+    /// Returns `None` if the architecture interface of the session doesn't collect telemetry.
+    /// Currently, only Xtensa targets do.
+    pub fn telemetry(&self) -> Option<&Telemetry> {
+        match &self.interface {
+            ArchitectureInterface::Xtensa(interface) => Some(interface.telemetry()),
+            _ => None,
+        }
+    }
+
+    /// Returns the telemetry of the debug operations done in this session, to enable or disable
+    /// recording.
+    ///
+    /// Returns `None` if the architecture interface of the session doesn't collect telemetry.
+    pub fn telemetry_mut(&mut self) -> Option<&mut Telemetry> {
+        match &mut self.interface {
+            ArchitectureInterface::Xtensa(interface) => Some(interface.telemetry_mut()),
+            _ => None,
+        }
+    }
+
     #[tracing::instrument(skip_all)]
     fn reattach_arm_interface(
         interface: &mut Box<dyn ArmProbeInterface>,
@@ -502,7 +1308,7 @@ impl Session {
     pub fn has_sequence_erase_all(&self) -> bool {
         match &self.target.debug_sequence {
             DebugSequence::Arm(seq) => seq.debug_erase_sequence().is_some(),
-            // Currently, debug_erase_sequence is ARM (and ATSAM) specific
+            DebugSequence::Xtensa(seq) => seq.rom_flash_routines().is_some(),
             _ => false,
         }
     }
@@ -516,6 +1322,11 @@ impl Session {
     /// NotImplemented if no custom erase sequence exists
     /// Err(e) if the custom erase sequence failed
     pub fn sequence_erase_all(&mut self) -> Result<(), Error> {
+        if let DebugSequence::Xtensa(sequence) = &self.target.debug_sequence {
+            let sequence = sequence.clone();
+            return self.xtensa_erase_all(&*sequence);
+        }
+
         let ArchitectureInterface::Arm(ref mut interface) = self.interface else {
             return Err(Error::Probe(crate::DebugProbeError::NotImplemented(
                 "Debug Erase Sequence",
@@ -551,6 +1362,76 @@ impl Session {
         Ok(())
     }
 
+    /// Erases the flash of an Xtensa chip with the ROM routines of its debug sequence.
+    fn xtensa_erase_all(&mut self, sequence: &dyn XtensaDebugSequence) -> Result<(), Error> {
+        let Some(routines) = sequence.rom_flash_routines() else {
+            return Err(Error::Probe(crate::DebugProbeError::NotImplemented(
+                "Debug Erase Sequence",
+            )));
+        };
+
+        // The whole SPI flash is erased, including the ranges the memory hooks protect.
+        for region in &self.target.memory_map {
+            if let MemoryRegion::Nvm(region) = region {
+                self.memory_hooks
+                    .before_erase(0, region.range.clone(), 0xFF)?;
+            }
+        }
+
+        // The ROM routines must not be disturbed by the firmware, or by a watchdog.
+        self.core(0)?.reset_and_halt(Duration::from_millis(500))?;
+        self.get_xtensa_interface()?
+            .disable_watchdogs(sequence.watchdogs())?;
+
+        if sequence.flash_encryption_enabled(self.get_xtensa_interface()?)? {
+            return Err(XtensaError::FlashEncrypted.into());
+        }
+
+        let mut core = self.core(0)?;
+        let result = routines.erase_all(&mut core);
+        let reset = core.reset_and_halt(Duration::from_millis(500));
+        result?;
+        reset?;
+
+        tracing::info!("Device Erased Successfully");
+        Ok(())
+    }
+
+    /// Reads the eFuses of an Espressif chip through the first core, e.g. to get its MAC
+    /// address, revision and flash encryption state.
+    ///
+    /// The eFuses are only read. Programming them is irreversible, and is left to the tools of
+    /// the vendor. The first core must be halted, unless it is halted implicitly, see
+    /// [`Session::set_implicit_halts`].
+    pub fn read_efuses(&mut self) -> Result<Efuses, Error> {
+        let layout = match &self.target.debug_sequence {
+            DebugSequence::Xtensa(sequence) => sequence.efuse_layout(),
+            _ => None,
+        };
+        let Some(layout) = layout else {
+            return Err(Error::NotImplemented("read_efuses"));
+        };
+
+        Efuses::read(&mut self.core(0)?, layout)
+    }
+
+    /// Creates a [`FlashLoader`] like [`Target::flash_loader`], which additionally uses the
+    /// mappings that the cache MMU of the chip currently has set up.
+    ///
+    /// The `flash_mappings` of the target description take precedence. The MMU table is only
+    /// consulted for addresses they don't cover, e.g. when the bootloader or the firmware mapped
+    /// the flash at an offset the target description doesn't know about. The first core must be
+    /// halted to read the MMU table.
+    pub fn flash_loader_with_mmu_mappings(&mut self) -> Result<FlashLoader, Error> {
+        let mut mappings = self.target.flash_mappings.clone();
+        if let DebugSequence::Xtensa(sequence) = &self.target.debug_sequence {
+            let sequence = sequence.clone();
+            mappings.extend(sequence.flash_mappings(self.get_xtensa_interface()?)?);
+        }
+
+        Ok(self.target.flash_loader().with_flash_mappings(mappings))
+    }
+
     /// Reads all the available ARM CoresightComponents of the currently attached target.
     ///
     /// This will recursively parse the Romtable of the attached target
@@ -641,7 +1522,7 @@ impl Session {
 
     /// Clears all hardware breakpoints on all cores
     pub fn clear_all_hw_breakpoints(&mut self) -> Result<(), Error> {
-        { 0..self.cores.len() }.try_for_each(|n| {
+        self.enabled_cores().into_iter().try_for_each(|n| {
             self.core(n)
                 .and_then(|mut core| core.clear_all_hw_breakpoints())
         })
@@ -655,17 +1536,41 @@ static_assertions::assert_impl_all!(Session: Send);
 impl Drop for Session {
     #[tracing::instrument(name = "session_drop", skip(self))]
     fn drop(&mut self) {
-        if let Err(err) = { 0..self.cores.len() }.try_for_each(|i| {
-            self.core(i)
-                .and_then(|mut core| core.clear_all_hw_breakpoints())
-        }) {
-            tracing::warn!("Could not clear all hardware breakpoints: {:?}", err);
+        // Without permission to halt, no breakpoints or watchpoints were set.
+        if self.permissions.halt().is_ok() {
+            if let Err(err) = self.enabled_cores().into_iter().try_for_each(|i| {
+                self.core(i)
+                    .and_then(|mut core| core.clear_all_hw_breakpoints())
+            }) {
+                tracing::warn!("Could not clear all hardware breakpoints: {:?}", err);
+            }
+
+            if let Err(err) = self.enabled_cores().into_iter().try_for_each(|i| {
+                self.core(i)
+                    .and_then(|mut core| core.clear_all_hw_watchpoints())
+            }) {
+                tracing::warn!("Could not clear all hardware watchpoints: {:?}", err);
+            }
         }
 
-        // Call any necessary deconfiguration/shutdown hooks.
-        if let Err(err) = { 0..self.cores.len() }
-            .try_for_each(|i| self.core(i).and_then(|mut core| core.debug_core_stop()))
+        if let Err(err) = self
+            .enabled_cores()
+            .into_iter()
+            .try_for_each(|i| self.core(i).and_then(|mut core| core.on_session_stop()))
         {
+            tracing::warn!("Failed to restore the core state during shutdown: {err:?}");
+        }
+
+        // Call any necessary deconfiguration/shutdown hooks. These let the cores run, so they
+        // are skipped for halted cores that should stay halted.
+        let detach_policy = self.detach_policy;
+        if let Err(err) = self.enabled_cores().into_iter().try_for_each(|i| {
+            let mut core = self.core(i)?;
+            if detach_policy == DetachPolicy::KeepHalted && core.core_halted()? {
+                return Ok(());
+            }
+            core.debug_core_stop()
+        }) {
             tracing::warn!("Failed to deconfigure device during shutdown: {err:?}");
         }
     }
@@ -745,6 +1650,13 @@ fn get_target_from_selector(
                 tracing::debug!("No RISC-V interface was present. Skipping Riscv autodetect.");
             }
 
+            if found_chip.is_none() {
+                let found_jtag_chip;
+                (probe, found_jtag_chip) = read_jtag_chip_info(probe);
+
+                found_chip = found_jtag_chip.map(ChipInfo::from);
+            }
+
             // Now we can deassert reset in case we asserted it before. This is always okay.
             probe.target_reset_deassert()?;
 
@@ -759,6 +1671,64 @@ fn get_target_from_selector(
     Ok((probe, target))
 }
 
+/// Reads the IDCODEs of the TAPs on the JTAG chain, and the detect registers of all chips which
+/// match them, for auto-detection of chips which have no ARM ROM table.
+fn read_jtag_chip_info(mut probe: Probe) -> (Probe, Option<JtagChipInfo>) {
+    let idcodes = match probe.scan_jtag_chain() {
+        Ok(chain) if !chain.is_empty() => chain.iter().map(|tap| tap.idcode).collect::<Vec<_>>(),
+        Ok(_) => return (probe, None),
+        Err(err) => {
+            tracing::debug!("Unable to scan the JTAG chain for autodetection: {}", err);
+            return (probe, None);
+        }
+    };
+    tracing::debug!("ID Codes on the JTAG chain: {:x?}", idcodes);
+
+    let mut registers = Vec::new();
+    for (address, architecture) in crate::config::get_detect_registers_by_idcodes(&idcodes) {
+        let value;
+        (probe, value) = read_detect_register(probe, address, architecture);
+
+        match value {
+            Ok(value) => registers.push((address, value)),
+            Err(err) => {
+                tracing::debug!("Unable to read detect register {:#010x}: {}", address, err)
+            }
+        }
+    }
+
+    (probe, Some(JtagChipInfo { idcodes, registers }))
+}
+
+/// Reads the chip detect register at `address` with the interface of `architecture`.
+fn read_detect_register(
+    probe: Probe,
+    address: u64,
+    architecture: Architecture,
+) -> (Probe, Result<u32, Error>) {
+    match architecture {
+        Architecture::Riscv => match probe.try_into_riscv_interface() {
+            Ok(mut interface) => {
+                let value = interface.read_word_32(address);
+                (interface.close(), value)
+            }
+            Err((probe, err)) => (probe, Err(err.into())),
+        },
+        Architecture::Xtensa => match probe.try_into_xtensa_interface() {
+            Ok(mut interface) => {
+                let value = interface.read_word_32(address);
+                (interface.close(), value)
+            }
+            Err((probe, err)) => (probe, Err(err.into())),
+        },
+        // ARM chips are detected from their ROM table.
+        Architecture::Arm => (
+            probe,
+            Err(Error::NotImplemented("detect registers of ARM chips")),
+        ),
+    }
+}
+
 /// The `Permissions` struct represents what a [Session] is allowed to do with a target.
 /// Some operations can be irreversible, so need to be explicitly allowed by the user.
 ///
@@ -769,11 +1739,19 @@ fn get_target_from_selector(
 ///
 /// let permissions = Permissions::new().allow_erase_all();
 /// ```
+///
+/// Tools which must not disturb a running target can attach with [`Permissions::read_only`].
 #[non_exhaustive]
 #[derive(Debug, Clone, Default)]
 pub struct Permissions {
     /// When set to true, all memory of the chip may be erased or reset to factory default
     erase_all: bool,
+    /// When set to true, the cores may not be halted
+    deny_halt: bool,
+    /// When set to true, the memory of the chip may not be written
+    deny_memory_writes: bool,
+    /// When set to true, the chip may not be reset
+    deny_reset: bool,
 }
 
 impl Permissions {
@@ -782,6 +1760,18 @@ impl Permissions {
         Self::default()
     }
 
+    /// Constructs a permissions object which only allows observing the target.
+    ///
+    /// The cores are not halted, not even while attaching, and the memory is not written. The
+    /// chip is not reset and its watchdogs are left running, so the firmware keeps running
+    /// undisturbed.
+    pub fn read_only() -> Self {
+        Self::default()
+            .deny_halt()
+            .deny_memory_writes()
+            .deny_reset()
+    }
+
     /// Allow the session to erase all memory of the chip or reset it to factory default.
     ///
     /// # Warning
@@ -795,6 +1785,36 @@ impl Permissions {
         }
     }
 
+    /// Forbid the session to halt the cores, or to set breakpoints and watchpoints that halt
+    /// them.
+    ///
+    /// Cores that are already halted can still be stepped and resumed.
+    #[must_use]
+    pub fn deny_halt(self) -> Self {
+        Self {
+            deny_halt: true,
+            ..self
+        }
+    }
+
+    /// Forbid the session to write the memory of the chip, which also prevents flashing it.
+    #[must_use]
+    pub fn deny_memory_writes(self) -> Self {
+        Self {
+            deny_memory_writes: true,
+            ..self
+        }
+    }
+
+    /// Forbid the session to reset the chip, which also prevents attaching under reset.
+    #[must_use]
+    pub fn deny_reset(self) -> Self {
+        Self {
+            deny_reset: true,
+            ..self
+        }
+    }
+
     pub(crate) fn erase_all(&self) -> Result<(), MissingPermissions> {
         if self.erase_all {
             Ok(())
@@ -802,8 +1822,38 @@ impl Permissions {
             Err(MissingPermissions("erase_all".into()))
         }
     }
+
+    pub(crate) fn halt(&self) -> Result<(), MissingPermissions> {
+        if self.deny_halt {
+            Err(MissingPermissions("halt".into()))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn write_memory(&self) -> Result<(), MissingPermissions> {
+        if self.deny_memory_writes {
+            Err(MissingPermissions("write_memory".into()))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn reset(&self) -> Result<(), MissingPermissions> {
+        if self.deny_reset {
+            Err(MissingPermissions("reset".into()))
+        } else {
+            Ok(())
+        }
+    }
 }
 
 #[derive(Debug, Clone, thiserror::Error)]
 #[error("An operation could not be performed because it lacked the permission to do so: {0}")]
 pub struct MissingPermissions(pub String);
+
+impl From<MissingPermissions> for Error {
+    fn from(MissingPermissions(permission): MissingPermissions) -> Self {
+        Error::MissingPermissions(permission)
+    }
+}
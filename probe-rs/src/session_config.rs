@@ -0,0 +1,247 @@
+//! Debug options of a project, which can be stored in a file next to the firmware.
+
+use std::{ops::Range, path::Path};
+
+use crate::config::TargetSelector;
+use crate::{
+    Architecture, AttachMethod, AttachOptions, Error, Permissions, Probe, Session, WireProtocol,
+};
+
+/// The debug options of a project, e.g. loaded from a `probe-rs.yaml` file with
+/// [`SessionConfig::load`].
+///
+/// All options are optional, so a file only needs to contain what differs from the defaults:
+///
+/// ```yaml
+/// chip: esp32c6
+/// speed: 10000
+/// connect_under_reset: true
+/// watchdogs: keep
+/// rtt:
+///   scan_ranges:
+///     - { start: 0x40800000, end: 0x40810000 }
+/// cores:
+///   - { index: 1, enabled: false }
+/// ```
+///
+/// A session with these options is opened with [`SessionConfig::attach`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SessionConfig {
+    /// The name of the chip. The chip is detected automatically if not given.
+    pub chip: Option<String>,
+    /// The protocol speed in kHz. The probe's default speed is used if not given.
+    pub speed: Option<u32>,
+    /// The wire protocol. The probe's default protocol is used if not given.
+    pub protocol: Option<WireProtocol>,
+    /// Whether the chip is held in reset while attaching, see [`Probe::attach_under_reset`].
+    pub connect_under_reset: bool,
+    /// What happens to the watchdogs of the chip while its cores are halted.
+    pub watchdogs: WatchdogPolicy,
+    /// The interrupt level of debug exceptions of Xtensa cores, between 2 and 7.
+    pub xtensa_debug_level: Option<u8>,
+    /// Where to look for the RTT control block.
+    pub rtt: RttConfig,
+    /// Options of individual cores. Cores which are not listed use the defaults.
+    pub cores: Vec<CoreConfig>,
+}
+
+/// What happens to the watchdogs of the chip while its cores are halted, see
+/// [`AttachOptions::keep_watchdogs`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatchdogPolicy {
+    /// The watchdogs are stopped while the cores are halted.
+    #[default]
+    Stop,
+    /// The watchdogs keep running, and may reset the chip while it is halted.
+    Keep,
+}
+
+/// Where to look for the RTT control block.
+#[non_exhaustive]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RttConfig {
+    /// The exact address of the control block.
+    pub control_block_address: Option<u32>,
+    /// The memory ranges to scan for the control block. All RAM is scanned if empty.
+    pub scan_ranges: Vec<Range<u64>>,
+}
+
+impl RttConfig {
+    /// Returns the region to pass to [`Rtt::attach_region`](crate::rtt::Rtt::attach_region).
+    #[cfg(feature = "rtt")]
+    pub fn scan_region(&self) -> crate::rtt::ScanRegion {
+        use crate::rtt::ScanRegion;
+
+        match self.control_block_address {
+            Some(address) => ScanRegion::Exact(address),
+            None if self.scan_ranges.is_empty() => ScanRegion::Ram,
+            None => ScanRegion::Ranges(self.scan_ranges.clone()),
+        }
+    }
+}
+
+/// The options of a single core.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CoreConfig {
+    /// The index of the core, as listed by [`Session::list_cores`].
+    pub index: usize,
+    /// Whether the core is used by the session, see [`Session::set_core_enabled`].
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// An error while loading a [`SessionConfig`].
+#[derive(Debug, thiserror::Error)]
+pub enum SessionConfigError {
+    /// The file could not be read.
+    #[error("Failed to read the session configuration")]
+    Io(#[from] std::io::Error),
+    /// The file is not a valid configuration.
+    #[error("Failed to parse the session configuration")]
+    Parse(#[from] serde_yaml::Error),
+    /// The Xtensa debug level is out of range.
+    #[error("Invalid Xtensa debug level {0}, the debug level must be between 2 and 7")]
+    InvalidXtensaDebugLevel(u8),
+}
+
+impl SessionConfig {
+    /// Loads the configuration from a YAML or JSON file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SessionConfigError> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::from_yaml(&contents)
+    }
+
+    /// Parses the configuration from YAML, or JSON, which is a subset of YAML.
+    pub fn from_yaml(yaml: &str) -> Result<Self, SessionConfigError> {
+        let config: Self = serde_yaml::from_str(yaml)?;
+
+        if let Some(level) = config.xtensa_debug_level {
+            if !(2..=7).contains(&level) {
+                return Err(SessionConfigError::InvalidXtensaDebugLevel(level));
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// The attach options of sessions opened with this configuration.
+    pub fn attach_options(&self) -> AttachOptions {
+        match self.watchdogs {
+            WatchdogPolicy::Stop => AttachOptions::new(),
+            WatchdogPolicy::Keep => AttachOptions::new().keep_watchdogs(),
+        }
+    }
+
+    /// Configures `probe` and attaches to the chip, then applies the options of the session
+    /// with [`SessionConfig::apply`].
+    pub fn attach(&self, mut probe: Probe) -> Result<Session, Error> {
+        if let Some(protocol) = self.protocol {
+            probe.select_protocol(protocol)?;
+        }
+        if let Some(speed) = self.speed {
+            let actual = probe.set_speed(speed)?;
+            if actual != speed {
+                tracing::info!("Protocol speed set to {actual} kHz instead of {speed} kHz");
+            }
+        }
+
+        let target = match &self.chip {
+            Some(chip) => TargetSelector::from(chip.as_str()),
+            None => TargetSelector::Auto,
+        };
+
+        let method = if self.connect_under_reset {
+            AttachMethod::UnderReset
+        } else {
+            AttachMethod::Normal
+        };
+        let mut session =
+            probe.attach_with_options(target, method, Permissions::new(), self.attach_options())?;
+        self.apply(&mut session)?;
+
+        Ok(session)
+    }
+
+    /// Applies the options which can be changed on an open `session`: the enabled cores and
+    /// the Xtensa debug level.
+    pub fn apply(&self, session: &mut Session) -> Result<(), Error> {
+        for core in &self.cores {
+            session.set_core_enabled(core.index, core.enabled)?;
+        }
+
+        if let Some(level) = self.xtensa_debug_level {
+            if session.architecture() == Architecture::Xtensa {
+                session.get_xtensa_interface()?.set_debug_level(level)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_partial_config() {
+        let config = SessionConfig::from_yaml(
+            "
+            chip: esp32c6
+            speed: 10000
+            connect_under_reset: true
+            watchdogs: keep
+            rtt:
+              scan_ranges:
+                - { start: 0x40800000, end: 0x40810000 }
+            cores:
+              - { index: 1, enabled: false }
+              - { index: 2 }
+            ",
+        )
+        .unwrap();
+
+        assert_eq!(config.chip.as_deref(), Some("esp32c6"));
+        assert_eq!(config.speed, Some(10000));
+        assert_eq!(config.protocol, None);
+        assert!(config.connect_under_reset);
+        assert_eq!(config.watchdogs, WatchdogPolicy::Keep);
+        assert_eq!(config.rtt.scan_ranges.len(), 1);
+        assert_eq!(config.rtt.scan_ranges[0], 0x4080_0000..0x4081_0000);
+        assert_eq!(
+            config.cores,
+            [
+                CoreConfig {
+                    index: 1,
+                    enabled: false
+                },
+                CoreConfig {
+                    index: 2,
+                    enabled: true
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn reject_invalid_config() {
+        assert!(matches!(
+            SessionConfig::from_yaml("xtensa_debug_level: 8"),
+            Err(SessionConfigError::InvalidXtensaDebugLevel(8))
+        ));
+        assert!(matches!(
+            SessionConfig::from_yaml("speeed: 1000"),
+            Err(SessionConfigError::Parse(_))
+        ));
+    }
+}
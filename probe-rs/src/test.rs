@@ -1,6 +1,15 @@
 //! Helpers for testing the crate
 
-use crate::MemoryInterface;
+use std::time::Duration;
+
+use crate::{
+    core::{
+        registers::{CoreRegister, CoreRegisters, RegisterId, RegisterValue},
+        CoreInterface,
+    },
+    Architecture, CoreInformation, CoreStatus, CoreType, HaltReason, InstructionSet,
+    MemoryInterface,
+};
 
 #[derive(Debug)]
 pub(crate) struct MockMemory {
@@ -185,6 +194,237 @@ impl MemoryInterface for MockMemory {
     }
 }
 
+/// A core which, like an Xtensa core, can only access its [`MockMemory`] while halted.
+#[derive(Debug)]
+pub(crate) struct MockCore {
+    memory: MockMemory,
+    halted: bool,
+}
+
+impl MockCore {
+    /// Creates a running core.
+    pub(crate) fn new(memory: MockMemory) -> Self {
+        MockCore {
+            memory,
+            halted: false,
+        }
+    }
+
+    fn check_halted(&self) -> Result<(), crate::Error> {
+        if self.halted {
+            Ok(())
+        } else {
+            Err(crate::Error::Other(anyhow::anyhow!(
+                "Memory accessed while the core is running"
+            )))
+        }
+    }
+}
+
+impl MemoryInterface for MockCore {
+    fn supports_native_64bit_access(&mut self) -> bool {
+        false
+    }
+
+    fn read_word_64(&mut self, address: u64) -> anyhow::Result<u64, crate::Error> {
+        self.check_halted()?;
+        self.memory.read_word_64(address)
+    }
+
+    fn read_word_32(&mut self, address: u64) -> anyhow::Result<u32, crate::Error> {
+        self.check_halted()?;
+        self.memory.read_word_32(address)
+    }
+
+    fn read_word_8(&mut self, address: u64) -> anyhow::Result<u8, crate::Error> {
+        self.check_halted()?;
+        self.memory.read_word_8(address)
+    }
+
+    fn read_64(&mut self, address: u64, data: &mut [u64]) -> anyhow::Result<(), crate::Error> {
+        self.check_halted()?;
+        self.memory.read_64(address, data)
+    }
+
+    fn read_32(&mut self, address: u64, data: &mut [u32]) -> anyhow::Result<(), crate::Error> {
+        self.check_halted()?;
+        self.memory.read_32(address, data)
+    }
+
+    fn read_8(&mut self, address: u64, data: &mut [u8]) -> anyhow::Result<(), crate::Error> {
+        self.check_halted()?;
+        self.memory.read_8(address, data)
+    }
+
+    fn supports_8bit_transfers(&self) -> Result<bool, crate::Error> {
+        Ok(true)
+    }
+
+    fn write_word_64(&mut self, _address: u64, _data: u64) -> anyhow::Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn write_word_32(&mut self, _address: u64, _data: u32) -> anyhow::Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn write_word_8(&mut self, _address: u64, _data: u8) -> anyhow::Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn write_64(&mut self, _address: u64, _data: &[u64]) -> anyhow::Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn write_32(&mut self, _address: u64, _data: &[u32]) -> anyhow::Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn write_8(&mut self, _address: u64, _data: &[u8]) -> anyhow::Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn flush(&mut self) -> anyhow::Result<(), crate::Error> {
+        Ok(())
+    }
+}
+
+impl CoreInterface for MockCore {
+    fn id(&self) -> usize {
+        0
+    }
+
+    fn wait_for_core_halted(&mut self, _timeout: Duration) -> Result<(), crate::Error> {
+        Ok(())
+    }
+
+    fn core_halted(&mut self) -> Result<bool, crate::Error> {
+        Ok(self.halted)
+    }
+
+    fn status(&mut self) -> Result<CoreStatus, crate::Error> {
+        Ok(if self.halted {
+            CoreStatus::Halted(HaltReason::Request)
+        } else {
+            CoreStatus::Running
+        })
+    }
+
+    fn halt(&mut self, _timeout: Duration) -> Result<CoreInformation, crate::Error> {
+        self.halted = true;
+        Ok(CoreInformation { pc: 0 })
+    }
+
+    fn run(&mut self) -> Result<(), crate::Error> {
+        self.halted = false;
+        Ok(())
+    }
+
+    fn reset(&mut self) -> Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn reset_and_halt(&mut self, _timeout: Duration) -> Result<CoreInformation, crate::Error> {
+        todo!()
+    }
+
+    fn step(&mut self) -> Result<CoreInformation, crate::Error> {
+        todo!()
+    }
+
+    fn read_core_reg(&mut self, _address: RegisterId) -> Result<RegisterValue, crate::Error> {
+        todo!()
+    }
+
+    fn write_core_reg(
+        &mut self,
+        _address: RegisterId,
+        _value: RegisterValue,
+    ) -> Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn available_breakpoint_units(&mut self) -> Result<u32, crate::Error> {
+        Ok(0)
+    }
+
+    fn hw_breakpoints(&mut self) -> Result<Vec<Option<u64>>, crate::Error> {
+        Ok(vec![])
+    }
+
+    fn enable_breakpoints(&mut self, _state: bool) -> Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn set_hw_breakpoint(&mut self, _unit_index: usize, _addr: u64) -> Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn clear_hw_breakpoint(&mut self, _unit_index: usize) -> Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn registers(&self) -> &'static CoreRegisters {
+        todo!()
+    }
+
+    fn program_counter(&self) -> &'static CoreRegister {
+        todo!()
+    }
+
+    fn frame_pointer(&self) -> &'static CoreRegister {
+        todo!()
+    }
+
+    fn stack_pointer(&self) -> &'static CoreRegister {
+        todo!()
+    }
+
+    fn return_address(&self) -> &'static CoreRegister {
+        todo!()
+    }
+
+    fn hw_breakpoints_enabled(&self) -> bool {
+        false
+    }
+
+    fn architecture(&self) -> Architecture {
+        Architecture::Xtensa
+    }
+
+    fn core_type(&self) -> CoreType {
+        CoreType::Xtensa
+    }
+
+    fn instruction_set(&mut self) -> Result<InstructionSet, crate::Error> {
+        Ok(InstructionSet::Xtensa)
+    }
+
+    fn fpu_support(&mut self) -> Result<bool, crate::Error> {
+        Ok(false)
+    }
+
+    fn floating_point_register_count(&mut self) -> Result<usize, crate::Error> {
+        Ok(0)
+    }
+
+    fn requires_halted_memory_access(&self) -> bool {
+        true
+    }
+
+    fn reset_catch_set(&mut self) -> Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn reset_catch_clear(&mut self) -> Result<(), crate::Error> {
+        todo!()
+    }
+
+    fn debug_core_stop(&mut self) -> Result<(), crate::Error> {
+        Ok(())
+    }
+}
+
 #[test]
 fn mock_memory_read() {
     let mut mock_memory = MockMemory::new();
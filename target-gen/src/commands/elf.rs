@@ -101,11 +101,15 @@ pub fn cmd_elf(
                         range: 0x1_0000..0x2_0000,
                         cores: vec!["main".to_owned()],
                         name: None,
+                        access_width: Default::default(),
                     }),
                 ],
                 flash_algorithms: vec![algorithm_name],
                 rtt_scan_ranges: None,
                 scan_chain: None,
+                scratch_region: None,
+                flash_mappings: vec![],
+                detect_register: None,
                 default_binary_format: None,
             }],
             flash_algorithms: vec![algorithm],
@@ -164,6 +164,9 @@ where
             flash_algorithms: flash_algorithm_names,
             rtt_scan_ranges: None,
             scan_chain: None, // TODO, parse from sdf
+            scratch_region: None,
+            flash_mappings: vec![],
+            detect_register: None,
             default_binary_format: None,
         });
     }
@@ -188,7 +191,7 @@ fn create_core(processor: &Processor) -> Result<ProbeCore> {
                 cti_base: None,
             }),
             Architecture::Riscv => CoreAccessOptions::Riscv(RiscvCoreAccessOptions {}),
-            Architecture::Xtensa => CoreAccessOptions::Xtensa(XtensaCoreAccessOptions {}),
+            Architecture::Xtensa => CoreAccessOptions::Xtensa(XtensaCoreAccessOptions::default()),
         },
     })
 }
@@ -530,6 +533,7 @@ pub(crate) fn get_mem_map(device: &Device, cores: &[probe_rs_target::Core]) -> V
                     range: region.memory_start..region.memory_end,
                     is_boot_memory: region.is_boot_memory,
                     cores,
+                    access_width: Default::default(),
                     }));
                 }
             },
@@ -559,6 +563,7 @@ pub(crate) fn get_mem_map(device: &Device, cores: &[probe_rs_target::Core]) -> V
                     name: Some(region.name.clone()),
                     range: region.memory_start..region.memory_end,
                     cores,
+                    access_width: Default::default(),
                     }));
                 }
             },